@@ -1,4 +1,4 @@
-use sc_cli::{RunCmd, Subcommand};
+use sc_cli::RunCmd;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -9,3 +9,14 @@ pub struct Cli {
 	#[structopt(flatten)]
 	pub run: RunCmd,
 }
+
+#[derive(Debug, StructOpt)]
+pub enum Subcommand {
+	/// A set of base subcommands handled by `sc_cli`.
+	#[structopt(flatten)]
+	Base(sc_cli::Subcommand),
+
+	/// Key management commands.
+	#[structopt(name = "key", about = "Key management commands.")]
+	Key(sc_cli::KeySubcommand),
+}
@@ -16,7 +16,7 @@
 // limitations under the License.
 
 use crate::chain_spec;
-use crate::cli::Cli;
+use crate::cli::{Cli, Subcommand};
 use crate::service;
 use sc_cli::SubstrateCli;
 
@@ -65,10 +65,11 @@ pub fn run() -> sc_cli::Result<()> {
 	let cli = Cli::from_args();
 
 	match &cli.subcommand {
-		Some(subcommand) => {
+		Some(Subcommand::Base(subcommand)) => {
 			let runner = cli.create_runner(subcommand)?;
 			runner.run_subcommand(subcommand, |config| Ok(new_full_start!(config).0))
 		}
+		Some(Subcommand::Key(cmd)) => cmd.run(&cli),
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node(
@@ -7,6 +7,7 @@ use sc_consensus::LongestChain;
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_service::{error::{Error as ServiceError}, AbstractService, Configuration, ServiceBuilder};
 use sp_inherents::InherentDataProviders;
+use sp_runtime::Percent;
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
@@ -74,8 +75,8 @@ macro_rules! new_full_start {
 
 				let import_queue = sc_consensus_aura::import_queue::<_, _, _, AuraPair, _>(
 					sc_consensus_aura::slot_duration(&*client)?,
-					aura_block_import,
-					Some(Box::new(grandpa_block_import.clone())),
+					aura_block_import.clone(),
+					Some(Box::new(grandpa_block_import)),
 					None,
 					client,
 					inherent_data_providers.clone(),
@@ -83,7 +84,8 @@ macro_rules! new_full_start {
 					registry,
 				)?;
 
-				import_setup = Some((grandpa_block_import, grandpa_link));
+				// authored blocks go through the same fork-choice rule as imported ones.
+				import_setup = Some((aura_block_import, grandpa_link));
 
 				Ok(import_queue)
 			})?;
@@ -136,6 +138,8 @@ pub fn new_full(config: Configuration) -> Result<impl AbstractService, ServiceEr
 			service.network(),
 			inherent_data_providers.clone(),
 			force_authoring,
+			// author as backup once half of a slot passed without a block, if the runtime allows.
+			Some(Percent::from_percent(50)),
 			service.keystore(),
 			can_author_with,
 		)?;
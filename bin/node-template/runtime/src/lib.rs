@@ -191,8 +191,26 @@ impl system::Trait for Runtime {
 	type AccountData = balances::AccountData<Balance>;
 }
 
+parameter_types! {
+	pub const AllowSecondaryAuthors: bool = false;
+}
+
 impl aura::Trait for Runtime {
 	type AuthorityId = AuraId;
+
+	type KeyOwnerProofSystem = ();
+
+	type KeyOwnerProof =
+		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, AuraId)>>::Proof;
+
+	type KeyOwnerIdentification = <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(
+		KeyTypeId,
+		AuraId,
+	)>>::IdentificationTuple;
+
+	type HandleEquivocation = ();
+
+	type AllowSecondaryAuthors = AllowSecondaryAuthors;
 }
 
 impl grandpa::Trait for Runtime {
@@ -268,8 +286,8 @@ construct_runtime!(
 		System: system::{Module, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: randomness_collective_flip::{Module, Call, Storage},
 		Timestamp: timestamp::{Module, Call, Storage, Inherent},
-		Aura: aura::{Module, Config<T>, Inherent(Timestamp)},
-		Grandpa: grandpa::{Module, Call, Storage, Config, Event},
+		Aura: aura::{Module, Call, Config<T>, Inherent(Timestamp), ValidateUnsigned},
+		Grandpa: grandpa::{Module, Call, Storage, Config, Event, ValidateUnsigned},
 		Balances: balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: transaction_payment::{Module, Storage},
 		Sudo: sudo::{Module, Call, Config<T>, Storage, Event<T>},
@@ -374,6 +392,30 @@ impl_runtime_apis! {
 		fn authorities() -> Vec<AuraId> {
 			Aura::authorities()
 		}
+
+		fn generate_key_ownership_proof(
+			_slot_number: u64,
+			_authority_id: AuraId,
+		) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+			// NOTE: this is the only implementation possible since we've
+			// defined our key owner proof type as a bottom type (i.e. a type
+			// with no values).
+			None
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_aura::EquivocationProof<
+				<Block as BlockT>::Header,
+				AuraId,
+			>,
+			_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			None
+		}
+
+		fn secondary_authors_allowed() -> bool {
+			Aura::secondary_authors_allowed()
+		}
 	}
 
 	impl sp_session::SessionKeys<Block> for Runtime {
@@ -393,7 +435,7 @@ impl_runtime_apis! {
 			Grandpa::grandpa_authorities()
 		}
 
-		fn submit_report_equivocation_extrinsic(
+		fn submit_report_equivocation_unsigned_extrinsic(
 			_equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
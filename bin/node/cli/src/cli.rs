@@ -28,6 +28,11 @@ pub struct Cli {
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Never switch to a fork that would revert more than the given number of
+	/// blocks of the current best chain.
+	#[structopt(long = "max-reorg-depth", value_name = "BLOCKS")]
+	pub max_reorg_depth: Option<u32>,
 }
 
 /// Possible subcommands of the main binary.
@@ -44,7 +49,25 @@ pub enum Subcommand {
 	)]
 	Inspect(node_inspect::cli::InspectCmd),
 
+	/// Key management commands.
+	#[structopt(name = "key", about = "Key management commands.")]
+	Key(sc_cli::KeySubcommand),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// The differential subcommand comparing the executors on the chain data.
+	#[structopt(
+		name = "differential",
+		about = "Replay blocks or runtime calls with all executors and report divergences."
+	)]
+	Differential(substrate_executor_differential::DifferentialCmd),
+
+	/// The upgrade dry-run subcommand checking a runtime upgrade against the chain state.
+	#[structopt(
+		name = "upgrade-dry-run",
+		about = "Apply a runtime upgrade to the state of a block and report its effects."
+	)]
+	UpgradeDryRun(frame_upgrade_dry_run_cli::UpgradeDryRunCmd),
 }
@@ -39,9 +39,10 @@ use sc_consensus::LongestChain;
 /// Use this macro if you don't actually need the full service, but just the builder in order to
 /// be able to perform chain operations.
 macro_rules! new_full_start {
-	($config:expr) => {{
+	($config:expr, $max_reorg_depth:expr) => {{
 		use std::sync::Arc;
 
+		let max_reorg_depth: Option<u32> = $max_reorg_depth;
 		let mut import_setup = None;
 		let mut rpc_setup = None;
 		let inherent_data_providers = sp_inherents::InherentDataProviders::new();
@@ -87,6 +88,17 @@ macro_rules! new_full_start {
 					client.clone(),
 				)?;
 
+				let block_import = match max_reorg_depth {
+					Some(max_depth) => block_import.with_fork_choice(Arc::new(
+						sc_consensus::fork_choice::DepthLimited::new(
+							client.clone(),
+							max_depth,
+							sc_consensus::fork_choice::HeaviestChainRule::new(),
+						),
+					)),
+					None => block_import,
+				};
+
 				let import_queue = sc_consensus_babe::import_queue(
 					babe_link.clone(),
 					block_import.clone(),
@@ -107,6 +119,14 @@ macro_rules! new_full_start {
 
 				let shared_authority_set = grandpa_link.shared_authority_set().clone();
 				let shared_voter_state = grandpa::SharedVoterState::empty();
+				let justification_stream = grandpa_link.justification_stream();
+				let subscriptions = jsonrpc_pubsub::manager::SubscriptionManager::new(
+					Arc::new(builder.spawn_handle()),
+				);
+				let finality_provider = Arc::new(grandpa::FinalityProofProvider::new(
+					builder.backend().clone(),
+					builder.client().clone() as Arc<dyn grandpa::StorageAndProofProvider<_, _>>,
+				));
 
 				rpc_setup = Some((shared_voter_state.clone()));
 
@@ -136,6 +156,9 @@ macro_rules! new_full_start {
 						grandpa: node_rpc::GrandpaDeps {
 							shared_voter_state: shared_voter_state.clone(),
 							shared_authority_set: shared_authority_set.clone(),
+							justification_stream: justification_stream.clone(),
+							subscriptions: subscriptions.clone(),
+							finality_provider: finality_provider.clone(),
 						},
 					};
 
@@ -144,6 +167,9 @@ macro_rules! new_full_start {
 			})?;
 
 		(builder, import_setup, inherent_data_providers, rpc_setup)
+	}};
+	($config:expr) => {{
+		new_full_start!($config, None)
 	}}
 }
 
@@ -152,7 +178,7 @@ macro_rules! new_full_start {
 /// We need to use a macro because the test suit doesn't work with an opaque service. It expects
 /// concrete types instead.
 macro_rules! new_full {
-	($config:expr, $with_startup_data: expr) => {{
+	($config:expr, $max_reorg_depth:expr, $with_startup_data: expr) => {{
 		use futures::prelude::*;
 		use sc_network::Event;
 		use sc_client_api::ExecutorProvider;
@@ -171,7 +197,7 @@ macro_rules! new_full {
 		);
 
 		let (builder, mut import_setup, inherent_data_providers, mut rpc_setup) =
-			new_full_start!($config);
+			new_full_start!($config, $max_reorg_depth);
 
 		let service = builder
 			.with_finality_proof_provider(|client, backend| {
@@ -306,6 +332,9 @@ macro_rules! new_full {
 
 		Ok((service, inherent_data_providers))
 	}};
+	($config:expr, $with_startup_data: expr) => {{
+		new_full!($config, None, $with_startup_data)
+	}};
 	($config:expr) => {{
 		new_full!($config, |_, _| {})
 	}}
@@ -318,6 +347,14 @@ pub fn new_full(config: Configuration)
 	new_full!(config).map(|(service, _)| service)
 }
 
+/// Builds a new service for a full client which never reorgs more than
+/// `max_reorg_depth` blocks of its best chain, if given.
+pub fn new_full_with_max_reorg_depth(config: Configuration, max_reorg_depth: Option<u32>)
+-> Result<impl AbstractService, ServiceError>
+{
+	new_full!(config, max_reorg_depth, |_, _| {}).map(|(service, _)| service)
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(config: Configuration)
 -> Result<impl AbstractService, ServiceError> {
@@ -584,7 +621,6 @@ mod tests {
 				let check_nonce = frame_system::CheckNonce::from(index);
 				let check_weight = frame_system::CheckWeight::new();
 				let payment = pallet_transaction_payment::ChargeTransactionPayment::from(0);
-				let validate_grandpa_equivocation = pallet_grandpa::ValidateEquivocationReport::new();
 				let extra = (
 					check_spec_version,
 					check_tx_version,
@@ -593,12 +629,11 @@ mod tests {
 					check_nonce,
 					check_weight,
 					payment,
-					validate_grandpa_equivocation,
 				);
 				let raw_payload = SignedPayload::from_raw(
 					function,
 					extra,
-					(spec_version, transaction_version, genesis_hash, genesis_hash, (), (), (), ())
+					(spec_version, transaction_version, genesis_hash, genesis_hash, (), (), ())
 				);
 				let signature = raw_payload.using_encoded(|payload|	{
 					signer.sign(payload)
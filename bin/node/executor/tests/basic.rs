@@ -817,3 +817,19 @@ fn should_import_block_with_test_client() {
 
 	client.import(BlockOrigin::Own, block).unwrap();
 }
+
+#[test]
+fn upgrade_dry_run_api_returns_migration_weight() {
+	let mut t = new_test_ext(COMPACT_CODE, false);
+
+	let expected = t.execute_with(|| node_runtime::Executive::execute_on_runtime_upgrade());
+
+	let weight = executor_call::<NeverNativeValue, fn() -> _>(
+		&mut t,
+		"UpgradeDryRunApi_on_runtime_upgrade",
+		&[],
+		false,
+		None,
+	).0.unwrap();
+	assert_eq!(u64::decode(&mut &weight.as_encoded()[..]).unwrap(), expected);
+}
@@ -0,0 +1,3 @@
+pub mod chain_spec;
+pub mod pow;
+pub mod service;
@@ -0,0 +1,13 @@
+//! PoW/GRANDPA Template Node CLI library.
+#![warn(missing_docs)]
+
+mod chain_spec;
+mod pow;
+#[macro_use]
+mod service;
+mod cli;
+mod command;
+
+fn main() -> sc_cli::Result<()> {
+	command::run()
+}
@@ -0,0 +1,118 @@
+//! Blake2 proof of work with a difficulty adjusted to the target block time of the runtime.
+
+use std::sync::Arc;
+use codec::{Encode, Decode};
+use rand::{Rng, thread_rng};
+use sc_client_api::backend::AuxStore;
+use sc_consensus_pow::{Error, PowAlgorithm, difficulty::{Lwma, next_difficulty}};
+use sp_blockchain::HeaderBackend;
+use sp_consensus_pow::Seal;
+use sp_core::{U256, hashing::blake2_256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use pow_grandpa_template_runtime::MILLISECS_PER_BLOCK;
+
+/// Difficulty of the blocks mined before there is enough history to adjust it.
+const INITIAL_DIFFICULTY: u64 = 1_000_000;
+
+/// Number of blocks the difficulty adjustment averages over.
+const DIFFICULTY_WINDOW: usize = 45;
+
+/// Whether `hash` satisfies `difficulty`, i.e. `hash * difficulty` fits in 256 bits.
+fn hash_meets_difficulty(hash: &[u8; 32], difficulty: U256) -> bool {
+	let (_, overflowed) = U256::from(&hash[..]).overflowing_mul(difficulty);
+	!overflowed
+}
+
+/// Hash of the work done with `nonce` on the block with `pre_hash`.
+fn work_hash<H: Encode>(pre_hash: &H, nonce: U256) -> [u8; 32] {
+	blake2_256(&(pre_hash, nonce).encode())
+}
+
+/// Blake2 proof of work. The seal is the SCALE-encoded nonce.
+pub struct Blake2Algorithm<C> {
+	client: Arc<C>,
+}
+
+impl<C> Blake2Algorithm<C> {
+	/// Create a new instance reading the difficulty history from `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client }
+	}
+}
+
+// Manual implementation, the client does not need to be `Clone`.
+impl<C> Clone for Blake2Algorithm<C> {
+	fn clone(&self) -> Self {
+		Self { client: self.client.clone() }
+	}
+}
+
+impl<B, C> PowAlgorithm<B> for Blake2Algorithm<C> where
+	B: BlockT,
+	C: HeaderBackend<B> + AuxStore,
+{
+	type Difficulty = U256;
+
+	fn difficulty(&self, parent: B::Hash) -> Result<U256, Error<B>> {
+		let adjustment = Lwma {
+			window: DIFFICULTY_WINDOW,
+			target_block_time: MILLISECS_PER_BLOCK,
+		};
+
+		next_difficulty(self.client.as_ref(), parent, &adjustment, U256::from(INITIAL_DIFFICULTY))
+	}
+
+	fn requires_timestamp(&self) -> bool {
+		true
+	}
+
+	fn verify(
+		&self,
+		_parent: &BlockId<B>,
+		pre_hash: &B::Hash,
+		seal: &Seal,
+		difficulty: U256,
+	) -> Result<bool, Error<B>> {
+		let nonce = match U256::decode(&mut &seal[..]) {
+			Ok(nonce) => nonce,
+			Err(_) => return Ok(false),
+		};
+
+		Ok(hash_meets_difficulty(&work_hash(pre_hash, nonce), difficulty))
+	}
+
+	fn mine(
+		&self,
+		_parent: &BlockId<B>,
+		pre_hash: &B::Hash,
+		difficulty: U256,
+		round: u32,
+	) -> Result<Option<Seal>, Error<B>> {
+		let start = U256::from(thread_rng().gen::<[u8; 32]>());
+
+		for i in 0..round {
+			let nonce = start.overflowing_add(U256::from(i)).0;
+			if hash_meets_difficulty(&work_hash(pre_hash, nonce), difficulty) {
+				return Ok(Some(nonce.encode()))
+			}
+		}
+
+		Ok(None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn difficulty_bounds_the_hash() {
+		assert!(hash_meets_difficulty(&[0xff; 32], U256::one()));
+		assert!(!hash_meets_difficulty(&[0xff; 32], U256::from(2)));
+
+		let mut hash = [0u8; 32];
+		hash[0] = 0x01;
+		assert!(hash_meets_difficulty(&hash, U256::from(255)));
+		assert!(!hash_meets_difficulty(&hash, U256::from(256)));
+	}
+}
@@ -0,0 +1,214 @@
+//! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
+//!
+//! Blocks are mined with proof of work and finalized by GRANDPA. The PoW block import wraps the
+//! GRANDPA block import and [`sc_consensus_pow::HeaviestChain`] lets GRANDPA vote on the chain
+//! with the most work. Light clients are not supported.
+
+use std::sync::Arc;
+use std::time::Duration;
+use sc_client_api::ExecutorProvider;
+use sc_service::{error::{Error as ServiceError}, AbstractService, Configuration};
+use sc_executor::native_executor_instance;
+pub use sc_executor::NativeExecutor;
+use sc_finality_grandpa::{
+	FinalityProofProvider as GrandpaFinalityProofProvider, StorageAndProofProvider, SharedVoterState,
+};
+
+// Our native executor instance.
+native_executor_instance!(
+	pub Executor,
+	pow_grandpa_template_runtime::api::dispatch,
+	pow_grandpa_template_runtime::native_version,
+);
+
+/// Number of threads mining on authority nodes.
+const MINING_THREADS: usize = 1;
+
+/// Number of nonces each mining thread tries before checking for new work.
+const MINING_ROUND: u32 = 10_000;
+
+/// Starts a `ServiceBuilder` for a full service.
+///
+/// Use this macro if you don't actually need the full service, but just the builder in order to
+/// be able to perform chain operations.
+macro_rules! new_full_start {
+	($config:expr) => {{
+		use std::sync::Arc;
+
+		let mut import_setup = None;
+		let inherent_data_providers = sp_inherents::InherentDataProviders::new();
+
+		let builder = sc_service::ServiceBuilder::new_full::<
+			pow_grandpa_template_runtime::opaque::Block,
+			pow_grandpa_template_runtime::RuntimeApi,
+			crate::service::Executor
+		>($config)?
+			.with_select_chain(|_config, backend| {
+				Ok(sc_consensus_pow::HeaviestChain::new(backend.clone()))
+			})?
+			.with_transaction_pool(|builder| {
+				let pool_api = sc_transaction_pool::FullChainApi::new(
+					builder.client().clone(),
+				);
+				Ok(sc_transaction_pool::BasicPool::new(
+					builder.config().transaction_pool.clone(),
+					std::sync::Arc::new(pool_api),
+					builder.prometheus_registry(),
+				))
+			})?
+			.with_import_queue(|
+				_config,
+				client,
+				mut select_chain,
+				_transaction_pool,
+				spawn_task_handle,
+				registry,
+			| {
+				let select_chain = select_chain.take()
+					.ok_or_else(|| sc_service::Error::SelectChainRequired)?;
+
+				let (grandpa_block_import, grandpa_link) = sc_finality_grandpa::block_import(
+					client.clone(),
+					&(client.clone() as Arc<_>),
+					select_chain.clone(),
+				)?;
+
+				let algorithm = crate::pow::Blake2Algorithm::new(client.clone());
+
+				// PoW checks the seal and the fork choice, GRANDPA tracks authority set changes.
+				let pow_block_import = sc_consensus_pow::PowBlockImport::new(
+					grandpa_block_import.clone(),
+					client.clone(),
+					algorithm.clone(),
+					0,
+					Some(select_chain),
+					inherent_data_providers.clone(),
+				);
+
+				let import_queue = sc_consensus_pow::import_queue(
+					Box::new(pow_block_import.clone()),
+					Some(Box::new(grandpa_block_import)),
+					None,
+					algorithm.clone(),
+					inherent_data_providers.clone(),
+					spawn_task_handle,
+					registry,
+				)?;
+
+				import_setup = Some((pow_block_import, grandpa_link, algorithm));
+
+				Ok(import_queue)
+			})?;
+
+		(builder, import_setup, inherent_data_providers)
+	}}
+}
+
+/// Builds a new service for a full client.
+pub fn new_full(config: Configuration) -> Result<impl AbstractService, ServiceError> {
+	let role = config.role.clone();
+	let name = config.network.node_name.clone();
+	let disable_grandpa = config.disable_grandpa;
+
+	let (builder, mut import_setup, inherent_data_providers) = new_full_start!(config);
+
+	let (block_import, grandpa_link, algorithm) =
+		import_setup.take()
+			.expect("Link Half and Block Import are present for Full Services or setup failed before. qed");
+
+	let service = builder
+		.with_finality_proof_provider(|client, backend| {
+			// GenesisAuthoritySetProvider is implemented for StorageAndProofProvider
+			let provider = client as Arc<dyn StorageAndProofProvider<_, _>>;
+			Ok(Arc::new(GrandpaFinalityProofProvider::new(backend, provider)) as _)
+		})?
+		.build_full()?;
+
+	if role.is_authority() {
+		let proposer = sc_basic_authorship::ProposerFactory::new(
+			service.client(),
+			service.transaction_pool(),
+			service.prometheus_registry().as_ref(),
+		);
+
+		let client = service.client();
+		let select_chain = service.select_chain()
+			.ok_or(ServiceError::SelectChainRequired)?;
+
+		let can_author_with =
+			sp_consensus::CanAuthorWithNativeVersion::new(client.executor().clone());
+
+		let (mining_handle, mining_worker) = sc_consensus_pow::start_mining_worker(
+			Box::new(block_import),
+			client,
+			algorithm.clone(),
+			proposer,
+			None,
+			service.network(),
+			Duration::from_secs(10),
+			Duration::from_secs(2),
+			Some(select_chain),
+			inherent_data_providers.clone(),
+			can_author_with,
+		);
+
+		// the mining worker is considered essential, i.e. if it
+		// fails we take down the service with it.
+		service.spawn_essential_task_handle().spawn_blocking("pow-mining-worker", mining_worker);
+
+		sc_consensus_pow::start_mining_threads(mining_handle, algorithm, MINING_THREADS, MINING_ROUND);
+	}
+
+	// if the node isn't actively participating in consensus then it doesn't
+	// need a keystore, regardless of which protocol we use below.
+	let keystore = if role.is_authority() {
+		Some(service.keystore() as sp_core::traits::BareCryptoStorePtr)
+	} else {
+		None
+	};
+
+	let grandpa_config = sc_finality_grandpa::Config {
+		// FIXME #1578 make this available through chainspec
+		gossip_duration: Duration::from_millis(333),
+		justification_period: 512,
+		name: Some(name),
+		observer_enabled: false,
+		keystore,
+		is_authority: role.is_network_authority(),
+	};
+
+	let enable_grandpa = !disable_grandpa;
+	if enable_grandpa {
+		// start the full GRANDPA voter
+		// NOTE: non-authorities could run the GRANDPA observer protocol, but at
+		// this point the full voter should provide better guarantees of block
+		// and vote data availability than the observer. The observer has not
+		// been tested extensively yet and having most nodes in a network run it
+		// could lead to finality stalls.
+		let grandpa_config = sc_finality_grandpa::GrandpaParams {
+			config: grandpa_config,
+			link: grandpa_link,
+			network: service.network(),
+			inherent_data_providers: inherent_data_providers.clone(),
+			telemetry_on_connect: Some(service.telemetry_on_connect_stream()),
+			voting_rule: sc_finality_grandpa::VotingRulesBuilder::default().build(),
+			prometheus_registry: service.prometheus_registry(),
+			shared_voter_state: SharedVoterState::empty(),
+		};
+
+		// the GRANDPA voter task is considered infallible, i.e.
+		// if it fails we take down the service with it.
+		service.spawn_essential_task_handle().spawn_blocking(
+			"grandpa-voter",
+			sc_finality_grandpa::run_grandpa_voter(grandpa_config)?
+		);
+	} else {
+		sc_finality_grandpa::setup_disabled_grandpa(
+			service.client(),
+			&inherent_data_providers,
+			service.network(),
+		)?;
+	}
+
+	Ok(service)
+}
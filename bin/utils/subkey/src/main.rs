@@ -28,156 +28,24 @@ use itertools::Itertools;
 use libp2p::identity::{ed25519 as libp2p_ed25519, PublicKey};
 use node_primitives::{Balance, Hash, Index, AccountId, Signature};
 use node_runtime::{BalancesCall, Call, Runtime, SignedPayload, UncheckedExtrinsic, VERSION};
-use serde_json::json;
+use sc_cli::{utils::print_from_uri, OutputType};
 use sp_core::{
 	crypto::{set_default_ss58_version, Ss58AddressFormat, Ss58Codec},
 	ed25519, sr25519, ecdsa, Pair, Public, H256, hexdisplay::HexDisplay,
 };
-use sp_runtime::{traits::{AccountIdConversion, IdentifyAccount, Verify}, generic::Era, ModuleId};
+use sp_runtime::{traits::{AccountIdConversion, IdentifyAccount, Verify}, generic::Era, ModuleId, MultiSigner};
 use std::{
-	convert::{TryInto, TryFrom}, io::{stdin, Read}, str::FromStr, path::PathBuf, fs, fmt,
+	convert::{TryInto, TryFrom}, str::FromStr, fs, fmt,
 };
 
 mod rpc;
-mod vanity;
-
-enum OutputType {
-	Json,
-	Text,
-}
-
-impl<'a> TryFrom<&'a str> for OutputType {
-	type Error = ();
-
-	fn try_from(s: &'a str) -> Result<OutputType, ()> {
-		match s {
-			"json" => Ok(OutputType::Json),
-			"text" => Ok(OutputType::Text),
-			_ => Err(()),
-		}
-	}
-
-}
 
 trait Crypto: Sized {
 	type Pair: Pair<Public = Self::Public>;
-	type Public: Public + Ss58Codec + AsRef<[u8]> + std::hash::Hash;
+	type Public: Public + Ss58Codec + AsRef<[u8]> + std::hash::Hash + Into<MultiSigner>;
 	fn pair_from_suri(suri: &str, password: Option<&str>) -> Self::Pair {
 		Self::Pair::from_string(suri, password).expect("Invalid phrase")
 	}
-	fn ss58_from_pair(pair: &Self::Pair) -> String where
-		<Self::Pair as Pair>::Public: PublicT,
-	{
-		pair.public().into_runtime().into_account().to_ss58check()
-	}
-	fn public_from_pair(pair: &Self::Pair) -> Self::Public {
-		pair.public()
-	}
-	fn print_from_uri(
-		uri: &str,
-		password: Option<&str>,
-		network_override: Option<Ss58AddressFormat>,
-		output: OutputType,
-	) where
-		<Self::Pair as Pair>::Public: PublicT,
-	{
-		let v = network_override.unwrap_or_default();
-		if let Ok((pair, seed)) = Self::Pair::from_phrase(uri, password) {
-			let public_key = Self::public_from_pair(&pair);
-
-			match output {
-				OutputType::Json => {
-					let json = json!({
-						"secretPhrase": uri,
-						"networkId": String::from(v),
-						"secretSeed": format_seed::<Self>(seed),
-						"publicKey": format_public_key::<Self>(public_key.clone()),
-						"accountId": format_account_id::<Self>(public_key),
-						"ss58Address": Self::ss58_from_pair(&pair),
-					});
-					println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
-				},
-				OutputType::Text => {
-					println!("Secret phrase `{}` is account:\n  \
-						Network ID/version: {}\n  \
-						Secret seed:        {}\n  \
-						Public key (hex):   {}\n  \
-						Account ID:         {}\n  \
-						SS58 Address:       {}",
-						uri,
-						String::from(v),
-						format_seed::<Self>(seed),
-						format_public_key::<Self>(public_key.clone()),
-						format_account_id::<Self>(public_key),
-						Self::ss58_from_pair(&pair),
-					);
-				},
-			}
-		} else if let Ok((pair, seed)) = Self::Pair::from_string_with_seed(uri, password) {
-			let public_key = Self::public_from_pair(&pair);
-
-			match output {
-				OutputType::Json => {
-					let json = json!({
-						"secretKeyUri": uri,
-						"networkId": String::from(v),
-						"secretSeed": if let Some(seed) = seed { format_seed::<Self>(seed) } else { "n/a".into() },
-						"publicKey": format_public_key::<Self>(public_key.clone()),
-						"accountId": format_account_id::<Self>(public_key),
-						"ss58Address": Self::ss58_from_pair(&pair),
-					});
-					println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
-				},
-				OutputType::Text => {
-					println!("Secret Key URI `{}` is account:\n  \
-						Network ID/version: {}\n  \
-						Secret seed:        {}\n  \
-						Public key (hex):   {}\n  \
-						Account ID:         {}\n  \
-						SS58 Address:       {}",
-						uri,
-						String::from(v),
-						if let Some(seed) = seed { format_seed::<Self>(seed) } else { "n/a".into() },
-						format_public_key::<Self>(public_key.clone()),
-						format_account_id::<Self>(public_key),
-						Self::ss58_from_pair(&pair),
-					);
-				},
-			}
-		} else if let Ok((public_key, v)) =
-			<Self::Pair as Pair>::Public::from_string_with_version(uri)
-		{
-			let v = network_override.unwrap_or(v);
-
-			match output {
-				OutputType::Json => {
-					let json = json!({
-						"publicKeyUri": uri,
-						"networkId": String::from(v),
-						"publicKey": format_public_key::<Self>(public_key.clone()),
-						"accountId": format_account_id::<Self>(public_key.clone()),
-						"ss58Address": public_key.to_ss58check_with_version(v),
-					});
-					println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
-				},
-				OutputType::Text => {
-					println!("Public Key URI `{}` is account:\n  \
-						Network ID/version: {}\n  \
-						Public key (hex):   {}\n  \
-						Account ID:         {}\n  \
-						SS58 Address:       {}",
-						uri,
-						String::from(v),
-						format_public_key::<Self>(public_key.clone()),
-						format_account_id::<Self>(public_key.clone()),
-						public_key.to_ss58check_with_version(v),
-					);
-				},
-			}
-		} else {
-			eprintln!("Invalid phrase/URI given");
-		}
-	}
 }
 
 struct Ed25519;
@@ -207,7 +75,6 @@ impl Crypto for Ecdsa {
 
 type SignatureOf<C> = <<C as Crypto>::Pair as Pair>::Signature;
 type PublicOf<C> = <<C as Crypto>::Pair as Pair>::Public;
-type SeedOf<C> = <<C as Crypto>::Pair as Pair>::Seed;
 type AccountPublic = <Signature as Verify>::Signer;
 
 trait SignatureT: AsRef<[u8]> + AsMut<[u8]> + Default {
@@ -351,20 +218,8 @@ fn main() -> Result<(), Error> {
 /// If the `URI` given as CLI argument is a file, the file content is taken as `URI`.
 /// If no `URI` is given to the CLI, the user is prompted for it.
 fn get_uri(match_name: &str, matches: &ArgMatches) -> Result<String, Error> {
-	let uri = if let Some(uri) = matches.value_of(match_name) {
-		let file = PathBuf::from(uri);
-		if file.is_file() {
-			fs::read_to_string(uri)?
-				.trim_end()
-				.into()
-		} else {
-			uri.into()
-		}
-	} else {
-		rpassword::read_password_from_tty(Some("URI: "))?
-	};
-
-	Ok(uri)
+	let uri = matches.value_of(match_name).map(String::from);
+	Ok(sc_cli::utils::read_uri(uri.as_ref())?)
 }
 
 #[derive(derive_more::Display, derive_more::From)]
@@ -372,6 +227,7 @@ enum Error {
 	Static(&'static str),
 	Io(std::io::Error),
 	Formatted(String),
+	Cli(sc_cli::Error),
 }
 
 impl fmt::Debug for Error {
@@ -417,7 +273,7 @@ where
 		set_default_ss58_version(network);
 	}
 
-	let output: OutputType = match matches.value_of("output").map(TryInto::try_into) {
+	let output: OutputType = match matches.value_of("output").map(str::parse) {
 		Some(Err(_)) => return Err(Error::Static("Invalid output name. See --help for available outputs.")),
 		Some(Ok(v)) => v,
 		None => OutputType::Text,
@@ -426,7 +282,7 @@ where
 	match matches.subcommand() {
 		("generate", Some(matches)) => {
 			let mnemonic = generate_mnemonic(matches)?;
-			C::print_from_uri(mnemonic.phrase(), password, maybe_network, output);
+			print_from_uri::<C::Pair>(mnemonic.phrase(), password, maybe_network, output)?;
 		}
 		("generate-node-key", Some(matches)) => {
 			let file = matches.value_of("file").ok_or(Error::Static("Output file name is required"))?;
@@ -440,7 +296,8 @@ where
 			println!("{}", peer_id);
 		}
 		("inspect", Some(matches)) => {
-			C::print_from_uri(&get_uri("uri", &matches)?, password, maybe_network, output);
+			let uri = get_uri("uri", &matches)?;
+			print_from_uri::<C::Pair>(&uri, password, maybe_network, output)?;
 		}
 		("inspect-node-key", Some(matches)) => {
 			let file = matches.value_of("file").ok_or(Error::Static("Input file name is required"))?;
@@ -457,16 +314,19 @@ where
 			let suri = get_uri("suri", &matches)?;
 			let should_decode = matches.is_present("hex");
 
-			let message = read_message_from_stdin(should_decode)?;
-			let signature = do_sign::<C>(&suri, message, password)?;
+			let message = sc_cli::utils::read_message(None, should_decode)?;
+			let signature = sc_cli::utils::sign::<C::Pair>(&suri, password, &message)?;
 			println!("{}", signature);
 		}
 		("verify", Some(matches)) => {
 			let uri = get_uri("uri", &matches)?;
 			let should_decode = matches.is_present("hex");
 
-			let message = read_message_from_stdin(should_decode)?;
-			let is_valid_signature = do_verify::<C>(matches, &uri, message)?;
+			let signature = matches
+				.value_of("sig")
+				.expect("signature parameter is required; thus it can't be None; qed");
+			let message = sc_cli::utils::read_message(None, should_decode)?;
+			let is_valid_signature = sc_cli::utils::verify::<C::Pair>(signature, &message, &uri)?;
 			if is_valid_signature {
 				println!("Signature verifies correctly.");
 			} else {
@@ -478,9 +338,8 @@ where
 				.value_of("pattern")
 				.map(str::to_string)
 				.unwrap_or_default();
-			let result = vanity::generate_key::<C>(&desired)?;
-			let formated_seed = format_seed::<C>(result.seed);
-			C::print_from_uri(&formated_seed, None, maybe_network, output);
+			let seed = sc_cli::generate_key::<C::Pair>(&desired, maybe_network)?;
+			print_from_uri::<C::Pair>(&seed, None, maybe_network, output)?;
 		}
 		("transfer", Some(matches)) => {
 			let signer = read_pair::<C>(matches.value_of("from"), password)?;
@@ -540,7 +399,12 @@ where
 			let account_id: AccountId = ModuleId(id_fixed_array).into_account();
 			let v = maybe_network.unwrap_or(Ss58AddressFormat::SubstrateAccount);
 
-			C::print_from_uri(&account_id.to_ss58check_with_version(v), password, maybe_network, output);
+			print_from_uri::<C::Pair>(
+				&account_id.to_ss58check_with_version(v),
+				password,
+				maybe_network,
+				output,
+			)?;
 		}
 		_ => print_usage(&matches),
 	}
@@ -561,42 +425,10 @@ fn generate_mnemonic(matches: &ArgMatches) -> Result<Mnemonic, Error> {
 	Ok(Mnemonic::new(words, Language::English))
 }
 
-fn do_sign<C: Crypto>(suri: &str, message: Vec<u8>, password: Option<&str>) -> Result<String, Error>
-where
-	SignatureOf<C>: SignatureT,
-	PublicOf<C>: PublicT,
-{
-	let pair = read_pair::<C>(Some(suri), password)?;
-	let signature = pair.sign(&message);
-	Ok(format_signature::<C>(&signature))
-}
-
-fn do_verify<C: Crypto>(matches: &ArgMatches, uri: &str, message: Vec<u8>) -> Result<bool, Error>
-where
-	SignatureOf<C>: SignatureT,
-	PublicOf<C>: PublicT,
-{
-
-	let signature = read_signature::<C>(matches)?;
-	let pubkey = read_public_key::<C>(Some(uri));
-	Ok(<<C as Crypto>::Pair as Pair>::verify(&signature, &message, &pubkey))
-}
-
 fn decode_hex<T: AsRef<[u8]>>(message: T) -> Result<Vec<u8>, Error> {
 	hex::decode(message).map_err(|e| Error::Formatted(format!("Invalid hex ({})", e)))
 }
 
-fn read_message_from_stdin(should_decode: bool) -> Result<Vec<u8>, Error> {
-	let mut message = vec![];
-	stdin()
-		.lock()
-		.read_to_end(&mut message)?;
-	if should_decode {
-		message = decode_hex(&message)?;
-	}
-	Ok(message)
-}
-
 fn read_required_parameter<T: FromStr>(matches: &ArgMatches, name: &str) -> Result<T, Error> where
 	<T as FromStr>::Err: std::fmt::Debug,
 {
@@ -622,46 +454,6 @@ fn read_genesis_hash(matches: &ArgMatches) -> Result<H256, Error> {
 	Ok(genesis_hash)
 }
 
-fn read_signature<C: Crypto>(matches: &ArgMatches) -> Result<SignatureOf<C>, Error>
-where
-	SignatureOf<C>: SignatureT,
-	PublicOf<C>: PublicT,
-{
-	let sig_data = matches
-		.value_of("sig")
-		.expect("signature parameter is required; thus it can't be None; qed");
-	let mut signature = <<C as Crypto>::Pair as Pair>::Signature::default();
-	let sig_data = decode_hex(sig_data)?;
-	if sig_data.len() != signature.as_ref().len() {
-		return Err(Error::Formatted(format!(
-			"signature has an invalid length. read {} bytes, expected {} bytes",
-			sig_data.len(),
-			signature.as_ref().len(),
-		)));
-	}
-	signature.as_mut().copy_from_slice(&sig_data);
-	Ok(signature)
-}
-
-fn read_public_key<C: Crypto>(matched_uri: Option<&str>) -> PublicOf<C>
-where
-	PublicOf<C>: PublicT,
-{
-	let uri = matched_uri.expect("parameter is required; thus it can't be None; qed");
-	let uri = if uri.starts_with("0x") {
-		&uri[2..]
-	} else {
-		uri
-	};
-	if let Ok(pubkey_vec) = hex::decode(uri) {
-		<C as Crypto>::Public::from_slice(pubkey_vec.as_slice())
-	} else {
-		<C as Crypto>::Public::from_string(uri)
-			.ok()
-			.expect("Invalid URI; expecting either a secret URI or a public URI.")
-	}
-}
-
 fn read_account_id(matched_uri: Option<&str>) -> AccountId {
 	let uri = matched_uri.expect("parameter is required; thus it can't be None; qed");
 	let uri = if uri.starts_with("0x") {
@@ -689,24 +481,6 @@ fn read_pair<C: Crypto>(
 	Ok(C::pair_from_suri(suri, password))
 }
 
-fn format_signature<C: Crypto>(signature: &SignatureOf<C>) -> String {
-	format!("{}", HexDisplay::from(&signature.as_ref()))
-}
-
-fn format_seed<C: Crypto>(seed: SeedOf<C>) -> String {
-	format!("0x{}", HexDisplay::from(&seed.as_ref()))
-}
-
-fn format_public_key<C: Crypto>(public_key: PublicOf<C>) -> String {
-	format!("0x{}", HexDisplay::from(&public_key.as_ref()))
-}
-
-fn format_account_id<C: Crypto>(public_key: PublicOf<C>) -> String where
-	PublicOf<C>: PublicT,
-{
-	format!("0x{}", HexDisplay::from(&public_key.into_runtime().into_account().as_ref()))
-}
-
 fn create_extrinsic<C: Crypto>(
 	function: Call,
 	index: Index,
@@ -725,7 +499,6 @@ fn create_extrinsic<C: Crypto>(
 			frame_system::CheckNonce::<Runtime>::from(i),
 			frame_system::CheckWeight::<Runtime>::new(),
 			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(f),
-			pallet_grandpa::ValidateEquivocationReport::<Runtime>::new(),
 		)
 	};
 	let raw_payload = SignedPayload::from_raw(
@@ -739,7 +512,6 @@ fn create_extrinsic<C: Crypto>(
 			(),
 			(),
 			(),
-			(),
 		),
 	);
 	let signature = raw_payload.using_encoded(|payload| signer.sign(payload)).into_runtime();
@@ -785,20 +557,25 @@ mod tests {
 		let (pair, seed) =
 			<<CryptoType as Crypto>::Pair as Pair>::from_phrase(mnemonic.phrase(), password)
 				.unwrap();
-		let public_key = CryptoType::public_from_pair(&pair);
-		let public_key = format_public_key::<CryptoType>(public_key);
-		let seed = format_seed::<CryptoType>(seed);
+		let public_key = sc_cli::utils::format_public_key::<CryptoType::Pair>(pair.public());
+		let seed = sc_cli::utils::format_seed::<CryptoType::Pair>(seed);
 		let message = "Blah Blah\n".as_bytes().to_vec();
 
-		let signature = do_sign::<CryptoType>(&seed, message.clone(), password).expect("signing failed");
+		let signature = sc_cli::utils::sign::<CryptoType::Pair>(&seed, password, &message)
+			.expect("signing failed");
 
 		// Verify the previous signature.
 		let arg_vec = vec!["subkey", "verify", &signature[..], &public_key[..]];
 
 		let matches = get_app(&usage).get_matches_from(arg_vec);
 		let matches = matches.subcommand().1.unwrap();
+		let signature = matches.value_of("sig").unwrap();
+		let uri = matches.value_of("uri").unwrap();
 
-		assert!(do_verify::<CryptoType>(matches, &public_key, message).expect("verify failed"));
+		assert!(
+			sc_cli::utils::verify::<CryptoType::Pair>(signature, &message, uri)
+				.expect("verify failed")
+		);
 	}
 
 	#[test]
@@ -25,7 +25,7 @@ use codec::Decode;
 use sp_core::{
 	ExecutionContext,
 	offchain::{self, OffchainExt, TransactionPoolExt},
-	traits::{BareCryptoStorePtr, KeystoreExt},
+	traits::{BareCryptoStorePtr, KeystoreExt, RuntimeHeapLimitExt},
 };
 use sp_runtime::{
 	generic::BlockId,
@@ -82,6 +82,7 @@ impl ExtensionsFactory for () {
 pub struct ExecutionExtensions<Block: traits::Block> {
 	strategies: ExecutionStrategies,
 	keystore: Option<BareCryptoStorePtr>,
+	runtime_heap_limit: Option<u32>,
 	// FIXME: these two are only RwLock because of https://github.com/paritytech/substrate/issues/4587
 	//        remove when fixed.
 	transaction_pool: RwLock<Option<Weak<dyn sp_transaction_pool::OffchainSubmitTransaction<Block>>>>,
@@ -93,6 +94,7 @@ impl<Block: traits::Block> Default for ExecutionExtensions<Block> {
 		Self {
 			strategies: Default::default(),
 			keystore: None,
+			runtime_heap_limit: None,
 			transaction_pool: RwLock::new(None),
 			extensions_factory: RwLock::new(Box::new(())),
 		}
@@ -107,7 +109,22 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 	) -> Self {
 		let transaction_pool = RwLock::new(None);
 		let extensions_factory = Box::new(());
-		Self { strategies, keystore, extensions_factory: RwLock::new(extensions_factory), transaction_pool }
+		Self {
+			strategies,
+			keystore,
+			runtime_heap_limit: None,
+			extensions_factory: RwLock::new(extensions_factory),
+			transaction_pool,
+		}
+	}
+
+	/// Let offchain worker and RPC calls allocate at most `runtime_heap_limit` bytes at the same
+	/// time.
+	///
+	/// Other calls, including the runtime API calls made by default, are never limited.
+	pub fn with_runtime_heap_limit(mut self, runtime_heap_limit: Option<u32>) -> Self {
+		self.runtime_heap_limit = runtime_heap_limit;
+		self
 	}
 
 	/// Get a reference to the execution strategies.
@@ -174,7 +191,10 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 			}
 		}
 
+		// `OffchainCall(None)` is the default context of runtime API calls, which are also made
+		// by block import, so only offchain workers are limited.
 		if let ExecutionContext::OffchainCall(Some(ext)) = context {
+			self.register_runtime_heap_limit(&mut extensions);
 			extensions.register(
 				OffchainExt::new(offchain::LimitedExternalities::new(capabilities, ext.0))
 			);
@@ -182,6 +202,19 @@ impl<Block: traits::Block> ExecutionExtensions<Block> {
 
 		(manager, extensions)
 	}
+
+	/// Create `Extensions` for a call made on behalf of an RPC client.
+	pub fn rpc_extensions(&self) -> Extensions {
+		let mut extensions = Extensions::new();
+		self.register_runtime_heap_limit(&mut extensions);
+		extensions
+	}
+
+	fn register_runtime_heap_limit(&self, extensions: &mut Extensions) {
+		if let Some(limit) = self.runtime_heap_limit {
+			extensions.register(RuntimeHeapLimitExt(limit));
+		}
+	}
 }
 
 /// A wrapper type to pass `BlockId` to the actual transaction pool.
@@ -203,3 +236,43 @@ impl<Block: traits::Block> offchain::TransactionPool for TransactionPoolAdapter<
 		self.pool.submit_at(&self.at, xt)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::any::TypeId;
+	use sp_core::offchain::testing::TestOffchainExt;
+	use substrate_test_runtime::Block;
+
+	fn is_limited(
+		extensions: &ExecutionExtensions<Block>,
+		context: ExecutionContext,
+	) -> bool {
+		let (_, mut extensions) = extensions.manager_and_extensions::<String, ()>(
+			&BlockId::Number(0),
+			context,
+		);
+		extensions.get_mut(TypeId::of::<RuntimeHeapLimitExt>()).is_some()
+	}
+
+	#[test]
+	fn only_offchain_workers_and_rpc_calls_are_heap_limited() {
+		let extensions = ExecutionExtensions::<Block>::new(Default::default(), None)
+			.with_runtime_heap_limit(Some(1024 * 1024));
+
+		// the default context of `runtime_api()` calls.
+		assert!(!is_limited(&extensions, ExecutionContext::OffchainCall(None)));
+		assert!(!is_limited(&extensions, ExecutionContext::Importing));
+		assert!(!is_limited(&extensions, ExecutionContext::Syncing));
+		assert!(!is_limited(&extensions, ExecutionContext::BlockConstruction));
+
+		let offchain_worker = ExecutionContext::OffchainCall(Some((
+			Box::new(TestOffchainExt::new().0),
+			offchain::Capabilities::all(),
+		)));
+		assert!(is_limited(&extensions, offchain_worker));
+
+		let mut rpc_extensions = extensions.rpc_extensions();
+		assert!(rpc_extensions.get_mut(TypeId::of::<RuntimeHeapLimitExt>()).is_some());
+	}
+}
@@ -20,7 +20,7 @@
 
 // FIXME #1021 move this into sp-consensus
 
-use std::{time, sync::Arc};
+use std::{collections::HashSet, time, sync::Arc};
 use sc_client_api::backend;
 use codec::Decode;
 use sp_consensus::{evaluation, Proposal, RecordProof};
@@ -227,6 +227,8 @@ impl<A, B, Block, C> Proposer<B, Block, C, A>
 		let block_timer = time::Instant::now();
 		let mut skipped = 0;
 		let mut unqueue_invalid = Vec::new();
+		// Members of the bundles that were already pushed or skipped as a whole.
+		let mut handled_bundle_members = HashSet::new();
 		let pending_iterator = match executor::block_on(future::select(
 			self.transaction_pool.ready_at(self.parent_number),
 			futures_timer::Delay::new(deadline.saturating_duration_since((self.now)()) / 8),
@@ -254,10 +256,39 @@ impl<A, B, Block, C> Proposer<B, Block, C, A>
 
 			let pending_tx_data = pending_tx.data().clone();
 			let pending_tx_hash = pending_tx.hash().clone();
-			trace!("[{:?}] Pushing to the block.", pending_tx_hash);
-			match sc_block_builder::BlockBuilder::push(&mut block_builder, pending_tx_data) {
+			if handled_bundle_members.contains(&pending_tx_hash) {
+				continue;
+			}
+			let (pending_tx_hashes, push_result) = match self.transaction_pool.bundle_of(&pending_tx_hash) {
+				Some(members) => {
+					// The whole bundle is pushed when its first member comes up, the other members
+					// are looked up in the pool rather than taken from the iterator.
+					handled_bundle_members.extend(members.iter().cloned());
+					let xts = members.iter()
+						.map(|hash| self.transaction_pool.ready_transaction(hash).map(|tx| tx.data().clone()))
+						.collect::<Option<Vec<_>>>();
+					let xts = match xts {
+						Some(xts) => xts,
+						None => {
+							warn!("{:?} Not all members of the bundle are ready, skipping it.", members);
+							continue;
+						}
+					};
+
+					trace!("{:?} Pushing bundle to the block.", members);
+					let result = block_builder.push_bundle(xts);
+					(members, result)
+				},
+				None => {
+					trace!("[{:?}] Pushing to the block.", pending_tx_hash);
+					let result = sc_block_builder::BlockBuilder::push(&mut block_builder, pending_tx_data);
+					(vec![pending_tx_hash], result)
+				},
+			};
+
+			match push_result {
 				Ok(()) => {
-					debug!("[{:?}] Pushed to the block.", pending_tx_hash);
+					debug!("{:?} Pushed to the block.", pending_tx_hashes);
 				}
 				Err(ApplyExtrinsicFailed(Validity(e)))
 						if e.exhausted_resources() => {
@@ -274,14 +305,14 @@ impl<A, B, Block, C> Proposer<B, Block, C, A>
 				}
 				Err(e) if skipped > 0 => {
 					trace!(
-						"[{:?}] Ignoring invalid transaction when skipping: {}",
-						pending_tx_hash,
+						"{:?} Ignoring invalid transaction when skipping: {}",
+						pending_tx_hashes,
 						e
 					);
 				}
 				Err(e) => {
-					debug!("[{:?}] Invalid transaction: {}", pending_tx_hash, e);
-					unqueue_invalid.push(pending_tx_hash);
+					debug!("{:?} Invalid transaction: {}", pending_tx_hashes, e);
+					unqueue_invalid.extend(pending_tx_hashes);
 				}
 			}
 		}
@@ -597,4 +628,60 @@ mod tests {
 		let block = propose_block(&client, 1, 2, 5);
 		client.import(BlockOrigin::Own, block).unwrap();
 	}
+
+	#[test]
+	fn should_include_bundles_all_or_nothing() {
+		// given
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let txpool = Arc::new(
+			BasicPool::new(
+				Default::default(),
+				Arc::new(FullChainApi::new(client.clone())),
+				None,
+			).0
+		);
+		let transfer = |from: AccountKeyring, nonce, amount| Transfer {
+			amount,
+			nonce,
+			from: from.into(),
+			to: Default::default(),
+		}.into_signed_tx();
+
+		let valid = futures::executor::block_on(txpool.submit_bundle(&BlockId::number(0), SOURCE, vec![
+			transfer(AccountKeyring::Alice, 0, 0),
+			transfer(AccountKeyring::Bob, 0, 0),
+		])).unwrap();
+		// Charlie can't afford the transfer, so Alice's second transfer must not be applied either.
+		futures::executor::block_on(txpool.submit_bundle(&BlockId::number(0), SOURCE, vec![
+			transfer(AccountKeyring::Alice, 1, 0),
+			transfer(AccountKeyring::Charlie, 0, u64::max_value()),
+		])).unwrap();
+
+		futures::executor::block_on(
+			txpool.maintain(chain_event(
+				client.header(&BlockId::Number(0u64))
+					.expect("header get error")
+					.expect("there should be header")
+			))
+		);
+
+		let mut proposer_factory = ProposerFactory::new(client.clone(), txpool.clone(), None);
+		let proposer = proposer_factory.init_with_now(
+			&client.header(&BlockId::number(0)).unwrap().unwrap(),
+			Box::new(move || time::Instant::now()),
+		);
+
+		// when
+		let deadline = time::Duration::from_secs(9);
+		let block = futures::executor::block_on(
+			proposer.propose(Default::default(), Default::default(), deadline, RecordProof::No)
+		).map(|r| r.block).unwrap();
+
+		// then
+		assert_eq!(
+			block.extrinsics().iter().map(|xt| BlakeTwo256::hash_of(xt)).collect::<Vec<_>>(),
+			valid,
+		);
+		assert_eq!(txpool.ready().map(|tx| tx.hash().clone()).collect::<Vec<_>>(), valid);
+	}
 }
@@ -171,6 +171,39 @@ where
 		})
 	}
 
+	/// Push a bundle of extrinsics onto the block's list of extrinsics.
+	///
+	/// The extrinsics are applied all-or-nothing: if any of them is invalid or fails to dispatch,
+	/// the changes made by the others are discarded and none of them is added to the block.
+	pub fn push_bundle(
+		&mut self,
+		xts: Vec<<Block as BlockT>::Extrinsic>,
+	) -> Result<(), ApiErrorFor<A, Block>> {
+		let block_id = &self.block_id;
+		let extrinsics = &mut self.extrinsics;
+
+		// `map_api_result` applies the extrinsics in a storage transaction that is rolled back
+		// when an error is returned.
+		self.api.map_api_result(|api| {
+			for xt in &xts {
+				match api.apply_extrinsic_with_context(
+					block_id,
+					ExecutionContext::BlockConstruction,
+					xt.clone(),
+				)? {
+					Ok(Ok(())) => {}
+					Ok(Err(dispatch_error)) => return Err(ApplyExtrinsicFailed::Msg(
+						format!("Bundle member failed to dispatch: {:?}", dispatch_error),
+					).into()),
+					Err(tx_validity) => return Err(ApplyExtrinsicFailed::Validity(tx_validity).into()),
+				}
+			}
+
+			extrinsics.extend(xts);
+			Ok(())
+		})
+	}
+
 	/// Consume the builder to build a valid `Block` containing all pushed extrinsics.
 	///
 	/// Returns the build `Block`, the changes to the storage and an optional `StorageProof`
@@ -174,6 +174,27 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// Output format of the key commands.
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum OutputType {
+		Json,
+		Text,
+	}
+}
+
+arg_enum! {
+	/// Cryptography scheme of a key.
+	#[allow(missing_docs)]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub enum CryptoScheme {
+		Ed25519,
+		Sr25519,
+		Ecdsa,
+	}
+}
+
 /// Default value for the `--execution-syncing` parameter.
 pub const DEFAULT_EXECUTION_SYNCING: ExecutionStrategy = ExecutionStrategy::NativeElseWasm;
 /// Default value for the `--execution-import-block` parameter.
@@ -0,0 +1,73 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{KeystoreParams, SharedParams};
+use crate::CliConfiguration;
+use super::key::{open_keystore, read_password};
+use sc_service::Configuration;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// The `key change-password` command used to re-encrypt the keystore with a new password.
+#[derive(Debug, StructOpt)]
+pub struct ChangeKeystorePasswordCmd {
+	/// The new keystore password.
+	///
+	/// Asked for interactively if neither this nor `--new-password-filename` is given.
+	#[structopt(long = "new-password", conflicts_with = "new-password-filename")]
+	pub new_password: Option<String>,
+
+	/// File that contains the new keystore password.
+	#[structopt(long = "new-password-filename", value_name = "PATH", parse(from_os_str))]
+	pub new_password_filename: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ChangeKeystorePasswordCmd {
+	/// Run the change-password command
+	pub fn run(&self, config: Configuration) -> error::Result<()> {
+		let keystore = open_keystore(&config.keystore)?;
+		let password = read_password(
+			&self.new_password,
+			&self.new_password_filename,
+			"New keystore password: ",
+		)?;
+		let converted = keystore.write().change_password(password)?;
+
+		println!("{} key file(s) encrypted with the new password.", converted);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ChangeKeystorePasswordCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
@@ -0,0 +1,88 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{KeystoreParams, SharedParams};
+use crate::CliConfiguration;
+use super::key::{open_keystore, parse_key_type, read_password};
+use sc_service::Configuration;
+use sp_core::crypto::KeyTypeId;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// The `key export` command used to back up keys.
+#[derive(Debug, StructOpt)]
+pub struct ExportKeysCmd {
+	/// Key type to export, e.g. `babe` or `gran`. Can be given multiple times.
+	///
+	/// All keys are exported if no key type is given.
+	#[structopt(long = "key-type", value_name = "KEY_TYPE", parse(try_from_str = parse_key_type))]
+	pub key_types: Vec<KeyTypeId>,
+
+	/// Output file, the backup is written to stdout if not given.
+	#[structopt(long, short, value_name = "PATH", parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	/// Password the backup is encrypted with.
+	///
+	/// Asked for interactively if neither this nor `--backup-password-filename` is given.
+	#[structopt(long = "backup-password", conflicts_with = "backup-password-filename")]
+	pub backup_password: Option<String>,
+
+	/// File that contains the password the backup is encrypted with.
+	#[structopt(long = "backup-password-filename", value_name = "PATH", parse(from_os_str))]
+	pub backup_password_filename: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ExportKeysCmd {
+	/// Run the export command
+	pub fn run(&self, config: Configuration) -> error::Result<()> {
+		let keystore = open_keystore(&config.keystore)?;
+		let password = read_password(
+			&self.backup_password,
+			&self.backup_password_filename,
+			"Backup password: ",
+		)?;
+		let backup = keystore.read().export_keys(&self.key_types, password)?;
+
+		match &self.output {
+			Some(output) => fs::write(output, backup)?,
+			None => println!("{}", backup),
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportKeysCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
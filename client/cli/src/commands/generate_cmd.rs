@@ -0,0 +1,75 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::{self, Error};
+use crate::params::{CryptoSchemeFlag, NetworkSchemeFlag, OutputTypeFlag, SecretPasswordParams};
+use crate::CryptoScheme;
+use super::utils::print_from_uri;
+use bip39::{Language, Mnemonic, MnemonicType};
+use sp_core::{ecdsa, ed25519, sr25519};
+use structopt::StructOpt;
+
+/// The `key generate` command used to create a new random key.
+#[derive(Debug, StructOpt)]
+pub struct GenerateCmd {
+	/// The number of words in the phrase to generate. One of 12 (default), 15, 18, 21 and 24.
+	#[structopt(long, short = "w", value_name = "WORDS")]
+	pub words: Option<usize>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub password_params: SecretPasswordParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub network_scheme: NetworkSchemeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub output_scheme: OutputTypeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}
+
+impl GenerateCmd {
+	/// Run the generate command
+	pub fn run(&self) -> error::Result<()> {
+		let words = match self.words {
+			Some(words) => MnemonicType::for_word_count(words).map_err(|_| {
+				Error::Input("Invalid number of words given for phrase: must be 12/15/18/21/24".into())
+			})?,
+			None => MnemonicType::Words12,
+		};
+		let mnemonic = Mnemonic::new(words, Language::English);
+		let password = self.password_params.password()?;
+		let password = password.as_ref().map(String::as_str);
+		let network = self.network_scheme.network;
+		let output = self.output_scheme.output_type;
+
+		match self.crypto_scheme.scheme {
+			CryptoScheme::Ed25519 =>
+				print_from_uri::<ed25519::Pair>(mnemonic.phrase(), password, network, output),
+			CryptoScheme::Sr25519 =>
+				print_from_uri::<sr25519::Pair>(mnemonic.phrase(), password, network, output),
+			CryptoScheme::Ecdsa =>
+				print_from_uri::<ecdsa::Pair>(mnemonic.phrase(), password, network, output),
+		}
+	}
+}
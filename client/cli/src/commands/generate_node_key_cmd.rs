@@ -0,0 +1,55 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::{self, Error};
+use sc_network::config::{identity::{ed25519, Keypair}, NodeKeyConfig, Secret};
+use sp_core::hexdisplay::HexDisplay;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// The `key generate-node-key` command used to create a new node key.
+#[derive(Debug, StructOpt)]
+pub struct GenerateNodeKeyCmd {
+	/// File to save the secret key to.
+	///
+	/// The hex encoded secret key is printed to stdout and the peer id to stderr if not given.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub file: Option<PathBuf>,
+}
+
+impl GenerateNodeKeyCmd {
+	/// Run the generate-node-key command
+	pub fn run(&self) -> error::Result<()> {
+		match &self.file {
+			Some(file) => {
+				if file.exists() {
+					return Err(Error::Input(format!("{} already exists", file.display())))
+				}
+				let keypair = NodeKeyConfig::Ed25519(Secret::File(file.clone())).into_keypair()?;
+				println!("{}", keypair.public().into_peer_id());
+			},
+			None => {
+				let keypair = ed25519::Keypair::generate();
+				println!("{}", HexDisplay::from(&keypair.secret().as_ref()));
+				eprintln!("{}", Keypair::Ed25519(keypair).public().into_peer_id());
+			},
+		}
+
+		Ok(())
+	}
+}
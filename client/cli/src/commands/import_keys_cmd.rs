@@ -0,0 +1,85 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{KeystoreParams, SharedParams};
+use crate::CliConfiguration;
+use super::key::{open_keystore, read_password};
+use sc_service::Configuration;
+use sp_core::hexdisplay::HexDisplay;
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// The `key import` command used to restore keys from a backup.
+#[derive(Debug, StructOpt)]
+pub struct ImportKeysCmd {
+	/// Backup file created by `key export`.
+	#[structopt(long, short, value_name = "PATH", parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// Password the backup is encrypted with.
+	///
+	/// Asked for interactively if neither this nor `--backup-password-filename` is given.
+	#[structopt(long = "backup-password", conflicts_with = "backup-password-filename")]
+	pub backup_password: Option<String>,
+
+	/// File that contains the password the backup is encrypted with.
+	#[structopt(long = "backup-password-filename", value_name = "PATH", parse(from_os_str))]
+	pub backup_password_filename: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl ImportKeysCmd {
+	/// Run the import command
+	pub fn run(&self, config: Configuration) -> error::Result<()> {
+		let keystore = open_keystore(&config.keystore)?;
+		let backup = fs::read_to_string(&self.input)?;
+		let password = read_password(
+			&self.backup_password,
+			&self.backup_password_filename,
+			"Backup password: ",
+		)?;
+
+		for (key_type, public) in keystore.read().import_keys(&backup, password)? {
+			println!(
+				"Imported {} key 0x{}",
+				String::from_utf8_lossy(&key_type.0),
+				HexDisplay::from(&public),
+			);
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportKeysCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
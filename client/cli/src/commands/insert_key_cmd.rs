@@ -0,0 +1,88 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{CryptoSchemeFlag, KeystoreParams, SharedParams};
+use crate::{CliConfiguration, CryptoScheme};
+use super::key::{open_keystore, parse_key_type};
+use super::utils::read_uri;
+use sc_service::Configuration;
+use sp_core::{crypto::KeyTypeId, ecdsa, ed25519, sr25519};
+use structopt::StructOpt;
+
+/// The `key insert` command used to add a key to the keystore of a node.
+///
+/// The key is written directly into the keystore, the node does not need to be running.
+#[derive(Debug, StructOpt)]
+pub struct InsertKeyCmd {
+	/// The secret key URI.
+	///
+	/// If the value is a file, the file content is used as URI. If not given, you will be
+	/// prompted for the URI.
+	#[structopt(long, value_name = "SURI")]
+	pub suri: Option<String>,
+
+	/// Key type, e.g. `babe` or `gran`.
+	#[structopt(long = "key-type", value_name = "KEY_TYPE", parse(try_from_str = parse_key_type))]
+	pub key_type: KeyTypeId,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl InsertKeyCmd {
+	/// Run the insert command
+	pub fn run(&self, config: Configuration) -> error::Result<()> {
+		let suri = read_uri(self.suri.as_ref())?;
+		let keystore = open_keystore(&config.keystore)?;
+		let keystore = keystore.read();
+
+		match self.crypto_scheme.scheme {
+			CryptoScheme::Ed25519 => {
+				keystore.insert_by_type::<ed25519::Pair>(self.key_type, &suri)?;
+			},
+			CryptoScheme::Sr25519 => {
+				keystore.insert_by_type::<sr25519::Pair>(self.key_type, &suri)?;
+			},
+			CryptoScheme::Ecdsa => {
+				keystore.insert_by_type::<ecdsa::Pair>(self.key_type, &suri)?;
+			},
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InsertKeyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
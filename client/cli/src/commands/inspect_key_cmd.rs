@@ -0,0 +1,69 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{CryptoSchemeFlag, NetworkSchemeFlag, OutputTypeFlag, SecretPasswordParams};
+use crate::CryptoScheme;
+use super::utils::{print_from_uri, read_uri};
+use sp_core::{ecdsa, ed25519, sr25519};
+use structopt::StructOpt;
+
+/// The `key inspect` command used to print the public parts of a key.
+#[derive(Debug, StructOpt)]
+pub struct InspectKeyCmd {
+	/// A key URI to be inspected. May be a secret seed, secret URI (with derivation paths and
+	/// password), SS58 or public URI.
+	///
+	/// If the value is a file, the file content is used as URI. If not given, you will be
+	/// prompted for the URI.
+	#[structopt(value_name = "URI")]
+	pub uri: Option<String>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub password_params: SecretPasswordParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub network_scheme: NetworkSchemeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub output_scheme: OutputTypeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}
+
+impl InspectKeyCmd {
+	/// Run the inspect command
+	pub fn run(&self) -> error::Result<()> {
+		let uri = read_uri(self.uri.as_ref())?;
+		let password = self.password_params.password()?;
+		let password = password.as_ref().map(String::as_str);
+		let network = self.network_scheme.network;
+		let output = self.output_scheme.output_type;
+
+		match self.crypto_scheme.scheme {
+			CryptoScheme::Ed25519 => print_from_uri::<ed25519::Pair>(&uri, password, network, output),
+			CryptoScheme::Sr25519 => print_from_uri::<sr25519::Pair>(&uri, password, network, output),
+			CryptoScheme::Ecdsa => print_from_uri::<ecdsa::Pair>(&uri, password, network, output),
+		}
+	}
+}
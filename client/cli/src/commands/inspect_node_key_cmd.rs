@@ -0,0 +1,44 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::{self, Error};
+use sc_network::config::identity::{ed25519, PublicKey};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// The `key inspect-node-key` command used to print the peer id of a node key.
+#[derive(Debug, StructOpt)]
+pub struct InspectNodeKeyCmd {
+	/// File to read the secret key from.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub file: PathBuf,
+}
+
+impl InspectNodeKeyCmd {
+	/// Run the inspect-node-key command
+	pub fn run(&self) -> error::Result<()> {
+		let mut file_content = fs::read(&self.file)?;
+		let secret = ed25519::SecretKey::from_bytes(&mut file_content)
+			.map_err(|_| Error::Input("Bad node key file".into()))?;
+		let keypair = ed25519::Keypair::from(secret);
+
+		println!("{}", PublicKey::Ed25519(keypair.public()).into_peer_id());
+
+		Ok(())
+	}
+}
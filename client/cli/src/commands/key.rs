@@ -0,0 +1,200 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Key management subcommands.
+
+use crate::error::{self, Error};
+use crate::SubstrateCli;
+use super::{
+	ChangeKeystorePasswordCmd, ExportKeysCmd, GenerateCmd, GenerateNodeKeyCmd, ImportKeysCmd,
+	InsertKeyCmd, InspectKeyCmd, InspectNodeKeyCmd, MigrateKeystoreCmd, SignCmd, VanityCmd,
+	VerifyCmd,
+};
+use sc_keystore::{KeyStorePtr, Store};
+use sc_service::config::KeystoreConfig;
+use sp_core::crypto::{KeyTypeId, Protected};
+use std::{convert::TryFrom, path::PathBuf};
+use structopt::StructOpt;
+
+/// Key management commands.
+#[derive(Debug, StructOpt)]
+pub enum KeySubcommand {
+	/// Generate a random key and print its secret phrase and public parts.
+	Generate(GenerateCmd),
+
+	/// Print the public parts of a secret phrase, secret URI or public URI.
+	Inspect(InspectKeyCmd),
+
+	/// Sign a message with a secret key.
+	Sign(SignCmd),
+
+	/// Verify the signature of a message with a public key.
+	Verify(VerifyCmd),
+
+	/// Generate a key whose address contains the given pattern.
+	Vanity(VanityCmd),
+
+	/// Generate a random node key, save it to a file and print its peer id.
+	GenerateNodeKey(GenerateNodeKeyCmd),
+
+	/// Print the peer id of the node key in the given file.
+	InspectNodeKey(InspectNodeKeyCmd),
+
+	/// Insert a key into the keystore of a node.
+	Insert(InsertKeyCmd),
+
+	/// Encrypt all key files of the keystore that are still stored in plaintext.
+	Migrate(MigrateKeystoreCmd),
+
+	/// Encrypt all key files of the keystore with a new password.
+	ChangePassword(ChangeKeystorePasswordCmd),
+
+	/// Export keys of the keystore into an encrypted backup.
+	Export(ExportKeysCmd),
+
+	/// Import keys from an encrypted backup into the keystore.
+	Import(ImportKeysCmd),
+}
+
+impl KeySubcommand {
+	/// Run the key subcommand
+	///
+	/// Only the commands that access the keystore of a node need its configuration.
+	pub fn run<C: SubstrateCli>(&self, cli: &C) -> error::Result<()> {
+		match self {
+			KeySubcommand::Generate(cmd) => cmd.run(),
+			KeySubcommand::Inspect(cmd) => cmd.run(),
+			KeySubcommand::Sign(cmd) => cmd.run(),
+			KeySubcommand::Verify(cmd) => cmd.run(),
+			KeySubcommand::Vanity(cmd) => cmd.run(),
+			KeySubcommand::GenerateNodeKey(cmd) => cmd.run(),
+			KeySubcommand::InspectNodeKey(cmd) => cmd.run(),
+			KeySubcommand::Insert(cmd) => cli.create_runner(cmd)?.sync_run(|config| cmd.run(config)),
+			KeySubcommand::Migrate(cmd) => cli.create_runner(cmd)?.sync_run(|config| cmd.run(config)),
+			KeySubcommand::ChangePassword(cmd) => {
+				cli.create_runner(cmd)?.sync_run(|config| cmd.run(config))
+			},
+			KeySubcommand::Export(cmd) => cli.create_runner(cmd)?.sync_run(|config| cmd.run(config)),
+			KeySubcommand::Import(cmd) => cli.create_runner(cmd)?.sync_run(|config| cmd.run(config)),
+		}
+	}
+}
+
+/// Open the keystore of the given configuration.
+///
+/// Fails if the keys are not stored on disk.
+pub(crate) fn open_keystore(config: &KeystoreConfig) -> error::Result<KeyStorePtr> {
+	match config {
+		KeystoreConfig::Path { path, password } => {
+			Ok(Store::open(path.clone(), password.clone())?)
+		},
+		KeystoreConfig::Encrypted { path, password } => {
+			Ok(Store::open_encrypted(path.clone(), password.clone())?)
+		},
+		KeystoreConfig::Remote { .. } | KeystoreConfig::InMemory => {
+			Err(Error::Input("Key management requires a keystore on disk".into()))
+		},
+	}
+}
+
+/// Read a password from the given value, file or an interactive prompt, in this order.
+///
+/// A single trailing line break of a password file is not part of the password.
+pub(crate) fn read_password(
+	value: &Option<String>,
+	file: &Option<PathBuf>,
+	prompt: &str,
+) -> error::Result<Protected<String>> {
+	if let Some(value) = value {
+		return Ok(value.clone().into())
+	}
+
+	if let Some(file) = file {
+		return Ok(crate::params::read_password_file(file)?.into())
+	}
+
+	#[cfg(not(target_os = "unknown"))]
+	{
+		rpassword::read_password_from_tty(Some(prompt))
+			.map(Into::into)
+			.map_err(|e| format!("{:?}", e).into())
+	}
+	#[cfg(target_os = "unknown")]
+	{
+		let _ = prompt;
+		Err(Error::Input("A password is required".into()))
+	}
+}
+
+/// Parse a four character key type, e.g. `babe`.
+pub(crate) fn parse_key_type(key_type: &str) -> Result<KeyTypeId, String> {
+	KeyTypeId::try_from(key_type)
+		.map_err(|_| format!("Invalid key type `{}`, expected four characters", key_type))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::KeystoreParams;
+	use sp_core::{sr25519, Pair};
+	use std::fs;
+
+	#[test]
+	fn read_password_trims_one_trailing_line_break() {
+		let dir = tempfile::tempdir().unwrap();
+		let read = |contents: &str| {
+			let file = dir.path().join("password");
+			fs::write(&file, contents).unwrap();
+			read_password(&None, &Some(file), "").unwrap().to_string()
+		};
+
+		assert_eq!(read("secret"), "secret");
+		assert_eq!(read("secret\n"), "secret");
+		assert_eq!(read("secret\r\n"), "secret");
+		assert_eq!(read("secret \n\n"), "secret \n");
+	}
+
+	#[test]
+	fn change_password_with_a_password_file_round_trips() {
+		let dir = tempfile::tempdir().unwrap();
+		let keystore_path = dir.path().join("keystore");
+		let password_file = dir.path().join("password");
+		fs::write(&password_file, "secret\n").unwrap();
+
+		let keystore = Store::open(keystore_path.clone(), None).unwrap();
+		let key_type = KeyTypeId(*b"test");
+		let pair = keystore.write().generate_by_type::<sr25519::Pair>(key_type).unwrap();
+		let password = read_password(&None, &Some(password_file.clone()), "").unwrap();
+		keystore.write().change_password(password).unwrap();
+		drop(keystore);
+
+		let params = KeystoreParams {
+			keystore_path: Some(keystore_path),
+			keystore_uri: None,
+			keystore_timeout: None,
+			password_interactive: false,
+			password: None,
+			password_filename: Some(password_file),
+			keystore_encrypted: true,
+		};
+		let config = params.keystore_config(&dir.path().to_path_buf()).unwrap();
+		let keystore = open_keystore(&config).unwrap();
+		let read = keystore.read().key_pair_by_type::<sr25519::Pair>(&pair.public(), key_type);
+		assert_eq!(read.unwrap().public(), pair.public());
+	}
+}
@@ -0,0 +1,57 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{KeystoreParams, SharedParams};
+use crate::CliConfiguration;
+use super::key::open_keystore;
+use sc_service::Configuration;
+use structopt::StructOpt;
+
+/// The `key migrate` command used to encrypt plaintext key files.
+#[derive(Debug, StructOpt)]
+pub struct MigrateKeystoreCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl MigrateKeystoreCmd {
+	/// Run the migrate command
+	pub fn run(&self, config: Configuration) -> error::Result<()> {
+		let keystore = open_keystore(&config.keystore)?;
+		let converted = keystore.write().encrypt_key_files()?;
+
+		println!("{} key file(s) encrypted.", converted);
+		Ok(())
+	}
+}
+
+impl CliConfiguration for MigrateKeystoreCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
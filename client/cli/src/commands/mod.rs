@@ -16,22 +16,51 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 mod build_spec_cmd;
+mod change_keystore_password_cmd;
 mod check_block_cmd;
 mod export_blocks_cmd;
+mod export_keys_cmd;
 mod export_state_cmd;
+mod generate_cmd;
+mod generate_node_key_cmd;
 mod import_blocks_cmd;
+mod import_keys_cmd;
+mod insert_key_cmd;
+mod inspect_key_cmd;
+mod inspect_node_key_cmd;
+mod key;
+mod migrate_keystore_cmd;
+mod precompile_wasm_cmd;
 mod purge_chain_cmd;
 mod revert_cmd;
 mod run_cmd;
+mod sign_cmd;
+pub mod utils;
+mod vanity_cmd;
+mod verify_cmd;
 
 pub use self::build_spec_cmd::BuildSpecCmd;
+pub use self::change_keystore_password_cmd::ChangeKeystorePasswordCmd;
 pub use self::check_block_cmd::CheckBlockCmd;
 pub use self::export_blocks_cmd::ExportBlocksCmd;
+pub use self::export_keys_cmd::ExportKeysCmd;
 pub use self::export_state_cmd::ExportStateCmd;
+pub use self::generate_cmd::GenerateCmd;
+pub use self::generate_node_key_cmd::GenerateNodeKeyCmd;
 pub use self::import_blocks_cmd::ImportBlocksCmd;
+pub use self::import_keys_cmd::ImportKeysCmd;
+pub use self::insert_key_cmd::InsertKeyCmd;
+pub use self::inspect_key_cmd::InspectKeyCmd;
+pub use self::inspect_node_key_cmd::InspectNodeKeyCmd;
+pub use self::key::KeySubcommand;
+pub use self::migrate_keystore_cmd::MigrateKeystoreCmd;
+pub use self::precompile_wasm_cmd::PrecompileWasmCmd;
 pub use self::purge_chain_cmd::PurgeChainCmd;
 pub use self::revert_cmd::RevertCmd;
 pub use self::run_cmd::RunCmd;
+pub use self::sign_cmd::SignCmd;
+pub use self::vanity_cmd::{generate_key, VanityCmd};
+pub use self::verify_cmd::VerifyCmd;
 use std::fmt::Debug;
 use structopt::StructOpt;
 
@@ -62,6 +91,9 @@ pub enum Subcommand {
 
 	/// Export state as raw chain spec.
 	ExportState(ExportStateCmd),
+
+	/// Compile a Wasm runtime ahead of time into the runtime cache.
+	PrecompileWasm(PrecompileWasmCmd),
 }
 
 // TODO: move to config.rs?
@@ -278,6 +310,25 @@ macro_rules! substrate_cli_subcommands {
 				}
 			}
 
+			fn wasm_runtime_cache(&self, base_path: &::sc_service::config::BasePath)
+			-> $crate::Result<::std::option::Option<::std::path::PathBuf>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.wasm_runtime_cache(base_path)),*
+				}
+			}
+
+			fn runtime_stack_height_limit(&self) -> $crate::Result<::std::option::Option<u32>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.runtime_stack_height_limit()),*
+				}
+			}
+
+			fn runtime_fuel_limit(&self) -> $crate::Result<::std::option::Option<u64>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.runtime_fuel_limit()),*
+				}
+			}
+
 			fn execution_strategies(&self, is_dev: bool, is_validator: bool)
 			-> $crate::Result<::sc_client_api::execution_extensions::ExecutionStrategies> {
 				match self {
@@ -403,6 +454,25 @@ macro_rules! substrate_cli_subcommands {
 				}
 			}
 
+			fn runtime_heap_limit(&self) -> $crate::Result<::std::option::Option<u32>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.runtime_heap_limit()),*
+				}
+			}
+
+			fn runtime_instances_memory(&self) -> $crate::Result<::std::option::Option<u64>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.runtime_instances_memory()),*
+				}
+			}
+
+			fn runtime_profiling(&self, base_path: &::sc_service::config::BasePath)
+			-> $crate::Result<::std::option::Option<::sc_service::config::RuntimeProfiling>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.runtime_profiling(base_path)),*
+				}
+			}
+
 			fn log_filters(&self) -> $crate::Result<String> {
 				match self {
 					$($enum::$variant(cmd) => cmd.log_filters()),*
@@ -413,5 +483,6 @@ macro_rules! substrate_cli_subcommands {
 }
 
 substrate_cli_subcommands!(
-	Subcommand => BuildSpec, ExportBlocks, ImportBlocks, CheckBlock, Revert, PurgeChain, ExportState
+	Subcommand => BuildSpec, ExportBlocks, ImportBlocks, CheckBlock, Revert, PurgeChain, ExportState,
+	PrecompileWasm
 );
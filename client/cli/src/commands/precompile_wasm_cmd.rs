@@ -0,0 +1,95 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{ImportParams, SharedParams};
+use crate::CliConfiguration;
+use sc_service::Configuration;
+use std::fs;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// The `precompile-wasm` command used to warm the runtime cache.
+///
+/// Compiling a runtime that is going to be enacted by an upcoming runtime upgrade ahead of time
+/// spares the node from compiling it when importing the enactment block.
+///
+/// The runtime is always compiled for the compiled execution method, regardless of the
+/// `--wasm-execution` of the command. `--wasm-stack-height-limit` and `--wasm-fuel-limit` have to
+/// match the ones of the node, otherwise it doesn't use the artifacts.
+#[derive(Debug, StructOpt)]
+pub struct PrecompileWasmCmd {
+	/// Path to the Wasm runtime blob.
+	#[structopt(parse(from_os_str))]
+	pub input: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+impl PrecompileWasmCmd {
+	/// Run the precompile-wasm command
+	pub fn run(&self, config: Configuration) -> error::Result<()> {
+		let cache = config.wasm_runtime_cache
+			.ok_or_else(|| error::Error::Input("The Wasm runtime cache is disabled".into()))?;
+		let code = fs::read(&self.input)?;
+
+		// The artifacts are only used by nodes that instrument the code the same way, so they are
+		// compiled with the limits the node is configured with.
+		sc_executor::precompile_wasm_runtime(
+			compiled_wasm_method()?,
+			&code,
+			&cache,
+			config.runtime_fuel_limit.is_some(),
+			config.runtime_stack_height_limit,
+		)
+			.map_err(|e| error::Error::Other(format!("Failed to precompile {:?}: {}", self.input, e)))?;
+
+		println!("{:?} compiled into {:?}", self.input, cache);
+		Ok(())
+	}
+}
+
+/// The execution method the artifacts are used by.
+fn compiled_wasm_method() -> error::Result<sc_executor::WasmExecutionMethod> {
+	#[cfg(feature = "wasmtime")]
+	{
+		Ok(sc_executor::WasmExecutionMethod::Compiled)
+	}
+	#[cfg(not(feature = "wasmtime"))]
+	{
+		Err(error::Error::Input(
+			"Substrate must be compiled with \"wasmtime\" feature to precompile runtimes".into(),
+		))
+	}
+}
+
+impl CliConfiguration for PrecompileWasmCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
@@ -27,11 +27,14 @@ use crate::params::TransactionPoolParams;
 use crate::CliConfiguration;
 use regex::Regex;
 use sc_service::{
-	config::{BasePath, MultiaddrWithPeerId, PrometheusConfig, TransactionPoolOptions},
+	config::{
+		BasePath, MultiaddrWithPeerId, PrometheusConfig, RuntimeProfiling, TransactionPoolOptions,
+	},
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// The `run` command used to run a node.
@@ -244,6 +247,35 @@ pub struct RunCmd {
 	#[structopt(long)]
 	pub max_runtime_instances: Option<usize>,
 
+	/// Limit the heap a single RPC or offchain worker runtime call can allocate at the same time,
+	/// in MiB.
+	///
+	/// Calls exceeding the limit fail. Other runtime calls, including block import and authoring,
+	/// are never limited. Values higher than 4095 are capped.
+	#[structopt(long, value_name = "MiB")]
+	pub runtime_heap_limit: Option<u32>,
+
+	/// Limit the linear memory of all cached runtime instances, in MiB.
+	///
+	/// Instances that don't fit into the limit are dropped after their call.
+	#[structopt(long, value_name = "MiB")]
+	pub runtime_instances_memory: Option<u64>,
+
+	/// Profile the calls of the given runtime methods, or of all methods if none is given.
+	///
+	/// The time spent in each function of the runtime is written in the folded stack format to
+	/// the `--runtime-profile-dir`. Only compiled runtimes can be profiled, so this requires
+	/// `--wasm-execution Compiled` and an execution strategy that executes the Wasm runtime.
+	/// Profiling slows down all runtime calls.
+	#[structopt(long, value_name = "METHOD")]
+	pub profile_runtime: Option<Vec<String>>,
+
+	/// Directory to write runtime profiles to.
+	///
+	/// Defaults to `runtime-profiles` in the base path.
+	#[structopt(long, value_name = "PATH", parse(from_os_str), requires = "profile-runtime")]
+	pub runtime_profile_dir: Option<PathBuf>,
+
 	/// Specify a list of sentry node public addresses.
 	///
 	/// Can't be used with --public-addr as the sentry node would take precedence over the public address
@@ -465,6 +497,22 @@ impl CliConfiguration for RunCmd {
 		Ok(self.max_runtime_instances.map(|x| x.min(256)))
 	}
 
+	fn runtime_heap_limit(&self) -> Result<Option<u32>> {
+		Ok(self.runtime_heap_limit.map(|x| x.min(4095) * 1024 * 1024))
+	}
+
+	fn runtime_instances_memory(&self) -> Result<Option<u64>> {
+		Ok(self.runtime_instances_memory.map(|x| x.saturating_mul(1024 * 1024)))
+	}
+
+	fn runtime_profiling(&self, base_path: &BasePath) -> Result<Option<RuntimeProfiling>> {
+		Ok(self.profile_runtime.clone().map(|methods| RuntimeProfiling {
+			methods,
+			output_dir: self.runtime_profile_dir.clone()
+				.unwrap_or_else(|| base_path.path().join(crate::config::DEFAULT_RUNTIME_PROFILE_PATH)),
+		}))
+	}
+
 	fn base_path(&self) -> Result<Option<BasePath>> {
 		Ok(if self.tmp {
 			Some(BasePath::new_temp_dir()?)
@@ -0,0 +1,70 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{CryptoSchemeFlag, SecretPasswordParams};
+use crate::CryptoScheme;
+use super::utils::{read_message, read_uri, sign};
+use sp_core::{ecdsa, ed25519, sr25519};
+use structopt::StructOpt;
+
+/// The `key sign` command used to sign a message.
+#[derive(Debug, StructOpt)]
+pub struct SignCmd {
+	/// The secret key URI.
+	///
+	/// If the value is a file, the file content is used as URI. If not given, you will be
+	/// prompted for the URI.
+	#[structopt(long, value_name = "SURI")]
+	pub suri: Option<String>,
+
+	/// The message to sign, read from stdin if not given.
+	#[structopt(long, value_name = "MESSAGE")]
+	pub message: Option<String>,
+
+	/// The message is hex-encoded data.
+	#[structopt(long)]
+	pub hex: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub password_params: SecretPasswordParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}
+
+impl SignCmd {
+	/// Run the sign command
+	pub fn run(&self) -> error::Result<()> {
+		let suri = read_uri(self.suri.as_ref())?;
+		let message = read_message(self.message.as_ref(), self.hex)?;
+		let password = self.password_params.password()?;
+		let password = password.as_ref().map(String::as_str);
+
+		let signature = match self.crypto_scheme.scheme {
+			CryptoScheme::Ed25519 => sign::<ed25519::Pair>(&suri, password, &message)?,
+			CryptoScheme::Sr25519 => sign::<sr25519::Pair>(&suri, password, &message)?,
+			CryptoScheme::Ecdsa => sign::<ecdsa::Pair>(&suri, password, &message)?,
+		};
+		println!("{}", signature);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,305 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Helpers shared by the key commands.
+
+use crate::arg_enums::OutputType;
+use crate::error::{self, Error};
+use serde_json::json;
+use sp_core::{
+	crypto::{Ss58AddressFormat, Ss58Codec},
+	hexdisplay::HexDisplay,
+	Pair, Public,
+};
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use std::{fs, io::Read, path::PathBuf};
+
+/// Public key type of the pair `P`.
+pub type PublicFor<P> = <P as Pair>::Public;
+/// Seed type of the pair `P`.
+pub type SeedFor<P> = <P as Pair>::Seed;
+/// Signature type of the pair `P`.
+pub type SignatureFor<P> = <P as Pair>::Signature;
+
+/// Read a URI from the given argument or from an interactive prompt.
+///
+/// If the argument is a file, the file content is used as URI.
+pub fn read_uri(uri: Option<&String>) -> error::Result<String> {
+	match uri {
+		Some(uri) => {
+			let file = PathBuf::from(uri);
+			if file.is_file() {
+				Ok(fs::read_to_string(file)?.trim_end().into())
+			} else {
+				Ok(uri.clone())
+			}
+		},
+		#[cfg(not(target_os = "unknown"))]
+		None => rpassword::read_password_from_tty(Some("URI: ")).map_err(Into::into),
+		#[cfg(target_os = "unknown")]
+		None => Err(Error::Input("A URI is required".into())),
+	}
+}
+
+/// Read a message from the given argument or from stdin.
+///
+/// The message is hex decoded if `should_decode` is set.
+pub fn read_message(message: Option<&String>, should_decode: bool) -> error::Result<Vec<u8>> {
+	let message = match message {
+		Some(message) => message.as_bytes().to_vec(),
+		None => {
+			let mut message = vec![];
+			std::io::stdin().lock().read_to_end(&mut message)?;
+			message
+		},
+	};
+
+	if should_decode {
+		decode_hex(&message)
+	} else {
+		Ok(message)
+	}
+}
+
+/// Decode the given hex string, with or without `0x` prefix.
+pub fn decode_hex<T: AsRef<[u8]>>(message: T) -> error::Result<Vec<u8>> {
+	let mut message = message.as_ref();
+	if message.starts_with(b"0x") {
+		message = &message[2..];
+	}
+	hex::decode(message).map_err(|e| Error::Input(format!("Invalid hex ({})", e)))
+}
+
+/// Print the keys derived from the given secret phrase, secret URI or public URI.
+pub fn print_from_uri<P: Pair>(
+	uri: &str,
+	password: Option<&str>,
+	network_override: Option<Ss58AddressFormat>,
+	output: OutputType,
+) -> error::Result<()>
+where
+	PublicFor<P>: Into<MultiSigner>,
+{
+	let network = network_override.unwrap_or_default();
+
+	if let Ok((pair, seed)) = P::from_phrase(uri, password) {
+		let public_key = pair.public();
+
+		match output {
+			OutputType::Json => {
+				let json = json!({
+					"secretPhrase": uri,
+					"networkId": String::from(network),
+					"secretSeed": format_seed::<P>(seed),
+					"publicKey": format_public_key::<P>(public_key.clone()),
+					"accountId": format_account_id::<P>(public_key.clone()),
+					"ss58Address": format_ss58::<P>(public_key, network),
+				});
+				println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
+			},
+			OutputType::Text => {
+				println!("Secret phrase `{}` is account:\n  \
+					Network ID/version: {}\n  \
+					Secret seed:        {}\n  \
+					Public key (hex):   {}\n  \
+					Account ID:         {}\n  \
+					SS58 Address:       {}",
+					uri,
+					String::from(network),
+					format_seed::<P>(seed),
+					format_public_key::<P>(public_key.clone()),
+					format_account_id::<P>(public_key.clone()),
+					format_ss58::<P>(public_key, network),
+				);
+			},
+		}
+	} else if let Ok((pair, seed)) = P::from_string_with_seed(uri, password) {
+		let public_key = pair.public();
+		let seed = seed.map(format_seed::<P>).unwrap_or_else(|| "n/a".into());
+
+		match output {
+			OutputType::Json => {
+				let json = json!({
+					"secretKeyUri": uri,
+					"networkId": String::from(network),
+					"secretSeed": seed,
+					"publicKey": format_public_key::<P>(public_key.clone()),
+					"accountId": format_account_id::<P>(public_key.clone()),
+					"ss58Address": format_ss58::<P>(public_key, network),
+				});
+				println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
+			},
+			OutputType::Text => {
+				println!("Secret Key URI `{}` is account:\n  \
+					Network ID/version: {}\n  \
+					Secret seed:        {}\n  \
+					Public key (hex):   {}\n  \
+					Account ID:         {}\n  \
+					SS58 Address:       {}",
+					uri,
+					String::from(network),
+					seed,
+					format_public_key::<P>(public_key.clone()),
+					format_account_id::<P>(public_key.clone()),
+					format_ss58::<P>(public_key, network),
+				);
+			},
+		}
+	} else if let Ok((public_key, network)) = PublicFor::<P>::from_string_with_version(uri) {
+		let network = network_override.unwrap_or(network);
+
+		match output {
+			OutputType::Json => {
+				let json = json!({
+					"publicKeyUri": uri,
+					"networkId": String::from(network),
+					"publicKey": format_public_key::<P>(public_key.clone()),
+					"accountId": format_account_id::<P>(public_key.clone()),
+					"ss58Address": format_ss58::<P>(public_key, network),
+				});
+				println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
+			},
+			OutputType::Text => {
+				println!("Public Key URI `{}` is account:\n  \
+					Network ID/version: {}\n  \
+					Public key (hex):   {}\n  \
+					Account ID:         {}\n  \
+					SS58 Address:       {}",
+					uri,
+					String::from(network),
+					format_public_key::<P>(public_key.clone()),
+					format_account_id::<P>(public_key.clone()),
+					format_ss58::<P>(public_key, network),
+				);
+			},
+		}
+	} else {
+		return Err(Error::Input("Invalid phrase/URI given".into()))
+	}
+
+	Ok(())
+}
+
+/// Sign the message with the key pair of the given secret URI.
+///
+/// Returns the hex encoded signature.
+pub fn sign<P: Pair>(suri: &str, password: Option<&str>, message: &[u8]) -> error::Result<String> {
+	let pair = pair_from_suri::<P>(suri, password)?;
+	Ok(format_signature::<P>(&pair.sign(message)))
+}
+
+/// Verify the hex encoded signature of the message with the given public URI or hex public key.
+pub fn verify<P: Pair>(signature: &str, message: &[u8], uri: &str) -> error::Result<bool>
+where
+	SignatureFor<P>: AsMut<[u8]> + Default,
+{
+	let mut sig = SignatureFor::<P>::default();
+	let sig_data = decode_hex(signature)?;
+	if sig_data.len() != sig.as_ref().len() {
+		return Err(Error::Input(format!(
+			"Signature has an invalid length. read {} bytes, expected {} bytes",
+			sig_data.len(),
+			sig.as_ref().len(),
+		)))
+	}
+	sig.as_mut().copy_from_slice(&sig_data);
+
+	let public = match decode_hex(uri) {
+		Ok(public) => PublicFor::<P>::from_slice(&public),
+		Err(_) => PublicFor::<P>::from_string(uri)
+			.map_err(|e| Error::Input(format!("Invalid public URI: {:?}", e)))?,
+	};
+
+	Ok(P::verify(&sig, message, &public))
+}
+
+/// Create the key pair of the given secret URI.
+pub fn pair_from_suri<P: Pair>(suri: &str, password: Option<&str>) -> error::Result<P> {
+	P::from_string(suri, password)
+		.map_err(|e| Error::Input(format!("Invalid secret URI: {:?}", e)))
+}
+
+/// Format the seed as hex string.
+pub fn format_seed<P: Pair>(seed: SeedFor<P>) -> String {
+	format!("0x{}", HexDisplay::from(&seed.as_ref()))
+}
+
+/// Format the public key as hex string.
+pub fn format_public_key<P: Pair>(public_key: PublicFor<P>) -> String {
+	format!("0x{}", HexDisplay::from(&public_key.as_ref()))
+}
+
+/// Format the account id of the public key as hex string.
+pub fn format_account_id<P: Pair>(public_key: PublicFor<P>) -> String
+where
+	PublicFor<P>: Into<MultiSigner>,
+{
+	let account = Into::<MultiSigner>::into(public_key).into_account();
+	let account: &[u8] = account.as_ref();
+	format!("0x{}", HexDisplay::from(&account))
+}
+
+/// Format the account id of the public key as SS58 address of the given network.
+pub fn format_ss58<P: Pair>(public_key: PublicFor<P>, network: Ss58AddressFormat) -> String
+where
+	PublicFor<P>: Into<MultiSigner>,
+{
+	Into::<MultiSigner>::into(public_key).into_account().to_ss58check_with_version(network)
+}
+
+/// Format the signature as hex string.
+pub fn format_signature<P: Pair>(signature: &SignatureFor<P>) -> String {
+	format!("{}", HexDisplay::from(&signature.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{ecdsa, ed25519, sr25519};
+
+	fn sign_and_verify<P: Pair>()
+	where
+		SignatureFor<P>: AsMut<[u8]> + Default,
+	{
+		let (pair, seed) = P::generate();
+		let seed = format_seed::<P>(seed);
+		let public = format_public_key::<P>(pair.public());
+
+		let signature = sign::<P>(&seed, None, b"Blah Blah").unwrap();
+
+		assert!(verify::<P>(&signature, b"Blah Blah", &public).unwrap());
+		assert!(!verify::<P>(&signature, b"Blah", &public).unwrap());
+		assert!(verify::<P>("0x00", b"Blah Blah", &public).is_err());
+	}
+
+	#[test]
+	fn sign_and_verify_works_for_all_schemes() {
+		sign_and_verify::<ed25519::Pair>();
+		sign_and_verify::<sr25519::Pair>();
+		sign_and_verify::<ecdsa::Pair>();
+	}
+
+	#[test]
+	fn public_uri_prints_account() {
+		let public = sr25519::Pair::from_string("//Alice", None).unwrap().public();
+		let uri = format_ss58::<sr25519::Pair>(public, Default::default());
+
+		assert!(print_from_uri::<sr25519::Pair>(&uri, None, None, OutputType::Json).is_ok());
+		assert!(print_from_uri::<sr25519::Pair>("invalid", None, None, OutputType::Text).is_err());
+	}
+}
@@ -0,0 +1,242 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error;
+use crate::params::{CryptoSchemeFlag, NetworkSchemeFlag, OutputTypeFlag};
+use crate::CryptoScheme;
+use super::utils::{format_seed, format_ss58, print_from_uri, PublicFor};
+use rand::{rngs::OsRng, RngCore};
+use sp_core::{crypto::Ss58AddressFormat, ecdsa, ed25519, sr25519, Pair};
+use sp_runtime::MultiSigner;
+use structopt::StructOpt;
+
+/// The `key vanity` command used to generate a key with a vanity address.
+#[derive(Debug, StructOpt)]
+pub struct VanityCmd {
+	/// Desired pattern of the address.
+	#[structopt(long, value_name = "PATTERN", parse(try_from_str = assert_non_empty_string))]
+	pub pattern: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub network_scheme: NetworkSchemeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub output_scheme: OutputTypeFlag,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}
+
+impl VanityCmd {
+	/// Run the vanity command
+	pub fn run(&self) -> error::Result<()> {
+		let network = self.network_scheme.network;
+		let output = self.output_scheme.output_type;
+
+		match self.crypto_scheme.scheme {
+			CryptoScheme::Ed25519 => {
+				let seed = generate_key::<ed25519::Pair>(&self.pattern, network)?;
+				print_from_uri::<ed25519::Pair>(&seed, None, network, output)
+			},
+			CryptoScheme::Sr25519 => {
+				let seed = generate_key::<sr25519::Pair>(&self.pattern, network)?;
+				print_from_uri::<sr25519::Pair>(&seed, None, network, output)
+			},
+			CryptoScheme::Ecdsa => {
+				let seed = generate_key::<ecdsa::Pair>(&self.pattern, network)?;
+				print_from_uri::<ecdsa::Pair>(&seed, None, network, output)
+			},
+		}
+	}
+}
+
+fn assert_non_empty_string(pattern: &str) -> Result<String, &'static str> {
+	if pattern.is_empty() {
+		Err("Pattern must not be empty")
+	} else {
+		Ok(pattern.to_string())
+	}
+}
+
+fn good_waypoint(done: u64) -> u64 {
+	match done {
+		0..=1_000_000 => 100_000,
+		0..=10_000_000 => 1_000_000,
+		0..=100_000_000 => 10_000_000,
+		_ => 100_000_000,
+	}
+}
+
+fn next_seed(seed: &mut [u8]) {
+	for i in 0..seed.len() {
+		match seed[i] {
+			255 => {
+				seed[i] = 0;
+			}
+			_ => {
+				seed[i] += 1;
+				break;
+			}
+		}
+	}
+}
+
+/// Calculate the score of a key based on the desired
+/// input.
+fn calculate_score(_desired: &str, key: &str) -> usize {
+	for truncate in 0.._desired.len() {
+		let snip_size = _desired.len() - truncate;
+		let truncated = &_desired[0..snip_size];
+		if let Some(pos) = key.find(truncated) {
+			return (47 - pos) + (snip_size * 48);
+		}
+	}
+	0
+}
+
+/// Validate whether the char is allowed to be used in base58.
+/// num 0, lower l, upper I and O are not allowed.
+fn validate_base58(c: char) -> bool {
+	c.is_alphanumeric() && !"0lIO".contains(c)
+}
+
+/// Search for a key whose SS58 address contains the desired pattern.
+///
+/// Returns the hex encoded seed of the key.
+pub fn generate_key<P: Pair>(
+	desired: &str,
+	network: Option<Ss58AddressFormat>,
+) -> Result<String, &'static str>
+where
+	PublicFor<P>: Into<MultiSigner>,
+{
+	if desired.is_empty() {
+		return Err("Pattern must not be empty");
+	}
+
+	if !desired.chars().all(validate_base58) {
+		return Err("Pattern can only contains valid characters in base58 \
+			(all alphanumeric except for 0, l, I and O)");
+	}
+
+	eprintln!("Generating key containing pattern '{}'", desired);
+
+	let network = network.unwrap_or_default();
+	let top = 45 + (desired.len() * 48);
+	let mut best = 0;
+	let mut seed = P::Seed::default();
+	let mut done = 0;
+
+	loop {
+		if done % 100000 == 0 {
+			OsRng.fill_bytes(seed.as_mut());
+		} else {
+			next_seed(seed.as_mut());
+		}
+
+		let p = P::from_seed(&seed);
+		let ss58 = format_ss58::<P>(p.public(), network);
+		let score = calculate_score(&desired, &ss58);
+		if score > best || desired.len() < 2 {
+			best = score;
+			if best >= top {
+				eprintln!("best: {} == top: {}", best, top);
+				return Ok(format_seed::<P>(seed.clone()));
+			}
+		}
+		done += 1;
+
+		if done % good_waypoint(done) == 0 {
+			eprintln!("{} keys searched; best is {}/{} complete", done, best, top);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::crypto::Ss58Codec;
+
+	#[test]
+	fn generation_with_single_char() {
+		let seed = generate_key::<sr25519::Pair>("j", None).unwrap();
+		let seed = crate::utils::decode_hex(&seed).unwrap();
+		assert!(sr25519::Pair::from_seed_slice(&seed)
+			.unwrap()
+			.public()
+			.to_ss58check()
+			.contains("j"));
+	}
+
+	#[test]
+	fn score_1_char_100() {
+		let score = calculate_score("j", "5jolkadotwHY5k9GpdTgpqs9xjuNvtv8EcwCFpEeyEf3KHim");
+		assert_eq!(score, 94);
+	}
+
+	#[test]
+	fn score_100() {
+		let score = calculate_score(
+			"Polkadot",
+			"5PolkadotwHY5k9GpdTgpqs9xjuNvtv8EcwCFpEeyEf3KHim",
+		);
+		assert_eq!(score, 430);
+	}
+
+	#[test]
+	fn score_50_2() {
+		// 50% for the position + 50% for the size
+		assert_eq!(
+			calculate_score(
+				"Polkadot",
+				"5PolkXXXXwHY5k9GpdTgpqs9xjuNvtv8EcwCFpEeyEf3KHim"
+			),
+			238
+		);
+	}
+
+	#[test]
+	fn score_0() {
+		assert_eq!(
+			calculate_score(
+				"Polkadot",
+				"5GUWv4bLCchGUHJrzULXnh4JgXsMpTKRnjuXTY7Qo1Kh9uYK"
+			),
+			0
+		);
+	}
+
+	#[test]
+	fn invalid_pattern() {
+		assert!(generate_key::<ed25519::Pair>("", None).is_err());
+		assert!(generate_key::<ed25519::Pair>("0", None).is_err());
+		assert!(generate_key::<ed25519::Pair>("l", None).is_err());
+		assert!(generate_key::<ed25519::Pair>("I", None).is_err());
+		assert!(generate_key::<ed25519::Pair>("O", None).is_err());
+		assert!(generate_key::<ed25519::Pair>("!", None).is_err());
+	}
+
+	#[test]
+	fn valid_pattern() {
+		assert!(generate_key::<ed25519::Pair>("o", None).is_ok());
+		assert!(generate_key::<ed25519::Pair>("L", None).is_ok());
+	}
+}
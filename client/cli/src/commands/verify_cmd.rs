@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::error::{self, Error};
+use crate::params::CryptoSchemeFlag;
+use crate::CryptoScheme;
+use super::utils::{read_message, read_uri, verify};
+use sp_core::{ecdsa, ed25519, sr25519};
+use structopt::StructOpt;
+
+/// The `key verify` command used to verify a signature.
+#[derive(Debug, StructOpt)]
+pub struct VerifyCmd {
+	/// Signature, hex-encoded.
+	#[structopt(value_name = "SIGNATURE")]
+	pub sig: String,
+
+	/// The public key URI or the hex-encoded public key.
+	///
+	/// If the value is a file, the file content is used as URI. If not given, you will be
+	/// prompted for the URI.
+	#[structopt(value_name = "URI")]
+	pub uri: Option<String>,
+
+	/// The signed message, read from stdin if not given.
+	#[structopt(long, value_name = "MESSAGE")]
+	pub message: Option<String>,
+
+	/// The message is hex-encoded data.
+	#[structopt(long)]
+	pub hex: bool,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub crypto_scheme: CryptoSchemeFlag,
+}
+
+impl VerifyCmd {
+	/// Run the verify command
+	pub fn run(&self) -> error::Result<()> {
+		let uri = read_uri(self.uri.as_ref())?;
+		let message = read_message(self.message.as_ref(), self.hex)?;
+
+		let is_valid = match self.crypto_scheme.scheme {
+			CryptoScheme::Ed25519 => verify::<ed25519::Pair>(&self.sig, &message, &uri)?,
+			CryptoScheme::Sr25519 => verify::<sr25519::Pair>(&self.sig, &message, &uri)?,
+			CryptoScheme::Ecdsa => verify::<ecdsa::Pair>(&self.sig, &message, &uri)?,
+		};
+
+		if is_valid {
+			println!("Signature verifies correctly.");
+			Ok(())
+		} else {
+			Err(Error::Other("Signature invalid.".into()))
+		}
+	}
+}
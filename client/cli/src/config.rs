@@ -29,7 +29,8 @@ use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::config::{
 	BasePath, Configuration, DatabaseConfig, ExtTransport, KeystoreConfig, NetworkConfiguration,
 	NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
-	TaskExecutor, TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
+	RuntimeProfiling, TaskExecutor, TelemetryEndpoints, TransactionPoolOptions,
+	WasmExecutionMethod,
 };
 use sc_service::{ChainSpec, TracingReceiver};
 use std::net::SocketAddr;
@@ -41,6 +42,12 @@ pub(crate) const NODE_NAME_MAX_LENGTH: usize = 64;
 /// default sub directory to store network config
 pub(crate) const DEFAULT_NETWORK_CONFIG_PATH: &'static str = "network";
 
+/// default sub directory of the base path to cache compiled Wasm runtimes in
+pub(crate) const DEFAULT_WASM_RUNTIME_CACHE_PATH: &'static str = "wasm-cache";
+
+/// default sub directory of the base path to write runtime profiles to
+pub(crate) const DEFAULT_RUNTIME_PROFILE_PATH: &'static str = "runtime-profiles";
+
 /// A trait that allows converting an object to a Configuration
 pub trait CliConfiguration: Sized {
 	/// Get the SharedParams for this object
@@ -239,6 +246,37 @@ pub trait CliConfiguration: Sized {
 			.unwrap_or(Default::default()))
 	}
 
+	/// Get the directory to cache compiled Wasm runtimes in.
+	///
+	/// By default this is a directory in the `base_path`, unless the cache is disabled in
+	/// `ImportParams`.
+	fn wasm_runtime_cache(&self, base_path: &BasePath) -> Result<Option<PathBuf>> {
+		let disabled = self.import_params().map(|x| x.disable_wasm_cache).unwrap_or(false);
+		Ok(if disabled {
+			None
+		} else {
+			Some(base_path.path().join(DEFAULT_WASM_RUNTIME_CACHE_PATH))
+		})
+	}
+
+	/// Get the limit of the value stack height of runtime calls.
+	///
+	/// By default this is retrieved from `ImportParams` if it is available. Otherwise it is
+	/// `DEFAULT_STACK_HEIGHT_LIMIT`.
+	fn runtime_stack_height_limit(&self) -> Result<Option<u32>> {
+		Ok(self.import_params()
+			.map(|x| x.wasm_stack_height_limit())
+			.unwrap_or(Some(sc_executor::DEFAULT_STACK_HEIGHT_LIMIT)))
+	}
+
+	/// Get the maximal number of instructions a runtime call can execute.
+	///
+	/// By default this is retrieved from `ImportParams` if it is available. Otherwise it is
+	/// `None`, i.e. calls are not metered.
+	fn runtime_fuel_limit(&self) -> Result<Option<u64>> {
+		Ok(self.import_params().and_then(|x| x.wasm_fuel_limit()))
+	}
+
 	/// Get the execution strategies.
 	///
 	/// By default this is retrieved from `ImportParams` if it is available. Otherwise its
@@ -395,6 +433,27 @@ pub trait CliConfiguration: Sized {
 		Ok(Default::default())
 	}
 
+	/// Get the number of bytes an RPC or offchain runtime call can allocate at the same time
+	///
+	/// By default this is `None`, i.e. unlimited.
+	fn runtime_heap_limit(&self) -> Result<Option<u32>> {
+		Ok(Default::default())
+	}
+
+	/// Get the number of bytes of linear memory the cached runtime instances can use
+	///
+	/// By default this is `None`, i.e. unlimited.
+	fn runtime_instances_memory(&self) -> Result<Option<u64>> {
+		Ok(Default::default())
+	}
+
+	/// Get the runtime calls to profile
+	///
+	/// By default this is `None`, i.e. no calls are profiled.
+	fn runtime_profiling(&self, _base_path: &BasePath) -> Result<Option<RuntimeProfiling>> {
+		Ok(Default::default())
+	}
+
 	/// Activate or not the automatic announcing of blocks after import
 	///
 	/// By default this is `false`.
@@ -426,6 +485,8 @@ pub trait CliConfiguration: Sized {
 		let node_key = self.node_key(&net_config_dir)?;
 		let role = self.role(is_dev)?;
 		let max_runtime_instances = self.max_runtime_instances()?.unwrap_or(8);
+		let wasm_runtime_cache = self.wasm_runtime_cache(&base_path)?;
+		let runtime_profiling = self.runtime_profiling(&base_path)?;
 		let is_validator = role.is_network_authority();
 
 		let unsafe_pruning = self
@@ -471,6 +532,12 @@ pub trait CliConfiguration: Sized {
 			tracing_receiver: self.tracing_receiver()?,
 			chain_spec,
 			max_runtime_instances,
+			runtime_heap_limit: self.runtime_heap_limit()?,
+			runtime_stack_height_limit: self.runtime_stack_height_limit()?,
+			runtime_fuel_limit: self.runtime_fuel_limit()?,
+			runtime_instances_memory: self.runtime_instances_memory()?,
+			runtime_profiling,
+			wasm_runtime_cache,
 			announce_block: self.announce_block()?,
 			role,
 			base_path: Some(base_path),
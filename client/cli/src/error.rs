@@ -32,6 +32,8 @@ pub enum Error {
 	Service(sc_service::Error),
 	/// Client error
 	Client(sp_blockchain::Error),
+	/// Keystore error
+	Keystore(sc_keystore::Error),
 	/// Input error
 	#[from(ignore)]
 	Input(String),
@@ -65,6 +67,7 @@ impl std::error::Error for Error {
 			Error::Cli(ref err) => Some(err),
 			Error::Service(ref err) => Some(err),
 			Error::Client(ref err) => Some(err),
+			Error::Keystore(ref err) => Some(err),
 			Error::Input(_) => None,
 			Error::InvalidListenMultiaddress => None,
 			Error::Other(_) => None,
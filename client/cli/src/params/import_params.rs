@@ -55,6 +55,28 @@ pub struct ImportParams {
 	)]
 	pub wasm_method: WasmExecutionMethod,
 
+	/// Disable the on-disk cache of compiled Wasm runtimes.
+	///
+	/// Without the cache, runtimes executed with `--wasm-execution Compiled` are compiled again
+	/// on every start.
+	#[structopt(long = "disable-wasm-cache")]
+	pub disable_wasm_cache: bool,
+
+	/// Maximal height of the value stack a runtime call can use.
+	///
+	/// The runtime code is instrumented to trap once a call exceeds it, so that deep calls fail at
+	/// the same point on every node. All nodes of a network have to use the same limit. `0`
+	/// disables the instrumentation.
+	#[structopt(long = "wasm-stack-height-limit", value_name = "HEIGHT", default_value = "65536")]
+	pub wasm_stack_height_limit: u32,
+
+	/// Maximal number of instructions a runtime call executed with `--wasm-execution Compiled`
+	/// can execute.
+	///
+	/// Calls are not metered by default. Metering slows down all calls.
+	#[structopt(long = "wasm-fuel-limit", value_name = "INSTRUCTIONS")]
+	pub wasm_fuel_limit: Option<u64>,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub execution_strategies: ExecutionStrategiesParams,
@@ -103,6 +125,16 @@ impl ImportParams {
 		self.wasm_method.into()
 	}
 
+	/// Get the limit of the value stack height of runtime calls, if any.
+	pub fn wasm_stack_height_limit(&self) -> Option<u32> {
+		Some(self.wasm_stack_height_limit).filter(|limit| *limit != 0)
+	}
+
+	/// Get the maximal number of instructions a runtime call can execute, if calls are metered.
+	pub fn wasm_fuel_limit(&self) -> Option<u64> {
+		self.wasm_fuel_limit
+	}
+
 	/// Get execution strategies for the parameters
 	pub fn execution_strategies(&self, is_dev: bool, is_validator: bool) -> ExecutionStrategies {
 		let exec = &self.execution_strategies;
@@ -200,3 +232,18 @@ pub struct ExecutionStrategiesParams {
 	)]
 	pub execution: Option<ExecutionStrategy>,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn stack_height_is_limited_by_default() {
+		let params = ImportParams::from_iter(&["test"]);
+		assert_eq!(params.wasm_stack_height_limit(), Some(sc_executor::DEFAULT_STACK_HEIGHT_LIMIT));
+		assert_eq!(params.wasm_fuel_limit(), None);
+
+		let params = ImportParams::from_iter(&["test", "--wasm-stack-height-limit", "0"]);
+		assert_eq!(params.wasm_stack_height_limit(), None);
+	}
+}
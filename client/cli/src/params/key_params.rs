@@ -0,0 +1,95 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::arg_enums::{CryptoScheme, OutputType};
+use crate::error::Result;
+use sp_core::crypto::Ss58AddressFormat;
+use std::convert::TryFrom;
+use structopt::StructOpt;
+
+/// Parameters to select the cryptography scheme of a key.
+#[derive(Debug, StructOpt)]
+pub struct CryptoSchemeFlag {
+	/// Cryptography scheme of the key.
+	#[structopt(
+		long,
+		value_name = "SCHEME",
+		possible_values = &CryptoScheme::variants(),
+		case_insensitive = true,
+		default_value = "Sr25519"
+	)]
+	pub scheme: CryptoScheme,
+}
+
+/// Parameters to select the output format of the key commands.
+#[derive(Debug, StructOpt)]
+pub struct OutputTypeFlag {
+	/// Output format.
+	#[structopt(
+		long,
+		value_name = "FORMAT",
+		possible_values = &OutputType::variants(),
+		case_insensitive = true,
+		default_value = "Text"
+	)]
+	pub output_type: OutputType,
+}
+
+/// Parameters to select the network an address is formatted for.
+#[derive(Debug, StructOpt)]
+pub struct NetworkSchemeFlag {
+	/// Network the SS58 address is formatted for, e.g. `polkadot`, `kusama` or a number.
+	#[structopt(short = "n", long, value_name = "NETWORK", parse(try_from_str = parse_ss58_format))]
+	pub network: Option<Ss58AddressFormat>,
+}
+
+/// Parameters of the password of a secret key URI.
+#[derive(Debug, StructOpt)]
+pub struct SecretPasswordParams {
+	/// Password of the secret key URI.
+	#[structopt(long, conflicts_with = "password-interactive")]
+	pub password: Option<String>,
+
+	/// Use interactive shell for entering the password of the secret key URI.
+	#[structopt(long = "password-interactive")]
+	pub password_interactive: bool,
+}
+
+impl SecretPasswordParams {
+	/// Returns the password of the secret key URI, if any.
+	pub fn password(&self) -> Result<Option<String>> {
+		if self.password_interactive {
+			#[cfg(not(target_os = "unknown"))]
+			{
+				return rpassword::read_password_from_tty(Some("Key password: "))
+					.map(Some)
+					.map_err(|e| format!("{:?}", e).into())
+			}
+		}
+
+		Ok(self.password.clone())
+	}
+}
+
+fn parse_ss58_format(network: &str) -> std::result::Result<Ss58AddressFormat, String> {
+	Ss58AddressFormat::try_from(network).map_err(|_| format!(
+		"Invalid network `{}`, expected one of: {}",
+		network,
+		Ss58AddressFormat::all().iter().cloned().map(String::from).collect::<Vec<_>>().join(", "),
+	))
+}
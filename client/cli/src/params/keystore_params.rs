@@ -16,10 +16,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use sc_service::config::KeystoreConfig;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use structopt::StructOpt;
 
 /// default sub directory for the key store
@@ -32,6 +33,25 @@ pub struct KeystoreParams {
 	#[structopt(long = "keystore-path", value_name = "PATH", parse(from_os_str))]
 	pub keystore_path: Option<PathBuf>,
 
+	/// Use a remote signer listening at the given URI instead of the local keystore.
+	///
+	/// Only `http://` URIs are supported.
+	#[structopt(
+		long = "keystore-uri",
+		value_name = "URI",
+		conflicts_with_all = &[
+			"keystore-path", "password-interactive", "password", "password-filename",
+			"keystore-encrypted",
+		]
+	)]
+	pub keystore_uri: Option<String>,
+
+	/// Number of seconds to wait for the remote signer to answer a request.
+	///
+	/// The default value is 5.
+	#[structopt(long = "keystore-timeout", value_name = "SECONDS", requires = "keystore-uri")]
+	pub keystore_timeout: Option<u64>,
+
 	/// Use interactive shell for entering the password used by the keystore.
 	#[structopt(
 		long = "password-interactive",
@@ -54,11 +74,25 @@ pub struct KeystoreParams {
 		conflicts_with_all = &[ "password-interactive", "password" ]
 	)]
 	pub password_filename: Option<PathBuf>,
+
+	/// Encrypt the key files with the keystore password.
+	///
+	/// Key files that are still stored in plaintext can be read, use `key migrate` to encrypt
+	/// them as well.
+	#[structopt(long = "keystore-encrypted")]
+	pub keystore_encrypted: bool,
 }
 
 impl KeystoreParams {
 	/// Get the keystore configuration for the parameters
 	pub fn keystore_config(&self, base_path: &PathBuf) -> Result<KeystoreConfig> {
+		if let Some(ref uri) = self.keystore_uri {
+			let timeout = self.keystore_timeout
+				.map(Duration::from_secs)
+				.unwrap_or(sc_keystore::remote::DEFAULT_REQUEST_TIMEOUT);
+			return Ok(KeystoreConfig::Remote { uri: uri.clone(), timeout })
+		}
+
 		let password = if self.password_interactive {
 			#[cfg(not(target_os = "unknown"))]
 			{
@@ -67,11 +101,7 @@ impl KeystoreParams {
 			#[cfg(target_os = "unknown")]
 			None
 		} else if let Some(ref file) = self.password_filename {
-			Some(
-				fs::read_to_string(file)
-					.map_err(|e| format!("{}", e))?
-					.into(),
-			)
+			Some(read_password_file(file)?.into())
 		} else if let Some(ref password) = self.password {
 			Some(password.clone().into())
 		} else {
@@ -83,10 +113,31 @@ impl KeystoreParams {
 			.clone()
 			.unwrap_or(base_path.join(DEFAULT_KEYSTORE_CONFIG_PATH));
 
+		if self.keystore_encrypted {
+			let password = password.ok_or_else(|| {
+				Error::Input("`--keystore-encrypted` requires a keystore password".into())
+			})?;
+			return Ok(KeystoreConfig::Encrypted { path, password })
+		}
+
 		Ok(KeystoreConfig::Path { path, password })
 	}
 }
 
+/// Read the password stored in the given file.
+///
+/// A single trailing line break of a password file is not part of the password.
+pub(crate) fn read_password_file(file: &Path) -> Result<String> {
+	let mut password = fs::read_to_string(file)?;
+	if password.ends_with('\n') {
+		password.pop();
+		if password.ends_with('\r') {
+			password.pop();
+		}
+	}
+	Ok(password)
+}
+
 #[cfg(not(target_os = "unknown"))]
 fn input_keystore_password() -> Result<String> {
 	rpassword::read_password_from_tty(Some("Keystore password: "))
@@ -17,6 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 mod database_params;
 mod import_params;
+mod key_params;
 mod keystore_params;
 mod network_params;
 mod node_key_params;
@@ -30,6 +31,7 @@ use sp_runtime::{generic::BlockId, traits::{Block as BlockT, NumberFor}};
 
 pub use crate::params::database_params::*;
 pub use crate::params::import_params::*;
+pub use crate::params::key_params::*;
 pub use crate::params::keystore_params::*;
 pub use crate::params::network_params::*;
 pub use crate::params::node_key_params::*;
@@ -102,6 +102,13 @@ pub struct NetworkParams {
 	/// By default this option is true for `--dev` and false otherwise.
 	#[structopt(long)]
 	pub discover_local: bool,
+
+	/// Forget the nodes and reputations saved by previous runs.
+	///
+	/// By default, the reputations of the known nodes and the addresses of the nodes we managed
+	/// to connect to are saved in the network configuration directory and restored on startup.
+	#[structopt(long = "reset-peer-store")]
+	pub reset_peer_store: bool,
 }
 
 impl NetworkParams {
@@ -138,6 +145,7 @@ impl NetworkParams {
 		NetworkConfiguration {
 			boot_nodes,
 			net_config_path,
+			reset_peer_store: self.reset_peer_store,
 			reserved_nodes: self.reserved_nodes.clone(),
 			non_reserved_mode: if self.reserved_only {
 				NonReservedPeerMode::Deny
@@ -147,6 +155,7 @@ impl NetworkParams {
 			listen_addresses,
 			public_addresses,
 			notifications_protocols: Vec::new(),
+			request_response_protocols: Vec::new(),
 			node_key,
 			node_name: node_name.to_string(),
 			client_version: client_id.to_string(),
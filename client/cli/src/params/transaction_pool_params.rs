@@ -16,7 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use sc_service::config::TransactionPoolOptions;
+use sc_service::config::{TransactionPoolOptions, TransactionPoolSenderLimit};
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 /// Parameters used to create the pool configuration.
@@ -29,6 +30,28 @@ pub struct TransactionPoolParams {
 	/// Maximum number of kilobytes of all transactions stored in the pool.
 	#[structopt(long = "pool-kbytes", value_name = "COUNT", default_value = "20480")]
 	pub pool_kbytes: usize,
+
+	/// Maximum number of ready transactions of a single sender in the transaction pool.
+	///
+	/// Enables fair eviction: when the pool is full, the transactions of the senders
+	/// with the most transactions are dropped first.
+	#[structopt(long = "pool-sender-limit", value_name = "COUNT")]
+	pub pool_sender_limit: Option<usize>,
+
+	/// Number of leading bytes of the first provided tag that identify the sender of a transaction.
+	#[structopt(long = "pool-sender-tag-prefix", value_name = "BYTES", default_value = "32")]
+	pub pool_sender_tag_prefix: usize,
+
+	/// Minimal priority increase, in percent, a transaction needs to replace another one.
+	#[structopt(long = "pool-min-priority-bump", value_name = "PERCENT", default_value = "0")]
+	pub pool_min_priority_bump: u32,
+
+	/// Keep the transactions of the pool in the given file across restarts.
+	///
+	/// The file is written periodically and on shutdown. On startup, the transactions are
+	/// imported again and validated against the best block.
+	#[structopt(long = "pool-journal", value_name = "PATH", parse(from_os_str))]
+	pub pool_journal: Option<PathBuf>,
 }
 
 impl TransactionPoolParams {
@@ -45,6 +68,16 @@ impl TransactionPoolParams {
 		opts.future.count = self.pool_limit / factor;
 		opts.future.total_bytes = self.pool_kbytes * 1024 / factor;
 
+		// per sender limits
+		opts.sender = self.pool_sender_limit.map(|limit| TransactionPoolSenderLimit {
+			tag_prefix: self.pool_sender_tag_prefix,
+			ready: limit,
+			future: std::cmp::max(limit / factor, 1),
+		});
+		opts.min_priority_bump = self.pool_min_priority_bump;
+
+		opts.journal = self.pool_journal.clone();
+
 		opts
 	}
 }
@@ -254,6 +254,7 @@ impl<C: SubstrateCli> Runner<C> {
 			Subcommand::Revert(cmd) => cmd.run(self.config, builder),
 			Subcommand::PurgeChain(cmd) => cmd.run(self.config),
 			Subcommand::ExportState(cmd) => cmd.run(self.config, builder),
+			Subcommand::PrecompileWasm(cmd) => cmd.run(self.config),
 		}
 	}
 
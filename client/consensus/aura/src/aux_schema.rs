@@ -0,0 +1,67 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Schema for Aura block weights in the aux-db.
+
+use codec::{Decode, Encode};
+
+use sc_client_api::backend::AuxStore;
+use sp_blockchain::{Result as ClientResult, Error as ClientError};
+use sp_runtime::traits::{Block as BlockT, NumberFor, UniqueSaturatedInto};
+
+use crate::AuraBlockWeight;
+
+fn block_weight_key<H: Encode>(block_hash: H) -> Vec<u8> {
+	(b"aura_block_weight", block_hash).encode()
+}
+
+/// Write the cumulative chain-weight of a block to aux storage.
+pub(crate) fn write_block_weight<H: Encode, F, R>(
+	block_hash: H,
+	block_weight: AuraBlockWeight,
+	write_aux: F,
+) -> R where
+	F: FnOnce(&[(Vec<u8>, &[u8])]) -> R,
+{
+	let key = block_weight_key(block_hash);
+	block_weight.using_encoded(|s|
+		write_aux(
+			&[(key, s)],
+		)
+	)
+}
+
+/// Load the cumulative chain-weight associated with a block.
+///
+/// Blocks imported before weights were tracked have no weight stored, all
+/// of them were authored by primary authors and their weight is thus the
+/// block number.
+pub(crate) fn load_block_weight<Block: BlockT, B: AuxStore>(
+	backend: &B,
+	block_hash: Block::Hash,
+	block_number: NumberFor<Block>,
+) -> ClientResult<AuraBlockWeight> {
+	let corrupt = |e: codec::Error| {
+		ClientError::Backend(format!("Aura DB is corrupted. Decode error: {}", e.what()))
+	};
+
+	match backend.get_aux(&block_weight_key(block_hash))? {
+		None => Ok(block_number.unique_saturated_into()),
+		Some(weight) => AuraBlockWeight::decode(&mut &weight[..]).map_err(corrupt),
+	}
+}
@@ -22,7 +22,7 @@
 //! `CompatibleDigestItem` trait to appear in public interfaces.
 
 use sp_core::Pair;
-use sp_consensus_aura::AURA_ENGINE_ID;
+use sp_consensus_aura::{AURA_ENGINE_ID, PreDigest};
 use sp_runtime::generic::{DigestItem, OpaqueDigestItemId};
 use codec::{Encode, Codec};
 use std::fmt::Debug;
@@ -37,11 +37,12 @@ pub trait CompatibleDigestItem<P: Pair>: Sized {
 	/// If this item is an Aura seal, return the signature.
 	fn as_aura_seal(&self) -> Option<Signature<P>>;
 
-	/// Construct a digest item which contains the slot number
-	fn aura_pre_digest(slot_num: u64) -> Self;
+	/// Construct a digest item which contains the slot number and, for
+	/// blocks by backup authors, the index of the author.
+	fn aura_pre_digest(pre_digest: PreDigest) -> Self;
 
-	/// If this item is an AuRa pre-digest, return the slot number
-	fn as_aura_pre_digest(&self) -> Option<u64>;
+	/// If this item is an AuRa pre-digest, return it.
+	fn as_aura_pre_digest(&self) -> Option<PreDigest>;
 }
 
 impl<P, Hash> CompatibleDigestItem<P> for DigestItem<Hash> where
@@ -57,11 +58,11 @@ impl<P, Hash> CompatibleDigestItem<P> for DigestItem<Hash> where
 		self.try_to(OpaqueDigestItemId::Seal(&AURA_ENGINE_ID))
 	}
 
-	fn aura_pre_digest(slot_num: u64) -> Self {
-		DigestItem::PreRuntime(AURA_ENGINE_ID, slot_num.encode())
+	fn aura_pre_digest(pre_digest: PreDigest) -> Self {
+		DigestItem::PreRuntime(AURA_ENGINE_ID, pre_digest.encode())
 	}
 
-	fn as_aura_pre_digest(&self) -> Option<u64> {
+	fn as_aura_pre_digest(&self) -> Option<PreDigest> {
 		self.try_to(OpaqueDigestItemId::PreRuntime(&AURA_ENGINE_ID))
 	}
 }
@@ -28,6 +28,12 @@
 //! Blocks from future steps will be either deferred or rejected depending on how
 //! far in the future they are.
 //!
+//! If allowed by the runtime, a backup author may issue a block for a slot
+//! whose author hasn't produced one after a configurable fraction of the slot
+//! has passed. The backup author of slot s is A[(s + 1) % |A|]. Blocks by
+//! backup authors don't add to the weight of a chain, so that chains of blocks
+//! by slot authors are preferred when choosing between forks.
+//!
 //! NOTE: Aura itself is designed to be generic over the crypto used.
 #![forbid(missing_docs, unsafe_code)]
 use std::{
@@ -37,7 +43,7 @@ use std::{
 
 use futures::prelude::*;
 use parking_lot::Mutex;
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use prometheus_endpoint::Registry;
 
 use codec::{Encode, Decode, Codec};
@@ -50,6 +56,7 @@ use sp_consensus::import_queue::{
 	Verifier, BasicQueue, BoxJustificationImport, BoxFinalityProofImport,
 };
 use sc_client_api::{backend::AuxStore, BlockOf};
+use sc_consensus::fork_choice;
 use sp_blockchain::{
 	self, Result as CResult, well_known_cache_keys::{self, Id as CacheKeyId},
 	ProvideCache, HeaderBackend,
@@ -59,9 +66,9 @@ use sp_core::crypto::Public;
 use sp_application_crypto::{AppKey, AppPublic};
 use sp_runtime::{
 	generic::{BlockId, OpaqueDigestItemId},
-	Justification,
+	Justification, Percent,
 };
-use sp_runtime::traits::{Block as BlockT, Header, DigestItemFor, Zero, Member};
+use sp_runtime::traits::{Block as BlockT, Header, DigestItemFor, Zero, One, Member};
 use sp_api::ProvideRuntimeApi;
 use sp_core::{traits::BareCryptoStore, crypto::Pair};
 use sp_inherents::{InherentDataProviders, InherentData};
@@ -71,14 +78,16 @@ use sp_timestamp::{
 use sc_telemetry::{telemetry, CONSENSUS_TRACE, CONSENSUS_DEBUG, CONSENSUS_INFO};
 
 use sc_consensus_slots::{
-	CheckedHeader, SlotWorker, SlotInfo, SlotCompatible, StorageChanges, check_equivocation,
+	CheckedHeader, SlotWorker, SimpleSlotWorker, SlotInfo, SlotCompatible, StorageChanges,
+	check_equivocation,
 };
 
 use sc_keystore::KeyStorePtr;
 use sp_api::ApiExt;
+use futures_timer::Delay;
 
 pub use sp_consensus_aura::{
-	ConsensusLog, AuraApi, AURA_ENGINE_ID,
+	ConsensusLog, AuraApi, AURA_ENGINE_ID, PreDigest, secondary_slot_author_index,
 	inherents::{
 		InherentType as AuraInherent,
 		AuraInherentData, INHERENT_IDENTIFIER, InherentDataProvider,
@@ -87,10 +96,15 @@ pub use sp_consensus_aura::{
 pub use sp_consensus::SyncOracle;
 pub use digests::CompatibleDigestItem;
 
+mod aux_schema;
 mod digests;
 
 type AuthorityId<P> = <P as Pair>::Public;
 
+/// The cumulative weight of an Aura chain, i.e. the number of blocks in it
+/// authored by primary slot authors.
+pub type AuraBlockWeight = u32;
+
 /// Slot duration type for Aura.
 pub type SlotDuration = sc_consensus_slots::SlotDuration<u64>;
 
@@ -104,21 +118,46 @@ pub fn slot_duration<A, B, C>(client: &C) -> CResult<SlotDuration> where
 	SlotDuration::get_or_compute(client, |a, b| a.slot_duration(b))
 }
 
-/// Get slot author for given block along with authorities.
-fn slot_author<P: Pair>(slot_num: u64, authorities: &[AuthorityId<P>]) -> Option<&AuthorityId<P>> {
-	if authorities.is_empty() { return None }
+/// Get the author of a block with the given pre-digest from the authorities.
+fn slot_author<'a, P: Pair>(
+	pre_digest: &PreDigest,
+	authorities: &'a [AuthorityId<P>],
+) -> Option<&'a AuthorityId<P>> {
+	pre_digest.author_index(authorities.len())
+		.and_then(|index| authorities.get(index as usize))
+}
+
+/// Whether the runtime at the given block allows backup authors.
+fn secondary_authors_allowed<A, B, C>(client: &C, at: &BlockId<B>) -> CResult<bool> where
+	A: Codec,
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: AuraApi<B, A> + ApiExt<B, Error = sp_blockchain::Error>,
+{
+	let runtime_api = client.runtime_api();
+
+	// runtimes predating backup authors don't allow them.
+	if !runtime_api.has_api_with::<dyn AuraApi<B, A, Error = ()>, _>(at, |v| v >= 3)? {
+		return Ok(false);
+	}
 
-	let idx = slot_num % (authorities.len() as u64);
-	assert!(
-		idx <= usize::max_value() as u64,
-		"It is impossible to have a vector with length beyond the address space; qed",
-	);
+	runtime_api.secondary_authors_allowed(at)
+}
 
-	let current_author = authorities.get(idx as usize)
-		.expect("authorities not empty; index constrained to list length;\
-				this is a valid index; qed");
+/// The weight of the chain ending at the given block, which has the given
+/// pre-digest.
+fn block_weight<B: BlockT, C: AuxStore>(
+	client: &C,
+	header: &B::Header,
+	pre_digest: &PreDigest,
+) -> CResult<AuraBlockWeight> {
+	let parent_weight = aux_schema::load_block_weight::<B, _>(
+		client,
+		*header.parent_hash(),
+		*header.number() - One::one(),
+	)?;
 
-	Some(current_author)
+	Ok(if pre_digest.is_primary() { parent_weight + 1 } else { parent_weight })
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -138,6 +177,13 @@ impl SlotCompatible for AuraSlotCompatible {
 }
 
 /// Start the aura worker. The returned future should be run in a futures executor.
+///
+/// If `secondary_timeout` is given and the runtime allows it, the worker
+/// authors blocks as the backup author of slots whose author hasn't produced
+/// a block once the given fraction of the slot has passed.
+///
+/// Authored blocks only follow the fork-choice rule of `AuraBlockImport` if
+/// `block_import` is one, like the block import of the import queue should be.
 pub fn start_aura<B, C, SC, E, I, P, SO, CAW, Error>(
 	slot_duration: SlotDuration,
 	client: Arc<C>,
@@ -147,12 +193,13 @@ pub fn start_aura<B, C, SC, E, I, P, SO, CAW, Error>(
 	sync_oracle: SO,
 	inherent_data_providers: InherentDataProviders,
 	force_authoring: bool,
+	secondary_timeout: Option<Percent>,
 	keystore: KeyStorePtr,
 	can_author_with: CAW,
 ) -> Result<impl Future<Output = ()>, sp_consensus::Error> where
 	B: BlockT,
-	C: ProvideRuntimeApi<B> + BlockOf + ProvideCache<B> + AuxStore + Send + Sync,
-	C::Api: AuraApi<B, AuthorityId<P>>,
+	C: ProvideRuntimeApi<B> + BlockOf + ProvideCache<B> + AuxStore + HeaderBackend<B> + Send + Sync,
+	C::Api: AuraApi<B, AuthorityId<P>> + ApiExt<B, Error = sp_blockchain::Error>,
 	SC: SelectChain<B>,
 	E: Environment<B, Error = Error> + Send + Sync + 'static,
 	E::Proposer: Proposer<B, Error = Error, Transaction = sp_api::TransactionFor<C, B>>,
@@ -167,10 +214,11 @@ pub fn start_aura<B, C, SC, E, I, P, SO, CAW, Error>(
 	let worker = AuraWorker {
 		client: client.clone(),
 		block_import: Arc::new(Mutex::new(block_import)),
-		env,
+		env: Arc::new(Mutex::new(env)),
 		keystore,
 		sync_oracle: sync_oracle.clone(),
 		force_authoring,
+		secondary_timeout,
 		_key_type: PhantomData::<P>,
 	};
 	register_aura_inherent_data_provider(
@@ -191,17 +239,34 @@ pub fn start_aura<B, C, SC, E, I, P, SO, CAW, Error>(
 struct AuraWorker<C, E, I, P, SO> {
 	client: Arc<C>,
 	block_import: Arc<Mutex<I>>,
-	env: E,
+	env: Arc<Mutex<E>>,
 	keystore: KeyStorePtr,
 	sync_oracle: SO,
 	force_authoring: bool,
+	secondary_timeout: Option<Percent>,
 	_key_type: PhantomData<P>,
 }
 
-impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraWorker<C, E, I, P, SO> where
+impl<C, E, I, P, SO: Clone> Clone for AuraWorker<C, E, I, P, SO> {
+	fn clone(&self) -> Self {
+		AuraWorker {
+			client: self.client.clone(),
+			block_import: self.block_import.clone(),
+			env: self.env.clone(),
+			keystore: self.keystore.clone(),
+			sync_oracle: self.sync_oracle.clone(),
+			force_authoring: self.force_authoring,
+			secondary_timeout: self.secondary_timeout,
+			_key_type: PhantomData,
+		}
+	}
+}
+
+impl<B, C, E, I, P, Error, SO> SimpleSlotWorker<B> for AuraWorker<C, E, I, P, SO> where
 	B: BlockT,
-	C: ProvideRuntimeApi<B> + BlockOf + ProvideCache<B> + Sync,
-	C::Api: AuraApi<B, AuthorityId<P>>,
+	C: ProvideRuntimeApi<B> + BlockOf + ProvideCache<B> + AuxStore + HeaderBackend<B>
+		+ Send + Sync + 'static,
+	C::Api: AuraApi<B, AuthorityId<P>> + ApiExt<B, Error = sp_blockchain::Error>,
 	E: Environment<B, Error = Error>,
 	E::Proposer: Proposer<B, Error = Error, Transaction = sp_api::TransactionFor<C, B>>,
 	I: BlockImport<B, Transaction = sp_api::TransactionFor<C, B>> + Send + Sync + 'static,
@@ -217,7 +282,7 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 		dyn Future<Output = Result<E::Proposer, sp_consensus::Error>> + Send + 'static
 	>>;
 	type Proposer = E::Proposer;
-	type Claim = P::Public;
+	type Claim = (P::Public, PreDigest);
 	type EpochData = Vec<AuthorityId<P>>;
 
 	fn logging_target(&self) -> &'static str {
@@ -242,26 +307,47 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 
 	fn claim_slot(
 		&self,
-		_header: &B::Header,
+		header: &B::Header,
 		slot_number: u64,
 		epoch_data: &Self::EpochData,
 	) -> Option<Self::Claim> {
-		let expected_author = slot_author::<P>(slot_number, epoch_data);
-		expected_author.and_then(|p| {
-			self.keystore.read()
-				.key_pair_by_type::<P>(&p, sp_application_crypto::key_types::AURA).ok()
-		}).and_then(|p| {
-			Some(p.public())
-		})
+		let claim = |pre_digest: PreDigest| {
+			slot_author::<P>(&pre_digest, epoch_data).and_then(|p| {
+				self.keystore.read()
+					.key_pair_by_type::<P>(&p, sp_application_crypto::key_types::AURA).ok()
+			}).map(|p| (p.public(), pre_digest))
+		};
+
+		let primary_claim = claim(PreDigest::primary(slot_number));
+		if primary_claim.is_some() || self.secondary_timeout.is_none() {
+			return primary_claim;
+		}
+
+		let secondary_claim = claim(PreDigest::secondary(
+			slot_number,
+			secondary_slot_author_index(slot_number, epoch_data.len())?,
+		))?;
+
+		match secondary_authors_allowed::<AuthorityId<P>, _, _>(
+			&*self.client,
+			&BlockId::Hash(header.hash()),
+		) {
+			Ok(true) => Some(secondary_claim),
+			Ok(false) => None,
+			Err(err) => {
+				warn!(target: "aura", "Unable to check whether backup authors are allowed: {:?}", err);
+				None
+			}
+		}
 	}
 
 	fn pre_digest_data(
 		&self,
-		slot_number: u64,
-		_claim: &Self::Claim,
+		_slot_number: u64,
+		claim: &Self::Claim,
 	) -> Vec<sp_runtime::DigestItem<B::Hash>> {
 		vec![
-			<DigestItemFor<B> as CompatibleDigestItem<P>>::aura_pre_digest(slot_number),
+			<DigestItemFor<B> as CompatibleDigestItem<P>>::aura_pre_digest(claim.1),
 		]
 	}
 
@@ -277,7 +363,7 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 		sp_consensus::Error> + Send + 'static>
 	{
 		let keystore = self.keystore.clone();
-		Box::new(move |header, header_hash, body, storage_changes, public, _epoch| {
+		Box::new(move |header, header_hash, body, storage_changes, (public, _), _epoch| {
 			// sign the pre-sealed hash of the block and then
 			// add it to a digest item.
 			let public_type_pair = public.to_public_crypto_pair();
@@ -302,6 +388,7 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 			import_block.post_digests.push(signature_digest_item);
 			import_block.body = Some(body);
 			import_block.storage_changes = Some(storage_changes);
+			// replaced by the fork-choice rule of `AuraBlockImport`.
 			import_block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 
 			Ok(import_block)
@@ -317,7 +404,7 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 	}
 
 	fn proposer(&mut self, block: &B::Header) -> Self::CreateProposer {
-		Box::pin(self.env.init(block).map_err(|e| {
+		Box::pin(self.env.lock().init(block).map_err(|e| {
 			sp_consensus::Error::ClientImport(format!("{:?}", e)).into()
 		}))
 	}
@@ -331,7 +418,7 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 
 		let parent_slot = match find_pre_digest::<B, P>(head) {
 			Err(_) => return Some(slot_remaining),
-			Ok(d) => d,
+			Ok(d) => d.slot_number,
 		};
 
 		if let Some(slot_lenience) =
@@ -352,21 +439,77 @@ impl<B, C, E, I, P, Error, SO> sc_consensus_slots::SimpleSlotWorker<B> for AuraW
 
 impl<B: BlockT, C, E, I, P, Error, SO> SlotWorker<B> for AuraWorker<C, E, I, P, SO> where
 	B: BlockT,
-	C: ProvideRuntimeApi<B> + BlockOf + ProvideCache<B> + Sync + Send,
-	C::Api: AuraApi<B, AuthorityId<P>>,
-	E: Environment<B, Error = Error> + Send + Sync,
+	C: ProvideRuntimeApi<B> + BlockOf + ProvideCache<B> + AuxStore + HeaderBackend<B> + Sync + Send + 'static,
+	C::Api: AuraApi<B, AuthorityId<P>> + ApiExt<B, Error = sp_blockchain::Error>,
+	E: Environment<B, Error = Error> + Send + Sync + 'static,
 	E::Proposer: Proposer<B, Error = Error, Transaction = sp_api::TransactionFor<C, B>>,
 	I: BlockImport<B, Transaction = sp_api::TransactionFor<C, B>> + Send + Sync + 'static,
 	P: Pair + Send + Sync,
 	P::Public: AppPublic + Member + Encode + Decode + Hash,
 	P::Signature: TryFrom<Vec<u8>> + Member + Encode + Decode + Hash + Debug,
-	SO: SyncOracle + Send + Sync + Clone,
+	SO: SyncOracle + Send + Sync + Clone + 'static,
 	Error: std::error::Error + Send + From<sp_consensus::Error> + 'static,
 {
 	type OnSlot = Pin<Box<dyn Future<Output = Result<(), sp_consensus::Error>> + Send>>;
 
 	fn on_slot(&mut self, chain_head: B::Header, slot_info: SlotInfo) -> Self::OnSlot {
-		<Self as sc_consensus_slots::SimpleSlotWorker<B>>::on_slot(self, chain_head, slot_info)
+		let secondary_timeout = match self.secondary_timeout {
+			Some(timeout) => timeout,
+			None => return <Self as SimpleSlotWorker<B>>::on_slot(self, chain_head, slot_info),
+		};
+
+		let is_secondary_claim =
+			<Self as SimpleSlotWorker<B>>::epoch_data(self, &chain_head, slot_info.number)
+				.ok()
+				.and_then(|authorities| <Self as SimpleSlotWorker<B>>::claim_slot(
+					self,
+					&chain_head,
+					slot_info.number,
+					&authorities,
+				))
+				.map_or(false, |(_, pre_digest)| !pre_digest.is_primary());
+
+		if !is_secondary_claim {
+			return <Self as SimpleSlotWorker<B>>::on_slot(self, chain_head, slot_info);
+		}
+
+		// as the backup author we wait for the slot author's block until the
+		// timeout, counted from the start of the slot.
+		let remaining = <Self as SimpleSlotWorker<B>>::slot_remaining_duration(self, &slot_info);
+		let elapsed = Duration::from_millis(slot_info.duration)
+			.checked_sub(remaining)
+			.unwrap_or_default();
+		let wait = Duration::from_millis(secondary_timeout.mul_floor(slot_info.duration))
+			.checked_sub(elapsed)
+			.unwrap_or_default();
+
+		let mut worker = self.clone();
+		Box::pin(async move {
+			Delay::new(wait).await;
+
+			let best_hash = worker.client.info().best_hash;
+			let best = match worker.client.header(BlockId::Hash(best_hash)) {
+				Ok(Some(best)) => best,
+				Ok(None) | Err(_) => {
+					warn!(target: "aura", "Unable to fetch best header {:?}", best_hash);
+					return Ok(());
+				}
+			};
+
+			let block_arrived = find_pre_digest::<B, P>(&best)
+				.map_or(true, |pre_digest| pre_digest.slot_number >= slot_info.number);
+
+			if block_arrived {
+				debug!(target: "aura",
+					"Not authoring as backup at slot {}, a block for the slot was imported.",
+					slot_info.number,
+				);
+
+				return Ok(());
+			}
+
+			<Self as SimpleSlotWorker<B>>::on_slot(&mut worker, best, slot_info).await
+		})
 	}
 }
 
@@ -387,6 +530,8 @@ enum Error<B: BlockT> {
 	HeaderBadSeal(B::Hash),
 	#[display(fmt = "Slot Author not found")]
 	SlotAuthorNotFound,
+	#[display(fmt = "Header {:?} is by a backup author, which the runtime doesn't allow", _0)]
+	SecondaryAuthorsDisallowed(B::Hash),
 	#[display(fmt = "Bad signature on {:?}", _0)]
 	BadSignature(B::Hash),
 	#[display(fmt = "Rejecting block too far in future")]
@@ -406,16 +551,16 @@ impl<B: BlockT> std::convert::From<Error<B>> for String {
 	}
 }
 
-fn find_pre_digest<B: BlockT, P: Pair>(header: &B::Header) -> Result<u64, Error<B>>
+fn find_pre_digest<B: BlockT, P: Pair>(header: &B::Header) -> Result<PreDigest, Error<B>>
 	where DigestItemFor<B>: CompatibleDigestItem<P>,
 		P::Signature: Decode,
 		P::Public: Encode + Decode + PartialEq + Clone,
 {
 	if header.number().is_zero() {
-		return Ok(0);
+		return Ok(PreDigest::primary(0));
 	}
 
-	let mut pre_digest: Option<u64> = None;
+	let mut pre_digest: Option<PreDigest> = None;
 	for log in header.digest().logs() {
 		trace!(target: "aura", "Checking log {:?}", log);
 		match (log.as_aura_pre_digest(), pre_digest.is_some()) {
@@ -428,20 +573,18 @@ fn find_pre_digest<B: BlockT, P: Pair>(header: &B::Header) -> Result<u64, Error<
 }
 
 /// check a header has been signed by the right key. If the slot is too far in the future, an error will be returned.
-/// if it's successful, returns the pre-header and the digest item containing the seal.
+/// if it's successful, returns the pre-header, its pre-digest and the digest item containing the seal.
 ///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
 //
-fn check_header<C, B: BlockT, P: Pair>(
-	client: &C,
+fn check_header<B: BlockT, P: Pair>(
 	slot_now: u64,
 	mut header: B::Header,
 	hash: B::Hash,
 	authorities: &[AuthorityId<P>],
-) -> Result<CheckedHeader<B::Header, (u64, DigestItemFor<B>)>, Error<B>> where
+) -> Result<CheckedHeader<B::Header, (PreDigest, DigestItemFor<B>)>, Error<B>> where
 	DigestItemFor<B>: CompatibleDigestItem<P>,
 	P::Signature: Decode,
-	C: sc_client_api::backend::AuxStore,
 	P::Public: Encode + Decode + PartialEq + Clone,
 {
 	let seal = match header.digest_mut().pop() {
@@ -453,7 +596,8 @@ fn check_header<C, B: BlockT, P: Pair>(
 		aura_err(Error::HeaderBadSeal(hash))
 	})?;
 
-	let slot_num = find_pre_digest::<B, _>(&header)?;
+	let pre_digest = find_pre_digest::<B, _>(&header)?;
+	let slot_num = pre_digest.slot_number;
 
 	if slot_num > slot_now {
 		header.digest_mut().push(seal);
@@ -461,7 +605,7 @@ fn check_header<C, B: BlockT, P: Pair>(
 	} else {
 		// check the signature is valid under the expected authority and
 		// chain state.
-		let expected_author = match slot_author::<P>(slot_num, &authorities) {
+		let expected_author = match slot_author::<P>(&pre_digest, &authorities) {
 			None => return Err(Error::SlotAuthorNotFound),
 			Some(author) => author,
 		};
@@ -469,22 +613,7 @@ fn check_header<C, B: BlockT, P: Pair>(
 		let pre_hash = header.hash();
 
 		if P::verify(&sig, pre_hash.as_ref(), expected_author) {
-			if let Some(equivocation_proof) = check_equivocation(
-				client,
-				slot_now,
-				slot_num,
-				&header,
-				expected_author,
-			).map_err(Error::Client)? {
-				info!(
-					"Slot author is equivocating at slot {} with headers {:?} and {:?}",
-					slot_num,
-					equivocation_proof.fst_header().hash(),
-					equivocation_proof.snd_header().hash(),
-				);
-			}
-
-			Ok(CheckedHeader::Checked(header, (slot_num, seal)))
+			Ok(CheckedHeader::Checked(header, (pre_digest, seal)))
 		} else {
 			Err(Error::BadSignature(hash))
 		}
@@ -501,7 +630,7 @@ pub struct AuraVerifier<C, P> {
 impl<C, P> AuraVerifier<C, P>
 	where P: Send + Sync + 'static
 {
-	fn check_inherents<B: BlockT>(
+	fn check_inherents(
 		&self,
 		block: B,
 		block_id: BlockId<B>,
@@ -550,6 +679,77 @@ impl<C, P> AuraVerifier<C, P>
 			Ok(())
 		}
 	}
+
+	fn check_and_report_equivocation<B: BlockT>(
+		&self,
+		slot_now: u64,
+		slot: u64,
+		header: &B::Header,
+		author: &AuthorityId<P>,
+		origin: &BlockOrigin,
+	) -> Result<(), Error<B>>
+		where
+			C: AuxStore + HeaderBackend<B> + ProvideRuntimeApi<B>,
+			C::Api: AuraApi<B, AuthorityId<P>> + ApiExt<B, Error = sp_blockchain::Error>,
+			P: Pair,
+			P::Public: Clone + Encode + Decode + PartialEq + Debug,
+	{
+		// don't report any equivocations during initial sync
+		// as they are most likely stale.
+		if *origin == BlockOrigin::NetworkInitialSync {
+			return Ok(());
+		}
+
+		// check if authorship of this header is an equivocation and return a proof if so.
+		let equivocation_proof =
+			match check_equivocation(&*self.client, slot_now, slot, header, author)
+				.map_err(Error::Client)?
+			{
+				Some(proof) => proof,
+				None => return Ok(()),
+			};
+
+		info!(
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			author,
+			slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		// we generate the key ownership proof and submit the report on top of
+		// the best block, the session of the equivocation must still be live
+		// there for the proof to be generated.
+		let best_id = BlockId::Hash(self.client.info().best_hash);
+
+		let key_owner_proof = match self.client.runtime_api()
+			.generate_key_ownership_proof(
+				&best_id,
+				slot,
+				equivocation_proof.offender.clone(),
+			)
+			.map_err(Error::Client)?
+		{
+			Some(proof) => proof,
+			None => {
+				debug!(target: "aura", "Equivocation offender is not part of the authority set.");
+				return Ok(());
+			}
+		};
+
+		// submit equivocation report at best block.
+		self.client.runtime_api()
+			.submit_report_equivocation_unsigned_extrinsic(
+				&best_id,
+				equivocation_proof,
+				key_owner_proof,
+			)
+			.map_err(Error::Client)?;
+
+		info!(target: "aura", "Submitted equivocation report for author {:?}", author);
+
+		Ok(())
+	}
 }
 
 #[forbid(deprecated)]
@@ -558,6 +758,7 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 		Send +
 		Sync +
 		sc_client_api::backend::AuxStore +
+		HeaderBackend<B> +
 		ProvideCache<B> +
 		BlockOf,
 	C::Api: BlockBuilderApi<B> + AuraApi<B, AuthorityId<P>> + ApiExt<B, Error = sp_blockchain::Error>,
@@ -586,15 +787,39 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 		// we add one to allow for some small drift.
 		// FIXME #1019 in the future, alter this queue to allow deferring of
 		// headers
-		let checked_header = check_header::<C, B, P>(
-			&self.client,
+		let checked_header = check_header::<B, P>(
 			slot_now + 1,
 			header,
 			hash,
 			&authorities[..],
 		).map_err(|e| e.to_string())?;
 		match checked_header {
-			CheckedHeader::Checked(pre_header, (slot_num, seal)) => {
+			CheckedHeader::Checked(pre_header, (pre_digest, seal)) => {
+				let slot_num = pre_digest.slot_number;
+
+				// blocks by backup authors are only valid if the runtime allows them.
+				if !pre_digest.is_primary() && !secondary_authors_allowed::<AuthorityId<P>, _, _>(
+					&*self.client,
+					&BlockId::Hash(parent_hash),
+				).map_err(|e| format!("{:?}", e))? {
+					return Err(aura_err(Error::<B>::SecondaryAuthorsDisallowed(hash)).into());
+				}
+
+				// the header is valid but let's check if there was something else already
+				// proposed at the same slot by the given author. if there was, we will
+				// report the equivocation to the runtime.
+				if let Some(author) = slot_author::<P>(&pre_digest, &authorities) {
+					if let Err(err) = self.check_and_report_equivocation(
+						slot_now + 1,
+						slot_num,
+						&pre_header,
+						author,
+						&origin,
+					) {
+						warn!(target: "aura", "Error checking/reporting AURA equivocation: {:?}", err);
+					}
+				}
+
 				// if the body is passed through, we need to use the runtime
 				// to check that the internally-set timestamp in the inherents
 				// actually matches the slot set in the seal.
@@ -644,6 +869,7 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 				import_block.post_digests.push(seal);
 				import_block.body = body;
 				import_block.justification = justification;
+				// replaced by the fork-choice rule of `AuraBlockImport`.
 				import_block.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 				import_block.post_hash = Some(hash);
 
@@ -732,9 +958,14 @@ fn register_aura_inherent_data_provider(
 }
 
 /// A block-import handler for Aura.
+///
+/// Decides which imported blocks become the best block, following the
+/// heaviest chain rule unless another rule is given with `with_fork_choice`.
 pub struct AuraBlockImport<Block: BlockT, C, I: BlockImport<Block>, P> {
 	inner: I,
 	client: Arc<C>,
+	fork_choice: Arc<dyn fork_choice::ForkChoiceStrategy<Block, AuraBlockWeight>>,
+	import_lock: Arc<Mutex<()>>,
 	_phantom: PhantomData<(Block, P)>,
 }
 
@@ -743,6 +974,8 @@ impl<Block: BlockT, C, I: Clone + BlockImport<Block>, P> Clone for AuraBlockImpo
 		AuraBlockImport {
 			inner: self.inner.clone(),
 			client: self.client.clone(),
+			fork_choice: self.fork_choice.clone(),
+			import_lock: self.import_lock.clone(),
 			_phantom: PhantomData,
 		}
 	}
@@ -757,15 +990,28 @@ impl<Block: BlockT, C, I: BlockImport<Block>, P> AuraBlockImport<Block, C, I, P>
 		Self {
 			inner,
 			client,
+			fork_choice: Arc::new(fork_choice::HeaviestChainRule::new()),
+			import_lock: Default::default(),
 			_phantom: PhantomData,
 		}
 	}
+
+	/// Decide which imported blocks become the best block with the given
+	/// fork-choice rule. The rule is given the number of blocks by primary
+	/// slot authors as the weight of a chain.
+	pub fn with_fork_choice(
+		mut self,
+		fork_choice: Arc<dyn fork_choice::ForkChoiceStrategy<Block, AuraBlockWeight>>,
+	) -> Self {
+		self.fork_choice = fork_choice;
+		self
+	}
 }
 
 impl<Block: BlockT, C, I, P> BlockImport<Block> for AuraBlockImport<Block, C, I, P> where
 	I: BlockImport<Block, Transaction = sp_api::TransactionFor<C, Block>> + Send + Sync,
 	I::Error: Into<ConsensusError>,
-	C: HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block> + ProvideRuntimeApi<Block> + AuxStore,
 	P: Pair + Send + Sync + 'static,
 	P::Public: Clone + Eq + Send + Sync + Hash + Debug + Encode + Decode,
 	P::Signature: Encode + Decode,
@@ -782,13 +1028,14 @@ impl<Block: BlockT, C, I, P> BlockImport<Block> for AuraBlockImport<Block, C, I,
 
 	fn import_block(
 		&mut self,
-		block: BlockImportParams<Block, Self::Transaction>,
+		mut block: BlockImportParams<Block, Self::Transaction>,
 		new_cache: HashMap<CacheKeyId, Vec<u8>>,
 	) -> Result<ImportResult, Self::Error> {
 		let hash = block.post_hash();
-		let slot_number = find_pre_digest::<Block, P>(&block.header)
+		let pre_digest = find_pre_digest::<Block, P>(&block.header)
 			.expect("valid Aura headers must contain a predigest; \
 					 header has been already verified; qed");
+		let slot_number = pre_digest.slot_number;
 
 		let parent_hash = *block.header.parent_hash();
 		let parent_header = self.client.header(BlockId::Hash(parent_hash))
@@ -799,7 +1046,8 @@ impl<Block: BlockT, C, I, P> BlockImport<Block> for AuraBlockImport<Block, C, I,
 
 		let parent_slot = find_pre_digest::<Block, P>(&parent_header)
 			.expect("valid Aura headers contain a pre-digest; \
-					parent header has already been verified; qed");
+					parent header has already been verified; qed")
+			.slot_number;
 
 		// make sure that slot number is strictly increasing
 		if slot_number <= parent_slot {
@@ -810,11 +1058,38 @@ impl<Block: BlockT, C, I, P> BlockImport<Block> for AuraBlockImport<Block, C, I,
 			);
 		}
 
+		// the best block must not change between the fork choice and the
+		// import of the block.
+		let _import_lock = self.import_lock.lock();
+
+		let weight = block_weight::<Block, _>(&*self.client, &block.header, &pre_digest)
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()))?;
+
+		let info = self.client.info();
+		let best = fork_choice::BestBlock {
+			hash: info.best_hash,
+			number: info.best_number,
+			weight: aux_schema::load_block_weight::<Block, _>(
+				&*self.client,
+				info.best_hash,
+				info.best_number,
+			).map_err(|e| ConsensusError::ClientImport(e.to_string()))?,
+		};
+		let is_new_best = self.fork_choice.is_new_best(&block.header, &weight, &best)?;
+		block.fork_choice = Some(ForkChoiceStrategy::Custom(is_new_best));
+
+		aux_schema::write_block_weight(hash, weight, |values| block.auxiliary.extend(
+			values.iter().map(|(k, v)| (k.to_vec(), Some(v.to_vec())))
+		));
+
 		self.inner.import_block(block, new_cache).map_err(Into::into)
 	}
 }
 
 /// Start an import queue for the Aura consensus algorithm.
+///
+/// Blocks only follow the fork-choice rule of Aura if `block_import` is an
+/// `AuraBlockImport`, which uses the heaviest chain rule by default.
 pub fn import_queue<B, I, C, P, S>(
 	slot_duration: SlotDuration,
 	block_import: I,
@@ -859,7 +1134,7 @@ mod tests {
 	use super::*;
 	use sp_consensus::{NoNetwork as DummyOracle, Proposal, RecordProof};
 	use sc_network_test::{Block as TestBlock, *};
-	use sp_runtime::traits::{Block as BlockT, DigestFor};
+	use sp_runtime::{generic::Digest, traits::{Block as BlockT, DigestFor}};
 	use sc_network::config::ProtocolConfig;
 	use parking_lot::Mutex;
 	use sp_keyring::sr25519::Keyring;
@@ -1025,6 +1300,7 @@ mod tests {
 				DummyOracle,
 				inherent_data_providers,
 				false,
+				None,
 				keystore,
 				sp_consensus::AlwaysCanAuthor,
 			).expect("Starts aura"));
@@ -1078,13 +1354,14 @@ mod tests {
 		let client = peer.client().as_full().expect("full clients are created").clone();
 		let environ = DummyFactory(client.clone());
 
-		let worker = AuraWorker {
+		let worker: AuraWorker<_, _, _, _, _> = AuraWorker {
 			client: client.clone(),
 			block_import: Arc::new(Mutex::new(client)),
-			env: environ,
+			env: Arc::new(Mutex::new(environ)),
 			keystore,
 			sync_oracle: DummyOracle.clone(),
 			force_authoring: false,
+			secondary_timeout: None,
 			_key_type: PhantomData::<AuthorityPair>,
 		};
 
@@ -1104,4 +1381,170 @@ mod tests {
 		assert!(worker.claim_slot(&head, 6, &authorities).is_none());
 		assert!(worker.claim_slot(&head, 7, &authorities).is_some());
 	}
+
+	#[test]
+	fn backup_author_should_claim_secondary_slot() {
+		let net = AuraTestNet::new(4);
+
+		let mut authorities = vec![
+			Keyring::Alice.public().into(),
+			Keyring::Bob.public().into(),
+			Keyring::Charlie.public().into()
+		];
+
+		let keystore_path = tempfile::tempdir().expect("Creates keystore path");
+		let keystore = sc_keystore::Store::open(keystore_path.path(), None).expect("Creates keystore.");
+		let my_key = keystore.write()
+			.generate_by_type::<AuthorityPair>(AuthorityPair::ID)
+			.expect("Key should be created");
+		authorities.push(my_key.public());
+
+		let net = Arc::new(Mutex::new(net));
+
+		let mut net = net.lock();
+		let peer = net.peer(3);
+		let client = peer.client().as_full().expect("full clients are created").clone();
+		let environ = DummyFactory(client.clone());
+		let head = client.header(&BlockId::Number(0)).unwrap().unwrap();
+
+		let mut worker: AuraWorker<_, _, _, _, _> = AuraWorker {
+			client: client.clone(),
+			block_import: Arc::new(Mutex::new(client)),
+			env: Arc::new(Mutex::new(environ)),
+			keystore,
+			sync_oracle: DummyOracle.clone(),
+			force_authoring: false,
+			secondary_timeout: None,
+			_key_type: PhantomData::<AuthorityPair>,
+		};
+
+		// we are the backup author of slot 2, which needs a secondary timeout.
+		assert!(worker.claim_slot(&head, 2, &authorities).is_none());
+
+		worker.secondary_timeout = Some(Percent::from_percent(50));
+		assert_eq!(worker.claim_slot(&head, 1, &authorities), None);
+		assert_eq!(
+			worker.claim_slot(&head, 2, &authorities),
+			Some((my_key.public(), PreDigest::secondary(2, 3))),
+		);
+		assert_eq!(
+			worker.claim_slot(&head, 3, &authorities),
+			Some((my_key.public(), PreDigest::primary(3))),
+		);
+	}
+
+	#[test]
+	fn block_import_follows_the_fork_choice_rule() {
+		let mut net = AuraTestNet::new(1);
+		let peer = net.peer(0);
+		let client = peer.client().as_full().expect("full clients are created").clone();
+
+		let mut block_import = AuraBlockImport::<_, _, _, AuthorityPair>::new(
+			client.clone(),
+			client.clone(),
+		).with_fork_choice(Arc::new(fork_choice::HeaviestChainRule::strict()));
+
+		let mut import_on_genesis = |pre_digest: PreDigest| {
+			let digest = Digest {
+				logs: vec![
+					<DigestItemFor<TestBlock> as CompatibleDigestItem<AuthorityPair>>::aura_pre_digest(
+						pre_digest,
+					),
+				],
+			};
+			let block = client.new_block_at(&BlockId::Number(0), digest, false)
+				.unwrap()
+				.build()
+				.unwrap()
+				.block;
+			let hash = block.header.hash();
+
+			let (header, body) = block.deconstruct();
+			let mut params = BlockImportParams::new(BlockOrigin::Own, header);
+			params.body = Some(body);
+			block_import.import_block(params, Default::default()).unwrap();
+
+			hash
+		};
+
+		// only chains with more primary blocks replace the best chain.
+		let primary = import_on_genesis(PreDigest::primary(1));
+		assert_eq!(client.info().best_hash, primary);
+
+		import_on_genesis(PreDigest::secondary(2, 0));
+		assert_eq!(client.info().best_hash, primary);
+	}
+
+	#[test]
+	#[allow(deprecated)]
+	fn backup_authors_fill_slots_of_offline_authors() {
+		let _ = env_logger::try_init();
+		let net = AuraTestNet::new(3);
+
+		// Charlie is offline, the slots assigned to Charlie are backed up by Alice.
+		let peers = &[
+			(0, Keyring::Alice),
+			(1, Keyring::Bob),
+		];
+
+		let net = Arc::new(Mutex::new(net));
+		let mut aura_futures = Vec::new();
+
+		let mut keystore_paths = Vec::new();
+		for (peer_id, key) in peers {
+			let mut net = net.lock();
+			let peer = net.peer(*peer_id);
+			let client = peer.client().as_full().expect("full clients are created").clone();
+			let select_chain = peer.select_chain().expect("full client has a select chain");
+			let keystore_path = tempfile::tempdir().expect("Creates keystore path");
+			let keystore = sc_keystore::Store::open(keystore_path.path(), None).expect("Creates keystore.");
+
+			keystore.write().insert_ephemeral_from_seed::<AuthorityPair>(&key.to_seed())
+				.expect("Creates authority key");
+			keystore_paths.push(keystore_path);
+
+			let environ = DummyFactory(client.clone());
+			let slot_duration = slot_duration(&*client).expect("slot duration available");
+
+			let inherent_data_providers = InherentDataProviders::new();
+			register_aura_inherent_data_provider(
+				&inherent_data_providers, slot_duration.get()
+			).expect("Registers aura inherent data provider");
+
+			aura_futures.push(start_aura::<_, _, _, _, _, AuthorityPair, _, _, _>(
+				slot_duration,
+				client.clone(),
+				select_chain,
+				client,
+				environ,
+				DummyOracle,
+				inherent_data_providers,
+				false,
+				Some(Percent::from_percent(50)),
+				keystore,
+				sp_consensus::AlwaysCanAuthor,
+			).expect("Starts aura"));
+		}
+
+		let client = net.lock().peer(0).client().as_full().expect("full clients are created").clone();
+		let imported = client.import_notification_stream()
+			.take_while(|n| future::ready(n.header.number() < &5))
+			.for_each(|_| future::ready(()));
+
+		futures::executor::block_on(future::select(
+			future::poll_fn(move |cx| {
+				net.lock().poll(cx);
+				Poll::<()>::Pending
+			}),
+			future::select(future::join_all(aura_futures), imported),
+		));
+
+		// every slot got a block, so at least one of them is from Charlie's
+		// slots and was authored by the backup author.
+		let best = client.chain_info().best_number;
+		assert!((1..=best).any(|number| {
+			let header = client.header(&BlockId::Number(number)).unwrap().unwrap();
+			!find_pre_digest::<TestBlock, AuthorityPair>(&header).unwrap().is_primary()
+		}));
+	}
 }
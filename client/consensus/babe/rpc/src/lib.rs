@@ -22,17 +22,26 @@ use sc_consensus_babe::{Epoch, authorship, Config};
 use futures::{FutureExt as _, TryFutureExt as _};
 use jsonrpc_core::{
 	Error as RpcError,
+	Result as RpcResult,
 	futures::future as rpc_future,
 };
 use jsonrpc_derive::rpc;
-use sc_consensus_epochs::{descendent_query, Epoch as EpochT, SharedEpochChanges};
+use sc_consensus_epochs::{
+	descendent_query, Epoch as EpochT, EpochIdentifier, EpochIdentifierPosition,
+	IsDescendentOfBuilder, PersistedEpochHeader, SharedEpochChanges,
+};
 use sp_consensus_babe::{
+	AllowedSlots as BabeAllowedSlots,
 	AuthorityId,
+	AuthorityIndex,
 	BabeApi as BabeRuntimeApi,
-	digests::PreDigest,
+	BabeAuthorityWeight,
+	SlotNumber,
+	digests::{CompatibleDigestItem, PreDigest},
 };
 use serde::{Deserialize, Serialize};
 use sp_core::{
+	H256,
 	crypto::Public,
 	traits::BareCryptoStore,
 };
@@ -40,20 +49,42 @@ use sp_application_crypto::AppKey;
 use sc_keystore::KeyStorePtr;
 use sc_rpc_api::DenyUnsafe;
 use sp_api::{ProvideRuntimeApi, BlockId};
-use sp_runtime::traits::{Block as BlockT, Header as _};
+use sp_runtime::traits::{Block as BlockT, Header as _, NumberFor, Zero};
 use sp_consensus::{SelectChain, Error as ConsensusError};
 use sp_blockchain::{HeaderBackend, HeaderMetadata, Error as BlockChainError};
 use std::{collections::HashMap, sync::Arc};
 
 type FutureResult<T> = Box<dyn rpc_future::Future<Item = T, Error = RpcError> + Send>;
 
+/// The maximum number of slots that can be inspected with `babe_slotAuthors`.
+const MAX_SLOT_AUTHORS_COUNT: u64 = 4096;
+
 /// Provides rpc methods for interacting with Babe.
 #[rpc]
-pub trait BabeApi {
+pub trait BabeApi<Hash, Number> {
 	/// Returns data about which slots (primary or secondary) can be claimed in the current epoch
 	/// with the keys in the keystore.
 	#[rpc(name = "babe_epochAuthorship")]
 	fn epoch_authorship(&self) -> FutureResult<HashMap<AuthorityId, EpochAuthorship>>;
+
+	/// Returns the epoch of the current best block.
+	#[rpc(name = "babe_currentEpoch")]
+	fn current_epoch(&self) -> RpcResult<EpochInfo>;
+
+	/// Returns the epoch following the one of the current best block, if it has already been
+	/// announced.
+	#[rpc(name = "babe_nextEpoch")]
+	fn next_epoch(&self) -> RpcResult<Option<EpochInfo>>;
+
+	/// Returns the fork tree of viable epochs, in pre-order. Each node is the block that
+	/// announced its epochs.
+	#[rpc(name = "babe_epochTree")]
+	fn epoch_tree(&self) -> RpcResult<Vec<EpochTreeNode<Hash, Number>>>;
+
+	/// Returns the author of each of the last `count` slots of the best chain, up to and
+	/// including the slot of the best block. Slots without a block were missed.
+	#[rpc(name = "babe_slotAuthors")]
+	fn slot_authors(&self, count: u64) -> RpcResult<Vec<SlotAuthorship<Hash, Number>>>;
 }
 
 /// Implements the BabeRpc trait for interacting with Babe.
@@ -93,7 +124,168 @@ impl<B: BlockT, C, SC> BabeRpcHandler<B, C, SC> {
 	}
 }
 
-impl<B, C, SC> BabeApi for BabeRpcHandler<B, C, SC>
+impl<B, C, SC> BabeRpcHandler<B, C, SC>
+	where
+		B: BlockT,
+		C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
+		C::Api: BabeRuntimeApi<B>,
+		SC: SelectChain<B>,
+{
+	fn best_epoch(&self) -> Result<Epoch, Error> {
+		let header = self.select_chain.best_chain()?;
+		let epoch_start = self.client.runtime_api()
+			.current_epoch_start(&BlockId::Hash(header.hash()))
+			.map_err(|err| Error::StringError(format!("{:?}", err)))?;
+
+		epoch_data(
+			&self.shared_epoch_changes,
+			&self.client,
+			&self.babe_config,
+			epoch_start,
+			&self.select_chain,
+		)
+	}
+
+	fn next_best_epoch(&self) -> Result<Option<Epoch>, Error> {
+		// the first epoch after genesis is only announced by block #1.
+		if self.select_chain.best_chain()?.number().is_zero() {
+			return Ok(None);
+		}
+
+		let current = self.best_epoch()?;
+		let next = epoch_data(
+			&self.shared_epoch_changes,
+			&self.client,
+			&self.babe_config,
+			current.end_slot(),
+			&self.select_chain,
+		)?;
+
+		// if the next epoch hasn't been announced yet we get the current one back.
+		if next.start_slot() == current.end_slot() {
+			Ok(Some(next))
+		} else {
+			Ok(None)
+		}
+	}
+
+	fn viable_epochs(&self) -> Result<Vec<EpochTreeNode<B::Hash, NumberFor<B>>>, Error> {
+		let epoch_changes = self.shared_epoch_changes.lock();
+		let is_descendent_of = descendent_query(&*self.client).build_is_descendent_of(None);
+
+		let mut nodes = Vec::new();
+		// the nodes on the path from the root to the last visited node. since the tree is
+		// traversed in pre-order, the parent of a node is the deepest one it descends from.
+		let mut path: Vec<B::Hash> = Vec::new();
+
+		for (hash, number, header) in epoch_changes.tree().iter() {
+			while let Some(ancestor) = path.last() {
+				let is_descendent = is_descendent_of(ancestor, hash)
+					.map_err(|e| Error::Consensus(ConsensusError::ChainLookup(format!("{:?}", e))))?;
+
+				if is_descendent {
+					break;
+				}
+
+				path.pop();
+			}
+
+			let positions: &[EpochIdentifierPosition] = match header {
+				PersistedEpochHeader::Genesis(..) =>
+					&[EpochIdentifierPosition::Genesis0, EpochIdentifierPosition::Genesis1],
+				PersistedEpochHeader::Regular(..) => &[EpochIdentifierPosition::Regular],
+			};
+
+			let epochs = positions.iter()
+				.filter_map(|position| epoch_changes.epoch(&EpochIdentifier {
+					position: *position,
+					hash: *hash,
+					number: *number,
+				}))
+				.map(EpochInfo::from)
+				.collect();
+
+			nodes.push(EpochTreeNode {
+				hash: *hash,
+				number: *number,
+				parent: path.last().cloned(),
+				epochs,
+			});
+			path.push(*hash);
+		}
+
+		Ok(nodes)
+	}
+
+	fn best_slot_authors(
+		&self,
+		count: u64,
+	) -> Result<Vec<SlotAuthorship<B::Hash, NumberFor<B>>>, Error> {
+		if count > MAX_SLOT_AUTHORS_COUNT {
+			return Err(Error::StringError(format!(
+				"Cannot inspect more than {} slots, {} were requested",
+				MAX_SLOT_AUTHORS_COUNT,
+				count,
+			)));
+		}
+
+		let mut header = self.select_chain.best_chain()?;
+		if header.number().is_zero() {
+			return Ok(Vec::new());
+		}
+
+		let best_slot = find_pre_digest::<B>(&header)?.slot_number();
+		let first_slot = (best_slot + 1).saturating_sub(count);
+
+		let mut blocks = HashMap::new();
+		while !header.number().is_zero() {
+			let pre_digest = find_pre_digest::<B>(&header)?;
+			let slot_number = pre_digest.slot_number();
+			if slot_number < first_slot {
+				break;
+			}
+
+			let parent = self.client.header(BlockId::Hash(*header.parent_hash()))
+				.map_err(|e| Error::Consensus(ConsensusError::ChainLookup(format!("{:?}", e))))?
+				.ok_or_else(|| Error::StringError(
+					format!("Missing parent of block {:?}", header.hash()),
+				))?;
+
+			let epoch = self.shared_epoch_changes.lock().epoch_data_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot_number,
+				|slot| Epoch::genesis(&self.babe_config, slot),
+			)
+				.map_err(|e| Error::Consensus(ConsensusError::ChainLookup(format!("{:?}", e))))?;
+
+			let authority_index = pre_digest.authority_index();
+			let author = epoch.and_then(|epoch| {
+				epoch.authorities.get(authority_index as usize).map(|(id, _)| id.clone())
+			});
+
+			blocks.insert(slot_number, AuthoredBlock {
+				hash: header.hash(),
+				number: *header.number(),
+				author,
+				authority_index,
+				claim: SlotClaim::from(&pre_digest),
+			});
+
+			header = parent;
+		}
+
+		Ok((first_slot..=best_slot)
+			.map(|slot_number| SlotAuthorship {
+				slot_number,
+				block: blocks.remove(&slot_number),
+			})
+			.collect())
+	}
+}
+
+impl<B, C, SC> BabeApi<B::Hash, NumberFor<B>> for BabeRpcHandler<B, C, SC>
 	where
 		B: BlockT,
 		C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
@@ -167,6 +359,24 @@ impl<B, C, SC> BabeApi for BabeRpcHandler<B, C, SC>
 
 		Box::new(future.compat())
 	}
+
+	fn current_epoch(&self) -> RpcResult<EpochInfo> {
+		Ok(EpochInfo::from(&self.best_epoch()?))
+	}
+
+	fn next_epoch(&self) -> RpcResult<Option<EpochInfo>> {
+		Ok(self.next_best_epoch()?.as_ref().map(EpochInfo::from))
+	}
+
+	fn epoch_tree(&self) -> RpcResult<Vec<EpochTreeNode<B::Hash, NumberFor<B>>>> {
+		Ok(self.viable_epochs()?)
+	}
+
+	fn slot_authors(&self, count: u64) -> RpcResult<Vec<SlotAuthorship<B::Hash, NumberFor<B>>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(self.best_slot_authors(count)?)
+	}
 }
 
 /// Holds information about the `slot_number`'s that can be claimed by a given key.
@@ -180,6 +390,129 @@ pub struct EpochAuthorship {
 	secondary_vrf: Vec<u64>,
 }
 
+/// Describes a BABE epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EpochInfo {
+	/// The epoch index.
+	pub epoch_index: u64,
+	/// The first slot of the epoch.
+	pub start_slot: SlotNumber,
+	/// The number of slots in the epoch.
+	pub duration: SlotNumber,
+	/// The authorities of the epoch and their weights.
+	pub authorities: Vec<(AuthorityId, BabeAuthorityWeight)>,
+	/// The randomness of the epoch.
+	pub randomness: H256,
+	/// The configuration of the epoch.
+	pub config: EpochConfiguration,
+}
+
+impl<'a> From<&'a Epoch> for EpochInfo {
+	fn from(epoch: &'a Epoch) -> Self {
+		EpochInfo {
+			epoch_index: epoch.epoch_index,
+			start_slot: epoch.start_slot,
+			duration: epoch.duration,
+			authorities: epoch.authorities.clone(),
+			randomness: H256::from(epoch.randomness),
+			config: EpochConfiguration {
+				c: epoch.config.c,
+				allowed_slots: epoch.config.allowed_slots.into(),
+			},
+		}
+	}
+}
+
+/// The configuration of a BABE epoch.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EpochConfiguration {
+	/// The constant used in the primary slot threshold, as a rational.
+	pub c: (u64, u64),
+	/// The types of slots that can be claimed.
+	pub allowed_slots: AllowedSlots,
+}
+
+/// The types of slots that can be claimed in an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AllowedSlots {
+	/// Only primary slots.
+	PrimarySlots,
+	/// Primary and secondary plain slots.
+	PrimaryAndSecondaryPlainSlots,
+	/// Primary and secondary VRF slots.
+	PrimaryAndSecondaryVRFSlots,
+}
+
+impl From<BabeAllowedSlots> for AllowedSlots {
+	fn from(allowed_slots: BabeAllowedSlots) -> Self {
+		match allowed_slots {
+			BabeAllowedSlots::PrimarySlots => AllowedSlots::PrimarySlots,
+			BabeAllowedSlots::PrimaryAndSecondaryPlainSlots =>
+				AllowedSlots::PrimaryAndSecondaryPlainSlots,
+			BabeAllowedSlots::PrimaryAndSecondaryVRFSlots =>
+				AllowedSlots::PrimaryAndSecondaryVRFSlots,
+		}
+	}
+}
+
+/// A node of the epoch fork tree.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EpochTreeNode<Hash, Number> {
+	/// Hash of the block that announced the epochs.
+	pub hash: Hash,
+	/// Number of the block that announced the epochs.
+	pub number: Number,
+	/// Hash of the parent node in the tree, `None` for roots.
+	pub parent: Option<Hash>,
+	/// The epochs announced by the block, genesis nodes hold the first two epochs.
+	pub epochs: Vec<EpochInfo>,
+}
+
+/// The block authored in a slot, if any.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SlotAuthorship<Hash, Number> {
+	/// The slot number.
+	pub slot_number: SlotNumber,
+	/// The block authored in the slot, `None` if the slot was missed.
+	pub block: Option<AuthoredBlock<Hash, Number>>,
+}
+
+/// A block of the best chain and its author.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuthoredBlock<Hash, Number> {
+	/// Hash of the block.
+	pub hash: Hash,
+	/// Number of the block.
+	pub number: Number,
+	/// The author of the block, `None` if its epoch is no longer known.
+	pub author: Option<AuthorityId>,
+	/// Index of the author in the authorities of the epoch.
+	pub authority_index: AuthorityIndex,
+	/// The type of claim the slot was authored with.
+	pub claim: SlotClaim,
+}
+
+/// The type of claim a slot was authored with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SlotClaim {
+	/// A primary VRF-based claim.
+	Primary,
+	/// A secondary round-robin claim.
+	SecondaryPlain,
+	/// A secondary round-robin claim with a VRF output.
+	SecondaryVRF,
+}
+
+impl<'a> From<&'a PreDigest> for SlotClaim {
+	fn from(pre_digest: &'a PreDigest) -> Self {
+		match pre_digest {
+			PreDigest::Primary(..) => SlotClaim::Primary,
+			PreDigest::SecondaryPlain(..) => SlotClaim::SecondaryPlain,
+			PreDigest::SecondaryVRF(..) => SlotClaim::SecondaryVRF,
+		}
+	}
+}
+
 /// Errors encountered by the RPC
 #[derive(Debug, derive_more::Display, derive_more::From)]
 pub enum Error {
@@ -224,12 +557,23 @@ fn epoch_data<B, C, SC>(
 		.ok_or(Error::Consensus(ConsensusError::InvalidAuthoritiesSet))
 }
 
+/// extracts the BABE pre-digest of a header.
+fn find_pre_digest<B: BlockT>(header: &B::Header) -> Result<PreDigest, Error> {
+	header.digest().logs().iter()
+		.filter_map(|log| log.as_babe_pre_digest())
+		.next()
+		.ok_or_else(|| Error::StringError(
+			format!("No BABE pre-runtime digest found in block {:?}", header.hash()),
+		))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use substrate_test_runtime_client::{
-		runtime::Block,
+		runtime::{Block, Header},
 		Backend,
+		BlockBuilderExt,
 		DefaultTestClientBuilderExt,
 		TestClient,
 		TestClientBuilderExt,
@@ -238,11 +582,21 @@ mod tests {
 	use sp_application_crypto::AppPair;
 	use sp_keyring::Ed25519Keyring;
 	use sc_keystore::Store;
-
-	use std::sync::Arc;
-	use sc_consensus_babe::{Config, block_import, AuthorityPair};
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_consensus::{BlockImport, BlockImportParams, BlockOrigin, ImportResult};
+	use sp_core::hashing::twox_128;
+	use sp_runtime::generic::{Digest, DigestItem};
+	use codec::Encode;
+
+	use std::{any::Any, borrow::Cow, sync::Arc};
+	use sc_consensus_babe::{
+		Config, block_import, AuthorityPair, BabeBlockImport, BabeIntermediate, ConsensusLog,
+		NextEpochDescriptor, SecondaryPlainPreDigest, BABE_ENGINE_ID, INTERMEDIATE_KEY,
+	};
 	use jsonrpc_core::IoHandler;
 
+	type TestBabeBlockImport = BabeBlockImport<Block, TestClient, Arc<TestClient>>;
+
 	/// creates keystore backed by a temp file
 	fn create_temp_keystore<P: AppPair>(authority: Ed25519Keyring) -> (KeyStorePtr, tempfile::TempDir) {
 		let keystore_path = tempfile::tempdir().expect("Creates keystore path");
@@ -279,6 +633,133 @@ mod tests {
 		)
 	}
 
+	/// the storage key of a value of the BABE pallet.
+	fn babe_storage_key(name: &[u8]) -> Vec<u8> {
+		let mut key = twox_128(b"Babe").to_vec();
+		key.extend_from_slice(&twox_128(name));
+		key
+	}
+
+	/// a client fed through a BABE block import.
+	struct TestChain {
+		client: Arc<TestClient>,
+		longest_chain: sc_consensus::LongestChain<Backend, Block>,
+		block_import: TestBabeBlockImport,
+		epoch_changes: SharedEpochChanges<Block, Epoch>,
+		config: Config,
+	}
+
+	impl TestChain {
+		/// the test runtime never runs the BABE pallet, so its genesis slot is set to 1,
+		/// the slot of the first imported block.
+		fn new() -> Self {
+			let builder = TestClientBuilder::new()
+				.add_extra_storage(babe_storage_key(b"GenesisSlot"), 1u64.encode());
+			let (client, longest_chain) = builder.build_with_longest_chain();
+			let client = Arc::new(client);
+			let config = Config::get_or_compute(&*client).expect("config available");
+			let (block_import, link) = block_import(
+				config.clone(),
+				client.clone(),
+				client.clone(),
+			).expect("can initialize block-import");
+
+			let epoch_changes = link.epoch_changes().clone();
+
+			TestChain { client, longest_chain, block_import, epoch_changes, config }
+		}
+
+		fn io_handler(&self) -> IoHandler {
+			let mut io = IoHandler::new();
+			io.extend_with(BabeApi::to_delegate(BabeRpcHandler::new(
+				self.client.clone(),
+				self.epoch_changes.clone(),
+				create_temp_keystore::<AuthorityPair>(Ed25519Keyring::Alice).0,
+				self.config.clone(),
+				self.longest_chain.clone(),
+				DenyUnsafe::No,
+			)));
+			io
+		}
+
+		fn genesis(&self) -> Header {
+			self.client.header(BlockId::Number(0)).unwrap().unwrap()
+		}
+
+		/// imports a block claimed by the given authority in the given slot, announcing the
+		/// next epoch if it's the first block of its epoch. `storage` is written by the block.
+		fn import_block_at_slot(
+			&mut self,
+			parent: &Header,
+			slot_number: SlotNumber,
+			authority_index: AuthorityIndex,
+			storage: Vec<(Vec<u8>, Vec<u8>)>,
+		) -> Header {
+			let epoch_descriptor = self.epoch_changes.lock().epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot_number,
+			).unwrap().unwrap();
+
+			let parent_slot = if parent.number().is_zero() {
+				0
+			} else {
+				find_pre_digest::<Block>(parent).unwrap().slot_number()
+			};
+
+			let mut logs = vec![
+				DigestItem::babe_pre_digest(PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+					authority_index,
+					slot_number,
+				})),
+			];
+			if parent_slot < epoch_descriptor.start_slot() {
+				// the test runtime doesn't announce epochs, keep the genesis authorities forever.
+				let next_epoch = ConsensusLog::NextEpochData(NextEpochDescriptor {
+					authorities: self.config.genesis_authorities.clone(),
+					randomness: self.config.randomness,
+				});
+				logs.push(DigestItem::Consensus(BABE_ENGINE_ID, next_epoch.encode()));
+			}
+
+			let mut builder = self.client.new_block_at(
+				&BlockId::Hash(parent.hash()),
+				Digest { logs },
+				false,
+			).unwrap();
+			for (key, value) in storage {
+				builder.push_storage_change(key, Some(value)).unwrap();
+			}
+			let block = builder.build().unwrap().block;
+
+			let mut import = BlockImportParams::new(BlockOrigin::Own, block.header.clone());
+			import.body = Some(block.extrinsics);
+			import.intermediates.insert(
+				Cow::from(INTERMEDIATE_KEY),
+				Box::new(BabeIntermediate::<Block> { epoch_descriptor }) as Box<dyn Any>,
+			);
+
+			match self.block_import.import_block(import, Default::default()).unwrap() {
+				ImportResult::Imported(_) => {},
+				_ => panic!("expected block to be imported"),
+			}
+
+			block.header
+		}
+	}
+
+	fn request<T: serde::de::DeserializeOwned>(io: &IoHandler, method: &str, params: &str) -> T {
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params": [{}],"id":1}}"#,
+			method,
+			params,
+		);
+		let response = io.handle_request_sync(&request).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		serde_json::from_value(response["result"].take()).unwrap()
+	}
+
 	#[test]
 	fn epoch_authorship_works() {
 		let handler = test_babe_rpc_handler(DenyUnsafe::No);
@@ -305,4 +786,174 @@ mod tests {
 
 		assert_eq!(error, RpcError::method_not_found())
 	}
+
+	#[test]
+	fn current_epoch_works() {
+		let handler = test_babe_rpc_handler(DenyUnsafe::Yes);
+		let mut io = IoHandler::new();
+
+		io.extend_with(BabeApi::to_delegate(handler));
+		let request = r#"{"jsonrpc":"2.0","method":"babe_currentEpoch","params": [],"id":1}"#;
+
+		let response = io.handle_request_sync(request).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let epoch: EpochInfo = serde_json::from_value(response["result"].take()).unwrap();
+
+		assert_eq!(epoch.epoch_index, 0);
+		assert_eq!(epoch.start_slot, 0);
+		assert_eq!(epoch.duration, 6);
+		assert_eq!(epoch.authorities.len(), 3);
+		assert_eq!(epoch.config.allowed_slots, AllowedSlots::PrimaryAndSecondaryPlainSlots);
+	}
+
+	#[test]
+	fn next_epoch_and_epoch_tree_are_empty_at_genesis() {
+		let handler = test_babe_rpc_handler(DenyUnsafe::Yes);
+		let mut io = IoHandler::new();
+
+		io.extend_with(BabeApi::to_delegate(handler));
+
+		let request = r#"{"jsonrpc":"2.0","method":"babe_nextEpoch","params": [],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+		assert_eq!(Some(response.into()), io.handle_request_sync(request));
+
+		let request = r#"{"jsonrpc":"2.0","method":"babe_epochTree","params": [],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+		assert_eq!(Some(response.into()), io.handle_request_sync(request));
+	}
+
+	#[test]
+	fn slot_authors_is_unsafe() {
+		let handler = test_babe_rpc_handler(DenyUnsafe::Yes);
+		let mut io = IoHandler::new();
+
+		io.extend_with(BabeApi::to_delegate(handler));
+		let request = r#"{"jsonrpc":"2.0","method":"babe_slotAuthors","params": [10],"id":1}"#;
+
+		let response = io.handle_request_sync(request).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let error: RpcError = serde_json::from_value(response["error"].take()).unwrap();
+
+		assert_eq!(error, RpcError::method_not_found())
+	}
+
+	#[test]
+	fn slot_authors_limits_count() {
+		let handler = test_babe_rpc_handler(DenyUnsafe::No);
+		let mut io = IoHandler::new();
+
+		io.extend_with(BabeApi::to_delegate(handler));
+
+		let request = r#"{"jsonrpc":"2.0","method":"babe_slotAuthors","params": [10],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+		assert_eq!(Some(response.into()), io.handle_request_sync(request));
+
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"babe_slotAuthors","params": [{}],"id":1}}"#,
+			MAX_SLOT_AUTHORS_COUNT + 1,
+		);
+		let response = io.handle_request_sync(&request).unwrap();
+		let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		assert_eq!(response["error"]["code"], 1234);
+	}
+
+	#[test]
+	fn epochs_follow_the_imported_chain() {
+		let mut chain = TestChain::new();
+		let io = chain.io_handler();
+
+		// block #1 starts the genesis epoch and announces the next one.
+		let genesis = chain.genesis();
+		let block_1 = chain.import_block_at_slot(&genesis, 1, 0, Vec::new());
+		let block_2 = chain.import_block_at_slot(&block_1, 2, 0, Vec::new());
+		// slots 3 and 4 are missed.
+		let block_3 = chain.import_block_at_slot(&block_2, 5, 0, Vec::new());
+
+		let current: EpochInfo = request(&io, "babe_currentEpoch", "");
+		assert_eq!((current.epoch_index, current.start_slot, current.duration), (0, 1, 6));
+
+		let next: Option<EpochInfo> = request(&io, "babe_nextEpoch", "");
+		let next = next.expect("block #1 announces the next epoch");
+		assert_eq!((next.epoch_index, next.start_slot), (1, 7));
+
+		// the first block of epoch 1 comes after the missed slots 6 to 8 and announces epoch 2,
+		// the runtime enacts epoch 1 in it.
+		let block_4 = chain.import_block_at_slot(
+			&block_3,
+			9,
+			0,
+			vec![(babe_storage_key(b"EpochIndex"), 1u64.encode())],
+		);
+		chain.import_block_at_slot(&block_4, 10, 0, Vec::new());
+
+		let current: EpochInfo = request(&io, "babe_currentEpoch", "");
+		assert_eq!((current.epoch_index, current.start_slot), (1, 7));
+
+		let next: Option<EpochInfo> = request(&io, "babe_nextEpoch", "");
+		let next = next.expect("block #4 announces the next epoch");
+		assert_eq!((next.epoch_index, next.start_slot), (2, 13));
+		assert_eq!(next.authorities, chain.config.genesis_authorities);
+
+		let tree: Vec<EpochTreeNode<H256, u64>> = request(&io, "babe_epochTree", "");
+		assert_eq!(tree.len(), 2);
+
+		assert_eq!((tree[0].hash, tree[0].number, tree[0].parent), (block_1.hash(), 1, None));
+		assert_eq!(
+			tree[0].epochs.iter().map(|e| (e.epoch_index, e.start_slot)).collect::<Vec<_>>(),
+			vec![(0, 1), (1, 7)],
+		);
+
+		assert_eq!(
+			(tree[1].hash, tree[1].number, tree[1].parent),
+			(block_4.hash(), 4, Some(block_1.hash())),
+		);
+		assert_eq!(
+			tree[1].epochs.iter().map(|e| (e.epoch_index, e.start_slot)).collect::<Vec<_>>(),
+			vec![(2, 13)],
+		);
+	}
+
+	#[test]
+	fn slot_authors_reports_missed_slots_across_an_epoch_change() {
+		let mut chain = TestChain::new();
+		let io = chain.io_handler();
+
+		// blocks are authored in slots 1, 2 and 4 of the genesis epoch, and slots 8 and 9 of
+		// the next one, which starts at slot 7.
+		let mut parent = chain.genesis();
+		let mut blocks = HashMap::new();
+		for &(slot_number, authority_index) in &[(1, 0), (2, 1), (4, 2), (8, 1), (9, 0)] {
+			parent = chain.import_block_at_slot(&parent, slot_number, authority_index, Vec::new());
+			blocks.insert(slot_number, AuthoredBlock {
+				hash: parent.hash(),
+				number: *parent.number(),
+				author: Some(chain.config.genesis_authorities[authority_index as usize].0.clone()),
+				authority_index,
+				claim: SlotClaim::SecondaryPlain,
+			});
+		}
+
+		let expected = |slots: std::ops::RangeInclusive<SlotNumber>| slots
+			.map(|slot_number| SlotAuthorship {
+				slot_number,
+				block: blocks.get(&slot_number).cloned(),
+			})
+			.collect::<Vec<_>>();
+
+		let authors: Vec<SlotAuthorship<H256, u64>> = request(&io, "babe_slotAuthors", "9");
+		assert_eq!(authors, expected(1..=9));
+		assert_eq!(
+			authors.iter().filter(|slot| slot.block.is_none()).map(|slot| slot.slot_number)
+				.collect::<Vec<_>>(),
+			vec![3, 5, 6, 7],
+		);
+
+		// only the last slots of the best chain are inspected.
+		let authors: Vec<SlotAuthorship<H256, u64>> = request(&io, "babe_slotAuthors", "3");
+		assert_eq!(authors, expected(7..=9));
+
+		// slots before block #1 have no block either.
+		let authors: Vec<SlotAuthorship<H256, u64>> = request(&io, "babe_slotAuthors", "20");
+		assert_eq!(authors, expected(0..=9));
+	}
 }
@@ -67,7 +67,7 @@ pub use sp_consensus_babe::{
 	BabeApi, ConsensusLog, BABE_ENGINE_ID, SlotNumber,
 	BabeEpochConfiguration, BabeGenesisConfiguration,
 	AuthorityId, AuthorityPair, AuthoritySignature,
-	BabeAuthorityWeight, VRF_OUTPUT_LENGTH,
+	BabeAuthorityWeight, BabeBlockWeight, VRF_OUTPUT_LENGTH,
 	digests::{
 		CompatibleDigestItem, NextEpochDescriptor, NextConfigDescriptor, PreDigest,
 		PrimaryPreDigest, SecondaryPlainPreDigest,
@@ -99,6 +99,7 @@ use sp_consensus::{
 	SelectChain, SlotData,
 };
 use sp_consensus_babe::inherents::BabeInherentData;
+use sc_consensus::fork_choice;
 use sp_timestamp::{TimestampInherentData, InherentType as TimestampInherent};
 use sp_consensus::import_queue::{Verifier, BasicQueue, CacheKeyId};
 use sc_client_api::{
@@ -289,8 +290,9 @@ impl Config {
 			let has_api_v1 = a.has_api_with::<dyn BabeApi<B, Error = sp_blockchain::Error>, _>(
 				&b, |v| v == 1,
 			)?;
+			// version 3 only added equivocation reporting, the configuration is unchanged.
 			let has_api_v2 = a.has_api_with::<dyn BabeApi<B, Error = sp_blockchain::Error>, _>(
-				&b, |v| v == 2,
+				&b, |v| v >= 2,
 			)?;
 
 			if has_api_v1 {
@@ -731,8 +733,9 @@ pub struct BabeVerifier<Block: BlockT, Client> {
 impl<Block, Client> BabeVerifier<Block, Client>
 	where
 		Block: BlockT,
-		Client: HeaderBackend<Block> + HeaderMetadata<Block> + ProvideRuntimeApi<Block>,
-		Client::Api: BlockBuilderApi<Block, Error = sp_blockchain::Error>,
+		Client: AuxStore + HeaderBackend<Block> + HeaderMetadata<Block> + ProvideRuntimeApi<Block>,
+		Client::Api: BlockBuilderApi<Block, Error = sp_blockchain::Error>
+			+ BabeApi<Block, Error = sp_blockchain::Error>,
 {
 	fn check_inherents(
 		&self,
@@ -757,6 +760,71 @@ impl<Block, Client> BabeVerifier<Block, Client>
 			Ok(())
 		}
 	}
+
+	fn check_and_report_equivocation(
+		&self,
+		slot_now: SlotNumber,
+		slot: SlotNumber,
+		header: &Block::Header,
+		author: &AuthorityId,
+		origin: &BlockOrigin,
+	) -> Result<(), Error<Block>> {
+		// don't report any equivocations during initial sync
+		// as they are most likely stale.
+		if *origin == BlockOrigin::NetworkInitialSync {
+			return Ok(());
+		}
+
+		// check if authorship of this header is an equivocation and return a proof if so.
+		let equivocation_proof =
+			match check_equivocation(&*self.client, slot_now, slot, header, author)
+				.map_err(Error::Client)?
+			{
+				Some(proof) => proof,
+				None => return Ok(()),
+			};
+
+		info!(
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			author,
+			slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		// we generate the key ownership proof and submit the report on top of
+		// the best block, the session of the equivocation must still be live
+		// there for the proof to be generated.
+		let best_id = BlockId::Hash(self.client.info().best_hash);
+
+		let key_owner_proof = match self.client.runtime_api()
+			.generate_key_ownership_proof(
+				&best_id,
+				slot,
+				equivocation_proof.offender.clone(),
+			)
+			.map_err(Error::Client)?
+		{
+			Some(proof) => proof,
+			None => {
+				debug!(target: "babe", "Equivocation offender is not part of the authority set.");
+				return Ok(());
+			}
+		};
+
+		// submit equivocation report at best block.
+		self.client.runtime_api()
+			.submit_report_equivocation_unsigned_extrinsic(
+				&best_id,
+				equivocation_proof,
+				key_owner_proof,
+			)
+			.map_err(Error::Client)?;
+
+		info!(target: "babe", "Submitted equivocation report for author {:?}", author);
+
+		Ok(())
+	}
 }
 
 impl<Block, Client> Verifier<Block> for BabeVerifier<Block, Client> where
@@ -830,21 +898,16 @@ impl<Block, Client> Verifier<Block> for BabeVerifier<Block, Client> where
 				let author = verified_info.author;
 
 				// the header is valid but let's check if there was something else already
-				// proposed at the same slot by the given author
-				if let Some(equivocation_proof) = check_equivocation(
-					&*self.client,
+				// proposed at the same slot by the given author. if there was, we will
+				// report the equivocation to the runtime.
+				if let Err(err) = self.check_and_report_equivocation(
 					slot_now,
-					babe_pre_digest.slot_number(),
+					slot_number,
 					&header,
 					&author,
-				).map_err(|e| e.to_string())? {
-					info!(
-						"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
-						author,
-						babe_pre_digest.slot_number(),
-						equivocation_proof.fst_header().hash(),
-						equivocation_proof.snd_header().hash(),
-					);
+					&origin,
+				) {
+					warn!(target: "babe", "Error checking/reporting BABE equivocation: {:?}", err);
 				}
 
 				// if the body is passed through, we need to use the runtime
@@ -925,6 +988,7 @@ pub struct BabeBlockImport<Block: BlockT, Client, I> {
 	client: Arc<Client>,
 	epoch_changes: SharedEpochChanges<Block, Epoch>,
 	config: Config,
+	fork_choice: Arc<dyn fork_choice::ForkChoiceStrategy<Block, BabeBlockWeight>>,
 }
 
 impl<Block: BlockT, I: Clone, Client> Clone for BabeBlockImport<Block, Client, I> {
@@ -934,6 +998,7 @@ impl<Block: BlockT, I: Clone, Client> Clone for BabeBlockImport<Block, Client, I
 			client: self.client.clone(),
 			epoch_changes: self.epoch_changes.clone(),
 			config: self.config.clone(),
+			fork_choice: self.fork_choice.clone(),
 		}
 	}
 }
@@ -950,8 +1015,20 @@ impl<Block: BlockT, Client, I> BabeBlockImport<Block, Client, I> {
 			inner: block_import,
 			epoch_changes,
 			config,
+			fork_choice: Arc::new(fork_choice::HeaviestChainRule::new()),
 		}
 	}
+
+	/// Use the given fork-choice rule instead of picking the heaviest chain,
+	/// i.e. the one with the most primary blocks. The weight passed to the rule
+	/// is the number of primary blocks in the chain.
+	pub fn with_fork_choice(
+		mut self,
+		fork_choice: Arc<dyn fork_choice::ForkChoiceStrategy<Block, BabeBlockWeight>>,
+	) -> Self {
+		self.fork_choice = fork_choice;
+		self
+	}
 }
 
 impl<Block, Client, Inner> BlockImport<Block> for BabeBlockImport<Block, Client, Inner> where
@@ -1158,9 +1235,9 @@ impl<Block, Client, Inner> BlockImport<Block> for BabeBlockImport<Block, Client,
 			),
 		);
 
-		// The fork choice rule is that we pick the heaviest chain (i.e.
-		// more primary blocks), if there's a tie we go with the longest
-		// chain.
+		// The fork choice rule is pluggable, by default we pick the heaviest
+		// chain (i.e. more primary blocks), if there's a tie we go with the
+		// longest chain.
 		block.fork_choice = {
 			let (last_best, last_best_number) = (info.best_hash, info.best_number);
 
@@ -1176,13 +1253,15 @@ impl<Block, Client, Inner> BlockImport<Block> for BabeBlockImport<Block, Client,
 					)?
 			};
 
-			Some(ForkChoiceStrategy::Custom(if total_weight > last_best_weight {
-				true
-			} else if total_weight == last_best_weight {
-				number > last_best_number
-			} else {
-				false
-			}))
+			let best = fork_choice::BestBlock {
+				hash: last_best,
+				number: last_best_number,
+				weight: last_best_weight,
+			};
+
+			Some(ForkChoiceStrategy::Custom(
+				self.fork_choice.is_new_best(&block.header, &total_weight, &best)?
+			))
 		};
 
 		let import_result = self.inner.import_block(block, new_cache);
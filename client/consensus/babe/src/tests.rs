@@ -840,3 +840,14 @@ fn babe_transcript_generation_match() {
 	};
 	debug_assert!(test(orig_transcript) == test(transcript_from_data(new_transcript)));
 }
+
+#[test]
+fn config_is_computed_for_the_current_runtime_api() {
+	let client = substrate_test_runtime_client::new();
+	let config = Config::get_or_compute(&client).expect("config available");
+
+	assert_eq!(config.slot_duration, 1000);
+	assert_eq!(config.epoch_length, 6);
+	assert_eq!(config.genesis_authorities.len(), 3);
+	assert_eq!(config.allowed_slots, AllowedSlots::PrimaryAndSecondaryPlainSlots);
+}
@@ -0,0 +1,273 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Pluggable fork-choice rules.
+//!
+//! Consensus engines consult a [`ForkChoiceStrategy`] when importing a block to decide whether
+//! it should become the new best block. Each engine defines its own notion of chain weight (e.g.
+//! the number of primary slots in BABE or the total difficulty in PoW), engines without one use
+//! `()`.
+
+use std::sync::Arc;
+use log::debug;
+use sp_blockchain::HeaderMetadata;
+use sp_consensus::Error as ConsensusError;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor};
+
+/// The current best block, as seen by a fork-choice rule.
+#[derive(Debug, Clone)]
+pub struct BestBlock<Block: BlockT, Weight> {
+	/// Hash of the best block.
+	pub hash: Block::Hash,
+	/// Number of the best block.
+	pub number: NumberFor<Block>,
+	/// Weight of the chain ending at the best block.
+	pub weight: Weight,
+}
+
+/// A rule deciding whether a block being imported should become the new best block.
+pub trait ForkChoiceStrategy<Block: BlockT, Weight>: Send + Sync {
+	/// Whether the block with the given `header`, whose chain has the given `weight`, should
+	/// replace `best` as the best block.
+	fn is_new_best(
+		&self,
+		header: &Block::Header,
+		weight: &Weight,
+		best: &BestBlock<Block, Weight>,
+	) -> Result<bool, ConsensusError>;
+}
+
+impl<Block: BlockT, Weight, T: ForkChoiceStrategy<Block, Weight> + ?Sized>
+	ForkChoiceStrategy<Block, Weight> for Arc<T>
+{
+	fn is_new_best(
+		&self,
+		header: &Block::Header,
+		weight: &Weight,
+		best: &BestBlock<Block, Weight>,
+	) -> Result<bool, ConsensusError> {
+		(**self).is_new_best(header, weight, best)
+	}
+}
+
+/// Picks the chain with the highest block number, ignoring weights.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LongestChainRule;
+
+impl<Block: BlockT, Weight> ForkChoiceStrategy<Block, Weight> for LongestChainRule {
+	fn is_new_best(
+		&self,
+		header: &Block::Header,
+		_weight: &Weight,
+		best: &BestBlock<Block, Weight>,
+	) -> Result<bool, ConsensusError> {
+		Ok(header.number() > &best.number)
+	}
+}
+
+/// Picks the chain with the highest weight.
+///
+/// By default ties are broken in favour of the longest chain, a strict rule keeps the current
+/// best block instead.
+#[derive(Debug, Clone, Copy)]
+pub struct HeaviestChainRule {
+	longest_on_tie: bool,
+}
+
+impl HeaviestChainRule {
+	/// Create a heaviest chain rule that breaks ties by block number.
+	pub fn new() -> Self {
+		HeaviestChainRule { longest_on_tie: true }
+	}
+
+	/// Create a heaviest chain rule that only switches to strictly heavier chains.
+	pub fn strict() -> Self {
+		HeaviestChainRule { longest_on_tie: false }
+	}
+}
+
+impl Default for HeaviestChainRule {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<Block: BlockT, Weight: Ord> ForkChoiceStrategy<Block, Weight> for HeaviestChainRule {
+	fn is_new_best(
+		&self,
+		header: &Block::Header,
+		weight: &Weight,
+		best: &BestBlock<Block, Weight>,
+	) -> Result<bool, ConsensusError> {
+		Ok(if weight > &best.weight {
+			true
+		} else if weight == &best.weight {
+			self.longest_on_tie && header.number() > &best.number
+		} else {
+			false
+		})
+	}
+}
+
+/// Wraps another rule and refuses to switch to any fork that would revert more than `max_depth`
+/// blocks of the current best chain.
+pub struct DepthLimited<Client, Inner> {
+	client: Arc<Client>,
+	max_depth: u32,
+	inner: Inner,
+}
+
+impl<Client, Inner> DepthLimited<Client, Inner> {
+	/// Create a new depth limited rule deferring to `inner` for forks within `max_depth`.
+	pub fn new(client: Arc<Client>, max_depth: u32, inner: Inner) -> Self {
+		DepthLimited { client, max_depth, inner }
+	}
+}
+
+impl<Block, Weight, Client, Inner> ForkChoiceStrategy<Block, Weight> for DepthLimited<Client, Inner> where
+	Block: BlockT,
+	Client: HeaderMetadata<Block, Error = sp_blockchain::Error> + Send + Sync,
+	Inner: ForkChoiceStrategy<Block, Weight>,
+{
+	fn is_new_best(
+		&self,
+		header: &Block::Header,
+		weight: &Weight,
+		best: &BestBlock<Block, Weight>,
+	) -> Result<bool, ConsensusError> {
+		if !self.inner.is_new_best(header, weight, best)? {
+			return Ok(false);
+		}
+
+		let parent_hash = *header.parent_hash();
+		if parent_hash == best.hash {
+			return Ok(true);
+		}
+
+		let route = sp_blockchain::tree_route(&*self.client, best.hash, parent_hash)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?;
+		let depth = route.retracted().len();
+
+		if depth > self.max_depth as usize {
+			debug!(
+				target: "fork-choice",
+				"Not switching to block #{} ({}): reorg of {} blocks exceeds the limit of {}.",
+				header.number(),
+				header.hash(),
+				depth,
+				self.max_depth,
+			);
+
+			return Ok(false);
+		}
+
+		Ok(true)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_consensus::BlockOrigin;
+	use sp_runtime::generic::BlockId;
+	use substrate_test_runtime_client::{
+		prelude::*,
+		runtime::{Block, Header},
+	};
+
+	fn best(header: &Header, weight: u32) -> BestBlock<Block, u32> {
+		BestBlock { hash: header.hash(), number: *header.number(), weight }
+	}
+
+	#[test]
+	fn longest_chain_rule_ignores_weight() {
+		let client = substrate_test_runtime_client::new();
+		let genesis = client.header(&BlockId::Number(0)).unwrap().unwrap();
+
+		let mut child = genesis.clone();
+		child.number = 1;
+
+		assert!(LongestChainRule.is_new_best(&child, &0, &best(&genesis, 10)).unwrap());
+		assert!(!LongestChainRule.is_new_best(&genesis, &10, &best(&child, 0)).unwrap());
+	}
+
+	#[test]
+	fn heaviest_chain_rule_breaks_ties_unless_strict() {
+		let client = substrate_test_runtime_client::new();
+		let genesis = client.header(&BlockId::Number(0)).unwrap().unwrap();
+
+		let mut child = genesis.clone();
+		child.number = 1;
+
+		let rule = HeaviestChainRule::new();
+		assert!(rule.is_new_best(&genesis, &2, &best(&child, 1)).unwrap());
+		assert!(!rule.is_new_best(&child, &1, &best(&genesis, 2)).unwrap());
+		assert!(rule.is_new_best(&child, &1, &best(&genesis, 1)).unwrap());
+
+		assert!(!HeaviestChainRule::strict().is_new_best(&child, &1, &best(&genesis, 1)).unwrap());
+	}
+
+	#[test]
+	fn depth_limited_rejects_deep_reorgs() {
+		let mut client = substrate_test_runtime_client::new();
+
+		// build a canonical chain of 3 blocks
+		let mut canonical = Vec::new();
+		for _ in 0..3 {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			client.import(BlockOrigin::Own, block.clone()).unwrap();
+			canonical.push(block.header);
+		}
+
+		// and a competing block on top of genesis, which differs from the first
+		// canonical block by including an extrinsic
+		let mut builder = client.new_block_at(
+			&BlockId::Number(0),
+			Default::default(),
+			false,
+		).unwrap();
+		builder.push_storage_change(vec![1], Some(vec![2])).unwrap();
+		let fork = builder.build().unwrap().block;
+		client.import(BlockOrigin::Own, fork.clone()).unwrap();
+
+		let mut candidate = fork.header.clone();
+		candidate.number = 2;
+		candidate.parent_hash = fork.header.hash();
+
+		let client = Arc::new(client);
+		let tip = best(&canonical[2], 0);
+
+		// switching to the fork reverts all three canonical blocks
+		let rule = DepthLimited::new(client.clone(), 2, HeaviestChainRule::new());
+		assert!(!rule.is_new_best(&candidate, &1, &tip).unwrap());
+
+		let rule = DepthLimited::new(client.clone(), 3, HeaviestChainRule::new());
+		assert!(rule.is_new_best(&candidate, &1, &tip).unwrap());
+
+		// extending the best block is never limited
+		let mut child = canonical[2].clone();
+		child.number = 4;
+		child.parent_hash = canonical[2].hash();
+		let rule = DepthLimited::new(client, 0, HeaviestChainRule::strict());
+		assert!(rule.is_new_best(&child, &1, &tip).unwrap());
+
+		// and the inner rule is always consulted first
+		assert!(!rule.is_new_best(&child, &0, &tip).unwrap());
+	}
+}
@@ -16,5 +16,6 @@
 
 //! Collection of common consensus specific implementations
 mod longest_chain;
+pub mod fork_choice;
 
 pub use longest_chain::LongestChain;
@@ -0,0 +1,143 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BABE consensus data provider
+
+use super::ConsensusDataProvider;
+use crate::Error;
+use std::{any::Any, borrow::Cow, sync::Arc};
+use sc_client_api::AuxStore;
+use sc_consensus_babe::{
+	Config, Epoch, BabeIntermediate, INTERMEDIATE_KEY, CompatibleDigestItem, authorship,
+};
+use sc_consensus_epochs::{SharedEpochChanges, ViableEpochDescriptor, descendent_query};
+use sc_keystore::KeyStorePtr;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::{HeaderBackend, HeaderMetadata};
+use sp_consensus::BlockImportParams;
+use sp_consensus_babe::{BabeApi, SlotNumber};
+use sp_inherents::InherentData;
+use sp_runtime::{
+	generic::Digest,
+	traits::{Block as BlockT, DigestFor, DigestItemFor, Header, NumberFor},
+};
+use sp_timestamp::TimestampInherentData;
+
+/// Provides BABE-compatible pre-digests and block import params, so that manual seal can
+/// author blocks for BABE runtimes.
+///
+/// The slot of a block is derived from its timestamp inherent, so that it follows the time
+/// travel of manual seal. Slots are claimed with the authority keys of the keystore, the keystore
+/// therefore needs to hold the key of an authority that is allowed to author every slot.
+pub struct BabeConsensusDataProvider<B: BlockT, C> {
+	/// shared reference to keystore
+	keystore: KeyStorePtr,
+	/// Shared reference to the client.
+	client: Arc<C>,
+	/// Shared epoch changes
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	/// BABE config, gotten from the runtime.
+	config: Config,
+}
+
+impl<B, C> BabeConsensusDataProvider<B, C>
+	where
+		B: BlockT,
+		C: AuxStore + HeaderBackend<B> + HeaderMetadata<B, Error = sp_blockchain::Error>
+			+ ProvideRuntimeApi<B>,
+		C::Api: BabeApi<B, Error = sp_blockchain::Error>,
+{
+	/// Create a new `BabeConsensusDataProvider` sharing the epoch changes of the BABE block import.
+	pub fn new(
+		client: Arc<C>,
+		keystore: KeyStorePtr,
+		epoch_changes: SharedEpochChanges<B, Epoch>,
+	) -> Result<Self, Error> {
+		let config = Config::get_or_compute(&*client)?;
+
+		Ok(Self {
+			config,
+			client,
+			keystore,
+			epoch_changes,
+		})
+	}
+
+	fn slot_number(&self, inherents: &InherentData) -> Result<SlotNumber, Error> {
+		Ok(inherents.timestamp_inherent_data()? / self.config.slot_duration)
+	}
+
+	fn epoch_descriptor(
+		&self,
+		parent: &B::Header,
+		slot_number: SlotNumber,
+	) -> Result<ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>, Error> {
+		self.epoch_changes.lock()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				parent.number().clone(),
+				slot_number,
+			)
+			.map_err(|e| Error::StringError(format!("failed to fetch epoch descriptor: {:?}", e)))?
+			.ok_or_else(|| sp_consensus::Error::InvalidAuthoritiesSet.into())
+	}
+}
+
+impl<B, C> ConsensusDataProvider<B> for BabeConsensusDataProvider<B, C>
+	where
+		B: BlockT,
+		C: AuxStore + HeaderBackend<B> + HeaderMetadata<B, Error = sp_blockchain::Error>
+			+ ProvideRuntimeApi<B> + Send + Sync,
+		C::Api: BabeApi<B, Error = sp_blockchain::Error>,
+{
+	type Transaction = sp_api::TransactionFor<C, B>;
+
+	fn create_digest(&self, parent: &B::Header, inherents: &InherentData) -> Result<DigestFor<B>, Error> {
+		let slot_number = self.slot_number(inherents)?;
+		let epoch_descriptor = self.epoch_descriptor(parent, slot_number)?;
+		let epoch_changes = self.epoch_changes.lock();
+		let epoch = epoch_changes
+			.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+			.ok_or_else(|| Error::from(sp_consensus::Error::InvalidAuthoritiesSet))?;
+
+		let (pre_digest, _) = authorship::claim_slot(slot_number, epoch.as_ref(), &self.keystore)
+			.ok_or_else(|| Error::StringError(format!("failed to claim slot {}", slot_number)))?;
+
+		Ok(Digest {
+			logs: vec![<DigestItemFor<B> as CompatibleDigestItem>::babe_pre_digest(pre_digest)],
+		})
+	}
+
+	fn append_block_import(
+		&self,
+		parent: &B::Header,
+		params: &mut BlockImportParams<B, Self::Transaction>,
+		inherents: &InherentData,
+	) -> Result<(), Error> {
+		let slot_number = self.slot_number(inherents)?;
+		let epoch_descriptor = self.epoch_descriptor(parent, slot_number)?;
+
+		params.intermediates.insert(
+			Cow::from(INTERMEDIATE_KEY),
+			Box::new(BabeIntermediate::<B> { epoch_descriptor }) as Box<dyn Any>,
+		);
+
+		Ok(())
+	}
+}
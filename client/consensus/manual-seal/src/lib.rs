@@ -20,6 +20,7 @@
 //! This is suitable for a testing environment.
 
 use futures::prelude::*;
+use futures_timer::Delay;
 use sp_consensus::{
 	Environment, Proposer, ForkChoiceStrategy, BlockImportParams, BlockOrigin, SelectChain,
 	import_queue::{BasicQueue, CacheKeyId, Verifier, BoxBlockImport},
@@ -29,19 +30,23 @@ use sp_inherents::InherentDataProviders;
 use sp_runtime::{traits::Block as BlockT, Justification};
 use sc_client_api::backend::{Backend as ClientBackend, Finalizer};
 use sc_transaction_pool::txpool;
-use std::{sync::Arc, marker::PhantomData};
+use std::{sync::Arc, marker::PhantomData, time::Duration};
 use prometheus_endpoint::Registry;
 
 mod error;
 mod finalize_block;
+mod revert_to;
 mod seal_new_block;
+pub mod consensus;
 pub mod rpc;
 
 use self::{
 	finalize_block::{finalize_block, FinalizeBlockParams},
+	revert_to::{revert_to, RevertToParams},
 	seal_new_block::{seal_new_block, SealBlockParams},
 };
 pub use self::{
+	consensus::ConsensusDataProvider,
 	error::Error,
 	rpc::{EngineCommand, CreatedBlock},
 };
@@ -87,15 +92,97 @@ pub fn import_queue<Block, Transaction>(
 	)
 }
 
+/// Params required to start the manual sealing authorship task.
+pub struct ManualSealParams<B: BlockT, E, C, CB, A: txpool::ChainApi, SC, CS, T> {
+	/// Block import instance for importing the created blocks.
+	pub block_import: BoxBlockImport<B, T>,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// Client instance
+	pub client: Arc<C>,
+
+	/// Client backend, used to revert blocks.
+	pub backend: Arc<CB>,
+
+	/// Shared reference to the transaction pool.
+	pub pool: Arc<txpool::Pool<A>>,
+
+	/// Stream<Item = EngineCommands>, Basically the receiving end of a channel for sending
+	/// commands to the authorship task.
+	pub commands_stream: CS,
+
+	/// SelectChain strategy.
+	pub select_chain: SC,
+
+	/// Digest provider for inclusion in blocks.
+	pub consensus_data_provider: Option<Box<dyn ConsensusDataProvider<B, Transaction = T>>>,
+
+	/// Provider for inherents to include in blocks.
+	pub inherent_data_providers: InherentDataProviders,
+}
+
+/// Params required to start the instant and interval sealing authorship tasks.
+pub struct AutoSealParams<B: BlockT, E, C, CB, A: txpool::ChainApi, SC, T> {
+	/// Block import instance for importing the created blocks.
+	pub block_import: BoxBlockImport<B, T>,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// Client instance
+	pub client: Arc<C>,
+
+	/// Client backend, used to revert blocks.
+	pub backend: Arc<CB>,
+
+	/// Shared reference to the transaction pool.
+	pub pool: Arc<txpool::Pool<A>>,
+
+	/// SelectChain strategy.
+	pub select_chain: SC,
+
+	/// Digest provider for inclusion in blocks.
+	pub consensus_data_provider: Option<Box<dyn ConsensusDataProvider<B, Transaction = T>>>,
+
+	/// Provider for inherents to include in blocks.
+	pub inherent_data_providers: InherentDataProviders,
+}
+
+impl<B: BlockT, E, C, CB, A: txpool::ChainApi, SC, T> AutoSealParams<B, E, C, CB, A, SC, T> {
+	/// Attach the `commands_stream` the manual sealing authorship task listens to.
+	fn with_commands_stream<CS>(
+		self,
+		commands_stream: CS,
+	) -> ManualSealParams<B, E, C, CB, A, SC, CS, T> {
+		ManualSealParams {
+			block_import: self.block_import,
+			env: self.env,
+			client: self.client,
+			backend: self.backend,
+			pool: self.pool,
+			commands_stream,
+			select_chain: self.select_chain,
+			consensus_data_provider: self.consensus_data_provider,
+			inherent_data_providers: self.inherent_data_providers,
+		}
+	}
+}
+
 /// Creates the background authorship task for the manual seal engine.
-pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
-	mut block_import: BoxBlockImport<B, T>,
-	mut env: E,
-	client: Arc<C>,
-	pool: Arc<txpool::Pool<A>>,
-	mut commands_stream: S,
-	select_chain: SC,
-	inherent_data_providers: InherentDataProviders,
+pub async fn run_manual_seal<B, CB, E, C, A, SC, CS, T>(
+	ManualSealParams {
+		mut block_import,
+		mut env,
+		client,
+		backend,
+		pool,
+		mut commands_stream,
+		select_chain,
+		consensus_data_provider,
+		inherent_data_providers,
+	}: ManualSealParams<B, E, C, CB, A, SC, CS, T>
 )
 	where
 		A: txpool::ChainApi<Block=B> + 'static,
@@ -105,26 +192,33 @@ pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
 		E: Environment<B> + 'static,
 		E::Error: std::fmt::Display,
 		<E::Proposer as Proposer<B>>::Error: std::fmt::Display,
-		S: Stream<Item=EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+		CS: Stream<Item=EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
 		SC: SelectChain<B> + 'static,
 {
+	// offset of the block timestamps to the local time, after time travel.
+	let mut timestamp_offset = 0;
+
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock {
 				create_empty,
 				finalize,
 				parent_hash,
+				timestamp,
 				sender,
 			} => {
 				seal_new_block(
 					SealBlockParams {
 						sender,
 						parent_hash,
+						timestamp,
+						timestamp_offset: &mut timestamp_offset,
 						finalize,
 						create_empty,
 						env: &mut env,
 						select_chain: &select_chain,
 						block_import: &mut block_import,
+						consensus_data_provider: consensus_data_provider.as_ref().map(|p| &**p),
 						inherent_data_provider: &inherent_data_providers,
 						pool: pool.clone(),
 						client: client.clone(),
@@ -142,6 +236,16 @@ pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
 					}
 				).await
 			}
+			EngineCommand::RevertTo { hash, sender } => {
+				revert_to(
+					RevertToParams {
+						hash,
+						sender,
+						client: client.clone(),
+						backend: backend.clone(),
+					}
+				).await
+			}
 		}
 	}
 }
@@ -150,12 +254,7 @@ pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
 /// instant-seal creates a new block for every transaction imported into
 /// the transaction pool.
 pub async fn run_instant_seal<B, CB, E, C, A, SC, T>(
-	block_import: BoxBlockImport<B, T>,
-	env: E,
-	client: Arc<C>,
-	pool: Arc<txpool::Pool<A>>,
-	select_chain: SC,
-	inherent_data_providers: InherentDataProviders,
+	params: AutoSealParams<B, E, C, CB, A, SC, T>,
 )
 	where
 		A: txpool::ChainApi<Block=B> + 'static,
@@ -169,26 +268,52 @@ pub async fn run_instant_seal<B, CB, E, C, A, SC, T>(
 {
 	// instant-seal creates blocks as soon as transactions are imported
 	// into the transaction pool.
-	let commands_stream = pool.validated_pool()
+	let commands_stream = params.pool.validated_pool()
 		.import_notification_stream()
 		.map(|_| {
 			EngineCommand::SealNewBlock {
 				create_empty: false,
 				finalize: false,
 				parent_hash: None,
+				timestamp: None,
 				sender: None,
 			}
 		});
 
-	run_manual_seal(
-		block_import,
-		env,
-		client,
-		pool,
-		commands_stream,
-		select_chain,
-		inherent_data_providers,
-	).await
+	run_manual_seal(params.with_commands_stream(commands_stream)).await
+}
+
+/// runs the background authorship task for the interval seal engine.
+/// interval-seal creates a new block every `interval`, including the transactions
+/// of the transaction pool, empty blocks are created if there are none.
+pub async fn run_interval_seal<B, CB, E, C, A, SC, T>(
+	params: AutoSealParams<B, E, C, CB, A, SC, T>,
+	interval: Duration,
+	finalize: bool,
+)
+	where
+		A: txpool::ChainApi<Block=B> + 'static,
+		B: BlockT + 'static,
+		C: HeaderBackend<B> + Finalizer<B, CB> + 'static,
+		CB: ClientBackend<B> + 'static,
+		E: Environment<B> + 'static,
+		E::Error: std::fmt::Display,
+		<E::Proposer as Proposer<B>>::Error: std::fmt::Display,
+		SC: SelectChain<B> + 'static
+{
+	let commands_stream = futures::stream::unfold((), move |()| async move {
+		Delay::new(interval).await;
+		let command = EngineCommand::SealNewBlock {
+			create_empty: true,
+			finalize,
+			parent_hash: None,
+			timestamp: None,
+			sender: None,
+		};
+		Some((command, ()))
+	});
+
+	run_manual_seal(params.with_commands_stream(Box::pin(commands_stream))).await
 }
 
 #[cfg(test)]
@@ -206,20 +331,123 @@ mod tests {
 	};
 	use substrate_test_runtime_transaction_pool::{TestApi, uxt};
 	use sp_transaction_pool::{TransactionPool, MaintainedTransactionPool, TransactionSource};
-	use sp_runtime::generic::BlockId;
+	use sp_runtime::{
+		generic::{BlockId, Digest, DigestItem},
+		traits::{DigestFor, Header as _},
+	};
 	use sp_consensus::ImportedAux;
-	use sp_inherents::InherentDataProviders;
+	use sp_inherents::{InherentData, InherentDataProviders};
+	use sp_timestamp::TimestampInherentData;
 	use sc_basic_authorship::ProposerFactory;
+	use substrate_test_runtime_client::{runtime::{Block, Header}, Sr25519Keyring, TestClient};
+	use sc_consensus_babe::{
+		AuthorityPair, CompatibleDigestItem, Config, ConsensusLog, Epoch, NextEpochDescriptor,
+		SlotNumber, BABE_ENGINE_ID,
+	};
+	use sc_consensus_epochs::{SharedEpochChanges, descendent_query};
+	use sc_client_api::BlockchainEvents;
+	use codec::Encode;
+	use futures::compat::Future01CompatExt;
+	use crate::consensus::babe::BabeConsensusDataProvider;
+	use crate::rpc::{ManualSeal, ManualSealApi};
 
 	fn api() -> Arc<TestApi> {
 		Arc::new(TestApi::empty())
 	}
 
+	/// Records the timestamp inherent of the created blocks in their digest.
+	struct TimestampDigest<T>(PhantomData<fn() -> T>);
+
+	impl<T> ConsensusDataProvider<Block> for TimestampDigest<T> {
+		type Transaction = T;
+
+		fn create_digest(&self, _: &Header, inherents: &InherentData) -> Result<DigestFor<Block>, Error> {
+			let timestamp = inherents.timestamp_inherent_data()?;
+			Ok(Digest { logs: vec![DigestItem::Other(timestamp.to_le_bytes().to_vec())] })
+		}
+
+		fn append_block_import(
+			&self,
+			_: &Header,
+			_: &mut BlockImportParams<Block, T>,
+			_: &InherentData,
+		) -> Result<(), Error> {
+			Ok(())
+		}
+	}
+
+	fn digest_timestamp(header: &Header) -> u64 {
+		match header.digest().logs() {
+			[DigestItem::Other(timestamp)] => {
+				let mut bytes = [0; 8];
+				bytes.copy_from_slice(timestamp);
+				u64::from_le_bytes(bytes)
+			}
+			logs => panic!("unexpected digest {:?}", logs),
+		}
+	}
+
+	fn babe_slot(header: &Header) -> SlotNumber {
+		header.digest().logs().iter()
+			.find_map(|log| log.as_babe_pre_digest())
+			.map(|pre_digest| pre_digest.slot_number())
+			// the genesis block has no pre-digest.
+			.unwrap_or(0)
+	}
+
+	/// Announces the next epoch in the first block of each epoch, on top of the digest of the
+	/// `BabeConsensusDataProvider`, as the BABE pallet would. The test runtime doesn't.
+	struct AnnounceEpochs {
+		babe: BabeConsensusDataProvider<Block, TestClient>,
+		client: Arc<TestClient>,
+		epoch_changes: SharedEpochChanges<Block, Epoch>,
+		config: Config,
+	}
+
+	impl ConsensusDataProvider<Block> for AnnounceEpochs {
+		type Transaction = sp_api::TransactionFor<TestClient, Block>;
+
+		fn create_digest(&self, parent: &Header, inherents: &InherentData) -> Result<DigestFor<Block>, Error> {
+			let mut digest = self.babe.create_digest(parent, inherents)?;
+			let slot_number = digest.logs.iter()
+				.find_map(|log| log.as_babe_pre_digest())
+				.expect("the BABE digest holds a pre-digest")
+				.slot_number();
+
+			let epoch_descriptor = self.epoch_changes.lock().epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot_number,
+			).unwrap().unwrap();
+
+			if babe_slot(parent) < epoch_descriptor.start_slot() {
+				let next_epoch = ConsensusLog::NextEpochData(NextEpochDescriptor {
+					authorities: self.config.genesis_authorities.clone(),
+					randomness: self.config.randomness,
+				});
+				digest.push(DigestItem::Consensus(BABE_ENGINE_ID, next_epoch.encode()));
+			}
+
+			Ok(digest)
+		}
+
+		fn append_block_import(
+			&self,
+			parent: &Header,
+			params: &mut BlockImportParams<Block, Self::Transaction>,
+			inherents: &InherentData,
+		) -> Result<(), Error> {
+			self.babe.append_block_import(parent, params, inherents)
+		}
+	}
+
 	const SOURCE: TransactionSource = TransactionSource::External;
 
 	#[tokio::test]
 	async fn instant_seal() {
 		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
 		let (client, select_chain) = builder.build_with_longest_chain();
 		let client = Arc::new(client);
 		let inherent_data_providers = InherentDataProviders::new();
@@ -241,17 +469,22 @@ mod tests {
 					create_empty: false,
 					finalize: true,
 					parent_hash: None,
+					timestamp: None,
 					sender
 				}
 			});
 		let future = run_manual_seal(
-			Box::new(client.clone()),
-			env,
-			client.clone(),
-			pool.pool().clone(),
-			stream,
-			select_chain,
-			inherent_data_providers,
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			}
 		);
 		std::thread::spawn(|| {
 			let mut rt = tokio::runtime::Runtime::new().unwrap();
@@ -285,6 +518,7 @@ mod tests {
 	#[tokio::test]
 	async fn manual_seal_and_finalization() {
 		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
 		let (client, select_chain) = builder.build_with_longest_chain();
 		let client = Arc::new(client);
 		let inherent_data_providers = InherentDataProviders::new();
@@ -297,13 +531,17 @@ mod tests {
 		// this test checks that blocks are created as soon as an engine command is sent over the stream.
 		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
 		let future = run_manual_seal(
-			Box::new(client.clone()),
-			env,
-			client.clone(),
-			pool.pool().clone(),
-			stream,
-			select_chain,
-			inherent_data_providers,
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			}
 		);
 		std::thread::spawn(|| {
 			let mut rt = tokio::runtime::Runtime::new().unwrap();
@@ -317,6 +555,7 @@ mod tests {
 		let (tx, rx) = futures::channel::oneshot::channel();
 		sink.send(EngineCommand::SealNewBlock {
 			parent_hash: None,
+			timestamp: None,
 			sender: Some(tx),
 			create_empty: false,
 			finalize: false,
@@ -350,9 +589,104 @@ mod tests {
 		assert_eq!(rx.await.unwrap().unwrap(), ());
 	}
 
+	#[tokio::test]
+	async fn interval_seal_creates_and_finalizes_empty_blocks() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		let pool = Arc::new(BasicPool::new(Options::default(), api(), None).0);
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+		let mut imports = client.import_notification_stream();
+		let future = run_interval_seal(
+			AutoSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			},
+			Duration::from_millis(100),
+			true,
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		// a block is created on every tick, although the pool is empty.
+		let first = imports.next().await.unwrap();
+		let second = imports.next().await.unwrap();
+		assert_eq!(*first.header.number(), 1);
+		assert_eq!(*second.header.number(), 2);
+		assert_eq!(*second.header.parent_hash(), first.hash);
+
+		// and finalized right after it is imported.
+		assert!(client.info().finalized_number >= 1);
+	}
+
+	#[tokio::test]
+	async fn create_blocks_rpc_seals_blocks_on_top_of_each_other() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		let pool = Arc::new(BasicPool::new(Options::default(), api(), None).0);
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+		let (sink, stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			}
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		let rpc = ManualSeal::new(sink);
+		let created = rpc.create_blocks(3, true, false).compat().await.unwrap();
+		assert_eq!(created.len(), 3);
+
+		let mut parent_hash = client.info().genesis_hash;
+		for (number, block) in (1..).zip(&created) {
+			assert!(block.aux.is_new_best);
+			let header = client.header(&BlockId::Hash(block.hash)).unwrap().unwrap();
+			assert_eq!(*header.number(), number);
+			assert_eq!(*header.parent_hash(), parent_hash);
+			parent_hash = block.hash;
+		}
+		assert_eq!(client.info().best_hash, created[2].hash);
+		assert_eq!(client.info().finalized_number, 0);
+	}
+
 	#[tokio::test]
 	async fn manual_seal_fork_blocks() {
 		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
 		let (client, select_chain) = builder.build_with_longest_chain();
 		let client = Arc::new(client);
 		let inherent_data_providers = InherentDataProviders::new();
@@ -366,13 +700,17 @@ mod tests {
 		// this test checks that blocks are created as soon as an engine command is sent over the stream.
 		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
 		let future = run_manual_seal(
-			Box::new(client.clone()),
-			env,
-			client.clone(),
-			pool.pool().clone(),
-			stream,
-			select_chain,
-			inherent_data_providers,
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			}
 		);
 		std::thread::spawn(|| {
 			let mut rt = tokio::runtime::Runtime::new().unwrap();
@@ -387,6 +725,7 @@ mod tests {
 		let (tx, rx) = futures::channel::oneshot::channel();
 		sink.send(EngineCommand::SealNewBlock {
 			parent_hash: None,
+			timestamp: None,
 			sender: Some(tx),
 			create_empty: false,
 			finalize: false,
@@ -424,6 +763,7 @@ mod tests {
 		let (tx1, rx1) = futures::channel::oneshot::channel();
 		assert!(sink.send(EngineCommand::SealNewBlock {
 			parent_hash: Some(created_block.hash),
+			timestamp: None,
 			sender: Some(tx1),
 			create_empty: false,
 			finalize: false,
@@ -439,6 +779,7 @@ mod tests {
 		let (tx2, rx2) = futures::channel::oneshot::channel();
 		assert!(sink.send(EngineCommand::SealNewBlock {
 			parent_hash: Some(created_block.hash),
+			timestamp: None,
 			sender: Some(tx2),
 			create_empty: false,
 			finalize: false,
@@ -447,4 +788,174 @@ mod tests {
 		// assert that fork block is in the db
 		assert!(client.header(&BlockId::Hash(imported.hash)).unwrap().is_some())
 	}
+
+	#[tokio::test]
+	async fn manual_seal_time_travel_and_revert() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		inherent_data_providers.register_provider(sp_timestamp::InherentDataProvider).unwrap();
+		let pool = Arc::new(BasicPool::new(Options::default(), api(), None).0);
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: Some(Box::new(TimestampDigest(PhantomData))),
+				inherent_data_providers,
+			}
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		// travel to 2100-01-01.
+		let travelled = 4_102_444_800_000;
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			parent_hash: None,
+			timestamp: Some(travelled),
+			sender: Some(tx),
+			create_empty: true,
+			finalize: false,
+		}).await.unwrap();
+		let first = rx.await.unwrap().unwrap();
+		let header = client.header(&BlockId::Hash(first.hash)).unwrap().unwrap();
+		assert_eq!(digest_timestamp(&header), travelled);
+
+		// later blocks stay in the future.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			parent_hash: None,
+			timestamp: None,
+			sender: Some(tx),
+			create_empty: true,
+			finalize: false,
+		}).await.unwrap();
+		let second = rx.await.unwrap().unwrap();
+		let header = client.header(&BlockId::Hash(second.hash)).unwrap().unwrap();
+		let timestamp = digest_timestamp(&header);
+		assert!(timestamp >= travelled && timestamp < travelled + 60_000);
+		assert_eq!(client.info().best_number, 2);
+
+		// revert to the first block.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo {
+			hash: first.hash,
+			sender: Some(tx),
+		}).await.unwrap();
+		assert_eq!(rx.await.unwrap().unwrap(), ());
+		assert_eq!(client.info().best_hash, first.hash);
+		assert_eq!(client.info().best_number, 1);
+
+		// the chain can't be reverted to a block off the best chain.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo {
+			hash: second.hash,
+			sender: Some(tx),
+		}).await.unwrap();
+		assert!(rx.await.unwrap().is_err());
+	}
+
+	#[tokio::test]
+	async fn manual_seal_babe_blocks() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		inherent_data_providers.register_provider(sp_timestamp::InherentDataProvider).unwrap();
+		let pool = Arc::new(BasicPool::new(Options::default(), api(), None).0);
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+
+		let config = Config::get_or_compute(&*client).unwrap();
+		let (block_import, link) = sc_consensus_babe::block_import(
+			config.clone(),
+			client.clone(),
+			client.clone(),
+		).unwrap();
+		let epoch_changes = link.epoch_changes().clone();
+
+		// every slot can be claimed with the keys of all the authorities.
+		let keystore_path = tempfile::tempdir().unwrap();
+		let keystore = sc_keystore::Store::open(keystore_path.path(), None).unwrap();
+		for authority in &[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie] {
+			keystore.write().insert_ephemeral_from_seed::<AuthorityPair>(&authority.to_seed()).unwrap();
+		}
+		let babe = BabeConsensusDataProvider::new(
+			client.clone(),
+			keystore,
+			epoch_changes.clone(),
+		).unwrap();
+
+		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(
+			ManualSealParams {
+				block_import: Box::new(block_import),
+				env,
+				client: client.clone(),
+				backend,
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: Some(Box::new(AnnounceEpochs {
+					babe,
+					client: client.clone(),
+					epoch_changes: epoch_changes.clone(),
+					config: config.clone(),
+				})),
+				inherent_data_providers,
+			}
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		// the slots of the blocks follow their timestamps: slot 2 is missed and slot 7 starts
+		// the epoch after the genesis one, which starts at the slot of block #1.
+		let mut hashes = Vec::new();
+		for &slot_number in &[1, 3, 7] {
+			let (tx, rx) = futures::channel::oneshot::channel();
+			sink.send(EngineCommand::SealNewBlock {
+				parent_hash: None,
+				timestamp: Some(slot_number * config.slot_duration),
+				sender: Some(tx),
+				create_empty: true,
+				finalize: false,
+			}).await.unwrap();
+			let created_block = rx.await.unwrap().unwrap();
+			assert!(created_block.aux.is_new_best);
+
+			let header = client.header(&BlockId::Hash(created_block.hash)).unwrap().unwrap();
+			assert_eq!(babe_slot(&header), slot_number);
+			hashes.push(created_block.hash);
+		}
+		assert_eq!(client.info().best_number, 3);
+
+		// the BABE block import tracked the epochs announced by blocks #1 and #3.
+		let announced = epoch_changes.lock().tree().iter()
+			.map(|(hash, number, _)| (*hash, *number))
+			.collect::<Vec<_>>();
+		assert_eq!(announced, vec![(hashes[0], 1), (hashes[2], 3)]);
+	}
 }
@@ -0,0 +1,85 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Block reverting utilities
+
+use crate::{Error, rpc};
+use sp_runtime::traits::Block as BlockT;
+use sp_blockchain::HeaderBackend;
+use sc_client_api::backend::Backend as ClientBackend;
+use std::sync::Arc;
+
+/// params for reverting the chain to a block.
+pub struct RevertToParams<B: BlockT, C, CB> {
+	/// hash of the block to revert to.
+	pub hash: <B as BlockT>::Hash,
+	/// sender to report errors/success to the rpc.
+	pub sender: rpc::Sender<()>,
+	/// header backend
+	pub client: Arc<C>,
+	/// backend the blocks are reverted in.
+	pub backend: Arc<CB>,
+}
+
+/// reverts the best chain to the block with the given params.
+///
+/// The block becomes the new best block, finalized blocks can't be reverted.
+pub async fn revert_to<B, C, CB>(params: RevertToParams<B, C, CB>)
+	where
+		B: BlockT,
+		C: HeaderBackend<B>,
+		CB: ClientBackend<B>,
+{
+	let RevertToParams {
+		hash,
+		mut sender,
+		client,
+		backend,
+	} = params;
+
+	let result = (|| -> Result<(), Error> {
+		let number = client.number(hash)?
+			.ok_or_else(|| Error::BlockNotFound(format!("{}", hash)))?;
+		if client.hash(number)? != Some(hash) {
+			return Err(Error::StringError(format!("Block {} is not on the best chain", hash)))
+		}
+
+		let blocks = client.info().best_number - number;
+		let reverted = backend.revert(blocks, false)?;
+		if reverted != blocks {
+			return Err(Error::StringError(format!(
+				"Reverted {} of {} blocks, finalized blocks can't be reverted",
+				reverted,
+				blocks,
+			)))
+		}
+
+		Ok(())
+	})();
+
+	match result {
+		Err(e) => {
+			log::warn!("Failed to revert to block {:?}", e);
+			rpc::send_result(&mut sender, Err(e))
+		}
+		Ok(()) => {
+			log::info!("⏪ Successfully reverted to block: {}", hash);
+			rpc::send_result(&mut sender, Ok(()))
+		}
+	}
+}
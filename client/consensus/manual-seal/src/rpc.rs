@@ -49,6 +49,10 @@ pub enum EngineCommand<Hash> {
 		finalize: bool,
 		/// specify the parent hash of the about-to-created block
 		parent_hash: Option<Hash>,
+		/// specify the timestamp of the about-to-created block, in milliseconds.
+		///
+		/// Later blocks keep the offset of this timestamp to the local time.
+		timestamp: Option<u64>,
 		/// sender to report errors/success to the rpc.
 		sender: Sender<CreatedBlock<Hash>>,
 	},
@@ -60,6 +64,15 @@ pub enum EngineCommand<Hash> {
 		sender: Sender<()>,
 		/// finalization justification
 		justification: Option<Justification>,
+	},
+	/// Tells the engine to revert the best chain to the block with the supplied hash
+	///
+	/// The block becomes the new best block, finalized blocks can't be reverted.
+	RevertTo {
+		/// hash of the block
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
 	}
 }
 
@@ -72,9 +85,19 @@ pub trait ManualSealApi<Hash> {
 		&self,
 		create_empty: bool,
 		finalize: bool,
-		parent_hash: Option<Hash>
+		parent_hash: Option<Hash>,
+		timestamp: Option<u64>
 	) -> FutureResult<CreatedBlock<Hash>>;
 
+	/// Instructs the manual-seal authorship task to create `count` blocks on top of the best block
+	#[rpc(name = "engine_createBlocks")]
+	fn create_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool
+	) -> FutureResult<Vec<CreatedBlock<Hash>>>;
+
 	/// Instructs the manual-seal authorship task to finalize a block
 	#[rpc(name = "engine_finalizeBlock")]
 	fn finalize_block(
@@ -82,6 +105,10 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<Justification>
 	) -> FutureResult<bool>;
+
+	/// Instructs the manual-seal authorship task to revert the best chain to a block
+	#[rpc(name = "engine_revertTo")]
+	fn revert_to(&self, hash: Hash) -> FutureResult<bool>;
 }
 
 /// A struct that implements the [`ManualSealApi`].
@@ -110,7 +137,8 @@ impl<Hash: Send + 'static> ManualSealApi<Hash> for ManualSeal<Hash> {
 		&self,
 		create_empty: bool,
 		finalize: bool,
-		parent_hash: Option<Hash>
+		parent_hash: Option<Hash>,
+		timestamp: Option<u64>
 	) -> FutureResult<CreatedBlock<Hash>> {
 		let mut sink = self.import_block_channel.clone();
 		let future = async move {
@@ -119,6 +147,7 @@ impl<Hash: Send + 'static> ManualSealApi<Hash> for ManualSeal<Hash> {
 				create_empty,
 				finalize,
 				parent_hash,
+				timestamp,
 				sender: Some(sender),
 			};
 			sink.send(command).await?;
@@ -128,6 +157,34 @@ impl<Hash: Send + 'static> ManualSealApi<Hash> for ManualSeal<Hash> {
 		Box::new(future.map_err(Error::from).compat())
 	}
 
+	fn create_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool
+	) -> FutureResult<Vec<CreatedBlock<Hash>>> {
+		let mut sink = self.import_block_channel.clone();
+		let future = async move {
+			let mut created = Vec::with_capacity(count as usize);
+			for _ in 0..count {
+				let (sender, receiver) = oneshot::channel();
+				let command = EngineCommand::SealNewBlock {
+					create_empty,
+					finalize,
+					parent_hash: None,
+					timestamp: None,
+					sender: Some(sender),
+				};
+				sink.send(command).await?;
+				created.push(receiver.await??);
+			}
+
+			Ok::<_, crate::Error>(created)
+		}.boxed();
+
+		Box::new(future.map_err(Error::from).compat())
+	}
+
 	fn finalize_block(&self, hash: Hash, justification: Option<Justification>) -> FutureResult<bool> {
 		let mut sink = self.import_block_channel.clone();
 		let future = async move {
@@ -141,6 +198,18 @@ impl<Hash: Send + 'static> ManualSealApi<Hash> for ManualSeal<Hash> {
 
 		Box::new(future.boxed().map_err(Error::from).compat())
 	}
+
+	fn revert_to(&self, hash: Hash) -> FutureResult<bool> {
+		let mut sink = self.import_block_channel.clone();
+		let future = async move {
+			let (sender, receiver) = oneshot::channel();
+			sink.send(EngineCommand::RevertTo { hash, sender: Some(sender) }).await?;
+
+			receiver.await?.map(|_| true)
+		};
+
+		Box::new(future.boxed().map_err(Error::from).compat())
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
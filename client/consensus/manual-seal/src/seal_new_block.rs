@@ -16,7 +16,7 @@
 
 //! Block sealing utilities
 
-use crate::{Error, rpc};
+use crate::{Error, rpc, ConsensusDataProvider};
 use std::sync::Arc;
 use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT},
@@ -36,6 +36,7 @@ use sp_blockchain::HeaderBackend;
 use std::collections::HashMap;
 use std::time::Duration;
 use sp_inherents::InherentDataProviders;
+use sp_timestamp::TimestampInherentData;
 
 /// max duration for creating a proposal in secs
 const MAX_PROPOSAL_DURATION: u64 = 10;
@@ -49,6 +50,12 @@ pub struct SealBlockParams<'a, B: BlockT, SC, HB, E, T, P: txpool::ChainApi> {
 	pub finalize: bool,
 	/// specify the parent hash of the about-to-created block
 	pub parent_hash: Option<<B as BlockT>::Hash>,
+	/// timestamp of the about-to-created block, in milliseconds.
+	pub timestamp: Option<u64>,
+	/// offset of the timestamps of the created blocks to the local time, in milliseconds.
+	///
+	/// Updated by blocks with an explicit `timestamp`, so that later blocks keep the time travel.
+	pub timestamp_offset: &'a mut i64,
 	/// sender to report errors/success to the rpc.
 	pub sender: rpc::Sender<CreatedBlock<<B as BlockT>::Hash>>,
 	/// transaction pool
@@ -61,6 +68,8 @@ pub struct SealBlockParams<'a, B: BlockT, SC, HB, E, T, P: txpool::ChainApi> {
 	pub select_chain: &'a SC,
 	/// block import object
 	pub block_import: &'a mut BoxBlockImport<B, T>,
+	/// consensus data provider, adding the consensus digests to the created blocks.
+	pub consensus_data_provider: Option<&'a dyn ConsensusDataProvider<B, Transaction = T>>,
 	/// inherent data provider
 	pub inherent_data_provider: &'a InherentDataProviders,
 }
@@ -72,10 +81,13 @@ pub async fn seal_new_block<B, SC, HB, E, T, P>(
 		finalize,
 		pool,
 		parent_hash,
+		timestamp,
+		timestamp_offset,
 		client,
 		select_chain,
 		block_import,
 		env,
+		consensus_data_provider: digest_provider,
 		inherent_data_provider,
 		mut sender,
 		..
@@ -110,24 +122,54 @@ pub async fn seal_new_block<B, SC, HB, E, T, P>(
 
 		let proposer = env.init(&header)
 			.map_err(|err| Error::StringError(format!("{}", err))).await?;
-		let id = inherent_data_provider.create_inherent_data()?;
+		let mut id = inherent_data_provider.create_inherent_data()?;
+		// time travel: override the timestamp inherent with the requested timestamp, or keep
+		// the offset of the last requested timestamp.
+		let now = id.timestamp_inherent_data().ok();
+		let timestamp = match (timestamp, now) {
+			(Some(timestamp), Some(now)) => {
+				*timestamp_offset = timestamp as i64 - now as i64;
+				Some(timestamp)
+			}
+			(Some(timestamp), None) => Some(timestamp),
+			(None, Some(now)) if *timestamp_offset != 0 =>
+				Some((now as i64).saturating_add(*timestamp_offset).max(0) as u64),
+			(None, _) => None,
+		};
+		if let Some(timestamp) = timestamp {
+			id.replace_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp);
+		}
+
 		let inherents_len = id.len();
-		let proposal = proposer.propose(id, Default::default(), Duration::from_secs(MAX_PROPOSAL_DURATION), false.into())
+		let digest = match digest_provider {
+			Some(digest_provider) => digest_provider.create_digest(&header, &id)?,
+			None => Default::default(),
+		};
+		let proposal = proposer.propose(
+			id.clone(),
+			digest,
+			Duration::from_secs(MAX_PROPOSAL_DURATION),
+			false.into(),
+		)
 			.map_err(|err| Error::StringError(format!("{}", err))).await?;
 
 		if proposal.block.extrinsics().len() == inherents_len && !create_empty {
 			return Err(Error::EmptyTransactionPool)
 		}
 
-		let (header, body) = proposal.block.deconstruct();
-		let mut params = BlockImportParams::new(BlockOrigin::Own, header.clone());
+		let (new_header, body) = proposal.block.deconstruct();
+		let mut params = BlockImportParams::new(BlockOrigin::Own, new_header.clone());
 		params.body = Some(body);
 		params.finalized = finalize;
 		params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 
+		if let Some(digest_provider) = digest_provider {
+			digest_provider.append_block_import(&header, &mut params, &id)?;
+		}
+
 		match block_import.import_block(params, HashMap::new())? {
 			ImportResult::Imported(aux) => {
-				Ok(CreatedBlock { hash: <B as BlockT>::Header::hash(&header), aux })
+				Ok(CreatedBlock { hash: <B as BlockT>::Header::hash(&new_header), aux })
 			},
 			other => Err(other.into()),
 		}
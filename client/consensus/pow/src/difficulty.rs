@@ -0,0 +1,343 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Difficulty adjustment algorithms for PoW chains.
+//!
+//! The algorithms compute the difficulty of the next block from the difficulties and timestamps
+//! of the most recent blocks, so that blocks are found every `target_block_time` milliseconds
+//! whatever the hashrate of the network. The history is read from the PoW auxiliary data with
+//! [`next_difficulty`], which requires blocks to carry their timestamp, see
+//! [`PowAlgorithm::requires_timestamp`](crate::PowAlgorithm::requires_timestamp).
+
+use std::convert::TryFrom;
+use sc_client_api::backend::AuxStore;
+use sp_blockchain::HeaderBackend;
+use sp_core::U256;
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT, Zero}};
+use crate::{Error, PowAux};
+
+/// Difficulty and timestamp of a block.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub struct DifficultyRecord {
+	/// Difficulty the block was mined at.
+	pub difficulty: U256,
+	/// Timestamp of the block in milliseconds.
+	pub timestamp: u64,
+}
+
+/// An algorithm computing the difficulty of the next block.
+pub trait DifficultyAdjustment {
+	/// Number of most recent blocks the algorithm needs the records of.
+	fn history(&self) -> usize;
+	/// Compute the difficulty of the block following the last of `records`.
+	///
+	/// The records are ordered from the oldest to the most recent block, and hold at most
+	/// `history` entries. Returns `None` if there are not enough records yet.
+	fn next_difficulty(&self, records: &[DifficultyRecord]) -> Option<U256>;
+}
+
+/// Simple moving average over the last `window` blocks.
+///
+/// The difficulty is the average difficulty of the window scaled by the ratio between the
+/// expected and the actual timespan of the window. The timespan is clamped to a factor of four
+/// of the expected timespan, which limits the adjustment of a single block.
+#[derive(Clone, Copy, Debug)]
+pub struct MovingAverage {
+	/// Number of blocks to average over.
+	pub window: usize,
+	/// Target block time in milliseconds.
+	pub target_block_time: u64,
+}
+
+impl DifficultyAdjustment for MovingAverage {
+	fn history(&self) -> usize {
+		self.window + 1
+	}
+
+	fn next_difficulty(&self, records: &[DifficultyRecord]) -> Option<U256> {
+		if records.len() < 2 {
+			return None
+		}
+
+		let blocks = records.len() as u64 - 1;
+		let timespan = records[records.len() - 1].timestamp.saturating_sub(records[0].timestamp);
+		let expected = self.target_block_time.saturating_mul(blocks);
+		let timespan = timespan.max(expected / 4).min(expected.saturating_mul(4)).max(1);
+
+		let total = records[1..].iter()
+			.fold(U256::zero(), |total, record| total.saturating_add(record.difficulty));
+
+		Some(scale(total, self.target_block_time, timespan))
+	}
+}
+
+/// Linearly weighted moving average over the last `window` blocks.
+///
+/// Recent solve times weigh more than older ones, so the difficulty reacts faster to hashrate
+/// changes than with a [`MovingAverage`] of the same window. Solve times are clamped to
+/// `[1, 6 * target_block_time]`, which bounds the effect of wrong timestamps.
+#[derive(Clone, Copy, Debug)]
+pub struct Lwma {
+	/// Number of blocks to average over.
+	pub window: usize,
+	/// Target block time in milliseconds.
+	pub target_block_time: u64,
+}
+
+impl DifficultyAdjustment for Lwma {
+	fn history(&self) -> usize {
+		self.window + 1
+	}
+
+	fn next_difficulty(&self, records: &[DifficultyRecord]) -> Option<U256> {
+		if records.len() < 2 {
+			return None
+		}
+
+		let n = records.len() as u64 - 1;
+		let max_solve_time = self.target_block_time.saturating_mul(6);
+		let mut weighted_solve_times = 0u64;
+		let mut total = U256::zero();
+		for (i, pair) in records.windows(2).enumerate() {
+			let solve_time = pair[1].timestamp.saturating_sub(pair[0].timestamp)
+				.max(1)
+				.min(max_solve_time);
+			weighted_solve_times = weighted_solve_times
+				.saturating_add(solve_time.saturating_mul(i as u64 + 1));
+			total = total.saturating_add(pair[1].difficulty);
+		}
+
+		// Weighted solve times of blocks found exactly at the target block time.
+		let k = (n * (n + 1) / 2).saturating_mul(self.target_block_time);
+		let weighted_solve_times = weighted_solve_times.max(k / 10).max(1);
+
+		Some(scale(total, k, n.saturating_mul(weighted_solve_times)))
+	}
+}
+
+/// Exponential adjustment from the solve time of the parent block, in the style of ASERT.
+///
+/// Every block off the target block time changes the difficulty exponentially, such that blocks
+/// which are `half_life` milliseconds late in total halve the difficulty. Only the parent and
+/// grandparent records are needed.
+#[derive(Clone, Copy, Debug)]
+pub struct Asert {
+	/// Target block time in milliseconds.
+	pub target_block_time: u64,
+	/// Time in milliseconds blocks need to be late in total to halve the difficulty.
+	pub half_life: u64,
+}
+
+impl DifficultyAdjustment for Asert {
+	fn history(&self) -> usize {
+		2
+	}
+
+	fn next_difficulty(&self, records: &[DifficultyRecord]) -> Option<U256> {
+		if records.len() < 2 {
+			return None
+		}
+
+		let parent = records[records.len() - 1];
+		let grandparent = records[records.len() - 2];
+		let solve_time = parent.timestamp.saturating_sub(grandparent.timestamp);
+
+		// Exponent in 16.16 fixed point.
+		let exponent = (self.target_block_time as i128 - solve_time as i128) * 65536
+			/ self.half_life.max(1) as i128;
+
+		Some(pow2(parent.difficulty, exponent).max(U256::one()))
+	}
+}
+
+/// Compute `value * numerator / denominator`, saturating and at least one.
+fn scale(value: U256, numerator: u64, denominator: u64) -> U256 {
+	let result = match value.checked_mul(U256::from(numerator)) {
+		Some(product) => product / U256::from(denominator.max(1)),
+		None => (value / U256::from(denominator.max(1))).saturating_mul(U256::from(numerator)),
+	};
+
+	result.max(U256::one())
+}
+
+/// Compute `value * 2^(exponent / 65536)`, saturating.
+///
+/// The fractional part uses the cubic approximation of ASERT, which is accurate to within 0.013%.
+fn pow2(value: U256, exponent: i128) -> U256 {
+	let shifts = exponent >> 16;
+	let frac = (exponent & 0xffff) as u128;
+	let factor = 65536u128 + ((
+		195_766_423_245_049u128 * frac
+			+ 971_821_376u128 * frac * frac
+			+ 5127u128 * frac * frac * frac
+			+ (1u128 << 47)
+	) >> 48);
+
+	let value = match value.checked_mul(U256::from(factor)) {
+		Some(product) => product >> 16,
+		None => (value >> 16).saturating_mul(U256::from(factor)),
+	};
+
+	if shifts >= 0 {
+		if shifts >= value.leading_zeros() as i128 {
+			U256::max_value()
+		} else {
+			value << shifts as usize
+		}
+	} else if -shifts >= 256 {
+		U256::zero()
+	} else {
+		value >> (-shifts) as usize
+	}
+}
+
+/// Compute the difficulty of the block following `parent` with `adjustment`.
+///
+/// The records are read from the PoW auxiliary data of the ancestors of `parent`, up to genesis
+/// or the first block without a timestamp. Returns `initial` as long as there is not enough
+/// history, and the difficulty of `parent` if the result does not fit the difficulty type.
+pub fn next_difficulty<B, C, A, D>(
+	client: &C,
+	parent: B::Hash,
+	adjustment: &A,
+	initial: D,
+) -> Result<D, Error<B>> where
+	B: BlockT,
+	C: HeaderBackend<B> + AuxStore,
+	A: DifficultyAdjustment,
+	D: codec::Decode + Default + Copy + Into<U256> + TryFrom<U256>,
+{
+	let history = adjustment.history();
+	let mut records = Vec::with_capacity(history);
+	let mut hash = parent;
+	while records.len() < history {
+		let header = client.header(BlockId::Hash(hash))
+			.map_err(Error::Client)?
+			.ok_or_else(|| Error::Client(sp_blockchain::Error::UnknownBlock(format!("{}", hash))))?;
+		if header.number().is_zero() {
+			break
+		}
+
+		let aux = PowAux::<D>::read::<_, B>(client, &hash)?;
+		match aux.timestamp {
+			Some(timestamp) => records.push(DifficultyRecord {
+				difficulty: aux.difficulty.into(),
+				timestamp,
+			}),
+			None => break,
+		}
+		hash = *header.parent_hash();
+	}
+	records.reverse();
+
+	Ok(match adjustment.next_difficulty(&records) {
+		Some(difficulty) => D::try_from(difficulty).ok()
+			.or_else(|| records.last().and_then(|record| D::try_from(record.difficulty).ok()))
+			.unwrap_or(initial),
+		None => initial,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TARGET: u64 = 10_000;
+
+	/// Mine `phases` of `(blocks, hashrate)` with `adjustment`, where each block takes exactly
+	/// `difficulty / hashrate` milliseconds, and return the mean solve time of the last 50
+	/// blocks of each phase.
+	fn simulate<A: DifficultyAdjustment>(
+		adjustment: &A,
+		initial: u64,
+		phases: &[(usize, u64)],
+	) -> Vec<u64> {
+		let mut records: Vec<DifficultyRecord> = Vec::new();
+		let mut now = 0;
+		phases.iter().map(|&(blocks, hashrate)| {
+			let mut solve_times = Vec::with_capacity(blocks);
+			for _ in 0..blocks {
+				let history = &records[records.len().saturating_sub(adjustment.history())..];
+				let difficulty = adjustment.next_difficulty(history)
+					.unwrap_or_else(|| U256::from(initial));
+				let solve_time = (difficulty / U256::from(hashrate)).low_u64().max(1);
+				now += solve_time;
+				records.push(DifficultyRecord { difficulty, timestamp: now });
+				solve_times.push(solve_time);
+			}
+			solve_times[blocks - 50..].iter().sum::<u64>() / 50
+		}).collect()
+	}
+
+	fn assert_on_target(means: Vec<u64>) {
+		for mean in means {
+			assert!(
+				mean > TARGET * 95 / 100 && mean < TARGET * 105 / 100,
+				"mean solve time {} is off target {}", mean, TARGET,
+			);
+		}
+	}
+
+	fn hashrate_shocks() -> Vec<(usize, u64)> {
+		vec![(400, 1_000), (400, 10_000), (400, 100)]
+	}
+
+	#[test]
+	fn moving_average_recovers_from_hashrate_shocks() {
+		let adjustment = MovingAverage { window: 30, target_block_time: TARGET };
+		assert_on_target(simulate(&adjustment, 10_000_000, &hashrate_shocks()));
+		assert_on_target(simulate(&adjustment, 100_000, &[(400, 1_000)]));
+	}
+
+	#[test]
+	fn lwma_recovers_from_hashrate_shocks() {
+		let adjustment = Lwma { window: 60, target_block_time: TARGET };
+		assert_on_target(simulate(&adjustment, 10_000_000, &hashrate_shocks()));
+		assert_on_target(simulate(&adjustment, 100_000, &[(400, 1_000)]));
+	}
+
+	#[test]
+	fn asert_recovers_from_hashrate_shocks() {
+		let adjustment = Asert { target_block_time: TARGET, half_life: 10 * TARGET };
+		assert_on_target(simulate(&adjustment, 10_000_000, &hashrate_shocks()));
+		assert_on_target(simulate(&adjustment, 100_000, &[(400, 1_000)]));
+	}
+
+	#[test]
+	fn not_enough_history() {
+		let record = DifficultyRecord { difficulty: U256::from(1_000), timestamp: 0 };
+		let moving_average = MovingAverage { window: 30, target_block_time: TARGET };
+		let lwma = Lwma { window: 60, target_block_time: TARGET };
+		let asert = Asert { target_block_time: TARGET, half_life: TARGET };
+
+		assert_eq!(moving_average.next_difficulty(&[record]), None);
+		assert_eq!(lwma.next_difficulty(&[]), None);
+		assert_eq!(asert.next_difficulty(&[record]), None);
+	}
+
+	#[test]
+	fn pow2_works() {
+		let value = U256::from(1_000_000);
+		assert_eq!(pow2(value, 0), value);
+		assert_eq!(pow2(value, 65536), U256::from(2_000_000));
+		assert_eq!(pow2(value, -65536), U256::from(500_000));
+		let sqrt2 = pow2(value, 32768).low_u64();
+		assert!(sqrt2 >= 1_414_000 && sqrt2 <= 1_414_400, "{}", sqrt2);
+		assert_eq!(pow2(U256::max_value(), 65536), U256::max_value());
+		assert_eq!(pow2(value, -300 * 65536), U256::zero());
+	}
+}
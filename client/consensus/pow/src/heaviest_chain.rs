@@ -0,0 +1,126 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Select chain following the PoW fork choice.
+
+use std::{marker::PhantomData, sync::Arc};
+use sc_client_api::backend;
+use sp_blockchain::{Backend, HeaderBackend};
+use sp_consensus::{SelectChain, Error as ConsensusError};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor},
+};
+
+/// Select chain where the best chain is the best block picked by the `PowBlockImport`, the chain
+/// with the most total difficulty building on the last finalized block.
+///
+/// Unlike `LongestChain`, finality targets are searched on the best chain rather than on the
+/// longest one, so that GRANDPA votes on the chain miners build on. Targets which are not on the
+/// best chain fall back to the longest chain containing them.
+pub struct HeaviestChain<B, Block> {
+	backend: Arc<B>,
+	_phantom: PhantomData<Block>,
+}
+
+impl<B, Block> Clone for HeaviestChain<B, Block> {
+	fn clone(&self) -> Self {
+		HeaviestChain {
+			backend: self.backend.clone(),
+			_phantom: PhantomData,
+		}
+	}
+}
+
+impl<B, Block> HeaviestChain<B, Block> where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	/// Instantiate a new `HeaviestChain` for backend `B`.
+	pub fn new(backend: Arc<B>) -> Self {
+		HeaviestChain {
+			backend,
+			_phantom: PhantomData,
+		}
+	}
+
+	fn header(&self, hash: Block::Hash) -> sp_blockchain::Result<Block::Header> {
+		self.backend.blockchain().header(BlockId::Hash(hash))?
+			.ok_or_else(|| sp_blockchain::Error::UnknownBlock(format!("{}", hash)))
+	}
+
+	fn best_block_header(&self) -> sp_blockchain::Result<Block::Header> {
+		self.header(self.backend.blockchain().info().best_hash)
+	}
+
+	fn best_containing(
+		&self,
+		target_hash: Block::Hash,
+		maybe_max_number: Option<NumberFor<Block>>,
+	) -> sp_blockchain::Result<Option<Block::Hash>> {
+		let target_number = match self.backend.blockchain().number(target_hash)? {
+			Some(number) => number,
+			None => return Ok(None),
+		};
+		if maybe_max_number.map_or(false, |max_number| target_number > max_number) {
+			return Ok(None)
+		}
+
+		let mut best = self.best_block_header()?;
+		if let Some(max_number) = maybe_max_number {
+			while *best.number() > max_number {
+				best = self.header(*best.parent_hash())?;
+			}
+		}
+
+		let mut current = best.clone();
+		while *current.number() > target_number {
+			current = self.header(*current.parent_hash())?;
+		}
+
+		if current.hash() == target_hash {
+			Ok(Some(best.hash()))
+		} else {
+			let import_lock = self.backend.get_import_lock();
+			self.backend.blockchain().best_containing(target_hash, maybe_max_number, import_lock)
+		}
+	}
+}
+
+impl<B, Block> SelectChain<Block> for HeaviestChain<B, Block> where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	fn leaves(&self) -> Result<Vec<Block::Hash>, ConsensusError> {
+		self.backend.blockchain().leaves()
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
+	}
+
+	fn best_chain(&self) -> Result<Block::Header, ConsensusError> {
+		self.best_block_header()
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
+	}
+
+	fn finality_target(
+		&self,
+		target_hash: Block::Hash,
+		maybe_max_number: Option<NumberFor<Block>>,
+	) -> Result<Option<Block::Hash>, ConsensusError> {
+		self.best_containing(target_hash, maybe_max_number)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
+	}
+}
@@ -21,7 +21,9 @@
 //! To use this engine, you can need to have a struct that implements
 //! `PowAlgorithm`. After that, pass an instance of the struct, along
 //! with other necessary client references to `import_queue` to setup
-//! the queue. Use the `start_mine` function for basic CPU mining.
+//! the queue. Use the `start_mining_worker` function to maintain the work to
+//! mine, and `start_mining_threads` for basic CPU mining. External miners can
+//! fetch the work and submit seals through the RPC in [`rpc`].
 //!
 //! The auxiliary storage for PoW engine only stores the total difficulty.
 //! For other storage requirements for particular PoW algorithm (such as
@@ -30,6 +32,23 @@
 //! for the auxiliary storage. It is also possible to just use the runtime
 //! as the storage, but it is not recommended as it won't work well with light
 //! clients.
+//!
+//! PoW chains can get finality from GRANDPA by wrapping the GRANDPA block import
+//! in the `PowBlockImport`, and passing the GRANDPA justification import to
+//! `import_queue`. The fork choice of `PowBlockImport` never picks a best block
+//! which doesn't build on the last finalized block, and GRANDPA should be run
+//! with the [`HeaviestChain`] select chain so that it votes on the chain miners
+//! build on.
+
+pub mod difficulty;
+mod heaviest_chain;
+pub mod rpc;
+#[cfg(test)]
+mod tests;
+mod worker;
+
+pub use heaviest_chain::HeaviestChain;
+pub use worker::{MiningHandle, MiningMetadata, start_mining_threads, start_mining_worker};
 
 use std::sync::Arc;
 use std::any::Any;
@@ -37,19 +56,20 @@ use std::borrow::Cow;
 use std::thread;
 use std::collections::HashMap;
 use std::marker::PhantomData;
-use sc_client_api::{BlockOf, backend::AuxStore};
-use sp_blockchain::{HeaderBackend, ProvideCache, well_known_cache_keys::Id as CacheKeyId};
+use sc_client_api::{BlockOf, BlockchainEvents, backend::AuxStore};
+use sp_blockchain::{
+	HeaderBackend, HeaderMetadata, ProvideCache, well_known_cache_keys::Id as CacheKeyId,
+};
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_runtime::{Justification, RuntimeString};
-use sp_runtime::generic::{BlockId, Digest, DigestItem};
+use sp_runtime::generic::{BlockId, DigestItem};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
 use sp_api::ProvideRuntimeApi;
-use sp_consensus_pow::{Seal, TotalDifficulty, POW_ENGINE_ID};
+use sp_consensus_pow::{Seal, TotalDifficulty, POW_ENGINE_ID, POW_TIMESTAMP_ENGINE_ID};
 use sp_inherents::{InherentDataProviders, InherentData};
 use sp_consensus::{
-	BlockImportParams, BlockOrigin, ForkChoiceStrategy, SyncOracle, Environment, Proposer,
-	SelectChain, Error as ConsensusError, CanAuthorWith, RecordProof, BlockImport,
-	BlockCheckParams, ImportResult,
+	BlockImportParams, BlockOrigin, ForkChoiceStrategy, SelectChain, Error as ConsensusError,
+	BlockImport, BlockCheckParams, ImportResult, CanAuthorWith, Environment, Proposer, SyncOracle,
 };
 use sp_consensus::import_queue::{
 	BoxBlockImport, BasicQueue, Verifier, BoxJustificationImport, BoxFinalityProofImport,
@@ -57,6 +77,7 @@ use sp_consensus::import_queue::{
 use codec::{Encode, Decode};
 use prometheus_endpoint::Registry;
 use sc_client_api;
+use sc_consensus::fork_choice;
 use log::*;
 use sp_timestamp::{InherentError as TIError, TimestampInherentData};
 
@@ -72,6 +93,10 @@ pub enum Error<B: BlockT> {
 	FailedPreliminaryVerify,
 	#[display(fmt = "Rejecting block too far in future")]
 	TooFarInFuture,
+	#[display(fmt = "Header {:?} has no timestamp", _0)]
+	MissingTimestamp(B::Hash),
+	#[display(fmt = "Timestamp {} is not after the timestamp {} of the parent", _0, _1)]
+	TimestampNotIncreasing(u64, u64),
 	#[display(fmt = "Fetching best header failed using select chain: {:?}", _0)]
 	BestHeaderSelectChain(ConsensusError),
 	#[display(fmt = "Fetching best header failed: {:?}", _0)]
@@ -125,12 +150,24 @@ pub struct PowIntermediate<Difficulty> {
 pub static INTERMEDIATE_KEY: &[u8] = b"pow1";
 
 /// Auxiliary storage data for PoW.
-#[derive(Encode, Decode, Clone, Debug, Default)]
+#[derive(Encode, Clone, Debug, Default)]
 pub struct PowAux<Difficulty> {
 	/// Difficulty of the current block.
 	pub difficulty: Difficulty,
 	/// Total difficulty up to current block.
 	pub total_difficulty: Difficulty,
+	/// Timestamp of the current block in milliseconds, if the block carries one.
+	pub timestamp: Option<u64>,
+}
+
+impl<Difficulty: Decode> Decode for PowAux<Difficulty> {
+	fn decode<I: codec::Input>(input: &mut I) -> Result<Self, codec::Error> {
+		let difficulty = Difficulty::decode(input)?;
+		let total_difficulty = Difficulty::decode(input)?;
+		// Auxiliary data written before timestamps were recorded ends here.
+		let timestamp = Option::<u64>::decode(input).unwrap_or(None);
+		Ok(Self { difficulty, total_difficulty, timestamp })
+	}
 }
 
 impl<Difficulty> PowAux<Difficulty> where
@@ -147,6 +184,14 @@ impl<Difficulty> PowAux<Difficulty> where
 	}
 }
 
+/// Returns the timestamp in milliseconds the `header` carries in its pre-runtime digest.
+pub fn find_timestamp<B: BlockT>(header: &B::Header) -> Option<u64> {
+	header.digest().logs().iter()
+		.filter_map(|log| log.as_pre_runtime())
+		.find(|(id, _)| *id == POW_TIMESTAMP_ENGINE_ID)
+		.and_then(|(_, mut data)| u64::decode(&mut data).ok())
+}
+
 /// Algorithm used for proof of work.
 pub trait PowAlgorithm<B: BlockT> {
 	/// Difficulty for the algorithm.
@@ -155,8 +200,17 @@ pub trait PowAlgorithm<B: BlockT> {
 	/// Get the next block's difficulty.
 	///
 	/// This function will be called twice during the import process, so the implementation
-	/// should be properly cached.
+	/// should be properly cached. See the [`difficulty`] module for reusable difficulty
+	/// adjustment algorithms.
 	fn difficulty(&self, parent: B::Hash) -> Result<Self::Difficulty, Error<B>>;
+	/// Whether blocks need to carry their timestamp in a pre-runtime digest.
+	///
+	/// This is required by the difficulty adjustment algorithms of the [`difficulty`] module, as
+	/// they read the timestamps recorded in the auxiliary data. Blocks without a timestamp are
+	/// rejected if this returns `true`.
+	fn requires_timestamp(&self) -> bool {
+		false
+	}
 	/// Verify that the seal is valid against given pre hash when parent block is not yet imported.
 	///
 	/// None means that preliminary verify is not available for this algorithm.
@@ -186,16 +240,19 @@ pub trait PowAlgorithm<B: BlockT> {
 }
 
 /// A block importer for PoW.
-pub struct PowBlockImport<B: BlockT, I, C, S, Algorithm> {
+pub struct PowBlockImport<B: BlockT, I, C, S, Algorithm: PowAlgorithm<B>> {
 	algorithm: Algorithm,
 	inner: I,
 	select_chain: Option<S>,
 	client: Arc<C>,
 	inherent_data_providers: sp_inherents::InherentDataProviders,
 	check_inherents_after: <<B as BlockT>::Header as HeaderT>::Number,
+	fork_choice: Arc<dyn fork_choice::ForkChoiceStrategy<B, Algorithm::Difficulty>>,
 }
 
-impl<B: BlockT, I: Clone, C, S: Clone, Algorithm: Clone> Clone for PowBlockImport<B, I, C, S, Algorithm> {
+impl<B: BlockT, I: Clone, C, S: Clone, Algorithm: PowAlgorithm<B> + Clone> Clone
+	for PowBlockImport<B, I, C, S, Algorithm>
+{
 	fn clone(&self) -> Self {
 		Self {
 			algorithm: self.algorithm.clone(),
@@ -204,6 +261,7 @@ impl<B: BlockT, I: Clone, C, S: Clone, Algorithm: Clone> Clone for PowBlockImpor
 			client: self.client.clone(),
 			inherent_data_providers: self.inherent_data_providers.clone(),
 			check_inherents_after: self.check_inherents_after.clone(),
+			fork_choice: self.fork_choice.clone(),
 		}
 	}
 }
@@ -226,7 +284,20 @@ impl<B, I, C, S, Algorithm> PowBlockImport<B, I, C, S, Algorithm> where
 		inherent_data_providers: sp_inherents::InherentDataProviders,
 	) -> Self {
 		Self { inner, client, algorithm, check_inherents_after,
-			   select_chain, inherent_data_providers }
+			   select_chain, inherent_data_providers,
+			   fork_choice: Arc::new(fork_choice::HeaviestChainRule::strict()) }
+	}
+
+	/// Use the given fork-choice rule instead of only switching to strictly
+	/// heavier chains. The weight passed to the rule is the total difficulty of
+	/// the chain. Regardless of the rule, a chain that doesn't include the last
+	/// finalized block never becomes the best chain.
+	pub fn with_fork_choice(
+		mut self,
+		fork_choice: Arc<dyn fork_choice::ForkChoiceStrategy<B, Algorithm::Difficulty>>,
+	) -> Self {
+		self.fork_choice = fork_choice;
+		self
 	}
 
 	fn check_inherents(
@@ -268,6 +339,28 @@ impl<B, I, C, S, Algorithm> PowBlockImport<B, I, C, S, Algorithm> where
 			Ok(())
 		}
 	}
+
+	/// Check the `timestamp` of a block against the timestamp of its parent, if known, and against
+	/// the local time.
+	fn check_timestamp(&self, timestamp: u64, parent_timestamp: Option<u64>) -> Result<(), Error<B>> {
+		const MAX_TIMESTAMP_DRIFT_MILLIS: u64 = 60_000;
+
+		if let Some(parent_timestamp) = parent_timestamp {
+			if timestamp <= parent_timestamp {
+				return Err(Error::TimestampNotIncreasing(timestamp, parent_timestamp))
+			}
+		}
+
+		let timestamp_now = self.inherent_data_providers.create_inherent_data()
+			.map_err(Error::CreateInherents)?
+			.timestamp_inherent_data()
+			.map_err(Error::CreateInherents)?;
+		if timestamp > timestamp_now + MAX_TIMESTAMP_DRIFT_MILLIS {
+			return Err(Error::TooFarInFuture)
+		}
+
+		Ok(())
+	}
 }
 
 impl<B, I, C, S, Algorithm> BlockImport<B> for PowBlockImport<B, I, C, S, Algorithm> where
@@ -275,7 +368,8 @@ impl<B, I, C, S, Algorithm> BlockImport<B> for PowBlockImport<B, I, C, S, Algori
 	I: BlockImport<B, Transaction = sp_api::TransactionFor<C, B>> + Send + Sync,
 	I::Error: Into<ConsensusError>,
 	S: SelectChain<B>,
-	C: ProvideRuntimeApi<B> + Send + Sync + HeaderBackend<B> + AuxStore + ProvideCache<B> + BlockOf,
+	C: ProvideRuntimeApi<B> + Send + Sync + HeaderBackend<B> + AuxStore + ProvideCache<B> + BlockOf
+		+ HeaderMetadata<B, Error = sp_blockchain::Error>,
 	C::Api: BlockBuilderApi<B, Error = sp_blockchain::Error>,
 	Algorithm: PowAlgorithm<B>,
 	Algorithm::Difficulty: 'static,
@@ -295,17 +389,30 @@ impl<B, I, C, S, Algorithm> BlockImport<B> for PowBlockImport<B, I, C, S, Algori
 		mut block: BlockImportParams<B, Self::Transaction>,
 		new_cache: HashMap<CacheKeyId, Vec<u8>>,
 	) -> Result<ImportResult, Self::Error> {
-		let best_hash = match self.select_chain.as_ref() {
-			Some(select_chain) => select_chain.best_chain()
-				.map_err(|e| format!("Fetch best chain failed via select chain: {:?}", e))?
-				.hash(),
-			None => self.client.info().best_hash,
+		let (best_hash, best_number) = match self.select_chain.as_ref() {
+			Some(select_chain) => {
+				let best_header = select_chain.best_chain()
+					.map_err(|e| format!("Fetch best chain failed via select chain: {:?}", e))?;
+				(best_header.hash(), *best_header.number())
+			},
+			None => {
+				let info = self.client.info();
+				(info.best_hash, info.best_number)
+			},
 		};
 
 		let parent_hash = *block.header.parent_hash();
 		let best_aux = PowAux::read::<_, B>(self.client.as_ref(), &best_hash)?;
 		let mut aux = PowAux::read::<_, B>(self.client.as_ref(), &parent_hash)?;
 
+		let timestamp = find_timestamp::<B>(&block.header);
+		match timestamp {
+			Some(timestamp) => self.check_timestamp(timestamp, aux.timestamp)?,
+			None if self.algorithm.requires_timestamp() =>
+				return Err(Error::<B>::MissingTimestamp(block.header.hash()).into()),
+			None => (),
+		}
+
 		if let Some(inner_body) = block.body.take() {
 			let inherent_data = self.inherent_data_providers
 				.create_inherent_data().map_err(|e| e.into_string())?;
@@ -355,12 +462,26 @@ impl<B, I, C, S, Algorithm> BlockImport<B> for PowBlockImport<B, I, C, S, Algori
 
 		aux.difficulty = difficulty;
 		aux.total_difficulty.increment(difficulty);
+		aux.timestamp = timestamp;
 
 		let key = aux_key(&block.post_hash());
 		block.auxiliary.push((key, Some(aux.encode())));
 		if block.fork_choice.is_none() {
+			let best = fork_choice::BestBlock {
+				hash: best_hash,
+				number: best_number,
+				weight: best_aux.total_difficulty,
+			};
+			let is_new_best = self.fork_choice.is_new_best(
+				&block.header,
+				&aux.total_difficulty,
+				&best,
+			)?;
+
+			// a chain which doesn't include the last finalized block must not become
+			// the best chain, it can never be finalized.
 			block.fork_choice = Some(ForkChoiceStrategy::Custom(
-				aux.total_difficulty > best_aux.total_difficulty
+				is_new_best && builds_on_finalized(self.client.as_ref(), parent_hash)?
 			));
 		}
 
@@ -368,6 +489,21 @@ impl<B, I, C, S, Algorithm> BlockImport<B> for PowBlockImport<B, I, C, S, Algori
 	}
 }
 
+/// Whether the block `parent_hash` is, or descends from, the last finalized block.
+fn builds_on_finalized<B, C>(client: &C, parent_hash: B::Hash) -> Result<bool, Error<B>> where
+	B: BlockT,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = sp_blockchain::Error>,
+{
+	let finalized_hash = client.info().finalized_hash;
+	if finalized_hash == parent_hash {
+		return Ok(true)
+	}
+
+	let route = sp_blockchain::tree_route(client, finalized_hash, parent_hash)
+		.map_err(Error::Client)?;
+	Ok(route.retracted().is_empty())
+}
+
 /// A verifier for PoW blocks.
 pub struct PowVerifier<B: BlockT, Algorithm> {
 	algorithm: Algorithm,
@@ -500,179 +636,53 @@ pub fn import_queue<B, Transaction, Algorithm>(
 /// information, or just be a graffiti. `round` is for number of rounds the
 /// CPU miner runs each time. This parameter should be tweaked so that each
 /// mining round is within sub-second time.
+///
+/// This runs the mining worker and a single mining thread on a background
+/// thread, without exposing the work to external miners.
+#[deprecated(note = "Use `start_mining_worker` and `start_mining_threads` instead.")]
 pub fn start_mine<B: BlockT, C, Algorithm, E, SO, S, CAW>(
-	mut block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
+	block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
 	client: Arc<C>,
 	algorithm: Algorithm,
-	mut env: E,
+	env: E,
 	preruntime: Option<Vec<u8>>,
 	round: u32,
-	mut sync_oracle: SO,
+	sync_oracle: SO,
 	build_time: std::time::Duration,
 	select_chain: Option<S>,
 	inherent_data_providers: sp_inherents::InherentDataProviders,
 	can_author_with: CAW,
 ) where
-	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B> + 'static,
-	Algorithm: PowAlgorithm<B> + Send + Sync + 'static,
+	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B> + BlockchainEvents<B> + 'static,
+	Algorithm: PowAlgorithm<B> + Clone + Send + Sync + 'static,
+	Algorithm::Difficulty: Send + 'static,
 	E: Environment<B> + Send + Sync + 'static,
 	E::Error: std::fmt::Debug,
 	E::Proposer: Proposer<B, Transaction = sp_api::TransactionFor<C, B>>,
 	SO: SyncOracle + Send + Sync + 'static,
 	S: SelectChain<B> + 'static,
 	CAW: CanAuthorWith<B> + Send + 'static,
-{
-	if let Err(_) = register_pow_inherent_data_provider(&inherent_data_providers) {
-		warn!("Registering inherent data provider for timestamp failed");
-	}
-
-	thread::spawn(move || {
-		loop {
-			match mine_loop(
-				&mut block_import,
-				client.as_ref(),
-				&algorithm,
-				&mut env,
-				preruntime.as_ref(),
-				round,
-				&mut sync_oracle,
-				build_time.clone(),
-				select_chain.as_ref(),
-				&inherent_data_providers,
-				&can_author_with,
-			) {
-				Ok(()) => (),
-				Err(e) => error!(
-					"Mining block failed with {:?}. Sleep for 1 second before restarting...",
-					e
-				),
-			}
-			std::thread::sleep(std::time::Duration::new(1, 0));
-		}
-	});
-}
-
-fn mine_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
-	block_import: &mut BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
-	client: &C,
-	algorithm: &Algorithm,
-	env: &mut E,
-	preruntime: Option<&Vec<u8>>,
-	round: u32,
-	sync_oracle: &mut SO,
-	build_time: std::time::Duration,
-	select_chain: Option<&S>,
-	inherent_data_providers: &sp_inherents::InherentDataProviders,
-	can_author_with: &CAW,
-) -> Result<(), Error<B>> where
-	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B>,
-	Algorithm: PowAlgorithm<B>,
-	Algorithm::Difficulty: 'static,
-	E: Environment<B>,
-	E::Proposer: Proposer<B, Transaction = sp_api::TransactionFor<C, B>>,
-	E::Error: std::fmt::Debug,
-	SO: SyncOracle,
-	S: SelectChain<B>,
+	MiningHandle<B, Algorithm, C>: Send + 'static,
 	sp_api::TransactionFor<C, B>: 'static,
-	CAW: CanAuthorWith<B>,
 {
-	'outer: loop {
-		if sync_oracle.is_major_syncing() {
-			debug!(target: "pow", "Skipping proposal due to sync.");
-			std::thread::sleep(std::time::Duration::new(1, 0));
-			continue 'outer
-		}
-
-		let (best_hash, best_header) = match select_chain {
-			Some(select_chain) => {
-				let header = select_chain.best_chain()
-					.map_err(Error::BestHeaderSelectChain)?;
-				let hash = header.hash();
-				(hash, header)
-			},
-			None => {
-				let hash = client.info().best_hash;
-				let header = client.header(BlockId::Hash(hash))
-					.map_err(Error::BestHeader)?
-					.ok_or(Error::NoBestHeader)?;
-				(hash, header)
-			},
-		};
-
-		if let Err(err) = can_author_with.can_author_with(&BlockId::Hash(best_hash)) {
-			warn!(
-				target: "pow",
-				"Skipping proposal `can_author_with` returned: {} \
-				Probably a node update is required!",
-				err,
-			);
-			std::thread::sleep(std::time::Duration::from_secs(1));
-			continue 'outer
-		}
-
-		let proposer = futures::executor::block_on(env.init(&best_header))
-			.map_err(|e| Error::Environment(format!("{:?}", e)))?;
+	/// How often the work is rebuilt to include new transactions.
+	const REBUILD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-		let inherent_data = inherent_data_providers
-			.create_inherent_data().map_err(Error::CreateInherents)?;
-		let mut inherent_digest = Digest::default();
-		if let Some(preruntime) = &preruntime {
-			inherent_digest.push(DigestItem::PreRuntime(POW_ENGINE_ID, preruntime.to_vec()));
-		}
-		let proposal = futures::executor::block_on(proposer.propose(
-			inherent_data,
-			inherent_digest,
-			build_time.clone(),
-			RecordProof::No,
-		)).map_err(|e| Error::BlockProposingError(format!("{:?}", e)))?;
-
-		let (header, body) = proposal.block.deconstruct();
-		let (difficulty, seal) = {
-			let difficulty = algorithm.difficulty(best_hash)?;
-
-			loop {
-				let seal = algorithm.mine(
-					&BlockId::Hash(best_hash),
-					&header.hash(),
-					difficulty,
-					round,
-				)?;
-
-				if let Some(seal) = seal {
-					break (difficulty, seal)
-				}
-
-				if best_hash != client.info().best_hash {
-					continue 'outer
-				}
-			}
-		};
-
-		let (hash, seal) = {
-			let seal = DigestItem::Seal(POW_ENGINE_ID, seal);
-			let mut header = header.clone();
-			header.digest_mut().push(seal);
-			let hash = header.hash();
-			let seal = header.digest_mut().pop()
-				.expect("Pushed one seal above; length greater than zero; qed");
-			(hash, seal)
-		};
-
-		let intermediate = PowIntermediate::<Algorithm::Difficulty> {
-			difficulty: Some(difficulty),
-		};
-
-		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
-		import_block.post_digests.push(seal);
-		import_block.body = Some(body);
-		import_block.storage_changes = Some(proposal.storage_changes);
-		import_block.intermediates.insert(
-			Cow::from(INTERMEDIATE_KEY),
-			Box::new(intermediate) as Box<dyn Any>
+	thread::spawn(move || {
+		let (handle, task) = start_mining_worker(
+			block_import,
+			client,
+			algorithm.clone(),
+			env,
+			preruntime,
+			sync_oracle,
+			REBUILD_TIMEOUT,
+			build_time,
+			select_chain,
+			inherent_data_providers,
+			can_author_with,
 		);
-		import_block.post_hash = Some(hash);
-
-		block_import.import_block(import_block, HashMap::default())
-			.map_err(|e| Error::BlockBuiltError(best_hash, e))?;
-	}
+		start_mining_threads(handle, algorithm, 1, round);
+		futures::executor::block_on(task);
+	});
 }
@@ -0,0 +1,89 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! RPC API for external miners, to fetch the current work and to submit seals.
+
+use codec::Encode;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+use crate::{MiningHandle, PowAlgorithm};
+
+pub use self::gen_client::Client as PowClient;
+
+/// The work to mine, returned by `pow_getWork`.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Work<Hash> {
+	/// Hash of the block the work is built on.
+	pub best_hash: Hash,
+	/// The hash to mine a seal for.
+	pub pre_hash: Hash,
+	/// The SCALE encoded difficulty the seal needs to satisfy.
+	pub difficulty: Bytes,
+}
+
+/// RPC trait that provides methods for external miners.
+#[rpc]
+pub trait PowApi<Hash> {
+	/// Returns the current work, or `null` if the node doesn't mine at the moment.
+	#[rpc(name = "pow_getWork")]
+	fn work(&self) -> Result<Option<Work<Hash>>>;
+
+	/// Submits the seal for the work with the given `pre_hash`.
+	///
+	/// Returns `true` if the seal was valid and the sealed block was imported, `false` if the
+	/// seal was invalid or the work is stale.
+	#[rpc(name = "pow_submitSeal")]
+	fn submit_seal(&self, pre_hash: Hash, seal: Bytes) -> Result<bool>;
+}
+
+/// A struct that implements the [`PowApi`].
+pub struct Pow<B: BlockT, Algorithm: PowAlgorithm<B>, C: ProvideRuntimeApi<B>> {
+	handle: MiningHandle<B, Algorithm, C>,
+}
+
+impl<B: BlockT, Algorithm: PowAlgorithm<B>, C: ProvideRuntimeApi<B>> Pow<B, Algorithm, C> {
+	/// Create new `Pow` with the given handle to the mining worker.
+	pub fn new(handle: MiningHandle<B, Algorithm, C>) -> Self {
+		Self { handle }
+	}
+}
+
+impl<B, Algorithm, C> PowApi<B::Hash> for Pow<B, Algorithm, C> where
+	B: BlockT,
+	Algorithm: PowAlgorithm<B> + 'static,
+	Algorithm::Difficulty: 'static,
+	C: ProvideRuntimeApi<B> + 'static,
+	sp_api::TransactionFor<C, B>: 'static,
+	MiningHandle<B, Algorithm, C>: Send + Sync,
+{
+	fn work(&self) -> Result<Option<Work<B::Hash>>> {
+		Ok(self.handle.metadata().map(|metadata| Work {
+			best_hash: metadata.best_hash,
+			pre_hash: metadata.pre_hash,
+			difficulty: metadata.difficulty.encode().into(),
+		}))
+	}
+
+	fn submit_seal(&self, pre_hash: B::Hash, seal: Bytes) -> Result<bool> {
+		Ok(self.handle.submit(&pre_hash, seal.0))
+	}
+}
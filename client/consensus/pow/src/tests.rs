@@ -0,0 +1,498 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Tests for PoW block production through the mining worker and its RPC, and for GRANDPA
+//! finality of PoW blocks.
+
+use super::*;
+use std::time::Duration;
+use futures::{future, prelude::*};
+use parking_lot::Mutex;
+use sc_finality_grandpa::{
+	Config, GenesisAuthoritySetProvider, GrandpaParams, LinkHalf, SharedVoterState,
+	run_grandpa_voter,
+};
+use sc_network::config::{ProtocolConfig, BoxFinalityProofRequestBuilder};
+use sc_network_test::{
+	Block, BlockImportAdapter, Hash, Peer, PeersClient, PeersFullClient, TestNetFactory,
+};
+use sp_consensus::import_queue::{BoxFinalityProofImport, BoxJustificationImport};
+use sp_core::{U256, traits::BareCryptoStorePtr};
+use sp_finality_grandpa::{AuthorityList, AuthorityPair};
+use sp_keyring::Ed25519Keyring;
+use tokio::runtime::Runtime;
+
+type PeerData = Mutex<
+	Option<
+		LinkHalf<Block, PeersFullClient, HeaviestChain<substrate_test_runtime_client::Backend, Block>>
+	>
+>;
+
+/// PoW algorithm where every block has difficulty one and the empty seal is valid.
+#[derive(Clone)]
+struct TestAlgorithm;
+
+impl PowAlgorithm<Block> for TestAlgorithm {
+	type Difficulty = U256;
+
+	fn difficulty(&self, _parent: Hash) -> Result<U256, Error<Block>> {
+		Ok(U256::one())
+	}
+
+	fn verify(
+		&self,
+		_parent: &BlockId<Block>,
+		_pre_hash: &Hash,
+		seal: &Seal,
+		_difficulty: U256,
+	) -> Result<bool, Error<Block>> {
+		Ok(seal.is_empty())
+	}
+
+	fn mine(
+		&self,
+		_parent: &BlockId<Block>,
+		_pre_hash: &Hash,
+		_difficulty: U256,
+		_round: u32,
+	) -> Result<Option<Seal>, Error<Block>> {
+		Ok(Some(Vec::new()))
+	}
+}
+
+struct TestGenesis(AuthorityList);
+
+impl GenesisAuthoritySetProvider<Block> for TestGenesis {
+	fn get(&self) -> sp_blockchain::Result<AuthorityList> {
+		Ok(self.0.clone())
+	}
+}
+
+/// Network of peers importing PoW blocks through the GRANDPA block import.
+struct PowGrandpaTestNet {
+	peers: Vec<Peer<PeerData>>,
+	genesis_authorities: TestGenesis,
+}
+
+impl PowGrandpaTestNet {
+	fn new(genesis_authorities: AuthorityList, n_peers: usize) -> Self {
+		let mut net = PowGrandpaTestNet {
+			peers: Vec::with_capacity(n_peers),
+			genesis_authorities: TestGenesis(genesis_authorities),
+		};
+		for _ in 0..n_peers {
+			net.add_full_peer();
+		}
+		net
+	}
+}
+
+impl TestNetFactory for PowGrandpaTestNet {
+	type Verifier = PowVerifier<Block, TestAlgorithm>;
+	type PeerData = PeerData;
+
+	fn from_config(_config: &ProtocolConfig) -> Self {
+		PowGrandpaTestNet {
+			peers: Vec::new(),
+			genesis_authorities: TestGenesis(Vec::new()),
+		}
+	}
+
+	fn make_verifier(
+		&self,
+		_client: PeersClient,
+		_config: &ProtocolConfig,
+		_peer_data: &PeerData,
+	) -> Self::Verifier {
+		PowVerifier::new(TestAlgorithm)
+	}
+
+	fn make_block_import<Transaction>(&self, client: PeersClient)
+		-> (
+			BlockImportAdapter<Transaction>,
+			Option<BoxJustificationImport<Block>>,
+			Option<BoxFinalityProofImport<Block>>,
+			Option<BoxFinalityProofRequestBuilder<Block>>,
+			PeerData,
+		)
+	{
+		match client {
+			PeersClient::Full(client, backend) => {
+				let select_chain = HeaviestChain::new(backend);
+				let (grandpa_block_import, link) = sc_finality_grandpa::block_import(
+					client.clone(),
+					&self.genesis_authorities,
+					select_chain.clone(),
+				).expect("Could not create block import for fresh peer.");
+				let justification_import = Box::new(grandpa_block_import.clone());
+
+				let inherent_data_providers = InherentDataProviders::new();
+				register_pow_inherent_data_provider(&inherent_data_providers)
+					.expect("Registers the timestamp inherent data provider");
+				let block_import = PowBlockImport::new(
+					grandpa_block_import,
+					client,
+					TestAlgorithm,
+					0,
+					Some(select_chain),
+					inherent_data_providers,
+				);
+
+				(
+					BlockImportAdapter::new_full(block_import),
+					Some(justification_import),
+					None,
+					None,
+					Mutex::new(Some(link)),
+				)
+			},
+			PeersClient::Light(..) => panic!("PoW test network only supports full peers"),
+		}
+	}
+
+	fn peer(&mut self, i: usize) -> &mut Peer<PeerData> {
+		&mut self.peers[i]
+	}
+
+	fn peers(&self) -> &Vec<Peer<PeerData>> {
+		&self.peers
+	}
+
+	fn mut_peers<F: FnOnce(&mut Vec<Peer<PeerData>>)>(&mut self, closure: F) {
+		closure(&mut self.peers);
+	}
+}
+
+/// Seal a built block with the empty seal of the `TestAlgorithm`.
+fn seal(
+	builder: sc_block_builder::BlockBuilder<Block, PeersFullClient, substrate_test_runtime_client::Backend>,
+) -> Block {
+	let mut block = builder.build().expect("Builds the block").block;
+	block.header.digest_mut().push(DigestItem::Seal(POW_ENGINE_ID, Vec::new()));
+	block
+}
+
+fn make_ids(keys: &[Ed25519Keyring]) -> AuthorityList {
+	keys.iter().map(|key| (key.public().into(), 1)).collect()
+}
+
+fn create_keystore(authority: Ed25519Keyring) -> (BareCryptoStorePtr, tempfile::TempDir) {
+	let keystore_path = tempfile::tempdir().expect("Creates keystore path");
+	let keystore = sc_keystore::Store::open(keystore_path.path(), None).expect("Creates keystore");
+	keystore.write().insert_ephemeral_from_seed::<AuthorityPair>(&authority.to_seed())
+		.expect("Creates authority key");
+
+	(keystore, keystore_path)
+}
+
+#[test]
+fn grandpa_finalizes_pow_blocks() {
+	let _ = env_logger::try_init();
+	let mut runtime = Runtime::new().unwrap();
+	let peers = &[Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+
+	let mut net = PowGrandpaTestNet::new(make_ids(peers), 3);
+	net.peer(0).generate_blocks(20, BlockOrigin::File, seal);
+	net.block_until_sync();
+
+	for i in 0..3 {
+		assert_eq!(net.peer(i).client().info().best_number, 20, "Peer #{} failed to sync", i);
+	}
+
+	let net = Arc::new(Mutex::new(net));
+	let mut finalized = Vec::new();
+	let mut keystore_paths = Vec::new();
+	for (peer_id, key) in peers.iter().enumerate() {
+		let (keystore, keystore_path) = create_keystore(*key);
+		keystore_paths.push(keystore_path);
+
+		let (client, network, link) = {
+			let mut net = net.lock();
+			let peer = net.peer(peer_id);
+			let link = peer.data.lock().take().expect("link initialized at startup; qed");
+			(peer.client().clone(), peer.network_service().clone(), link)
+		};
+
+		finalized.push(
+			client.finality_notification_stream()
+				.take_while(|n| future::ready(*n.header.number() < 20))
+				.for_each(|_| future::ready(()))
+		);
+
+		let voter = run_grandpa_voter(GrandpaParams {
+			config: Config {
+				gossip_duration: Duration::from_millis(500),
+				justification_period: 32,
+				keystore: Some(keystore),
+				name: Some(format!("peer#{}", peer_id)),
+				is_authority: true,
+				observer_enabled: true,
+			},
+			link,
+			network,
+			inherent_data_providers: InherentDataProviders::new(),
+			telemetry_on_connect: None,
+			voting_rule: (),
+			prometheus_registry: None,
+			shared_voter_state: SharedVoterState::empty(),
+		}).expect("all in order with client and network");
+		runtime.spawn(voter);
+	}
+
+	let drive_network = future::poll_fn(|cx| {
+		net.lock().poll(cx);
+		std::task::Poll::<()>::Pending
+	});
+	runtime.block_on(future::select(Box::pin(future::join_all(finalized)), Box::pin(drive_network)));
+
+	for i in 0..3 {
+		assert_eq!(net.lock().peer(i).client().info().finalized_number, 20);
+	}
+}
+
+#[test]
+fn heavier_chain_without_finalized_block_is_not_best() {
+	let _ = env_logger::try_init();
+	let mut net = PowGrandpaTestNet::new(make_ids(&[Ed25519Keyring::Alice]), 1);
+	let peer = net.peer(0);
+
+	let common = peer.generate_blocks(1, BlockOrigin::File, seal);
+	let finalized = peer.generate_blocks_at(BlockId::Hash(common), 2, BlockOrigin::File, seal, false);
+	let heavier = peer.generate_blocks_at(BlockId::Hash(common), 3, BlockOrigin::File, seal, false);
+	assert_eq!(peer.client().info().best_hash, heavier);
+
+	// finalizing a block on a lighter fork makes it the best block.
+	peer.client().finalize_block(BlockId::Hash(finalized), None, false).unwrap();
+	assert_eq!(peer.client().info().best_hash, finalized);
+
+	// the heavier fork doesn't build on the finalized block, it doesn't become best when growing.
+	peer.generate_blocks_at(BlockId::Hash(heavier), 1, BlockOrigin::File, seal, false);
+	assert_eq!(peer.client().info().best_hash, finalized);
+
+	// while blocks building on the finalized block do.
+	let best = peer.generate_blocks_at(BlockId::Hash(finalized), 1, BlockOrigin::File, seal, false);
+	assert_eq!(peer.client().info().best_hash, best);
+
+	let select_chain = match peer.client() {
+		PeersClient::Full(_, backend) => HeaviestChain::new(backend.clone()),
+		PeersClient::Light(..) => unreachable!("the test network only has full peers"),
+	};
+	assert_eq!(select_chain.best_chain().unwrap().hash(), best);
+	// finality targets are searched on the best chain rather than on the longest one.
+	assert_eq!(select_chain.finality_target(common, None).unwrap(), Some(best));
+}
+
+type TestClient = substrate_test_runtime_client::TestClient;
+type TestHandle = MiningHandle<Block, TestAlgorithm, TestClient>;
+
+/// Environment building empty blocks with the block builder of the client.
+struct TestEnvironment(Arc<TestClient>);
+
+struct TestProposer {
+	client: Arc<TestClient>,
+	parent_hash: Hash,
+}
+
+impl Environment<Block> for TestEnvironment {
+	type CreateProposer = future::Ready<Result<TestProposer, ConsensusError>>;
+	type Proposer = TestProposer;
+	type Error = ConsensusError;
+
+	fn init(&mut self, parent_header: &<Block as BlockT>::Header) -> Self::CreateProposer {
+		future::ready(Ok(TestProposer { client: self.0.clone(), parent_hash: parent_header.hash() }))
+	}
+}
+
+impl Proposer<Block> for TestProposer {
+	type Error = ConsensusError;
+	type Transaction = sp_api::TransactionFor<TestClient, Block>;
+	type Proposal = future::Ready<Result<sp_consensus::Proposal<Block, Self::Transaction>, ConsensusError>>;
+
+	fn propose(
+		self,
+		_: InherentData,
+		inherent_digests: sp_runtime::traits::DigestFor<Block>,
+		_: Duration,
+		_: sp_consensus::RecordProof,
+	) -> Self::Proposal {
+		use sc_block_builder::BlockBuilderProvider;
+
+		let proposal = self.client.new_block_at(&BlockId::Hash(self.parent_hash), inherent_digests, false)
+			.and_then(|builder| builder.build())
+			.map(|built| sp_consensus::Proposal {
+				block: built.block,
+				proof: built.proof,
+				storage_changes: built.storage_changes,
+			})
+			.map_err(|e| ConsensusError::ClientImport(e.to_string()));
+
+		future::ready(proposal)
+	}
+}
+
+/// Start a mining worker importing blocks into `client`, without mining threads.
+fn start_test_worker(client: Arc<TestClient>) -> (TestHandle, impl Future<Output = ()>) {
+	let inherent_data_providers = InherentDataProviders::new();
+	let block_import = PowBlockImport::new(
+		client.clone(),
+		client.clone(),
+		TestAlgorithm,
+		0,
+		None::<sc_consensus::LongestChain<substrate_test_runtime_client::Backend, Block>>,
+		inherent_data_providers.clone(),
+	);
+
+	start_mining_worker(
+		Box::new(block_import),
+		client.clone(),
+		TestAlgorithm,
+		TestEnvironment(client),
+		None,
+		sp_consensus::NoNetwork,
+		Duration::from_secs(60),
+		Duration::from_secs(1),
+		None::<sc_consensus::LongestChain<substrate_test_runtime_client::Backend, Block>>,
+		inherent_data_providers,
+		sp_consensus::AlwaysCanAuthor,
+	)
+}
+
+/// Drive the worker `task` until it publishes work satisfying `condition`.
+fn wait_for_work(
+	task: &mut (impl Future<Output = ()> + Unpin),
+	handle: &TestHandle,
+	condition: impl Fn(&MiningMetadata<Hash, U256>) -> bool,
+) -> MiningMetadata<Hash, U256> {
+	let work = async {
+		loop {
+			match handle.metadata() {
+				Some(work) if condition(&work) => return work,
+				_ => futures_timer::Delay::new(Duration::from_millis(10)).await,
+			}
+		}
+	};
+
+	match futures::executor::block_on(future::select(task, Box::pin(work))) {
+		future::Either::Left(_) => panic!("the mining worker stopped"),
+		future::Either::Right((work, _)) => work,
+	}
+}
+
+#[test]
+fn mining_worker_imports_valid_seals_of_the_current_work() {
+	let _ = env_logger::try_init();
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let genesis_hash = client.info().best_hash;
+	let (handle, task) = start_test_worker(client.clone());
+	let mut task = Box::pin(task);
+
+	let work = wait_for_work(&mut task, &handle, |_| true);
+	assert_eq!(work.best_hash, genesis_hash);
+	assert_eq!(work.difficulty, U256::one());
+
+	// invalid seals and seals of unknown work are rejected without consuming the work.
+	assert!(!handle.submit(&work.pre_hash, vec![1]));
+	assert!(!handle.submit(&Hash::default(), Vec::new()));
+	assert_eq!(handle.metadata(), Some(work.clone()));
+
+	let version = handle.version();
+	assert!(handle.submit(&work.pre_hash, Vec::new()));
+	assert_eq!(client.info().best_number, 1);
+	assert!(handle.version() > version);
+	assert_eq!(handle.metadata(), None);
+
+	// the work is consumed by the import.
+	assert!(!handle.submit(&work.pre_hash, Vec::new()));
+
+	// and rebuilt on top of the imported block.
+	let best_hash = client.info().best_hash;
+	let work = wait_for_work(&mut task, &handle, |work| work.best_hash == best_hash);
+	assert!(handle.submit(&work.pre_hash, Vec::new()));
+	assert_eq!(client.info().best_number, 2);
+}
+
+#[test]
+fn mining_worker_cancels_work_on_stale_best_block() {
+	use sc_block_builder::BlockBuilderProvider;
+	use substrate_test_runtime_client::ClientBlockImportExt;
+
+	let _ = env_logger::try_init();
+	let mut client = Arc::new(substrate_test_runtime_client::new());
+	let (handle, task) = start_test_worker(client.clone());
+	let mut task = Box::pin(task);
+
+	let stale = wait_for_work(&mut task, &handle, |_| true);
+	let version = handle.version();
+
+	// a block imported from elsewhere becomes the new best block.
+	let mut block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	block.header.digest_mut().push(DigestItem::Seal(POW_ENGINE_ID, Vec::new()));
+	let best_hash = block.header.hash();
+	client.import(BlockOrigin::File, block).unwrap();
+
+	let work = wait_for_work(&mut task, &handle, |work| work.best_hash == best_hash);
+	assert!(handle.version() > version);
+	assert_ne!(work.pre_hash, stale.pre_hash);
+
+	// seals of the work on the previous best block are rejected.
+	assert!(!handle.submit(&stale.pre_hash, Vec::new()));
+	assert_eq!(client.info().best_hash, best_hash);
+
+	assert!(handle.submit(&work.pre_hash, Vec::new()));
+	assert_eq!(client.info().best_number, 2);
+}
+
+#[test]
+fn rpc_serves_the_work_and_submits_seals() {
+	use jsonrpc_core::IoHandler;
+	use rpc::{Pow, PowApi};
+
+	let _ = env_logger::try_init();
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let (handle, task) = start_test_worker(client.clone());
+	let mut task = Box::pin(task);
+
+	let mut io = IoHandler::new();
+	io.extend_with(PowApi::to_delegate(Pow::new(handle.clone())));
+
+	let request = r#"{"jsonrpc":"2.0","method":"pow_getWork","params":[],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(Some(response.into()), io.handle_request_sync(request));
+
+	let work = wait_for_work(&mut task, &handle, |_| true);
+
+	let response = format!(
+		r#"{{"jsonrpc":"2.0","result":{{"bestHash":"{:?}","preHash":"{:?}","difficulty":"0x01{}"}},"id":1}}"#,
+		work.best_hash,
+		work.pre_hash,
+		"00".repeat(31),
+	);
+	assert_eq!(Some(response), io.handle_request_sync(request));
+
+	let submit = |seal: &str| format!(
+		r#"{{"jsonrpc":"2.0","method":"pow_submitSeal","params":["{:?}","{}"],"id":1}}"#,
+		work.pre_hash,
+		seal,
+	);
+	let rejected = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	let accepted = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(Some(rejected.into()), io.handle_request_sync(&submit("0x01")));
+	assert_eq!(Some(accepted.into()), io.handle_request_sync(&submit("0x")));
+	assert_eq!(client.info().best_number, 1);
+	assert_eq!(Some(rejected.into()), io.handle_request_sync(&submit("0x")));
+}
@@ -0,0 +1,462 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! The mining worker holding the current work of a PoW node.
+//!
+//! The worker builds a block on top of the best block and publishes its pre-hash and difficulty
+//! as the current work. Any number of miners, local threads as well as external miners through
+//! the RPC, can then search for a seal and submit it. The first valid seal imports the block.
+//!
+//! The work is rebuilt whenever a new best block is imported and after a timeout, so that new
+//! transactions are included. Work on the previous best block is cancelled as soon as the new
+//! best block is known, before the new block is built.
+
+use std::{
+	any::Any,
+	borrow::Cow,
+	collections::HashMap,
+	sync::{Arc, atomic::{AtomicUsize, Ordering}},
+	thread,
+	time::Duration,
+};
+use codec::Encode;
+use futures::{prelude::*, select};
+use futures_timer::Delay;
+use log::*;
+use parking_lot::Mutex;
+use sc_client_api::{BlockchainEvents, backend::AuxStore};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::{
+	BlockImportParams, BlockOrigin, CanAuthorWith, Environment, Proposal, Proposer, RecordProof,
+	SelectChain, SyncOracle, import_queue::BoxBlockImport,
+};
+use sp_consensus_pow::{Seal, POW_ENGINE_ID, POW_TIMESTAMP_ENGINE_ID};
+use sp_runtime::{
+	generic::{BlockId, Digest, DigestItem},
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use sp_timestamp::TimestampInherentData;
+use crate::{
+	Error, INTERMEDIATE_KEY, PowAlgorithm, PowAux, PowIntermediate,
+	register_pow_inherent_data_provider,
+};
+
+/// The work miners search a seal for.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct MiningMetadata<H, D> {
+	/// Hash of the block the work is built on.
+	pub best_hash: H,
+	/// Hash of the built block without the seal, the hash the seal is mined for.
+	pub pre_hash: H,
+	/// The difficulty the seal needs to satisfy.
+	pub difficulty: D,
+}
+
+/// A built block waiting for its seal.
+struct MiningBuild<B: BlockT, Algorithm: PowAlgorithm<B>, C: ProvideRuntimeApi<B>> {
+	metadata: MiningMetadata<B::Hash, Algorithm::Difficulty>,
+	proposal: Proposal<B, sp_api::TransactionFor<C, B>>,
+}
+
+/// A handle to the mining worker, to fetch the current work and to submit seals.
+///
+/// Every change of the current work bumps the version of the worker, so that miners can cheaply
+/// check whether their work became stale.
+pub struct MiningHandle<B: BlockT, Algorithm: PowAlgorithm<B>, C: ProvideRuntimeApi<B>> {
+	build: Arc<Mutex<Option<MiningBuild<B, Algorithm, C>>>>,
+	algorithm: Arc<Algorithm>,
+	block_import: Arc<Mutex<BoxBlockImport<B, sp_api::TransactionFor<C, B>>>>,
+	version: Arc<AtomicUsize>,
+}
+
+impl<B: BlockT, Algorithm: PowAlgorithm<B>, C: ProvideRuntimeApi<B>> Clone for MiningHandle<B, Algorithm, C> {
+	fn clone(&self) -> Self {
+		Self {
+			build: self.build.clone(),
+			algorithm: self.algorithm.clone(),
+			block_import: self.block_import.clone(),
+			version: self.version.clone(),
+		}
+	}
+}
+
+impl<B, Algorithm, C> MiningHandle<B, Algorithm, C> where
+	B: BlockT,
+	Algorithm: PowAlgorithm<B>,
+	Algorithm::Difficulty: 'static,
+	C: ProvideRuntimeApi<B>,
+	sp_api::TransactionFor<C, B>: 'static,
+{
+	fn new(algorithm: Algorithm, block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>) -> Self {
+		Self {
+			build: Arc::new(Mutex::new(None)),
+			algorithm: Arc::new(algorithm),
+			block_import: Arc::new(Mutex::new(block_import)),
+			version: Arc::new(AtomicUsize::new(0)),
+		}
+	}
+
+	/// The version of the current work.
+	pub fn version(&self) -> usize {
+		self.version.load(Ordering::SeqCst)
+	}
+
+	/// The current work, if there is any.
+	pub fn metadata(&self) -> Option<MiningMetadata<B::Hash, Algorithm::Difficulty>> {
+		self.build.lock().as_ref().map(|build| build.metadata.clone())
+	}
+
+	/// Submit a `seal` for the work with the given `pre_hash`.
+	///
+	/// Returns `true` if the seal is valid for the current work and the sealed block was
+	/// imported. Seals of stale work are rejected.
+	///
+	/// The current work isn't locked while verifying the seal and importing the block, so that
+	/// miners and the worker aren't blocked meanwhile.
+	pub fn submit(&self, pre_hash: &B::Hash, seal: Seal) -> bool {
+		let metadata = match self.metadata() {
+			Some(metadata) if metadata.pre_hash == *pre_hash => metadata,
+			_ => {
+				debug!(target: "pow", "Rejecting seal for stale work {:?}", pre_hash);
+				return false
+			}
+		};
+
+		match self.algorithm.verify(
+			&BlockId::Hash(metadata.best_hash),
+			&metadata.pre_hash,
+			&seal,
+			metadata.difficulty,
+		) {
+			Ok(true) => (),
+			Ok(false) => {
+				debug!(target: "pow", "Rejecting invalid seal for {:?}", pre_hash);
+				return false
+			}
+			Err(e) => {
+				warn!(target: "pow", "Verifying seal for {:?} failed: {:?}", pre_hash, e);
+				return false
+			}
+		}
+
+		// The work may have been replaced or consumed by another seal while verifying.
+		let build = {
+			let mut current = self.build.lock();
+			match current.take() {
+				Some(build) if build.metadata.pre_hash == *pre_hash => {
+					// The work is consumed by the import, whether it succeeds or not.
+					self.version.fetch_add(1, Ordering::SeqCst);
+					build
+				}
+				build => {
+					*current = build;
+					debug!(target: "pow", "Rejecting seal for stale work {:?}", pre_hash);
+					return false
+				}
+			}
+		};
+
+		let (header, body) = build.proposal.block.deconstruct();
+		let (hash, seal) = {
+			let seal = DigestItem::Seal(POW_ENGINE_ID, seal);
+			let mut header = header.clone();
+			header.digest_mut().push(seal);
+			let hash = header.hash();
+			let seal = header.digest_mut().pop()
+				.expect("Pushed one seal above; length greater than zero; qed");
+			(hash, seal)
+		};
+
+		let intermediate = PowIntermediate::<Algorithm::Difficulty> {
+			difficulty: Some(build.metadata.difficulty),
+		};
+
+		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
+		import_block.post_digests.push(seal);
+		import_block.body = Some(body);
+		import_block.storage_changes = Some(build.proposal.storage_changes);
+		import_block.intermediates.insert(
+			Cow::from(INTERMEDIATE_KEY),
+			Box::new(intermediate) as Box<dyn Any>
+		);
+		import_block.post_hash = Some(hash);
+
+		let import_result = self.block_import.lock().import_block(import_block, HashMap::default());
+		match import_result {
+			Ok(_) => {
+				info!(target: "pow", "✅ Successfully mined block on top of: {}", build.metadata.best_hash);
+				true
+			}
+			Err(e) => {
+				error!(
+					target: "pow",
+					"{}",
+					Error::<B>::BlockBuiltError(build.metadata.best_hash, e),
+				);
+				false
+			}
+		}
+	}
+
+	/// Replace the current work by `build`.
+	fn on_build(&self, build: Option<MiningBuild<B, Algorithm, C>>) {
+		*self.build.lock() = build;
+		self.version.fetch_add(1, Ordering::SeqCst);
+	}
+}
+
+/// Start the mining worker for PoW.
+///
+/// Returns a handle to the worker and the future that keeps its work up to date, which needs
+/// to be spawned. Work is built on top of the best block with the given `env` whenever a new best
+/// block is imported, and at least every `timeout` to include new transactions.
+///
+/// `preruntime` is a parameter that allows a custom additional pre-runtime digest to be inserted
+/// for blocks being built. This can encode authorship information, or just be a graffiti.
+pub fn start_mining_worker<B, C, Algorithm, E, SO, S, CAW>(
+	block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
+	client: Arc<C>,
+	algorithm: Algorithm,
+	mut env: E,
+	preruntime: Option<Vec<u8>>,
+	sync_oracle: SO,
+	timeout: Duration,
+	build_time: Duration,
+	select_chain: Option<S>,
+	inherent_data_providers: sp_inherents::InherentDataProviders,
+	can_author_with: CAW,
+) -> (MiningHandle<B, Algorithm, C>, impl Future<Output = ()>) where
+	B: BlockT,
+	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B> + BlockchainEvents<B> + 'static,
+	Algorithm: PowAlgorithm<B> + Clone + Send + Sync + 'static,
+	Algorithm::Difficulty: 'static,
+	E: Environment<B> + Send + Sync + 'static,
+	E::Error: std::fmt::Debug,
+	E::Proposer: Proposer<B, Transaction = sp_api::TransactionFor<C, B>>,
+	SO: SyncOracle + Send + Sync + 'static,
+	S: SelectChain<B> + 'static,
+	CAW: CanAuthorWith<B> + Send + 'static,
+	sp_api::TransactionFor<C, B>: 'static,
+{
+	if let Err(_) = register_pow_inherent_data_provider(&inherent_data_providers) {
+		warn!("Registering inherent data provider for timestamp failed");
+	}
+
+	let handle = MiningHandle::new(algorithm.clone(), block_import);
+	let worker = handle.clone();
+	let mut notifications = client.import_notification_stream().fuse();
+
+	let task = async move {
+		let mut sync_oracle = sync_oracle;
+		loop {
+			match build(
+				client.as_ref(),
+				&algorithm,
+				&mut env,
+				preruntime.as_ref(),
+				&mut sync_oracle,
+				build_time,
+				select_chain.as_ref(),
+				&inherent_data_providers,
+				&can_author_with,
+			).await {
+				Ok(build) => worker.on_build(build),
+				Err(e) => {
+					error!(target: "pow", "Building work failed with {:?}", e);
+					worker.on_build(None);
+				}
+			}
+
+			let mut delay = Delay::new(timeout).fuse();
+			loop {
+				select! {
+					notification = notifications.next() => match notification {
+						Some(notification) if notification.is_new_best => {
+							// Stop the miners before building the new work.
+							worker.on_build(None);
+							break
+						}
+						Some(_) => continue,
+						None => return,
+					},
+					_ = delay => break,
+				}
+			}
+		}
+	};
+
+	(handle, task)
+}
+
+/// Build the work on top of the best block, if the node should author blocks.
+async fn build<B, C, Algorithm, E, SO, S, CAW>(
+	client: &C,
+	algorithm: &Algorithm,
+	env: &mut E,
+	preruntime: Option<&Vec<u8>>,
+	sync_oracle: &mut SO,
+	build_time: Duration,
+	select_chain: Option<&S>,
+	inherent_data_providers: &sp_inherents::InherentDataProviders,
+	can_author_with: &CAW,
+) -> Result<Option<MiningBuild<B, Algorithm, C>>, Error<B>> where
+	B: BlockT,
+	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B>,
+	Algorithm: PowAlgorithm<B>,
+	E: Environment<B>,
+	E::Proposer: Proposer<B, Transaction = sp_api::TransactionFor<C, B>>,
+	E::Error: std::fmt::Debug,
+	SO: SyncOracle,
+	S: SelectChain<B>,
+	CAW: CanAuthorWith<B>,
+{
+	if sync_oracle.is_major_syncing() {
+		debug!(target: "pow", "Skipping proposal due to sync.");
+		return Ok(None)
+	}
+
+	let (best_hash, best_header) = match select_chain {
+		Some(select_chain) => {
+			let header = select_chain.best_chain()
+				.map_err(Error::BestHeaderSelectChain)?;
+			let hash = header.hash();
+			(hash, header)
+		},
+		None => {
+			let hash = client.info().best_hash;
+			let header = client.header(BlockId::Hash(hash))
+				.map_err(Error::BestHeader)?
+				.ok_or(Error::NoBestHeader)?;
+			(hash, header)
+		},
+	};
+
+	if let Err(err) = can_author_with.can_author_with(&BlockId::Hash(best_hash)) {
+		warn!(
+			target: "pow",
+			"Skipping proposal `can_author_with` returned: {} \
+			Probably a node update is required!",
+			err,
+		);
+		return Ok(None)
+	}
+
+	let proposer = env.init(&best_header).await
+		.map_err(|e| Error::Environment(format!("{:?}", e)))?;
+
+	let inherent_data = inherent_data_providers
+		.create_inherent_data().map_err(Error::CreateInherents)?;
+	let mut inherent_digest = Digest::default();
+	if let Some(preruntime) = &preruntime {
+		inherent_digest.push(DigestItem::PreRuntime(POW_ENGINE_ID, preruntime.to_vec()));
+	}
+	if algorithm.requires_timestamp() {
+		let parent_timestamp = PowAux::<Algorithm::Difficulty>::read::<_, B>(client, &best_hash)?
+			.timestamp;
+		let timestamp = inherent_data.timestamp_inherent_data()
+			.map_err(Error::CreateInherents)?;
+		// Timestamps must strictly increase along the chain, even if the local clock lags.
+		let timestamp = match parent_timestamp {
+			Some(parent_timestamp) if timestamp <= parent_timestamp => parent_timestamp + 1,
+			_ => timestamp,
+		};
+		inherent_digest.push(DigestItem::PreRuntime(POW_TIMESTAMP_ENGINE_ID, timestamp.encode()));
+	}
+	let proposal = proposer.propose(
+		inherent_data,
+		inherent_digest,
+		build_time,
+		RecordProof::No,
+	).await.map_err(|e| Error::BlockProposingError(format!("{:?}", e)))?;
+
+	let metadata = MiningMetadata {
+		best_hash,
+		pre_hash: proposal.block.header().hash(),
+		difficulty: algorithm.difficulty(best_hash)?,
+	};
+
+	Ok(Some(MiningBuild { metadata, proposal }))
+}
+
+/// Start `threads` background threads mining the work of the worker with `algorithm`.
+///
+/// Note that because PoW mining is CPU-intensive, it is not possible to use an async future to
+/// define this. However, it's not recommended to use background threads in the rest of the
+/// codebase.
+///
+/// Each call of `PowAlgorithm::mine` runs `round` rounds, this parameter should be tweaked so
+/// that each call takes a fraction of a second, since stale work is only abandoned in between.
+/// All threads mine the same work, so `mine` should pick the nonces it tries at random.
+pub fn start_mining_threads<B, Algorithm, C>(
+	handle: MiningHandle<B, Algorithm, C>,
+	algorithm: Algorithm,
+	threads: usize,
+	round: u32,
+) where
+	B: BlockT,
+	Algorithm: PowAlgorithm<B> + Clone + Send + 'static,
+	Algorithm::Difficulty: Send + 'static,
+	C: ProvideRuntimeApi<B> + 'static,
+	MiningHandle<B, Algorithm, C>: Send + 'static,
+	sp_api::TransactionFor<C, B>: 'static,
+{
+	/// How long to wait for new work if there is none.
+	const IDLE_INTERVAL: Duration = Duration::from_millis(50);
+
+	for index in 0..threads {
+		let handle = handle.clone();
+		let algorithm = algorithm.clone();
+		let spawned = thread::Builder::new()
+			.name(format!("pow-miner-{}", index))
+			.spawn(move || loop {
+				let version = handle.version();
+				let metadata = match handle.metadata() {
+					Some(metadata) => metadata,
+					None => {
+						thread::sleep(IDLE_INTERVAL);
+						continue
+					}
+				};
+
+				while handle.version() == version {
+					let seal = algorithm.mine(
+						&BlockId::Hash(metadata.best_hash),
+						&metadata.pre_hash,
+						metadata.difficulty,
+						round,
+					);
+
+					match seal {
+						Ok(Some(seal)) => {
+							handle.submit(&metadata.pre_hash, seal);
+							break
+						}
+						Ok(None) => (),
+						Err(e) => {
+							error!(target: "pow", "Mining failed with {:?}", e);
+							thread::sleep(IDLE_INTERVAL);
+						}
+					}
+				}
+			});
+
+		if let Err(e) = spawned {
+			error!(target: "pow", "Spawning mining thread failed: {:?}", e);
+		}
+	}
+}
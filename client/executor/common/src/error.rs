@@ -81,6 +81,9 @@ pub enum Error {
 	/// Execution of a host function failed.
 	#[display(fmt="Host function {} execution failed with: {}", _0, _1)]
 	FunctionExecution(String, String),
+	/// The call consumed all of the fuel it was given.
+	#[display(fmt="Execution ran out of fuel")]
+	OutOfFuel,
 }
 
 impl std::error::Error for Error {
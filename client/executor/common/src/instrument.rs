@@ -0,0 +1,227 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Instrumentation of runtime code that makes execution limits deterministic.
+//!
+//! The native stack an executor uses for a call depends on the executor and on the machine, so
+//! instead of relying on it, the code is instrumented to account the height of the value stack
+//! itself and to execute `unreachable` once the limit is exceeded. If every executor applies the
+//! same instrumentation, a call exhausts the stack at the very same point everywhere. The limit
+//! changes the outcome of deep calls, so executors only apply it when it is configured.
+//!
+//! Besides that, the code can be instrumented to meter the executed instructions or to report
+//! entering and leaving functions to a profiler.
+
+use crate::error::WasmError;
+use parity_wasm::elements::{
+	deserialize_buffer, serialize, BlockType, External, FunctionType, ImportCountType, ImportEntry,
+	Instruction, Internal, Module as RawModule, Section, Type, ValueType,
+};
+
+/// The suggested maximal height of the value stack that a runtime call can use.
+///
+/// The height of a function frame is the number of its locals plus the maximal height of its
+/// operand stack.
+pub const DEFAULT_STACK_HEIGHT_LIMIT: u32 = 65536;
+
+/// The module the fuel metering function is imported from.
+pub const FUEL_IMPORT_MODULE: &str = "env";
+
+/// The name of the imported function that is charged with the fuel consumed by each block.
+pub const FUEL_IMPORT_NAME: &str = "gas";
+
+/// The module the profiler hooks are imported from.
+pub const PROFILER_IMPORT_MODULE: &str = "env";
+
+/// The name of the imported function that is called with the index of a function when it is
+/// entered.
+pub const PROFILER_ENTER_IMPORT_NAME: &str = "profiler_enter";
+
+/// The name of the imported function that is called with the index of a function when it returns.
+pub const PROFILER_EXIT_IMPORT_NAME: &str = "profiler_exit";
+
+/// Instrument `code` to trap once the value stack of a call exceeds `limit`.
+pub fn inject_stack_limiter(code: &[u8], limit: u32) -> Result<Vec<u8>, WasmError> {
+	let module = deserialize(code)?;
+	let module = pwasm_utils::stack_height::inject_limiter(module, limit)
+		.map_err(|e| WasmError::Other(format!("cannot inject stack limiter: {:?}", e)))?;
+	serialize(module).map_err(|_| WasmError::Other("cannot serialize instrumented module".into()))
+}
+
+/// Instrument `code` to call the imported [`FUEL_IMPORT_NAME`] function with the number of
+/// instructions of each block before executing it.
+pub fn inject_fuel_metering(code: &[u8]) -> Result<Vec<u8>, WasmError> {
+	let module = deserialize(code)?;
+	let module = pwasm_utils::inject_gas_counter(module, &Default::default())
+		.map_err(|_| WasmError::Other("cannot inject fuel metering".into()))?;
+	serialize(module).map_err(|_| WasmError::Other("cannot serialize instrumented module".into()))
+}
+
+/// Instrument `code` to call the imported [`PROFILER_ENTER_IMPORT_NAME`] and
+/// [`PROFILER_EXIT_IMPORT_NAME`] functions with the index of every function defined by the module
+/// when it is entered and when it returns.
+///
+/// Returns the instrumented code and the names of all functions of the original module by index,
+/// taken from the name section if there is one. The name section is dropped from the instrumented
+/// code, since the hooks shift the indices of all defined functions.
+pub fn inject_profiler(code: &[u8]) -> Result<(Vec<u8>, Vec<String>), WasmError> {
+	let mut module = deserialize(code)?
+		.parse_names()
+		.unwrap_or_else(|(_, module)| module);
+	let names = function_names(&module);
+
+	let imported = module.import_count(ImportCountType::Function) as u32;
+	let (enter, exit) = (imported, imported + 1);
+	let shift = |index: u32| if index >= imported { index + 2 } else { index };
+
+	let hook_type = hook_type_index(&mut module)?;
+	let block_types = defined_function_block_types(&module)?;
+
+	module.import_section_mut()
+		.ok_or_else(|| WasmError::Other("cannot profile a module without imports".into()))?
+		.entries_mut()
+		.extend(vec![
+			ImportEntry::new(
+				PROFILER_IMPORT_MODULE.into(),
+				PROFILER_ENTER_IMPORT_NAME.into(),
+				External::Function(hook_type),
+			),
+			ImportEntry::new(
+				PROFILER_IMPORT_MODULE.into(),
+				PROFILER_EXIT_IMPORT_NAME.into(),
+				External::Function(hook_type),
+			),
+		]);
+
+	module.sections_mut().retain(|section| match section {
+		Section::Name(_) => false,
+		_ => true,
+	});
+	for section in module.sections_mut() {
+		match section {
+			Section::Export(exports) => for export in exports.entries_mut() {
+				if let Internal::Function(index) = export.internal_mut() {
+					*index = shift(*index);
+				}
+			},
+			Section::Element(elements) => for segment in elements.entries_mut() {
+				for index in segment.members_mut() {
+					*index = shift(*index);
+				}
+			},
+			Section::Start(index) => *index = shift(*index),
+			Section::Code(code) => for (defined, body) in code.bodies_mut().iter_mut().enumerate() {
+				let id = Instruction::I32Const((imported + defined as u32) as i32);
+				let original = std::mem::replace(body.code_mut().elements_mut(), Vec::new());
+				let mut instrumented = Vec::with_capacity(original.len() + 7);
+
+				// The original body is wrapped into a block, so that returning from the function
+				// can be replaced by branching out of the block to the exit hook.
+				instrumented.extend(vec![
+					id.clone(),
+					Instruction::Call(enter),
+					Instruction::Block(block_types[defined]),
+				]);
+				let mut depth = 0;
+				// The last instruction is the `end` of the function body.
+				let body_len = original.len().saturating_sub(1);
+				for instruction in original.into_iter().take(body_len) {
+					instrumented.push(match instruction {
+						Instruction::Block(_) | Instruction::Loop(_) | Instruction::If(_) => {
+							depth += 1;
+							instruction
+						}
+						Instruction::End => {
+							depth -= 1;
+							instruction
+						}
+						Instruction::Return => Instruction::Br(depth),
+						Instruction::Call(index) => Instruction::Call(shift(index)),
+						instruction => instruction,
+					});
+				}
+				instrumented.extend(vec![Instruction::End, id, Instruction::Call(exit), Instruction::End]);
+
+				*body.code_mut().elements_mut() = instrumented;
+			},
+			_ => {}
+		}
+	}
+
+	let code = serialize(module)
+		.map_err(|_| WasmError::Other("cannot serialize instrumented module".into()))?;
+	Ok((code, names))
+}
+
+/// The names of all functions of `module` by index.
+fn function_names(module: &RawModule) -> Vec<String> {
+	let imported = module.import_section()
+		.map(|imports| imports.entries().iter()
+			.filter_map(|entry| match entry.external() {
+				External::Function(_) => Some(format!("{}::{}", entry.module(), entry.field())),
+				_ => None,
+			})
+			.collect::<Vec<_>>()
+		)
+		.unwrap_or_default();
+	let defined = module.function_section().map_or(0, |functions| functions.entries().len());
+	let names = module.names_section().and_then(|names| names.functions());
+
+	let first_defined = imported.len();
+	imported.into_iter()
+		.chain((first_defined..first_defined + defined).map(|index| {
+			names.and_then(|names| names.names().get(index as u32))
+				.cloned()
+				.unwrap_or_else(|| format!("wasm-function[{}]", index))
+		}))
+		.collect()
+}
+
+/// Returns the index of the `(i32) -> ()` type of the profiler hooks, adding it if necessary.
+fn hook_type_index(module: &mut RawModule) -> Result<u32, WasmError> {
+	let hook_type = Type::Function(FunctionType::new(vec![ValueType::I32], None));
+	let types = module.type_section_mut()
+		.ok_or_else(|| WasmError::Other("cannot profile a module without types".into()))?
+		.types_mut();
+
+	Ok(match types.iter().position(|ty| *ty == hook_type) {
+		Some(index) => index as u32,
+		None => {
+			types.push(hook_type);
+			types.len() as u32 - 1
+		}
+	})
+}
+
+/// The block types matching the results of the functions defined by `module`.
+fn defined_function_block_types(module: &RawModule) -> Result<Vec<BlockType>, WasmError> {
+	let types = module.type_section().map_or(&[][..], |types| types.types());
+	module.function_section()
+		.map_or(&[][..], |functions| functions.entries())
+		.iter()
+		.map(|function| match types.get(function.type_ref() as usize) {
+			Some(Type::Function(ty)) =>
+				Ok(ty.return_type().map_or(BlockType::NoResult, BlockType::Value)),
+			None => Err(WasmError::Other("function refers to an unknown type".into())),
+		})
+		.collect()
+}
+
+fn deserialize(code: &[u8]) -> Result<RawModule, WasmError> {
+	deserialize_buffer(code).map_err(|_| WasmError::CantDeserializeWasm)
+}
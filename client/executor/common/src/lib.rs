@@ -19,6 +19,7 @@
 #![warn(missing_docs)]
 
 pub mod error;
+pub mod instrument;
 pub mod sandbox;
 pub mod util;
 pub mod wasm_runtime;
@@ -17,6 +17,7 @@
 //! Definitions for a wasm runtime.
 
 use crate::error::Error;
+use sp_allocator::AllocationStats;
 use sp_wasm_interface::Value;
 
 /// A trait that defines an abstract WASM runtime module.
@@ -35,7 +36,28 @@ pub trait WasmInstance: Send {
 	/// Returns the encoded result on success.
 	fn call(&self, method: &str, data: &[u8]) -> Result<Vec<u8>, Error>;
 
+	/// Call a method like [`call`](WasmInstance::call) and profile the call.
+	///
+	/// Returns the result of the call along with the time spent by each stack of Wasm functions in
+	/// the folded stack format, if this instance supports profiling.
+	fn call_profiled(&self, method: &str, data: &[u8]) -> (Result<Vec<u8>, Error>, Option<String>) {
+		(self.call(method, data), None)
+	}
+
 	/// Get the value from a global with the given `name`.
 	/// This method is only suitable for getting immutable globals.
 	fn get_global_const(&self, name: &str) -> Result<Option<Value>, Error>;
+
+	/// Let the following calls allocate at most `heap_limit` bytes at the same time.
+	///
+	/// Calls are not limited until this is called. Allocations beyond the limit fail.
+	fn set_heap_limit(&self, heap_limit: Option<u32>);
+
+	/// Statistics of the heap allocations of the last call.
+	///
+	/// `None` if there was no call yet or if the last call didn't resolve an export.
+	fn allocation_stats(&self) -> Option<AllocationStats>;
+
+	/// The size in bytes of the linear memory of this instance.
+	fn memory_size(&self) -> u64;
 }
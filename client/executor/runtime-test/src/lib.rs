@@ -289,6 +289,10 @@ sp_core::wasm_export_functions! {
 		data.to_vec()
 	}
 
+	fn test_recursion(depth: u32) -> u32 {
+		recurse(depth)
+	}
+
 	// Check that the heap at `heap_base + offset` don't contains the test message.
 	// After the check succeeds the test message is written into the heap.
 	//
@@ -304,6 +308,21 @@ sp_core::wasm_export_functions! {
 	}
  }
 
+/// Recurse `depth` times, keeping a few locals alive in every frame.
+#[cfg(not(feature = "std"))]
+#[inline(never)]
+fn recurse(depth: u32) -> u32 {
+	let locals = [depth; 4];
+	if depth == 0 {
+		return 0
+	}
+
+	let result = recurse(depth - 1);
+	// The volatile read keeps the compiler from turning the recursion into a loop.
+	let local = unsafe { sp_std::ptr::read_volatile(&locals[(result % 4) as usize]) };
+	result.wrapping_add(local)
+}
+
 #[cfg(not(feature = "std"))]
 fn execute_sandboxed(
 	code: &[u8],
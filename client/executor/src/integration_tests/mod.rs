@@ -41,13 +41,23 @@ fn call_in_wasm<E: Externalities>(
 	call_data: &[u8],
 	execution_method: WasmExecutionMethod,
 	ext: &mut E,
+) -> Result<Vec<u8>, String> {
+	call_in_wasm_with_stack_height_limit(function, call_data, execution_method, None, ext)
+}
+
+fn call_in_wasm_with_stack_height_limit<E: Externalities>(
+	function: &str,
+	call_data: &[u8],
+	execution_method: WasmExecutionMethod,
+	stack_height_limit: Option<u32>,
+	ext: &mut E,
 ) -> Result<Vec<u8>, String> {
 	let executor = crate::WasmExecutor::new(
 		execution_method,
 		Some(1024),
 		HostFunctions::host_functions(),
 		8,
-	);
+	).with_stack_height_limit(stack_height_limit);
 	executor.call_in_wasm(
 		&WASM_BINARY[..],
 		None,
@@ -553,6 +563,11 @@ fn returns_mutable_static(wasm_method: WasmExecutionMethod) {
 		&WASM_BINARY[..],
 		HostFunctions::host_functions(),
 		true,
+		None,
+		None,
+		None,
+		None,
+		false,
 	).expect("Creates runtime");
 
 	let instance = runtime.new_instance().unwrap();
@@ -587,6 +602,11 @@ fn restoration_of_globals(wasm_method: WasmExecutionMethod) {
 		&WASM_BINARY[..],
 		HostFunctions::host_functions(),
 		true,
+		None,
+		None,
+		None,
+		None,
+		false,
 	).expect("Creates runtime");
 	let instance = runtime.new_instance().unwrap();
 
@@ -599,6 +619,255 @@ fn restoration_of_globals(wasm_method: WasmExecutionMethod) {
 	assert!(res.is_ok());
 }
 
+#[cfg(feature = "wasmtime")]
+#[test]
+fn compiled_runtime_goes_through_artifact_cache() {
+	let cache = tempfile::tempdir().unwrap();
+	crate::precompile_wasm_runtime(
+		WasmExecutionMethod::Compiled,
+		&WASM_BINARY[..],
+		cache.path(),
+		false,
+		None,
+	).expect("Precompiles runtime");
+	assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 1);
+
+	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
+		WasmExecutionMethod::Compiled,
+		1024,
+		&WASM_BINARY[..],
+		HostFunctions::host_functions(),
+		true,
+		Some(cache.path()),
+		None,
+		None,
+		None,
+		false,
+	).expect("Creates runtime");
+
+	let instance = runtime.new_instance().unwrap();
+	let res = instance.call("returns_mutable_static", &[0]).unwrap();
+	assert_eq!(33, u64::decode(&mut &res[..]).unwrap());
+	assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 1);
+}
+
+#[cfg(feature = "wasmtime")]
+#[test]
+fn precompiled_artifacts_follow_the_instrumentation() {
+	let cache = tempfile::tempdir().unwrap();
+	crate::precompile_wasm_runtime(
+		WasmExecutionMethod::Compiled,
+		&WASM_BINARY[..],
+		cache.path(),
+		true,
+		Some(crate::DEFAULT_STACK_HEIGHT_LIMIT),
+	).expect("Precompiles runtime");
+	assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 1);
+
+	let create = |fuel_limit| crate::wasm_runtime::create_wasm_runtime_with_code(
+		WasmExecutionMethod::Compiled,
+		1024,
+		&WASM_BINARY[..],
+		HostFunctions::host_functions(),
+		true,
+		Some(cache.path()),
+		fuel_limit,
+		None,
+		Some(crate::DEFAULT_STACK_HEIGHT_LIMIT),
+		false,
+	).expect("Creates runtime");
+
+	// A runtime with the same instrumentation uses the precompiled artifacts.
+	create(Some(1_000_000));
+	assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 1);
+
+	// A runtime without fuel metering is compiled from different code.
+	create(None);
+	assert_eq!(std::fs::read_dir(cache.path()).unwrap().count(), 2);
+}
+
+#[cfg(feature = "wasmtime")]
+#[test]
+fn compiled_runtime_calls_can_be_profiled() {
+	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
+		WasmExecutionMethod::Compiled,
+		1024,
+		&WASM_BINARY[..],
+		HostFunctions::host_functions(),
+		true,
+		None,
+		None,
+		None,
+		None,
+		true,
+	).expect("Creates runtime");
+	let instance = runtime.new_instance().unwrap();
+
+	let (res, folded) = instance.call_profiled("test_blake2_256", &b"Hello world!".to_vec().encode());
+	assert_eq!(
+		res.unwrap(),
+		blake2_256(&b"Hello world!"[..]).to_vec().encode(),
+	);
+
+	let folded = folded.expect("Profiled calls return folded stacks");
+	assert!(folded.lines().any(|line| line.starts_with("test_blake2_256")));
+	assert!(folded.lines().all(|line| {
+		line.rsplit(' ').next().map_or(false, |nanos| nanos.parse::<u64>().is_ok())
+	}));
+
+	// Calls of missing exports are not profiled.
+	let (res, folded) = instance.call_profiled("missing_export", &[]);
+	assert!(res.is_err());
+	assert!(folded.is_none());
+
+	// Runtimes that are not instrumented can't be profiled.
+	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
+		WasmExecutionMethod::Compiled,
+		1024,
+		&WASM_BINARY[..],
+		HostFunctions::host_functions(),
+		true,
+		None,
+		None,
+		None,
+		None,
+		false,
+	).expect("Creates runtime");
+	let (res, folded) = runtime.new_instance().unwrap().call_profiled("test_empty_return", &[]);
+	res.unwrap();
+	assert!(folded.is_none());
+}
+
+#[test]
+fn interpreted_runtime_can_not_be_precompiled() {
+	let cache = tempfile::tempdir().unwrap();
+	assert!(
+		crate::precompile_wasm_runtime(
+			WasmExecutionMethod::Interpreted,
+			&WASM_BINARY[..],
+			cache.path(),
+			false,
+			None,
+		).is_err()
+	);
+}
+
+/// Returns the smallest recursion depth at which `test_recursion` exhausts the stack.
+fn stack_exhaustion_depth(wasm_method: WasmExecutionMethod) -> u32 {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+	let mut traps = |depth: u32| {
+		call_in_wasm_with_stack_height_limit(
+			"test_recursion",
+			&depth.encode(),
+			wasm_method,
+			Some(crate::DEFAULT_STACK_HEIGHT_LIMIT),
+			&mut ext,
+		).is_err()
+	};
+
+	let (mut ok, mut trapping) = (0u32, 1_000_000u32);
+	assert!(!traps(ok));
+	assert!(traps(trapping));
+	while trapping - ok > 1 {
+		let depth = ok + (trapping - ok) / 2;
+		if traps(depth) {
+			trapping = depth;
+		} else {
+			ok = depth;
+		}
+	}
+	trapping
+}
+
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn stack_exhaustion_traps_as_unreachable(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+
+	let err = call_in_wasm_with_stack_height_limit(
+		"test_recursion",
+		&1_000_000u32.encode(),
+		wasm_method,
+		Some(crate::DEFAULT_STACK_HEIGHT_LIMIT),
+		&mut ext,
+	).unwrap_err();
+	assert_eq!(err, "Trap: Trap { kind: Unreachable }");
+}
+
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn stack_is_not_limited_by_default(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+
+	let err = call_in_wasm("test_recursion", &1_000_000u32.encode(), wasm_method, &mut ext)
+		.unwrap_err();
+	assert_eq!(err, "Trap: Trap { kind: StackOverflow }");
+}
+
+#[cfg(feature = "wasmtime")]
+#[test]
+fn stack_is_exhausted_at_the_same_depth_in_all_executors() {
+	assert_eq!(
+		stack_exhaustion_depth(WasmExecutionMethod::Interpreted),
+		stack_exhaustion_depth(WasmExecutionMethod::Compiled),
+	);
+}
+
+#[cfg(feature = "wasmtime")]
+#[test]
+fn traps_are_reported_the_same_way_in_all_executors() {
+	let cases: &[(&str, Vec<u8>)] = &[
+		("test_panic", vec![]),
+		("test_conditional_panic", vec![2].encode()),
+		("test_recursion", 1_000_000u32.encode()),
+	];
+
+	for (function, input) in cases {
+		let mut ext = TestExternalities::default();
+		let mut ext = ext.ext();
+		let call = |wasm_method, ext: &mut _| call_in_wasm_with_stack_height_limit(
+			function,
+			input,
+			wasm_method,
+			Some(crate::DEFAULT_STACK_HEIGHT_LIMIT),
+			ext,
+		);
+		let interpreted = call(WasmExecutionMethod::Interpreted, &mut ext);
+		let compiled = call(WasmExecutionMethod::Compiled, &mut ext);
+		assert_eq!(interpreted, compiled, "{} is not reported the same way", function);
+	}
+}
+
+#[cfg(feature = "wasmtime")]
+#[test]
+fn compiled_runtime_runs_out_of_fuel() {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+	let executor = |fuel_limit| crate::WasmExecutor::new(
+		WasmExecutionMethod::Compiled,
+		Some(1024),
+		HostFunctions::host_functions(),
+		8,
+	).with_fuel_limit(fuel_limit);
+	let call = |executor: crate::WasmExecutor, ext: &mut dyn Externalities| executor.call_in_wasm(
+		&WASM_BINARY[..],
+		None,
+		"test_recursion",
+		&100u32.encode(),
+		ext,
+		sp_core::traits::MissingHostFunctions::Allow,
+	);
+
+	assert_eq!(call(executor(Some(100)), &mut ext).unwrap_err(), "Execution ran out of fuel");
+	assert_eq!(
+		call(executor(Some(1_000_000)), &mut ext).unwrap(),
+		call(executor(None), &mut ext).unwrap(),
+	);
+}
+
 #[test_case(WasmExecutionMethod::Interpreted)]
 fn heap_is_reset_between_calls(wasm_method: WasmExecutionMethod) {
 	let runtime = crate::wasm_runtime::create_wasm_runtime_with_code(
@@ -607,6 +876,11 @@ fn heap_is_reset_between_calls(wasm_method: WasmExecutionMethod) {
 		&WASM_BINARY[..],
 		HostFunctions::host_functions(),
 		true,
+		None,
+		None,
+		None,
+		None,
+		false,
 	).expect("Creates runtime");
 	let instance = runtime.new_instance().unwrap();
 
@@ -623,6 +897,96 @@ fn heap_is_reset_between_calls(wasm_method: WasmExecutionMethod) {
 	instance.call("check_and_set_in_heap", &params).unwrap();
 }
 
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn heap_limit_is_enforced_per_call(wasm_method: WasmExecutionMethod) {
+	let instance = crate::wasm_runtime::create_wasm_runtime_with_code(
+		wasm_method,
+		1024,
+		&WASM_BINARY[..],
+		HostFunctions::host_functions(),
+		true,
+		None,
+		None,
+		None,
+		false,
+	).expect("Creates runtime").new_instance().unwrap();
+
+	assert!(instance.allocation_stats().is_none());
+	instance.call("test_exhaust_heap", &[]).unwrap();
+	assert!(instance.allocation_stats().unwrap().peak_heap_size > 16 * 1024 * 1024);
+
+	instance.set_heap_limit(Some(1024 * 1024));
+	instance.call("test_exhaust_heap", &[]).unwrap_err();
+	instance.call("test_empty_return", &[]).unwrap();
+	assert!(instance.allocation_stats().unwrap().allocations > 0);
+
+	// Calls of missing exports don't report statistics, not even the ones of the previous call.
+	instance.call("missing_export", &[]).unwrap_err();
+	assert!(instance.allocation_stats().is_none());
+
+	instance.set_heap_limit(None);
+	instance.call("test_exhaust_heap", &[]).unwrap();
+}
+
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn heap_limit_extension_only_limits_calls_carrying_it(wasm_method: WasmExecutionMethod) {
+	let executor = crate::WasmExecutor::new(
+		wasm_method,
+		Some(1024),
+		HostFunctions::host_functions(),
+		8,
+	);
+	let code_hash = blake2_256(WASM_BINARY).to_vec();
+	let call = |ext: &mut TestExternalities| executor.call_in_wasm(
+		&WASM_BINARY[..],
+		Some(code_hash.clone()),
+		"test_exhaust_heap",
+		&[],
+		&mut ext.ext(),
+		sp_core::traits::MissingHostFunctions::Allow,
+	);
+
+	let mut limited = TestExternalities::default();
+	limited.register_extension(sp_core::traits::RuntimeHeapLimitExt(1024 * 1024));
+	call(&mut limited).unwrap_err();
+
+	// The limit doesn't stick to the cached instance.
+	call(&mut TestExternalities::default()).unwrap();
+}
+
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn cached_instances_respect_memory_limit(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+	let code_hash = blake2_256(WASM_BINARY).to_vec();
+	let mut call = |executor: &crate::WasmExecutor| executor.call_in_wasm(
+		&WASM_BINARY[..],
+		Some(code_hash.clone()),
+		"test_empty_return",
+		&[],
+		&mut ext,
+		sp_core::traits::MissingHostFunctions::Allow,
+	).unwrap();
+	let executor = |instances_memory_limit| crate::WasmExecutor::new(
+		wasm_method,
+		Some(8),
+		HostFunctions::host_functions(),
+		8,
+	).with_instances_memory_limit(instances_memory_limit);
+
+	let unlimited = executor(None);
+	call(&unlimited);
+	let instance_memory = unlimited.cached_instances_memory();
+	assert!(instance_memory > 0);
+
+	let limited = executor(Some(instance_memory - 1));
+	call(&limited);
+	assert_eq!(limited.cached_instances_memory(), 0);
+}
+
 #[test_case(WasmExecutionMethod::Interpreted)]
 #[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
 fn parallel_execution(wasm_method: WasmExecutionMethod) {
@@ -33,6 +33,8 @@
 
 #[macro_use]
 mod native_executor;
+mod metrics;
+mod profiling;
 mod wasm_runtime;
 #[cfg(test)]
 mod integration_tests;
@@ -45,9 +47,12 @@ pub use codec::Codec;
 pub use sp_core::traits::{Externalities, CallInWasm};
 #[doc(hidden)]
 pub use sp_wasm_interface;
-pub use wasm_runtime::WasmExecutionMethod;
+pub use wasm_runtime::{WasmExecutionMethod, precompile_wasm_runtime};
+pub use profiling::RuntimeProfiling;
+pub use metrics::Metrics;
 
 pub use sc_executor_common::{error, sandbox};
+pub use sc_executor_common::instrument::DEFAULT_STACK_HEIGHT_LIMIT;
 
 /// Provides runtime information.
 pub trait RuntimeInfo {
@@ -0,0 +1,129 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Executor Prometheus metrics.
+
+use prometheus_endpoint::{
+	register, exponential_buckets, Gauge, HistogramOpts, HistogramVec, Opts, PrometheusError,
+	Registry, U64,
+};
+use sp_allocator::AllocationStats;
+
+/// The runtime API methods whose calls are reported with their own `method` label.
+///
+/// Calls of all other methods are reported as [`OTHER_METHOD`], so that calls of arbitrary
+/// methods, e.g. through RPC, can't create an unbounded number of time series.
+const KNOWN_METHODS: &[&str] = &[
+	"Core_version",
+	"Core_execute_block",
+	"Core_initialize_block",
+	"Metadata_metadata",
+	"BlockBuilder_apply_extrinsic",
+	"BlockBuilder_finalize_block",
+	"BlockBuilder_inherent_extrinsics",
+	"BlockBuilder_check_inherents",
+	"BlockBuilder_random_seed",
+	"TaggedTransactionQueue_validate_transaction",
+	"OffchainWorkerApi_offchain_worker",
+	"SessionKeys_generate_session_keys",
+	"SessionKeys_decode_session_keys",
+	"GrandpaApi_grandpa_authorities",
+	"BabeApi_configuration",
+	"BabeApi_current_epoch_start",
+	"AuraApi_slot_duration",
+	"AuraApi_authorities",
+	"AccountNonceApi_account_nonce",
+	"TransactionPaymentApi_query_info",
+];
+
+/// The `method` label of calls of methods that are not in [`KNOWN_METHODS`].
+const OTHER_METHOD: &str = "other";
+
+/// The `method` label a call of the runtime `method` is reported with.
+fn method_label(method: &str) -> &str {
+	KNOWN_METHODS.iter().find(|known| **known == method).map_or(OTHER_METHOD, |known| *known)
+}
+
+/// Executor Prometheus metrics.
+pub struct Metrics {
+	/// Peak heap size of runtime calls by method.
+	call_peak_heap_size: HistogramVec,
+	/// Number of allocations of runtime calls by method.
+	call_allocations: HistogramVec,
+	/// Linear memory of the cached runtime instances.
+	cached_instances_memory: Gauge<U64>,
+}
+
+impl Metrics {
+	/// Register the executor metrics at the given `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			call_peak_heap_size: register(HistogramVec::new(
+				HistogramOpts {
+					common_opts: Opts::new(
+						"sub_executor_call_peak_heap_size_bytes",
+						"Maximal number of bytes allocated at the same time by a runtime call",
+					),
+					buckets: exponential_buckets(1024.0, 4.0, 12)
+						.expect("parameters are always valid values; qed"),
+				},
+				&["method"],
+			)?, registry)?,
+			call_allocations: register(HistogramVec::new(
+				HistogramOpts {
+					common_opts: Opts::new(
+						"sub_executor_call_allocations",
+						"Number of heap allocations made by a runtime call",
+					),
+					buckets: exponential_buckets(1.0, 4.0, 12)
+						.expect("parameters are always valid values; qed"),
+				},
+				&["method"],
+			)?, registry)?,
+			cached_instances_memory: register(Gauge::new(
+				"sub_executor_cached_instances_memory_bytes",
+				"Linear memory used by the cached runtime instances",
+			)?, registry)?,
+		})
+	}
+
+	/// Report the allocation statistics of a call of the runtime `method`.
+	pub fn report_call(&self, method: &str, stats: AllocationStats) {
+		let method = method_label(method);
+		self.call_peak_heap_size.with_label_values(&[method]).observe(stats.peak_heap_size as f64);
+		self.call_allocations.with_label_values(&[method]).observe(stats.allocations as f64);
+	}
+
+	/// Report the memory used by the cached runtime instances.
+	pub fn report_cached_instances_memory(&self, bytes: u64) {
+		self.cached_instances_memory.set(bytes);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unknown_methods_share_one_label() {
+		assert_eq!(method_label("Core_execute_block"), "Core_execute_block");
+		assert_eq!(method_label("BlockBuilder_apply_extrinsic"), "BlockBuilder_apply_extrinsic");
+		assert_eq!(method_label("Core_execute_block2"), OTHER_METHOD);
+		assert_eq!(method_label("random_method_from_rpc"), OTHER_METHOD);
+		assert_eq!(method_label(""), OTHER_METHOD);
+	}
+}
@@ -18,15 +18,21 @@
 
 use crate::{
 	RuntimeInfo, error::{Error, Result},
+	metrics::Metrics,
+	profiling::RuntimeProfiling,
 	wasm_runtime::{RuntimeCache, WasmExecutionMethod},
 };
 use sp_version::{NativeVersion, RuntimeVersion};
 use codec::{Decode, Encode};
 use sp_core::{
-	NativeOrEncoded, traits::{CodeExecutor, Externalities, RuntimeCode, MissingHostFunctions},
+	NativeOrEncoded,
+	traits::{
+		CodeExecutor, Externalities, ExternalitiesExt, RuntimeCode, MissingHostFunctions,
+		RuntimeHeapLimitExt,
+	},
 };
-use log::trace;
-use std::{result, panic::{UnwindSafe, AssertUnwindSafe}, sync::Arc};
+use log::{trace, warn};
+use std::{result, panic::{UnwindSafe, AssertUnwindSafe}, path::PathBuf, sync::Arc};
 use sp_wasm_interface::{HostFunctions, Function};
 use sc_executor_common::wasm_runtime::WasmInstance;
 
@@ -89,6 +95,18 @@ pub struct WasmExecutor {
 	cache: Arc<RuntimeCache>,
 	/// The size of the instances cache.
 	max_runtime_instances: usize,
+	/// Directory of the on-disk cache of compiled runtimes.
+	artifact_cache: Option<PathBuf>,
+	/// Maximal number of instructions a call may execute.
+	fuel_limit: Option<u64>,
+	/// Maximal number of bytes a call may allocate at the same time.
+	heap_limit: Option<u32>,
+	/// Deterministic limit of the value stack the code is instrumented with.
+	stack_height_limit: Option<u32>,
+	/// Prometheus metrics, if any.
+	metrics: Option<Arc<Metrics>>,
+	/// The calls to profile, if any.
+	profiling: Option<RuntimeProfiling>,
 }
 
 impl WasmExecutor {
@@ -110,11 +128,122 @@ impl WasmExecutor {
 			method,
 			default_heap_pages: default_heap_pages.unwrap_or(DEFAULT_HEAP_PAGES),
 			host_functions: Arc::new(host_functions),
-			cache: Arc::new(RuntimeCache::new(max_runtime_instances)),
+			cache: Arc::new(RuntimeCache::new(max_runtime_instances, None)),
 			max_runtime_instances,
+			artifact_cache: None,
+			fuel_limit: None,
+			heap_limit: None,
+			stack_height_limit: None,
+			metrics: None,
+			profiling: None,
 		}
 	}
 
+	/// Load compiled runtimes from and store them in the given directory.
+	///
+	/// Only has an effect for [`WasmExecutionMethod::Compiled`].
+	pub fn with_artifact_cache(mut self, artifact_cache: Option<PathBuf>) -> Self {
+		self.artifact_cache = artifact_cache;
+		self
+	}
+
+	/// Let every call execute at most `fuel_limit` instructions.
+	///
+	/// Calls that exceed the limit fail with [`Error::OutOfFuel`]. Only has an effect for
+	/// [`WasmExecutionMethod::Compiled`].
+	pub fn with_fuel_limit(mut self, fuel_limit: Option<u64>) -> Self {
+		self.fuel_limit = fuel_limit;
+		self
+	}
+
+	/// Let every call allocate at most `heap_limit` bytes at the same time.
+	///
+	/// Allocations beyond the limit fail, which makes the call fail. This applies to all calls,
+	/// including block import. Calls whose externalities carry a [`RuntimeHeapLimitExt`] are
+	/// limited by it as well.
+	pub fn with_heap_limit(mut self, heap_limit: Option<u32>) -> Self {
+		self.heap_limit = heap_limit;
+		self
+	}
+
+	/// Instrument the code to trap once the value stack of a call exceeds `stack_height_limit`.
+	///
+	/// Without the instrumentation, the depth at which calls exhaust the stack depends on the
+	/// execution method and on the machine. Enabling it changes which calls succeed, so all nodes
+	/// that have to agree on the results of calls, e.g. block import, have to use the same limit.
+	pub fn with_stack_height_limit(mut self, stack_height_limit: Option<u32>) -> Self {
+		self.stack_height_limit = stack_height_limit;
+		self
+	}
+
+	/// Keep at most `instances_memory_limit` bytes of linear memory in cached runtime instances.
+	///
+	/// Instances that don't fit into the limit are dropped after their call.
+	pub fn with_instances_memory_limit(mut self, instances_memory_limit: Option<u64>) -> Self {
+		self.cache = Arc::new(RuntimeCache::new(self.max_runtime_instances, instances_memory_limit));
+		self
+	}
+
+	/// Report allocation statistics of runtime calls and the memory of cached runtime instances
+	/// to the given `metrics`.
+	///
+	/// The metrics are registered once and can be shared by several executors.
+	pub fn with_metrics(mut self, metrics: Option<Arc<Metrics>>) -> Self {
+		self.metrics = metrics;
+		self
+	}
+
+	/// Profile the calls selected by `profiling`.
+	///
+	/// Only has an effect for [`WasmExecutionMethod::Compiled`], whose code is instrumented to
+	/// report entering and leaving functions. This slows down all calls.
+	pub fn with_profiling(mut self, profiling: Option<RuntimeProfiling>) -> Self {
+		if profiling.is_some() && self.method == WasmExecutionMethod::Interpreted {
+			warn!(target: "executor", "Profiling is only supported for compiled runtimes");
+		}
+		self.profiling = profiling;
+		self
+	}
+
+	/// The number of bytes of linear memory used by the cached runtime instances.
+	pub fn cached_instances_memory(&self) -> u64 {
+		self.cache.cached_instances_memory()
+	}
+
+	/// Call `method` of the given `instance` and report the allocation statistics of the call.
+	///
+	/// The call is profiled if profiling of `method` is enabled.
+	fn call_instance(
+		&self,
+		instance: AssertUnwindSafe<&dyn WasmInstance>,
+		ext: &mut dyn Externalities,
+		method: &str,
+		data: &[u8],
+	) -> Result<Result<Vec<u8>>> {
+		let called: &dyn WasmInstance = *instance;
+		// Instances are reused, so the limit has to be set for every call.
+		let call_limit = (&mut *ext).extension::<RuntimeHeapLimitExt>().map(|limit| limit.0);
+		called.set_heap_limit(match (call_limit, self.heap_limit) {
+			(Some(call_limit), Some(limit)) => Some(call_limit.min(limit)),
+			(call_limit, limit) => call_limit.or(limit),
+		});
+		let result = match self.profiling.as_ref().filter(|profiling| profiling.profiles(method)) {
+			Some(profiling) => with_externalities_safe(ext, move || instance.call_profiled(method, data))
+				.map(|(result, folded)| {
+					if let Some(folded) = folded {
+						profiling.write(method, &folded);
+					}
+					result
+				}),
+			None => with_externalities_safe(ext, move || instance.call(method, data)),
+		};
+
+		if let (Some(metrics), Some(stats)) = (&self.metrics, called.allocation_stats()) {
+			metrics.report_call(method, stats);
+		}
+		result
+	}
+
 	/// Execute the given closure `f` with the latest runtime (based on `runtime_code`).
 	///
 	/// The closure `f` is expected to return `Err(_)` when there happened a `panic!` in native code
@@ -141,19 +270,29 @@ impl WasmExecutor {
 			AssertUnwindSafe<&mut dyn Externalities>,
 		) -> Result<Result<R>>,
 	{
-		match self.cache.with_instance(
+		let result = self.cache.with_instance(
 			runtime_code,
 			ext,
 			self.method,
 			self.default_heap_pages,
 			&*self.host_functions,
 			allow_missing_host_functions,
+			self.artifact_cache.as_ref().map(|p| p.as_path()),
+			self.fuel_limit,
+			self.stack_height_limit,
+			self.profiling.is_some(),
 			|instance, version, ext| {
 				let instance = AssertUnwindSafe(instance);
 				let ext = AssertUnwindSafe(ext);
 				f(instance, version, ext)
 			}
-		)? {
+		);
+
+		if let Some(metrics) = &self.metrics {
+			metrics.report_cached_instances_memory(self.cache.cached_instances_memory());
+		}
+
+		match result? {
 			Ok(r) => r,
 			Err(e) => Err(e),
 		}
@@ -180,10 +319,7 @@ impl sp_core::traits::CallInWasm for WasmExecutor {
 			};
 
 			self.with_instance(&code, ext, allow_missing_host_functions, |instance, _, mut ext| {
-				with_externalities_safe(
-					&mut **ext,
-					move || instance.call(method, call_data),
-				)
+				self.call_instance(instance, &mut **ext, method, call_data)
 			}).map_err(|e| e.to_string())
 		} else {
 			let module = crate::wasm_runtime::create_wasm_runtime_with_code(
@@ -192,21 +328,19 @@ impl sp_core::traits::CallInWasm for WasmExecutor {
 				&wasm_code,
 				self.host_functions.to_vec(),
 				allow_missing_host_functions,
+				self.artifact_cache.as_ref().map(|p| p.as_path()),
+				self.fuel_limit,
+				self.stack_height_limit,
+				self.profiling.is_some(),
 			)
 				.map_err(|e| format!("Failed to create module: {:?}", e))?;
 
 			let instance = module.new_instance()
 				.map_err(|e| format!("Failed to create instance: {:?}", e))?;
 
-			let instance = AssertUnwindSafe(instance);
-			let mut ext = AssertUnwindSafe(ext);
-
-			with_externalities_safe(
-				&mut **ext,
-				move || instance.call(method, call_data),
-			)
-			.and_then(|r| r)
-			.map_err(|e| e.to_string())
+			self.call_instance(AssertUnwindSafe(&*instance), ext, method, call_data)
+				.and_then(|r| r)
+				.map_err(|e| e.to_string())
 		}
 	}
 }
@@ -253,6 +387,63 @@ impl<D: NativeExecutionDispatch> NativeExecutor<D> {
 			wasm: wasm_executor,
 		}
 	}
+
+	/// Load compiled runtimes from and store them in the given directory.
+	///
+	/// See [`WasmExecutor::with_artifact_cache`].
+	pub fn with_artifact_cache(mut self, artifact_cache: Option<PathBuf>) -> Self {
+		self.wasm = self.wasm.with_artifact_cache(artifact_cache);
+		self
+	}
+
+	/// Let every call of the Wasm runtime execute at most `fuel_limit` instructions.
+	///
+	/// See [`WasmExecutor::with_fuel_limit`].
+	pub fn with_fuel_limit(mut self, fuel_limit: Option<u64>) -> Self {
+		self.wasm = self.wasm.with_fuel_limit(fuel_limit);
+		self
+	}
+
+	/// Let every call of the Wasm runtime allocate at most `heap_limit` bytes at the same time.
+	///
+	/// See [`WasmExecutor::with_heap_limit`].
+	pub fn with_heap_limit(mut self, heap_limit: Option<u32>) -> Self {
+		self.wasm = self.wasm.with_heap_limit(heap_limit);
+		self
+	}
+
+	/// Instrument the Wasm runtime to trap once the value stack of a call exceeds
+	/// `stack_height_limit`.
+	///
+	/// See [`WasmExecutor::with_stack_height_limit`].
+	pub fn with_stack_height_limit(mut self, stack_height_limit: Option<u32>) -> Self {
+		self.wasm = self.wasm.with_stack_height_limit(stack_height_limit);
+		self
+	}
+
+	/// Keep at most `instances_memory_limit` bytes of linear memory in cached runtime instances.
+	///
+	/// See [`WasmExecutor::with_instances_memory_limit`].
+	pub fn with_instances_memory_limit(mut self, instances_memory_limit: Option<u64>) -> Self {
+		self.wasm = self.wasm.with_instances_memory_limit(instances_memory_limit);
+		self
+	}
+
+	/// Profile the calls of the Wasm runtime selected by `profiling`.
+	///
+	/// See [`WasmExecutor::with_profiling`].
+	pub fn with_profiling(mut self, profiling: Option<RuntimeProfiling>) -> Self {
+		self.wasm = self.wasm.with_profiling(profiling);
+		self
+	}
+
+	/// Report metrics of the Wasm runtime calls to the given `metrics`.
+	///
+	/// See [`WasmExecutor::with_metrics`].
+	pub fn with_metrics(mut self, metrics: Option<Arc<Metrics>>) -> Self {
+		self.wasm = self.wasm.with_metrics(metrics);
+		self
+	}
 }
 
 impl<D: NativeExecutionDispatch> RuntimeInfo for NativeExecutor<D> {
@@ -312,16 +503,12 @@ impl<D: NativeExecutionDispatch + 'static> CodeExecutor for NativeExecutor<D> {
 							onchain_version,
 						);
 
-						with_externalities_safe(
-							&mut **ext,
-							move || instance.call(method, data).map(NativeOrEncoded::Encoded)
-						)
+						self.wasm.call_instance(instance, &mut **ext, method, data)
+							.map(|r| r.map(NativeOrEncoded::Encoded))
 					}
 					(false, _, _) => {
-						with_externalities_safe(
-							&mut **ext,
-							move || instance.call(method, data).map(NativeOrEncoded::Encoded)
-						)
+						self.wasm.call_instance(instance, &mut **ext, method, data)
+							.map(|r| r.map(NativeOrEncoded::Encoded))
 					},
 					(true, true, Some(call)) => {
 						trace!(
@@ -0,0 +1,119 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Profiling of runtime calls.
+//!
+//! Profiled calls are written to files in the folded stack format, one line per stack of Wasm
+//! functions followed by the nanoseconds spent in it, which can be rendered by flamegraph tools.
+
+use std::{
+	fs,
+	path::PathBuf,
+	sync::atomic::{AtomicUsize, Ordering},
+	time::{SystemTime, UNIX_EPOCH},
+};
+use log::{info, warn};
+
+/// Which runtime calls to profile and where to write the profiles to.
+///
+/// Only calls of compiled runtimes can be profiled.
+#[derive(Debug, Clone)]
+pub struct RuntimeProfiling {
+	/// The runtime methods whose calls are profiled, e.g. `Core_execute_block`.
+	///
+	/// Calls of all methods are profiled if it is empty.
+	pub methods: Vec<String>,
+	/// The directory the profiles are written to.
+	pub output_dir: PathBuf,
+}
+
+impl RuntimeProfiling {
+	/// Returns `true` if calls of `method` are profiled.
+	pub fn profiles(&self, method: &str) -> bool {
+		self.methods.is_empty() || self.methods.iter().any(|profiled| profiled == method)
+	}
+
+	/// Write the `folded` stacks of a call of `method` to a new file in the output directory.
+	///
+	/// The method name is part of the file name, so profiles of methods whose name is not a
+	/// plain identifier are not written.
+	pub(crate) fn write(&self, method: &str, folded: &str) {
+		static PROFILES: AtomicUsize = AtomicUsize::new(0);
+
+		if !is_file_name_safe(method) {
+			warn!(target: "executor", "Not writing the profile of `{}`, invalid method name", method);
+			return
+		}
+
+		let timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_millis())
+			.unwrap_or_default();
+		let path = self.output_dir.join(format!(
+			"{}-{}-{}.folded",
+			method,
+			timestamp,
+			PROFILES.fetch_add(1, Ordering::Relaxed),
+		));
+
+		match fs::create_dir_all(&self.output_dir).and_then(|_| fs::write(&path, folded)) {
+			Ok(()) => info!(target: "executor", "Profile of `{}` written to {:?}", method, path),
+			Err(e) => warn!(target: "executor", "Failed to write profile to {:?}: {}", path, e),
+		}
+	}
+}
+
+/// Returns `true` if `method` can't escape the output directory or refer to a special file.
+///
+/// Runtime API methods only consist of ASCII alphanumerics and underscores.
+fn is_file_name_safe(method: &str) -> bool {
+	!method.is_empty() && method.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn only_plain_method_names_are_used_as_file_names() {
+		assert!(is_file_name_safe("Core_execute_block"));
+		assert!(is_file_name_safe("BlockBuilder_apply_extrinsic"));
+
+		assert!(!is_file_name_safe(""));
+		assert!(!is_file_name_safe(".."));
+		assert!(!is_file_name_safe("../Core_version"));
+		assert!(!is_file_name_safe("Core/version"));
+		assert!(!is_file_name_safe("Core\\version"));
+		assert!(!is_file_name_safe("/etc/passwd"));
+	}
+
+	#[test]
+	fn profiles_of_unsafe_method_names_are_not_written() {
+		let dir = tempfile::tempdir().unwrap();
+		let profiling = RuntimeProfiling {
+			methods: Vec::new(),
+			output_dir: dir.path().join("profiles"),
+		};
+
+		profiling.write("../escaped", "main 1");
+		assert!(!dir.path().join("profiles").exists());
+		assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+
+		profiling.write("Core_version", "main 1");
+		assert_eq!(fs::read_dir(dir.path().join("profiles")).unwrap().count(), 1);
+	}
+}
@@ -19,7 +19,7 @@
 //! The primary means of accessing the runtimes is through a cache which saves the reusable
 //! components of the runtime that are expensive to initialize.
 
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 use crate::error::{Error, WasmError};
 use parking_lot::Mutex;
 use codec::Decode;
@@ -60,6 +60,8 @@ struct VersionedRuntime {
 	version: Option<RuntimeVersion>,
 	/// Cached instance pool.
 	instances: Vec<Mutex<Option<Box<dyn WasmInstance>>>>,
+	/// Memory accounting of the cached instances of all runtimes.
+	cached_memory: Arc<CachedInstancesMemory>,
 }
 
 impl VersionedRuntime {
@@ -83,9 +85,13 @@ impl VersionedRuntime {
 
 		match instance {
 			Some((index, mut locked)) => {
-				let (instance, new_inst) = locked.take()
-					.map(|r| Ok((r, false)))
-					.unwrap_or_else(|| self.module.new_instance().map(|i| (i, true)))?;
+				let (instance, new_inst) = match locked.take() {
+					Some(instance) => {
+						self.cached_memory.release(instance.memory_size());
+						(instance, false)
+					}
+					None => (self.module.new_instance()?, true),
+				};
 
 				let result = f(&*instance, self.version.as_ref(), ext);
 				if let Err(e) = &result {
@@ -102,7 +108,7 @@ impl VersionedRuntime {
 							e,
 						);
 					}
-				} else {
+				} else if self.cached_memory.try_reserve(instance.memory_size()) {
 					*locked = Some(instance);
 
 					if new_inst {
@@ -113,6 +119,13 @@ impl VersionedRuntime {
 							self.instances.len(),
 						);
 					}
+				} else {
+					log::debug!(
+						target: "wasm-runtime",
+						"Dropping WASM instance {}/{}, cached instances use too much memory",
+						index + 1,
+						self.instances.len(),
+					);
 				}
 
 				result
@@ -129,6 +142,44 @@ impl VersionedRuntime {
 	}
 }
 
+impl Drop for VersionedRuntime {
+	fn drop(&mut self) {
+		for instance in &self.instances {
+			if let Some(instance) = instance.lock().as_ref() {
+				self.cached_memory.release(instance.memory_size());
+			}
+		}
+	}
+}
+
+/// Accounting of the memory used by the cached runtime instances.
+struct CachedInstancesMemory {
+	/// The number of bytes the cached instances may use.
+	limit: Option<u64>,
+	/// The number of bytes the cached instances use.
+	used: Mutex<u64>,
+}
+
+impl CachedInstancesMemory {
+	/// Reserve `size` bytes for an instance that is going to be cached.
+	///
+	/// Returns `false` if the instance doesn't fit into the limit.
+	fn try_reserve(&self, size: u64) -> bool {
+		let mut used = self.used.lock();
+		if self.limit.map_or(false, |limit| *used + size > limit) {
+			return false
+		}
+		*used += size;
+		true
+	}
+
+	/// Release `size` bytes of an instance that was removed from the cache.
+	fn release(&self, size: u64) {
+		let mut used = self.used.lock();
+		*used = used.saturating_sub(size);
+	}
+}
+
 const MAX_RUNTIMES: usize = 2;
 
 /// Cache for the runtimes.
@@ -141,7 +192,8 @@ const MAX_RUNTIMES: usize = 2;
 /// the memory reset to the initial memory. So, one runtime instance is reused for every fetch
 /// request.
 ///
-/// The size of cache is equal to `MAX_RUNTIMES`.
+/// The size of cache is equal to `MAX_RUNTIMES`. Instances are only kept as long as the memory
+/// of all cached instances stays within the instances memory limit.
 pub struct RuntimeCache {
 	/// A cache of runtimes along with metadata.
 	///
@@ -149,17 +201,31 @@ pub struct RuntimeCache {
 	runtimes: Mutex<[Option<Arc<VersionedRuntime>>; MAX_RUNTIMES]>,
 	/// The size of the instances cache for each runtime.
 	max_runtime_instances: usize,
+	/// Memory accounting of the cached instances of all runtimes.
+	cached_memory: Arc<CachedInstancesMemory>,
 }
 
 impl RuntimeCache {
 	/// Creates a new instance of a runtimes cache.
-	pub fn new(max_runtime_instances: usize) -> RuntimeCache {
+	///
+	/// The cached instances of all runtimes use at most `instances_memory_limit` bytes of linear
+	/// memory if it is given.
+	pub fn new(max_runtime_instances: usize, instances_memory_limit: Option<u64>) -> RuntimeCache {
 		RuntimeCache {
 			runtimes: Default::default(),
 			max_runtime_instances,
+			cached_memory: Arc::new(CachedInstancesMemory {
+				limit: instances_memory_limit,
+				used: Mutex::new(0),
+			}),
 		}
 	}
 
+	/// The number of bytes of linear memory used by the cached instances of all runtimes.
+	pub fn cached_instances_memory(&self) -> u64 {
+		*self.cached_memory.used.lock()
+	}
+
 	/// Prepares a WASM module instance and executes given function for it.
 	///
 	/// This uses internal cache to find avaiable instance or create a new one.
@@ -179,6 +245,14 @@ impl RuntimeCache {
 	///
 	/// `max_runtime_instances` - The size of the instances cache.
 	///
+	/// `artifact_cache` - Directory of the on-disk cache of compiled runtimes, if any.
+	///
+	/// `fuel_limit` - Maximal number of instructions a call of a compiled runtime may execute.
+	///
+	/// `stack_height_limit` - Deterministic limit of the value stack the code is instrumented with.
+	///
+	/// `profiling` - Instrument compiled runtimes so that their calls can be profiled.
+	///
 	/// `f` - Function to execute.
 	///
 	/// # Returns result of `f` wrapped in an additonal result.
@@ -196,6 +270,10 @@ impl RuntimeCache {
 		default_heap_pages: u64,
 		host_functions: &[&'static dyn Function],
 		allow_missing_func_imports: bool,
+		artifact_cache: Option<&Path>,
+		fuel_limit: Option<u64>,
+		stack_height_limit: Option<u32>,
+		profiling: bool,
 		f: F,
 	) -> Result<Result<R, Error>, Error>
 		where F: FnOnce(
@@ -231,6 +309,11 @@ impl RuntimeCache {
 					host_functions.into(),
 					allow_missing_func_imports,
 					self.max_runtime_instances,
+					artifact_cache,
+					fuel_limit,
+					stack_height_limit,
+					profiling,
+					self.cached_memory.clone(),
 				);
 				if let Err(ref err) = result {
 					log::warn!(target: "wasm-runtime", "Cannot create a runtime: {:?}", err);
@@ -261,12 +344,22 @@ impl RuntimeCache {
 }
 
 /// Create a wasm runtime with the given `code`.
+///
+/// Compiled runtimes are loaded from and stored in the `artifact_cache` directory if any. Calls of
+/// compiled runtimes trap after executing `fuel_limit` instructions if it is given. The code is
+/// instrumented with a deterministic stack limit if `stack_height_limit` is given. Compiled
+/// runtimes are instrumented for profiling their calls if `profiling` is set.
+#[cfg_attr(not(feature = "wasmtime"), allow(unused_variables))]
 pub fn create_wasm_runtime_with_code(
 	wasm_method: WasmExecutionMethod,
 	heap_pages: u64,
 	code: &[u8],
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
+	artifact_cache: Option<&Path>,
+	fuel_limit: Option<u64>,
+	stack_height_limit: Option<u32>,
+	profiling: bool,
 ) -> Result<Box<dyn WasmModule>, WasmError> {
 	match wasm_method {
 		WasmExecutionMethod::Interpreted =>
@@ -274,7 +367,8 @@ pub fn create_wasm_runtime_with_code(
 				code,
 				heap_pages,
 				host_functions,
-				allow_missing_func_imports
+				allow_missing_func_imports,
+				stack_height_limit,
 			).map(|runtime| -> Box<dyn WasmModule> { Box::new(runtime) }),
 		#[cfg(feature = "wasmtime")]
 		WasmExecutionMethod::Compiled =>
@@ -282,11 +376,43 @@ pub fn create_wasm_runtime_with_code(
 				code,
 				heap_pages,
 				host_functions,
-				allow_missing_func_imports
+				allow_missing_func_imports,
+				artifact_cache,
+				fuel_limit,
+				stack_height_limit,
+				profiling,
 			).map(|runtime| -> Box<dyn WasmModule> { Box::new(runtime) }),
 	}
 }
 
+/// Compile the given `code` ahead of time and store the result in the `artifact_cache` directory.
+///
+/// Only runtimes executed with [`WasmExecutionMethod::Compiled`] can be precompiled. The result is
+/// only used by executors that instrument the code the same way, i.e. that meter fuel if
+/// `fuel_metering` is set and use the same `stack_height_limit`.
+#[cfg_attr(not(feature = "wasmtime"), allow(unused_variables))]
+pub fn precompile_wasm_runtime(
+	wasm_method: WasmExecutionMethod,
+	code: &[u8],
+	artifact_cache: &Path,
+	fuel_metering: bool,
+	stack_height_limit: Option<u32>,
+) -> Result<(), WasmError> {
+	match wasm_method {
+		WasmExecutionMethod::Interpreted => Err(WasmError::Other(
+			"Interpreted runtimes can not be precompiled".into(),
+		)),
+		#[cfg(feature = "wasmtime")]
+		WasmExecutionMethod::Compiled =>
+			sc_executor_wasmtime::precompile_runtime(
+				code,
+				artifact_cache,
+				fuel_metering,
+				stack_height_limit,
+			),
+	}
+}
+
 fn decode_version(version: &[u8]) -> Result<RuntimeVersion, WasmError> {
 	let v: RuntimeVersion = sp_api::OldRuntimeVersion::decode(&mut &version[..])
 		.map_err(|_|
@@ -315,6 +441,11 @@ fn create_versioned_wasm_runtime(
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
 	max_instances: usize,
+	artifact_cache: Option<&Path>,
+	fuel_limit: Option<u64>,
+	stack_height_limit: Option<u32>,
+	profiling: bool,
+	cached_memory: Arc<CachedInstancesMemory>,
 ) -> Result<VersionedRuntime, WasmError> {
 	#[cfg(not(target_os = "unknown"))]
 	let time = std::time::Instant::now();
@@ -324,6 +455,10 @@ fn create_versioned_wasm_runtime(
 		&code,
 		host_functions,
 		allow_missing_func_imports,
+		artifact_cache,
+		fuel_limit,
+		stack_height_limit,
+		profiling,
 	)?;
 
 	// Call to determine runtime version.
@@ -361,6 +496,7 @@ fn create_versioned_wasm_runtime(
 		heap_pages,
 		wasm_method,
 		instances,
+		cached_memory,
 	})
 }
 
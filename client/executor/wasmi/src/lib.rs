@@ -16,10 +16,10 @@
 
 //! This crate provides an implementation of `WasmModule` that is baked by wasmi.
 
-use std::{str, cell::RefCell, sync::Arc};
+use std::{str, cell::{Cell, RefCell}, sync::Arc};
 use wasmi::{
 	Module, ModuleInstance, MemoryInstance, MemoryRef, TableRef, ImportsBuilder, ModuleRef,
-	memory_units::Pages,
+	memory_units::{Bytes, Pages},
 	RuntimeValue::{I32, I64, self},
 };
 use codec::{Encode, Decode};
@@ -29,9 +29,11 @@ use sp_wasm_interface::{
 	FunctionContext, Pointer, WordSize, Sandbox, MemoryId, Result as WResult, Function,
 };
 use sp_runtime_interface::unpack_ptr_and_len;
+use sp_allocator::AllocationStats;
 use sc_executor_common::wasm_runtime::{WasmModule, WasmInstance};
 use sc_executor_common::{
 	error::{Error, WasmError},
+	instrument,
 	sandbox,
 };
 use sc_executor_common::util::{DataSegmentsSnapshot, WasmModuleInfo};
@@ -50,6 +52,7 @@ impl<'a> FunctionExecutor<'a> {
 	fn new(
 		m: MemoryRef,
 		heap_base: u32,
+		heap_limit: Option<u32>,
 		t: Option<TableRef>,
 		host_functions: &'a [&'static dyn Function],
 		allow_missing_func_imports: bool,
@@ -57,7 +60,7 @@ impl<'a> FunctionExecutor<'a> {
 	) -> Result<Self, Error> {
 		Ok(FunctionExecutor {
 			sandbox_store: sandbox::Store::new(),
-			heap: sp_allocator::FreeingBumpHeapAllocator::new(heap_base),
+			heap: sp_allocator::FreeingBumpHeapAllocator::new(heap_base).with_heap_limit(heap_limit),
 			memory: m,
 			table: t,
 			host_functions,
@@ -440,6 +443,8 @@ fn call_in_wasm_module(
 	host_functions: &[&'static dyn Function],
 	allow_missing_func_imports: bool,
 	missing_functions: &Vec<String>,
+	heap_limit: Option<u32>,
+	allocation_stats: &Cell<Option<AllocationStats>>,
 ) -> Result<Vec<u8>, Error> {
 	// Initialize FunctionExecutor.
 	let table: Option<TableRef> = module_instance
@@ -450,6 +455,7 @@ fn call_in_wasm_module(
 	let mut fec = FunctionExecutor::new(
 		memory.clone(),
 		heap_base,
+		heap_limit,
 		table,
 		host_functions,
 		allow_missing_func_imports,
@@ -465,6 +471,10 @@ fn call_in_wasm_module(
 		&[I32(u32::from(offset) as i32), I32(data.len() as i32)],
 		&mut fec,
 	);
+	// Calls of missing exports only allocated the call data, they have no statistics.
+	if module_instance.export_by_name(method).is_some() {
+		allocation_stats.set(Some(fec.heap.stats()));
+	}
 
 	match result {
 		Ok(Some(I64(r))) => {
@@ -608,18 +618,33 @@ impl WasmModule for WasmiRuntime {
 			host_functions: self.host_functions.clone(),
 			allow_missing_func_imports: self.allow_missing_func_imports,
 			missing_functions,
+			heap_limit: Cell::new(None),
+			allocation_stats: Cell::new(None),
 		}))
 	}
 }
 
 /// Create a new `WasmiRuntime` given the code. This function loads the module and
 /// stores it in the instance.
+///
+/// If `stack_height_limit` is given, the code is instrumented with the same deterministic stack
+/// limit as in the compiled executor. This changes the point at which deep calls trap, so all
+/// nodes of a network have to agree on it.
 pub fn create_runtime(
 	code: &[u8],
 	heap_pages: u64,
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
+	stack_height_limit: Option<u32>,
 ) -> Result<WasmiRuntime, WasmError> {
+	let instrumented;
+	let code = match stack_height_limit {
+		Some(limit) => {
+			instrumented = instrument::inject_stack_limiter(code, limit)?;
+			&instrumented[..]
+		}
+		None => code,
+	};
 	let module = Module::from_buffer(&code).map_err(|_| WasmError::InvalidModule)?;
 
 	// Extract the data segments from the wasm code.
@@ -672,6 +697,10 @@ pub struct WasmiInstance {
 	allow_missing_func_imports: bool,
 	/// List of missing functions detected during function resolution
 	missing_functions: Vec<String>,
+	/// The number of bytes a call can allocate at the same time.
+	heap_limit: Cell<Option<u32>>,
+	/// Allocation statistics of the last call.
+	allocation_stats: Cell<Option<AllocationStats>>,
 }
 
 // This is safe because `WasmiInstance` does not leak any references to `self.memory` and `self.instance`
@@ -679,6 +708,9 @@ unsafe impl Send for WasmiInstance {}
 
 impl WasmInstance for WasmiInstance {
 	fn call(&self, method: &str, data: &[u8]) -> Result<Vec<u8>, Error> {
+		// Don't report the statistics of the previous call if this one doesn't get to run.
+		self.allocation_stats.set(None);
+
 		// We reuse a single wasm instance for multiple calls and a previous call (if any)
 		// altered the state. Therefore, we need to restore the instance to original state.
 
@@ -706,6 +738,8 @@ impl WasmInstance for WasmiInstance {
 			self.host_functions.as_ref(),
 			self.allow_missing_func_imports,
 			self.missing_functions.as_ref(),
+			self.heap_limit.get(),
+			&self.allocation_stats,
 		)
 	}
 
@@ -721,4 +755,16 @@ impl WasmInstance for WasmiInstance {
 			None => Ok(None),
 		}
 	}
+
+	fn set_heap_limit(&self, heap_limit: Option<u32>) {
+		self.heap_limit.set(heap_limit);
+	}
+
+	fn allocation_stats(&self) -> Option<AllocationStats> {
+		self.allocation_stats.get()
+	}
+
+	fn memory_size(&self) -> u64 {
+		Bytes::from(self.memory.current_size()).0 as u64
+	}
 }
@@ -0,0 +1,140 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Records the locked versions of the crates producing the compiled artifacts, which key the
+//! on-disk artifact cache. Without a `Cargo.lock`, the version requirements of our own manifest
+//! are recorded instead.
+
+use std::{env, fs, path::{Path, PathBuf}};
+
+/// Packages whose version determines the compiled artifacts.
+const PACKAGES: &[&str] = &[
+	"cranelift-codegen",
+	"cranelift-wasm",
+	"substrate-wasmtime",
+	"substrate-wasmtime-runtime",
+	"wasmtime-environ",
+];
+
+fn main() {
+	let lock_file = ["CARGO_MANIFEST_DIR", "OUT_DIR"].iter()
+		.filter_map(|var| env::var_os(var))
+		.filter_map(|dir| find_lock_file(Path::new(&dir)))
+		.next();
+
+	let versions = match &lock_file {
+		Some(lock_file) => {
+			println!("cargo:rerun-if-changed={}", lock_file.display());
+			fs::read_to_string(lock_file).map(|lock| locked_versions(&lock)).unwrap_or_default()
+		},
+		None => Vec::new(),
+	};
+
+	let versions = if versions.is_empty() {
+		println!(
+			"cargo:warning=Could not find the locked wasmtime version, \
+			keying the artifact cache on the required versions",
+		);
+		let manifest = Path::new(&env::var_os("CARGO_MANIFEST_DIR").expect("set by cargo; qed"))
+			.join("Cargo.toml");
+		println!("cargo:rerun-if-changed={}", manifest.display());
+		let required = fs::read_to_string(&manifest)
+			.map(|manifest| required_versions(&manifest))
+			.unwrap_or_default();
+		if required.len() != PACKAGES.len() {
+			panic!(
+				"Could not find the required versions of {:?} in {}",
+				PACKAGES,
+				manifest.display(),
+			);
+		}
+		required.join(",")
+	} else {
+		versions.join(",")
+	};
+
+	println!("cargo:rustc-env=WASMTIME_VERSION={}", versions);
+}
+
+/// Find the `Cargo.lock` in `dir` or any of its ancestors.
+fn find_lock_file(dir: &Path) -> Option<PathBuf> {
+	dir.ancestors()
+		.map(|dir| dir.join("Cargo.lock"))
+		.find(|lock_file| lock_file.is_file())
+}
+
+/// The `name version` of every locked version of the `PACKAGES`, sorted.
+fn locked_versions(lock: &str) -> Vec<String> {
+	let mut versions = Vec::new();
+	let mut name = None;
+	for line in lock.lines() {
+		let line = line.trim();
+		if line == "[[package]]" {
+			name = None;
+		} else if line.starts_with("name = ") {
+			name = Some(line["name = ".len()..].trim_matches('"').to_string());
+		} else if line.starts_with("version = ") {
+			if let Some(name) = name.take().filter(|name| PACKAGES.contains(&name.as_str())) {
+				versions.push(format!("{} {}", name, line["version = ".len()..].trim_matches('"')));
+			}
+		}
+	}
+	versions.sort();
+	versions
+}
+
+/// The `name requirement` of every dependency on one of the `PACKAGES` in the manifest, sorted.
+fn required_versions(manifest: &str) -> Vec<String> {
+	let mut versions = Vec::new();
+	for line in manifest.lines() {
+		let mut parts = line.splitn(2, '=');
+		let (key, value) = match (parts.next(), parts.next()) {
+			(Some(key), Some(value)) => (key.trim(), value.trim()),
+			_ => continue,
+		};
+
+		// Either `name = "version"` or `key = { package = "name", version = "version", .. }`.
+		let (name, version) = if value.starts_with('"') {
+			(key, value.trim_matches('"'))
+		} else {
+			(
+				table_value(value, "package").unwrap_or(key),
+				table_value(value, "version").unwrap_or_default(),
+			)
+		};
+		if PACKAGES.contains(&name) && !version.is_empty() {
+			versions.push(format!("{} {}", name, version));
+		}
+	}
+	versions.sort();
+	versions
+}
+
+/// The quoted value of `key` in an inline table.
+fn table_value<'a>(table: &'a str, key: &str) -> Option<&'a str> {
+	table.trim_matches(|c| c == '{' || c == '}')
+		.split(',')
+		.filter_map(|entry| {
+			let mut parts = entry.splitn(2, '=');
+			match (parts.next(), parts.next()) {
+				(Some(k), Some(v)) if k.trim() == key => Some(v.trim().trim_matches('"')),
+				_ => None,
+			}
+		})
+		.next()
+}
@@ -0,0 +1,193 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-disk cache of compiled runtime artifacts.
+//!
+//! Every runtime gets an entry directory named after the hash of its code, the wasmtime version
+//! and the compilation settings. The entry contains a wasmtime cache configuration that points
+//! wasmtime at the entry's `modules` directory, where the compiled code ends up.
+//!
+//! Once the code is compiled the entry is sealed by writing a manifest with the hashes of all
+//! artifacts. An entry that is not sealed or whose artifacts don't match the manifest is wiped
+//! when opened, so the runtime is just compiled again.
+
+use std::{fs, io, path::{Path, PathBuf}};
+use codec::Encode;
+use log::{debug, warn};
+use sp_core::{hashing::blake2_256, hexdisplay::HexDisplay};
+
+/// Locked versions of wasmtime and cranelift the artifacts are produced by, or their required
+/// versions without a `Cargo.lock`, recorded by the build script.
+const WASMTIME_VERSION: &str = env!("WASMTIME_VERSION");
+
+const MANIFEST: &str = "MANIFEST";
+const CONFIG: &str = "config.toml";
+const MODULES: &str = "modules";
+
+/// An entry of the cache holding the artifacts of a single runtime.
+pub struct CacheEntry {
+	path: PathBuf,
+}
+
+impl CacheEntry {
+	/// Open the entry for the given `code` compiled with the given `settings` under `root`.
+	///
+	/// An entry that fails the integrity check is wiped.
+	pub fn open(root: &Path, code: &[u8], settings: &str) -> io::Result<Self> {
+		let key = (code, WASMTIME_VERSION, env!("CARGO_PKG_VERSION"), settings)
+			.using_encoded(blake2_256);
+		let entry = CacheEntry {
+			path: root.join(format!("{}", HexDisplay::from(&key))),
+		};
+
+		if entry.path.exists() && !entry.is_intact()? {
+			warn!(target: "wasmtime", "Discarding corrupted cache entry {:?}", entry.path);
+			fs::remove_dir_all(&entry.path)?;
+		}
+
+		fs::create_dir_all(entry.path.join(MODULES))?;
+		let config = format!(
+			"[cache]\nenabled = true\ndirectory = {:?}\n",
+			entry.path.join(MODULES),
+		);
+		fs::write(entry.config_path(), config)?;
+
+		Ok(entry)
+	}
+
+	/// Path of the wasmtime cache configuration of this entry.
+	pub fn config_path(&self) -> PathBuf {
+		self.path.join(CONFIG)
+	}
+
+	/// Returns `true` if the artifacts of this entry were already recorded.
+	pub fn is_sealed(&self) -> bool {
+		self.path.join(MANIFEST).exists()
+	}
+
+	/// Record the artifacts wasmtime has written for this entry.
+	pub fn seal(&self) -> io::Result<()> {
+		let manifest = encode_manifest(&artifacts(&self.path.join(MODULES))?);
+		let tmp = self.path.join(format!("{}.tmp", MANIFEST));
+		fs::write(&tmp, manifest)?;
+		fs::rename(tmp, self.path.join(MANIFEST))?;
+		debug!(target: "wasmtime", "Sealed cache entry {:?}", self.path);
+		Ok(())
+	}
+
+	fn is_intact(&self) -> io::Result<bool> {
+		let manifest = match fs::read_to_string(self.path.join(MANIFEST)) {
+			Ok(manifest) => manifest,
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+			Err(e) => return Err(e),
+		};
+		Ok(manifest == encode_manifest(&artifacts(&self.path.join(MODULES))?))
+	}
+}
+
+/// Collect the relative paths and hashes of all artifacts in `dir`, sorted by path.
+///
+/// Statistics and temporary files that wasmtime maintains next to the artifacts are skipped.
+fn artifacts(dir: &Path) -> io::Result<Vec<(String, [u8; 32])>> {
+	fn visit(root: &Path, dir: &Path, out: &mut Vec<(String, [u8; 32])>) -> io::Result<()> {
+		for entry in fs::read_dir(dir)? {
+			let path = entry?.path();
+			if path.is_dir() {
+				visit(root, &path, out)?;
+				continue
+			}
+
+			let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+			if name.starts_with('.') || name.ends_with(".stats") || name.contains(".wip-") {
+				continue
+			}
+
+			let relative = path.strip_prefix(root)
+				.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+				.to_string_lossy()
+				.into_owned();
+			out.push((relative, blake2_256(&fs::read(&path)?)));
+		}
+		Ok(())
+	}
+
+	let mut out = Vec::new();
+	visit(dir, dir, &mut out)?;
+	out.sort();
+	Ok(out)
+}
+
+fn encode_manifest(artifacts: &[(String, [u8; 32])]) -> String {
+	artifacts.iter()
+		.map(|(path, hash)| format!("{} {}\n", HexDisplay::from(hash), path))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn write_artifact(entry: &CacheEntry, contents: &[u8]) {
+		let dir = entry.path.join(MODULES).join("compiler");
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join("module"), contents).unwrap();
+		fs::write(dir.join("module.stats"), b"usages = 1").unwrap();
+	}
+
+	#[test]
+	fn keeps_sealed_entries() {
+		let root = tempfile::tempdir().unwrap();
+		let entry = CacheEntry::open(root.path(), b"code", "settings").unwrap();
+		assert!(!entry.is_sealed());
+		write_artifact(&entry, b"artifact");
+		entry.seal().unwrap();
+
+		let entry = CacheEntry::open(root.path(), b"code", "settings").unwrap();
+
+		assert!(entry.is_sealed());
+		assert_eq!(fs::read(entry.path.join(MODULES).join("compiler").join("module")).unwrap(), b"artifact");
+	}
+
+	#[test]
+	fn wipes_corrupted_and_unsealed_entries() {
+		let root = tempfile::tempdir().unwrap();
+		let entry = CacheEntry::open(root.path(), b"code", "settings").unwrap();
+		write_artifact(&entry, b"artifact");
+		entry.seal().unwrap();
+		write_artifact(&entry, b"corrupted");
+
+		let entry = CacheEntry::open(root.path(), b"code", "settings").unwrap();
+		assert!(!entry.is_sealed());
+		assert!(!entry.path.join(MODULES).join("compiler").exists());
+
+		write_artifact(&entry, b"artifact");
+		let entry = CacheEntry::open(root.path(), b"code", "settings").unwrap();
+		assert!(!entry.path.join(MODULES).join("compiler").exists());
+	}
+
+	#[test]
+	fn entries_depend_on_code_and_settings() {
+		let root = tempfile::tempdir().unwrap();
+		let a = CacheEntry::open(root.path(), b"code", "settings").unwrap();
+		let b = CacheEntry::open(root.path(), b"other code", "settings").unwrap();
+		let c = CacheEntry::open(root.path(), b"code", "other settings").unwrap();
+
+		assert_ne!(a.path, b.path);
+		assert_ne!(a.path, c.path);
+	}
+}
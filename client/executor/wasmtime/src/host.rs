@@ -22,7 +22,7 @@ use crate::util;
 use std::{cell::RefCell, rc::Rc};
 use log::trace;
 use codec::{Encode, Decode};
-use sp_allocator::FreeingBumpHeapAllocator;
+use sp_allocator::{AllocationStats, FreeingBumpHeapAllocator};
 use sc_executor_common::error::Result;
 use sc_executor_common::sandbox::{self, SandboxCapabilities, SupervisorFuncIndex};
 use sp_core::sandbox as sandbox_primitives;
@@ -64,6 +64,11 @@ impl HostState {
 		}
 	}
 
+	/// Statistics of the allocations made so far.
+	pub fn allocation_stats(&self) -> AllocationStats {
+		self.allocator.borrow().stats()
+	}
+
 	/// Materialize `HostContext` that can be used to invoke a substrate host `dyn Function`.
 	pub fn materialize<'a>(&'a self) -> HostContext<'a> {
 		HostContext(self)
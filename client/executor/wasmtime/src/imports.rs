@@ -16,10 +16,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::state_holder;
-use sc_executor_common::error::WasmError;
+use crate::{profiler::Profiler, state_holder};
+use sc_executor_common::{
+	error::WasmError,
+	instrument::{
+		FUEL_IMPORT_MODULE, FUEL_IMPORT_NAME, PROFILER_ENTER_IMPORT_NAME, PROFILER_EXIT_IMPORT_NAME,
+	},
+};
 use sp_wasm_interface::{Function, Value, ValueType};
-use std::any::Any;
+use std::{any::Any, cell::Cell, rc::Rc};
 use wasmtime::{
 	Extern, ExternType, Func, FuncType, ImportType, Limits, Memory, MemoryType, Module,
 	Trap, Val,
@@ -34,14 +39,20 @@ pub struct Imports {
 
 /// Goes over all imports of a module and prepares a vector of `Extern`s that can be used for
 /// instantiation of the module. Returns an error if there are imports that cannot be satisfied.
+///
+/// If `fuel` is given, the fuel metering import of an instrumented module is charged from it. If
+/// `profiler` is given, the profiler hooks of an instrumented module report to it.
 pub fn resolve_imports(
 	module: &Module,
 	host_functions: &[&'static dyn Function],
 	heap_pages: u32,
 	allow_missing_func_imports: bool,
+	fuel: Option<&Rc<Fuel>>,
+	profiler: Option<&Rc<Profiler>>,
 ) -> Result<Imports, WasmError> {
 	let mut externs = vec![];
 	let mut memory_import_index = None;
+	let profiler_hooks = profiler.map(|profiler| profiler.clone().into_externs(module));
 	for import_ty in module.imports() {
 		if import_ty.module() != "env" {
 			return Err(WasmError::Other(format!(
@@ -56,6 +67,17 @@ pub fn resolve_imports(
 				memory_import_index = Some(externs.len());
 				resolve_memory_import(module, &import_ty, heap_pages)?
 			}
+			FUEL_IMPORT_NAME if fuel.is_some() && import_ty.module() == FUEL_IMPORT_MODULE => {
+				fuel.expect("checked by the guard above; qed").clone().into_extern(module)
+			}
+			PROFILER_ENTER_IMPORT_NAME | PROFILER_EXIT_IMPORT_NAME if profiler_hooks.is_some() => {
+				let (enter, exit) = profiler_hooks.as_ref().expect("checked by the guard above; qed");
+				if import_ty.name() == PROFILER_ENTER_IMPORT_NAME {
+					enter.clone()
+				} else {
+					exit.clone()
+				}
+			}
 			_ => resolve_func_import(
 				module,
 				&import_ty,
@@ -235,6 +257,64 @@ impl HostFuncHandler {
 	}
 }
 
+/// Fuel available to the calls of an instance.
+pub struct Fuel {
+	limit: u64,
+	remaining: Cell<u64>,
+	exhausted: Cell<bool>,
+}
+
+impl Fuel {
+	/// Create a new tank holding `limit` units of fuel per call.
+	pub fn new(limit: u64) -> Self {
+		Self {
+			limit,
+			remaining: Cell::new(limit),
+			exhausted: Cell::new(false),
+		}
+	}
+
+	/// Fill up the tank before a call.
+	pub fn refill(&self) {
+		self.remaining.set(self.limit);
+		self.exhausted.set(false);
+	}
+
+	/// Returns `true` if the last call trapped because it ran out of fuel.
+	pub fn is_exhausted(&self) -> bool {
+		self.exhausted.get()
+	}
+
+	fn consume(&self, amount: u64) -> Result<(), Trap> {
+		match self.remaining.get().checked_sub(amount) {
+			Some(remaining) => {
+				self.remaining.set(remaining);
+				Ok(())
+			}
+			None => {
+				self.remaining.set(0);
+				self.exhausted.set(true);
+				Err(Trap::new("out of fuel"))
+			}
+		}
+	}
+
+	fn into_extern(self: Rc<Self>, module: &Module) -> Extern {
+		let func_ty = FuncType::new(
+			vec![wasmtime::ValType::I32].into_boxed_slice(),
+			Vec::new().into_boxed_slice(),
+		);
+		let func = Func::new(module.store(), func_ty,
+			move |_, params, _| {
+				// The instrumentation only ever charges non-negative amounts.
+				let amount = params[0].unwrap_i32() as u32;
+				self.consume(amount as u64)
+			}
+		);
+		Extern::Func(func)
+	}
+}
+
 /// A `Callable` handler for missing functions.
 struct MissingHostFuncHandler {
 	module: String,
@@ -16,11 +16,13 @@
 
 ///! Defines a `WasmRuntime` that uses the Wasmtime JIT to execute.
 
+mod artifact_cache;
 mod host;
 mod runtime;
 mod state_holder;
 mod imports;
 mod instance_wrapper;
+mod profiler;
 mod util;
 
-pub use runtime::create_runtime;
+pub use runtime::{create_runtime, precompile_runtime};
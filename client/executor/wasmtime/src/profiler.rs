@@ -0,0 +1,164 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Profiler attributing the time of runtime calls to Wasm functions.
+//!
+//! The runtime code is instrumented to report entering and leaving each function to the profiler.
+//! While a call is profiled, the profiler maintains the stack of Wasm functions and attributes the
+//! time between two reports to the stack as it was in between. Time spent in host functions is
+//! attributed to the calling Wasm function.
+
+use std::{cell::{Cell, RefCell}, collections::BTreeMap, rc::Rc, sync::Arc, time::{Duration, Instant}};
+use wasmtime::{Extern, Func, FuncType, Module, ValType};
+
+/// Profiler of the calls of a single instance.
+pub struct Profiler {
+	/// Names of the Wasm functions by index.
+	names: Arc<Vec<String>>,
+	/// Whether the current call is profiled.
+	active: Cell<bool>,
+	/// Indices of the functions on the stack, the innermost last.
+	stack: RefCell<Vec<u32>>,
+	/// The time of the last report.
+	last_report: Cell<Instant>,
+	/// The time spent by each stack.
+	samples: RefCell<BTreeMap<Vec<u32>, Duration>>,
+}
+
+impl Profiler {
+	/// Create a profiler of a runtime with the functions of the given `names`.
+	pub fn new(names: Arc<Vec<String>>) -> Self {
+		Self {
+			names,
+			active: Cell::new(false),
+			stack: RefCell::new(Vec::new()),
+			last_report: Cell::new(Instant::now()),
+			samples: RefCell::new(BTreeMap::new()),
+		}
+	}
+
+	/// Start profiling a call.
+	pub fn start(&self) {
+		self.stack.borrow_mut().clear();
+		self.samples.borrow_mut().clear();
+		self.last_report.set(Instant::now());
+		self.active.set(true);
+	}
+
+	/// Stop profiling and return the time spent by each stack in the folded stack format, in
+	/// nanoseconds.
+	///
+	/// Functions that didn't return, because the call trapped, are still on the stack.
+	pub fn finish(&self) -> String {
+		self.account();
+		self.active.set(false);
+		self.stack.borrow_mut().clear();
+
+		let unknown = "<unknown>".to_string();
+		self.samples.borrow()
+			.iter()
+			.filter(|(stack, _)| !stack.is_empty())
+			.map(|(stack, time)| {
+				let frames = stack.iter()
+					.map(|index| self.names.get(*index as usize).unwrap_or(&unknown).as_str())
+					.collect::<Vec<_>>();
+				format!("{} {}\n", frames.join(";"), time.as_nanos())
+			})
+			.collect()
+	}
+
+	/// Attribute the time since the last report to the current stack.
+	fn account(&self) {
+		let now = Instant::now();
+		let elapsed = now - self.last_report.replace(now);
+		*self.samples.borrow_mut()
+			.entry(self.stack.borrow().clone())
+			.or_default() += elapsed;
+	}
+
+	fn enter(&self, index: u32) {
+		if self.active.get() {
+			self.account();
+			self.stack.borrow_mut().push(index);
+		}
+	}
+
+	fn exit(&self, index: u32) {
+		if self.active.get() {
+			self.account();
+			let mut stack = self.stack.borrow_mut();
+			if let Some(position) = stack.iter().rposition(|entered| *entered == index) {
+				stack.truncate(position);
+			}
+		}
+	}
+
+	/// The host functions called by the instrumented code, in the order of their imports.
+	pub fn into_externs(self: Rc<Self>, module: &Module) -> (Extern, Extern) {
+		let hook = |profiler: Rc<Self>, report: fn(&Self, u32)| {
+			let func_ty = FuncType::new(
+				vec![ValType::I32].into_boxed_slice(),
+				Vec::new().into_boxed_slice(),
+			);
+			Extern::Func(Func::new(module.store(), func_ty, move |_, params, _| {
+				report(&profiler, params[0].unwrap_i32() as u32);
+				Ok(())
+			}))
+		};
+		(hook(self.clone(), Self::enter), hook(self, Self::exit))
+	}
+}
+
+/// Make the name of a function readable and usable as a frame of a folded stack.
+pub fn frame_name(name: &str) -> String {
+	format!("{:#}", rustc_demangle::demangle(name))
+		.replace(';', ":")
+		.replace(' ', "_")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn attributes_time_to_stacks() {
+		let profiler = Profiler::new(Arc::new(vec!["a".into(), "b".into()]));
+		profiler.enter(0);
+
+		profiler.start();
+		profiler.enter(0);
+		profiler.enter(1);
+		profiler.exit(1);
+		profiler.enter(1);
+		let folded = profiler.finish();
+
+		let stacks = folded.lines()
+			.map(|line| line.rsplitn(2, ' ').nth(1).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(stacks, vec!["a", "a;b"]);
+		assert!(profiler.stack.borrow().is_empty());
+	}
+
+	#[test]
+	fn frame_names_are_demangled() {
+		assert_eq!(
+			frame_name("_ZN4core3fmt5write17h0123456789abcdefE"),
+			"core::fmt::write",
+		);
+		assert_eq!(frame_name("a b;c"), "a_b:c");
+	}
+}
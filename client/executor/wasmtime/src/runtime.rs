@@ -17,20 +17,27 @@
 //! Defines the compiled Wasm runtime that uses Wasmtime internally.
 
 use crate::host::HostState;
-use crate::imports::{Imports, resolve_imports};
+use crate::imports::{Fuel, Imports, resolve_imports};
 use crate::instance_wrapper::{ModuleWrapper, InstanceWrapper, GlobalsSnapshot};
 use crate::state_holder;
+use crate::artifact_cache::CacheEntry;
+use crate::profiler::{self, Profiler};
 
+use std::cell::Cell;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
+use log::warn;
 use sc_executor_common::{
 	error::{Error, Result, WasmError},
+	instrument,
 	wasm_runtime::{WasmModule, WasmInstance},
 };
-use sp_allocator::FreeingBumpHeapAllocator;
+use sp_allocator::{AllocationStats, FreeingBumpHeapAllocator};
 use sp_runtime_interface::unpack_ptr_and_len;
 use sp_wasm_interface::{Function, Pointer, WordSize, Value};
 use wasmtime::{Config, Engine, Store};
+use cranelift_codegen::ir::TrapCode;
 
 /// A `WasmModule` implementation using wasmtime to compile the runtime module to machine code
 /// and execute the compiled code.
@@ -39,10 +46,16 @@ pub struct WasmtimeRuntime {
 	heap_pages: u32,
 	allow_missing_func_imports: bool,
 	host_functions: Vec<&'static dyn Function>,
+	fuel_limit: Option<u64>,
+	profiler_names: Option<Arc<Vec<String>>>,
 }
 
 impl WasmModule for WasmtimeRuntime {
 	fn new_instance(&self) -> Result<Box<dyn WasmInstance>> {
+		let fuel = self.fuel_limit.map(|limit| Rc::new(Fuel::new(limit)));
+		let profiler = self.profiler_names.as_ref()
+			.map(|names| Rc::new(Profiler::new(names.clone())));
+
 		// Scan all imports, find the matching host functions, and create stubs that adapt arguments
 		// and results.
 		let imports = resolve_imports(
@@ -50,6 +63,8 @@ impl WasmModule for WasmtimeRuntime {
 			&self.host_functions,
 			self.heap_pages,
 			self.allow_missing_func_imports,
+			fuel.as_ref(),
+			profiler.as_ref(),
 		)?;
 
 		let instance_wrapper =
@@ -64,6 +79,10 @@ impl WasmModule for WasmtimeRuntime {
 			globals_snapshot,
 			heap_pages: self.heap_pages,
 			heap_base,
+			heap_limit: Cell::new(None),
+			fuel,
+			profiler,
+			allocation_stats: Cell::new(None),
 		}))
 	}
 }
@@ -77,16 +96,21 @@ pub struct WasmtimeInstance {
 	imports: Imports,
 	heap_pages: u32,
 	heap_base: u32,
+	heap_limit: Cell<Option<u32>>,
+	fuel: Option<Rc<Fuel>>,
+	profiler: Option<Rc<Profiler>>,
+	allocation_stats: Cell<Option<AllocationStats>>,
 }
 
 // This is safe because `WasmtimeInstance` does not leak reference to `self.imports`
 // and all imports don't reference any anything, other than host functions and memory
 unsafe impl Send for WasmtimeInstance {}
 
-impl WasmInstance for WasmtimeInstance {
-	fn call(&self, method: &str, data: &[u8]) -> Result<Vec<u8>> {
-		let entrypoint = self.instance_wrapper.resolve_entrypoint(method)?;
-		let allocator = FreeingBumpHeapAllocator::new(self.heap_base);
+impl WasmtimeInstance {
+	/// Call the resolved `entrypoint` with a fresh instance state.
+	fn call_entrypoint(&self, entrypoint: wasmtime::Func, data: &[u8]) -> Result<Vec<u8>> {
+		let allocator = FreeingBumpHeapAllocator::new(self.heap_base)
+			.with_heap_limit(self.heap_limit.get());
 
 		self.module_wrapper
 			.data_segments_snapshot()
@@ -97,51 +121,188 @@ impl WasmInstance for WasmtimeInstance {
 
 		self.globals_snapshot.apply(&*self.instance_wrapper)?;
 
+		if let Some(fuel) = &self.fuel {
+			fuel.refill();
+		}
+
 		perform_call(
 			data,
 			Rc::clone(&self.instance_wrapper),
 			entrypoint,
 			allocator,
+			self.fuel.as_ref().map(|fuel| &**fuel),
+			&self.allocation_stats,
 		)
 	}
+}
+
+impl WasmInstance for WasmtimeInstance {
+	fn call(&self, method: &str, data: &[u8]) -> Result<Vec<u8>> {
+		// Don't report the statistics of the previous call if this one doesn't get to run.
+		self.allocation_stats.set(None);
+		let entrypoint = self.instance_wrapper.resolve_entrypoint(method)?;
+		self.call_entrypoint(entrypoint, data)
+	}
 
 	fn get_global_const(&self, name: &str) -> Result<Option<Value>> {
 		let instance = InstanceWrapper::new(&self.module_wrapper, &self.imports, self.heap_pages)?;
 		instance.get_global_val(name)
 	}
+
+	fn call_profiled(&self, method: &str, data: &[u8]) -> (Result<Vec<u8>>, Option<String>) {
+		let profiler = match &self.profiler {
+			Some(profiler) => profiler,
+			None => return (self.call(method, data), None),
+		};
+
+		// Calls of missing exports don't run any code, there is nothing to profile.
+		self.allocation_stats.set(None);
+		let entrypoint = match self.instance_wrapper.resolve_entrypoint(method) {
+			Ok(entrypoint) => entrypoint,
+			Err(e) => return (Err(e), None),
+		};
+
+		profiler.start();
+		let result = self.call_entrypoint(entrypoint, data);
+		(result, Some(profiler.finish()))
+	}
+
+	fn set_heap_limit(&self, heap_limit: Option<u32>) {
+		self.heap_limit.set(heap_limit);
+	}
+
+	fn allocation_stats(&self) -> Option<AllocationStats> {
+		self.allocation_stats.get()
+	}
+
+	fn memory_size(&self) -> u64 {
+		self.instance_wrapper.memory_size() as u64
+	}
 }
 
-/// Create a new `WasmtimeRuntime` given the code. This function performs translation from Wasm to
-/// machine code, which can be computationally heavy.
-pub fn create_runtime(
-	code: &[u8],
-	heap_pages: u64,
-	host_functions: Vec<&'static dyn Function>,
-	allow_missing_func_imports: bool,
-) -> std::result::Result<WasmtimeRuntime, WasmError> {
-	// Create the engine, store and finally the module from the given code.
+/// Settings the runtime code is compiled with, these are part of the artifact cache key.
+///
+/// Must describe everything that `engine_config` changes.
+const COMPILATION_SETTINGS: &str = "cranelift_opt_level=speed_and_size";
+
+fn engine_config() -> Config {
 	let mut config = Config::new();
 	config.cranelift_opt_level(wasmtime::OptLevel::SpeedAndSize);
+	config
+}
+
+/// Instrument and compile the given code, going through the artifact cache under `cache_path` if
+/// any.
+///
+/// Returns the names of the functions reported to the profiler if the code is instrumented for
+/// `profiling`.
+fn compile(
+	code: &[u8],
+	cache_path: Option<&Path>,
+	fuel_metering: bool,
+	stack_height_limit: Option<u32>,
+	profiling: bool,
+) -> std::result::Result<(ModuleWrapper, Option<Vec<String>>), WasmError> {
+	// The profiler hooks are injected first, so that the names of the original code still apply.
+	let (code, profiler_names) = if profiling {
+		let (code, names) = instrument::inject_profiler(code)?;
+		(code, Some(names.iter().map(|name| profiler::frame_name(name)).collect()))
+	} else {
+		(code.to_vec(), None)
+	};
+	let code = if fuel_metering {
+		instrument::inject_fuel_metering(&code)?
+	} else {
+		code
+	};
+	let code = &match stack_height_limit {
+		Some(limit) => instrument::inject_stack_limiter(&code, limit)?,
+		None => code,
+	};
 
+	let mut config = engine_config();
+	let cache_entry = cache_path.and_then(|root| {
+		let entry = CacheEntry::open(root, code, COMPILATION_SETTINGS)
+			.map_err(|e| warn!(target: "wasmtime", "Failed to open artifact cache entry: {}", e))
+			.ok()?;
+		config.cache_config_load(entry.config_path())
+			.map_err(|e| warn!(target: "wasmtime", "Failed to load artifact cache config: {}", e))
+			.ok()?;
+		Some(entry)
+	});
+
+	// Create the engine, store and finally the module from the given code.
 	let engine = Engine::new(&config);
 	let store = Store::new(&engine);
 
 	let module_wrapper = ModuleWrapper::new(&store, code)
 		.map_err(|e| WasmError::Other(format!("cannot create module: {}", e)))?;
 
+	if let Some(entry) = cache_entry.filter(|entry| !entry.is_sealed()) {
+		if let Err(e) = entry.seal() {
+			warn!(target: "wasmtime", "Failed to seal artifact cache entry: {}", e);
+		}
+	}
+
+	Ok((module_wrapper, profiler_names))
+}
+
+/// Create a new `WasmtimeRuntime` given the code. This function performs translation from Wasm to
+/// machine code, which can be computationally heavy.
+///
+/// If `cache_path` is given, compiled artifacts are loaded from and stored in the cache under it.
+///
+/// If `stack_height_limit` is given, the code is instrumented with a deterministic stack limit.
+/// This changes the point at which deep calls trap, so all nodes of a network have to agree on it.
+/// If `fuel_limit` is given, the code is instrumented to count executed instructions and every call
+/// traps once it executed more than `fuel_limit` of them.
+///
+/// If `profiling` is set, the code is instrumented so that calls can be profiled with
+/// `WasmInstance::call_profiled`.
+pub fn create_runtime(
+	code: &[u8],
+	heap_pages: u64,
+	host_functions: Vec<&'static dyn Function>,
+	allow_missing_func_imports: bool,
+	cache_path: Option<&Path>,
+	fuel_limit: Option<u64>,
+	stack_height_limit: Option<u32>,
+	profiling: bool,
+) -> std::result::Result<WasmtimeRuntime, WasmError> {
+	let (module_wrapper, profiler_names) =
+		compile(code, cache_path, fuel_limit.is_some(), stack_height_limit, profiling)?;
+
 	Ok(WasmtimeRuntime {
 		module_wrapper: Arc::new(module_wrapper),
 		heap_pages: heap_pages as u32,
 		allow_missing_func_imports,
 		host_functions,
+		fuel_limit,
+		profiler_names: profiler_names.map(Arc::new),
 	})
 }
 
+/// Compile the given code and store the artifacts in the cache under `cache_path`, so that
+/// creating a runtime from the same code later on doesn't need to compile it again.
+///
+/// The artifacts depend on the instrumentation, so `fuel_metering` and `stack_height_limit` have to
+/// match the settings of the runtimes that should use them. The code is compiled without profiling.
+pub fn precompile_runtime(
+	code: &[u8],
+	cache_path: &Path,
+	fuel_metering: bool,
+	stack_height_limit: Option<u32>,
+) -> std::result::Result<(), WasmError> {
+	compile(code, Some(cache_path), fuel_metering, stack_height_limit, false).map(|_| ())
+}
+
 fn perform_call(
 	data: &[u8],
 	instance_wrapper: Rc<InstanceWrapper>,
 	entrypoint: wasmtime::Func,
 	mut allocator: FreeingBumpHeapAllocator,
+	fuel: Option<&Fuel>,
+	allocation_stats: &Cell<Option<AllocationStats>>,
 ) -> Result<Vec<u8>> {
 	let (data_ptr, data_len) = inject_input_data(&instance_wrapper, &mut allocator, data)?;
 
@@ -155,20 +316,64 @@ fn perform_call(
 				let retval = results[0].unwrap_i64() as u64;
 				Ok(unpack_ptr_and_len(retval))
 			}
-			Err(trap) => {
-				return Err(Error::from(format!(
-					"Wasm execution trapped: {}",
-					trap
-				)));
-			}
+			Err(_) if fuel.map_or(false, |fuel| fuel.is_exhausted()) => Err(Error::OutOfFuel),
+			Err(trap) => Err(trap_to_error(trap)),
 		}
 	});
+	allocation_stats.set(Some(host_state.allocation_stats()));
 	let (output_ptr, output_len) = ret?;
 	let output = extract_output_data(&instance_wrapper, output_ptr, output_len)?;
 
 	Ok(output)
 }
 
+/// The description wasmtime gives each trap code raised by compiled code, in the messages of the
+/// form `wasm trap: <description>, <location>`.
+const TRAP_DESCRIPTIONS: &[(TrapCode, &str)] = &[
+	(TrapCode::StackOverflow, "call stack exhausted"),
+	(TrapCode::HeapOutOfBounds, "out of bounds memory access"),
+	(TrapCode::TableOutOfBounds, "undefined element"),
+	(TrapCode::IndirectCallToNull, "uninitialized element"),
+	(TrapCode::BadSignature, "indirect call type mismatch"),
+	(TrapCode::IntegerOverflow, "integer overflow"),
+	(TrapCode::IntegerDivisionByZero, "integer divide by zero"),
+	(TrapCode::BadConversionToInteger, "invalid conversion to integer"),
+	(TrapCode::UnreachableCodeReached, "unreachable"),
+	(TrapCode::Interrupt, "interrupt"),
+];
+
+/// Guess the code of a trap raised by the compiled code from the message of `trap`.
+///
+/// This release of wasmtime doesn't expose the code of a trap, it only carries its description in
+/// the message. The guess breaks if wasmtime changes the descriptions, which makes traps of the
+/// Wasm code show up as generic errors.
+fn trap_code_from_message(trap: &wasmtime::Trap) -> Option<TrapCode> {
+	let rest = trap.message().splitn(2, "wasm trap: ").nth(1)?;
+	TRAP_DESCRIPTIONS.iter()
+		.find(|(_, description)| rest.starts_with(description))
+		.map(|(code, _)| *code)
+}
+
+/// Convert a trap into an error, reporting traps raised by the Wasm code itself the same way as
+/// `sc-executor-wasmi` does.
+fn trap_to_error(trap: wasmtime::Trap) -> Error {
+	use wasmi::TrapKind;
+
+	let kind = match trap_code_from_message(&trap) {
+		Some(TrapCode::UnreachableCodeReached) => TrapKind::Unreachable,
+		Some(TrapCode::StackOverflow) => TrapKind::StackOverflow,
+		Some(TrapCode::HeapOutOfBounds) => TrapKind::MemoryAccessOutOfBounds,
+		Some(TrapCode::TableOutOfBounds) => TrapKind::TableAccessOutOfBounds,
+		Some(TrapCode::IndirectCallToNull) => TrapKind::ElemUninitialized,
+		Some(TrapCode::BadSignature) => TrapKind::UnexpectedSignature,
+		Some(TrapCode::IntegerDivisionByZero) => TrapKind::DivisionByZero,
+		Some(TrapCode::IntegerOverflow) | Some(TrapCode::BadConversionToInteger) =>
+			TrapKind::InvalidConversionToInt,
+		_ => return Error::from(format!("Wasm execution trapped: {}", trap)),
+	};
+	Error::Trap(wasmi::Trap::new(kind))
+}
+
 fn inject_input_data(
 	instance: &InstanceWrapper,
 	allocator: &mut FreeingBumpHeapAllocator,
@@ -0,0 +1,53 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Finality proofs served over RPC.
+
+use serde::{Serialize, Deserialize};
+use sc_client_api::backend::Backend;
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+use finality_grandpa::BlockNumberOps;
+use sc_finality_grandpa::FinalityProofProvider;
+
+/// An encoded finality proof, in the format used by light clients syncing over the network.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EncodedFinalityProof(pub sp_core::Bytes);
+
+/// Local trait mainly to allow mocking in tests.
+pub trait RpcFinalityProofProvider<Block: BlockT> {
+	/// Prove finality of the given block, returns `None` if it isn't finalized.
+	fn rpc_prove_finality(
+		&self,
+		block: Block::Hash,
+	) -> Result<Option<EncodedFinalityProof>, sp_blockchain::Error>;
+}
+
+impl<B, Block> RpcFinalityProofProvider<Block> for FinalityProofProvider<B, Block>
+where
+	Block: BlockT,
+	NumberFor<Block>: BlockNumberOps,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	fn rpc_prove_finality(
+		&self,
+		block: Block::Hash,
+	) -> Result<Option<EncodedFinalityProof>, sp_blockchain::Error> {
+		self.prove_block_finality(block)
+			.map(|proof| proof.map(|proof| EncodedFinalityProof(proof.into())))
+	}
+}
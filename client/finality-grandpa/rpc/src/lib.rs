@@ -19,69 +19,204 @@
 //! RPC API for GRANDPA.
 #![warn(missing_docs)]
 
-use futures::{FutureExt, TryFutureExt};
+use std::sync::Arc;
+
+use futures::{FutureExt, TryFutureExt, StreamExt, TryStreamExt};
+use jsonrpc_core::futures::{Future, Sink, Stream};
 use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
+use sp_runtime::traits::Block as BlockT;
 
 mod error;
+mod finality;
+mod notification;
 mod report;
 
+use sc_finality_grandpa::GrandpaJustificationStream;
+
+use error::Error;
+use finality::{EncodedFinalityProof, RpcFinalityProofProvider};
+use notification::JustificationNotification;
 use report::{ReportAuthoritySet, ReportVoterState, ReportedRoundStates};
 
 /// Returned when Grandpa RPC endpoint is not ready.
 pub const NOT_READY_ERROR_CODE: i64 = 1;
+/// Returned when GRANDPA fails to prove finality of a block.
+pub const PROVE_FINALITY_ERROR_CODE: i64 = 2;
 
 type FutureResult<T> =
 	Box<dyn jsonrpc_core::futures::Future<Item = T, Error = jsonrpc_core::Error> + Send>;
 
 /// Provides RPC methods for interacting with GRANDPA.
 #[rpc]
-pub trait GrandpaApi {
+pub trait GrandpaApi<Notification, Hash> {
+	/// RPC Metadata
+	type Metadata;
+
 	/// Returns the state of the current best round state as well as the
 	/// ongoing background rounds.
 	#[rpc(name = "grandpa_roundState")]
 	fn round_state(&self) -> FutureResult<ReportedRoundStates>;
+
+	/// Subscribe to the justifications of blocks finalized by GRANDPA, as
+	/// they are imported or produced by the local voter.
+	#[pubsub(
+		subscription = "grandpa_justifications",
+		subscribe,
+		name = "grandpa_subscribeJustifications"
+	)]
+	fn subscribe_justifications(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<Notification>
+	);
+
+	/// Unsubscribe from receiving justifications.
+	#[pubsub(
+		subscription = "grandpa_justifications",
+		unsubscribe,
+		name = "grandpa_unsubscribeJustifications"
+	)]
+	fn unsubscribe_justifications(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId
+	) -> jsonrpc_core::Result<bool>;
+
+	/// Prove finality of the given block, returning the encoded proof or `null`
+	/// if the block hasn't been finalized yet.
+	///
+	/// This needs the state of the parent of the block, so it fails for old blocks
+	/// on nodes that prune the state.
+	#[rpc(name = "grandpa_proveFinality")]
+	fn prove_finality(&self, block: Hash) -> FutureResult<Option<EncodedFinalityProof>>;
 }
 
 /// Implements the GrandpaApi RPC trait for interacting with GRANDPA.
-pub struct GrandpaRpcHandler<AuthoritySet, VoterState> {
+pub struct GrandpaRpcHandler<AuthoritySet, VoterState, Block: BlockT, ProofProvider> {
 	authority_set: AuthoritySet,
 	voter_state: VoterState,
+	justification_stream: GrandpaJustificationStream<Block>,
+	subscriptions: SubscriptionManager,
+	finality_proof_provider: Arc<ProofProvider>,
 }
 
-impl<AuthoritySet, VoterState> GrandpaRpcHandler<AuthoritySet, VoterState> {
+impl<AuthoritySet, VoterState, Block: BlockT, ProofProvider>
+	GrandpaRpcHandler<AuthoritySet, VoterState, Block, ProofProvider>
+{
 	/// Creates a new GrandpaRpcHander instance.
-	pub fn new(authority_set: AuthoritySet, voter_state: VoterState) -> Self {
+	pub fn new(
+		authority_set: AuthoritySet,
+		voter_state: VoterState,
+		justification_stream: GrandpaJustificationStream<Block>,
+		subscriptions: SubscriptionManager,
+		finality_proof_provider: Arc<ProofProvider>,
+	) -> Self {
 		Self {
 			authority_set,
 			voter_state,
+			justification_stream,
+			subscriptions,
+			finality_proof_provider,
 		}
 	}
 }
 
-impl<AuthoritySet, VoterState> GrandpaApi for GrandpaRpcHandler<AuthoritySet, VoterState>
+impl<AuthoritySet, VoterState, Block, ProofProvider>
+	GrandpaApi<JustificationNotification, Block::Hash>
+	for GrandpaRpcHandler<AuthoritySet, VoterState, Block, ProofProvider>
 where
 	VoterState: ReportVoterState + Send + Sync + 'static,
 	AuthoritySet: ReportAuthoritySet + Send + Sync + 'static,
+	Block: BlockT,
+	ProofProvider: RpcFinalityProofProvider<Block> + Send + Sync + 'static,
 {
+	type Metadata = sc_rpc_api::Metadata;
+
 	fn round_state(&self) -> FutureResult<ReportedRoundStates> {
 		let round_states = ReportedRoundStates::from(&self.authority_set, &self.voter_state);
 		let future = async move { round_states }.boxed();
 		Box::new(future.map_err(jsonrpc_core::Error::from).compat())
 	}
+
+	fn subscribe_justifications(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<JustificationNotification>
+	) {
+		let stream = self.justification_stream.subscribe()
+			.map(|x| Ok::<_, ()>(JustificationNotification::from(x)))
+			.compat();
+
+		self.subscriptions.add(subscriber, |sink| {
+			let stream = stream.map(|res| Ok(res));
+			sink.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+				.send_all(stream)
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_justifications(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+
+	fn prove_finality(&self, block: Block::Hash) -> FutureResult<Option<EncodedFinalityProof>> {
+		let result = self.finality_proof_provider.rpc_prove_finality(block);
+		let future = async move { result }.boxed();
+		Box::new(
+			future
+				.map_err(|e| {
+					warn!("Error proving finality: {}", e);
+					Error::ProveFinalityFailed(e)
+				})
+				.map_err(jsonrpc_core::Error::from)
+				.compat()
+		)
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use jsonrpc_core::IoHandler;
-	use sc_finality_grandpa::{report, AuthorityId};
-	use sp_core::crypto::Public;
+	use jsonrpc_core::{futures::future as future01, MetaIoHandler};
+	use parity_scale_codec::{Decode, Encode};
+	use sc_finality_grandpa::{
+		report, AuthorityId, Commit, GrandpaJustification, GrandpaJustificationSender,
+	};
+	use sc_rpc_api::Metadata;
+	use sp_core::{crypto::Public, H256};
 	use std::{collections::HashSet, convert::TryInto};
+	use substrate_test_runtime_client::runtime::{Block, Header};
 
 	struct TestAuthoritySet;
 	struct TestVoterState;
 	struct EmptyVoterState;
 
+	struct TestFinalityProofProvider {
+		finality_proof: Option<Vec<u8>>,
+	}
+
+	type Boxed01Future01 = Box<dyn Future<Item = (), Error = ()> + Send + 'static>;
+
+	struct TestExecutor;
+
+	impl future01::Executor<Boxed01Future01> for TestExecutor {
+		fn execute(
+			&self,
+			future: Boxed01Future01,
+		) -> Result<(), future01::ExecuteError<Boxed01Future01>> {
+			std::thread::spawn(move || {
+				let _ = future.wait();
+			});
+			Ok(())
+		}
+	}
+
 	fn voters() -> HashSet<AuthorityId> {
 		let voter_id_1 = AuthorityId::from_slice(&[1; 32]);
 		let voter_id_2 = AuthorityId::from_slice(&[2; 32]);
@@ -133,23 +268,70 @@ mod tests {
 		}
 	}
 
+	impl RpcFinalityProofProvider<Block> for TestFinalityProofProvider {
+		fn rpc_prove_finality(
+			&self,
+			_block: H256,
+		) -> Result<Option<EncodedFinalityProof>, sp_blockchain::Error> {
+			Ok(self.finality_proof.clone().map(|proof| EncodedFinalityProof(proof.into())))
+		}
+	}
+
+	fn setup_io_handler<VoterState>(
+		voter_state: VoterState,
+	) -> (MetaIoHandler<Metadata>, GrandpaJustificationSender<Block>) where
+		VoterState: ReportVoterState + Send + Sync + 'static,
+	{
+		setup_io_handler_with_finality_proof(voter_state, None)
+	}
+
+	fn setup_io_handler_with_finality_proof<VoterState>(
+		voter_state: VoterState,
+		finality_proof: Option<Vec<u8>>,
+	) -> (MetaIoHandler<Metadata>, GrandpaJustificationSender<Block>) where
+		VoterState: ReportVoterState + Send + Sync + 'static,
+	{
+		let (justification_sender, justification_stream) = GrandpaJustificationSender::channel();
+		let handler = GrandpaRpcHandler::new(
+			TestAuthoritySet,
+			voter_state,
+			justification_stream,
+			SubscriptionManager::new(Arc::new(TestExecutor)),
+			Arc::new(TestFinalityProofProvider { finality_proof }),
+		);
+
+		let mut io = MetaIoHandler::default();
+		io.extend_with(GrandpaApi::to_delegate(handler));
+
+		(io, justification_sender)
+	}
+
+	fn create_justification() -> GrandpaJustification<Block> {
+		let commit: Commit<Block> = finality_grandpa::Commit {
+			target_hash: H256::repeat_byte(1),
+			target_number: 1,
+			precommits: Vec::new(),
+		};
+
+		// justifications can only be built from commits by the voter, so we
+		// go through the codec to create one for a given round.
+		let encoded = (1u64, commit, Vec::<Header>::new()).encode();
+		GrandpaJustification::decode(&mut &encoded[..]).unwrap()
+	}
+
 	#[test]
 	fn uninitialized_rpc_handler() {
-		let handler = GrandpaRpcHandler::new(TestAuthoritySet, EmptyVoterState);
-		let mut io = IoHandler::new();
-		io.extend_with(GrandpaApi::to_delegate(handler));
+		let (io, _) = setup_io_handler(EmptyVoterState);
 
 		let request = r#"{"jsonrpc":"2.0","method":"grandpa_roundState","params":[],"id":1}"#;
 		let response = r#"{"jsonrpc":"2.0","error":{"code":1,"message":"GRANDPA RPC endpoint not ready"},"id":1}"#;
 
-		assert_eq!(Some(response.into()), io.handle_request_sync(request));
+		assert_eq!(Some(response.into()), io.handle_request_sync(request, Default::default()));
 	}
 
 	#[test]
 	fn working_rpc_handler() {
-		let handler = GrandpaRpcHandler::new(TestAuthoritySet, TestVoterState);
-		let mut io = IoHandler::new();
-		io.extend_with(GrandpaApi::to_delegate(handler));
+		let (io, _) = setup_io_handler(TestVoterState);
 
 		let request = r#"{"jsonrpc":"2.0","method":"grandpa_roundState","params":[],"id":1}"#;
 		let response = "{\"jsonrpc\":\"2.0\",\"result\":{\
@@ -166,6 +348,79 @@ mod tests {
 			\"setId\":1\
 		},\"id\":1}";
 
-		assert_eq!(io.handle_request_sync(request), Some(response.into()));
+		assert_eq!(io.handle_request_sync(request, Default::default()), Some(response.into()));
+	}
+
+	#[test]
+	fn subscribe_and_listen_to_one_justification() {
+		let (io, justification_sender) = setup_io_handler(TestVoterState);
+		let (transport, meta) = Metadata::new_test();
+
+		let request = r#"{"jsonrpc":"2.0","method":"grandpa_subscribeJustifications","params":[],"id":1}"#;
+		let response = io.handle_request_sync(request, meta).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let subscription_id = response["result"].take();
+		assert!(subscription_id.is_string());
+
+		let justification = create_justification();
+		justification_sender.notify(&justification);
+
+		let notifications = transport.take(1).collect().wait().unwrap();
+		let mut notification: serde_json::Value = serde_json::from_str(&notifications[0]).unwrap();
+		assert_eq!(notification["method"], "grandpa_justifications");
+		assert_eq!(notification["params"]["subscription"], subscription_id);
+
+		let encoded: sp_core::Bytes =
+			serde_json::from_value(notification["params"]["result"].take()).unwrap();
+		assert_eq!(encoded.0, justification.encode());
+	}
+
+	#[test]
+	fn subscribe_and_unsubscribe_to_justifications() {
+		let (io, _) = setup_io_handler(TestVoterState);
+		let (_transport, meta) = Metadata::new_test();
+
+		let request = r#"{"jsonrpc":"2.0","method":"grandpa_subscribeJustifications","params":[],"id":1}"#;
+		let response = io.handle_request_sync(request, meta.clone()).unwrap();
+		let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let subscription_id = response["result"].as_str().unwrap().to_string();
+
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"grandpa_unsubscribeJustifications","params":["{}"],"id":1}}"#,
+			subscription_id,
+		);
+		let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+		assert_eq!(io.handle_request_sync(&request, meta.clone()), Some(response.into()));
+
+		// the subscription is gone
+		let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+		assert_eq!(io.handle_request_sync(&request, meta), Some(response.into()));
+	}
+
+	#[test]
+	fn prove_finality_with_test_finality_proof_provider() {
+		let (io, _) = setup_io_handler_with_finality_proof(
+			TestVoterState,
+			Some(vec![0xa, 0xb, 0xc]),
+		);
+
+		let request = "{\"jsonrpc\":\"2.0\",\"method\":\"grandpa_proveFinality\",\"params\":[\
+			\"0x0000000000000000000000000000000000000000000000000000000000000000\"\
+		],\"id\":1}";
+		let response = r#"{"jsonrpc":"2.0","result":"0x0a0b0c","id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request, Default::default()), Some(response.into()));
+	}
+
+	#[test]
+	fn prove_finality_of_unfinalized_block() {
+		let (io, _) = setup_io_handler(TestVoterState);
+
+		let request = "{\"jsonrpc\":\"2.0\",\"method\":\"grandpa_proveFinality\",\"params\":[\
+			\"0x0000000000000000000000000000000000000000000000000000000000000000\"\
+		],\"id\":1}";
+		let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+		assert_eq!(io.handle_request_sync(request, Default::default()), Some(response.into()));
 	}
 }
@@ -52,6 +52,7 @@ use crate::authorities::{AuthoritySet, SharedAuthoritySet};
 use crate::communication::Network as NetworkT;
 use crate::consensus_changes::SharedConsensusChanges;
 use crate::justification::GrandpaJustification;
+use crate::notification::GrandpaJustificationSender;
 use crate::until_imported::UntilVoteTargetImported;
 use crate::voting_rule::VotingRule;
 use sp_finality_grandpa::{
@@ -404,6 +405,7 @@ pub(crate) struct Environment<Backend, Block: BlockT, C, N: NetworkT<Block>, SC,
 	pub(crate) voter_set_state: SharedVoterSetState<Block>,
 	pub(crate) voting_rule: VR,
 	pub(crate) metrics: Option<Metrics>,
+	pub(crate) justification_sender: Option<GrandpaJustificationSender<Block>>,
 	pub(crate) _phantom: PhantomData<Backend>,
 }
 
@@ -516,7 +518,7 @@ where
 		);
 
 		self.client.runtime_api()
-			.submit_report_equivocation_extrinsic(
+			.submit_report_equivocation_unsigned_extrinsic(
 				&BlockId::Hash(best_header.hash()),
 				equivocation_proof,
 				key_owner_proof,
@@ -1005,6 +1007,7 @@ where
 			&self.authority_set,
 			&self.consensus_changes,
 			Some(self.config.justification_period.into()),
+			self.justification_sender.as_ref(),
 			hash,
 			number,
 			(round, commit).into(),
@@ -1069,6 +1072,7 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 	authority_set: &SharedAuthoritySet<Block::Hash, NumberFor<Block>>,
 	consensus_changes: &SharedConsensusChanges<Block::Hash, NumberFor<Block>>,
 	justification_period: Option<NumberFor<Block>>,
+	justification_sender: Option<&GrandpaJustificationSender<Block>>,
 	hash: Block::Hash,
 	number: NumberFor<Block>,
 	justification_or_commit: JustificationOrCommit<Block>,
@@ -1144,7 +1148,7 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 		// justifications for transition blocks which will be requested by
 		// syncing clients.
 		let justification = match justification_or_commit {
-			JustificationOrCommit::Justification(justification) => Some(justification.encode()),
+			JustificationOrCommit::Justification(justification) => Some(justification),
 			JustificationOrCommit::Commit((round_number, commit)) => {
 				let mut justification_required =
 					// justification is always required when block that enacts new authorities
@@ -1171,7 +1175,7 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 						commit,
 					)?;
 
-					Some(justification.encode())
+					Some(justification)
 				} else {
 					None
 				}
@@ -1182,7 +1186,8 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 
 		// ideally some handle to a synchronization oracle would be used
 		// to avoid unconditionally notifying.
-		client.apply_finality(import_op, BlockId::Hash(hash), justification, true).map_err(|e| {
+		let encoded_justification = justification.as_ref().map(Encode::encode);
+		client.apply_finality(import_op, BlockId::Hash(hash), encoded_justification, true).map_err(|e| {
 			warn!(target: "afg", "Error applying finality to block {:?}: {:?}", (hash, number), e);
 			e
 		})?;
@@ -1236,12 +1241,17 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 			}
 		}
 
-		Ok(new_authorities.map(VoterCommand::ChangeAuthorities))
+		Ok((new_authorities.map(VoterCommand::ChangeAuthorities), justification))
 	});
 
+	// subscribers are only notified once the finality has been applied.
+	if let (Ok((_, Some(justification))), Some(sender)) = (&update_res, justification_sender) {
+		sender.notify(justification);
+	}
+
 	match update_res {
-		Ok(Some(command)) => Err(CommandOrError::VoterCommand(command)),
-		Ok(None) => Ok(()),
+		Ok((Some(command), _)) => Err(CommandOrError::VoterCommand(command)),
+		Ok((None, _)) => Ok(()),
 		Err(e) => {
 			*authority_set = old_authority_set;
 
@@ -49,7 +49,7 @@ use parity_scale_codec::{Encode, Decode};
 use finality_grandpa::BlockNumberOps;
 use sp_runtime::{
 	Justification, generic::BlockId,
-	traits::{NumberFor, Block as BlockT, Header as HeaderT, One},
+	traits::{NumberFor, Block as BlockT, Header as HeaderT, One, Zero},
 };
 use sp_core::storage::StorageKey;
 use sc_telemetry::{telemetry, CONSENSUS_INFO};
@@ -171,6 +171,53 @@ impl<B, Block: BlockT> FinalityProofProvider<B, Block>
 	}
 }
 
+impl<B, Block> FinalityProofProvider<B, Block>
+	where
+		Block: BlockT,
+		NumberFor<Block>: BlockNumberOps,
+		B: Backend<Block> + Send + Sync + 'static,
+{
+	/// Prove finality of the given canonical block, regardless of the authority set
+	/// that finalized it.
+	///
+	/// The proof starts with the earliest justification that finalizes the block and
+	/// is encoded in the same format used for network requests. Returns `None` if the
+	/// block isn't finalized yet or if it's the genesis block.
+	///
+	/// Note that the authority sets are read from the state of the parent of the block and of
+	/// the justified blocks, so proving finality of blocks whose state was pruned fails. Only
+	/// archive nodes can prove finality of arbitrary old blocks.
+	pub fn prove_block_finality(&self, block: Block::Hash) -> Result<Option<Vec<u8>>, ClientError> {
+		let blockchain = self.backend.blockchain();
+		let number = blockchain.expect_block_number_from_id(&BlockId::Hash(block))?;
+		if number.is_zero() {
+			return Ok(None);
+		}
+
+		if blockchain.hash(number)? != Some(block) {
+			return Err(ClientError::Backend(
+				format!("Cannot generate finality proof for non-canonical block: {}", block),
+			));
+		}
+
+		let parent_number = number - One::one();
+		let parent = blockchain.expect_block_hash_from_id(&BlockId::Number(parent_number))?;
+		if !self.backend.have_state_at(&parent, parent_number) {
+			return Err(ClientError::Backend(
+				format!("Cannot generate finality proof for block {}: the state of its parent was pruned", block),
+			));
+		}
+
+		prove_finality::<_, _, GrandpaJustification<Block>>(
+			&*blockchain,
+			&*self.authority_provider,
+			None,
+			parent,
+			block,
+		)
+	}
+}
+
 impl<B, Block> sc_network::config::FinalityProofProvider<Block> for FinalityProofProvider<B, Block>
 	where
 		Block: BlockT,
@@ -191,7 +238,7 @@ impl<B, Block> sc_network::config::FinalityProofProvider<Block> for FinalityProo
 			FinalityProofRequest::Original(request) => prove_finality::<_, _, GrandpaJustification<Block>>(
 				&*self.backend.blockchain(),
 				&*self.authority_provider,
-				request.authorities_set_id,
+				Some(request.authorities_set_id),
 				request.last_finalized,
 				for_block,
 			),
@@ -268,11 +315,14 @@ pub(crate) fn make_finality_proof_request<H: Encode + Decode>(last_finalized: H,
 /// It is assumed that the caller already have a proof-of-finality for the block 'begin'.
 /// It is assumed that the caller already knows all blocks in the range (begin; end].
 ///
+/// If `authorities_set_id` is given, the proof is only built if its first justification
+/// was signed by that authority set.
+///
 /// Returns None if there are no finalized blocks unknown to the caller.
 pub(crate) fn prove_finality<Block: BlockT, B: BlockchainBackend<Block>, J>(
 	blockchain: &B,
 	authorities_provider: &dyn AuthoritySetForFinalityProver<Block>,
-	authorities_set_id: u64,
+	authorities_set_id: Option<u64>,
 	begin: Block::Hash,
 	end: Block::Hash,
 ) -> ::sp_blockchain::Result<Option<Vec<u8>>>
@@ -353,7 +403,7 @@ pub(crate) fn prove_finality<Block: BlockT, B: BlockchainBackend<Block>, J>(
 			let justifies_authority_set_change = proof_fragment.authorities_proof.is_some();
 			if justifies_end_block || justifies_authority_set_change {
 				// check if the proof is generated by the requested authority set
-				if finality_proof.is_empty() {
+				if let (true, Some(authorities_set_id)) = (finality_proof.is_empty(), authorities_set_id) {
 					let justification_check_result = J::decode_and_verify(
 						&proof_fragment.justification,
 						authorities_set_id,
@@ -586,6 +636,7 @@ pub(crate) mod tests {
 	use sc_client_api::NewBlockState;
 	use sc_client_api::in_mem::Blockchain as InMemoryBlockchain;
 	use super::*;
+	use sp_blockchain::HeaderBackend;
 	use sp_core::crypto::Public;
 
 	pub(crate) type FinalityProof = super::FinalityProof<Header>;
@@ -683,7 +734,7 @@ pub(crate) mod tests {
 				|_| unreachable!("should return before calling GetAuthorities"),
 				|_| unreachable!("should return before calling ProveAuthorities"),
 			),
-			0,
+			Some(0),
 			header(2).hash(),
 			header(2).hash(),
 		).unwrap_err();
@@ -703,7 +754,7 @@ pub(crate) mod tests {
 				|_| unreachable!("should return before calling GetAuthorities"),
 				|_| unreachable!("should return before calling ProveAuthorities"),
 			),
-			0,
+			Some(0),
 			header(3).hash(),
 			header(4).hash(),
 		).unwrap();
@@ -729,7 +780,7 @@ pub(crate) mod tests {
 				|_| unreachable!("should return before calling GetAuthorities"),
 				|_| unreachable!("should return before calling ProveAuthorities"),
 			),
-			0,
+			Some(0),
 			side_header(4).hash(),
 			second_side_header(5).hash(),
 		).unwrap_err();
@@ -748,7 +799,7 @@ pub(crate) mod tests {
 				|_| Ok(vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)]),
 				|_| unreachable!("authorities didn't change => ProveAuthorities won't be called"),
 			),
-			0,
+			Some(0),
 			header(3).hash(),
 			header(4).hash(),
 		).unwrap();
@@ -772,7 +823,7 @@ pub(crate) mod tests {
 				|_| Ok(authorities.clone()),
 				|_| unreachable!("should return before calling ProveAuthorities"),
 			),
-			0,
+			Some(0),
 			header(3).hash(),
 			header(5).hash(),
 		).unwrap().unwrap()[..]).unwrap();
@@ -798,7 +849,7 @@ pub(crate) mod tests {
 				|_| Ok(vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)]),
 				|_| unreachable!("should return before calling ProveAuthorities"),
 			),
-			0,
+			Some(0),
 			header(3).hash(),
 			header(5).hash(),
 		).unwrap().unwrap()[..]).unwrap();
@@ -842,7 +893,7 @@ pub(crate) mod tests {
 					_ => unreachable!("no other authorities should be proved: {:?}", block_id),
 				},
 			),
-			0,
+			Some(0),
 			header(3).hash(),
 			header(6).hash(),
 		).unwrap().unwrap()[..]).unwrap();
@@ -1019,10 +1070,126 @@ pub(crate) mod tests {
 				|_| Ok(vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)]),
 				|_| unreachable!("should return before calling ProveAuthorities"),
 			),
-			0,
+			Some(0),
 			header(3).hash(),
 			header(4).hash(),
 		).unwrap();
 		assert!(proof_of_4.is_none());
 	}
-}
+
+	/// Build a client on a database backend keeping the state of `keep_blocks` finalized blocks,
+	/// whose genesis contains the given GRANDPA authorities, along with a finality proof provider.
+	fn client_with_finality_proof_provider(
+		keep_blocks: u32,
+		authorities: &AuthorityList,
+	) -> (
+		Arc<substrate_test_runtime_client::TestClient>,
+		FinalityProofProvider<substrate_test_runtime_client::Backend, Block>,
+	) {
+		use substrate_test_runtime_client::{TestClientBuilder, TestClientBuilderExt};
+
+		let (client, backend) = TestClientBuilder::with_pruning_window(keep_blocks)
+			.add_extra_storage(GRANDPA_AUTHORITIES_KEY.to_vec(), VersionedAuthorityList::from(authorities).encode())
+			.build_with_backend();
+		let client = Arc::new(client);
+		let provider = FinalityProofProvider::new(
+			backend,
+			client.clone() as Arc<dyn StorageAndProofProvider<Block, substrate_test_runtime_client::Backend>>,
+		);
+
+		(client, provider)
+	}
+
+	/// Import `n` blocks, finalizing the blocks whose number is in `justified` with the
+	/// justification `vec![number]`.
+	fn import_blocks(client: &mut Arc<substrate_test_runtime_client::TestClient>, n: u64, justified: &[u64]) {
+		use sc_block_builder::BlockBuilderProvider;
+		use sp_consensus::BlockOrigin;
+		use substrate_test_runtime_client::ClientBlockImportExt;
+
+		for _ in 0..n {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			let number = *block.header.number();
+			if justified.contains(&number) {
+				client.import_justified(BlockOrigin::Own, block, vec![number as u8]).unwrap();
+			} else {
+				client.import(BlockOrigin::Own, block).unwrap();
+			}
+		}
+	}
+
+	#[test]
+	fn prove_block_finality_works_with_database_backend() {
+		let authorities = vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)];
+		let (mut client, provider) = client_with_finality_proof_provider(256, &authorities);
+		import_blocks(&mut client, 4, &[3]);
+		let hash = |number: u64| client.hash(number).unwrap().unwrap();
+		let header = |number: u64| client.header(&BlockId::Number(number)).unwrap().unwrap();
+
+		// the genesis block and blocks that aren't finalized can't be proven
+		assert_eq!(provider.prove_block_finality(hash(0)).unwrap(), None);
+		assert_eq!(provider.prove_block_finality(hash(4)).unwrap(), None);
+
+		// blocks are proven by the first justification finalizing them, along with the headers
+		// from the block to the justified one
+		let proof_of_1 = provider.prove_block_finality(hash(1)).unwrap().unwrap();
+		assert_eq!(FinalityProof::decode(&mut &proof_of_1[..]).unwrap(), vec![FinalityProofFragment {
+			block: hash(3),
+			justification: vec![3],
+			unknown_headers: vec![header(2), header(3)],
+			authorities_proof: None,
+		}]);
+
+		let proof_of_3 = provider.prove_block_finality(hash(3)).unwrap().unwrap();
+		assert_eq!(FinalityProof::decode(&mut &proof_of_3[..]).unwrap(), vec![FinalityProofFragment {
+			block: hash(3),
+			justification: vec![3],
+			unknown_headers: Vec::new(),
+			authorities_proof: None,
+		}]);
+	}
+
+	#[test]
+	fn prove_finality_without_authority_set_id_works_with_database_backend() {
+		let authorities = vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)];
+		let (mut client, provider) = client_with_finality_proof_provider(256, &authorities);
+		import_blocks(&mut client, 4, &[2, 4]);
+		let hash = |number: u64| client.hash(number).unwrap().unwrap();
+		let header = |number: u64| client.header(&BlockId::Number(number)).unwrap().unwrap();
+
+		// without the set id, the justifications aren't checked and the proof for the end
+		// block includes the justification of the last finalized block
+		let authorities_provider =
+			client.clone() as Arc<dyn StorageAndProofProvider<Block, substrate_test_runtime_client::Backend>>;
+		let proof = prove_finality::<_, _, TestJustification>(
+			&*provider.backend.blockchain(),
+			&authorities_provider,
+			None,
+			hash(1),
+			hash(3),
+		).unwrap().unwrap();
+		assert_eq!(FinalityProof::decode(&mut &proof[..]).unwrap(), vec![FinalityProofFragment {
+			block: hash(4),
+			justification: vec![4],
+			unknown_headers: vec![header(4)],
+			authorities_proof: None,
+		}]);
+	}
+
+	#[test]
+	fn prove_block_finality_fails_if_state_of_parent_is_pruned() {
+		let authorities = vec![(AuthorityId::from_slice(&[1u8; 32]), 1u64)];
+		let (mut client, provider) = client_with_finality_proof_provider(4, &authorities);
+		import_blocks(&mut client, 8, &[2, 8]);
+		let hash = |number: u64| client.hash(number).unwrap().unwrap();
+
+		// the state of the parent of recent blocks is still available
+		assert!(provider.prove_block_finality(hash(8)).unwrap().is_some());
+
+		// while it was pruned for older blocks
+		match provider.prove_block_finality(hash(2)) {
+			Err(ClientError::Backend(message)) => assert!(message.contains("pruned")),
+			result => panic!("Unexpected result: {:?}", result),
+		}
+	}
+}
\ No newline at end of file
@@ -118,6 +118,7 @@ mod finality_proof;
 mod import;
 mod justification;
 mod light_import;
+mod notification;
 mod observer;
 mod until_imported;
 mod voting_rule;
@@ -127,6 +128,7 @@ pub use finality_proof::{FinalityProofProvider, StorageAndProofProvider};
 pub use import::GrandpaBlockImport;
 pub use justification::GrandpaJustification;
 pub use light_import::light_block_import;
+pub use notification::{GrandpaJustificationSender, GrandpaJustificationStream};
 pub use voting_rule::{
 	BeforeBestBlockBy, ThreeQuartersOfTheUnfinalizedChain, VotingRule, VotingRulesBuilder
 };
@@ -447,6 +449,8 @@ pub struct LinkHalf<Block: BlockT, C, SC> {
 	select_chain: SC,
 	persistent_data: PersistentData<Block>,
 	voter_commands_rx: TracingUnboundedReceiver<VoterCommand<Block::Hash, NumberFor<Block>>>,
+	justification_sender: GrandpaJustificationSender<Block>,
+	justification_stream: GrandpaJustificationStream<Block>,
 }
 
 impl<Block: BlockT, C, SC> LinkHalf<Block, C, SC> {
@@ -454,6 +458,11 @@ impl<Block: BlockT, C, SC> LinkHalf<Block, C, SC> {
 	pub fn shared_authority_set(&self) -> &SharedAuthoritySet<Block::Hash, NumberFor<Block>> {
 		&self.persistent_data.authority_set
 	}
+
+	/// Get the stream of justifications of finalized blocks.
+	pub fn justification_stream(&self) -> GrandpaJustificationStream<Block> {
+		self.justification_stream.clone()
+	}
 }
 
 /// Provider for the Grandpa authority set configured on the genesis block.
@@ -552,6 +561,8 @@ where
 
 	let (voter_commands_tx, voter_commands_rx) = tracing_unbounded("mpsc_grandpa_voter_command");
 
+	let (justification_sender, justification_stream) = GrandpaJustificationSender::channel();
+
 	// create pending change objects with 0 delay and enacted on finality
 	// (i.e. standard changes) for each authority set hard fork.
 	let authority_set_hard_forks = authority_set_hard_forks
@@ -578,12 +589,15 @@ where
 			voter_commands_tx,
 			persistent_data.consensus_changes.clone(),
 			authority_set_hard_forks,
+			justification_sender.clone(),
 		),
 		LinkHalf {
 			client,
 			select_chain,
 			persistent_data,
 			voter_commands_rx,
+			justification_sender,
+			justification_stream,
 		},
 	))
 }
@@ -718,6 +732,8 @@ pub fn run_grandpa_voter<Block: BlockT, BE: 'static, C, N, SC, VR>(
 		select_chain,
 		persistent_data,
 		voter_commands_rx,
+		justification_sender,
+		justification_stream: _,
 	} = link;
 
 	let network = NetworkBridge::new(
@@ -766,6 +782,7 @@ pub fn run_grandpa_voter<Block: BlockT, BE: 'static, C, N, SC, VR>(
 		voter_commands_rx,
 		prometheus_registry,
 		shared_voter_state,
+		justification_sender,
 	);
 
 	let voter_work = voter_work
@@ -826,6 +843,7 @@ where
 		voter_commands_rx: TracingUnboundedReceiver<VoterCommand<Block::Hash, NumberFor<Block>>>,
 		prometheus_registry: Option<prometheus_endpoint::Registry>,
 		shared_voter_state: SharedVoterState,
+		justification_sender: GrandpaJustificationSender<Block>,
 	) -> Self {
 		let metrics = match prometheus_registry.as_ref().map(Metrics::register) {
 			Some(Ok(metrics)) => Some(metrics),
@@ -849,6 +867,7 @@ where
 			consensus_changes: persistent_data.consensus_changes.clone(),
 			voter_set_state: persistent_data.set_state,
 			metrics: metrics.as_ref().map(|m| m.environment.clone()),
+			justification_sender: Some(justification_sender),
 			_phantom: PhantomData,
 		});
 
@@ -987,6 +1006,7 @@ where
 					network: self.env.network.clone(),
 					voting_rule: self.env.voting_rule.clone(),
 					metrics: self.env.metrics.clone(),
+					justification_sender: self.env.justification_sender.clone(),
 					_phantom: PhantomData,
 				});
 
@@ -0,0 +1,71 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Notifications about GRANDPA justifications.
+
+use std::sync::Arc;
+use parking_lot::Mutex;
+use sp_runtime::traits::Block as BlockT;
+use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
+
+use crate::justification::GrandpaJustification;
+
+type JustificationSenders<Block> = Arc<Mutex<Vec<TracingUnboundedSender<GrandpaJustification<Block>>>>>;
+
+/// The sending half of the justification notifications, used by block import
+/// and the voter to notify about every justification of a finalized block.
+#[derive(Clone)]
+pub struct GrandpaJustificationSender<Block: BlockT> {
+	subscribers: JustificationSenders<Block>,
+}
+
+impl<Block: BlockT> GrandpaJustificationSender<Block> {
+	/// Create a new sender and the stream its notifications can be
+	/// subscribed to.
+	pub fn channel() -> (Self, GrandpaJustificationStream<Block>) {
+		let subscribers = Arc::new(Mutex::new(Vec::new()));
+
+		(
+			GrandpaJustificationSender { subscribers: subscribers.clone() },
+			GrandpaJustificationStream { subscribers },
+		)
+	}
+
+	/// Send the justification to all subscribers, dropping the ones that are
+	/// no longer listening.
+	pub fn notify(&self, justification: &GrandpaJustification<Block>) {
+		self.subscribers.lock()
+			.retain(|subscriber| subscriber.unbounded_send(justification.clone()).is_ok());
+	}
+}
+
+/// The receiving half of the justification notifications, allows subscribing
+/// to the justifications of newly finalized blocks.
+#[derive(Clone)]
+pub struct GrandpaJustificationStream<Block: BlockT> {
+	subscribers: JustificationSenders<Block>,
+}
+
+impl<Block: BlockT> GrandpaJustificationStream<Block> {
+	/// Subscribe to the justifications of blocks finalized from now on.
+	pub fn subscribe(&self) -> TracingUnboundedReceiver<GrandpaJustification<Block>> {
+		let (sender, receiver) = tracing_unbounded("mpsc_grandpa_justification_notification_stream");
+		self.subscribers.lock().push(sender);
+		receiver
+	}
+}
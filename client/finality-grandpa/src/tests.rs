@@ -38,7 +38,7 @@ use sp_consensus::{
 	import_queue::{BoxJustificationImport, BoxFinalityProofImport},
 };
 use std::{collections::{HashMap, HashSet}, pin::Pin};
-use parity_scale_codec::Decode;
+use parity_scale_codec::{Decode, Encode};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT, HashFor};
 use sp_runtime::generic::{BlockId, DigestItem};
 use sp_core::{H256, crypto::Public};
@@ -217,7 +217,7 @@ sp_api::mock_impl_runtime_apis! {
 			self.inner.genesis_authorities.clone()
 		}
 
-		fn submit_report_equivocation_extrinsic(
+		fn submit_report_equivocation_unsigned_extrinsic(
 			_equivocation_proof: EquivocationProof<Hash, BlockNumber>,
 			_key_owner_proof: OpaqueKeyOwnershipProof,
 		) -> Option<()> {
@@ -715,6 +715,36 @@ fn justification_is_generated_periodically() {
 	}
 }
 
+#[test]
+fn justifications_are_notified_to_subscribers() {
+	let mut runtime = Runtime::new().unwrap();
+	let peers = &[Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+	let voters = make_ids(peers);
+
+	let mut net = GrandpaTestNet::new(TestApi::new(voters), 3);
+	net.peer(0).push_blocks(32, false);
+	net.block_until_sync();
+
+	let mut justifications = net.peer(0).data.lock()
+		.as_ref()
+		.expect("link initialized at startup; qed")
+		.justification_stream()
+		.subscribe();
+
+	let net = Arc::new(Mutex::new(net));
+	run_to_completion(&mut runtime, 32, net.clone(), peers);
+
+	// the justification generated for block #32 is pushed to the subscriber
+	let justification = justifications.try_next()
+		.expect("a justification was sent")
+		.expect("the stream is still open");
+	assert_eq!(justification.commit.target_number, 32);
+	assert_eq!(
+		Some(justification.encode()),
+		net.lock().peer(0).client().justification(&BlockId::Number(32)).unwrap(),
+	);
+}
+
 #[test]
 fn consensus_changes_works() {
 	let mut changes = ConsensusChanges::<H256, u64>::empty();
@@ -1570,6 +1600,7 @@ fn grandpa_environment_respects_voting_rules() {
 			network,
 			voting_rule,
 			metrics: None,
+			justification_sender: None,
 			_phantom: PhantomData,
 		}
 	};
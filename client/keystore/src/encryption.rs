@@ -0,0 +1,271 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Encrypted key file format.
+//!
+//! Secrets are encrypted with ChaCha20-Poly1305. The key-encryption key is derived from the
+//! keystore password with Argon2id. The derivation parameters are stored next to the
+//! ciphertext, so they can be changed without breaking existing files.
+
+use std::collections::HashMap;
+use chacha20poly1305::{ChaCha20Poly1305, aead::{Aead, NewAead, generic_array::GenericArray}};
+use parking_lot::Mutex;
+use rand::{RngCore, rngs::OsRng};
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::Protected;
+use crate::{Error, Result};
+
+/// Version of the encrypted file format.
+const VERSION: u8 = 1;
+/// Name of the only supported cipher.
+const CIPHER: &str = "chacha20poly1305";
+/// Name of the only supported key derivation function.
+const KDF: &str = "argon2id";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// Argon2 memory cost in KiB.
+#[cfg(not(test))]
+const MEM_COST: u32 = 64 * 1024;
+#[cfg(test)]
+const MEM_COST: u32 = 64;
+
+/// Argon2 number of passes.
+#[cfg(not(test))]
+const TIME_COST: u32 = 3;
+#[cfg(test)]
+const TIME_COST: u32 = 1;
+
+/// Parameters of the key derivation.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KdfParams {
+	/// Name of the key derivation function.
+	pub algorithm: String,
+	/// Hex encoded salt.
+	pub salt: String,
+	/// Memory cost in KiB.
+	pub mem_cost: u32,
+	/// Number of passes.
+	pub time_cost: u32,
+	/// Degree of parallelism.
+	pub lanes: u32,
+}
+
+/// An encrypted secret.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Envelope {
+	/// Version of the format.
+	pub version: u8,
+	/// How the key-encryption key is derived from the password.
+	pub kdf: KdfParams,
+	/// Name of the cipher.
+	pub cipher: String,
+	/// Hex encoded nonce.
+	pub nonce: String,
+	/// Hex encoded ciphertext, including the authentication tag.
+	pub ciphertext: String,
+}
+
+impl Envelope {
+	/// Returns `true` if the given JSON value looks like an encrypted secret.
+	pub fn is_envelope(value: &serde_json::Value) -> bool {
+		value.get("cipher").is_some() && value.get("ciphertext").is_some()
+	}
+}
+
+/// The secret stored in a key file.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct KeySecret {
+	/// The secret URI of the key.
+	pub suri: String,
+	/// The password the key pair is derived with.
+	///
+	/// This is the keystore password at the time the key was written, so the key can still be
+	/// derived after the keystore password was changed.
+	pub password: Option<String>,
+}
+
+/// A key in an exported backup.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackupKey {
+	/// The key type as four character string.
+	pub key_type: String,
+	/// The hex encoded raw public key.
+	pub public: String,
+	/// The secret URI of the key.
+	pub suri: String,
+	/// The password the key pair is derived with.
+	pub password: Option<String>,
+}
+
+/// Encrypts and decrypts secrets with a password.
+pub(crate) struct Cipher {
+	password: Protected<String>,
+	/// Salt used for newly encrypted secrets.
+	salt: [u8; SALT_LEN],
+	/// Key-encryption keys that were already derived.
+	keys: Mutex<HashMap<KdfParams, Protected<[u8; KEY_LEN]>>>,
+}
+
+impl Cipher {
+	/// Create a new cipher for the given password.
+	pub fn new(password: Protected<String>) -> Self {
+		let mut salt = [0u8; SALT_LEN];
+		OsRng.fill_bytes(&mut salt);
+
+		Self { password, salt, keys: Mutex::new(HashMap::new()) }
+	}
+
+	/// Returns the key-encryption key for the given parameters.
+	///
+	/// Derivation is expensive, every key is derived only once.
+	fn key(&self, params: &KdfParams) -> Result<Protected<[u8; KEY_LEN]>> {
+		if let Some(key) = self.keys.lock().get(params) {
+			return Ok(key.clone())
+		}
+
+		if params.algorithm != KDF {
+			return Err(Error::InvalidKeyFile(format!("Unsupported KDF: {}", params.algorithm)))
+		}
+		let salt = decode_hex(&params.salt)?;
+		let config = argon2::Config {
+			variant: argon2::Variant::Argon2id,
+			version: argon2::Version::Version13,
+			mem_cost: params.mem_cost,
+			time_cost: params.time_cost,
+			lanes: params.lanes,
+			thread_mode: argon2::ThreadMode::Sequential,
+			secret: &[],
+			ad: &[],
+			hash_length: KEY_LEN as u32,
+		};
+		let hash = Protected::from(
+			argon2::hash_raw(self.password.as_bytes(), &salt, &config)
+				.map_err(|e| Error::InvalidKeyFile(e.to_string()))?
+		);
+
+		let mut key = [0u8; KEY_LEN];
+		key.copy_from_slice(&hash[..]);
+		let key = Protected::from(key);
+		self.keys.lock().insert(params.clone(), key.clone());
+
+		Ok(key)
+	}
+
+	/// Encrypt the given plaintext.
+	pub fn encrypt(&self, plaintext: &[u8]) -> Result<Envelope> {
+		let kdf = KdfParams {
+			algorithm: KDF.into(),
+			salt: encode_hex(&self.salt),
+			mem_cost: MEM_COST,
+			time_cost: TIME_COST,
+			lanes: 1,
+		};
+		let key = self.key(&kdf)?;
+
+		let mut nonce = [0u8; NONCE_LEN];
+		OsRng.fill_bytes(&mut nonce);
+
+		let ciphertext = ChaCha20Poly1305::new(*GenericArray::from_slice(&key[..]))
+			.encrypt(GenericArray::from_slice(&nonce), plaintext)
+			.map_err(|_| Error::Unavailable)?;
+
+		Ok(Envelope {
+			version: VERSION,
+			kdf,
+			cipher: CIPHER.into(),
+			nonce: encode_hex(&nonce),
+			ciphertext: encode_hex(&ciphertext),
+		})
+	}
+
+	/// Decrypt the given envelope.
+	///
+	/// Returns `Error::InvalidPassword` if the envelope was encrypted with another password.
+	pub fn decrypt(&self, envelope: &Envelope) -> Result<Protected<Vec<u8>>> {
+		if envelope.version != VERSION {
+			return Err(Error::InvalidKeyFile(format!("Unsupported version: {}", envelope.version)))
+		}
+		if envelope.cipher != CIPHER {
+			return Err(Error::InvalidKeyFile(format!("Unsupported cipher: {}", envelope.cipher)))
+		}
+
+		let nonce = decode_hex(&envelope.nonce)?;
+		if nonce.len() != NONCE_LEN {
+			return Err(Error::InvalidKeyFile("Invalid nonce".into()))
+		}
+		let ciphertext = decode_hex(&envelope.ciphertext)?;
+		let key = self.key(&envelope.kdf)?;
+
+		ChaCha20Poly1305::new(*GenericArray::from_slice(&key[..]))
+			.decrypt(GenericArray::from_slice(&nonce), &ciphertext[..])
+			.map(Protected::from)
+			.map_err(|_| Error::InvalidPassword)
+	}
+}
+
+fn encode_hex(data: &[u8]) -> String {
+	format!("0x{}", hex::encode(data))
+}
+
+fn decode_hex(data: &str) -> Result<Vec<u8>> {
+	let data = if data.starts_with("0x") { &data[2..] } else { data };
+	hex::decode(data).map_err(|_| Error::InvalidKeyFile("Invalid hex encoding".into()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn encrypt_decrypt_roundtrip() {
+		let cipher = Cipher::new(String::from("password").into());
+		let envelope = cipher.encrypt(b"//Alice").unwrap();
+
+		assert_eq!(&cipher.decrypt(&envelope).unwrap()[..], b"//Alice");
+
+		// A fresh cipher with the same password needs to derive the key itself.
+		let cipher = Cipher::new(String::from("password").into());
+		assert_eq!(&cipher.decrypt(&envelope).unwrap()[..], b"//Alice");
+	}
+
+	#[test]
+	fn wrong_password_is_detected() {
+		let envelope = Cipher::new(String::from("password").into()).encrypt(b"//Alice").unwrap();
+		let cipher = Cipher::new(String::from("wrong").into());
+
+		assert!(matches!(cipher.decrypt(&envelope), Err(Error::InvalidPassword)));
+	}
+
+	#[test]
+	fn tampered_ciphertext_is_detected() {
+		let cipher = Cipher::new(String::from("password").into());
+		let mut envelope = cipher.encrypt(b"//Alice").unwrap();
+
+		let mut ciphertext = decode_hex(&envelope.ciphertext).unwrap();
+		ciphertext[0] ^= 1;
+		envelope.ciphertext = encode_hex(&ciphertext);
+
+		assert!(cipher.decrypt(&envelope).is_err());
+	}
+}
@@ -17,7 +17,10 @@
 //! Keystore (and session key management) for ed25519 based chains like Polkadot.
 
 #![warn(missing_docs)]
-use std::{collections::{HashMap, HashSet}, path::PathBuf, fs::{self, File}, io::{self, Write}, sync::Arc};
+use std::{
+	collections::{HashMap, HashSet}, convert::TryFrom, path::{Path, PathBuf}, fs::{self, File},
+	io::{self, Write}, sync::Arc, time::Duration,
+};
 use sp_core::{
 	crypto::{IsWrappedBy, CryptoTypePublicPair, KeyTypeId, Pair as PairT, Protected, Public},
 	traits::{BareCryptoStore, Error as TraitError},
@@ -27,6 +30,14 @@ use sp_core::{
 };
 use sp_application_crypto::{AppKey, AppPublic, AppPair, ed25519, sr25519, ecdsa};
 use parking_lot::RwLock;
+use encryption::{BackupKey, Cipher, Envelope, KeySecret};
+
+mod encryption;
+pub mod remote;
+
+/// Directory in the store the key files of an import are written to before they are moved into
+/// the store.
+const IMPORT_STAGING_DIR: &str = ".import";
 
 /// Keystore pointer
 pub type KeyStorePtr = Arc<RwLock<Store>>;
@@ -41,6 +52,13 @@ pub enum Error {
 	/// Invalid password.
 	#[display(fmt="Invalid password")]
 	InvalidPassword,
+	/// A password is required to access encrypted keys.
+	#[display(fmt="A password is required to access encrypted keys")]
+	PasswordRequired,
+	/// Invalid key file
+	#[display(fmt="Invalid key file: {}", "_0")]
+	#[from(ignore)]
+	InvalidKeyFile(String),
 	/// Invalid BIP39 phrase
 	#[display(fmt="Invalid recovery phrase (BIP39) data")]
 	InvalidPhrase,
@@ -56,6 +74,10 @@ pub enum Error {
 	/// Keystore unavailable
 	#[display(fmt="Keystore unavailable")]
 	Unavailable,
+	/// Remote signer error
+	#[display(fmt="Remote signer error: {}", "_0")]
+	#[from(ignore)]
+	Remote(String),
 }
 
 /// Keystore Result
@@ -66,12 +88,15 @@ impl From<Error> for TraitError {
 		match error {
 			Error::KeyNotSupported(id) => TraitError::KeyNotSupported(id),
 			Error::PairNotFound(e) => TraitError::PairNotFound(e),
-			Error::InvalidSeed | Error::InvalidPhrase | Error::InvalidPassword => {
+			Error::InvalidSeed | Error::InvalidPhrase | Error::InvalidPassword
+				| Error::PasswordRequired => {
 				TraitError::ValidationError(error.to_string())
 			},
+			Error::InvalidKeyFile(e) => TraitError::Other(e),
 			Error::Unavailable => TraitError::Unavailable,
 			Error::Io(e) => TraitError::Other(e.to_string()),
 			Error::Json(e) => TraitError::Other(e.to_string()),
+			Error::Remote(e) => TraitError::Other(e),
 		}
 	}
 }
@@ -91,11 +116,24 @@ impl std::error::Error for Error {
 /// Stores key pairs in a file system store + short lived key pairs in memory.
 ///
 /// Every pair that is being generated by a `seed`, will be placed in memory.
+///
+/// Key files are either stored as plaintext JSON, or encrypted with a key derived from the
+/// password. Both formats can be read, [`Store::open_encrypted`] selects the encrypted format
+/// for writing.
+///
+/// Alternatively the persistent keys can be held by a [`remote::RemoteSigner`], in which case
+/// the store only lists keys and signs through the signer. The `BareCryptoStore` methods then
+/// block until the signer answered, see [`Store::remote_signer`] for async access.
 pub struct Store {
 	path: Option<PathBuf>,
 	/// Map over `(KeyTypeId, Raw public key)` -> `Key phrase/seed`
 	additional: HashMap<(KeyTypeId, Vec<u8>), String>,
 	password: Option<Protected<String>>,
+	/// Cipher for encrypted key files, available if a password is set.
+	cipher: Option<Cipher>,
+	/// Whether key files are written in the encrypted format.
+	encrypt: bool,
+	remote: Option<remote::RemoteSigner>,
 }
 
 impl Store {
@@ -106,7 +144,49 @@ impl Store {
 		let path = path.into();
 		fs::create_dir_all(&path)?;
 
-		let instance = Self { path: Some(path), additional: HashMap::new(), password };
+		let instance = Self {
+			path: Some(path),
+			additional: HashMap::new(),
+			cipher: password.clone().map(Cipher::new),
+			password,
+			encrypt: false,
+			remote: None,
+		};
+		Ok(Arc::new(RwLock::new(instance)))
+	}
+
+	/// Open the store at the given path and write new keys in the encrypted format.
+	///
+	/// Keys that are still stored in plaintext can be read as well.
+	pub fn open_encrypted<T: Into<PathBuf>>(path: T, password: Protected<String>) -> Result<KeyStorePtr> {
+		let path = path.into();
+		fs::create_dir_all(&path)?;
+
+		let instance = Self {
+			path: Some(path),
+			additional: HashMap::new(),
+			cipher: Some(Cipher::new(password.clone())),
+			password: Some(password),
+			encrypt: true,
+			remote: None,
+		};
+		Ok(Arc::new(RwLock::new(instance)))
+	}
+
+	/// Open a store that uses the remote signer at the given URI.
+	///
+	/// Keys can not be generated or inserted into such a store, only ephemeral keys are kept
+	/// in memory. Requests to the signer fail after `timeout`, see
+	/// [`remote::DEFAULT_REQUEST_TIMEOUT`] for a sensible default.
+	pub fn open_remote(uri: &str, timeout: Duration) -> Result<KeyStorePtr> {
+		let instance = Self {
+			path: None,
+			additional: HashMap::new(),
+			password: None,
+			cipher: None,
+			encrypt: false,
+			remote: Some(remote::RemoteSigner::new(uri, timeout)?),
+		};
 		Ok(Arc::new(RwLock::new(instance)))
 	}
 
@@ -115,7 +195,10 @@ impl Store {
 		Arc::new(RwLock::new(Self {
 			path: None,
 			additional: HashMap::new(),
-			password: None
+			password: None,
+			cipher: None,
+			encrypt: false,
+			remote: None,
 		}))
 	}
 
@@ -141,10 +224,12 @@ impl Store {
 	///
 	/// Places it into the file system store.
 	fn insert_unknown(&self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<()> {
+		if self.remote.is_some() {
+			return Err(Error::Unavailable)
+		}
+
 		if let Some(path) = self.key_file_path(public, key_type) {
-			let mut file = File::create(path).map_err(Error::Io)?;
-			serde_json::to_writer(&file, &suri).map_err(Error::Json)?;
-			file.flush().map_err(Error::Io)?;
+			self.write_key_file(&path, &KeySecret { suri: suri.into(), password: self.derivation_password() })?;
 		}
 		Ok(())
 	}
@@ -173,11 +258,13 @@ impl Store {
 	///
 	/// Places it into the file system store.
 	pub fn generate_by_type<Pair: PairT>(&self, key_type: KeyTypeId) -> Result<Pair> {
+		if self.remote.is_some() {
+			return Err(Error::Unavailable)
+		}
+
 		let (pair, phrase, _) = Pair::generate_with_phrase(self.password.as_ref().map(|p| &***p));
 		if let Some(path) = self.key_file_path(pair.public().as_slice(), key_type) {
-			let mut file = File::create(path)?;
-			serde_json::to_writer(&file, &phrase)?;
-			file.flush()?;
+			self.write_key_file(&path, &KeySecret { suri: phrase, password: self.derivation_password() })?;
 		}
 		Ok(pair)
 	}
@@ -209,16 +296,14 @@ impl Store {
 		self.insert_ephemeral_from_seed_by_type::<Pair::Generic>(seed, Pair::ID).map(Into::into)
 	}
 
-	/// Get the key phrase for a given public key and key type.
-	fn key_phrase_by_type(&self, public: &[u8], key_type: KeyTypeId) -> Result<String> {
+	/// Get the key phrase and derivation password for a given public key and key type.
+	fn key_secret_by_type(&self, public: &[u8], key_type: KeyTypeId) -> Result<KeySecret> {
 		if let Some(phrase) = self.get_additional_pair(public, key_type) {
-			return Ok(phrase.clone())
+			return Ok(KeySecret { suri: phrase.clone(), password: self.derivation_password() })
 		}
 
 		let path = self.key_file_path(public, key_type).ok_or_else(|| Error::Unavailable)?;
-		let file = File::open(path)?;
-
-		serde_json::from_reader(&file).map_err(Into::into)
+		self.read_key_file(&path).map(|(secret, _)| secret)
 	}
 
 	/// Get a key pair for the given public key and key type.
@@ -226,10 +311,10 @@ impl Store {
 		public: &Pair::Public,
 		key_type: KeyTypeId,
 	) -> Result<Pair> {
-		let phrase = self.key_phrase_by_type(public.as_slice(), key_type)?;
+		let secret = self.key_secret_by_type(public.as_slice(), key_type)?;
 		let pair = Pair::from_string(
-			&phrase,
-			self.password.as_ref().map(|p| &***p),
+			&secret.suri,
+			secret.password.as_ref().map(|p| p.as_str()),
 		).map_err(|_| Error::InvalidPhrase)?;
 
 		if &pair.public() == public {
@@ -268,12 +353,9 @@ impl Store {
 		Some(buf)
 	}
 
-	/// Returns a list of raw public keys filtered by `KeyTypeId`
-	fn raw_public_keys(&self, id: KeyTypeId) -> Result<Vec<Vec<u8>>> {
-		let mut public_keys: Vec<Vec<u8>> = self.additional.keys()
-			.into_iter()
-			.filter_map(|k| if k.0 == id { Some(k.1.clone()) } else { None })
-			.collect();
+	/// Returns the key type, raw public key and path of every key file in the store.
+	fn key_files(&self) -> Result<Vec<(KeyTypeId, Vec<u8>, PathBuf)>> {
+		let mut files = Vec::new();
 
 		if let Some(path) = &self.path {
 			for entry in fs::read_dir(&path)? {
@@ -284,11 +366,9 @@ impl Store {
 				if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
 					match hex::decode(name) {
 						Ok(ref hex) if hex.len() > 4 => {
-							if &hex[0..4] != &id.0 {
-								continue;
-							}
-							let public = hex[4..].to_vec();
-							public_keys.push(public);
+							let mut key_type = KeyTypeId::default();
+							key_type.0.copy_from_slice(&hex[0..4]);
+							files.push((key_type, hex[4..].to_vec(), path.clone()));
 						}
 						_ => continue,
 					}
@@ -296,23 +376,281 @@ impl Store {
 			}
 		}
 
+		Ok(files)
+	}
+
+	/// The password keys written by this store are derived with.
+	fn derivation_password(&self) -> Option<String> {
+		self.password.as_ref().map(|p| p.as_str().to_owned())
+	}
+
+	/// Read the secret of a key file.
+	///
+	/// Returns the secret and whether the file is encrypted.
+	fn read_key_file(&self, path: &Path) -> Result<(KeySecret, bool)> {
+		let file = File::open(path)?;
+		let value: serde_json::Value = serde_json::from_reader(&file)?;
+
+		if Envelope::is_envelope(&value) {
+			let envelope: Envelope = serde_json::from_value(value)?;
+			let cipher = self.cipher.as_ref().ok_or(Error::PasswordRequired)?;
+			let plaintext = cipher.decrypt(&envelope)?;
+			Ok((serde_json::from_slice(&plaintext)?, true))
+		} else {
+			let suri = serde_json::from_value(value)?;
+			Ok((KeySecret { suri, password: self.derivation_password() }, false))
+		}
+	}
+
+	/// Write the secret of a key file in the format selected for this store.
+	fn write_key_file(&self, path: &Path, secret: &KeySecret) -> Result<()> {
+		let contents = self.key_file_contents(secret)?;
+
+		// Write to a temporary file first, so an existing key file is never left half written.
+		let tmp_path = path.with_extension("tmp");
+		let mut file = File::create(&tmp_path)?;
+		file.write_all(&contents)?;
+		file.flush()?;
+		fs::rename(tmp_path, path)?;
+
+		Ok(())
+	}
+
+	/// The contents of the key file holding the given secret, in the format selected for this
+	/// store.
+	fn key_file_contents(&self, secret: &KeySecret) -> Result<Vec<u8>> {
+		Ok(if self.encrypt {
+			let cipher = self.cipher.as_ref().ok_or(Error::PasswordRequired)?;
+			let plaintext = Protected::from(serde_json::to_vec(secret)?);
+			serde_json::to_vec(&cipher.encrypt(&plaintext)?)?
+		} else {
+			// Plaintext key files are always derived with the password of the store.
+			if secret.password != self.derivation_password() {
+				return Err(Error::InvalidPassword)
+			}
+			serde_json::to_vec(&secret.suri)?
+		})
+	}
+
+	/// Returns `true` if new keys are written in the encrypted format.
+	pub fn is_encrypted(&self) -> bool {
+		self.encrypt
+	}
+
+	/// Encrypt all key files that are still stored in plaintext.
+	///
+	/// The store has to be opened with the password the keys were inserted with. New keys are
+	/// written in the encrypted format afterwards.
+	///
+	/// Returns the number of converted files.
+	pub fn encrypt_key_files(&mut self) -> Result<usize> {
+		if self.cipher.is_none() {
+			return Err(Error::PasswordRequired)
+		}
+		self.encrypt = true;
+
+		let mut converted = 0;
+		for (_, _, path) in self.key_files()? {
+			let (secret, encrypted) = self.read_key_file(&path)?;
+			if !encrypted {
+				self.write_key_file(&path, &secret)?;
+				converted += 1;
+			}
+		}
+
+		Ok(converted)
+	}
+
+	/// Encrypt all key files with a new password.
+	///
+	/// Existing keys are still derived with the password they were inserted with, new keys are
+	/// derived with the new password and written in the encrypted format.
+	///
+	/// Returns the number of re-encrypted files.
+	pub fn change_password(&mut self, password: Protected<String>) -> Result<usize> {
+		// Read everything first, so a wrong password leaves the store untouched.
+		let secrets = self.key_files()?
+			.into_iter()
+			.map(|(_, _, path)| self.read_key_file(&path).map(|(secret, _)| (path, secret)))
+			.collect::<Result<Vec<_>>>()?;
+
+		self.cipher = Some(Cipher::new(password.clone()));
+		self.password = Some(password);
+		self.encrypt = true;
+
+		for (path, secret) in &secrets {
+			self.write_key_file(path, secret)?;
+		}
+
+		Ok(secrets.len())
+	}
+
+	/// Export the keys of the given types into a backup encrypted with `password`.
+	///
+	/// All keys are exported if `key_types` is empty. Keys that only live in memory are not
+	/// part of the backup.
+	pub fn export_keys(&self, key_types: &[KeyTypeId], password: Protected<String>) -> Result<String> {
+		let keys = self.key_files()?
+			.into_iter()
+			.filter(|(key_type, _, _)| key_types.is_empty() || key_types.contains(key_type))
+			.map(|(key_type, public, path)| {
+				let (secret, _) = self.read_key_file(&path)?;
+				Ok(BackupKey {
+					key_type: String::from_utf8_lossy(&key_type.0).into_owned(),
+					public: hex::encode(public),
+					suri: secret.suri,
+					password: secret.password,
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let plaintext = Protected::from(serde_json::to_vec(&keys)?);
+		let envelope = Cipher::new(password).encrypt(&plaintext)?;
+		serde_json::to_string_pretty(&envelope).map_err(Into::into)
+	}
+
+	/// Import the keys of a backup created by [`Store::export_keys`].
+	///
+	/// Nothing is imported unless the secret of every key derives its public key and every key
+	/// file could be written. The key files are written to a staging directory first and only
+	/// moved into the store once all of them are written.
+	///
+	/// Returns the key type and raw public key of every imported key.
+	pub fn import_keys(
+		&self,
+		backup: &str,
+		password: Protected<String>,
+	) -> Result<Vec<(KeyTypeId, Vec<u8>)>> {
+		let envelope: Envelope = serde_json::from_str(backup)?;
+		let plaintext = Cipher::new(password).decrypt(&envelope)?;
+		let keys: Vec<BackupKey> = serde_json::from_slice(&plaintext)?;
+
+		let keys = keys.into_iter()
+			.map(|key| {
+				let invalid = || Error::InvalidKeyFile("Invalid key in backup".into());
+				let key_type = KeyTypeId::try_from(key.key_type.as_str()).map_err(|_| invalid())?;
+				let public = hex::decode(&key.public).map_err(|_| invalid())?;
+				if !derives_public(&key.suri, key.password.as_ref().map(|p| p.as_str()), &public) {
+					return Err(Error::InvalidKeyFile(
+						format!("The secret of key 0x{} in backup doesn't derive it", key.public),
+					))
+				}
+				let path = self.key_file_path(&public, key_type).ok_or(Error::Unavailable)?;
+
+				Ok((key_type, public, path, KeySecret { suri: key.suri, password: key.password }))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		let contents = keys.iter()
+			.map(|(_, _, _, secret)| self.key_file_contents(secret))
+			.collect::<Result<Vec<_>>>()?;
+
+		let staging = self.path.as_ref().ok_or(Error::Unavailable)?.join(IMPORT_STAGING_DIR);
+		// Left over by an import that was interrupted.
+		if staging.exists() {
+			fs::remove_dir_all(&staging)?;
+		}
+		fs::create_dir(&staging)?;
+
+		let staged = keys.iter().zip(&contents)
+			.map(|((_, _, path, _), contents)| {
+				let staged = staging.join(path.file_name().expect("key file paths have a name; qed"));
+				fs::write(&staged, contents)?;
+				Ok(staged)
+			})
+			.collect::<Result<Vec<_>>>();
+		let staged = match staged {
+			Ok(staged) => staged,
+			Err(e) => {
+				let _ = fs::remove_dir_all(&staging);
+				return Err(e)
+			},
+		};
+
+		for (staged, (_, _, path, _)) in staged.iter().zip(&keys) {
+			fs::rename(staged, path)?;
+		}
+		fs::remove_dir(&staging)?;
+
+		Ok(keys.into_iter().map(|(key_type, public, _, _)| (key_type, public)).collect())
+	}
+
+	/// The remote signer used by this store, if any.
+	///
+	/// Its methods are async, so they can be awaited without blocking and without holding the
+	/// lock of the store.
+	pub fn remote_signer(&self) -> Option<remote::RemoteSigner> {
+		self.remote.clone()
+	}
+
+	/// Returns the keys of the given type held by the remote signer, if any.
+	fn remote_keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>> {
+		match &self.remote {
+			Some(remote) => futures::executor::block_on(remote.keys(id)),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Returns a list of raw public keys filtered by `KeyTypeId`
+	fn raw_public_keys(&self, id: KeyTypeId) -> Result<Vec<Vec<u8>>> {
+		let mut public_keys = self.local_public_keys(id)?;
+		for CryptoTypePublicPair(_, public) in self.remote_keys(id)? {
+			if !public_keys.contains(&public) {
+				public_keys.push(public);
+			}
+		}
+
+		Ok(public_keys)
+	}
+
+	/// Returns a list of raw public keys filtered by `KeyTypeId`, that are not held by the
+	/// remote signer.
+	fn local_public_keys(&self, id: KeyTypeId) -> Result<Vec<Vec<u8>>> {
+		let mut public_keys: Vec<Vec<u8>> = self.additional.keys()
+			.into_iter()
+			.filter_map(|k| if k.0 == id { Some(k.1.clone()) } else { None })
+			.collect();
+
+		public_keys.extend(
+			self.key_files()?
+				.into_iter()
+				.filter(|(key_type, _, _)| *key_type == id)
+				.map(|(_, public, _)| public)
+		);
+
 		Ok(public_keys)
 	}
 }
 
+/// Whether the key pair derived from `suri` with `password` has the given raw public key, for
+/// any of the supported crypto types.
+fn derives_public(suri: &str, password: Option<&str>, public: &[u8]) -> bool {
+	fn derives<Pair: PairT>(suri: &str, password: Option<&str>, public: &[u8]) -> bool {
+		Pair::from_string(suri, password)
+			.map(|pair| pair.public().as_slice() == public)
+			.unwrap_or(false)
+	}
+
+	derives::<sr25519::Pair>(suri, password, public)
+		|| derives::<ed25519::Pair>(suri, password, public)
+		|| derives::<ecdsa::Pair>(suri, password, public)
+}
+
 impl BareCryptoStore for Store {
 	fn keys(
 		&self,
 		id: KeyTypeId
 	) -> std::result::Result<Vec<CryptoTypePublicPair>, TraitError> {
-		let raw_keys = self.raw_public_keys(id)?;
-		Ok(raw_keys.into_iter()
+		let raw_keys = self.local_public_keys(id)?;
+		let mut keys = raw_keys.into_iter()
 			.fold(Vec::new(), |mut v, k| {
 				v.push(CryptoTypePublicPair(sr25519::CRYPTO_ID, k.clone()));
 				v.push(CryptoTypePublicPair(ed25519::CRYPTO_ID, k.clone()));
 				v.push(CryptoTypePublicPair(ecdsa::CRYPTO_ID, k.clone()));
 				v
-			}))
+			});
+		keys.extend(self.remote_keys(id)?);
+		Ok(keys)
 	}
 
 	fn supported_keys(
@@ -332,6 +670,13 @@ impl BareCryptoStore for Store {
 		key: &CryptoTypePublicPair,
 		msg: &[u8],
 	) -> std::result::Result<Vec<u8>, TraitError> {
+		if let Some(remote) = &self.remote {
+			if self.get_additional_pair(&key.1, id).is_none() {
+				return futures::executor::block_on(remote.sign_with(id, key, msg))
+					.map_err(Into::into)
+			}
+		}
+
 		match key.0 {
 			ed25519::CRYPTO_ID => {
 				let pub_key = ed25519::Public::from_slice(key.1.as_slice());
@@ -438,7 +783,22 @@ impl BareCryptoStore for Store {
 	}
 
 	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
-		public_keys.iter().all(|(p, t)| self.key_phrase_by_type(&p, *t).is_ok())
+		let missing = public_keys.iter()
+			.filter(|(p, t)| self.key_secret_by_type(p, *t).is_err())
+			.cloned()
+			.collect::<Vec<_>>();
+		if missing.is_empty() {
+			return true
+		}
+
+		match &self.remote {
+			Some(remote) => futures::executor::block_on(remote.has_keys(&missing))
+				.unwrap_or_else(|e| {
+					log::warn!(target: "keystore", "Failed to look up keys of the remote signer: {}", e);
+					false
+				}),
+			None => false,
+		}
 	}
 
 	fn sr25519_vrf_sign(
@@ -447,6 +807,14 @@ impl BareCryptoStore for Store {
 		public: &Sr25519Public,
 		transcript_data: VRFTranscriptData,
 	) -> std::result::Result<VRFSignature, TraitError> {
+		if let Some(remote) = &self.remote {
+			if self.get_additional_pair(public.as_ref(), key_type).is_none() {
+				return futures::executor::block_on(
+					remote.sr25519_vrf_sign(key_type, public, transcript_data)
+				).map_err(Into::into)
+			}
+		}
+
 		let transcript = make_transcript(transcript_data);
 		let pair = self.key_pair_by_type::<Sr25519Pair>(public, key_type)
 			.map_err(|e| TraitError::PairNotFound(e.to_string()))?;
@@ -568,6 +936,176 @@ mod tests {
 		assert_eq!(key_pair.public(), store_key_pair.public());
 	}
 
+	#[test]
+	fn encrypted_store_requires_password() {
+		let password = String::from("password");
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open_encrypted(temp_dir.path(), password.clone().into()).unwrap();
+
+		let pair: ed25519::AppPair = store.write().generate().unwrap();
+		let file = fs::read_dir(temp_dir.path()).unwrap().next().unwrap().unwrap().path();
+		let contents: serde_json::Value = serde_json::from_slice(&fs::read(file).unwrap()).unwrap();
+		assert!(Envelope::is_envelope(&contents));
+
+		let store = Store::open(temp_dir.path(), None).unwrap();
+		assert!(store.read().key_pair::<ed25519::AppPair>(&pair.public()).is_err());
+
+		let store = Store::open_encrypted(temp_dir.path(), String::from("wrong").into()).unwrap();
+		assert!(store.read().key_pair::<ed25519::AppPair>(&pair.public()).is_err());
+
+		let store = Store::open_encrypted(temp_dir.path(), password.into()).unwrap();
+		assert_eq!(
+			pair.public(),
+			store.read().key_pair::<ed25519::AppPair>(&pair.public()).unwrap().public(),
+		);
+	}
+
+	#[test]
+	fn plaintext_keys_are_migrated() {
+		let password = String::from("password");
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), Some(password.clone().into())).unwrap();
+
+		let pair: sr25519::AppPair = store.write().generate().unwrap();
+		assert_eq!(store.write().encrypt_key_files().unwrap(), 1);
+		assert_eq!(store.write().encrypt_key_files().unwrap(), 0);
+
+		let store = Store::open_encrypted(temp_dir.path(), password.into()).unwrap();
+		assert_eq!(
+			pair.public(),
+			store.read().key_pair::<sr25519::AppPair>(&pair.public()).unwrap().public(),
+		);
+	}
+
+	#[test]
+	fn changing_password_keeps_keys() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), Some(String::from("old").into())).unwrap();
+
+		let pair: sr25519::AppPair = store.write().generate().unwrap();
+		assert_eq!(store.write().change_password(String::from("new").into()).unwrap(), 1);
+
+		let store = Store::open_encrypted(temp_dir.path(), String::from("old").into()).unwrap();
+		assert!(store.read().key_pair::<sr25519::AppPair>(&pair.public()).is_err());
+
+		let store = Store::open_encrypted(temp_dir.path(), String::from("new").into()).unwrap();
+		assert_eq!(
+			pair.public(),
+			store.read().key_pair::<sr25519::AppPair>(&pair.public()).unwrap().public(),
+		);
+	}
+
+	#[test]
+	fn export_and_import_selected_keys() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open_encrypted(temp_dir.path(), String::from("password").into()).unwrap();
+
+		let sr25519_pair: sr25519::AppPair = store.write().generate().unwrap();
+		let ed25519_pair: ed25519::AppPair = store.write().generate().unwrap();
+
+		let backup = store.read()
+			.export_keys(&[sr25519::AppPair::ID], String::from("backup").into())
+			.unwrap();
+
+		let other_dir = TempDir::new().unwrap();
+		let other = Store::open_encrypted(other_dir.path(), String::from("other").into()).unwrap();
+		assert!(other.read().import_keys(&backup, String::from("wrong").into()).is_err());
+
+		let imported = other.read().import_keys(&backup, String::from("backup").into()).unwrap();
+		assert_eq!(imported, vec![(sr25519::AppPair::ID, sr25519_pair.public().to_raw_vec())]);
+
+		// The keys are still derived with the password of the original store.
+		let other = Store::open_encrypted(other_dir.path(), String::from("other").into()).unwrap();
+		assert_eq!(
+			sr25519_pair.public(),
+			other.read().key_pair::<sr25519::AppPair>(&sr25519_pair.public()).unwrap().public(),
+		);
+		assert!(other.read().key_pair::<ed25519::AppPair>(&ed25519_pair.public()).is_err());
+	}
+
+	#[test]
+	fn import_rejects_keys_not_derived_from_their_secret() {
+		let temp_dir = TempDir::new().unwrap();
+		let store = Store::open(temp_dir.path(), None).unwrap();
+
+		let pair: sr25519::AppPair = store.write().generate().unwrap();
+		let other: sr25519::AppPair = store.write().generate().unwrap();
+
+		let backup = |keys: Vec<BackupKey>| {
+			let plaintext = Protected::from(serde_json::to_vec(&keys).unwrap());
+			let envelope = Cipher::new(String::from("backup").into()).encrypt(&plaintext).unwrap();
+			serde_json::to_string(&envelope).unwrap()
+		};
+		let key = |public: &sr25519::AppPublic, suri: &str| BackupKey {
+			key_type: String::from_utf8_lossy(&sr25519::AppPair::ID.0).into_owned(),
+			public: hex::encode(public.to_raw_vec()),
+			suri: suri.into(),
+			password: None,
+		};
+		let secret = |public: &sr25519::AppPublic| {
+			store.read().key_secret_by_type(public.as_slice(), sr25519::AppPair::ID).unwrap().suri
+		};
+
+		// the second key claims the public key of the first one, with the secret of the other
+		let tampered = backup(vec![
+			key(&other.public(), &secret(&other.public())),
+			key(&pair.public(), &secret(&other.public())),
+		]);
+
+		let other_dir = TempDir::new().unwrap();
+		let other_store = Store::open(other_dir.path(), None).unwrap();
+		assert!(other_store.read().import_keys(&tampered, String::from("backup").into()).is_err());
+
+		// and no key is imported
+		assert!(other_store.read().key_files().unwrap().is_empty());
+
+		let valid = backup(vec![key(&pair.public(), &secret(&pair.public()))]);
+		assert_eq!(
+			other_store.read().import_keys(&valid, String::from("backup").into()).unwrap(),
+			vec![(sr25519::AppPair::ID, pair.public().to_raw_vec())],
+		);
+	}
+
+	#[test]
+	fn import_writes_no_key_unless_all_can_be_written() {
+		let plain_dir = TempDir::new().unwrap();
+		let plain = Store::open(plain_dir.path(), None).unwrap();
+		let encrypted_dir = TempDir::new().unwrap();
+		let encrypted = Store::open_encrypted(encrypted_dir.path(), String::from("password").into())
+			.unwrap();
+
+		let plain_pair: sr25519::AppPair = plain.write().generate().unwrap();
+		let encrypted_pair: sr25519::AppPair = encrypted.write().generate().unwrap();
+
+		let key = |store: &KeyStorePtr, public: &sr25519::AppPublic| {
+			let secret = store.read()
+				.key_secret_by_type(public.as_slice(), sr25519::AppPair::ID)
+				.unwrap();
+			BackupKey {
+				key_type: String::from_utf8_lossy(&sr25519::AppPair::ID.0).into_owned(),
+				public: hex::encode(public.to_raw_vec()),
+				suri: secret.suri,
+				password: secret.password,
+			}
+		};
+		let keys = vec![
+			key(&plain, &plain_pair.public()),
+			key(&encrypted, &encrypted_pair.public()),
+		];
+		let plaintext = Protected::from(serde_json::to_vec(&keys).unwrap());
+		let envelope = Cipher::new(String::from("backup").into()).encrypt(&plaintext).unwrap();
+		let backup = serde_json::to_string(&envelope).unwrap();
+
+		// a plaintext store can't hold the key derived with a password
+		let other_dir = TempDir::new().unwrap();
+		let other = Store::open(other_dir.path(), None).unwrap();
+		assert!(other.read().import_keys(&backup, String::from("backup").into()).is_err());
+
+		// so the valid key isn't imported either
+		assert!(other.read().key_files().unwrap().is_empty());
+		assert!(!other_dir.path().join(IMPORT_STAGING_DIR).exists());
+	}
+
 	#[test]
 	fn store_ignores_files_with_invalid_name() {
 		let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,711 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Remote signer support.
+//!
+//! A [`RemoteSigner`] forwards key listing and signing requests to a signer process that holds
+//! the private keys outside of the node. The signer speaks JSON-RPC 2.0 over plain HTTP and
+//! has to implement the following methods:
+//!
+//! - `signer_keys(key_type)` returns the `[crypto_type, public]` pairs it holds for `key_type`.
+//! - `signer_signWith(key_type, crypto_type, public, message)` returns the SCALE encoded
+//!   signature of `message`.
+//! - `signer_sr25519VrfSign(key_type, public, transcript)` returns an object with the VRF
+//!   `output` and `proof`.
+//!
+//! Key types and crypto types are passed as four character strings and binary data is hex
+//! encoded with a `0x` prefix. A VRF transcript is an object with a hex encoded `label` and a
+//! list of `items`, each of them being an object with a string `label` and either a hex encoded
+//! `bytes` or an integer `u64` field.
+//!
+//! [`LocalSigner`] is a minimal signer backed by a local [`Store`]. It is meant for tests.
+//!
+//! The methods of [`RemoteSigner`] are async. The synchronous `BareCryptoStore` methods of a
+//! [`Store`] using a remote signer block until the signer answered, async callers should get
+//! the signer through [`Store::remote_signer`] instead.
+
+use std::{
+	collections::HashMap,
+	convert::TryFrom,
+	io::{self, Read, Write},
+	net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+	sync::{Arc, atomic::{AtomicBool, Ordering}},
+	thread,
+	time::Duration,
+};
+use futures::channel::{mpsc, oneshot};
+use serde_json::{json, Value};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId, Public as _},
+	traits::BareCryptoStore,
+	sr25519,
+	vrf::{VRFSignature, VRFTranscriptData, VRFTranscriptValue},
+};
+use crate::{Error, KeyStorePtr, Result, Store};
+
+/// Default timeout for a single request to the remote signer.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum size of a message accepted from the other side.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// JSON-RPC error code used by the signer when it does not hold the requested key.
+pub const KEY_NOT_FOUND: i64 = 1000;
+
+/// JSON-RPC error code used by the signer when listing its keys failed.
+pub const SIGNER_ERROR: i64 = -32000;
+
+/// JSON-RPC error code used by the signer when the request could not be parsed.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// JSON-RPC error code used by the signer for unknown methods.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Transcript labels accepted by a [`LocalSigner`] by default, the ones of BABE.
+pub const DEFAULT_TRANSCRIPT_LABELS: &[&str] =
+	&["BABE", "slot number", "current epoch", "chain randomness"];
+
+/// A request waiting to be sent to the signer by the worker thread.
+struct Call {
+	method: &'static str,
+	params: Value,
+	result: oneshot::Sender<Result<Value>>,
+}
+
+/// Client side of the remote signer protocol.
+///
+/// Requests are performed by a dedicated worker thread, every method returns a future that
+/// resolves once the signer answered or the request timed out. Clones share the worker.
+#[derive(Clone)]
+pub struct RemoteSigner {
+	uri: String,
+	calls: mpsc::UnboundedSender<Call>,
+}
+
+impl RemoteSigner {
+	/// Create a new client for the signer listening at `uri`.
+	///
+	/// Only `http://host:port[/path]` URIs are supported. Requests that take longer than
+	/// `timeout` fail.
+	pub fn new(uri: &str, timeout: Duration) -> Result<Self> {
+		let endpoint = Endpoint::parse(uri, timeout)?;
+		let (calls, mut receiver) = mpsc::unbounded::<Call>();
+
+		thread::Builder::new()
+			.name("remote-signer".into())
+			.spawn(move || {
+				let mut next_id = 0u64;
+				while let Some(call) = futures::executor::block_on(
+					futures::StreamExt::next(&mut receiver)
+				) {
+					next_id += 1;
+					let _ = call.result.send(endpoint.request(next_id, call.method, call.params));
+				}
+			})?;
+
+		Ok(Self { uri: uri.into(), calls })
+	}
+
+	/// The URI of the signer.
+	pub fn uri(&self) -> &str {
+		&self.uri
+	}
+
+	async fn call(&self, method: &'static str, params: Value) -> Result<Value> {
+		let (result, receiver) = oneshot::channel();
+		self.calls.unbounded_send(Call { method, params, result })
+			.map_err(|_| Error::Remote("Remote signer worker has stopped".into()))?;
+
+		receiver.await.map_err(|_| Error::Remote("Remote signer worker has stopped".into()))?
+	}
+
+	/// Returns all public keys of the given key type the signer holds.
+	pub async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>> {
+		let result = self.call("signer_keys", json!([four_cc_to_string(&id.0)])).await?;
+		let invalid = || Error::Remote("Invalid `signer_keys` response".into());
+
+		result.as_array()
+			.ok_or_else(invalid)?
+			.iter()
+			.map(|entry| {
+				let crypto = entry.get(0).and_then(Value::as_str).and_then(four_cc);
+				let public = entry.get(1).and_then(Value::as_str).and_then(from_hex);
+				match (crypto, public) {
+					(Some(crypto), Some(public)) =>
+						Ok(CryptoTypePublicPair(CryptoTypeId(crypto), public)),
+					_ => Err(invalid()),
+				}
+			})
+			.collect()
+	}
+
+	/// Returns `true` if the signer holds all of the given keys.
+	///
+	/// The keys of every key type are requested at most once.
+	pub async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> Result<bool> {
+		let mut keys = HashMap::new();
+		for (public, id) in public_keys {
+			if !keys.contains_key(id) {
+				keys.insert(*id, self.keys(*id).await?);
+			}
+			if !keys[id].iter().any(|key: &CryptoTypePublicPair| &key.1 == public) {
+				return Ok(false)
+			}
+		}
+
+		Ok(true)
+	}
+
+	/// Sign `msg` with the given key.
+	///
+	/// Returns the SCALE encoded signature.
+	pub async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Vec<u8>> {
+		let params = json!([
+			four_cc_to_string(&id.0),
+			four_cc_to_string(&(key.0).0),
+			to_hex(&key.1),
+			to_hex(msg),
+		]);
+		let result = self.call("signer_signWith", params).await?;
+
+		result.as_str()
+			.and_then(from_hex)
+			.ok_or_else(|| Error::Remote("Invalid `signer_signWith` response".into()))
+	}
+
+	/// Generate a VRF signature for the given transcript data.
+	pub async fn sr25519_vrf_sign(
+		&self,
+		id: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData<'_>,
+	) -> Result<VRFSignature> {
+		let params = json!([
+			four_cc_to_string(&id.0),
+			to_hex(public.as_ref()),
+			transcript_to_json(&transcript_data),
+		]);
+		let result = self.call("signer_sr25519VrfSign", params).await?;
+		let invalid = || Error::Remote("Invalid `signer_sr25519VrfSign` response".into());
+
+		let output = result.get("output").and_then(Value::as_str).and_then(from_hex)
+			.ok_or_else(invalid)?;
+		let proof = result.get("proof").and_then(Value::as_str).and_then(from_hex)
+			.ok_or_else(invalid)?;
+
+		Ok(VRFSignature {
+			output: schnorrkel::vrf::VRFOutput::from_bytes(&output).map_err(|_| invalid())?,
+			proof: schnorrkel::vrf::VRFProof::from_bytes(&proof).map_err(|_| invalid())?,
+		})
+	}
+}
+
+/// Where to send the requests to.
+struct Endpoint {
+	host: String,
+	path: String,
+	timeout: Duration,
+}
+
+impl Endpoint {
+	fn parse(uri: &str, timeout: Duration) -> Result<Self> {
+		if !uri.starts_with("http://") {
+			return Err(Error::Remote(format!("Unsupported remote signer URI: {}", uri)))
+		}
+		let rest = &uri["http://".len()..];
+		let (host, path) = match rest.find('/') {
+			Some(pos) => (&rest[..pos], &rest[pos..]),
+			None => (rest, "/"),
+		};
+
+		if host.is_empty() {
+			return Err(Error::Remote(format!("Missing host in remote signer URI: {}", uri)))
+		}
+
+		Ok(Self { host: host.into(), path: path.into(), timeout })
+	}
+
+	fn connect(&self) -> io::Result<TcpStream> {
+		let mut last_err = None;
+		for addr in self.host.to_socket_addrs()? {
+			match TcpStream::connect_timeout(&addr, self.timeout) {
+				Ok(stream) => return Ok(stream),
+				Err(e) => last_err = Some(e),
+			}
+		}
+
+		Err(last_err.unwrap_or_else(|| io::Error::new(
+			io::ErrorKind::NotFound,
+			format!("Could not resolve {}", self.host),
+		)))
+	}
+
+	/// Send a JSON-RPC request and wait for the result.
+	fn request(&self, id: u64, method: &str, params: Value) -> Result<Value> {
+		let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+			.to_string();
+
+		let mut stream = self.connect()?;
+		stream.set_read_timeout(Some(self.timeout))?;
+		stream.set_write_timeout(Some(self.timeout))?;
+
+		write!(
+			stream,
+			"POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+			Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+			self.path,
+			self.host,
+			body.len(),
+			body,
+		)?;
+		stream.flush()?;
+
+		let (head, body) = read_http_message(&mut stream)?;
+		let status_ok = head.lines().next()
+			.map_or(false, |status| status.split_whitespace().nth(1) == Some("200"));
+		if !status_ok {
+			return Err(Error::Remote(format!(
+				"Unexpected response from remote signer: {}",
+				head.lines().next().unwrap_or_default(),
+			)))
+		}
+
+		let mut response: Value = serde_json::from_slice(&body)?;
+		if let Some(error) = response.get("error") {
+			let message = error.get("message").and_then(Value::as_str).unwrap_or_default();
+			return Err(match error.get("code").and_then(Value::as_i64) {
+				Some(KEY_NOT_FOUND) => Error::PairNotFound(message.into()),
+				_ => Error::Remote(message.into()),
+			})
+		}
+
+		response.get_mut("result")
+			.map(Value::take)
+			.ok_or_else(|| Error::Remote("Missing result in remote signer response".into()))
+	}
+}
+
+/// Read a HTTP message from the given stream.
+///
+/// Returns the head (start line and headers) and the body. If no `Content-Length` header is
+/// present, the body is read until the other side closes the connection. Chunked transfer
+/// encoding is not supported.
+fn read_http_message(stream: &mut impl Read) -> io::Result<(String, Vec<u8>)> {
+	let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+	let mut buf = Vec::new();
+	let mut chunk = [0u8; 4096];
+	let head_end = loop {
+		if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+			break pos;
+		}
+		if buf.len() > MAX_MESSAGE_SIZE {
+			return Err(invalid("HTTP head too large"))
+		}
+		let read = stream.read(&mut chunk)?;
+		if read == 0 {
+			return Err(invalid("Connection closed before end of HTTP head"))
+		}
+		buf.extend_from_slice(&chunk[..read]);
+	};
+
+	let head = String::from_utf8(buf[..head_end].to_vec())
+		.map_err(|_| invalid("HTTP head is not valid UTF-8"))?;
+	let mut body = buf.split_off(head_end + 4);
+
+	let content_length = head.lines()
+		.filter_map(|line| {
+			let mut parts = line.splitn(2, ':');
+			let name = parts.next()?.trim();
+			let value = parts.next()?.trim();
+			if name.eq_ignore_ascii_case("content-length") { value.parse::<usize>().ok() } else { None }
+		})
+		.next();
+
+	match content_length {
+		Some(len) if len > MAX_MESSAGE_SIZE => return Err(invalid("HTTP body too large")),
+		Some(len) => {
+			if body.len() < len {
+				let mut rest = vec![0u8; len - body.len()];
+				stream.read_exact(&mut rest)?;
+				body.extend_from_slice(&rest);
+			}
+			body.truncate(len);
+		},
+		None => {
+			stream.take((MAX_MESSAGE_SIZE - body.len().min(MAX_MESSAGE_SIZE)) as u64)
+				.read_to_end(&mut body)?;
+		},
+	}
+
+	Ok((head, body))
+}
+
+fn to_hex(data: &[u8]) -> String {
+	format!("0x{}", hex::encode(data))
+}
+
+fn from_hex(data: &str) -> Option<Vec<u8>> {
+	let data = if data.starts_with("0x") { &data[2..] } else { data };
+	hex::decode(data).ok()
+}
+
+fn four_cc(s: &str) -> Option<[u8; 4]> {
+	let bytes = s.as_bytes();
+	if bytes.len() != 4 {
+		return None
+	}
+
+	let mut res = [0u8; 4];
+	res.copy_from_slice(bytes);
+	Some(res)
+}
+
+fn four_cc_to_string(id: &[u8; 4]) -> String {
+	String::from_utf8_lossy(id).into_owned()
+}
+
+fn transcript_to_json(data: &VRFTranscriptData) -> Value {
+	let items = data.items.iter()
+		.map(|(label, value)| match value {
+			VRFTranscriptValue::Bytes(bytes) => json!({ "label": label, "bytes": to_hex(bytes) }),
+			VRFTranscriptValue::U64(val) => json!({ "label": label, "u64": val }),
+		})
+		.collect::<Vec<_>>();
+
+	json!({ "label": to_hex(data.label), "items": items })
+}
+
+/// Returns the label out of `labels` that equals `label`.
+///
+/// Transcript labels are `'static` in `merlin`, so labels received over the wire can only be
+/// used if they are known in advance.
+fn find_label(labels: &[&'static str], label: &[u8]) -> Option<&'static str> {
+	labels.iter().find(|known| known.as_bytes() == label).copied()
+}
+
+/// A signer serving the keys of a local [`Store`] over the remote signer protocol.
+///
+/// Stops serving requests when dropped.
+pub struct LocalSigner {
+	addr: SocketAddr,
+	shutdown: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl LocalSigner {
+	/// Start serving `store` on a random local port.
+	///
+	/// VRF signatures can be generated for the [`DEFAULT_TRANSCRIPT_LABELS`].
+	pub fn spawn(store: KeyStorePtr) -> io::Result<Self> {
+		Self::spawn_with_transcript_labels(store, DEFAULT_TRANSCRIPT_LABELS)
+	}
+
+	/// Start serving `store` on a random local port.
+	///
+	/// VRF signatures can only be generated for transcripts that use the given labels.
+	pub fn spawn_with_transcript_labels(
+		store: KeyStorePtr,
+		transcript_labels: &'static [&'static str],
+	) -> io::Result<Self> {
+		let listener = TcpListener::bind("127.0.0.1:0")?;
+		let addr = listener.local_addr()?;
+		let shutdown = Arc::new(AtomicBool::new(false));
+
+		let stop = shutdown.clone();
+		let handle = thread::Builder::new()
+			.name("local-signer".into())
+			.spawn(move || {
+				for stream in listener.incoming() {
+					if stop.load(Ordering::SeqCst) {
+						break;
+					}
+					if let Ok(mut stream) = stream {
+						let _ = serve_request(&mut stream, &store, transcript_labels);
+					}
+				}
+			})?;
+
+		Ok(Self { addr, shutdown, handle: Some(handle) })
+	}
+
+	/// The URI to pass to [`RemoteSigner::new`].
+	pub fn uri(&self) -> String {
+		format!("http://{}", self.addr)
+	}
+}
+
+impl Drop for LocalSigner {
+	fn drop(&mut self) {
+		self.shutdown.store(true, Ordering::SeqCst);
+		// Wake up the listener, so it notices the shutdown.
+		let _ = TcpStream::connect(self.addr);
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+	}
+}
+
+fn serve_request(
+	stream: &mut TcpStream,
+	store: &KeyStorePtr,
+	transcript_labels: &[&'static str],
+) -> io::Result<()> {
+	stream.set_read_timeout(Some(DEFAULT_REQUEST_TIMEOUT))?;
+	let (_, body) = read_http_message(stream)?;
+
+	let request: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+	let id = request.get("id").cloned().unwrap_or(Value::Null);
+	let method = request.get("method").and_then(Value::as_str).unwrap_or_default();
+	let params = request.get("params").and_then(Value::as_array).cloned().unwrap_or_default();
+
+	let response = match dispatch(&*store.read(), transcript_labels, method, &params) {
+		Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+		Err((code, message)) => json!({
+			"jsonrpc": "2.0",
+			"id": id,
+			"error": { "code": code, "message": message },
+		}),
+	}.to_string();
+
+	write!(
+		stream,
+		"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+		Connection: close\r\n\r\n{}",
+		response.len(),
+		response,
+	)?;
+	stream.flush()
+}
+
+fn dispatch(
+	store: &Store,
+	transcript_labels: &[&'static str],
+	method: &str,
+	params: &[Value],
+) -> std::result::Result<Value, (i64, String)> {
+	let invalid = || (INVALID_PARAMS, "Invalid params".to_string());
+	// The signer can only fail to sign if it cannot access the key.
+	let key_not_found = |e: sp_core::traits::Error| (KEY_NOT_FOUND, e.to_string());
+	let key_type = params.get(0)
+		.and_then(Value::as_str)
+		.and_then(|s| KeyTypeId::try_from(s).ok())
+		.ok_or_else(invalid)?;
+
+	match method {
+		"signer_keys" => {
+			let keys = store.keys(key_type).map_err(|e| (SIGNER_ERROR, e.to_string()))?;
+			Ok(Value::Array(keys.into_iter()
+				.map(|CryptoTypePublicPair(crypto, public)| {
+					json!([four_cc_to_string(&crypto.0), to_hex(&public)])
+				})
+				.collect()))
+		},
+		"signer_signWith" => {
+			let crypto = params.get(1).and_then(Value::as_str).and_then(four_cc)
+				.ok_or_else(invalid)?;
+			let public = params.get(2).and_then(Value::as_str).and_then(from_hex)
+				.ok_or_else(invalid)?;
+			let msg = params.get(3).and_then(Value::as_str).and_then(from_hex)
+				.ok_or_else(invalid)?;
+
+			store.sign_with(key_type, &CryptoTypePublicPair(CryptoTypeId(crypto), public), &msg)
+				.map(|signature| Value::String(to_hex(&signature)))
+				.map_err(key_not_found)
+		},
+		"signer_sr25519VrfSign" => {
+			let public = params.get(1).and_then(Value::as_str).and_then(from_hex)
+				.filter(|public| public.len() == 32)
+				.map(|public| sr25519::Public::from_slice(&public))
+				.ok_or_else(invalid)?;
+			let transcript = params.get(2).ok_or_else(invalid)?;
+
+			let label = transcript.get("label").and_then(Value::as_str).and_then(from_hex)
+				.and_then(|label| find_label(transcript_labels, &label))
+				.map(str::as_bytes)
+				.ok_or_else(invalid)?;
+			let items = transcript.get("items").and_then(Value::as_array).ok_or_else(invalid)?
+				.iter()
+				.map(|item| {
+					let label = item.get("label").and_then(Value::as_str)
+						.and_then(|label| find_label(transcript_labels, label.as_bytes()))?;
+					let value = match (item.get("bytes"), item.get("u64")) {
+						(Some(bytes), None) => bytes.as_str().and_then(from_hex).map(TranscriptValue::Bytes)?,
+						(None, Some(val)) => val.as_u64().map(TranscriptValue::U64)?,
+						_ => return None,
+					};
+					Some((label, value))
+				})
+				.collect::<Option<Vec<_>>>()
+				.ok_or_else(invalid)?;
+
+			let transcript_data = VRFTranscriptData {
+				label,
+				items: items.iter()
+					.map(|(label, value)| (*label, match value {
+						TranscriptValue::Bytes(bytes) => VRFTranscriptValue::Bytes(&bytes[..]),
+						TranscriptValue::U64(val) => VRFTranscriptValue::U64(*val),
+					}))
+					.collect(),
+			};
+
+			let signature = store.sr25519_vrf_sign(key_type, &public, transcript_data)
+				.map_err(key_not_found)?;
+			Ok(json!({
+				"output": to_hex(&signature.output.to_bytes()),
+				"proof": to_hex(&signature.proof.to_bytes()),
+			}))
+		},
+		_ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+	}
+}
+
+/// Owned version of [`VRFTranscriptValue`].
+enum TranscriptValue {
+	Bytes(Vec<u8>),
+	U64(u64),
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tempfile::TempDir;
+	use sp_core::{Decode, Pair, testing::SR25519, vrf::make_transcript};
+
+	const TRANSCRIPT_LABELS: &[&str] = &["remote signer", "one", "two"];
+
+	fn transcript_data() -> VRFTranscriptData<'static> {
+		VRFTranscriptData {
+			label: b"remote signer",
+			items: vec![
+				("one", VRFTranscriptValue::U64(1)),
+				("two", VRFTranscriptValue::Bytes(b"test")),
+			],
+		}
+	}
+
+	#[test]
+	fn remote_store_lists_keys_and_signs() {
+		let temp_dir = TempDir::new().unwrap();
+		let local = Store::open(temp_dir.path(), None).unwrap();
+		let pair = local.write().generate_by_type::<sr25519::Pair>(SR25519).unwrap();
+
+		let signer = LocalSigner::spawn_with_transcript_labels(local.clone(), TRANSCRIPT_LABELS)
+			.unwrap();
+		let remote = Store::open_remote(&signer.uri(), DEFAULT_REQUEST_TIMEOUT).unwrap();
+
+		assert_eq!(remote.read().sr25519_public_keys(SR25519), vec![pair.public()]);
+		assert!(remote.read().has_keys(&[(pair.public().to_raw_vec(), SR25519)]));
+
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+		let signature = remote.read().sign_with(SR25519, &key, b"hello").unwrap();
+		let signature = sr25519::Signature::decode(&mut &signature[..]).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"hello", &pair.public()));
+
+		let vrf = remote.read().sr25519_vrf_sign(SR25519, &pair.public(), transcript_data()).unwrap();
+		let (inout, _, _) = pair.as_ref().vrf_sign(make_transcript(transcript_data()));
+		assert_eq!(vrf.output, inout.to_output());
+	}
+
+	#[test]
+	fn remote_store_reports_missing_keys() {
+		let local = Store::new_in_memory();
+		let signer = LocalSigner::spawn(local).unwrap();
+		let remote = Store::open_remote(&signer.uri(), DEFAULT_REQUEST_TIMEOUT).unwrap();
+
+		let pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+
+		assert!(remote.read().sr25519_public_keys(SR25519).is_empty());
+		assert!(!remote.read().has_keys(&[(pair.public().to_raw_vec(), SR25519)]));
+		assert!(matches!(
+			remote.read().sign_with(SR25519, &key, b"hello"),
+			Err(sp_core::traits::Error::PairNotFound(_)),
+		));
+	}
+
+	#[test]
+	fn remote_signer_can_be_used_asynchronously() {
+		let local = Store::new_in_memory();
+		let pair = local.write()
+			.insert_ephemeral_from_seed_by_type::<sr25519::Pair>("//Alice", SR25519)
+			.unwrap();
+		let signer = LocalSigner::spawn(local).unwrap();
+		let remote = Store::open_remote(&signer.uri(), DEFAULT_REQUEST_TIMEOUT).unwrap();
+
+		let remote_signer = remote.read().remote_signer().unwrap();
+		let key = CryptoTypePublicPair(sr25519::CRYPTO_ID, pair.public().to_raw_vec());
+		let keys = futures::executor::block_on(remote_signer.keys(SR25519)).unwrap();
+		assert!(keys.contains(&key));
+
+		let signature = futures::executor::block_on(remote_signer.sign_with(SR25519, &key, b"hello"))
+			.unwrap();
+		let signature = sr25519::Signature::decode(&mut &signature[..]).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"hello", &pair.public()));
+	}
+
+	#[test]
+	fn unknown_transcript_labels_are_rejected() {
+		let local = Store::new_in_memory();
+		let pair = local.write()
+			.insert_ephemeral_from_seed_by_type::<sr25519::Pair>("//Alice", SR25519)
+			.unwrap();
+		let signer = LocalSigner::spawn(local).unwrap();
+		let remote = Store::open_remote(&signer.uri(), DEFAULT_REQUEST_TIMEOUT).unwrap();
+
+		assert!(remote.read().sr25519_vrf_sign(SR25519, &pair.public(), transcript_data()).is_err());
+	}
+
+	#[test]
+	fn requests_time_out() {
+		// Accepts connections, but never answers.
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("http://{}", listener.local_addr().unwrap());
+		let remote = Store::open_remote(&uri, Duration::from_millis(100)).unwrap();
+
+		let start = std::time::Instant::now();
+		assert!(remote.read().sr25519_public_keys(SR25519).is_empty());
+		assert!(start.elapsed() < DEFAULT_REQUEST_TIMEOUT);
+
+		// the failure is only visible through the async variant.
+		let pair = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let key = (pair.public().to_raw_vec(), SR25519);
+		assert!(!remote.read().has_keys(&[key.clone()]));
+		let remote_signer = remote.read().remote_signer().unwrap();
+		assert!(futures::executor::block_on(remote_signer.has_keys(&[key])).is_err());
+	}
+
+	#[test]
+	fn remote_store_does_not_generate_keys() {
+		let signer = LocalSigner::spawn(Store::new_in_memory()).unwrap();
+		let remote = Store::open_remote(&signer.uri(), DEFAULT_REQUEST_TIMEOUT).unwrap();
+
+		assert!(remote.write().sr25519_generate_new(SR25519, None).is_err());
+	}
+
+	#[test]
+	fn unsupported_uri_is_rejected() {
+		assert!(RemoteSigner::new("https://localhost:1234", DEFAULT_REQUEST_TIMEOUT).is_err());
+		assert!(RemoteSigner::new("http:///path", DEFAULT_REQUEST_TIMEOUT).is_err());
+	}
+}
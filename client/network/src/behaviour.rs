@@ -16,12 +16,13 @@
 
 use crate::{
 	config::{ProtocolId, Role}, block_requests, light_client_handler, finality_requests,
-	debug_info, discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
+	request_responses, debug_info, discovery::{DiscoveryBehaviour, DiscoveryConfig, DiscoveryOut},
 	protocol::{message::{self, Roles}, CustomMessageOutcome, Protocol},
 	Event, ObservedRole, DhtEvent, ExHashT,
 };
 
 use codec::Encode as _;
+use futures::channel::oneshot;
 use libp2p::NetworkBehaviour;
 use libp2p::core::{Multiaddr, PeerId, PublicKey};
 use libp2p::kad::record;
@@ -48,6 +49,8 @@ pub struct Behaviour<B: BlockT, H: ExHashT> {
 	finality_proof_requests: finality_requests::FinalityProofRequests<B>,
 	/// Light client request handling.
 	light_client_handler: light_client_handler::LightClientHandler<B>,
+	/// Generic request-response protocols.
+	request_responses: request_responses::RequestResponsesBehaviour,
 
 	/// Queue of events to produce for the outside.
 	#[behaviour(ignore)]
@@ -109,6 +112,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		block_requests: block_requests::BlockRequests<B>,
 		finality_proof_requests: finality_requests::FinalityProofRequests<B>,
 		light_client_handler: light_client_handler::LightClientHandler<B>,
+		request_responses: request_responses::RequestResponsesBehaviour,
 		disco_config: DiscoveryConfig,
 	) -> Self {
 		Behaviour {
@@ -118,6 +122,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 			block_requests,
 			finality_proof_requests,
 			light_client_handler,
+			request_responses,
 			events: VecDeque::new(),
 			role,
 		}
@@ -128,6 +133,12 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		self.discovery.known_peers()
 	}
 
+	/// Returns the addresses of the nodes we recently managed to connect to, most recently
+	/// reached first.
+	pub fn reachable_addresses(&self) -> impl Iterator<Item = (&PeerId, &Multiaddr)> {
+		self.discovery.reachable_addresses()
+	}
+
 	/// Adds a hard-coded address for the given peer, that never expires.
 	pub fn add_known_address(&mut self, peer_id: PeerId, addr: Multiaddr) {
 		self.discovery.add_known_address(peer_id, addr)
@@ -187,6 +198,19 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 		}
 	}
 
+	/// Sends a request on one of the registered request-response protocols.
+	///
+	/// The response, or the reason why the request failed, is sent on `pending_response`.
+	pub fn send_request(
+		&mut self,
+		target: &PeerId,
+		protocol: &str,
+		request: Vec<u8>,
+		pending_response: oneshot::Sender<Result<Vec<u8>, request_responses::RequestFailure>>,
+	) {
+		self.request_responses.send_request(target, protocol, request, pending_response)
+	}
+
 	/// Returns a shared reference to the user protocol.
 	pub fn user_protocol(&self) -> &Protocol<B, H> {
 		&self.substrate
@@ -355,6 +379,33 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<finality_requests::Even
 	}
 }
 
+impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<request_responses::Event> for Behaviour<B, H> {
+	fn inject_event(&mut self, event: request_responses::Event) {
+		match event {
+			request_responses::Event::AnsweredRequest { peer, protocol, build_time } => {
+				self.events.push_back(BehaviourOut::AnsweredRequest {
+					peer,
+					protocol: protocol.as_bytes().to_vec(),
+					build_time,
+				});
+			}
+			request_responses::Event::RequestStarted { peer, protocol } => {
+				self.events.push_back(BehaviourOut::RequestStarted {
+					peer,
+					protocol: protocol.as_bytes().to_vec(),
+				});
+			}
+			request_responses::Event::RequestFinished { peer, protocol, request_duration } => {
+				self.events.push_back(BehaviourOut::RequestFinished {
+					peer,
+					protocol: protocol.as_bytes().to_vec(),
+					request_duration,
+				});
+			}
+		}
+	}
+}
+
 impl<B: BlockT, H: ExHashT> NetworkBehaviourEventProcess<debug_info::DebugInfoEvent>
 	for Behaviour<B, H> {
 	fn inject_event(&mut self, event: debug_info::DebugInfoEvent) {
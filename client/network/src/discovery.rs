@@ -63,10 +63,14 @@ use libp2p::swarm::toggle::Toggle;
 use libp2p::mdns::{Mdns, MdnsEvent};
 use libp2p::multiaddr::Protocol;
 use log::{debug, info, trace, warn};
+use lru::LruCache;
 use std::{cmp, collections::{HashMap, HashSet, VecDeque}, io, time::Duration};
 use std::task::{Context, Poll};
 use sp_core::hexdisplay::HexDisplay;
 
+/// Maximum number of reachable addresses to remember, one per node.
+const MAX_REACHABLE_ADDRESSES: usize = 256;
+
 /// `DiscoveryBehaviour` configuration.
 ///
 /// Note: In order to discover nodes or load and store values via Kademlia one has to add at least
@@ -74,6 +78,7 @@ use sp_core::hexdisplay::HexDisplay;
 pub struct DiscoveryConfig {
 	local_peer_id: PeerId,
 	user_defined: Vec<(PeerId, Multiaddr)>,
+	restored: Vec<(PeerId, Multiaddr)>,
 	allow_private_ipv4: bool,
 	allow_non_globals_in_dht: bool,
 	discovery_only_if_under_num: u64,
@@ -87,6 +92,7 @@ impl DiscoveryConfig {
 		DiscoveryConfig {
 			local_peer_id: local_public_key.into_peer_id(),
 			user_defined: Vec::new(),
+			restored: Vec::new(),
 			allow_private_ipv4: true,
 			allow_non_globals_in_dht: false,
 			discovery_only_if_under_num: std::u64::MAX,
@@ -115,6 +121,18 @@ impl DiscoveryConfig {
 		self
 	}
 
+	/// Set addresses of nodes that were reachable in a previous run, most recently reached first.
+	///
+	/// Contrary to user-defined nodes, these addresses are only remembered for as long as the
+	/// nodes remain reachable.
+	pub fn with_restored_addresses<I>(&mut self, restored: I) -> &mut Self
+	where
+		I: IntoIterator<Item = (PeerId, Multiaddr)>
+	{
+		self.restored.extend(restored);
+		self
+	}
+
 	/// Should private IPv4 addresses be reported?
 	pub fn allow_private_ipv4(&mut self, value: bool) -> &mut Self {
 		self.allow_private_ipv4 = value;
@@ -171,13 +189,24 @@ impl DiscoveryConfig {
 	}
 
 	/// Create a `DiscoveryBehaviour` from this config.
-	pub fn finish(self) -> DiscoveryBehaviour {
+	pub fn finish(mut self) -> DiscoveryBehaviour {
+		let mut reachable_addresses = LruCache::new(MAX_REACHABLE_ADDRESSES);
+		let mut pending_events = VecDeque::new();
+		for (peer_id, addr) in self.restored.into_iter().rev() {
+			for kad in self.kademlias.values_mut() {
+				kad.add_address(&peer_id, addr.clone())
+			}
+			pending_events.push_back(DiscoveryOut::Discovered(peer_id.clone()));
+			reachable_addresses.put(peer_id, addr);
+		}
+
 		DiscoveryBehaviour {
 			user_defined: self.user_defined,
+			reachable_addresses,
 			kademlias: self.kademlias,
 			next_kad_random_query: Delay::new(Duration::new(0, 0)),
 			duration_to_next_kad: Duration::from_secs(1),
-			pending_events: VecDeque::new(),
+			pending_events,
 			local_peer_id: self.local_peer_id,
 			num_connections: 0,
 			allow_private_ipv4: self.allow_private_ipv4,
@@ -204,6 +233,9 @@ pub struct DiscoveryBehaviour {
 	/// User-defined list of nodes and their addresses. Typically includes bootstrap nodes and
 	/// reserved nodes.
 	user_defined: Vec<(PeerId, Multiaddr)>,
+	/// Address of the latest successful outgoing connection to each node, including the
+	/// addresses restored from a previous run that haven't failed yet.
+	reachable_addresses: LruCache<PeerId, Multiaddr>,
 	/// Kademlia requests and answers.
 	kademlias: HashMap<ProtocolId, Kademlia<MemoryStore>>,
 	/// Discovers nodes on the local network.
@@ -238,6 +270,11 @@ impl DiscoveryBehaviour {
 		set.into_iter()
 	}
 
+	/// Returns the addresses we recently managed to connect to, most recently reached first.
+	pub fn reachable_addresses(&self) -> impl Iterator<Item = (&PeerId, &Multiaddr)> {
+		self.reachable_addresses.iter()
+	}
+
 	/// Adds a hard-coded address for the given peer, that never expires.
 	///
 	/// This adds an entry to the parameter that was passed to `new`.
@@ -430,6 +467,9 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 
 	fn inject_connection_established(&mut self, peer_id: &PeerId, conn: &ConnectionId, endpoint: &ConnectedPoint) {
 		self.num_connections += 1;
+		if let ConnectedPoint::Dialer { address } = endpoint {
+			self.reachable_addresses.put(peer_id.clone(), address.clone());
+		}
 		for k in self.kademlias.values_mut() {
 			NetworkBehaviour::inject_connection_established(k, peer_id, conn, endpoint)
 		}
@@ -460,6 +500,11 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 		addr: &Multiaddr,
 		error: &dyn std::error::Error
 	) {
+		if let Some(peer_id) = peer_id {
+			if self.reachable_addresses.peek(peer_id) == Some(addr) {
+				self.reachable_addresses.pop(peer_id);
+			}
+		}
 		for k in self.kademlias.values_mut() {
 			NetworkBehaviour::inject_addr_reach_failure(k, peer_id, addr, error)
 		}
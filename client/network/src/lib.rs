@@ -142,6 +142,8 @@
 //! bytes containing some data associated with this block announcement, e.g. a candidate message.
 //! - Notifications protocols that are registered using the `register_notifications_protocol`
 //! method. For example: `/paritytech/grandpa/1`. See below for more information.
+//! - Request-response protocols declared in the `request_response_protocols` of the network
+//! configuration. See below for more information.
 //!
 //! ## The legacy Substrate substream
 //!
@@ -187,6 +189,11 @@
 //!
 //! Each request is performed in a new separate substream.
 //!
+//! The API of `sc-network` allows one to declare user-defined request-response protocols in
+//! [`config::NetworkConfiguration::request_response_protocols`]. Requests are sent with
+//! `NetworkService::request`, while incoming requests are sent on the `inbound_queue` of the
+//! protocol configuration, together with a channel to send the response back on.
+//!
 //! ## Notifications protocols
 //!
 //! A so-called notifications protocol is defined as follow:
@@ -250,7 +257,9 @@ mod discovery;
 mod finality_requests;
 mod light_client_handler;
 mod on_demand_layer;
+mod peer_store;
 mod protocol;
+mod request_responses;
 mod schema;
 mod service;
 mod transport;
@@ -260,7 +269,7 @@ pub mod config;
 pub mod error;
 pub mod network_state;
 
-pub use service::{NetworkService, NetworkWorker};
+pub use service::{NetworkService, NetworkWorker, RequestFailure};
 pub use protocol::PeerInfo;
 pub use protocol::event::{Event, DhtEvent, ObservedRole};
 pub use protocol::sync::SyncState;
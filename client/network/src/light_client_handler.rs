@@ -1491,6 +1491,7 @@ mod tests {
 			bootnodes: Vec::new(),
 			reserved_only: false,
 			priority_groups: Vec::new(),
+			reputations: Vec::new(),
 		};
 		sc_peerset::Peerset::from_config(cfg)
 	}
@@ -0,0 +1,208 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Persistence of the known peers across restarts.
+//!
+//! The [`PeerStore`] saves the reputations of the peerset manager and the addresses of the nodes
+//! we recently managed to connect to in a file under the network configuration path. When they
+//! are loaded back, reputations are decayed by the time the node has been offline, the same way
+//! they would have decayed had the node kept running.
+
+use libp2p::{Multiaddr, PeerId};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+	fs,
+	io,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Name of the file the peers are stored in, under the network configuration path.
+const PEER_STORE_FILE: &str = "peers.json";
+
+/// Peers known from a previous run.
+#[derive(Debug, Default)]
+pub struct StoredPeers {
+	/// Reputations of the nodes, decayed by the time elapsed since they were saved.
+	pub reputations: Vec<(PeerId, i32)>,
+	/// Addresses of the nodes we could connect to, most recently reached first.
+	pub addresses: Vec<(PeerId, Multiaddr)>,
+}
+
+/// Content of the peer store file.
+#[derive(Debug, Serialize, Deserialize)]
+struct PeerStoreContent {
+	/// When the content was saved, in seconds since the UNIX epoch.
+	saved_at: u64,
+	/// Base58-encoded node identities and their reputation.
+	reputations: Vec<(String, i32)>,
+	/// Base58-encoded node identities and their address.
+	addresses: Vec<(String, String)>,
+}
+
+/// File storing the peers known to the network.
+#[derive(Debug)]
+pub struct PeerStore {
+	path: PathBuf,
+}
+
+impl PeerStore {
+	/// Creates a peer store saving its content under the given network configuration path.
+	pub fn new(net_config_path: &Path) -> Self {
+		PeerStore {
+			path: net_config_path.join(PEER_STORE_FILE),
+		}
+	}
+
+	/// Loads the peers saved by a previous run.
+	///
+	/// Returns no peers if nothing has been saved yet or if the file can't be read.
+	pub fn load(&self) -> StoredPeers {
+		let content = match fs::read(&self.path) {
+			Ok(content) => content,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return StoredPeers::default(),
+			Err(err) => {
+				warn!(target: "sub-libp2p", "Failed to read the peer store {:?}: {}", self.path, err);
+				return StoredPeers::default();
+			}
+		};
+
+		let content: PeerStoreContent = match serde_json::from_slice(&content) {
+			Ok(content) => content,
+			Err(err) => {
+				warn!(target: "sub-libp2p", "Failed to decode the peer store {:?}: {}", self.path, err);
+				return StoredPeers::default();
+			}
+		};
+
+		let downtime = unix_time().saturating_sub(content.saved_at);
+
+		StoredPeers {
+			reputations: content.reputations.into_iter()
+				.filter_map(|(peer_id, reputation)| {
+					let reputation = sc_peerset::decay_reputation(reputation, downtime);
+					if reputation == 0 {
+						return None;
+					}
+					Some((peer_id.parse().ok()?, reputation))
+				})
+				.collect(),
+			addresses: content.addresses.into_iter()
+				.filter_map(|(peer_id, addr)| Some((peer_id.parse().ok()?, addr.parse().ok()?)))
+				.collect(),
+		}
+	}
+
+	/// Replaces the content of the store with the given reputations and addresses.
+	///
+	/// Nodes with a neutral reputation aren't saved.
+	pub fn save<'a>(
+		&self,
+		reputations: impl IntoIterator<Item = (PeerId, i32)>,
+		addresses: impl IntoIterator<Item = (&'a PeerId, &'a Multiaddr)>,
+	) -> io::Result<()> {
+		let content = PeerStoreContent {
+			saved_at: unix_time(),
+			reputations: reputations.into_iter()
+				.filter(|(_, reputation)| *reputation != 0)
+				.map(|(peer_id, reputation)| (peer_id.to_base58(), reputation))
+				.collect(),
+			addresses: addresses.into_iter()
+				.map(|(peer_id, addr)| (peer_id.to_base58(), addr.to_string()))
+				.collect(),
+		};
+
+		// Write to a temporary file first, so that the store isn't corrupted if we stop midway.
+		let tmp_path = self.path.with_extension("json.tmp");
+		fs::write(&tmp_path, serde_json::to_vec(&content)?)?;
+		fs::rename(&tmp_path, &self.path)
+	}
+
+	/// Removes all the stored peers.
+	pub fn clear(&self) -> io::Result<()> {
+		match fs::remove_file(&self.path) {
+			Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+			res => res,
+		}
+	}
+}
+
+/// Number of seconds elapsed since the UNIX epoch.
+fn unix_time() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn peers_roundtrip() {
+		let dir = tempfile::tempdir().unwrap();
+		let store = PeerStore::new(dir.path());
+		assert!(store.load().reputations.is_empty());
+
+		let good = PeerId::random();
+		let neutral = PeerId::random();
+		let addr: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+
+		store.save(
+			vec![(good.clone(), 1000), (neutral, 0)],
+			vec![(&good, &addr)],
+		).unwrap();
+
+		let stored = store.load();
+		assert_eq!(stored.reputations, vec![(good.clone(), 1000)]);
+		assert_eq!(stored.addresses, vec![(good, addr)]);
+
+		store.clear().unwrap();
+		let stored = store.load();
+		assert!(stored.reputations.is_empty());
+		assert!(stored.addresses.is_empty());
+	}
+
+	#[test]
+	fn reputations_decay_while_offline() {
+		let dir = tempfile::tempdir().unwrap();
+		let store = PeerStore::new(dir.path());
+
+		let banned = PeerId::random();
+		let forgiven = PeerId::random();
+		let content = PeerStoreContent {
+			saved_at: unix_time() - 60,
+			reputations: vec![
+				(banned.to_base58(), i32::min_value()),
+				(forgiven.to_base58(), -10),
+			],
+			addresses: Vec::new(),
+		};
+		fs::write(dir.path().join(PEER_STORE_FILE), serde_json::to_vec(&content).unwrap()).unwrap();
+
+		let stored = store.load();
+		assert_eq!(stored.reputations.len(), 1);
+		assert_eq!(stored.reputations[0].0, banned);
+		// Allow for a few seconds elapsing while running the test.
+		let reputation = stored.reputations[0].1;
+		assert!(reputation >= sc_peerset::decay_reputation(i32::min_value(), 60));
+		assert!(reputation <= sc_peerset::decay_reputation(i32::min_value(), 65));
+	}
+}
@@ -499,6 +499,11 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		self.behaviour.peerset_debug_info()
 	}
 
+	/// Returns the reputations of all the nodes known to the peerset manager.
+	pub fn peerset_reputations(&mut self) -> Vec<(PeerId, i32)> {
+		self.behaviour.peerset_reputations()
+	}
+
 	/// Returns the number of peers we're connected to.
 	pub fn num_connected_peers(&self) -> usize {
 		self.context_data.peers.values().count()
@@ -2159,6 +2164,7 @@ mod tests {
 				bootnodes: Vec::new(),
 				reserved_only: false,
 				priority_groups: Vec::new(),
+				reputations: Vec::new(),
 			},
 			Box::new(DefaultBlockAnnounceValidator::new(client.clone())),
 			None,
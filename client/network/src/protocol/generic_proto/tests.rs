@@ -80,6 +80,7 @@ fn build_nodes() -> (Swarm<CustomProtoWithAddr>, Swarm<CustomProtoWithAddr>) {
 			},
 			reserved_only: false,
 			priority_groups: Vec::new(),
+			reputations: Vec::new(),
 		});
 
 		let behaviour = CustomProtoWithAddr {
@@ -0,0 +1,729 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `NetworkBehaviour` implementation of generic request-response protocols.
+//!
+//! Each protocol is declared at initialization with a [`ProtocolConfig`], which names it and
+//! bounds the size of its requests and responses. Every request is sent on a separate substream,
+//! which gets closed after the response has been sent back. Requests and responses are opaque
+//! length-prefixed bytes, encoding and decoding them is left to the component using the protocol.
+//!
+//! Requests received from remotes are sent on the `inbound_queue` of their protocol, together
+//! with a channel to send the response back on. Protocols without an inbound queue can only be
+//! used to send requests and are not advertised to remotes.
+//!
+//! Failing to open a substream, for instance because the remote doesn't support the protocol,
+//! fails the request without closing the connection.
+
+use crate::error::Error;
+use bytes::Bytes;
+use futures::{
+	channel::{mpsc, oneshot},
+	future::{self, AbortHandle, Aborted, BoxFuture},
+	prelude::*,
+	stream::FuturesUnordered,
+};
+use futures_timer::Delay;
+use libp2p::{
+	core::{
+		Multiaddr,
+		PeerId,
+		connection::ConnectionId,
+		upgrade::{InboundUpgrade, OutboundUpgrade, ReadOneError, UpgradeInfo},
+		upgrade::{NegotiationError, UpgradeError, read_one, write_one},
+	},
+	swarm::{
+		KeepAlive,
+		NegotiatedSubstream,
+		NetworkBehaviour,
+		NetworkBehaviourAction,
+		NotifyHandler,
+		PollParameters,
+		ProtocolsHandler,
+		ProtocolsHandlerEvent,
+		ProtocolsHandlerUpgrErr,
+		SubstreamProtocol,
+	},
+};
+use sc_peerset::{PeersetHandle, ReputationChange};
+use std::{
+	borrow::Cow,
+	collections::{HashMap, HashSet, VecDeque},
+	io,
+	iter,
+	sync::Arc,
+	task::{Context, Poll},
+	time::Duration,
+};
+use wasm_timer::Instant;
+
+/// Reputation change for a peer when a request timed out.
+const TIMEOUT_REPUTATION_CHANGE: i32 = -(1 << 8);
+
+/// Reputation change for a peer when its response exceeds the maximum response size.
+const RESPONSE_TOO_LARGE_REPUTATION_CHANGE: i32 = -(1 << 12);
+
+/// How long a connection is kept alive after its last request or response, if nothing else
+/// keeps it open.
+const INACTIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type RequestId = u64;
+
+/// Configuration of a single request-response protocol.
+#[derive(Debug, Clone)]
+pub struct ProtocolConfig {
+	/// Name of the protocol on the wire. Should be something like `/foo/bar`.
+	pub name: Cow<'static, str>,
+
+	/// Maximum allowed size, in bytes, of an incoming request. Larger requests are dropped without
+	/// being answered, the connection to the remote stays open.
+	pub max_request_size: usize,
+
+	/// Maximum allowed size, in bytes, of a response. Larger responses fail the request and lower
+	/// the reputation of the remote.
+	pub max_response_size: usize,
+
+	/// Duration after which requests sent on this protocol are considered failed. Remotes not
+	/// answering in time have their reputation lowered.
+	pub request_timeout: Duration,
+
+	/// Channel on which the requests received on this protocol are sent.
+	///
+	/// If `None`, the protocol isn't advertised to remotes and incoming requests are refused.
+	/// Requests received while the channel is full are dropped without being answered.
+	pub inbound_queue: Option<mpsc::Sender<IncomingRequest>>,
+}
+
+impl ProtocolConfig {
+	/// Create an outbound-only protocol configuration with the following options:
+	///
+	/// - max. request size = 1 MiB
+	/// - max. response size = 1 MiB
+	/// - request timeout = 15s
+	pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+		ProtocolConfig {
+			name: name.into(),
+			max_request_size: 1024 * 1024,
+			max_response_size: 1024 * 1024,
+			request_timeout: Duration::from_secs(15),
+			inbound_queue: None,
+		}
+	}
+}
+
+/// A request received from a remote on one of the registered protocols.
+#[derive(Debug)]
+pub struct IncomingRequest {
+	/// Who sent the request.
+	pub peer: PeerId,
+
+	/// The request, as sent by the remote.
+	pub payload: Vec<u8>,
+
+	/// Channel to send the response on. Dropping it without sending anything closes the
+	/// substream without answering the remote.
+	pub pending_response: oneshot::Sender<Vec<u8>>,
+}
+
+/// Reasons why a request sent to a remote failed.
+#[derive(Debug, thiserror::Error)]
+pub enum RequestFailure {
+	/// No protocol with this name has been registered.
+	#[error("unknown protocol")]
+	UnknownProtocol,
+	/// We aren't connected to the target.
+	#[error("not connected to the target")]
+	NotConnected,
+	/// The remote doesn't support the protocol.
+	#[error("protocol not supported by the remote")]
+	UnsupportedProtocol,
+	/// The remote didn't answer within the timeout of the protocol.
+	#[error("request timed out")]
+	Timeout,
+	/// The response is larger than the maximum response size of the protocol.
+	#[error("response too large")]
+	ResponseTooLarge,
+	/// The connection to the remote was closed before the response arrived.
+	#[error("connection closed")]
+	ConnectionClosed,
+	/// Writing the request or reading the response failed.
+	#[error("network error: {0}")]
+	Network(#[source] io::Error),
+	/// The network worker was shut down before the request finished.
+	#[error("network worker shut down")]
+	Obsolete,
+}
+
+/// Event generated by the request-response behaviour.
+#[derive(Debug)]
+pub enum Event {
+	/// A remote sent us a request and we have sent back the response.
+	AnsweredRequest {
+		/// Peer which sent us the request.
+		peer: PeerId,
+		/// Name of the protocol of the request.
+		protocol: Cow<'static, str>,
+		/// Time it took to build the response.
+		build_time: Duration,
+	},
+	/// A request has been sent to a remote.
+	RequestStarted {
+		/// Peer the request has been sent to.
+		peer: PeerId,
+		/// Name of the protocol of the request.
+		protocol: Cow<'static, str>,
+	},
+	/// A request sent to a remote finished, successfully or not.
+	RequestFinished {
+		/// Peer the request had been sent to.
+		peer: PeerId,
+		/// Name of the protocol of the request.
+		protocol: Cow<'static, str>,
+		/// How long before the response came or the request failed.
+		request_duration: Duration,
+	},
+}
+
+/// A request sent to a remote and waiting for its response.
+struct PendingRequest {
+	/// Peer the request has been sent to.
+	peer: PeerId,
+	/// Name of the protocol of the request.
+	protocol: Cow<'static, str>,
+	/// When the request has been sent.
+	started: Instant,
+	/// Channel to send the outcome of the request on.
+	pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+	/// Handle to cancel the timeout of the request once it finished.
+	timeout: AbortHandle,
+}
+
+/// The request-response protocols handling behaviour.
+pub struct RequestResponsesBehaviour {
+	/// Registered protocols, by name.
+	protocols: HashMap<Cow<'static, str>, ProtocolConfig>,
+	/// Names and max. request sizes of the protocols accepting incoming requests.
+	inbound_protocols: Arc<Vec<(Cow<'static, str>, usize)>>,
+	/// Peers we're connected to.
+	connected: HashSet<PeerId>,
+	/// Requests sent to remotes and waiting for their response.
+	pending_requests: HashMap<RequestId, PendingRequest>,
+	/// Identifier of the next request to send.
+	next_request_id: RequestId,
+	/// Timeouts of the sent requests, yielding the identifier of the request. Timeouts of the
+	/// requests which finished beforehand are aborted.
+	timeouts: FuturesUnordered<BoxFuture<'static, Result<RequestId, Aborted>>>,
+	/// Futures sending back the responses to incoming requests.
+	responses: FuturesUnordered<BoxFuture<'static, Option<Event>>>,
+	/// Events to return as soon as possible from `poll`.
+	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol, Event>>,
+	/// Handle to use for reporting misbehaviour of peers.
+	peerset: PeersetHandle,
+}
+
+impl RequestResponsesBehaviour {
+	/// Initializes the behaviour with the given protocols.
+	///
+	/// Returns an error if the same protocol name is registered more than once.
+	pub fn new(
+		list: impl IntoIterator<Item = ProtocolConfig>,
+		peerset: PeersetHandle,
+	) -> Result<Self, Error> {
+		let mut protocols = HashMap::new();
+		for protocol in list {
+			if protocols.contains_key(&protocol.name) {
+				return Err(Error::DuplicateRequestResponseProtocol { protocol: protocol.name });
+			}
+			protocols.insert(protocol.name.clone(), protocol);
+		}
+
+		let inbound_protocols = protocols.values()
+			.filter(|p| p.inbound_queue.is_some())
+			.map(|p| (p.name.clone(), p.max_request_size))
+			.collect();
+
+		Ok(RequestResponsesBehaviour {
+			protocols,
+			inbound_protocols: Arc::new(inbound_protocols),
+			connected: HashSet::new(),
+			pending_requests: HashMap::new(),
+			next_request_id: 0,
+			timeouts: FuturesUnordered::new(),
+			responses: FuturesUnordered::new(),
+			pending_events: VecDeque::new(),
+			peerset,
+		})
+	}
+
+	/// Sends a request to the given target on the given protocol.
+	///
+	/// The response, or the reason why the request failed, is sent on `pending_response`.
+	pub fn send_request(
+		&mut self,
+		target: &PeerId,
+		protocol: &str,
+		request: Vec<u8>,
+		pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+	) {
+		let config = match self.protocols.get(protocol) {
+			Some(config) => config,
+			None => {
+				let _ = pending_response.send(Err(RequestFailure::UnknownProtocol));
+				return;
+			}
+		};
+
+		if !self.connected.contains(target) {
+			let _ = pending_response.send(Err(RequestFailure::NotConnected));
+			return;
+		}
+
+		let request_id = self.next_request_id;
+		self.next_request_id += 1;
+
+		log::trace!(
+			target: "sub-libp2p",
+			"Sending request {} on {} to {}",
+			request_id,
+			config.name,
+			target,
+		);
+
+		let (timeout, timeout_handle) = future::abortable(
+			Delay::new(config.request_timeout).map(move |_| request_id)
+		);
+		self.timeouts.push(timeout.boxed());
+		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
+			peer_id: target.clone(),
+			handler: NotifyHandler::Any,
+			event: OutboundProtocol {
+				request_id,
+				request,
+				max_response_size: config.max_response_size,
+				request_timeout: config.request_timeout,
+				protocol: Bytes::copy_from_slice(config.name.as_bytes()),
+			},
+		});
+		self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(Event::RequestStarted {
+			peer: target.clone(),
+			protocol: config.name.clone(),
+		}));
+		self.pending_requests.insert(request_id, PendingRequest {
+			peer: target.clone(),
+			protocol: config.name.clone(),
+			started: Instant::now(),
+			pending_response,
+			timeout: timeout_handle,
+		});
+	}
+
+	/// Sends the outcome of a pending request to its requester, lowering the reputation of the
+	/// remote if it misbehaved. Does nothing if the request has already finished.
+	fn finish_request(&mut self, request_id: RequestId, result: Result<Vec<u8>, RequestFailure>) {
+		let request = match self.pending_requests.remove(&request_id) {
+			Some(request) => request,
+			None => return,
+		};
+		request.timeout.abort();
+
+		match &result {
+			Ok(_) => {},
+			Err(RequestFailure::Timeout) =>
+				self.peerset.report_peer(
+					request.peer.clone(),
+					ReputationChange::new(TIMEOUT_REPUTATION_CHANGE, "request timeout"),
+				),
+			Err(RequestFailure::ResponseTooLarge) =>
+				self.peerset.report_peer(
+					request.peer.clone(),
+					ReputationChange::new(RESPONSE_TOO_LARGE_REPUTATION_CHANGE, "response too large"),
+				),
+			Err(err) => log::debug!(
+				target: "sub-libp2p",
+				"Request {} on {} to {} failed: {}",
+				request_id,
+				request.protocol,
+				request.peer,
+				err,
+			),
+		}
+
+		self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(Event::RequestFinished {
+			peer: request.peer,
+			protocol: request.protocol,
+			request_duration: request.started.elapsed(),
+		}));
+		let _ = request.pending_response.send(result);
+	}
+
+	/// Callback, invoked when a new request has been received from a remote.
+	fn on_request(
+		&mut self,
+		peer: PeerId,
+		protocol: Cow<'static, str>,
+		payload: Vec<u8>,
+		mut substream: NegotiatedSubstream,
+	) {
+		let queue = match self.protocols.get_mut(&protocol).and_then(|p| p.inbound_queue.as_mut()) {
+			Some(queue) => queue,
+			None => return,
+		};
+
+		let (tx, rx) = oneshot::channel();
+		let request = IncomingRequest { peer: peer.clone(), payload, pending_response: tx };
+		if let Err(err) = queue.try_send(request) {
+			log::debug!(
+				target: "sub-libp2p",
+				"Dropping request on {} from {}: {}",
+				protocol,
+				peer,
+				err,
+			);
+			return;
+		}
+
+		let started = Instant::now();
+		let future = async move {
+			// The response channel is dropped if the request isn't answered, in which case we
+			// close the substream.
+			let response = rx.await.ok()?;
+			if let Err(err) = write_one(&mut substream, response).await {
+				log::debug!(
+					target: "sub-libp2p",
+					"Error writing response on {} to {}: {}",
+					protocol,
+					peer,
+					err,
+				);
+				return None;
+			}
+
+			Some(Event::AnsweredRequest { peer, protocol, build_time: started.elapsed() })
+		};
+		self.responses.push(future.boxed());
+	}
+}
+
+impl NetworkBehaviour for RequestResponsesBehaviour {
+	type ProtocolsHandler = RequestResponsesHandler;
+	type OutEvent = Event;
+
+	fn new_handler(&mut self) -> Self::ProtocolsHandler {
+		RequestResponsesHandler::new(InboundProtocol {
+			protocols: self.inbound_protocols.clone(),
+		})
+	}
+
+	fn addresses_of_peer(&mut self, _: &PeerId) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn inject_connected(&mut self, peer: &PeerId) {
+		self.connected.insert(peer.clone());
+	}
+
+	fn inject_disconnected(&mut self, peer: &PeerId) {
+		self.connected.remove(peer);
+
+		let closed = self.pending_requests.iter()
+			.filter(|(_, request)| request.peer == *peer)
+			.map(|(id, _)| *id)
+			.collect::<Vec<_>>();
+		for request_id in closed {
+			self.finish_request(request_id, Err(RequestFailure::ConnectionClosed));
+		}
+	}
+
+	fn inject_event(
+		&mut self,
+		peer: PeerId,
+		_connection: ConnectionId,
+		event: NodeEvent<NegotiatedSubstream>,
+	) {
+		match event {
+			NodeEvent::Request { protocol, payload, substream } =>
+				self.on_request(peer, protocol, payload, substream),
+			NodeEvent::Response { request_id, result } =>
+				self.finish_request(request_id, result),
+		}
+	}
+
+	fn poll(&mut self, cx: &mut Context, _: &mut impl PollParameters)
+		-> Poll<NetworkBehaviourAction<OutboundProtocol, Event>>
+	{
+		while let Poll::Ready(Some(timeout)) = self.timeouts.poll_next_unpin(cx) {
+			if let Ok(request_id) = timeout {
+				self.finish_request(request_id, Err(RequestFailure::Timeout));
+			}
+		}
+
+		while let Poll::Ready(Some(event)) = self.responses.poll_next_unpin(cx) {
+			if let Some(event) = event {
+				self.pending_events.push_back(NetworkBehaviourAction::GenerateEvent(event));
+			}
+		}
+
+		if let Some(ev) = self.pending_events.pop_front() {
+			return Poll::Ready(ev);
+		}
+
+		Poll::Pending
+	}
+}
+
+/// Handler of the request-response protocols for a single connection.
+///
+/// Opens a substream for each request it is notified of, and reports failing to open it as the
+/// outcome of the request instead of closing the connection.
+pub struct RequestResponsesHandler {
+	/// Upgrade to use for incoming substreams.
+	inbound: InboundProtocol,
+	/// Number of requests whose substream is being opened or negotiated.
+	pending_outbound: usize,
+	/// Whether to keep the connection alive.
+	keep_alive: KeepAlive,
+	/// Events to return as soon as possible from `poll`.
+	pending_events: VecDeque<
+		ProtocolsHandlerEvent<OutboundProtocol, RequestId, NodeEvent<NegotiatedSubstream>, void::Void>
+	>,
+}
+
+impl RequestResponsesHandler {
+	/// Builds a new handler, accepting incoming requests with the given upgrade.
+	fn new(inbound: InboundProtocol) -> Self {
+		RequestResponsesHandler {
+			inbound,
+			pending_outbound: 0,
+			// Connections that never carry a request are not kept open by this handler.
+			keep_alive: KeepAlive::Until(Instant::now() + INACTIVE_TIMEOUT),
+			pending_events: VecDeque::new(),
+		}
+	}
+
+	/// Keeps the connection alive for a while if no more requests are being sent.
+	fn refresh_keep_alive(&mut self) {
+		if self.pending_outbound == 0 {
+			self.keep_alive = KeepAlive::Until(Instant::now() + INACTIVE_TIMEOUT);
+		}
+	}
+}
+
+impl ProtocolsHandler for RequestResponsesHandler {
+	type InEvent = OutboundProtocol;
+	type OutEvent = NodeEvent<NegotiatedSubstream>;
+	type Error = void::Void;
+	type InboundProtocol = InboundProtocol;
+	type OutboundProtocol = OutboundProtocol;
+	type OutboundOpenInfo = RequestId;
+
+	fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol> {
+		SubstreamProtocol::new(self.inbound.clone())
+	}
+
+	fn inject_fully_negotiated_inbound(
+		&mut self,
+		event: <Self::InboundProtocol as InboundUpgrade<NegotiatedSubstream>>::Output
+	) {
+		self.refresh_keep_alive();
+		self.pending_events.push_back(ProtocolsHandlerEvent::Custom(event));
+	}
+
+	fn inject_fully_negotiated_outbound(
+		&mut self,
+		event: <Self::OutboundProtocol as OutboundUpgrade<NegotiatedSubstream>>::Output,
+		_: RequestId,
+	) {
+		self.pending_outbound -= 1;
+		self.refresh_keep_alive();
+		self.pending_events.push_back(ProtocolsHandlerEvent::Custom(event));
+	}
+
+	fn inject_event(&mut self, request: OutboundProtocol) {
+		self.pending_outbound += 1;
+		self.keep_alive = KeepAlive::Yes;
+
+		// The timeout covers the negotiation as well as sending the request and reading the
+		// response, which is why it is the same as the one of the request.
+		let request_id = request.request_id;
+		let timeout = request.request_timeout;
+		self.pending_events.push_back(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+			protocol: SubstreamProtocol::new(request).with_timeout(timeout),
+			info: request_id,
+		});
+	}
+
+	fn inject_dial_upgrade_error(
+		&mut self,
+		request_id: RequestId,
+		err: ProtocolsHandlerUpgrErr<ReadOneError>,
+	) {
+		self.pending_outbound -= 1;
+		self.refresh_keep_alive();
+
+		let failure = match err {
+			ProtocolsHandlerUpgrErr::Timeout | ProtocolsHandlerUpgrErr::Timer =>
+				RequestFailure::Timeout,
+			ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Select(NegotiationError::Failed)) =>
+				RequestFailure::UnsupportedProtocol,
+			ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Select(NegotiationError::ProtocolError(err))) =>
+				RequestFailure::Network(io::Error::new(io::ErrorKind::Other, err)),
+			ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(ReadOneError::TooLarge { .. })) =>
+				RequestFailure::ResponseTooLarge,
+			ProtocolsHandlerUpgrErr::Upgrade(UpgradeError::Apply(ReadOneError::Io(err))) =>
+				RequestFailure::Network(err),
+		};
+
+		let event = NodeEvent::Response { request_id, result: Err(failure) };
+		self.pending_events.push_back(ProtocolsHandlerEvent::Custom(event));
+	}
+
+	fn inject_listen_upgrade_error(&mut self, err: ProtocolsHandlerUpgrErr<ReadOneError>) {
+		log::debug!(target: "sub-libp2p", "Error reading incoming request: {:?}", err);
+	}
+
+	fn connection_keep_alive(&self) -> KeepAlive {
+		self.keep_alive
+	}
+
+	fn poll(
+		&mut self,
+		_: &mut Context,
+	) -> Poll<ProtocolsHandlerEvent<Self::OutboundProtocol, Self::OutboundOpenInfo, Self::OutEvent, Self::Error>> {
+		if let Some(event) = self.pending_events.pop_front() {
+			return Poll::Ready(event);
+		}
+
+		Poll::Pending
+	}
+}
+
+/// Output type of inbound and outbound substream upgrades.
+#[derive(Debug)]
+pub enum NodeEvent<T> {
+	/// Incoming request from remote and substream to use for the response.
+	Request {
+		/// Name of the protocol the request has been received on.
+		protocol: Cow<'static, str>,
+		/// The request, as sent by the remote.
+		payload: Vec<u8>,
+		/// Substream to send the response on.
+		substream: T,
+	},
+	/// Outcome of a request sent to a remote.
+	Response {
+		/// Local identifier of the request.
+		request_id: RequestId,
+		/// The response of the remote, or the reason why none has been received.
+		result: Result<Vec<u8>, RequestFailure>,
+	},
+}
+
+/// Substream upgrade protocol.
+///
+/// Reads an incoming request on any of the protocols accepting them, which then becomes visible
+/// to the behaviour through `inject_event`.
+#[derive(Debug, Clone)]
+pub struct InboundProtocol {
+	/// Names and max. request sizes of the supported protocols.
+	protocols: Arc<Vec<(Cow<'static, str>, usize)>>,
+}
+
+impl UpgradeInfo for InboundProtocol {
+	type Info = Bytes;
+	type InfoIter = std::vec::IntoIter<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		self.protocols.iter()
+			.map(|(name, _)| Bytes::copy_from_slice(name.as_bytes()))
+			.collect::<Vec<_>>()
+			.into_iter()
+	}
+}
+
+impl<T> InboundUpgrade<T> for InboundProtocol
+where
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_inbound(self, mut s: T, info: Self::Info) -> Self::Future {
+		let (protocol, max_request_size) = self.protocols.iter()
+			.find(|(name, _)| name.as_bytes() == &info[..])
+			.cloned()
+			.expect("the negotiated protocol is one of those returned by `protocol_info`; qed");
+
+		async move {
+			let payload = read_one(&mut s, max_request_size).await?;
+			Ok(NodeEvent::Request { protocol, payload, substream: s })
+		}.boxed()
+	}
+}
+
+/// Substream upgrade protocol.
+///
+/// Sends a request to remote and awaits the response.
+///
+/// Failing to write the request or read the response doesn't fail the upgrade but is reported
+/// in the output, which lets the behaviour fail the request right away.
+#[derive(Debug, Clone)]
+pub struct OutboundProtocol {
+	/// Local identifier for the request. Used to associate it with a response.
+	request_id: RequestId,
+	/// The request to send.
+	request: Vec<u8>,
+	/// The max. response length in bytes.
+	max_response_size: usize,
+	/// How long to wait for the response, including opening the substream.
+	request_timeout: Duration,
+	/// The protocol to use for upgrade negotiation.
+	protocol: Bytes,
+}
+
+impl UpgradeInfo for OutboundProtocol {
+	type Info = Bytes;
+	type InfoIter = iter::Once<Self::Info>;
+
+	fn protocol_info(&self) -> Self::InfoIter {
+		iter::once(self.protocol.clone())
+	}
+}
+
+impl<T> OutboundUpgrade<T> for OutboundProtocol
+where
+	T: AsyncRead + AsyncWrite + Unpin + Send + 'static
+{
+	type Output = NodeEvent<T>;
+	type Error = ReadOneError;
+	type Future = BoxFuture<'static, Result<Self::Output, Self::Error>>;
+
+	fn upgrade_outbound(self, mut s: T, _: Self::Info) -> Self::Future {
+		async move {
+			let result = match write_one(&mut s, &self.request).await {
+				Ok(()) => read_one(&mut s, self.max_response_size).await.map_err(|err| match err {
+					ReadOneError::TooLarge { .. } => RequestFailure::ResponseTooLarge,
+					ReadOneError::Io(err) => RequestFailure::Network(err),
+				}),
+				Err(err) => Err(RequestFailure::Network(err)),
+			};
+
+			Ok(NodeEvent::Response { request_id: self.request_id, result })
+		}.boxed()
+	}
+}
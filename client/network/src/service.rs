@@ -37,11 +37,13 @@ use crate::{
 		NetworkState, NotConnectedPeer as NetworkStateNotConnectedPeer, Peer as NetworkStatePeer,
 	},
 	on_demand_layer::AlwaysBadChecker,
-	light_client_handler, block_requests, finality_requests,
+	peer_store::{PeerStore, StoredPeers},
+	light_client_handler, block_requests, finality_requests, request_responses,
 	protocol::{self, event::Event, LegacyConnectionKillError, sync::SyncState, PeerInfo, Protocol},
 	transport, ReputationChange,
 };
-use futures::prelude::*;
+use futures::{channel::oneshot, prelude::*};
+use futures_timer::Delay;
 use libp2p::{PeerId, Multiaddr};
 use libp2p::core::{ConnectedPoint, Executor, connection::{ConnectionError, PendingConnectionError}, either::EitherError};
 use libp2p::kad::record;
@@ -62,7 +64,7 @@ use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnbound
 use std::{
 	borrow::{Borrow, Cow},
 	collections::HashSet,
-	fs, io,
+	fs, io, iter,
 	marker::PhantomData,
 	num:: NonZeroUsize,
 	pin::Pin,
@@ -72,8 +74,11 @@ use std::{
 		Arc,
 	},
 	task::Poll,
+	time::Duration,
 };
 
+pub use crate::request_responses::RequestFailure;
+
 mod out_events;
 #[cfg(test)]
 mod tests;
@@ -127,9 +132,22 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let (to_worker, from_worker) = tracing_unbounded("mpsc_network_worker");
 
-		if let Some(path) = params.network_config.net_config_path {
-			fs::create_dir_all(&path)?;
-		}
+		let peer_store = match params.network_config.net_config_path {
+			Some(ref path) => {
+				fs::create_dir_all(path)?;
+				Some(PeerStore::new(path))
+			}
+			None => None,
+		};
+
+		let stored_peers = match peer_store {
+			Some(ref peer_store) if params.network_config.reset_peer_store => {
+				peer_store.clear()?;
+				StoredPeers::default()
+			}
+			Some(ref peer_store) => peer_store.load(),
+			None => StoredPeers::default(),
+		};
 
 		// List of multiaddresses that we know in the network.
 		let mut known_addresses = Vec::new();
@@ -201,6 +219,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			bootnodes,
 			reserved_only: params.network_config.non_reserved_mode == NonReservedPeerMode::Deny,
 			priority_groups,
+			reputations: stored_peers.reputations,
 		};
 
 		// Private and public keys configuration.
@@ -269,10 +288,20 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					peerset_handle.clone(),
 				)
 			};
+			let request_responses = request_responses::RequestResponsesBehaviour::new(
+				params.network_config.request_response_protocols.clone(),
+				peerset_handle.clone(),
+			)?;
 
 			let discovery_config = {
 				let mut config = DiscoveryConfig::new(local_public.clone());
 				config.with_user_defined(known_addresses);
+				config.with_restored_addresses(stored_peers.addresses.into_iter().filter(|(_, addr)| {
+					ensure_addresses_consistent_with_transport(
+						iter::once(addr),
+						&params.network_config.transport,
+					).is_ok()
+				}));
 				config.discovery_limit(u64::from(params.network_config.out_peers) + 15);
 				config.add_protocol(params.protocol_id.clone());
 				config.allow_non_globals_in_dht(params.network_config.allow_non_globals_in_dht);
@@ -299,6 +328,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				block_requests,
 				finality_proof_requests,
 				light_client_handler,
+				request_responses,
 				discovery_config
 			);
 
@@ -366,6 +396,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			event_streams: out_events::OutChannels::new(params.metrics_registry.as_ref())?,
 			metrics,
 			boot_node_ids,
+			peer_store,
+			next_peer_store_save: Delay::new(PEER_STORE_SAVE_INTERVAL),
 		})
 	}
 
@@ -424,6 +456,20 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 		self.network_service.add_known_address(peer_id, addr);
 	}
 
+	/// Saves the reputations of the nodes and the addresses we could reach to the peer store, if
+	/// any.
+	fn save_peer_store(&mut self) {
+		let peer_store = match self.peer_store {
+			Some(ref peer_store) => peer_store,
+			None => return,
+		};
+
+		let reputations = self.network_service.user_protocol_mut().peerset_reputations();
+		if let Err(err) = peer_store.save(reputations, self.network_service.reachable_addresses()) {
+			warn!(target: "sub-libp2p", "Failed to save the peer store: {}", err);
+		}
+	}
+
 	/// Return a `NetworkService` that can be shared through the code base and can be used to
 	/// manipulate the worker.
 	pub fn service(&self) -> &Arc<NetworkService<B, H>> {
@@ -560,6 +606,33 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		});
 	}
 
+	/// Sends a request to a peer on one of the request-response protocols declared in the
+	/// network configuration, and returns its response.
+	///
+	/// The request fails if we aren't connected to `target`, or if it doesn't answer within the
+	/// timeout of the protocol. Peers not answering in time or sending responses larger than the
+	/// protocol allows have their reputation lowered.
+	pub async fn request(
+		&self,
+		target: PeerId,
+		protocol: impl Into<Cow<'static, str>>,
+		request: Vec<u8>,
+	) -> Result<Vec<u8>, RequestFailure> {
+		let (tx, rx) = oneshot::channel();
+		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::Request {
+			target,
+			protocol: protocol.into(),
+			request,
+			pending_response: tx,
+		});
+
+		match rx.await {
+			Ok(result) => result,
+			// The channel can only be closed if the network worker no longer exists.
+			Err(_) => Err(RequestFailure::Obsolete),
+		}
+	}
+
 	/// Returns a stream containing the events that happen on the network.
 	///
 	/// If this method is called multiple times, the events are duplicated.
@@ -834,11 +907,20 @@ enum ServiceToWorkerMsg<B: BlockT, H: ExHashT> {
 		engine_id: ConsensusEngineId,
 		protocol_name: Cow<'static, [u8]>,
 	},
+	Request {
+		target: PeerId,
+		protocol: Cow<'static, str>,
+		request: Vec<u8>,
+		pending_response: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+	},
 	DisconnectPeer(PeerId),
 	UpdateChain,
 	OwnBlockImported(B::Hash, NumberFor<B>),
 }
 
+/// Interval at which the known nodes are saved to the peer store.
+const PEER_STORE_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
 /// Main network worker. Must be polled in order for the network to advance.
 ///
 /// You are encouraged to poll this in a separate background thread or task.
@@ -866,6 +948,10 @@ pub struct NetworkWorker<B: BlockT + 'static, H: ExHashT> {
 	metrics: Option<Metrics>,
 	/// The `PeerId`'s of all boot nodes.
 	boot_node_ids: Arc<HashSet<PeerId>>,
+	/// Where the known nodes are saved across restarts, if anywhere.
+	peer_store: Option<PeerStore>,
+	/// Fires when the peer store must be saved again.
+	next_peer_store_save: Delay,
 }
 
 struct Metrics {
@@ -1116,6 +1202,12 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 	fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context) -> Poll<Self::Output> {
 		let this = &mut *self;
 
+		// Periodically save the known nodes, so that they aren't lost if the node crashes.
+		while let Poll::Ready(()) = this.next_peer_store_save.poll_unpin(cx) {
+			this.next_peer_store_save.reset(PEER_STORE_SAVE_INTERVAL);
+			this.save_peer_store();
+		}
+
 		// Poll the import queue for actions to perform.
 		this.import_queue.poll_actions(cx, &mut NetworkLink {
 			protocol: &mut this.network_service,
@@ -1173,6 +1265,8 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 					this.network_service
 						.register_notifications_protocol(engine_id, protocol_name);
 				},
+				ServiceToWorkerMsg::Request { target, protocol, request, pending_response } =>
+					this.network_service.send_request(&target, &protocol, request, pending_response),
 				ServiceToWorkerMsg::DisconnectPeer(who) =>
 					this.network_service.user_protocol_mut().disconnect_peer(&who),
 				ServiceToWorkerMsg::UpdateChain =>
@@ -1267,11 +1361,11 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 						let reason = match cause {
 							ConnectionError::IO(_) => "transport-error",
 							ConnectionError::Handler(NodeHandlerWrapperError::Handler(EitherError::A(EitherError::A(
-								EitherError::A(EitherError::A(EitherError::B(
-								EitherError::A(PingFailure::Timeout)))))))) => "ping-timeout",
+								EitherError::A(EitherError::A(EitherError::A(EitherError::B(
+								EitherError::A(PingFailure::Timeout))))))))) => "ping-timeout",
 							ConnectionError::Handler(NodeHandlerWrapperError::Handler(EitherError::A(EitherError::A(
-								EitherError::A(EitherError::A(EitherError::A(
-								EitherError::B(LegacyConnectionKillError)))))))) =>	"force-closed",
+								EitherError::A(EitherError::A(EitherError::A(EitherError::A(
+								EitherError::B(LegacyConnectionKillError))))))))) =>	"force-closed",
 							ConnectionError::Handler(NodeHandlerWrapperError::Handler(_)) => "protocol-error",
 							ConnectionError::Handler(NodeHandlerWrapperError::KeepAliveTimeout) => "keep-alive-timeout",
 						};
@@ -1430,6 +1524,12 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 impl<B: BlockT + 'static, H: ExHashT> Unpin for NetworkWorker<B, H> {
 }
 
+impl<B: BlockT + 'static, H: ExHashT> Drop for NetworkWorker<B, H> {
+	fn drop(&mut self) {
+		self.save_peer_store();
+	}
+}
+
 /// Turns bytes that are potentially UTF-8 into a reasonable representable string.
 ///
 /// Meant to be used only for debugging or metrics-reporting purposes.
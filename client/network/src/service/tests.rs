@@ -16,8 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{config, Event, NetworkService, NetworkWorker};
+use crate::{config, Event, NetworkService, NetworkWorker, RequestFailure};
 
+use assert_matches::assert_matches;
 use libp2p::PeerId;
 use futures::prelude::*;
 use sp_runtime::traits::{Block as BlockT, Header as _};
@@ -29,6 +30,11 @@ type TestNetworkService = NetworkService<
 	substrate_test_runtime_client::runtime::Hash,
 >;
 
+type TestNetworkWorker = NetworkWorker<
+	substrate_test_runtime_client::runtime::Block,
+	substrate_test_runtime_client::runtime::Hash,
+>;
+
 /// Builds a full node to be used for testing. Returns the node service and its associated events
 /// stream.
 ///
@@ -37,6 +43,20 @@ type TestNetworkService = NetworkService<
 fn build_test_full_node(config: config::NetworkConfiguration)
 	-> (Arc<TestNetworkService>, impl Stream<Item = Event>)
 {
+	let worker = build_test_full_node_worker(config);
+	let service = worker.service().clone();
+	let event_stream = service.event_stream("test");
+
+	async_std::task::spawn(async move {
+		futures::pin_mut!(worker);
+		let _ = worker.await;
+	});
+
+	(service, event_stream)
+}
+
+/// Builds the worker of a full node to be used for testing, without running it.
+fn build_test_full_node_worker(config: config::NetworkConfiguration) -> TestNetworkWorker {
 	let client = Arc::new(
 		TestClientBuilder::with_default_backend()
 			.build_with_longest_chain()
@@ -92,7 +112,7 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 		None,
 	));
 
-	let worker = NetworkWorker::new(config::Params {
+	NetworkWorker::new(config::Params {
 		role: config::Role::Full,
 		executor: None,
 		network_config: config,
@@ -108,17 +128,7 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 		),
 		metrics_registry: None,
 	})
-	.unwrap();
-
-	let service = worker.service().clone();
-	let event_stream = service.event_stream("test");
-
-	async_std::task::spawn(async move {
-		futures::pin_mut!(worker);
-		let _ = worker.await;
-	});
-
-	(service, event_stream)
+	.unwrap()
 }
 
 const ENGINE_ID: sp_runtime::ConsensusEngineId = *b"foo\0";
@@ -345,6 +355,288 @@ fn lots_of_incoming_peers_works() {
 	});
 }
 
+/// Builds two nodes with the given request-response protocols, the second one connecting to
+/// the first one. Returns the services once they are connected.
+fn build_request_response_nodes(
+	protocols1: Vec<config::RequestResponseConfig>,
+	protocols2: Vec<config::RequestResponseConfig>,
+) -> (Arc<TestNetworkService>, Arc<TestNetworkService>) {
+	let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+
+	let (node1, _) = build_test_full_node(config::NetworkConfiguration {
+		notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+		request_response_protocols: protocols1,
+		listen_addresses: vec![listen_addr.clone()],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+
+	let (node2, mut events_stream2) = build_test_full_node(config::NetworkConfiguration {
+		notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+		request_response_protocols: protocols2,
+		listen_addresses: vec![],
+		reserved_nodes: vec![config::MultiaddrWithPeerId {
+			multiaddr: listen_addr,
+			peer_id: node1.local_peer_id().clone(),
+		}],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+
+	async_std::task::block_on(async move {
+		loop {
+			match events_stream2.next().await.unwrap() {
+				Event::NotificationStreamOpened { .. } => break,
+				_ => {}
+			}
+		}
+	});
+
+	(node1, node2)
+}
+
+#[test]
+fn request_response_protocols_work() {
+	const ECHO_PROTOCOL: &str = "/test/echo/1";
+	const SILENT_PROTOCOL: &str = "/test/silent/1";
+
+	let (echo_tx, mut echo_rx) = futures::channel::mpsc::channel(16);
+	let (silent_tx, mut silent_rx) = futures::channel::mpsc::channel(16);
+
+	let (node1, node2) = build_request_response_nodes(
+		vec![
+			config::RequestResponseConfig {
+				inbound_queue: Some(echo_tx),
+				.. config::RequestResponseConfig::new(ECHO_PROTOCOL)
+			},
+			config::RequestResponseConfig {
+				inbound_queue: Some(silent_tx),
+				.. config::RequestResponseConfig::new(SILENT_PROTOCOL)
+			},
+		],
+		vec![
+			config::RequestResponseConfig::new(ECHO_PROTOCOL),
+			config::RequestResponseConfig {
+				request_timeout: Duration::from_millis(500),
+				.. config::RequestResponseConfig::new(SILENT_PROTOCOL)
+			},
+		],
+	);
+
+	// Node 1 answers echo requests with the reversed payload, and never answers silent ones.
+	async_std::task::spawn(async move {
+		while let Some(request) = echo_rx.next().await {
+			let mut response = request.payload;
+			response.reverse();
+			let _ = request.pending_response.send(response);
+		}
+	});
+	async_std::task::spawn(async move {
+		let mut unanswered = Vec::new();
+		while let Some(request) = silent_rx.next().await {
+			unanswered.push(request);
+		}
+	});
+
+	async_std::task::block_on(async move {
+		let node1_id = node1.local_peer_id().clone();
+
+		let response = node2.request(node1_id.clone(), ECHO_PROTOCOL, b"hello".to_vec()).await;
+		assert_eq!(response.unwrap(), b"olleh".to_vec());
+
+		let response = node2.request(node1_id.clone(), "/test/unknown/1", b"hello".to_vec()).await;
+		assert_matches!(response, Err(RequestFailure::UnknownProtocol));
+
+		let response = node2.request(node1_id, SILENT_PROTOCOL, b"hello".to_vec()).await;
+		assert_matches!(response, Err(RequestFailure::Timeout));
+	});
+}
+
+#[test]
+fn slow_responses_within_default_timeout_work() {
+	const SLOW_PROTOCOL: &str = "/test/slow/1";
+
+	let (slow_tx, mut slow_rx) = futures::channel::mpsc::channel(16);
+
+	let (node1, node2) = build_request_response_nodes(
+		vec![
+			config::RequestResponseConfig {
+				inbound_queue: Some(slow_tx),
+				.. config::RequestResponseConfig::new(SLOW_PROTOCOL)
+			},
+		],
+		vec![config::RequestResponseConfig::new(SLOW_PROTOCOL)],
+	);
+
+	// Answer later than the 10 seconds libp2p allows for a substream by default, but within
+	// the default timeout of the request.
+	async_std::task::spawn(async move {
+		while let Some(request) = slow_rx.next().await {
+			futures_timer::Delay::new(Duration::from_secs(11)).await;
+			let _ = request.pending_response.send(request.payload);
+		}
+	});
+
+	async_std::task::block_on(async move {
+		let node1_id = node1.local_peer_id().clone();
+		let response = node2.request(node1_id, SLOW_PROTOCOL, b"hello".to_vec()).await;
+		assert_eq!(response.unwrap(), b"hello".to_vec());
+	});
+}
+
+#[test]
+fn requests_on_protocols_unsupported_by_the_remote_fail_fast() {
+	const ECHO_PROTOCOL: &str = "/test/echo/1";
+	const MISSING_PROTOCOL: &str = "/test/missing/1";
+
+	let (echo_tx, mut echo_rx) = futures::channel::mpsc::channel(16);
+
+	let (node1, node2) = build_request_response_nodes(
+		vec![
+			config::RequestResponseConfig {
+				inbound_queue: Some(echo_tx),
+				.. config::RequestResponseConfig::new(ECHO_PROTOCOL)
+			},
+		],
+		vec![
+			config::RequestResponseConfig::new(ECHO_PROTOCOL),
+			config::RequestResponseConfig::new(MISSING_PROTOCOL),
+		],
+	);
+
+	async_std::task::spawn(async move {
+		while let Some(request) = echo_rx.next().await {
+			let _ = request.pending_response.send(request.payload);
+		}
+	});
+
+	async_std::task::block_on(async move {
+		let node1_id = node1.local_peer_id().clone();
+
+		let started = std::time::Instant::now();
+		let response = node2.request(node1_id.clone(), MISSING_PROTOCOL, b"hello".to_vec()).await;
+		assert_matches!(response, Err(RequestFailure::UnsupportedProtocol));
+		assert!(started.elapsed() < Duration::from_secs(5));
+
+		// The connection is still open.
+		let response = node2.request(node1_id, ECHO_PROTOCOL, b"hello".to_vec()).await;
+		assert_eq!(response.unwrap(), b"hello".to_vec());
+	});
+}
+
+#[test]
+fn misbehaving_remotes_fail_requests_and_lose_reputation() {
+	const ECHO_PROTOCOL: &str = "/test/echo/1";
+	const SILENT_PROTOCOL: &str = "/test/silent/1";
+
+	let (echo_tx, mut echo_rx) = futures::channel::mpsc::channel(16);
+	let (silent_tx, mut silent_rx) = futures::channel::mpsc::channel(16);
+	let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+
+	let (node1, _) = build_test_full_node(config::NetworkConfiguration {
+		notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+		request_response_protocols: vec![
+			config::RequestResponseConfig {
+				inbound_queue: Some(echo_tx),
+				.. config::RequestResponseConfig::new(ECHO_PROTOCOL)
+			},
+			config::RequestResponseConfig {
+				inbound_queue: Some(silent_tx),
+				.. config::RequestResponseConfig::new(SILENT_PROTOCOL)
+			},
+		],
+		listen_addresses: vec![listen_addr.clone()],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+
+	// Node 2 only accepts responses of up to 4 bytes, and its worker is polled by hand so that
+	// the reputations of its peerset can be read.
+	let mut worker2 = build_test_full_node_worker(config::NetworkConfiguration {
+		notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+		request_response_protocols: vec![
+			config::RequestResponseConfig {
+				max_response_size: 4,
+				.. config::RequestResponseConfig::new(ECHO_PROTOCOL)
+			},
+			config::RequestResponseConfig {
+				request_timeout: Duration::from_millis(500),
+				.. config::RequestResponseConfig::new(SILENT_PROTOCOL)
+			},
+		],
+		listen_addresses: vec![],
+		reserved_nodes: vec![config::MultiaddrWithPeerId {
+			multiaddr: listen_addr,
+			peer_id: node1.local_peer_id().clone(),
+		}],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+	let node2 = worker2.service().clone();
+	let mut events_stream2 = node2.event_stream("test");
+
+	let (reputations_tx, mut reputations_rx) =
+		futures::channel::mpsc::unbounded::<futures::channel::oneshot::Sender<serde_json::Value>>();
+	async_std::task::spawn(future::poll_fn(move |cx| {
+		while let std::task::Poll::Ready(Some(tx)) = reputations_rx.poll_next_unpin(cx) {
+			let _ = tx.send(worker2.network_state().peerset);
+		}
+		worker2.poll_unpin(cx)
+	}));
+	let reputation = move |peer: PeerId| {
+		let reputations_tx = reputations_tx.clone();
+		async move {
+			let (tx, rx) = futures::channel::oneshot::channel();
+			reputations_tx.unbounded_send(tx).unwrap();
+			rx.await.unwrap()["nodes"][peer.to_base58()]["reputation"].as_i64().unwrap_or(0)
+		}
+	};
+
+	async_std::task::spawn(async move {
+		while let Some(request) = echo_rx.next().await {
+			let _ = request.pending_response.send(request.payload);
+		}
+	});
+	async_std::task::spawn(async move {
+		let mut unanswered = Vec::new();
+		while let Some(request) = silent_rx.next().await {
+			unanswered.push(request);
+		}
+	});
+
+	async_std::task::block_on(async move {
+		loop {
+			match events_stream2.next().await.unwrap() {
+				Event::NotificationStreamOpened { .. } => break,
+				_ => {}
+			}
+		}
+
+		let node1_id = node1.local_peer_id().clone();
+		let initial = reputation(node1_id.clone()).await;
+
+		let response = node2.request(node1_id.clone(), ECHO_PROTOCOL, b"hi".to_vec()).await;
+		assert_eq!(response.unwrap(), b"hi".to_vec());
+
+		let response = node2.request(node1_id.clone(), ECHO_PROTOCOL, b"hello".to_vec()).await;
+		assert_matches!(response, Err(RequestFailure::ResponseTooLarge));
+		// The report reaches the peerset asynchronously.
+		let mut after_too_large = reputation(node1_id.clone()).await;
+		while after_too_large >= initial {
+			futures_timer::Delay::new(Duration::from_millis(10)).await;
+			after_too_large = reputation(node1_id.clone()).await;
+		}
+
+		let response = node2.request(node1_id.clone(), SILENT_PROTOCOL, b"hello".to_vec()).await;
+		assert_matches!(response, Err(RequestFailure::Timeout));
+		let mut after_timeout = reputation(node1_id.clone()).await;
+		while after_timeout >= after_too_large {
+			futures_timer::Delay::new(Duration::from_millis(10)).await;
+			after_timeout = reputation(node1_id.clone()).await;
+		}
+	});
+}
+
 #[test]
 #[should_panic(expected = "don't match the transport")]
 fn ensure_listen_addresses_consistent_with_transport_memory() {
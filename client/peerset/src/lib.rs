@@ -174,6 +174,39 @@ pub struct PeersetConfig {
 	/// > **Note**: Keep in mind that the networking has to know an address for these nodes,
 	/// >			otherwise it will not be able to connect to them.
 	pub priority_groups: Vec<(String, HashSet<PeerId>)>,
+
+	/// Reputations of nodes, for example restored from a previous run. These nodes are added to
+	/// the list of known nodes.
+	pub reputations: Vec<(PeerId, i32)>,
+}
+
+/// Moves a reputation towards zero by one second worth of decay.
+///
+/// If we multiply each second the reputation by `k` (where `k` is between 0 and 1), it takes
+/// `ln(0.5) / ln(k)` seconds to reduce the reputation by half. Use this formula to empirically
+/// determine a value of `k` that looks correct.
+fn reput_tick(reput: i32) -> i32 {
+	// We use `k = 0.98`, so we divide by `50`. With that value, it takes 34.3 seconds to reduce
+	// the reputation by half.
+	let mut diff = reput / 50;
+	if diff == 0 && reput < 0 {
+		diff = -1;
+	} else if diff == 0 && reput > 0 {
+		diff = 1;
+	}
+	reput.saturating_sub(diff)
+}
+
+/// Returns the given reputation after `secs` seconds of decay, the same way the reputations of
+/// known nodes decay over time. Useful to account for the time a node has been offline.
+pub fn decay_reputation(mut reputation: i32, secs: u64) -> i32 {
+	for _ in 0..secs {
+		if reputation == 0 {
+			break;
+		}
+		reputation = reput_tick(reputation);
+	}
+	reputation
 }
 
 /// Side of the peer set manager owned by the network. In other words, the "receiving" side.
@@ -235,6 +268,14 @@ impl Peerset {
 			}
 		}
 
+		for (peer_id, reputation) in config.reputations {
+			match peerset.data.peer(&peer_id) {
+				peersstate::Peer::Connected(mut entry) => entry.set_reputation(reputation),
+				peersstate::Peer::NotConnected(mut entry) => entry.set_reputation(reputation),
+				peersstate::Peer::Unknown(entry) => entry.discover().set_reputation(reputation),
+			}
+		}
+
 		peerset.alloc_slots();
 		(peerset, handle)
 	}
@@ -367,22 +408,8 @@ impl Peerset {
 		};
 
 		// For each elapsed second, move the node reputation towards zero.
-		// If we multiply each second the reputation by `k` (where `k` is between 0 and 1), it
-		// takes `ln(0.5) / ln(k)` seconds to reduce the reputation by half. Use this formula to
-		// empirically determine a value of `k` that looks correct.
 		for _ in 0..secs_diff {
 			for peer_id in self.data.peers().cloned().collect::<Vec<_>>() {
-				// We use `k = 0.98`, so we divide by `50`. With that value, it takes 34.3 seconds
-				// to reduce the reputation by half.
-				fn reput_tick(reput: i32) -> i32 {
-					let mut diff = reput / 50;
-					if diff == 0 && reput < 0 {
-						diff = -1;
-					} else if diff == 0 && reput > 0 {
-						diff = 1;
-					}
-					reput.saturating_sub(diff)
-				}
 				match self.data.peer(&peer_id) {
 					peersstate::Peer::Connected(mut peer) => {
 						let before = peer.reputation();
@@ -624,6 +651,22 @@ impl Peerset {
 		})
 	}
 
+	/// Returns the reputations of all the known nodes.
+	pub fn reputations(&mut self) -> Vec<(PeerId, i32)> {
+		self.update_time();
+
+		self.data.peers().cloned().collect::<Vec<_>>().into_iter().map(|peer_id| {
+			let reputation = match self.data.peer(&peer_id) {
+				peersstate::Peer::Connected(entry) => entry.reputation(),
+				peersstate::Peer::NotConnected(entry) => entry.reputation(),
+				peersstate::Peer::Unknown(_) =>
+					unreachable!("We iterate over the known peers; QED")
+			};
+
+			(peer_id, reputation)
+		}).collect()
+	}
+
 	/// Returns the number of peers that we have discovered.
 	pub fn num_discovered_peers(&self) -> usize {
 		self.data.peers().len()
@@ -674,7 +717,10 @@ impl Stream for Peerset {
 mod tests {
 	use libp2p::PeerId;
 	use futures::prelude::*;
-	use super::{PeersetConfig, Peerset, Message, IncomingIndex, ReputationChange, BANNED_THRESHOLD};
+	use super::{
+		PeersetConfig, Peerset, Message, IncomingIndex, ReputationChange, BANNED_THRESHOLD,
+		decay_reputation,
+	};
 	use std::{pin::Pin, task::Poll, thread, time::Duration};
 
 	fn assert_messages(mut peerset: Peerset, messages: Vec<Message>) -> Peerset {
@@ -703,6 +749,7 @@ mod tests {
 			bootnodes: vec![bootnode],
 			reserved_only: true,
 			priority_groups: Vec::new(),
+			reputations: Vec::new(),
 		};
 
 		let (peerset, handle) = Peerset::from_config(config);
@@ -731,6 +778,7 @@ mod tests {
 			bootnodes: vec![bootnode.clone()],
 			reserved_only: false,
 			priority_groups: Vec::new(),
+			reputations: Vec::new(),
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
@@ -757,6 +805,7 @@ mod tests {
 			bootnodes: vec![],
 			reserved_only: true,
 			priority_groups: vec![],
+			reputations: Vec::new(),
 		};
 
 		let (mut peerset, _) = Peerset::from_config(config);
@@ -778,6 +827,7 @@ mod tests {
 			bootnodes: vec![bootnode.clone()],
 			reserved_only: false,
 			priority_groups: vec![],
+			reputations: Vec::new(),
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
@@ -791,6 +841,47 @@ mod tests {
 		]);
 	}
 
+	#[test]
+	fn test_peerset_restored_reputations() {
+		let good = PeerId::random();
+		let banned = PeerId::random();
+		let config = PeersetConfig {
+			in_peers: 0,
+			out_peers: 2,
+			bootnodes: vec![],
+			reserved_only: false,
+			priority_groups: vec![],
+			reputations: vec![(banned.clone(), BANNED_THRESHOLD - 1), (good.clone(), 100)],
+		};
+
+		let (mut peerset, _handle) = Peerset::from_config(config);
+		let mut reputations = peerset.reputations();
+		reputations.sort_by_key(|(_, reputation)| *reputation);
+		assert_eq!(reputations[0].0, banned);
+		assert!(reputations[0].1 < BANNED_THRESHOLD);
+		assert_eq!(reputations[1].0, good);
+
+		// Only the node with a good reputation gets dialed.
+		let fut = futures::future::poll_fn(move |cx| {
+			let msg = Stream::poll_next(Pin::new(&mut peerset), cx);
+			assert_eq!(msg, Poll::Ready(Some(Message::Connect(good.clone()))));
+			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+			Poll::Ready(())
+		});
+
+		futures::executor::block_on(fut);
+	}
+
+	#[test]
+	fn test_decay_reputation() {
+		assert_eq!(decay_reputation(0, 100), 0);
+		assert_eq!(decay_reputation(1000, 0), 1000);
+		assert_eq!(decay_reputation(1000, 1), 980);
+		assert_eq!(decay_reputation(-1000, 1), -980);
+		assert_eq!(decay_reputation(i32::min_value(), 3600), 0);
+		assert_eq!(decay_reputation(i32::max_value(), 3600), 0);
+	}
+
 	#[test]
 	fn test_peerset_banned() {
 		let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
@@ -799,6 +890,7 @@ mod tests {
 			bootnodes: vec![],
 			reserved_only: false,
 			priority_groups: vec![],
+			reputations: Vec::new(),
 		});
 
 		// We ban a node by setting its reputation under the threshold.
@@ -0,0 +1,144 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Types returned by the transaction pool inspection calls.
+
+use sp_core::Bytes;
+use serde::{Serialize, Deserialize};
+use sp_transaction_pool::{TransactionDetails, TransactionQueue, TransactionSource};
+
+/// The queue of the pool an extrinsic sits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Queue {
+	/// The extrinsic can be included in a block.
+	Ready,
+	/// The extrinsic waits for tags other extrinsics have to provide first.
+	Future,
+}
+
+/// Where an extrinsic came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+	/// The extrinsic was part of a block that got retracted.
+	InBlock,
+	/// The extrinsic was submitted by the node itself.
+	Local,
+	/// The extrinsic came from the network or over RPC.
+	External,
+}
+
+impl From<TransactionSource> for Source {
+	fn from(source: TransactionSource) -> Self {
+		match source {
+			TransactionSource::InBlock => Source::InBlock,
+			TransactionSource::Local => Source::Local,
+			TransactionSource::External => Source::External,
+		}
+	}
+}
+
+/// An extrinsic in the transaction pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolExtrinsic<Hash> {
+	/// Hash of the extrinsic.
+	pub hash: Hash,
+	/// Where the extrinsic came from.
+	pub source: Source,
+	/// Priority of the extrinsic.
+	pub priority: u64,
+	/// Tags required by the extrinsic.
+	pub requires: Vec<Bytes>,
+	/// Tags provided by the extrinsic.
+	pub provides: Vec<Bytes>,
+	/// Block number until which the extrinsic is valid.
+	pub longevity: u64,
+	/// Number of the block the extrinsic was validated at when it entered the pool.
+	pub inserted_at: Option<u64>,
+	/// The queue the extrinsic sits in.
+	pub queue: Queue,
+	/// Required tags that no extrinsic in the pool provides yet.
+	pub missing_tags: Vec<Bytes>,
+}
+
+impl<Hash> From<TransactionDetails<Hash>> for PoolExtrinsic<Hash> {
+	fn from(details: TransactionDetails<Hash>) -> Self {
+		let tags = |tags: Vec<Vec<u8>>| tags.into_iter().map(Bytes).collect();
+		PoolExtrinsic {
+			hash: details.hash,
+			source: details.source.into(),
+			priority: details.priority,
+			requires: tags(details.requires),
+			provides: tags(details.provides),
+			longevity: details.longevity,
+			inserted_at: details.inserted_at,
+			queue: match details.queue {
+				TransactionQueue::Ready => Queue::Ready,
+				TransactionQueue::Future => Queue::Future,
+			},
+			missing_tags: tags(details.missing_tags),
+		}
+	}
+}
+
+/// Why an extrinsic left the pool without being included in a block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DropReason<Hash> {
+	/// The extrinsic was replaced by the extrinsic with the given hash.
+	Usurped(Hash),
+	/// The extrinsic turned out to be invalid.
+	Invalid,
+	/// The extrinsic was dropped to keep the pool within its limits.
+	Limits,
+	/// The extrinsic was removed through `author_removeExtrinsic`.
+	Removed,
+}
+
+impl<Hash> From<sp_transaction_pool::DropReason<Hash>> for DropReason<Hash> {
+	fn from(reason: sp_transaction_pool::DropReason<Hash>) -> Self {
+		match reason {
+			sp_transaction_pool::DropReason::Usurped(hash) => DropReason::Usurped(hash),
+			sp_transaction_pool::DropReason::Invalid => DropReason::Invalid,
+			sp_transaction_pool::DropReason::Limits => DropReason::Limits,
+			sp_transaction_pool::DropReason::Removed => DropReason::Removed,
+		}
+	}
+}
+
+/// An extrinsic that recently left the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedExtrinsic<Hash> {
+	/// Hash of the extrinsic.
+	pub hash: Hash,
+	/// Why the extrinsic was dropped.
+	pub reason: DropReason<Hash>,
+}
+
+/// The state of a single extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicInspection<Hash> {
+	/// The extrinsic, if it is in the pool.
+	pub extrinsic: Option<PoolExtrinsic<Hash>>,
+	/// Why the extrinsic was dropped, if it recently left the pool.
+	pub dropped: Option<DropReason<Hash>>,
+}
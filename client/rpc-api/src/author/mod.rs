@@ -20,6 +20,7 @@
 
 pub mod error;
 pub mod hash;
+pub mod inspect;
 
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId};
@@ -39,6 +40,13 @@ pub trait AuthorApi<Hash, BlockHash> {
 	#[rpc(name = "author_submitExtrinsic")]
 	fn submit_extrinsic(&self, extrinsic: Bytes) -> FutureResult<Hash>;
 
+	/// Submit hex-encoded extrinsics that have to be included in the same block, in the given
+	/// order, or not at all.
+	///
+	/// All extrinsics are validated against the state of the best block.
+	#[rpc(name = "author_submitBundle")]
+	fn submit_bundle(&self, extrinsics: Vec<Bytes>) -> FutureResult<Vec<Hash>>;
+
 	/// Insert a key into the keystore.
 	#[rpc(name = "author_insertKey")]
 	fn insert_key(
@@ -76,6 +84,24 @@ pub trait AuthorApi<Hash, BlockHash> {
 		bytes_or_hash: Vec<hash::ExtrinsicOrHash<Hash>>
 	) -> Result<Vec<Hash>>;
 
+	/// Returns details of all extrinsics in the pool.
+	///
+	/// Ready extrinsics come first, in the order they would be included in a block.
+	#[rpc(name = "author_inspectPool")]
+	fn inspect_pool(&self) -> Result<Vec<inspect::PoolExtrinsic<Hash>>>;
+
+	/// Returns details of the extrinsic with given hash.
+	///
+	/// Shows the tags that block a future extrinsic and why the extrinsic was dropped,
+	/// if it recently left the pool.
+	#[rpc(name = "author_inspectExtrinsic")]
+	fn inspect_extrinsic(&self, hash: Hash) -> Result<inspect::ExtrinsicInspection<Hash>>;
+
+	/// Returns the extrinsics that recently left the pool without being included in a block,
+	/// oldest first.
+	#[rpc(name = "author_droppedExtrinsics")]
+	fn dropped_extrinsics(&self) -> Result<Vec<inspect::DroppedExtrinsic<Hash>>>;
+
 	/// Submit an extrinsic to watch.
 	///
 	/// See [`TransactionStatus`](sp_transaction_pool::TransactionStatus) for details on transaction
@@ -22,10 +22,12 @@
 
 mod errors;
 mod helpers;
+mod metadata;
 mod policy;
 
 pub use jsonrpc_core::IoHandlerExtension as RpcExtension;
 pub use helpers::Receiver;
+pub use metadata::Metadata;
 pub use policy::DenyUnsafe;
 
 pub mod author;
@@ -94,7 +94,7 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 		Client: HeaderBackend<P::Block> + ProvideRuntimeApi<P::Block> + Send + Sync + 'static,
 		Client::Api: SessionKeys<P::Block, Error = ClientError>,
 {
-	type Metadata = crate::metadata::Metadata;
+	type Metadata = crate::Metadata;
 
 	fn insert_key(
 		&self,
@@ -156,10 +156,59 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 		)
 	}
 
+	fn submit_bundle(&self, extrinsics: Vec<Bytes>) -> FutureResult<Vec<TxHash<P>>> {
+		let xts = extrinsics.iter()
+			.map(|ext| Decode::decode(&mut &ext[..]))
+			.collect::<std::result::Result<Vec<_>, _>>();
+		let xts = match xts {
+			Ok(xts) => xts,
+			Err(err) => return Box::new(result(Err(err.into()))),
+		};
+		let best_block_hash = self.client.info().best_hash;
+		Box::new(self.pool
+			.submit_bundle(&generic::BlockId::hash(best_block_hash), TX_SOURCE, xts)
+			.compat()
+			.map_err(|e| e.into_pool_error()
+				.map(Into::into)
+				.unwrap_or_else(|e| error::Error::Verification(Box::new(e)).into()))
+		)
+	}
+
 	fn pending_extrinsics(&self) -> Result<Vec<Bytes>> {
 		Ok(self.pool.ready().map(|tx| tx.data().encode().into()).collect())
 	}
 
+	fn inspect_pool(&self) -> Result<Vec<inspect::PoolExtrinsic<TxHash<P>>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(self.pool.inspect().into_iter().map(Into::into).collect())
+	}
+
+	fn inspect_extrinsic(&self, hash: TxHash<P>) -> Result<inspect::ExtrinsicInspection<TxHash<P>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let extrinsic = self.pool.inspect().into_iter().find(|details| details.hash == hash);
+		let dropped = self.pool.recently_dropped()
+			.into_iter()
+			.rev()
+			.find(|(dropped, _)| *dropped == hash)
+			.map(|(_, reason)| reason.into());
+
+		Ok(inspect::ExtrinsicInspection {
+			extrinsic: extrinsic.map(Into::into),
+			dropped,
+		})
+	}
+
+	fn dropped_extrinsics(&self) -> Result<Vec<inspect::DroppedExtrinsic<TxHash<P>>>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		Ok(self.pool.recently_dropped()
+			.into_iter()
+			.map(|(hash, reason)| inspect::DroppedExtrinsic { hash, reason: reason.into() })
+			.collect())
+	}
+
 	fn remove_extrinsic(
 		&self,
 		bytes_or_hash: Vec<hash::ExtrinsicOrHash<TxHash<P>>>,
@@ -178,7 +227,7 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 
 		Ok(
 			self.pool
-				.remove_invalid(&hashes)
+				.remove(&hashes)
 				.into_iter()
 				.map(|tx| tx.hash().clone())
 				.collect()
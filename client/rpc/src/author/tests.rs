@@ -197,6 +197,46 @@ fn should_return_pending_extrinsics() {
 	);
 }
 
+#[test]
+fn should_inspect_pool() {
+	let setup = TestSetup::default();
+	let p = setup.author();
+
+	let ready = p.submit_extrinsic(uxt(AccountKeyring::Alice, 0).encode().into()).wait().unwrap();
+	let future = p.submit_extrinsic(uxt(AccountKeyring::Alice, 2).encode().into()).wait().unwrap();
+
+	let pool = p.inspect_pool().unwrap();
+	assert_eq!(
+		pool.iter().map(|xt| (xt.hash, xt.queue, xt.inserted_at)).collect::<Vec<_>>(),
+		vec![(ready, inspect::Queue::Ready, Some(0)), (future, inspect::Queue::Future, Some(0))],
+	);
+	assert_eq!(pool[0].source, inspect::Source::External);
+	assert!(pool[0].missing_tags.is_empty());
+
+	// the future extrinsic waits for the one with nonce 1
+	let inspection = p.inspect_extrinsic(future).unwrap();
+	assert_eq!(inspection.extrinsic.unwrap().missing_tags, pool[1].requires);
+	assert_eq!(inspection.dropped, None);
+}
+
+#[test]
+fn should_report_dropped_extrinsics() {
+	let setup = TestSetup::default();
+	let p = setup.author();
+
+	let hash = p.submit_extrinsic(uxt(AccountKeyring::Alice, 0).encode().into()).wait().unwrap();
+	p.remove_extrinsic(vec![hash::ExtrinsicOrHash::Hash(hash)]).unwrap();
+
+	assert_eq!(
+		p.dropped_extrinsics().unwrap(),
+		vec![inspect::DroppedExtrinsic { hash, reason: inspect::DropReason::Removed }],
+	);
+	assert_eq!(
+		p.inspect_extrinsic(hash).unwrap(),
+		inspect::ExtrinsicInspection { extrinsic: None, dropped: Some(inspect::DropReason::Removed) },
+	);
+}
+
 #[test]
 fn should_remove_extrinsics() {
 	let setup = TestSetup::default();
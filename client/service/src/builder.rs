@@ -216,6 +216,15 @@ pub fn new_full_client<TBl, TRtApi, TExecDisp>(
 	new_full_parts(config).map(|parts| parts.0)
 }
 
+/// Register the metrics of the Wasm executor, which are shared by all clones of the executor.
+fn executor_metrics(config: &Configuration) -> Option<Arc<sc_executor::Metrics>> {
+	let registry = &config.prometheus_config.as_ref()?.registry;
+	sc_executor::Metrics::register(registry)
+		.map_err(|e| warn!("Failed to register executor metrics: {}", e))
+		.ok()
+		.map(Arc::new)
+}
+
 fn new_full_parts<TBl, TRtApi, TExecDisp>(
 	config: &Configuration,
 ) -> Result<TFullParts<TBl, TRtApi, TExecDisp>,	Error> where
@@ -227,6 +236,11 @@ fn new_full_parts<TBl, TRtApi, TExecDisp>(
 			path.clone(),
 			password.clone()
 		)?,
+		KeystoreConfig::Encrypted { path, password } => Keystore::open_encrypted(
+			path.clone(),
+			password.clone()
+		)?,
+		KeystoreConfig::Remote { uri, timeout } => Keystore::open_remote(uri, *timeout)?,
 		KeystoreConfig::InMemory => Keystore::new_in_memory(),
 	};
 
@@ -239,7 +253,13 @@ fn new_full_parts<TBl, TRtApi, TExecDisp>(
 		config.wasm_method,
 		config.default_heap_pages,
 		config.max_runtime_instances,
-	);
+	)
+		.with_artifact_cache(config.wasm_runtime_cache.clone())
+		.with_instances_memory_limit(config.runtime_instances_memory)
+		.with_stack_height_limit(config.runtime_stack_height_limit)
+		.with_fuel_limit(config.runtime_fuel_limit)
+		.with_profiling(config.runtime_profiling.clone())
+		.with_metrics(executor_metrics(&config));
 
 	let chain_spec = &config.chain_spec;
 	let fork_blocks = get_extension::<ForkBlocks<TBl>>(chain_spec.extensions())
@@ -262,7 +282,7 @@ fn new_full_parts<TBl, TRtApi, TExecDisp>(
 		let extensions = sc_client_api::execution_extensions::ExecutionExtensions::new(
 			config.execution_strategies.clone(),
 			Some(keystore.clone()),
-		);
+		).with_runtime_heap_limit(config.runtime_heap_limit);
 
 		new_client(
 			db_config,
@@ -395,6 +415,11 @@ impl ServiceBuilder<(), (), (), (), (), (), (), (), (), (), ()> {
 				path.clone(),
 				password.clone()
 			)?,
+			KeystoreConfig::Encrypted { path, password } => Keystore::open_encrypted(
+				path.clone(),
+				password.clone()
+			)?,
+			KeystoreConfig::Remote { uri, timeout } => Keystore::open_remote(uri, *timeout)?,
 			KeystoreConfig::InMemory => Keystore::new_in_memory(),
 		};
 
@@ -402,7 +427,13 @@ impl ServiceBuilder<(), (), (), (), (), (), (), (), (), (), ()> {
 			config.wasm_method,
 			config.default_heap_pages,
 			config.max_runtime_instances,
-		);
+		)
+			.with_artifact_cache(config.wasm_runtime_cache.clone())
+			.with_instances_memory_limit(config.runtime_instances_memory)
+			.with_stack_height_limit(config.runtime_stack_height_limit)
+			.with_fuel_limit(config.runtime_fuel_limit)
+			.with_profiling(config.runtime_profiling.clone())
+			.with_metrics(executor_metrics(&config));
 
 		let db_storage = {
 			let db_settings = sc_client_db::DatabaseSettings {
@@ -517,6 +548,11 @@ impl<TBl, TRtApi, TCl, TFchr, TSc, TImpQu, TFprb, TFpp, TExPool, TRpc, Backend>
 		self.remote_backend.clone()
 	}
 
+	/// Returns a handle for spawning tasks on the service's task manager.
+	pub fn spawn_handle(&self) -> SpawnTaskHandle {
+		self.task_manager.spawn_handle()
+	}
+
 	/// Defines which head-of-chain strategy to use.
 	pub fn with_opt_select_chain<USc>(
 		self,
@@ -21,11 +21,15 @@
 pub use sc_client_db::{Database, PruningMode, DatabaseSettingsSrc as DatabaseConfig};
 pub use sc_network::Multiaddr;
 pub use sc_network::config::{ExtTransport, MultiaddrWithPeerId, NetworkConfiguration, Role, NodeKeyConfig};
-pub use sc_executor::WasmExecutionMethod;
+pub use sc_executor::{WasmExecutionMethod, RuntimeProfiling, DEFAULT_STACK_HEIGHT_LIMIT};
 use sc_client_api::execution_extensions::ExecutionStrategies;
 
-use std::{io, future::Future, path::{PathBuf, Path}, pin::Pin, net::SocketAddr, sync::Arc};
+use std::{
+	io, future::Future, path::{PathBuf, Path}, pin::Pin, net::SocketAddr, sync::Arc,
+	time::Duration,
+};
 pub use sc_transaction_pool::txpool::Options as TransactionPoolOptions;
+pub use sc_transaction_pool::txpool::base_pool::SenderLimit as TransactionPoolSenderLimit;
 use sc_chain_spec::ChainSpec;
 use sp_core::crypto::Protected;
 pub use sc_telemetry::TelemetryEndpoints;
@@ -105,6 +109,30 @@ pub struct Configuration {
 	///
 	/// The default value is 8.
 	pub max_runtime_instances: usize,
+	/// The number of bytes an RPC or offchain runtime call can allocate at the same time.
+	///
+	/// Unlimited if `None`. Calls importing or authoring blocks are never limited.
+	pub runtime_heap_limit: Option<u32>,
+	/// The limit of the value stack height of runtime calls.
+	///
+	/// The runtime code is not instrumented if `None`. All nodes of a network have to agree on it.
+	pub runtime_stack_height_limit: Option<u32>,
+	/// The number of instructions a runtime call can execute.
+	///
+	/// Calls are not metered if `None`.
+	pub runtime_fuel_limit: Option<u64>,
+	/// The number of bytes of linear memory all cached runtime instances can use.
+	///
+	/// Unlimited if `None`.
+	pub runtime_instances_memory: Option<u64>,
+	/// The runtime calls to profile.
+	///
+	/// Profiling is disabled if `None`.
+	pub runtime_profiling: Option<RuntimeProfiling>,
+	/// Directory of the on-disk cache of compiled Wasm runtimes.
+	///
+	/// The cache is disabled if `None`.
+	pub wasm_runtime_cache: Option<PathBuf>,
 	/// Announce block automatically after they have been imported
 	pub announce_block: bool,
 	/// Base path of the configuration
@@ -132,6 +160,20 @@ pub enum KeystoreConfig {
 		/// Node keystore's password.
 		password: Option<Protected<String>>
 	},
+	/// Keystore at a path on-disk, with key files encrypted by the password.
+	Encrypted {
+		/// The path of the keystore.
+		path: PathBuf,
+		/// Password the key files are encrypted with.
+		password: Protected<String>,
+	},
+	/// Remote signer holding the keys outside of the node.
+	Remote {
+		/// The URI of the signer.
+		uri: String,
+		/// How long to wait for the signer to answer a request.
+		timeout: Duration,
+	},
 	/// In-memory keystore. Recommended for in-browser nodes.
 	InMemory,
 }
@@ -140,8 +182,8 @@ impl KeystoreConfig {
 	/// Returns the path for the keystore.
 	pub fn path(&self) -> Option<&Path> {
 		match self {
-			Self::Path { path, .. } => Some(path),
-			Self::InMemory => None,
+			Self::Path { path, .. } | Self::Encrypted { path, .. } => Some(path),
+			Self::Remote { .. } | Self::InMemory => None,
 		}
 	}
 }
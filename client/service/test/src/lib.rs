@@ -34,7 +34,7 @@ use sc_service::{
 	GenericChainSpec,
 	ChainSpecExtension,
 	Configuration,
-	config::{BasePath, DatabaseConfig, KeystoreConfig},
+	config::{BasePath, DatabaseConfig, KeystoreConfig, DEFAULT_STACK_HEIGHT_LIMIT},
 	RuntimeGenesis,
 	Role,
 	Error,
@@ -210,6 +210,12 @@ fn node_config<G: RuntimeGenesis + 'static, E: ChainSpecExtension + Clone + 'sta
 		tracing_targets: None,
 		tracing_receiver: Default::default(),
 		max_runtime_instances: 8,
+		runtime_heap_limit: None,
+		runtime_stack_height_limit: Some(DEFAULT_STACK_HEIGHT_LIMIT),
+		runtime_fuel_limit: None,
+		runtime_instances_memory: None,
+		runtime_profiling: None,
+		wasm_runtime_cache: None,
 		announce_block: true,
 		base_path: Some(BasePath::new(root)),
 		informant_output_format: Default::default(),
@@ -21,7 +21,7 @@
 //! For a more full-featured pool, have a look at the `pool` module.
 
 use std::{
-	collections::HashSet,
+	collections::{HashMap, HashSet},
 	fmt,
 	hash,
 	sync::Arc,
@@ -38,6 +38,7 @@ use sp_runtime::transaction_validity::{
 	TransactionSource as Source,
 };
 use sp_transaction_pool::{error, PoolStatus, InPoolTransaction};
+use wasm_timer::Instant;
 
 use crate::future::{FutureTransactions, WaitingTransaction};
 use crate::ready::ReadyTransactions;
@@ -248,6 +249,13 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		}
 	}
 
+	/// Require replacement transactions to exceed the priority of the replaced ones
+	/// by at least `percent` percent.
+	pub fn with_min_priority_bump(mut self, percent: u32) -> Self {
+		self.ready.set_min_priority_bump(percent);
+		self
+	}
+
 	/// Temporary enables future transactions, runs closure and then restores
 	/// `reject_future_transactions` flag back to previous value.
 	///
@@ -303,6 +311,61 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.import_to_ready(tx)
 	}
 
+	/// Imports a bundle of transactions to the ready queue.
+	///
+	/// Members of a bundle are only included in a block together and in the given order.
+	/// Every member has to be ready once the preceding members are imported and none
+	/// of them may replace a transaction that is already in the pool.
+	pub fn import_bundle(
+		&mut self,
+		txs: Vec<Transaction<Hash, Ex>>,
+	) -> error::Result<Vec<Imported<Hash, Ex>>> {
+		if txs.is_empty() {
+			return Err(error::Error::InvalidBundle("The bundle is empty".into()))
+		}
+
+		let mut provided = self.ready.provided_tags().clone();
+		let mut hashes = Vec::with_capacity(txs.len());
+		let mut members = Vec::with_capacity(txs.len());
+		for tx in txs {
+			if hashes.contains(&tx.hash) || self.future.contains(&tx.hash) || self.ready.contains(&tx.hash) {
+				return Err(error::Error::AlreadyImported(Box::new(tx.hash.clone())))
+			}
+			if tx.provides.iter().any(|tag| provided.contains_key(tag)) {
+				return Err(error::Error::InvalidBundle(
+					format!("{:?} would replace a transaction in the pool", tx.hash)
+				))
+			}
+
+			let tx = WaitingTransaction::new(tx, &provided, &self.recently_pruned);
+			if !tx.is_ready() {
+				return Err(error::Error::InvalidBundle(format!("{:?} is not ready", tx.transaction.hash)))
+			}
+
+			let hash = tx.transaction.hash.clone();
+			for tag in &tx.transaction.provides {
+				provided.insert(tag.clone(), hash.clone());
+			}
+			hashes.push(hash);
+			members.push(tx);
+		}
+
+		debug!(target: "txpool", "Importing bundle {:?}", hashes);
+		let mut imported = Vec::with_capacity(members.len());
+		for tx in members {
+			match self.import_to_ready(tx) {
+				Ok(result) => imported.push(result),
+				Err(e) => {
+					self.remove_subtree(&hashes);
+					return Err(e)
+				},
+			}
+		}
+		self.ready.add_bundle(hashes);
+
+		Ok(imported)
+	}
+
 	/// Imports transaction to ready queue.
 	///
 	/// NOTE the transaction has to have all requirements satisfied.
@@ -360,6 +423,17 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			return Err(error::Error::CycleDetected)
 		}
 
+		// The remaining members of replaced bundle members can't be included anymore.
+		let replaced = removed.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+		let rest = self.ready.remove_bundles(&replaced)
+			.iter()
+			.flat_map(|bundle| bundle.iter().cloned())
+			.filter(|member| !replaced.contains(member))
+			.collect::<Vec<_>>();
+		if !rest.is_empty() {
+			removed.append(&mut self.remove_subtree(&rest));
+		}
+
 		Ok(Imported::Ready {
 			hash,
 			promoted,
@@ -378,6 +452,11 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.future.all()
 	}
 
+	/// Returns an iterator over future transactions and the tags they are missing.
+	pub fn futures_with_missing_tags(&self) -> impl Iterator<Item=(&Transaction<Hash, Ex>, &HashSet<Tag>)> {
+		self.future.waiting().map(|waiting| (&*waiting.transaction, &waiting.missing_tags))
+	}
+
 	/// Returns pool transactions given list of hashes.
 	///
 	/// Includes both ready and future pool. For every hash in the `hashes`
@@ -398,30 +477,75 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		self.ready.by_hash(hash)
 	}
 
+	/// Returns the members of the bundle the given transaction belongs to.
+	pub fn bundle_of(&self, hash: &Hash) -> Option<Arc<Vec<Hash>>> {
+		self.ready.bundle_of(hash)
+	}
+
+	/// Registers the bundle again after its members were re-imported.
+	///
+	/// Returns `false` and leaves the bundle unregistered if any member is not ready.
+	pub fn restore_bundle(&mut self, members: &[Hash]) -> bool {
+		if !members.iter().all(|hash| self.ready.contains(hash)) {
+			return false
+		}
+		self.ready.add_bundle(members.to_vec());
+		true
+	}
+
 	/// Makes sure that the transactions in the queues stay within provided limits.
 	///
-	/// Removes and returns worst transactions from the queues and all transactions that depend on them.
+	/// Removes and returns worst transactions from the queues and all transactions that depend on them,
+	/// together with the reason of their eviction.
 	/// Technically the worst transaction should be evaluated by computing the entire pending set.
 	/// We use a simplified approach to remove the transaction that occupies the pool for the longest time.
-	pub fn enforce_limits(&mut self, ready: &Limit, future: &Limit) -> Vec<Arc<Transaction<Hash, Ex>>> {
+	///
+	/// If `sender` limits are given, senders exceeding their share of a queue lose their most recent
+	/// transactions first, whether the queue is full or not. When a queue is full, the most recent
+	/// transaction of the sender with the most transactions in that queue is removed.
+	pub fn enforce_limits(
+		&mut self,
+		ready: &Limit,
+		future: &Limit,
+		sender: Option<&SenderLimit>,
+	) -> Vec<(Arc<Transaction<Hash, Ex>>, EvictionReason)> {
 		let mut removed = vec![];
+		let mut senders = sender.map(|limit| SenderIndexes {
+			ready: self.ready_senders(limit),
+			future: self.future_senders(limit),
+			limit: limit.clone(),
+		});
+
+		if let Some(limit) = sender {
+			while let Some(worst) = senders.as_ref().and_then(|s| s.ready.over_limit(limit.ready)) {
+				self.evict(worst, EvictionReason::SenderReadyLimit, &mut removed, &mut senders);
+			}
+
+			while let Some(worst) = senders.as_ref().and_then(|s| s.future.over_limit(limit.future)) {
+				self.evict(worst, EvictionReason::SenderFutureLimit, &mut removed, &mut senders);
+			}
+		}
 
 		while ready.is_exceeded(self.ready.len(), self.ready.bytes()) {
 			// find the worst transaction
-			let minimal = self.ready
-				.fold(|minimal, current| {
-					let transaction = &current.transaction;
-					match minimal {
-						None => Some(transaction.clone()),
-						Some(ref tx) if tx.insertion_id > transaction.insertion_id => {
-							Some(transaction.clone())
-						},
-						other => other,
-					}
-				});
+			let worst = match senders {
+				Some(ref senders) => senders.ready.fairest_victim(),
+				None => self.ready
+					.fold(|minimal, current| {
+						let transaction = &current.transaction;
+						match minimal {
+							None => Some(transaction.clone()),
+							Some(ref tx) if tx.insertion_id > transaction.insertion_id => {
+								Some(transaction.clone())
+							},
+							other => other,
+						}
+					})
+					.map(|minimal| minimal.transaction.hash.clone()),
+			};
 
-			if let Some(minimal) = minimal {
-				removed.append(&mut self.remove_subtree(&[minimal.transaction.hash.clone()]))
+			if let Some(worst) = worst {
+				self.evict(worst, EvictionReason::ReadyLimit, &mut removed, &mut senders);
 			} else {
 				break;
 			}
@@ -429,19 +553,23 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 
 		while future.is_exceeded(self.future.len(), self.future.bytes()) {
 			// find the worst transaction
-			let minimal = self.future
-				.fold(|minimal, current| {
-					match minimal {
-						None => Some(current.clone()),
-						Some(ref tx) if tx.imported_at > current.imported_at => {
-							Some(current.clone())
-						},
-						other => other,
-					}
-				});
+			let worst = match senders {
+				Some(ref senders) => senders.future.fairest_victim(),
+				None => self.future
+					.fold(|minimal, current| {
+						match minimal {
+							None => Some(current.clone()),
+							Some(ref tx) if tx.imported_at > current.imported_at => {
+								Some(current.clone())
+							},
+							other => other,
+						}
+					})
+					.map(|minimal| minimal.transaction.hash.clone()),
+			};
 
-			if let Some(minimal) = minimal {
-				removed.append(&mut self.remove_subtree(&[minimal.transaction.hash.clone()]))
+			if let Some(worst) = worst {
+				self.evict(worst, EvictionReason::FutureLimit, &mut removed, &mut senders);
 			} else {
 				break;
 			}
@@ -450,6 +578,60 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 		removed
 	}
 
+	/// Removes the transaction and its subtree, recording the reason of the eviction and keeping
+	/// the sender indexes up to date.
+	fn evict(
+		&mut self,
+		hash: Hash,
+		reason: EvictionReason,
+		removed: &mut Vec<(Arc<Transaction<Hash, Ex>>, EvictionReason)>,
+		senders: &mut Option<SenderIndexes<Hash>>,
+	) {
+		debug!(target: "txpool", "[{:?}] Evicting: {}", hash, reason.as_str());
+		let evicted = self.remove_subtree(&[hash]);
+		if let Some(senders) = senders {
+			for tx in &evicted {
+				senders.forget(tx);
+			}
+		}
+		removed.extend(evicted.into_iter().map(|tx| (tx, reason)));
+	}
+
+	/// Indexes the ready transactions by sender.
+	fn ready_senders(&mut self, limit: &SenderLimit) -> SenderIndex<u64, Hash> {
+		let transactions = self.ready
+			.fold(|list, current| {
+				let transaction = &current.transaction;
+				let mut list = list.unwrap_or_else(Vec::new);
+				list.push((
+					limit.sender_of(&transaction.transaction).map(|sender| sender.to_vec()),
+					transaction.insertion_id,
+					transaction.transaction.hash.clone(),
+				));
+				Some(list)
+			})
+			.unwrap_or_default();
+
+		SenderIndex::new(transactions)
+	}
+
+	/// Indexes the future transactions by sender.
+	fn future_senders(&mut self, limit: &SenderLimit) -> SenderIndex<Instant, Hash> {
+		let transactions = self.future
+			.fold(|list, current| {
+				let mut list = list.unwrap_or_else(Vec::new);
+				list.push((
+					limit.sender_of(&current.transaction).map(|sender| sender.to_vec()),
+					current.imported_at,
+					current.transaction.hash.clone(),
+				));
+				Some(list)
+			})
+			.unwrap_or_default();
+
+		SenderIndex::new(transactions)
+	}
+
 	/// Removes all transactions represented by the hashes and all other transactions
 	/// that depend on them.
 	///
@@ -458,10 +640,38 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 	/// they were part of a chain, you may attempt to re-import them later.
 	/// NOTE If you want to remove ready transactions that were already used
 	/// and you don't want them to be stored in the pool use `prune_tags` method.
+	/// NOTE Bundles are removed as a whole.
 	pub fn remove_subtree(&mut self, hashes: &[Hash]) -> Vec<Arc<Transaction<Hash, Ex>>> {
-		let mut removed = self.ready.remove_subtree(hashes);
-		removed.extend(self.future.remove(hashes));
-		removed
+		self.remove_subtree_with_bundles(hashes).0
+	}
+
+	/// Same as `remove_subtree`, but also returns the bundles that were removed.
+	pub fn remove_subtree_with_bundles(
+		&mut self,
+		hashes: &[Hash],
+	) -> (Vec<Arc<Transaction<Hash, Ex>>>, Vec<Arc<Vec<Hash>>>) {
+		let mut removed = vec![];
+		let mut bundles = vec![];
+		let mut to_remove = hashes.to_vec();
+
+		while !to_remove.is_empty() {
+			let mut batch = self.ready.remove_subtree(&to_remove);
+			batch.extend(self.future.remove(&to_remove));
+
+			// remove the rest of the bundles that lost a member
+			let batch_hashes = batch.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+			let removed_bundles = self.ready.remove_bundles(&batch_hashes);
+			to_remove = removed_bundles
+				.iter()
+				.flat_map(|bundle| bundle.iter().cloned())
+				.filter(|member| self.ready.contains(member) || self.future.contains(member))
+				.collect();
+
+			removed.extend(batch);
+			bundles.extend(removed_bundles);
+		}
+
+		(removed, bundles)
 	}
 
 	/// Removes and returns all transactions from the future queue.
@@ -504,6 +714,17 @@ impl<Hash: hash::Hash + Member + Serialize, Ex: std::fmt::Debug> BasePool<Hash,
 			}
 		}
 
+		// Bundles are included as a whole, the rest of partially included bundles is discarded.
+		let pruned_hashes = pruned.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>();
+		let rest = self.ready.remove_bundles(&pruned_hashes)
+			.iter()
+			.flat_map(|bundle| bundle.iter().cloned())
+			.filter(|member| !pruned_hashes.contains(member))
+			.collect::<Vec<_>>();
+		if !rest.is_empty() {
+			failed.extend(self.remove_subtree(&rest).into_iter().map(|tx| tx.hash.clone()));
+		}
+
 		PruneStatus {
 			pruned,
 			failed,
@@ -538,6 +759,155 @@ impl Limit {
 	}
 }
 
+/// Limits of a single sender.
+///
+/// The sender of a transaction is identified by the first `tag_prefix` bytes of the first tag it
+/// provides. With the usual `(AccountId, Index)` tags, this is the account of the sender.
+#[derive(Debug, Clone)]
+pub struct SenderLimit {
+	/// Number of leading bytes of the first provided tag that identify the sender.
+	pub tag_prefix: usize,
+	/// Maximal number of transactions of a single sender in the ready queue.
+	pub ready: usize,
+	/// Maximal number of transactions of a single sender in the future queue.
+	pub future: usize,
+}
+
+impl SenderLimit {
+	/// Returns the sender of the given transaction.
+	///
+	/// Returns `None` if the transaction doesn't provide a tag longer than the prefix.
+	pub fn sender_of<'a, Hash, Ex>(&self, tx: &'a Transaction<Hash, Ex>) -> Option<&'a [u8]> {
+		tx.provides
+			.first()
+			.filter(|tag| tag.len() > self.tag_prefix)
+			.map(|tag| &tag[..self.tag_prefix])
+	}
+}
+
+/// Reason of removing a transaction from the pool to keep it within limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvictionReason {
+	/// The ready queue exceeded its limits.
+	ReadyLimit,
+	/// The future queue exceeded its limits.
+	FutureLimit,
+	/// The sender exceeded its share of the ready queue.
+	SenderReadyLimit,
+	/// The sender exceeded its share of the future queue.
+	SenderFutureLimit,
+}
+
+impl EvictionReason {
+	/// All eviction reasons.
+	pub const ALL: [EvictionReason; 4] = [
+		EvictionReason::ReadyLimit,
+		EvictionReason::FutureLimit,
+		EvictionReason::SenderReadyLimit,
+		EvictionReason::SenderFutureLimit,
+	];
+
+	/// Short name of the reason.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			EvictionReason::ReadyLimit => "ready_limit",
+			EvictionReason::FutureLimit => "future_limit",
+			EvictionReason::SenderReadyLimit => "sender_ready_limit",
+			EvictionReason::SenderFutureLimit => "sender_future_limit",
+		}
+	}
+}
+
+/// Transactions of the senders in one of the queues.
+///
+/// Built once when enforcing the limits, then updated as transactions get evicted.
+struct SenderIndex<Order, Hash> {
+	/// Transactions of every known sender, from the oldest to the most recent.
+	senders: HashMap<Vec<u8>, Vec<(Order, Hash)>>,
+	/// Transactions with unknown sender. Each of them counts as a sender of its own.
+	unknown: Vec<(Order, Hash)>,
+}
+
+impl<Order: Ord + Clone, Hash: PartialEq + Clone> SenderIndex<Order, Hash> {
+	/// Indexes the given transactions by sender.
+	fn new(transactions: Vec<(Option<Vec<u8>>, Order, Hash)>) -> Self {
+		let mut senders = HashMap::<Vec<u8>, Vec<(Order, Hash)>>::new();
+		let mut unknown = vec![];
+
+		for (sender, order, hash) in transactions {
+			match sender {
+				Some(sender) => senders.entry(sender).or_default().push((order, hash)),
+				None => unknown.push((order, hash)),
+			}
+		}
+		for transactions in senders.values_mut() {
+			transactions.sort_by(|a, b| a.0.cmp(&b.0));
+		}
+
+		SenderIndex { senders, unknown }
+	}
+
+	/// Removes the transaction from the index.
+	fn remove(&mut self, sender: Option<&[u8]>, hash: &Hash) {
+		match sender {
+			Some(sender) => {
+				let now_empty = match self.senders.get_mut(sender) {
+					Some(transactions) => {
+						transactions.retain(|(_, h)| h != hash);
+						transactions.is_empty()
+					},
+					None => false,
+				};
+				if now_empty {
+					self.senders.remove(sender);
+				}
+			},
+			None => self.unknown.retain(|(_, h)| h != hash),
+		}
+	}
+
+	/// Returns the most recent transaction of a sender that has more than `limit` transactions.
+	fn over_limit(&self, limit: usize) -> Option<Hash> {
+		self.senders
+			.values()
+			.find(|transactions| transactions.len() > limit)
+			.and_then(|transactions| transactions.last())
+			.map(|(_, hash)| hash.clone())
+	}
+
+	/// Returns the most recent transaction of the sender with the most transactions.
+	///
+	/// Among senders with the same number of transactions, the one whose most recent transaction
+	/// is the oldest is picked. So if every sender has a single transaction, the oldest one goes.
+	fn fairest_victim(&self) -> Option<Hash> {
+		self.senders
+			.values()
+			.filter_map(|transactions| transactions.last().map(|newest| (transactions.len(), newest)))
+			.chain(self.unknown.iter().map(|transaction| (1, transaction)))
+			.max_by(|a, b| a.0.cmp(&b.0).then_with(|| (b.1).0.cmp(&(a.1).0)))
+			.map(|(_, (_, hash))| hash.clone())
+	}
+}
+
+/// Indexes of both queues by sender.
+struct SenderIndexes<Hash> {
+	/// Identifies the sender of the transactions.
+	limit: SenderLimit,
+	/// Ready transactions by sender.
+	ready: SenderIndex<u64, Hash>,
+	/// Future transactions by sender.
+	future: SenderIndex<Instant, Hash>,
+}
+
+impl<Hash: PartialEq + Clone> SenderIndexes<Hash> {
+	/// Removes a transaction which left the pool from the indexes.
+	fn forget<Ex>(&mut self, tx: &Transaction<Hash, Ex>) {
+		let sender = self.limit.sender_of(tx);
+		self.ready.remove(sender, &tx.hash);
+		self.future.remove(sender, &tx.hash);
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1196,4 +1566,148 @@ source: TransactionSource::External, requires: [03,02], provides: [04], data: [4
 		assert_eq!(pool.reject_future_transactions, true);
 		assert_eq!(pool.future.len(), 1);
 	}
+
+	fn sender_tx(sender: u8, nonce: u8, requires: Vec<Vec<u8>>) -> Transaction<Hash, Vec<u8>> {
+		Transaction {
+			data: vec![sender, nonce],
+			bytes: 1,
+			hash: (sender as u64) * 10 + nonce as u64,
+			priority: 5u64,
+			valid_till: 64u64,
+			requires,
+			provides: vec![vec![sender, nonce]],
+			propagate: true,
+			source: Source::External,
+		}
+	}
+
+	fn import_senders(pool: &mut BasePool<Hash, Vec<u8>>, sender: u8, count: u8) {
+		for nonce in 0..count {
+			let requires = if nonce == 0 { vec![] } else { vec![vec![sender, nonce - 1]] };
+			pool.import(sender_tx(sender, nonce, requires)).unwrap();
+		}
+	}
+
+	#[test]
+	fn should_enforce_sender_limits() {
+		// given
+		let mut pool = pool();
+		import_senders(&mut pool, 1, 4);
+		import_senders(&mut pool, 2, 1);
+		pool.import(sender_tx(3, 1, vec![vec![9]])).unwrap();
+		pool.import(sender_tx(3, 2, vec![vec![9]])).unwrap();
+		let limit = Limit { count: 100, total_bytes: 1000 };
+		let sender = SenderLimit { tag_prefix: 1, ready: 2, future: 1 };
+
+		// when
+		let removed = pool.enforce_limits(&limit, &limit, Some(&sender));
+
+		// then
+		let mut ready = removed.iter()
+			.filter(|(_, reason)| *reason == EvictionReason::SenderReadyLimit)
+			.map(|(tx, _)| tx.hash)
+			.collect::<Vec<_>>();
+		ready.sort();
+		assert_eq!(ready, vec![12, 13]);
+		assert_eq!(
+			removed.iter().filter(|(_, reason)| *reason == EvictionReason::SenderFutureLimit).count(),
+			1,
+		);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<HashSet<_>>(), vec![10, 11, 20].into_iter().collect());
+		assert_eq!(pool.future.len(), 1);
+	}
+
+	#[test]
+	fn should_evict_sender_with_most_transactions() {
+		// given
+		let mut pool = pool();
+		import_senders(&mut pool, 2, 1);
+		import_senders(&mut pool, 1, 3);
+		let ready = Limit { count: 3, total_bytes: 1000 };
+		let future = Limit { count: 100, total_bytes: 1000 };
+		let sender = SenderLimit { tag_prefix: 1, ready: 100, future: 100 };
+
+		// when
+		let removed = pool.enforce_limits(&ready, &future, Some(&sender));
+
+		// then
+		assert_eq!(
+			removed.into_iter().map(|(tx, reason)| (tx.hash, reason)).collect::<Vec<_>>(),
+			vec![(12, EvictionReason::ReadyLimit)],
+		);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<HashSet<_>>(), vec![10, 11, 20].into_iter().collect());
+	}
+
+	#[test]
+	fn should_evict_oldest_transaction_without_sender_limits() {
+		// given
+		let mut pool = pool();
+		import_senders(&mut pool, 2, 1);
+		import_senders(&mut pool, 1, 3);
+		let ready = Limit { count: 3, total_bytes: 1000 };
+		let future = Limit { count: 100, total_bytes: 1000 };
+
+		// when
+		let removed = pool.enforce_limits(&ready, &future, None);
+
+		// then
+		assert_eq!(
+			removed.into_iter().map(|(tx, reason)| (tx.hash, reason)).collect::<Vec<_>>(),
+			vec![(20, EvictionReason::ReadyLimit)],
+		);
+	}
+
+	#[test]
+	fn should_only_import_bundles_that_are_ready() {
+		// given
+		let mut pool = pool();
+
+		// when
+		let err = pool.import_bundle(vec![sender_tx(1, 1, vec![vec![1, 0]])]).unwrap_err();
+		let imported = pool.import_bundle(vec![
+			sender_tx(1, 0, vec![]),
+			sender_tx(1, 1, vec![vec![1, 0]]),
+		]).unwrap();
+
+		// then
+		match err {
+			error::Error::InvalidBundle(_) => {},
+			e => panic!("Unexpected error: {:?}", e),
+		}
+		assert_eq!(imported.len(), 2);
+		assert_eq!(pool.ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![10, 11]);
+		assert_eq!(pool.bundle_of(&11).map(|b| (*b).clone()), Some(vec![10, 11]));
+	}
+
+	#[test]
+	fn should_remove_whole_bundle_with_its_subtree() {
+		// given
+		let mut pool = pool();
+		pool.import_bundle(vec![sender_tx(1, 0, vec![]), sender_tx(2, 0, vec![])]).unwrap();
+		pool.import(sender_tx(1, 1, vec![vec![1, 0]])).unwrap();
+
+		// when
+		let removed = pool.remove_subtree(&[20]);
+
+		// then
+		assert_eq!(removed.into_iter().map(|tx| tx.hash).collect::<HashSet<_>>(), vec![10, 11, 20].into_iter().collect());
+		assert_eq!(pool.ready().count(), 0);
+		assert_eq!(pool.bundle_of(&10), None);
+	}
+
+	#[test]
+	fn should_drop_rest_of_partially_pruned_bundle() {
+		// given
+		let mut pool = pool();
+		pool.import_bundle(vec![sender_tx(1, 0, vec![]), sender_tx(2, 0, vec![])]).unwrap();
+
+		// when
+		let result = pool.prune_tags(vec![vec![1, 0]]);
+
+		// then
+		assert_eq!(result.pruned.iter().map(|tx| tx.hash).collect::<Vec<_>>(), vec![10]);
+		assert_eq!(result.failed, vec![20]);
+		assert_eq!(pool.ready().count(), 0);
+		assert_eq!(pool.bundle_of(&20), None);
+	}
 }
@@ -226,6 +226,11 @@ impl<Hash: hash::Hash + Eq + Clone, Ex> FutureTransactions<Hash, Ex> {
 			.fold(None, f)
 	}
 
+	/// Returns iterator over all future transactions together with their missing tags.
+	pub fn waiting(&self) -> impl Iterator<Item=&WaitingTransaction<Hash, Ex>> {
+		self.waiting.values()
+	}
+
 	/// Returns iterator over all future transactions
 	pub fn all(&self) -> impl Iterator<Item=&Transaction<Hash, Ex>> {
 		self.waiting.values().map(|waiting| &*waiting.transaction)
@@ -25,21 +25,27 @@ use serde::Serialize;
 use crate::{watcher, ChainApi, ExtrinsicHash, BlockHash};
 use log::{debug, trace, warn};
 use sp_runtime::traits;
+use sp_transaction_pool::DropReason;
 
 /// Extrinsic pool default listener.
 pub struct Listener<H: hash::Hash + Eq, C: ChainApi> {
 	watchers: HashMap<H, watcher::Sender<H, ExtrinsicHash<C>>>,
 	finality_watchers: LinkedHashMap<ExtrinsicHash<C>, Vec<H>>,
+	recently_dropped: LinkedHashMap<H, DropReason<H>>,
 }
 
 /// Maximum number of blocks awaiting finality at any time.
 const MAX_FINALITY_WATCHERS: usize = 512;
 
+/// Maximum number of dropped transactions to remember.
+const MAX_RECENTLY_DROPPED: usize = 1024;
+
 impl<H: hash::Hash + Eq + Debug, C: ChainApi> Default for Listener<H, C> {
 	fn default() -> Self {
 		Listener {
 			watchers: Default::default(),
 			finality_watchers: Default::default(),
+			recently_dropped: Default::default(),
 		}
 	}
 }
@@ -58,6 +64,23 @@ impl<H: hash::Hash + traits::Member + Serialize, C: ChainApi> Listener<H, C> {
 		}
 	}
 
+	fn record_drop(&mut self, hash: &H, reason: DropReason<H>) {
+		// re-insert, so the entry moves to the back
+		self.recently_dropped.remove(hash);
+		self.recently_dropped.insert(hash.clone(), reason);
+		while self.recently_dropped.len() > MAX_RECENTLY_DROPPED {
+			self.recently_dropped.pop_front();
+		}
+	}
+
+	/// Returns the recently dropped transactions and the reason of dropping them, oldest first.
+	pub fn recently_dropped(&self) -> Vec<(H, DropReason<H>)> {
+		self.recently_dropped
+			.iter()
+			.map(|(hash, reason)| (hash.clone(), reason.clone()))
+			.collect()
+	}
+
 	/// Creates a new watcher for given verified extrinsic.
 	///
 	/// The watcher can be used to subscribe to life-cycle events of that extrinsic.
@@ -77,6 +100,7 @@ impl<H: hash::Hash + traits::Member + Serialize, C: ChainApi> Listener<H, C> {
 		trace!(target: "txpool", "[{:?}] Ready (replaced with {:?})", tx, old);
 		self.fire(tx, |watcher| watcher.ready());
 		if let Some(old) = old {
+			self.record_drop(old, DropReason::Usurped(tx.clone()));
 			self.fire(old, |watcher| watcher.usurped(tx.clone()));
 		}
 	}
@@ -90,6 +114,10 @@ impl<H: hash::Hash + traits::Member + Serialize, C: ChainApi> Listener<H, C> {
 	/// Transaction was dropped from the pool because of the limit.
 	pub fn dropped(&mut self, tx: &H, by: Option<&H>) {
 		trace!(target: "txpool", "[{:?}] Dropped (replaced with {:?})", tx, by);
+		self.record_drop(tx, match by {
+			Some(t) => DropReason::Usurped(t.clone()),
+			None => DropReason::Limits,
+		});
 		self.fire(tx, |watcher| match by {
 			Some(t) => watcher.usurped(t.clone()),
 			None => watcher.dropped(),
@@ -103,6 +131,14 @@ impl<H: hash::Hash + traits::Member + Serialize, C: ChainApi> Listener<H, C> {
 		} else {
 			debug!(target: "txpool", "[{:?}] Extrinsic invalid", tx);
 		}
+		self.record_drop(tx, DropReason::Invalid);
+		self.fire(tx, |watcher| watcher.invalid());
+	}
+
+	/// Transaction was removed at the request of the node operator.
+	pub fn removed(&mut self, tx: &H) {
+		debug!(target: "txpool", "[{:?}] Removed", tx);
+		self.record_drop(tx, DropReason::Removed);
 		self.fire(tx, |watcher| watcher.invalid());
 	}
 
@@ -18,6 +18,7 @@
 
 use std::{
 	collections::HashMap,
+	path::PathBuf,
 	sync::Arc,
 };
 
@@ -107,6 +108,18 @@ pub struct Options {
 	pub future: base::Limit,
 	/// Reject future transactions.
 	pub reject_future_transactions: bool,
+	/// Limits of a single sender.
+	///
+	/// If set, senders never hold more transactions than their share of a queue, and the
+	/// transactions of the senders with the most transactions are evicted first when a queue is
+	/// full. Otherwise the oldest transactions are evicted.
+	pub sender: Option<base::SenderLimit>,
+	/// Minimal priority increase, in percent, a transaction needs to replace another one.
+	pub min_priority_bump: u32,
+	/// File the pool contents are kept in across restarts.
+	///
+	/// The pool is not persisted if `None`.
+	pub journal: Option<PathBuf>,
 }
 
 impl Default for Options {
@@ -121,6 +134,9 @@ impl Default for Options {
 				total_bytes: 1 * 1024 * 1024,
 			},
 			reject_future_transactions: false,
+			sender: None,
+			min_priority_bump: 0,
+			journal: None,
 		}
 	}
 }
@@ -158,14 +174,16 @@ impl<B: ChainApi> Pool<B> {
 	) -> Result<Vec<Result<ExtrinsicHash<B>, B::Error>>, B::Error> where
 		T: IntoIterator<Item=ExtrinsicFor<B>>,
 	{
-		let validated_pool = self.validated_pool.clone();
+		let block_number = self.resolve_block_number(at)?.saturated_into::<u64>();
 		let xts = xts.into_iter().map(|xt| (source, xt));
-		self.verify(at, xts, force)
-			.map(move |validated_transactions| validated_transactions
-				.map(|validated_transactions| validated_pool.submit(validated_transactions
-					.into_iter()
-					.map(|(_, tx)| tx))))
-			.await
+		let validated_transactions = self.verify(at, xts, force).await?;
+		let results = self.validated_pool.submit(validated_transactions.into_iter().map(|(_, tx)| tx));
+		self.validated_pool.note_inserted(
+			results.iter().filter_map(|result| result.as_ref().ok().cloned()),
+			block_number,
+		);
+
+		Ok(results)
 	}
 
 	/// Imports one unverified extrinsic to the pool
@@ -183,6 +201,27 @@ impl<B: ChainApi> Pool<B> {
 			.await
 	}
 
+	/// Imports a bundle of unverified extrinsics to the pool.
+	///
+	/// All members are validated at the same block and imported all-or-nothing.
+	pub async fn submit_bundle(
+		&self,
+		at: &BlockId<B::Block>,
+		source: TransactionSource,
+		xts: Vec<ExtrinsicFor<B>>,
+	) -> Result<Vec<ExtrinsicHash<B>>, B::Error> {
+		let block_number = self.resolve_block_number(at)?;
+		let validated_transactions = futures::future::join_all(
+			xts.into_iter().map(|xt| self.verify_one(at, block_number, source, xt, false))
+		).await;
+		let hashes = self.validated_pool.submit_bundle(
+			validated_transactions.into_iter().map(|(_, tx)| tx).collect()
+		)?;
+		self.validated_pool.note_inserted(hashes.iter().cloned(), block_number.saturated_into::<u64>());
+
+		Ok(hashes)
+	}
+
 	/// Import a single extrinsic and starts to watch their progress in the pool.
 	pub async fn submit_and_watch(
 		&self,
@@ -191,10 +230,13 @@ impl<B: ChainApi> Pool<B> {
 		xt: ExtrinsicFor<B>,
 	) -> Result<Watcher<ExtrinsicHash<B>, ExtrinsicHash<B>>, B::Error> {
 		let block_number = self.resolve_block_number(at)?;
-		let (_, tx) = self.verify_one(
+		let (hash, tx) = self.verify_one(
 			at, block_number, source, xt, false
 		).await;
-		self.validated_pool.submit_and_watch(tx)
+		let watcher = self.validated_pool.submit_and_watch(tx)?;
+		self.validated_pool.note_inserted(std::iter::once(hash), block_number.saturated_into::<u64>());
+
+		Ok(watcher)
 	}
 
 	/// Resubmit some transaction that were validated elsewhere.
@@ -739,6 +781,37 @@ mod tests {
 		assert!(!pool.validated_pool.rotator().is_banned(&hash2));
 	}
 
+	#[test]
+	fn should_enforce_sender_limits_when_pool_is_not_full() {
+		// given
+		let pool = Pool::new(Options {
+			// every transaction of the test api provides a single byte tag, so they all have the
+			// same (empty) sender
+			sender: Some(base::SenderLimit { tag_prefix: 0, ready: 1, future: 1 }),
+			..Default::default()
+		}, TestApi::default().into());
+
+		let hash1 = block_on(pool.submit_one(&BlockId::Number(0), SOURCE, uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 0,
+		}))).unwrap();
+
+		// when
+		let err = block_on(pool.submit_one(&BlockId::Number(0), SOURCE, uxt(Transfer {
+			from: AccountId::from_h256(H256::from_low_u64_be(1)),
+			to: AccountId::from_h256(H256::from_low_u64_be(2)),
+			amount: 5,
+			nonce: 1,
+		}))).unwrap_err();
+
+		// then
+		assert_matches!(err, error::Error::ImmediatelyDropped);
+		assert_eq!(pool.validated_pool().status().ready, 1);
+		assert_eq!(pool.validated_pool().ready().map(|tx| tx.hash).collect::<Vec<_>>(), vec![hash1]);
+	}
+
 	#[test]
 	fn should_error_if_reject_immediately() {
 		// given
@@ -17,7 +17,7 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
-	collections::{HashMap, HashSet, BTreeSet},
+	collections::{HashMap, HashSet, BTreeSet, VecDeque},
 	cmp,
 	hash,
 	sync::Arc,
@@ -118,6 +118,12 @@ pub struct ReadyTransactions<Hash: hash::Hash + Eq, Ex> {
 	ready: TrackedMap<Hash, ReadyTx<Hash, Ex>>,
 	/// Best transactions that are ready to be included to the block without any other previous transaction.
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	/// Minimal priority increase, in percent, required to replace transactions.
+	min_priority_bump: u32,
+	/// Bundles, by the hashes of their members.
+	///
+	/// Members of a bundle are only returned by the best iterator together and in order.
+	bundles: HashMap<Hash, Arc<Vec<Hash>>>,
 }
 
 impl<Hash, Ex> tracked_map::Size for ReadyTx<Hash, Ex> {
@@ -133,11 +139,18 @@ impl<Hash: hash::Hash + Eq, Ex> Default for ReadyTransactions<Hash, Ex> {
 			provided_tags: Default::default(),
 			ready: Default::default(),
 			best: Default::default(),
+			min_priority_bump: 0,
+			bundles: Default::default(),
 		}
 	}
 }
 
 impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
+	/// Sets the minimal priority increase, in percent, required to replace transactions.
+	pub fn set_min_priority_bump(&mut self, percent: u32) {
+		self.min_priority_bump = percent;
+	}
+
 	/// Borrows a map of tags that are provided by transactions in this queue.
 	pub fn provided_tags(&self) -> &HashMap<Tag, Hash> {
 		&self.provided_tags
@@ -159,7 +172,39 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			all: self.ready.clone(),
 			best: self.best.clone(),
 			awaiting: Default::default(),
+			bundles: self.bundles.clone(),
+			parked: Default::default(),
+			pending: Default::default(),
+		}
+	}
+
+	/// Registers a bundle of transactions that have to be included together and in order.
+	pub fn add_bundle(&mut self, members: Vec<Hash>) {
+		let members = Arc::new(members);
+		for hash in members.iter() {
+			self.bundles.insert(hash.clone(), members.clone());
+		}
+	}
+
+	/// Returns the members of the bundle the given transaction belongs to.
+	pub fn bundle_of(&self, hash: &Hash) -> Option<Arc<Vec<Hash>>> {
+		self.bundles.get(hash).cloned()
+	}
+
+	/// Forgets the bundles the given transactions belong to.
+	///
+	/// Returns the forgotten bundles.
+	pub fn remove_bundles(&mut self, hashes: &[Hash]) -> Vec<Arc<Vec<Hash>>> {
+		let mut removed = vec![];
+		for hash in hashes {
+			if let Some(bundle) = self.bundles.get(hash).cloned() {
+				for member in bundle.iter() {
+					self.bundles.remove(member);
+				}
+				removed.push(bundle);
+			}
 		}
+		removed
 	}
 
 	/// Imports transactions to the pool of ready transactions.
@@ -443,7 +488,10 @@ impl<Hash: hash::Hash + Member + Serialize, Ex> ReadyTransactions<Hash, Ex> {
 			};
 
 			// bail - the transaction has too low priority to replace the old ones
-			if old_priority >= tx.priority {
+			let min_priority = old_priority.saturating_add(
+				old_priority.saturating_mul(self.min_priority_bump as u64) / 100
+			);
+			if old_priority >= tx.priority || min_priority > tx.priority {
 				return Err(error::Error::TooLowPriority { old: old_priority, new: tx.priority })
 			}
 
@@ -490,6 +538,11 @@ pub struct BestIterator<Hash, Ex> {
 	all: ReadOnlyTrackedMap<Hash, ReadyTx<Hash, Ex>>,
 	awaiting: HashMap<Hash, (usize, TransactionRef<Hash, Ex>)>,
 	best: BTreeSet<TransactionRef<Hash, Ex>>,
+	bundles: HashMap<Hash, Arc<Vec<Hash>>>,
+	/// Bundle members waiting for the rest of their bundle.
+	parked: HashMap<Hash, ReadyTx<Hash, Ex>>,
+	/// Members of a complete bundle that are yet to be returned.
+	pending: VecDeque<Arc<Transaction<Hash, Ex>>>,
 }
 
 impl<Hash: hash::Hash + Member, Ex> BestIterator<Hash, Ex> {
@@ -505,6 +558,26 @@ impl<Hash: hash::Hash + Member, Ex> BestIterator<Hash, Ex> {
 			self.awaiting.insert(tx_ref.transaction.hash.clone(), (satisfied, tx_ref));
 		}
 	}
+
+	/// Insert transactions that just got unlocked.
+	fn unlock<'a>(&mut self, unlocks: impl IntoIterator<Item=&'a Hash>) where Hash: 'a {
+		for hash in unlocks {
+			// first check local awaiting transactions
+			let res = if let Some((mut satisfied, tx_ref)) = self.awaiting.remove(hash) {
+				satisfied += 1;
+				Some((satisfied, tx_ref))
+			// then get from the pool
+			} else if let Some(next) = self.all.read().get(hash) {
+				Some((next.requires_offset + 1, next.transaction.clone()))
+			} else {
+				None
+			};
+
+			if let Some((satisfied, tx_ref)) = res {
+				self.best_or_awaiting(satisfied, tx_ref)
+			}
+		}
+	}
 }
 
 impl<Hash: hash::Hash + Member, Ex> Iterator for BestIterator<Hash, Ex> {
@@ -512,6 +585,10 @@ impl<Hash: hash::Hash + Member, Ex> Iterator for BestIterator<Hash, Ex> {
 
 	fn next(&mut self) -> Option<Self::Item> {
 		loop {
+			if let Some(member) = self.pending.pop_front() {
+				return Some(member)
+			}
+
 			let best = self.best.iter().next_back()?.clone();
 			let best = self.best.take(&best)?;
 
@@ -522,25 +599,26 @@ impl<Hash: hash::Hash + Member, Ex> Iterator for BestIterator<Hash, Ex> {
 				None => continue,
 			};
 
-			// Insert transactions that just got unlocked.
-			for hash in &ready.unlocks {
-				// first check local awaiting transactions
-				let res = if let Some((mut satisfied, tx_ref)) = self.awaiting.remove(hash) {
-					satisfied += 1;
-					Some((satisfied, tx_ref))
-				// then get from the pool
-				} else if let Some(next) = self.all.read().get(hash) {
-					Some((next.requires_offset + 1, next.transaction.clone()))
-				} else {
-					None
-				};
-
-				if let Some((satisfied, tx_ref)) = res {
-					self.best_or_awaiting(satisfied, tx_ref)
+			let bundle = match self.bundles.get(&best.transaction.hash) {
+				Some(bundle) => bundle.clone(),
+				None => {
+					self.unlock(&ready.unlocks);
+					return Some(best.transaction.clone())
+				},
+			};
+
+			// Park the member until the whole bundle is ready. Only later members of the same
+			// bundle may depend on it in the meantime.
+			self.unlock(ready.unlocks.iter().filter(|hash| bundle.contains(*hash)));
+			self.parked.insert(best.transaction.hash.clone(), ready);
+
+			if bundle.iter().all(|hash| self.parked.contains_key(hash)) {
+				for hash in bundle.iter() {
+					let member = self.parked.remove(hash).expect("All members are parked; qed");
+					self.unlock(member.unlocks.iter().filter(|hash| !bundle.contains(*hash)));
+					self.pending.push_back(member.transaction.transaction);
 				}
 			}
-
-			return Some(best.transaction.clone())
 		}
 	}
 }
@@ -607,6 +685,30 @@ mod tests {
 		assert_eq!(ready.get().count(), 1);
 	}
 
+	#[test]
+	fn should_require_minimal_priority_bump_for_replacement() {
+		// given
+		let mut ready = ReadyTransactions::default();
+		ready.set_min_priority_bump(10);
+		let mut tx1 = tx(1);
+		tx1.requires.clear();
+		tx1.priority = 100;
+		let mut tx2 = tx(2);
+		tx2.requires.clear();
+		tx2.provides = tx1.provides.clone();
+		tx2.priority = 109;
+		import(&mut ready, tx1).unwrap();
+
+		// when
+		let err = import(&mut ready, tx2.clone()).unwrap_err();
+		tx2.priority = 110;
+		import(&mut ready, tx2).unwrap();
+
+		// then
+		assert!(matches!(err, error::Error::TooLowPriority { old: 100, new: 109 }));
+		assert_eq!(ready.get().map(|tx| tx.hash).collect::<Vec<_>>(), vec![2]);
+	}
+
 	#[test]
 	fn should_replace_multiple_transactions_correctly() {
 		// given
@@ -689,6 +791,43 @@ mod tests {
 		assert_eq!(it.next(), None);
 	}
 
+	#[test]
+	fn should_return_bundle_members_together() {
+		// given
+		let mut ready = ReadyTransactions::default();
+		let mut tx1 = tx(1);
+		tx1.requires.clear();
+		tx1.provides = vec![vec![101]];
+		tx1.priority = 10;
+		let mut tx2 = tx(2);
+		tx2.requires.clear();
+		tx2.provides = vec![vec![102]];
+		tx2.priority = 1;
+		let mut tx3 = tx(3);
+		tx3.requires.clear();
+		tx3.provides = vec![vec![103]];
+		tx3.priority = 5;
+		let mut tx4 = tx(4);
+		tx4.requires = vec![vec![101]];
+		tx4.provides = vec![];
+		tx4.priority = 20;
+
+		// when
+		for tx in vec![tx1, tx2, tx3, tx4] {
+			import(&mut ready, tx).unwrap();
+		}
+		ready.add_bundle(vec![2, 1]);
+
+		// then
+		let mut it = ready.get().map(|tx| tx.data[0]);
+
+		assert_eq!(it.next(), Some(3));
+		assert_eq!(it.next(), Some(2));
+		assert_eq!(it.next(), Some(1));
+		assert_eq!(it.next(), Some(4));
+		assert_eq!(it.next(), None);
+	}
+
 	#[test]
 	fn can_report_heap_size() {
 		let mut ready = ReadyTransactions::default();
@@ -34,7 +34,7 @@ use sp_runtime::{
 	traits::{self, SaturatedConversion},
 	transaction_validity::{TransactionTag as Tag, ValidTransaction, TransactionSource},
 };
-use sp_transaction_pool::{error, PoolStatus};
+use sp_transaction_pool::{error, DropReason, PoolStatus, TransactionDetails, TransactionQueue};
 use wasm_timer::Instant;
 use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
 
@@ -99,6 +99,9 @@ pub struct ValidatedPool<B: ChainApi> {
 		ExtrinsicFor<B>,
 	>>,
 	import_notification_sinks: Mutex<Vec<TracingUnboundedSender<ExtrinsicHash<B>>>>,
+	eviction_notification_sinks: Mutex<Vec<TracingUnboundedSender<(ExtrinsicHash<B>, base::EvictionReason)>>>,
+	/// Number of the block the transactions in the pool were first validated at.
+	inserted_at: Mutex<HashMap<ExtrinsicHash<B>, u64>>,
 	rotator: PoolRotator<ExtrinsicHash<B>>,
 }
 
@@ -116,13 +119,16 @@ where
 impl<B: ChainApi> ValidatedPool<B> {
 	/// Create a new transaction pool.
 	pub fn new(options: Options, api: Arc<B>) -> Self {
-		let base_pool = base::BasePool::new(options.reject_future_transactions);
+		let base_pool = base::BasePool::new(options.reject_future_transactions)
+			.with_min_priority_bump(options.min_priority_bump);
 		ValidatedPool {
 			options,
 			listener: Default::default(),
 			api,
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
+			eviction_notification_sinks: Default::default(),
+			inserted_at: Default::default(),
 			rotator: Default::default(),
 		}
 	}
@@ -190,24 +196,31 @@ impl<B: ChainApi> ValidatedPool<B> {
 		let future_limit = &self.options.future;
 
 		log::debug!(target: "txpool", "Pool Status: {:?}", status);
-		if ready_limit.is_exceeded(status.ready, status.ready_bytes)
-			|| future_limit.is_exceeded(status.future, status.future_bytes)
-		{
-			log::debug!(
-				target: "txpool",
-				"Enforcing limits ({}/{}kB ready, {}/{}kB future",
-				ready_limit.count, ready_limit.total_bytes / 1024,
-				future_limit.count, future_limit.total_bytes / 1024,
-			);
+		let exceeded = ready_limit.is_exceeded(status.ready, status.ready_bytes)
+			|| future_limit.is_exceeded(status.future, status.future_bytes);
+		// per-sender limits apply even if the pool isn't full
+		if exceeded || self.options.sender.is_some() {
+			if exceeded {
+				log::debug!(
+					target: "txpool",
+					"Enforcing limits ({}/{}kB ready, {}/{}kB future",
+					ready_limit.count, ready_limit.total_bytes / 1024,
+					future_limit.count, future_limit.total_bytes / 1024,
+				);
+			}
 
 			// clean up the pool
 			let removed = {
 				let mut pool = self.pool.write();
-				let removed = pool.enforce_limits(ready_limit, future_limit)
-					.into_iter().map(|x| x.hash.clone()).collect::<HashSet<_>>();
+				let evicted = pool.enforce_limits(ready_limit, future_limit, self.options.sender.as_ref());
 				// ban all removed transactions
-				self.rotator.ban(&Instant::now(), removed.iter().map(|x| x.clone()));
-				removed
+				self.rotator.ban(&Instant::now(), evicted.iter().map(|(tx, _)| tx.hash.clone()));
+
+				let mut sinks = self.eviction_notification_sinks.lock();
+				evicted.into_iter().map(|(tx, reason)| {
+					sinks.retain(|sink| sink.unbounded_send((tx.hash.clone(), reason)).is_ok());
+					tx.hash.clone()
+				}).collect::<HashSet<_>>()
 			};
 			if !removed.is_empty() {
 				log::debug!(target: "txpool", "Enforcing limits: {} dropped", removed.len());
@@ -225,6 +238,53 @@ impl<B: ChainApi> ValidatedPool<B> {
 		}
 	}
 
+	/// Imports a pre-validated bundle of transactions to the pool.
+	///
+	/// Fails if any member is not valid, the members are imported all-or-nothing.
+	pub fn submit_bundle(
+		&self,
+		txs: Vec<ValidatedTransactionFor<B>>,
+	) -> Result<Vec<ExtrinsicHash<B>>, B::Error> {
+		let mut members = Vec::with_capacity(txs.len());
+		for tx in txs {
+			match tx {
+				ValidatedTransaction::Valid(tx) => members.push(tx),
+				ValidatedTransaction::Invalid(hash, err) => {
+					self.rotator.ban(&Instant::now(), std::iter::once(hash));
+					return Err(err)
+				},
+				ValidatedTransaction::Unknown(_, err) => return Err(err),
+			}
+		}
+
+		let imported = self.pool.write().import_bundle(members)?;
+		let hashes = imported.iter().map(|imported| imported.hash().clone()).collect::<Vec<_>>();
+		{
+			let mut sinks = self.import_notification_sinks.lock();
+			for hash in &hashes {
+				sinks.retain(|sink| sink.unbounded_send(hash.clone()).is_ok());
+			}
+		}
+		{
+			let mut listener = self.listener.write();
+			for imported in &imported {
+				fire_events(&mut *listener, imported);
+			}
+		}
+
+		let removed = self.enforce_limits();
+		if hashes.iter().any(|hash| removed.contains(hash)) {
+			return Err(error::Error::ImmediatelyDropped.into())
+		}
+
+		Ok(hashes)
+	}
+
+	/// Returns the members of the bundle the given transaction belongs to.
+	pub fn bundle_of(&self, hash: &ExtrinsicHash<B>) -> Option<Vec<ExtrinsicHash<B>>> {
+		self.pool.read().bundle_of(hash).map(|bundle| bundle.to_vec())
+	}
+
 	/// Import a single extrinsic and starts to watch their progress in the pool.
 	pub fn submit_and_watch(
 		&self,
@@ -267,13 +327,15 @@ impl<B: ChainApi> ValidatedPool<B> {
 			// we store last validation result (i.e. the pool entry) in txs_to_resubmit
 			let mut initial_statuses = HashMap::new();
 			let mut txs_to_resubmit = Vec::with_capacity(updated_transactions.len());
+			let mut bundles = Vec::new();
 			while !updated_transactions.is_empty() {
 				let hash = updated_transactions.keys().next().cloned().expect("transactions is not empty; qed");
 
 				// note we are not considering tx with hash invalid here - we just want
 				// to remove it along with dependent transactions and `remove_subtree()`
 				// does exactly what we need
-				let removed = pool.remove_subtree(&[hash.clone()]);
+				let (removed, mut removed_bundles) = pool.remove_subtree_with_bundles(&[hash.clone()]);
+				bundles.append(&mut removed_bundles);
 				for removed_tx in removed {
 					let removed_hash = removed_tx.hash.clone();
 					let updated_transaction = updated_transactions.remove(&removed_hash);
@@ -342,6 +404,15 @@ impl<B: ChainApi> ValidatedPool<B> {
 					}
 				}
 
+				// bundles stay together, unless some member didn't make it back to the ready queue
+				for bundle in bundles {
+					if !pool.restore_bundle(&bundle) {
+						for tx in pool.remove_subtree(&bundle) {
+							final_statuses.insert(tx.hash.clone(), Status::Dropped);
+						}
+					}
+				}
+
 				// if the pool is configured to reject future transactions, let's clear the future
 				// queue, updating final statuses as required
 				if reject_future_transactions {
@@ -514,6 +585,78 @@ impl<B: ChainApi> ValidatedPool<B> {
 		stream
 	}
 
+	/// Records the number of the block the given transactions entered the pool at.
+	///
+	/// Transactions that are already known keep their original insertion block.
+	pub fn note_inserted(&self, hashes: impl IntoIterator<Item=ExtrinsicHash<B>>, block_number: u64) {
+		// `inspect` locks the pool before `inserted_at`, so `inserted_at` is never held while
+		// locking the pool here.
+		let known_count = {
+			let mut inserted_at = self.inserted_at.lock();
+			for hash in hashes {
+				inserted_at.entry(hash).or_insert(block_number);
+			}
+			inserted_at.len()
+		};
+
+		// Forget about the transactions that left the pool once there are too many of them.
+		let status = self.status();
+		if known_count <= 2 * (status.ready + status.future) + 64 {
+			return;
+		}
+		let known = self.inserted_at.lock().keys().cloned().collect::<Vec<_>>();
+
+		let in_pool = self.pool.read().by_hashes(&known);
+		let mut inserted_at = self.inserted_at.lock();
+		for (hash, tx) in known.into_iter().zip(in_pool) {
+			if tx.is_none() {
+				inserted_at.remove(&hash);
+			}
+		}
+	}
+
+	/// Returns details of all transactions in the pool.
+	pub fn inspect(&self) -> Vec<TransactionDetails<ExtrinsicHash<B>>> {
+		let pool = self.pool.read();
+		let inserted_at = self.inserted_at.lock();
+		let details = |tx: &base::Transaction<_, _>, queue, missing_tags: Vec<Tag>| TransactionDetails {
+			hash: tx.hash.clone(),
+			source: tx.source,
+			priority: tx.priority,
+			requires: tx.requires.clone(),
+			provides: tx.provides.clone(),
+			longevity: tx.valid_till,
+			inserted_at: inserted_at.get(&tx.hash).copied(),
+			queue,
+			missing_tags,
+		};
+
+		let ready = pool.ready().map(|tx| details(&*tx, TransactionQueue::Ready, vec![])).collect::<Vec<_>>();
+		let mut future = pool.futures_with_missing_tags()
+			.map(|(tx, missing_tags)| {
+				let mut missing_tags = missing_tags.iter().cloned().collect::<Vec<_>>();
+				missing_tags.sort();
+				details(tx, TransactionQueue::Future, missing_tags)
+			})
+			.collect::<Vec<_>>();
+		// keep the output stable
+		future.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.inserted_at.cmp(&b.inserted_at)));
+
+		ready.into_iter().chain(future).collect()
+	}
+
+	/// Returns transactions recently dropped from the pool and why, oldest first.
+	pub fn recently_dropped(&self) -> Vec<(ExtrinsicHash<B>, DropReason<ExtrinsicHash<B>>)> {
+		self.listener.read().recently_dropped()
+	}
+
+	/// Get notified about transactions evicted to keep the pool within its limits.
+	pub fn eviction_notification_stream(&self) -> EventStream<(ExtrinsicHash<B>, base::EvictionReason)> {
+		let (sink, stream) = tracing_unbounded("mpsc_eviction_notifications");
+		self.eviction_notification_sinks.lock().push(sink);
+		stream
+	}
+
 	/// Invoked when extrinsics are broadcasted.
 	pub fn on_broadcasted(&self, propagated: HashMap<ExtrinsicHash<B>, Vec<String>>) {
 		let mut listener = self.listener.write();
@@ -551,11 +694,41 @@ impl<B: ChainApi> ValidatedPool<B> {
 		invalid
 	}
 
+	/// Remove a subtree of transactions from the pool at the request of the node operator.
+	///
+	/// Like with `remove_invalid`, the transactions passed as an argument are temporarily
+	/// banned, but they are reported as removed rather than invalid.
+	pub fn remove(&self, hashes: &[ExtrinsicHash<B>]) -> Vec<TransactionFor<B>> {
+		if hashes.is_empty() {
+			return vec![];
+		}
+
+		log::debug!(target: "txpool", "Removing transactions: {:?}", hashes);
+
+		self.rotator.ban(&Instant::now(), hashes.iter().cloned());
+
+		let removed = self.pool.write().remove_subtree(hashes);
+
+		let mut listener = self.listener.write();
+		for tx in &removed {
+			listener.removed(&tx.hash);
+		}
+
+		removed
+	}
+
 	/// Get an iterator for ready transactions ordered by priority
 	pub fn ready(&self) -> impl Iterator<Item=TransactionFor<B>> + Send {
 		self.pool.read().ready()
 	}
 
+	/// Returns the hash, extrinsic and source of all future transactions.
+	pub fn futures(&self) -> Vec<(ExtrinsicHash<B>, ExtrinsicFor<B>, TransactionSource)> {
+		self.pool.read().futures()
+			.map(|tx| (tx.hash.clone(), tx.data.clone(), tx.source))
+			.collect()
+	}
+
 	/// Returns pool status.
 	pub fn status(&self) -> PoolStatus {
 		self.pool.read().status()
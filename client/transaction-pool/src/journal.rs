@@ -0,0 +1,371 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! On-disk journal of the pool contents.
+//!
+//! The journal is written periodically and when the pool shuts down. When the pool is created
+//! again, the journaled transactions are re-imported and validated against the best block.
+//!
+//! Nothing is written before the journal of the previous run has been restored, so that it isn't
+//! overwritten by an empty pool. A journal that can't be restored is moved aside.
+
+use std::{
+	collections::HashMap, fs, io, path::PathBuf,
+	sync::{Arc, atomic::{AtomicBool, Ordering}},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use codec::{Decode, Encode};
+use futures::prelude::*;
+use parking_lot::Mutex;
+use sc_transaction_graph::{ChainApi, ExtrinsicFor, ExtrinsicHash, Pool};
+use sp_runtime::{generic::BlockId, transaction_validity::TransactionSource};
+
+/// Version of the journal format.
+const VERSION: u8 = 1;
+
+/// Interval between two writes of the journal.
+pub const JOURNAL_WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A transaction in the journal.
+#[derive(Debug, PartialEq, Encode, Decode)]
+struct JournalEntry {
+	/// The encoded extrinsic.
+	extrinsic: Vec<u8>,
+	/// Where the transaction originally came from.
+	source: TransactionSource,
+	/// When the transaction was inserted into the pool, in milliseconds since the Unix epoch.
+	inserted_at: u64,
+}
+
+/// Journal of the transactions in the pool.
+pub struct Journal<Api: ChainApi> {
+	path: PathBuf,
+	pool: Arc<Pool<Api>>,
+	/// Insertion time of the transactions in the pool.
+	inserted_at: Mutex<HashMap<ExtrinsicHash<Api>, u64>>,
+	/// Whether the journal of the previous run has been restored, or moved aside.
+	restored: AtomicBool,
+}
+
+impl<Api: ChainApi> Journal<Api> {
+	/// Create a journal of the given pool, stored at `path`.
+	pub fn new(path: PathBuf, pool: Arc<Pool<Api>>) -> Self {
+		Self { path, pool, inserted_at: Default::default(), restored: AtomicBool::new(false) }
+	}
+
+	/// Write all transactions of the pool to the journal.
+	///
+	/// Returns the number of transactions written.
+	pub fn write(&self) -> io::Result<usize> {
+		let validated_pool = self.pool.validated_pool();
+		let ready = validated_pool.ready().map(|tx| (tx.hash.clone(), tx.data.clone(), tx.source));
+		let transactions = ready.chain(validated_pool.futures()).collect::<Vec<_>>();
+
+		let now = now();
+		let entries = {
+			let mut inserted_at = self.inserted_at.lock();
+			let mut known = HashMap::with_capacity(transactions.len());
+			let entries = transactions.into_iter().map(|(hash, extrinsic, source)| {
+				let time = inserted_at.get(&hash).copied().unwrap_or(now);
+				known.insert(hash, time);
+				JournalEntry { extrinsic: extrinsic.encode(), source, inserted_at: time }
+			}).collect::<Vec<_>>();
+			// Forget about the transactions that left the pool.
+			*inserted_at = known;
+			entries
+		};
+
+		if let Some(parent) = self.path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, (VERSION, &entries).encode())?;
+		fs::rename(&tmp_path, &self.path)?;
+
+		Ok(entries.len())
+	}
+
+	/// Read the journal, an absent journal is empty.
+	fn read(&self) -> io::Result<Vec<JournalEntry>> {
+		let data = match fs::read(&self.path) {
+			Ok(data) => data,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+			Err(e) => return Err(e),
+		};
+
+		match <(u8, Vec<JournalEntry>)>::decode(&mut &data[..]) {
+			Ok((VERSION, entries)) => Ok(entries),
+			Ok((version, _)) => Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("Unsupported journal version: {}", version),
+			)),
+			Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e.what())),
+		}
+	}
+
+	/// Re-import the journaled transactions at the given block.
+	///
+	/// The transactions are validated again, invalid ones are dropped. Returns the number of
+	/// imported transactions.
+	///
+	/// If the journal can't be read or its transactions can't be submitted, the journal is moved
+	/// aside instead of being overwritten by the next write.
+	pub async fn restore(&self, at: &BlockId<Api::Block>) -> usize {
+		let entries = match self.read() {
+			Ok(entries) => entries,
+			Err(e) => {
+				log::warn!(target: "txpool", "Failed to read the pool journal: {}", e);
+				self.set_aside();
+				return 0
+			},
+		};
+		let total = entries.len();
+
+		// Keep the order of the journal, but submit every source as one batch.
+		let mut batches: Vec<(TransactionSource, Vec<(ExtrinsicFor<Api>, u64)>)> = Vec::new();
+		for entry in entries {
+			let extrinsic = match ExtrinsicFor::<Api>::decode(&mut &entry.extrinsic[..]) {
+				Ok(extrinsic) => extrinsic,
+				Err(e) => {
+					log::debug!(target: "txpool", "Dropping undecodable journal entry: {}", e.what());
+					continue
+				},
+			};
+			match batches.iter_mut().find(|(source, _)| *source == entry.source) {
+				Some((_, batch)) => batch.push((extrinsic, entry.inserted_at)),
+				None => batches.push((entry.source, vec![(extrinsic, entry.inserted_at)])),
+			}
+		}
+
+		let mut imported = 0;
+		let mut failed = false;
+		for (source, batch) in batches {
+			let (xts, times): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+			let results = match self.pool.submit_at(at, source, xts, false).await {
+				Ok(results) => results,
+				Err(e) => {
+					log::warn!(target: "txpool", "Failed to restore journaled transactions: {:?}", e);
+					failed = true;
+					continue
+				},
+			};
+
+			let mut inserted_at = self.inserted_at.lock();
+			for (result, time) in results.into_iter().zip(times) {
+				if let Ok(hash) = result {
+					inserted_at.insert(hash, time);
+					imported += 1;
+				}
+			}
+		}
+
+		log::info!(
+			target: "txpool",
+			"Restored {} of {} transactions from the pool journal",
+			imported,
+			total,
+		);
+
+		if failed {
+			self.set_aside();
+		} else {
+			self.restored.store(true, Ordering::SeqCst);
+		}
+
+		imported
+	}
+
+	/// Moves the journal aside, keeping it for inspection, and allows writing a new one.
+	///
+	/// If the journal can't be moved, it is left untouched and no new journal will be written.
+	fn set_aside(&self) {
+		let aside = self.path.with_extension("old");
+		match fs::rename(&self.path, &aside) {
+			Ok(()) => {
+				log::warn!(target: "txpool", "Moved the pool journal to {}", aside.display());
+				self.restored.store(true, Ordering::SeqCst);
+			},
+			Err(e) if e.kind() == io::ErrorKind::NotFound =>
+				self.restored.store(true, Ordering::SeqCst),
+			Err(e) => log::warn!(
+				target: "txpool",
+				"Failed to move the pool journal aside, it won't be updated: {}",
+				e,
+			),
+		}
+	}
+
+	/// Background task that keeps the journal up to date.
+	///
+	/// Records when transactions are imported and writes the journal periodically. The journal
+	/// is written once more when the task is dropped, e.g. on shutdown. Nothing is written until
+	/// the journal has been restored.
+	pub async fn run<R: intervalier::IntoStream>(self: Arc<Self>, interval: R)
+	where
+		R: Send,
+		R::Guard: Send,
+	{
+		let _guard = WriteOnDrop(self.clone());
+		let imports = self.pool.validated_pool().import_notification_stream().fuse();
+		let interval = interval.into_stream().fuse();
+		futures::pin_mut!(imports, interval);
+
+		loop {
+			futures::select! {
+				hash = imports.next() => match hash {
+					Some(hash) => {
+						self.inserted_at.lock().entry(hash).or_insert_with(now);
+					},
+					None => return,
+				},
+				_tick = interval.next() => self.write_logged(),
+			}
+		}
+	}
+
+	fn write_logged(&self) {
+		if !self.restored.load(Ordering::SeqCst) {
+			log::trace!(target: "txpool", "Not writing the pool journal before it was restored");
+			return
+		}
+
+		match self.write() {
+			Ok(count) => log::trace!(target: "txpool", "Journaled {} transactions", count),
+			Err(e) => log::warn!(target: "txpool", "Failed to write the pool journal: {}", e),
+		}
+	}
+}
+
+/// Writes the journal when dropped.
+struct WriteOnDrop<Api: ChainApi>(Arc<Journal<Api>>);
+
+impl<Api: ChainApi> Drop for WriteOnDrop<Api> {
+	fn drop(&mut self) {
+		self.0.write_logged();
+	}
+}
+
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_millis() as u64)
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::executor::block_on;
+	use substrate_test_runtime_client::AccountKeyring::*;
+	use substrate_test_runtime_transaction_pool::{TestApi, uxt};
+
+	const SOURCE: TransactionSource = TransactionSource::External;
+
+	fn pool() -> Arc<Pool<TestApi>> {
+		Arc::new(Pool::new(Default::default(), TestApi::with_alice_nonce(209).into()))
+	}
+
+	#[test]
+	fn should_restore_journaled_transactions() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let pool = pool();
+		let journal = Journal::new(path.clone(), pool.clone());
+		block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 209))).unwrap();
+		block_on(pool.submit_one(&BlockId::number(0), TransactionSource::Local, uxt(Alice, 210)))
+			.unwrap();
+		// A future transaction.
+		block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 212))).unwrap();
+		assert_eq!(journal.write().unwrap(), 3);
+
+		let restarted = self::pool();
+		let journal = Journal::new(path, restarted.clone());
+		assert_eq!(block_on(journal.restore(&BlockId::number(0))), 3);
+		assert_eq!(restarted.validated_pool().status().ready, 2);
+		assert_eq!(restarted.validated_pool().status().future, 1);
+
+		let sources = restarted.validated_pool().ready().map(|tx| tx.source).collect::<Vec<_>>();
+		assert_eq!(sources, vec![SOURCE, TransactionSource::Local]);
+	}
+
+	#[test]
+	fn should_drop_invalid_transactions_on_restore() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let pool = pool();
+		block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 209))).unwrap();
+		block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 210))).unwrap();
+		Journal::new(path.clone(), pool).write().unwrap();
+
+		// The first transaction was included in a block in the meantime.
+		let api = TestApi::with_alice_nonce(210);
+		let restarted = Arc::new(Pool::new(Default::default(), api.into()));
+		let journal = Journal::new(path, restarted.clone());
+
+		assert_eq!(block_on(journal.restore(&BlockId::number(0))), 1);
+		assert_eq!(restarted.validated_pool().status().ready, 1);
+	}
+
+	#[test]
+	fn should_not_write_before_restore() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+
+		let pool = pool();
+		block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 209))).unwrap();
+		Journal::new(path.clone(), pool).write().unwrap();
+
+		// The restarted pool shuts down before restoring the journal.
+		let restarted = self::pool();
+		let journal = Arc::new(Journal::new(path.clone(), restarted.clone()));
+		drop(WriteOnDrop(journal.clone()));
+		assert_eq!(journal.read().unwrap().len(), 1);
+
+		assert_eq!(block_on(journal.restore(&BlockId::number(0))), 1);
+		block_on(restarted.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 210))).unwrap();
+		drop(WriteOnDrop(journal.clone()));
+		assert_eq!(journal.read().unwrap().len(), 2);
+	}
+
+	#[test]
+	fn should_keep_unreadable_journal() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("journal");
+		fs::write(&path, b"garbage").unwrap();
+
+		let pool = pool();
+		let journal = Journal::new(path.clone(), pool.clone());
+		assert_eq!(block_on(journal.restore(&BlockId::number(0))), 0);
+		assert_eq!(fs::read(path.with_extension("old")).unwrap(), b"garbage".to_vec());
+
+		block_on(pool.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 209))).unwrap();
+		journal.write_logged();
+		assert_eq!(journal.read().unwrap().len(), 1);
+	}
+
+	#[test]
+	fn missing_journal_is_empty() {
+		let dir = tempfile::tempdir().unwrap();
+		let journal = Journal::new(dir.path().join("journal"), pool());
+
+		assert_eq!(block_on(journal.restore(&BlockId::number(0))), 0);
+	}
+}
@@ -23,6 +23,7 @@
 #![warn(unused_extern_crates)]
 
 mod api;
+mod journal;
 mod revalidation;
 mod metrics;
 
@@ -34,7 +35,9 @@ pub mod testing;
 pub use sc_transaction_graph as txpool;
 pub use crate::api::{FullChainApi, LightChainApi};
 
-use std::{collections::{HashMap, HashSet}, sync::Arc, pin::Pin};
+use std::{
+	collections::{HashMap, HashSet}, sync::{Arc, atomic::{AtomicBool, Ordering}}, pin::Pin,
+};
 use futures::{prelude::*, future::{self, ready}, channel::oneshot};
 use parking_lot::Mutex;
 
@@ -45,9 +48,10 @@ use sp_runtime::{
 use sp_transaction_pool::{
 	TransactionPool, PoolStatus, ImportNotificationStream, TxHash, TransactionFor,
 	TransactionStatusStreamFor, MaintainedTransactionPool, PoolFuture, ChainEvent,
-	TransactionSource,
+	TransactionSource, TransactionDetails, DropReason,
 };
 use sc_transaction_graph::{ChainApi, ExtrinsicHash};
+use sp_blockchain::HeaderBackend;
 use wasm_timer::Instant;
 
 use prometheus_endpoint::Registry as PrometheusRegistry;
@@ -75,6 +79,9 @@ pub struct BasicPool<PoolApi, Block>
 	revalidation_queue: Arc<revalidation::RevalidationQueue<PoolApi>>,
 	ready_poll: Arc<Mutex<ReadyPoll<ReadyIteratorFor<PoolApi>, Block>>>,
 	metrics: PrometheusMetrics,
+	journal: Option<Arc<journal::Journal<PoolApi>>>,
+	/// Whether the journal still has to be restored.
+	journal_pending: AtomicBool,
 }
 
 struct ReadyPoll<T, Block: BlockT> {
@@ -180,6 +187,8 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 				revalidation_strategy: Arc::new(Mutex::new(RevalidationStrategy::Always)),
 				ready_poll: Default::default(),
 				metrics: Default::default(),
+				journal: None,
+				journal_pending: AtomicBool::new(false),
 			},
 			background_task,
 			notifier,
@@ -188,12 +197,17 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 
 	/// Create new basic transaction pool with provided api and custom
 	/// revalidation type.
+	///
+	/// If the options contain a journal, the journaled transactions are restored at the best
+	/// block the pool is started at, see [`notification_future`], and the returned background
+	/// task keeps the journal up to date from then on.
 	pub fn with_revalidation_type(
 		options: sc_transaction_graph::Options,
 		pool_api: Arc<PoolApi>,
 		prometheus: Option<&PrometheusRegistry>,
 		revalidation_type: RevalidationType,
 	) -> (Self, Option<Pin<Box<dyn Future<Output=()> + Send>>>) {
+		let journal_path = options.journal.clone();
 		let pool = Arc::new(sc_transaction_graph::Pool::new(options, pool_api.clone()));
 		let (revalidation_queue, background_task) = match revalidation_type {
 			RevalidationType::Light => (revalidation::RevalidationQueue::new(pool_api.clone(), pool.clone()), None),
@@ -203,6 +217,35 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 			},
 		};
 
+		let journal = journal_path.map(|path| Arc::new(journal::Journal::new(path, pool.clone())));
+		let journal_task = journal.clone().map(|journal| {
+			journal.run(intervalier::Interval::new(journal::JOURNAL_WRITE_INTERVAL)).boxed()
+		});
+
+		let metrics = PrometheusMetrics::new(prometheus);
+		let evictions_task = if metrics.is_enabled() {
+			let metrics = metrics.clone();
+			let evictions = pool.validated_pool().eviction_notification_stream();
+			Some(evictions.for_each(move |(_, reason)| {
+				metrics.report(|metrics| {
+					metrics.evicted_transactions.with_label_values(&[reason.as_str()]).inc()
+				});
+				future::ready(())
+			}).boxed())
+		} else {
+			None
+		};
+
+		let tasks = background_task.into_iter()
+			.chain(journal_task)
+			.chain(evictions_task)
+			.collect::<Vec<_>>();
+		let background_task = if tasks.is_empty() {
+			None
+		} else {
+			Some(future::join_all(tasks).map(drop).boxed())
+		};
+
 		(
 			BasicPool {
 				api: pool_api,
@@ -215,7 +258,9 @@ impl<PoolApi, Block> BasicPool<PoolApi, Block>
 					}
 				)),
 				ready_poll: Default::default(),
-				metrics: PrometheusMetrics::new(prometheus),
+				metrics,
+				journal_pending: AtomicBool::new(journal.is_some()),
+				journal,
 			},
 			background_task,
 		)
@@ -280,6 +325,26 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		}.boxed()
 	}
 
+	fn submit_bundle(
+		&self,
+		at: &BlockId<Self::Block>,
+		source: TransactionSource,
+		xts: Vec<TransactionFor<Self>>,
+	) -> PoolFuture<Vec<TxHash<Self>>, Self::Error> {
+		let pool = self.pool.clone();
+		let at = *at;
+
+		self.metrics.report(|metrics| metrics.validations_scheduled.inc_by(xts.len() as u64));
+
+		let metrics = self.metrics.clone();
+		async move {
+			let tx_count = xts.len();
+			let res = pool.submit_bundle(&at, source, xts).await;
+			metrics.report(|metrics| metrics.validations_finished.inc_by(tx_count as u64));
+			res
+		}.boxed()
+	}
+
 	fn submit_and_watch(
 		&self,
 		at: &BlockId<Self::Block>,
@@ -309,6 +374,10 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		removed
 	}
 
+	fn remove(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>> {
+		self.pool.validated_pool().remove(hashes)
+	}
+
 	fn status(&self) -> PoolStatus {
 		self.pool.validated_pool().status()
 	}
@@ -329,6 +398,18 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		self.pool.validated_pool().ready_by_hash(hash)
 	}
 
+	fn bundle_of(&self, hash: &TxHash<Self>) -> Option<Vec<TxHash<Self>>> {
+		self.pool.validated_pool().bundle_of(hash)
+	}
+
+	fn inspect(&self) -> Vec<TransactionDetails<TxHash<Self>>> {
+		self.pool.validated_pool().inspect()
+	}
+
+	fn recently_dropped(&self) -> Vec<(TxHash<Self>, DropReason<TxHash<Self>>)> {
+		self.pool.validated_pool().recently_dropped()
+	}
+
 	fn ready_at(&self, at: NumberFor<Self::Block>) -> PolledIterator<PoolApi> {
 		if self.ready_poll.lock().updated_at() >= at {
 			log::trace!(target: "txpool", "Transaction pool already processed block  #{}", at);
@@ -547,8 +628,19 @@ impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 				let revalidation_queue = self.revalidation_queue.clone();
 				let ready_poll = self.ready_poll.clone();
 				let metrics = self.metrics.clone();
+				let journal = if is_new_best && self.journal_pending.swap(false, Ordering::SeqCst) {
+					self.journal.clone()
+				} else {
+					None
+				};
 
 				async move {
+					// Bring back the transactions of the previous run, before anything else
+					// touches the pool.
+					if let Some(journal) = journal {
+						journal.restore(&id).await;
+					}
+
 					// We keep track of everything we prune so that later we won't add
 					// tranactions with those hashes from the retracted blocks.
 					let mut pruned_log = HashSet::<ExtrinsicHash<PoolApi>>::new();
@@ -685,13 +777,16 @@ impl<PoolApi, Block> MaintainedTransactionPool for BasicPool<PoolApi, Block>
 }
 
 /// Inform the transaction pool about imported and finalized blocks.
+///
+/// The pool is first informed about the current best block, which restores its journal before
+/// any block is imported.
 pub async fn notification_future<Client, Pool, Block>(
 	client: Arc<Client>,
 	txpool: Arc<Pool>
 )
 	where
 		Block: BlockT,
-		Client: sc_client_api::BlockchainEvents<Block>,
+		Client: sc_client_api::BlockchainEvents<Block> + HeaderBackend<Block>,
 		Pool: MaintainedTransactionPool<Block=Block>,
 {
 	let import_stream = client.import_notification_stream().map(Into::into).fuse();
@@ -699,6 +794,21 @@ pub async fn notification_future<Client, Pool, Block>(
 		.map(Into::into)
 		.fuse();
 
+	let best_hash = client.info().best_hash;
+	match client.header(BlockId::Hash(best_hash)) {
+		Ok(Some(header)) => txpool.maintain(ChainEvent::NewBlock {
+			is_new_best: true,
+			hash: best_hash,
+			header,
+			tree_route: None,
+		}).await,
+		Ok(None) | Err(_) => log::warn!(
+			target: "txpool",
+			"Unable to fetch the best header {:?} to start the pool at",
+			best_hash,
+		),
+	}
+
 	futures::stream::select(import_stream, finality_stream)
 		.for_each(|evt| txpool.maintain(evt))
 		.await
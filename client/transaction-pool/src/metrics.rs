@@ -20,7 +20,7 @@
 
 use std::sync::Arc;
 
-use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use prometheus_endpoint::{register, Counter, CounterVec, Opts, PrometheusError, Registry, U64};
 
 #[derive(Clone, Default)]
 pub struct MetricsLink(Arc<Option<Metrics>>);
@@ -41,6 +41,11 @@ impl MetricsLink {
 			do_this(metrics);
 		}
 	}
+
+	/// Returns `true` if the metrics are registered.
+	pub fn is_enabled(&self) -> bool {
+		self.0.is_some()
+	}
 }
 
 /// Transaction pool Prometheus metrics.
@@ -50,6 +55,7 @@ pub struct Metrics {
 	pub validations_invalid: Counter<U64>,
 	pub block_transactions_pruned: Counter<U64>,
 	pub block_transactions_resubmitted: Counter<U64>,
+	pub evicted_transactions: CounterVec<U64>,
 }
 
 impl Metrics {
@@ -90,6 +96,16 @@ impl Metrics {
 				)?,
 				registry,
 			)?,
+			evicted_transactions: register(
+				CounterVec::new(
+					Opts::new(
+						"sub_txpool_evicted_transactions",
+						"Total number of transactions that were evicted to keep the pool within its limits",
+					),
+					&["reason"],
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
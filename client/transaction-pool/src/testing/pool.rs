@@ -1066,3 +1066,29 @@ fn import_notification_to_pool_maintain_works() {
 	block_on(pool.maintain(evt.into()));
 	assert_eq!(pool.status().ready, 0);
 }
+
+#[test]
+fn journal_is_restored_when_the_pool_starts() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("journal");
+	let client = Arc::new(substrate_test_runtime_client::new());
+
+	let api = Arc::new(FullChainApi::new(client.clone()));
+	let previous = Arc::new(Pool::new(Default::default(), api));
+	block_on(previous.submit_one(&BlockId::number(0), SOURCE, uxt(Alice, 0))).unwrap();
+	journal::Journal::new(path.clone(), previous).write().unwrap();
+
+	let options = txpool::Options { journal: Some(path), ..Default::default() };
+	let pool = Arc::new(
+		BasicPool::new(options, Arc::new(FullChainApi::new(client.clone())), None).0
+	);
+	assert_eq!(pool.status().ready, 0);
+
+	// No block is imported, the journal is restored at the best block right away.
+	let notifications = notification_future(client, pool.clone());
+	futures::pin_mut!(notifications);
+	block_on(futures::future::poll_fn(|cx| {
+		let _ = notifications.as_mut().poll(cx);
+		if pool.status().ready == 1 { Poll::Ready(()) } else { Poll::Pending }
+	}));
+}
@@ -29,6 +29,15 @@
 //! ### Public Functions
 //!
 //! - `slot_duration` - Determine the Aura slot-duration based on the Timestamp module configuration.
+//! - `secondary_authors_allowed` - Whether the backup author of a slot may author a block when
+//! the primary author fails to do so in time.
+//! - `submit_unsigned_equivocation_report` - Submit a report of an authority that authored
+//! two different blocks at the same slot.
+//!
+//! ### Dispatchable Functions
+//!
+//! - `report_equivocation` - Report an authority equivocation, checked against the historical
+//! session membership of the offender and handed over to the offences module.
 //!
 //! ## Related Modules
 //!
@@ -49,26 +58,68 @@ use pallet_timestamp;
 use sp_std::{result, prelude::*};
 use codec::{Encode, Decode};
 use frame_support::{
-	decl_storage, decl_module, Parameter, traits::{Get, FindAuthor},
+	decl_error, decl_storage, decl_module, Parameter,
+	dispatch::DispatchResult,
+	traits::{Get, FindAuthor, KeyOwnerProofSystem},
 	ConsensusEngineId,
 };
+use frame_system::ensure_none;
 use sp_runtime::{
-	RuntimeAppPublic,
+	KeyTypeId, RuntimeAppPublic,
 	traits::{SaturatedConversion, Saturating, Zero, Member, IsMember}, generic::DigestItem,
 };
+use sp_staking::SessionIndex;
 use sp_timestamp::OnTimestampSet;
 use sp_inherents::{InherentIdentifier, InherentData, ProvideInherent, MakeFatalError};
 use sp_consensus_aura::{
-	AURA_ENGINE_ID, ConsensusLog, AuthorityIndex,
+	AURA_ENGINE_ID, ConsensusLog, AuthorityIndex, EquivocationProof, PreDigest,
 	inherents::{INHERENT_IDENTIFIER, AuraInherentData},
 };
 
+mod equivocation;
 mod mock;
 mod tests;
 
+pub use equivocation::{
+	AuraEquivocation, AuraEquivocationOffence, EquivocationHandler, HandleEquivocation,
+};
+pub use sp_consensus_slots::SlotOffence;
+pub use pallet_session::{GetSessionNumber, GetValidatorCount};
+
 pub trait Trait: pallet_timestamp::Trait {
 	/// The identifier type for an authority.
 	type AuthorityId: Member + Parameter + RuntimeAppPublic + Default;
+
+	/// A system for proving ownership of keys, i.e. that a given key was part
+	/// of a validator set, needed for validating equivocation reports.
+	type KeyOwnerProofSystem: KeyOwnerProofSystem<
+		(KeyTypeId, Self::AuthorityId),
+		Proof = Self::KeyOwnerProof,
+		IdentificationTuple = Self::KeyOwnerIdentification,
+	>;
+
+	/// The proof of key ownership, used for validating equivocation reports.
+	/// The proof must include the session index and validator count of the
+	/// session at which the equivocation occurred.
+	type KeyOwnerProof: Parameter + GetSessionNumber + GetValidatorCount;
+
+	/// The identification of a key owner, used when reporting equivocations.
+	type KeyOwnerIdentification: Parameter;
+
+	/// The equivocation handling subsystem, defines methods to report an
+	/// offence (after the equivocation has been validated) and for submitting a
+	/// transaction to report an equivocation (from an offchain context).
+	/// NOTE: when enabling equivocation handling (i.e. this type isn't set to
+	/// `()`) you must use this pallet's `ValidateUnsigned` in the runtime
+	/// definition.
+	type HandleEquivocation: HandleEquivocation<Self>;
+
+	/// Whether the backup author of a slot may author a block when the
+	/// primary author's block hasn't arrived in time. Blocks by backup
+	/// authors are weighted below those by primary authors when choosing
+	/// between forks. Nodes only author and accept such blocks while this
+	/// returns `true`.
+	type AllowSecondaryAuthors: Get<bool>;
 }
 
 decl_storage! {
@@ -78,15 +129,94 @@ decl_storage! {
 
 		/// The current authorities
 		pub Authorities get(fn authorities): Vec<T::AuthorityId>;
+
+		/// The first slot of each session, used to check that reported
+		/// equivocations belong to the session of the key ownership proof.
+		///
+		/// TWOX-NOTE: `SessionIndex` is an increasing integer, so this is okay.
+		pub SessionStartSlot get(fn session_start_slot):
+			map hasher(twox_64_concat) SessionIndex => Option<u64>;
 	}
 	add_extra_genesis {
 		config(authorities): Vec<T::AuthorityId>;
-		build(|config| Module::<T>::initialize_authorities(&config.authorities))
+		build(|config| {
+			Module::<T>::initialize_authorities(&config.authorities);
+			SessionStartSlot::insert(0, 0);
+		})
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// An equivocation proof provided as part of an equivocation report is invalid.
+		InvalidEquivocationProof,
+		/// A key ownership proof provided as part of an equivocation report is invalid.
+		InvalidKeyOwnershipProof,
+		/// A given equivocation report is valid but already previously reported.
+		DuplicateOffenceReport,
+	}
+}
+
+/// Functions for calculating the weight of dispatchables.
+mod weight_for {
+	use frame_support::{
+		traits::Get,
+		weights::{Weight, constants::{WEIGHT_PER_MICROS, WEIGHT_PER_NANOS}},
+	};
+
+	/// Calculate the weight for `report_equivocation`.
+	///
+	/// The weight is dominated by checking the key ownership proof and by
+	/// reporting the offence, which may slash the offender and all of its
+	/// nominators, so we assume a lower bound of validators and an upper
+	/// bound of nominators.
+	pub(crate) fn report_equivocation<T: super::Trait>(validator_count: u32) -> Weight {
+		// we take the validator set count from the membership proof to
+		// calculate the weight but we set a floor of 100 validators.
+		let validator_count = validator_count.max(100) as u64;
+
+		// worst case we are considering is that the given offender
+		// is backed by 200 nominators
+		const MAX_NOMINATORS: u64 = 200;
+
+		// checking membership proof
+		(35 * WEIGHT_PER_MICROS)
+			.saturating_add((175 * WEIGHT_PER_NANOS).saturating_mul(validator_count))
+			.saturating_add(T::DbWeight::get().reads(5))
+			// check equivocation proof
+			.saturating_add(110 * WEIGHT_PER_MICROS)
+			// report offence
+			.saturating_add(110 * WEIGHT_PER_MICROS)
+			.saturating_add(25 * WEIGHT_PER_MICROS * MAX_NOMINATORS)
+			.saturating_add(T::DbWeight::get().reads(14 + 3 * MAX_NOMINATORS))
+			.saturating_add(T::DbWeight::get().writes(10 + 3 * MAX_NOMINATORS))
 	}
 }
 
 decl_module! {
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin { }
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		/// Report authority equivocation/misbehavior. This method will verify
+		/// the equivocation proof and validate the given key ownership proof
+		/// against the extracted offender. If both are valid, the offence will
+		/// be reported.
+		///
+		/// This extrinsic must be called unsigned and it is expected that only
+		/// block authors will call it (validated in `ValidateUnsigned`), as such
+		/// if the block author is defined it will be defined as the equivocation
+		/// reporter.
+		#[weight = weight_for::report_equivocation::<T>(key_owner_proof.validator_count())]
+		fn report_equivocation(
+			origin,
+			equivocation_proof: EquivocationProof<T::Header, T::AuthorityId>,
+			key_owner_proof: T::KeyOwnerProof,
+		) {
+			ensure_none(origin)?;
+
+			Self::do_report_equivocation(equivocation_proof, key_owner_proof)?;
+		}
+	}
 }
 
 impl<T: Trait> Module<T> {
@@ -100,6 +230,83 @@ impl<T: Trait> Module<T> {
 		<frame_system::Module<T>>::deposit_log(log.into());
 	}
 
+	/// Whether the given slot is part of the given session, according to the
+	/// recorded session start slots.
+	fn is_slot_in_session(slot: u64, session_index: SessionIndex) -> bool {
+		let start = match SessionStartSlot::get(session_index) {
+			Some(start) => start,
+			None => return false,
+		};
+
+		let next_start = SessionStartSlot::get(session_index.saturating_add(1));
+
+		slot >= start && next_start.map_or(true, |next_start| slot < next_start)
+	}
+
+	/// Verifies the given equivocation report, returning the identification of
+	/// the offender if both the equivocation proof and the key ownership proof
+	/// are valid.
+	pub fn check_equivocation_report(
+		equivocation_proof: &EquivocationProof<T::Header, T::AuthorityId>,
+		key_owner_proof: &T::KeyOwnerProof,
+	) -> Result<T::KeyOwnerIdentification, Error<T>> {
+		// validate the equivocation proof (check headers are different,
+		// target the same slot and are signed by the offender).
+		if !sp_consensus_aura::check_equivocation_proof(equivocation_proof.clone()) {
+			return Err(Error::InvalidEquivocationProof);
+		}
+
+		// the equivocation slot must be part of the session of the key
+		// ownership proof.
+		if !Self::is_slot_in_session(equivocation_proof.slot_number, key_owner_proof.session()) {
+			return Err(Error::InvalidKeyOwnershipProof);
+		}
+
+		// check the membership proof and extract the offender's id
+		T::KeyOwnerProofSystem::check_proof(
+			(T::AuthorityId::ID, equivocation_proof.offender.clone()),
+			key_owner_proof.clone(),
+		).ok_or(Error::InvalidKeyOwnershipProof)
+	}
+
+	fn do_report_equivocation(
+		equivocation_proof: EquivocationProof<T::Header, T::AuthorityId>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> DispatchResult {
+		let offender = Self::check_equivocation_report(&equivocation_proof, &key_owner_proof)?;
+
+		let offence = <T::HandleEquivocation as HandleEquivocation<T>>::Offence::new(
+			key_owner_proof.session(),
+			key_owner_proof.validator_count(),
+			offender,
+			equivocation_proof.slot_number,
+		);
+
+		let reporters = match T::HandleEquivocation::block_author() {
+			Some(author) => vec![author],
+			None => vec![],
+		};
+
+		T::HandleEquivocation::report_offence(reporters, offence)
+			.map_err(|_| Error::<T>::DuplicateOffenceReport)?;
+
+		Ok(())
+	}
+
+	/// Submits an extrinsic to report an equivocation. This method will create
+	/// an unsigned extrinsic with a call to `report_equivocation` and
+	/// will push the transaction to the pool. Only useful in an offchain
+	/// context.
+	pub fn submit_unsigned_equivocation_report(
+		equivocation_proof: EquivocationProof<T::Header, T::AuthorityId>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> Option<()> {
+		T::HandleEquivocation::submit_unsigned_equivocation_report(
+			equivocation_proof,
+			key_owner_proof,
+		).ok()
+	}
+
 	fn initialize_authorities(authorities: &[T::AuthorityId]) {
 		if !authorities.is_empty() {
 			assert!(<Authorities<T>>::get().is_empty(), "Authorities are already initialized!");
@@ -112,7 +319,7 @@ impl<T: Trait> sp_runtime::BoundToRuntimeAppPublic for Module<T> {
 	type Public = T::AuthorityId;
 }
 
-impl<T: Trait> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
+impl<T: Trait + pallet_session::Trait> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
 	type Key = T::AuthorityId;
 
 	fn on_genesis_session<'a, I: 'a>(validators: I)
@@ -125,6 +332,17 @@ impl<T: Trait> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
 	fn on_new_session<'a, I: 'a>(changed: bool, validators: I, _queued_validators: I)
 		where I: Iterator<Item=(&'a T::AccountId, T::AuthorityId)>
 	{
+		// the new session starts with the block being initialized, i.e. at the
+		// slot following the one of the last block.
+		let slot_duration = Self::slot_duration();
+		if !slot_duration.is_zero() {
+			let last_slot = (Self::last() / slot_duration).saturated_into::<u64>();
+			SessionStartSlot::insert(
+				<pallet_session::Module<T>>::current_index(),
+				last_slot.saturating_add(1),
+			);
+		}
+
 		// instant changes
 		if changed {
 			let next_authorities = validators.map(|(_, k)| k).collect::<Vec<_>>();
@@ -151,9 +369,12 @@ impl<T: Trait> FindAuthor<u32> for Module<T> {
 	{
 		for (id, mut data) in digests.into_iter() {
 			if id == AURA_ENGINE_ID {
-				if let Ok(slot_num) = u64::decode(&mut data) {
-					let author_index = slot_num % Self::authorities().len() as u64;
-					return Some(author_index as u32)
+				if let Ok(pre_digest) = PreDigest::decode(&mut data) {
+					if !pre_digest.is_primary() && !T::AllowSecondaryAuthors::get() {
+						return None
+					}
+
+					return pre_digest.author_index(Self::authorities().len())
 				}
 			}
 		}
@@ -199,6 +420,12 @@ impl<T: Trait> Module<T> {
 		<T as pallet_timestamp::Trait>::MinimumPeriod::get().saturating_mul(2.into())
 	}
 
+	/// Whether the backup author of a slot may author a block when the primary
+	/// author fails to do so in time.
+	pub fn secondary_authors_allowed() -> bool {
+		T::AllowSecondaryAuthors::get()
+	}
+
 	fn on_timestamp_set(now: T::Moment, slot_duration: T::Moment) {
 		let last = Self::last();
 		<Self as Store>::LastTimestamp::put(now);
@@ -19,20 +19,37 @@
 
 #![cfg(test)]
 
-use crate::{Trait, Module, GenesisConfig};
-use sp_consensus_aura::ed25519::AuthorityId;
+use std::cell::RefCell;
+use codec::Encode;
+use crate::{Trait, Module, GenesisConfig, AuraEquivocationOffence};
+use sp_consensus_aura::{
+	AURA_ENGINE_ID, EquivocationProof,
+	ed25519::{AuthorityId, AuthorityPair},
+};
 use sp_runtime::{
-	traits::IdentityLookup, Perbill,
-	testing::{Header, UintAuthorityId},
+	traits::{Header as _, IdentityLookup}, ConsensusEngineId, Perbill, RuntimeAppPublic,
+	testing::{Digest, DigestItem, Header, TestXt, UintAuthorityId},
+};
+use frame_support::{
+	impl_outer_dispatch, impl_outer_origin, parameter_types,
+	traits::{FindAuthor, Get, KeyOwnerProofSystem},
+	weights::Weight,
 };
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
 use sp_io;
-use sp_core::H256;
+use sp_core::{H256, U256, crypto::{KeyTypeId, Pair}};
+use sp_session::MembershipProof;
+use sp_staking::offence::{OffenceError, ReportOffence};
 
 impl_outer_origin!{
 	pub enum Origin for Test  where system = frame_system {}
 }
 
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		aura::Aura,
+	}
+}
+
 // Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Test;
@@ -50,7 +67,7 @@ impl frame_system::Trait for Test {
 	type Origin = Origin;
 	type Index = u64;
 	type BlockNumber = u64;
-	type Call = ();
+	type Call = Call;
 	type Hash = H256;
 	type Hashing = ::sp_runtime::traits::BlakeTwo256;
 	type AccountId = u64;
@@ -78,8 +95,121 @@ impl pallet_timestamp::Trait for Test {
 	type MinimumPeriod = MinimumPeriod;
 }
 
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = TestXt<Call, ()>;
+}
+
+/// The author of every block.
+pub const BLOCK_AUTHOR: u64 = 42;
+
+/// Finds `BLOCK_AUTHOR` as the author of every block.
+pub struct TestAuthor;
+
+impl FindAuthor<u64> for TestAuthor {
+	fn find_author<'a, I>(_: I) -> Option<u64>
+		where I: 'a + IntoIterator<Item=(ConsensusEngineId, &'a [u8])>
+	{
+		Some(BLOCK_AUTHOR)
+	}
+}
+
+impl pallet_authorship::Trait for Test {
+	type FindAuthor = TestAuthor;
+	type UncleGenerations = ();
+	type FilterUncle = ();
+	type EventHandler = ();
+}
+
 impl Trait for Test {
 	type AuthorityId = AuthorityId;
+
+	type KeyOwnerProofSystem = TestKeyOwnerProofSystem;
+	type KeyOwnerProof = MembershipProof;
+	type KeyOwnerIdentification = u64;
+	type HandleEquivocation = crate::EquivocationHandler<u64, OffenceRecorder>;
+	type AllowSecondaryAuthors = AllowSecondaryAuthors;
+}
+
+/// A key ownership proof system over the current AURA authorities. The proof
+/// commits to the encoded authority key instead of a merkle proof of session
+/// membership, and the owner is identified by its authority index. Proofs are
+/// generated for the genesis session.
+pub struct TestKeyOwnerProofSystem;
+
+impl KeyOwnerProofSystem<(KeyTypeId, AuthorityId)> for TestKeyOwnerProofSystem {
+	type Proof = MembershipProof;
+	type IdentificationTuple = u64;
+
+	fn prove(key: (KeyTypeId, AuthorityId)) -> Option<Self::Proof> {
+		let authorities = Aura::authorities();
+		if !authorities.contains(&key.1) {
+			return None;
+		}
+
+		Some(MembershipProof {
+			session: 0,
+			trie_nodes: vec![key.1.encode()],
+			validator_count: authorities.len() as u32,
+		})
+	}
+
+	fn check_proof(key: (KeyTypeId, AuthorityId), proof: Self::Proof) -> Option<u64> {
+		if key.0 != AuthorityId::ID || proof.trie_nodes != vec![key.1.encode()] {
+			return None;
+		}
+
+		Aura::authorities()
+			.iter()
+			.position(|id| *id == key.1)
+			.map(|index| index as u64)
+	}
+}
+
+thread_local! {
+	/// The offences reported so far, as `(offender, session_index, slot)`.
+	pub static OFFENCES: RefCell<Vec<(u64, u32, u64)>> = RefCell::new(Vec::new());
+
+	/// The reporters of the offences reported so far.
+	pub static REPORTERS: RefCell<Vec<Vec<u64>>> = RefCell::new(Vec::new());
+
+	/// Whether backup authors are allowed, see `AllowSecondaryAuthors`.
+	pub static SECONDARY_AUTHORS: RefCell<bool> = RefCell::new(false);
+}
+
+/// Allows backup authors when `SECONDARY_AUTHORS` is set.
+pub struct AllowSecondaryAuthors;
+
+impl Get<bool> for AllowSecondaryAuthors {
+	fn get() -> bool {
+		SECONDARY_AUTHORS.with(|allowed| *allowed.borrow())
+	}
+}
+
+/// Records reported offences in `OFFENCES`, rejecting duplicate reports.
+pub struct OffenceRecorder;
+
+impl ReportOffence<u64, u64, AuraEquivocationOffence<u64>> for OffenceRecorder {
+	fn report_offence(
+		reporters: Vec<u64>,
+		offence: AuraEquivocationOffence<u64>,
+	) -> Result<(), OffenceError> {
+		OFFENCES.with(|offences| {
+			let mut offences = offences.borrow_mut();
+			let offence = (offence.offender, offence.session_index, offence.slot);
+
+			if offences.contains(&offence) {
+				return Err(OffenceError::DuplicateReport);
+			}
+
+			offences.push(offence);
+			REPORTERS.with(|r| r.borrow_mut().push(reporters));
+			Ok(())
+		})
+	}
 }
 
 pub fn new_test_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
@@ -90,4 +220,50 @@ pub fn new_test_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
 	t.into()
 }
 
+pub fn new_test_ext_with_pairs(
+	authorities_len: usize,
+) -> (Vec<AuthorityPair>, sp_io::TestExternalities) {
+	let pairs = (0..authorities_len).map(|i| {
+		AuthorityPair::from_seed(&U256::from(i).into())
+	}).collect::<Vec<_>>();
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig::<Test>{
+		authorities: pairs.iter().map(|p| p.public()).collect(),
+	}.assimilate_storage(&mut t).unwrap();
+	(pairs, t.into())
+}
+
+/// Creates an equivocation proof at the given slot for two distinct headers
+/// signed by `offender_pair`.
+pub fn generate_equivocation_proof(
+	offender_pair: &AuthorityPair,
+	slot_number: u64,
+) -> EquivocationProof<Header, AuthorityId> {
+	let make_header = |extrinsics_root| {
+		let mut header = Header {
+			parent_hash: Default::default(),
+			number: 1,
+			state_root: Default::default(),
+			extrinsics_root,
+			digest: Digest {
+				logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot_number.encode())],
+			},
+		};
+
+		let pre_hash = header.hash();
+		let signature = offender_pair.sign(pre_hash.as_ref());
+		header.digest_mut().push(DigestItem::Seal(AURA_ENGINE_ID, signature.encode()));
+
+		header
+	};
+
+	EquivocationProof {
+		offender: offender_pair.public(),
+		slot_number,
+		first_header: make_header(H256::repeat_byte(1)),
+		second_header: make_header(H256::repeat_byte(2)),
+	}
+}
+
 pub type Aura = Module<Test>;
@@ -19,7 +19,16 @@
 
 #![cfg(test)]
 
-use crate::mock::{Aura, new_test_ext};
+use codec::Encode;
+use crate::{Error, SessionStartSlot, mock::*};
+use frame_support::{
+	assert_err, assert_ok, StorageMap,
+	traits::{FindAuthor, KeyOwnerProofSystem},
+	unsigned::{TransactionSource, ValidateUnsigned},
+};
+use sp_consensus_aura::{AURA_ENGINE_ID, PreDigest, ed25519::AuthorityId};
+use sp_core::crypto::Pair;
+use sp_runtime::{RuntimeAppPublic, transaction_validity::InvalidTransaction};
 
 #[test]
 fn initial_values() {
@@ -28,3 +37,183 @@ fn initial_values() {
 		assert_eq!(Aura::authorities().len(), 4);
 	});
 }
+
+#[test]
+fn find_author_honours_secondary_authors() {
+	new_test_ext(vec![0, 1, 2, 3]).execute_with(|| {
+		let find_author = |pre_digest: PreDigest| {
+			let data = pre_digest.encode();
+			Aura::find_author(vec![(AURA_ENGINE_ID, &data[..])])
+		};
+
+		assert_eq!(find_author(PreDigest::primary(5)), Some(1));
+
+		// blocks by the backup author are only recognized if allowed.
+		assert_eq!(find_author(PreDigest::secondary(5, 2)), None);
+
+		SECONDARY_AUTHORS.with(|allowed| *allowed.borrow_mut() = true);
+		assert!(Aura::secondary_authors_allowed());
+		assert_eq!(find_author(PreDigest::secondary(5, 2)), Some(2));
+
+		// and only the backup author of the slot is recognized.
+		assert_eq!(find_author(PreDigest::secondary(5, 3)), None);
+	});
+}
+
+fn reported_offences() -> Vec<(u64, u32, u64)> {
+	OFFENCES.with(|offences| offences.borrow().clone())
+}
+
+#[test]
+fn report_equivocation_works() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let offender_pair = &pairs[1];
+		let equivocation_proof = generate_equivocation_proof(offender_pair, 5);
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(AuthorityId::ID, offender_pair.public()),
+		).unwrap();
+
+		assert_ok!(Aura::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof));
+
+		assert_eq!(reported_offences(), vec![(1, 0, 5)]);
+		// the author of the block is the reporter
+		assert_eq!(REPORTERS.with(|r| r.borrow().clone()), vec![vec![BLOCK_AUTHOR]]);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_equivocation_proof() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let offender_pair = &pairs[0];
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(AuthorityId::ID, offender_pair.public()),
+		).unwrap();
+
+		let assert_invalid_equivocation = |equivocation_proof| {
+			assert_err!(
+				Aura::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof.clone()),
+				Error::<Test>::InvalidEquivocationProof,
+			)
+		};
+
+		let valid_proof = generate_equivocation_proof(offender_pair, 5);
+
+		// both headers are the same
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.second_header = equivocation_proof.first_header.clone();
+		assert_invalid_equivocation(equivocation_proof);
+
+		// the slot in the proof doesn't match the one in the headers
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.slot_number = 6;
+		assert_invalid_equivocation(equivocation_proof);
+
+		// the headers target different slots
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.second_header = generate_equivocation_proof(offender_pair, 6).second_header;
+		assert_invalid_equivocation(equivocation_proof);
+
+		// one of the headers is signed by a different authority
+		let mut equivocation_proof = valid_proof;
+		equivocation_proof.second_header = generate_equivocation_proof(&pairs[1], 5).second_header;
+		assert_invalid_equivocation(equivocation_proof);
+
+		assert!(reported_offences().is_empty());
+	});
+}
+
+#[test]
+fn report_equivocation_checks_session_of_slot() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		// the second session starts at slot 10
+		SessionStartSlot::insert(1, 10);
+
+		let offender_pair = &pairs[2];
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(AuthorityId::ID, offender_pair.public()),
+		).unwrap();
+
+		// the key ownership proof is for the first session
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::none(),
+				generate_equivocation_proof(offender_pair, 12),
+				key_owner_proof.clone(),
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+
+		assert_ok!(Aura::report_equivocation(
+			Origin::none(),
+			generate_equivocation_proof(offender_pair, 9),
+			key_owner_proof.clone(),
+		));
+
+		// the same offence can't be reported twice
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::none(),
+				generate_equivocation_proof(offender_pair, 9),
+				key_owner_proof,
+			),
+			Error::<Test>::DuplicateOffenceReport,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_key_owner_proof() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[0], 5);
+
+		// a key ownership proof for a different authority is rejected
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(AuthorityId::ID, pairs[1].public()),
+		).unwrap();
+
+		assert_err!(
+			Aura::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_is_only_valid_from_local_source() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let offender_pair = &pairs[0];
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(AuthorityId::ID, offender_pair.public()),
+		).unwrap();
+
+		let call = crate::Call::report_equivocation(
+			generate_equivocation_proof(offender_pair, 5),
+			key_owner_proof.clone(),
+		);
+
+		assert_eq!(
+			<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into(),
+		);
+		assert!(<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &call).is_ok());
+
+		let mut invalid_proof = generate_equivocation_proof(offender_pair, 5);
+		invalid_proof.second_header = invalid_proof.first_header.clone();
+		let invalid_call = crate::Call::report_equivocation(invalid_proof, key_owner_proof);
+
+		assert_eq!(
+			<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &invalid_call),
+			InvalidTransaction::BadProof.into(),
+		);
+	});
+}
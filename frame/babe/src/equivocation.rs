@@ -0,0 +1,204 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//!
+//! An opt-in utility module for reporting equivocations.
+//!
+//! This module defines the kind of the BABE equivocation offences,
+//! the offence itself being shared with other slot-based consensus engines
+//! in `sp_consensus_slots`, and some utility traits to wire together:
+//! - a system for reporting offences;
+//! - a system for submitting unsigned transactions;
+//!
+//! These can be used in an offchain context in order to submit equivocation
+//! reporting extrinsics (from the client that's importing BABE blocks).
+//! And in a runtime context, so that the BABE module can validate the
+//! equivocation proofs in the extrinsic and report the offences.
+//!
+//! IMPORTANT:
+//! When using this module for enabling equivocation reporting it is required
+//! that the `ValidateUnsigned` for the BABE pallet is used in the runtime
+//! definition.
+//!
+
+use frame_support::debug;
+use frame_system::offchain::{SendTransactionTypes, SubmitTransaction};
+use sp_consensus_babe::EquivocationProof;
+use sp_consensus_slots::{EquivocationKind, EquivocationOffence, SlotOffence};
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
+	ValidTransaction,
+};
+use sp_runtime::DispatchResult;
+use sp_staking::offence::{Kind, OffenceError, ReportOffence};
+use sp_std::prelude::*;
+
+use crate::{Call, Module, Trait};
+
+/// A trait with utility methods for handling equivocation reports in BABE.
+/// The trait provides methods for reporting an offence triggered by a valid
+/// equivocation report, and also for creating and submitting equivocation
+/// report extrinsics (useful only in offchain context).
+pub trait HandleEquivocation<T: Trait> {
+	/// The offence type used for reporting offences on valid equivocation reports.
+	type Offence: SlotOffence<T::KeyOwnerIdentification>;
+
+	/// Report an offence proved by the given reporters.
+	fn report_offence(
+		reporters: Vec<T::AccountId>,
+		offence: Self::Offence,
+	) -> Result<(), OffenceError>;
+
+	/// Create and dispatch an equivocation report extrinsic.
+	fn submit_unsigned_equivocation_report(
+		equivocation_proof: EquivocationProof<T::Header>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> DispatchResult;
+
+	/// Fetch the current block author id, if defined.
+	fn block_author() -> Option<T::AccountId>;
+}
+
+impl<T: Trait> HandleEquivocation<T> for () {
+	type Offence = BabeEquivocationOffence<T::KeyOwnerIdentification>;
+
+	fn report_offence(
+		_reporters: Vec<T::AccountId>,
+		_offence: BabeEquivocationOffence<T::KeyOwnerIdentification>,
+	) -> Result<(), OffenceError> {
+		Ok(())
+	}
+
+	fn submit_unsigned_equivocation_report(
+		_equivocation_proof: EquivocationProof<T::Header>,
+		_key_owner_proof: T::KeyOwnerProof,
+	) -> DispatchResult {
+		Ok(())
+	}
+
+	fn block_author() -> Option<T::AccountId> {
+		None
+	}
+}
+
+/// Generic equivocation handler. This type implements `HandleEquivocation`
+/// using existing subsystems that are part of frame (type bounds described
+/// below) and will dispatch to them directly, it's only purpose is to wire all
+/// subsystems together.
+pub struct EquivocationHandler<I, R, O = BabeEquivocationOffence<I>> {
+	_phantom: sp_std::marker::PhantomData<(I, R, O)>,
+}
+
+impl<I, R, O> Default for EquivocationHandler<I, R, O> {
+	fn default() -> Self {
+		Self {
+			_phantom: Default::default(),
+		}
+	}
+}
+
+impl<T, R, O> HandleEquivocation<T> for EquivocationHandler<T::KeyOwnerIdentification, R, O>
+where
+	// We use `offchain::SendTransactionTypes` for unsigned extrinsic creation
+	// and submission.
+	T: Trait + pallet_authorship::Trait + SendTransactionTypes<Call<T>>,
+	// A system for reporting offences after valid equivocation reports are
+	// processed.
+	R: ReportOffence<T::AccountId, T::KeyOwnerIdentification, O>,
+	// The offence type that should be used when reporting.
+	O: SlotOffence<T::KeyOwnerIdentification>,
+{
+	type Offence = O;
+
+	fn report_offence(reporters: Vec<T::AccountId>, offence: O) -> Result<(), OffenceError> {
+		R::report_offence(reporters, offence)
+	}
+
+	fn submit_unsigned_equivocation_report(
+		equivocation_proof: EquivocationProof<T::Header>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> DispatchResult {
+		let call = Call::report_equivocation(equivocation_proof, key_owner_proof);
+
+		match SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()) {
+			Ok(()) => {
+				debug::info!("Submitted BABE equivocation report.");
+				Ok(())
+			},
+			Err(e) => {
+				debug::error!("Error submitting equivocation report: {:?}", e);
+				Err("Error submitting equivocation report".into())
+			},
+		}
+	}
+
+	fn block_author() -> Option<T::AccountId> {
+		Some(<pallet_authorship::Module<T>>::author())
+	}
+}
+
+/// Methods for the `ValidateUnsigned` implementation:
+/// It restricts calls to `report_equivocation` to local calls (i.e. extrinsics
+/// generated on this node) or that already in a block. This guarantees that only
+/// block authors can include unsigned equivocation reports.
+impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		if let Call::report_equivocation(equivocation_proof, key_owner_proof) = call {
+			// discard equivocation report not coming from the local node
+			match source {
+				TransactionSource::Local | TransactionSource::InBlock => { /* allowed */ }
+				_ => {
+					debug::warn!(
+						target: "babe",
+						"rejecting unsigned report equivocation transaction because it is not local/in-block."
+					);
+
+					return InvalidTransaction::Call.into();
+				}
+			}
+
+			// check the validity of the equivocation and key ownership proofs
+			if <Module<T>>::check_equivocation_report(equivocation_proof, key_owner_proof).is_err() {
+				return InvalidTransaction::BadProof.into();
+			}
+
+			ValidTransaction::with_tag_prefix("BabeEquivocation")
+				// We assign the maximum priority for any equivocation report.
+				.priority(TransactionPriority::max_value())
+				// Only one equivocation report for the same offender at the same slot.
+				.and_provides((equivocation_proof.offender.clone(), equivocation_proof.slot_number))
+				// We don't propagate this. This can never be included on a remote node.
+				.propagate(false)
+				.build()
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
+}
+
+/// The kind of the BABE equivocation offences.
+pub struct BabeEquivocation;
+
+impl EquivocationKind for BabeEquivocation {
+	const ID: Kind = *b"babe:equivocatio";
+}
+
+/// A BABE equivocation offence report.
+pub type BabeEquivocationOffence<FullIdentification> =
+	EquivocationOffence<FullIdentification, BabeEquivocation>;
@@ -25,28 +25,36 @@ use pallet_timestamp;
 
 use sp_std::{result, prelude::*};
 use frame_support::{
-	decl_storage, decl_module, traits::{FindAuthor, Get, Randomness as RandomnessT},
+	decl_error, decl_storage, decl_module, Parameter,
+	dispatch::DispatchResult,
+	traits::{FindAuthor, Get, KeyOwnerProofSystem, Randomness as RandomnessT},
 	weights::Weight,
 };
+use frame_system::ensure_none;
 use sp_timestamp::OnTimestampSet;
-use sp_runtime::{generic::DigestItem, ConsensusEngineId, Perbill};
+use sp_runtime::{generic::DigestItem, ConsensusEngineId, KeyTypeId};
 use sp_runtime::traits::{IsMember, SaturatedConversion, Saturating, Hash, One};
-use sp_staking::{
-	SessionIndex,
-	offence::{Offence, Kind},
-};
 use sp_application_crypto::Public;
+use sp_staking::SessionIndex;
 
 use codec::{Encode, Decode};
 use sp_inherents::{InherentIdentifier, InherentData, ProvideInherent, MakeFatalError};
 use sp_consensus_babe::{
-	BABE_ENGINE_ID, ConsensusLog, BabeAuthorityWeight, SlotNumber,
+	BABE_ENGINE_ID, ConsensusLog, BabeAuthorityWeight, EquivocationProof, SlotNumber,
 	inherents::{INHERENT_IDENTIFIER, BabeInherentData},
 	digests::{NextEpochDescriptor, NextConfigDescriptor, PreDigest},
 };
 use sp_consensus_vrf::schnorrkel;
 pub use sp_consensus_babe::{AuthorityId, VRF_OUTPUT_LENGTH, RANDOMNESS_LENGTH, PUBLIC_KEY_LENGTH};
 
+mod equivocation;
+
+pub use equivocation::{
+	BabeEquivocation, BabeEquivocationOffence, EquivocationHandler, HandleEquivocation,
+};
+pub use sp_consensus_slots::SlotOffence;
+pub use pallet_session::{GetSessionNumber, GetValidatorCount};
+
 #[cfg(all(feature = "std", test))]
 mod tests;
 
@@ -70,6 +78,30 @@ pub trait Trait: pallet_timestamp::Trait {
 	/// Typically, the `ExternalTrigger` type should be used. An internal trigger should only be used
 	/// when no other module is responsible for changing authority set.
 	type EpochChangeTrigger: EpochChangeTrigger;
+
+	/// A system for proving ownership of keys, i.e. that a given key was part
+	/// of a validator set, needed for validating equivocation reports.
+	type KeyOwnerProofSystem: KeyOwnerProofSystem<
+		(KeyTypeId, AuthorityId),
+		Proof = Self::KeyOwnerProof,
+		IdentificationTuple = Self::KeyOwnerIdentification,
+	>;
+
+	/// The proof of key ownership, used for validating equivocation reports.
+	/// The proof must include the session index and validator count of the
+	/// session at which the equivocation occurred.
+	type KeyOwnerProof: Parameter + GetSessionNumber + GetValidatorCount;
+
+	/// The identification of a key owner, used when reporting equivocations.
+	type KeyOwnerIdentification: Parameter;
+
+	/// The equivocation handling subsystem, defines methods to report an
+	/// offence (after the equivocation has been validated) and for submitting a
+	/// transaction to report an equivocation (from an offchain context).
+	/// NOTE: when enabling equivocation handling (i.e. this type isn't set to
+	/// `()`) you must use this pallet's `ValidateUnsigned` in the runtime
+	/// definition.
+	type HandleEquivocation: HandleEquivocation<Self>;
 }
 
 /// Trigger an epoch change, if any should take place.
@@ -166,16 +198,76 @@ decl_storage! {
 		/// on block finalization. Querying this storage entry outside of block
 		/// execution context should always yield zero.
 		Lateness get(fn lateness): T::BlockNumber;
+
+		/// The epoch index at which each session started, used to check that
+		/// reported equivocations belong to the session of the key ownership
+		/// proof.
+		///
+		/// TWOX-NOTE: `SessionIndex` is an increasing integer, so this is okay.
+		pub SessionStartEpoch get(fn session_start_epoch):
+			map hasher(twox_64_concat) SessionIndex => Option<u64>;
 	}
 	add_extra_genesis {
 		config(authorities): Vec<(AuthorityId, BabeAuthorityWeight)>;
-		build(|config| Module::<T>::initialize_authorities(&config.authorities))
+		build(|config| {
+			Module::<T>::initialize_authorities(&config.authorities);
+			SessionStartEpoch::insert(0, 0);
+		})
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// An equivocation proof provided as part of an equivocation report is invalid.
+		InvalidEquivocationProof,
+		/// A key ownership proof provided as part of an equivocation report is invalid.
+		InvalidKeyOwnershipProof,
+		/// A given equivocation report is valid but already previously reported.
+		DuplicateOffenceReport,
+	}
+}
+
+/// Functions for calculating the weight of dispatchables.
+mod weight_for {
+	use frame_support::{
+		traits::Get,
+		weights::{Weight, constants::{WEIGHT_PER_MICROS, WEIGHT_PER_NANOS}},
+	};
+
+	/// Calculate the weight for `report_equivocation`.
+	///
+	/// The weight is dominated by checking the key ownership proof and by
+	/// reporting the offence, which may slash the offender and all of its
+	/// nominators, so we assume a lower bound of validators and an upper
+	/// bound of nominators.
+	pub(crate) fn report_equivocation<T: super::Trait>(validator_count: u32) -> Weight {
+		// we take the validator set count from the membership proof to
+		// calculate the weight but we set a floor of 100 validators.
+		let validator_count = validator_count.max(100) as u64;
+
+		// worst case we are considering is that the given offender
+		// is backed by 200 nominators
+		const MAX_NOMINATORS: u64 = 200;
+
+		// checking membership proof
+		(35 * WEIGHT_PER_MICROS)
+			.saturating_add((175 * WEIGHT_PER_NANOS).saturating_mul(validator_count))
+			.saturating_add(T::DbWeight::get().reads(5))
+			// check equivocation proof
+			.saturating_add(110 * WEIGHT_PER_MICROS)
+			// report offence
+			.saturating_add(110 * WEIGHT_PER_MICROS)
+			.saturating_add(25 * WEIGHT_PER_MICROS * MAX_NOMINATORS)
+			.saturating_add(T::DbWeight::get().reads(14 + 3 * MAX_NOMINATORS))
+			.saturating_add(T::DbWeight::get().writes(10 + 3 * MAX_NOMINATORS))
 	}
 }
 
 decl_module! {
 	/// The BABE Pallet
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
 		/// The number of **slots** that an epoch takes. We couple sessions to
 		/// epochs, i.e. we start a new session once the new epoch begins.
 		const EpochDuration: u64 = T::EpochDuration::get();
@@ -208,6 +300,26 @@ decl_module! {
 			// remove temporary "environment" entry from storage
 			Lateness::<T>::kill();
 		}
+
+		/// Report authority equivocation/misbehavior. This method will verify
+		/// the equivocation proof and validate the given key ownership proof
+		/// against the extracted offender. If both are valid, the offence will
+		/// be reported.
+		///
+		/// This extrinsic must be called unsigned and it is expected that only
+		/// block authors will call it (validated in `ValidateUnsigned`), as such
+		/// if the block author is defined it will be defined as the equivocation
+		/// reporter.
+		#[weight = weight_for::report_equivocation::<T>(key_owner_proof.validator_count())]
+		fn report_equivocation(
+			origin,
+			equivocation_proof: EquivocationProof<T::Header>,
+			key_owner_proof: T::KeyOwnerProof,
+		) {
+			ensure_none(origin)?;
+
+			Self::do_report_equivocation(equivocation_proof, key_owner_proof)?;
+		}
 	}
 }
 
@@ -274,51 +386,6 @@ impl<T: Trait> pallet_session::ShouldEndSession<T::BlockNumber> for Module<T> {
 	}
 }
 
-/// A BABE equivocation offence report.
-///
-/// When a validator released two or more blocks at the same slot.
-pub struct BabeEquivocationOffence<FullIdentification> {
-	/// A babe slot number in which this incident happened.
-	pub slot: u64,
-	/// The session index in which the incident happened.
-	pub session_index: SessionIndex,
-	/// The size of the validator set at the time of the offence.
-	pub validator_set_count: u32,
-	/// The authority that produced the equivocation.
-	pub offender: FullIdentification,
-}
-
-impl<FullIdentification: Clone> Offence<FullIdentification> for BabeEquivocationOffence<FullIdentification> {
-	const ID: Kind = *b"babe:equivocatio";
-	type TimeSlot = u64;
-
-	fn offenders(&self) -> Vec<FullIdentification> {
-		vec![self.offender.clone()]
-	}
-
-	fn session_index(&self) -> SessionIndex {
-		self.session_index
-	}
-
-	fn validator_set_count(&self) -> u32 {
-		self.validator_set_count
-	}
-
-	fn time_slot(&self) -> Self::TimeSlot {
-		self.slot
-	}
-
-	fn slash_fraction(
-		offenders_count: u32,
-		validator_set_count: u32,
-	) -> Perbill {
-		// the formula is min((3k / n)^2, 1)
-		let x = Perbill::from_rational_approximation(3 * offenders_count, validator_set_count);
-		// _ ^ 2
-		x.square()
-	}
-}
-
 impl<T: Trait> Module<T> {
 	/// Determine the BABE slot duration based on the Timestamp module configuration.
 	pub fn slot_duration() -> T::Moment {
@@ -555,6 +622,96 @@ impl<T: Trait> Module<T> {
 		this_randomness
 	}
 
+	/// Returns the index of the epoch the given slot belongs to, or `None` if
+	/// the slot is before the first epoch of the chain.
+	pub fn epoch_index_for(slot_number: SlotNumber) -> Option<u64> {
+		slot_number
+			.checked_sub(GenesisSlot::get())
+			.map(|slots| slots / T::EpochDuration::get())
+	}
+
+	/// Whether the given slot is part of the given session, according to the
+	/// recorded session start epochs.
+	fn is_slot_in_session(slot_number: SlotNumber, session_index: SessionIndex) -> bool {
+		let epoch_index = match Self::epoch_index_for(slot_number) {
+			Some(epoch_index) => epoch_index,
+			None => return false,
+		};
+
+		let start = match SessionStartEpoch::get(session_index) {
+			Some(start) => start,
+			None => return false,
+		};
+
+		let next_start = SessionStartEpoch::get(session_index.saturating_add(1));
+
+		epoch_index >= start && next_start.map_or(true, |next_start| epoch_index < next_start)
+	}
+
+	/// Verifies the given equivocation report, returning the identification of
+	/// the offender if both the equivocation proof and the key ownership proof
+	/// are valid.
+	pub fn check_equivocation_report(
+		equivocation_proof: &EquivocationProof<T::Header>,
+		key_owner_proof: &T::KeyOwnerProof,
+	) -> Result<T::KeyOwnerIdentification, Error<T>> {
+		// validate the equivocation proof (check headers are different,
+		// target the same slot and are signed by the offender).
+		if !sp_consensus_babe::check_equivocation_proof(equivocation_proof.clone()) {
+			return Err(Error::InvalidEquivocationProof);
+		}
+
+		// the equivocation slot must be part of the session of the key
+		// ownership proof.
+		if !Self::is_slot_in_session(equivocation_proof.slot_number, key_owner_proof.session()) {
+			return Err(Error::InvalidKeyOwnershipProof);
+		}
+
+		// check the membership proof and extract the offender's id
+		T::KeyOwnerProofSystem::check_proof(
+			(sp_consensus_babe::KEY_TYPE, equivocation_proof.offender.clone()),
+			key_owner_proof.clone(),
+		).ok_or(Error::InvalidKeyOwnershipProof)
+	}
+
+	fn do_report_equivocation(
+		equivocation_proof: EquivocationProof<T::Header>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> DispatchResult {
+		let offender = Self::check_equivocation_report(&equivocation_proof, &key_owner_proof)?;
+
+		let offence = <T::HandleEquivocation as HandleEquivocation<T>>::Offence::new(
+			key_owner_proof.session(),
+			key_owner_proof.validator_count(),
+			offender,
+			equivocation_proof.slot_number,
+		);
+
+		let reporters = match T::HandleEquivocation::block_author() {
+			Some(author) => vec![author],
+			None => vec![],
+		};
+
+		T::HandleEquivocation::report_offence(reporters, offence)
+			.map_err(|_| Error::<T>::DuplicateOffenceReport)?;
+
+		Ok(())
+	}
+
+	/// Submits an extrinsic to report an equivocation. This method will create
+	/// an unsigned extrinsic with a call to `report_equivocation` and
+	/// will push the transaction to the pool. Only useful in an offchain
+	/// context.
+	pub fn submit_unsigned_equivocation_report(
+		equivocation_proof: EquivocationProof<T::Header>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> Option<()> {
+		T::HandleEquivocation::submit_unsigned_equivocation_report(
+			equivocation_proof,
+			key_owner_proof,
+		).ok()
+	}
+
 	fn initialize_authorities(authorities: &[(AuthorityId, BabeAuthorityWeight)]) {
 		if !authorities.is_empty() {
 			assert!(Authorities::get().is_empty(), "Authorities are already initialized!");
@@ -589,7 +746,7 @@ impl<T: Trait> sp_runtime::BoundToRuntimeAppPublic for Module<T> {
 	type Public = AuthorityId;
 }
 
-impl<T: Trait> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
+impl<T: Trait + pallet_session::Trait> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
 	type Key = AuthorityId;
 
 	fn on_genesis_session<'a, I: 'a>(validators: I)
@@ -610,7 +767,14 @@ impl<T: Trait> pallet_session::OneSessionHandler<T::AccountId> for Module<T> {
 			(k, 1)
 		}).collect::<Vec<_>>();
 
-		Self::enact_epoch_change(authorities, next_authorities)
+		Self::enact_epoch_change(authorities, next_authorities);
+
+		// sessions are rotated on epoch changes, so the new session starts
+		// with the epoch that was just enacted.
+		SessionStartEpoch::insert(
+			<pallet_session::Module<T>>::current_index(),
+			EpochIndex::get(),
+		);
 	}
 
 	fn on_disabled(i: usize) {
@@ -17,28 +17,37 @@
 
 //! Test utilities
 
+use std::cell::RefCell;
 use codec::Encode;
-use super::{Trait, Module, GenesisConfig, CurrentSlot};
+use super::{Trait, Module, GenesisConfig, CurrentSlot, BabeEquivocationOffence, AuthorityId};
 use sp_runtime::{
-	Perbill, impl_opaque_keys,
-	testing::{Header, UintAuthorityId, Digest, DigestItem},
-	traits::IdentityLookup,
+	ConsensusEngineId, Perbill, impl_opaque_keys,
+	testing::{Header, TestXt, UintAuthorityId, Digest, DigestItem},
+	traits::{Header as _, IdentityLookup},
 };
 use frame_system::InitKind;
 use frame_support::{
-	impl_outer_origin, parameter_types, StorageValue,
-	traits::OnInitialize,
+	impl_outer_dispatch, impl_outer_origin, parameter_types, StorageValue,
+	traits::{FindAuthor, KeyOwnerProofSystem, OnInitialize},
 	weights::Weight,
 };
 use sp_io;
-use sp_core::{H256, U256, crypto::Pair};
-use sp_consensus_babe::AuthorityPair;
+use sp_core::{H256, U256, crypto::{KeyTypeId, Pair}};
+use sp_consensus_babe::{AuthorityPair, EquivocationProof};
 use sp_consensus_vrf::schnorrkel::{VRFOutput, VRFProof};
+use sp_session::MembershipProof;
+use sp_staking::offence::{OffenceError, ReportOffence};
 
 impl_outer_origin!{
 	pub enum Origin for Test where system = frame_system {}
 }
 
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		babe::Babe,
+	}
+}
+
 type DummyValidatorId = u64;
 
 // Workaround for https://github.com/rust-lang/rust/issues/26925 . Remove when sorted.
@@ -61,7 +70,7 @@ impl frame_system::Trait for Test {
 	type Origin = Origin;
 	type Index = u64;
 	type BlockNumber = u64;
-	type Call = ();
+	type Call = Call;
 	type Hash = H256;
 	type Version = ();
 	type Hashing = sp_runtime::traits::BlakeTwo256;
@@ -107,10 +116,109 @@ impl pallet_timestamp::Trait for Test {
 	type MinimumPeriod = MinimumPeriod;
 }
 
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = TestXt<Call, ()>;
+}
+
+/// The author of every block.
+pub const BLOCK_AUTHOR: u64 = 42;
+
+/// Finds `BLOCK_AUTHOR` as the author of every block.
+pub struct TestAuthor;
+
+impl FindAuthor<u64> for TestAuthor {
+	fn find_author<'a, I>(_: I) -> Option<u64>
+		where I: 'a + IntoIterator<Item=(ConsensusEngineId, &'a [u8])>
+	{
+		Some(BLOCK_AUTHOR)
+	}
+}
+
+impl pallet_authorship::Trait for Test {
+	type FindAuthor = TestAuthor;
+	type UncleGenerations = ();
+	type FilterUncle = ();
+	type EventHandler = ();
+}
+
 impl Trait for Test {
 	type EpochDuration = EpochDuration;
 	type ExpectedBlockTime = ExpectedBlockTime;
 	type EpochChangeTrigger = crate::ExternalTrigger;
+
+	type KeyOwnerProofSystem = TestKeyOwnerProofSystem;
+	type KeyOwnerProof = MembershipProof;
+	type KeyOwnerIdentification = u64;
+	type HandleEquivocation = super::EquivocationHandler<u64, OffenceRecorder>;
+}
+
+/// A key ownership proof system over the current BABE authorities. The proof
+/// commits to the encoded authority key instead of a merkle proof of session
+/// membership, and the owner is identified by its authority index.
+pub struct TestKeyOwnerProofSystem;
+
+impl KeyOwnerProofSystem<(KeyTypeId, AuthorityId)> for TestKeyOwnerProofSystem {
+	type Proof = MembershipProof;
+	type IdentificationTuple = u64;
+
+	fn prove(key: (KeyTypeId, AuthorityId)) -> Option<Self::Proof> {
+		let authorities = Babe::authorities();
+		if !authorities.iter().any(|(id, _)| *id == key.1) {
+			return None;
+		}
+
+		Some(MembershipProof {
+			session: Session::current_index(),
+			trie_nodes: vec![key.1.encode()],
+			validator_count: authorities.len() as u32,
+		})
+	}
+
+	fn check_proof(key: (KeyTypeId, AuthorityId), proof: Self::Proof) -> Option<u64> {
+		if key.0 != sp_consensus_babe::KEY_TYPE || proof.trie_nodes != vec![key.1.encode()] {
+			return None;
+		}
+
+		Babe::authorities()
+			.iter()
+			.position(|(id, _)| *id == key.1)
+			.map(|index| index as u64)
+	}
+}
+
+thread_local! {
+	/// The offences reported so far, as `(offender, session_index, slot)`.
+	pub static OFFENCES: RefCell<Vec<(u64, u32, u64)>> = RefCell::new(Vec::new());
+
+	/// The reporters of the offences reported so far.
+	pub static REPORTERS: RefCell<Vec<Vec<u64>>> = RefCell::new(Vec::new());
+}
+
+/// Records reported offences in `OFFENCES`, rejecting duplicate reports.
+pub struct OffenceRecorder;
+
+impl ReportOffence<u64, u64, BabeEquivocationOffence<u64>> for OffenceRecorder {
+	fn report_offence(
+		reporters: Vec<u64>,
+		offence: BabeEquivocationOffence<u64>,
+	) -> Result<(), OffenceError> {
+		OFFENCES.with(|offences| {
+			let mut offences = offences.borrow_mut();
+			let offence = (offence.offender, offence.session_index, offence.slot);
+
+			if offences.contains(&offence) {
+				return Err(OffenceError::DuplicateReport);
+			}
+
+			offences.push(offence);
+			REPORTERS.with(|r| r.borrow_mut().push(reporters));
+			Ok(())
+		})
+	}
 }
 
 pub fn new_test_ext(authorities_len: usize) -> (Vec<AuthorityPair>, sp_io::TestExternalities) {
@@ -177,6 +285,42 @@ pub fn make_secondary_plain_pre_digest(
 	Digest { logs: vec![log] }
 }
 
+/// Creates an equivocation proof at the given slot for two distinct headers
+/// signed by `offender_authority_pair`, claiming `offender_authority_index`
+/// in their pre-digests.
+pub fn generate_equivocation_proof(
+	offender_authority_index: u32,
+	offender_authority_pair: &AuthorityPair,
+	slot_number: u64,
+) -> EquivocationProof<Header> {
+	use sp_consensus_babe::digests::CompatibleDigestItem;
+
+	let make_header = |extrinsics_root| {
+		let mut header = Header {
+			parent_hash: System::parent_hash(),
+			number: System::block_number() + 1,
+			state_root: Default::default(),
+			extrinsics_root,
+			digest: make_secondary_plain_pre_digest(offender_authority_index, slot_number),
+		};
+
+		let pre_hash = header.hash();
+		let seal = <DigestItem as CompatibleDigestItem>::babe_seal(
+			offender_authority_pair.sign(pre_hash.as_ref()),
+		);
+		header.digest_mut().push(seal);
+
+		header
+	};
+
+	EquivocationProof {
+		offender: offender_authority_pair.public(),
+		slot_number,
+		first_header: make_header(H256::repeat_byte(1)),
+		second_header: make_header(H256::repeat_byte(2)),
+	}
+}
+
 pub type System = frame_system::Module<Test>;
 pub type Babe = Module<Test>;
 pub type Session = pallet_session::Module<Test>;
@@ -19,11 +19,20 @@
 
 use super::*;
 use mock::*;
-use frame_support::traits::OnFinalize;
+use frame_support::{
+	assert_err, assert_ok,
+	traits::OnFinalize,
+	unsigned::{TransactionSource, TransactionValidityError, ValidateUnsigned},
+};
 use pallet_session::ShouldEndSession;
 use sp_core::crypto::IsWrappedBy;
 use sp_consensus_babe::AllowedSlots;
 use sp_consensus_vrf::schnorrkel::{VRFOutput, VRFProof};
+use sp_runtime::traits::Header as _;
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionLongevity, TransactionPriority, TransactionValidity,
+	ValidTransaction,
+};
 
 const EMPTY_RANDOMNESS: [u8; 32] = [
 	74, 25, 49, 128, 53, 97, 244, 49,
@@ -183,3 +192,278 @@ fn can_enact_next_config() {
 		assert_eq!(header.digest.logs[2], consensus_digest.clone())
 	});
 }
+
+fn reported_offences() -> Vec<(u64, u32, u64)> {
+	OFFENCES.with(|offences| offences.borrow().clone())
+}
+
+#[test]
+fn report_equivocation_current_session_works() {
+	let (pairs, mut ext) = new_test_ext(3);
+
+	ext.execute_with(|| {
+		go_to_block(1, 6);
+
+		let offending_authority_index = 1;
+		let offending_authority_pair = &pairs[offending_authority_index as usize];
+
+		let equivocation_proof = generate_equivocation_proof(
+			offending_authority_index,
+			offending_authority_pair,
+			Babe::current_slot(),
+		);
+
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, offending_authority_pair.public()),
+		).unwrap();
+
+		assert_ok!(Babe::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof));
+
+		// the offender is reported with the session and slot of the equivocation
+		assert_eq!(reported_offences(), vec![(offending_authority_index as u64, 0, 6)]);
+		// by the author of the block
+		assert_eq!(REPORTERS.with(|r| r.borrow().clone()), vec![vec![BLOCK_AUTHOR]]);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_key_owner_proof() {
+	let (pairs, mut ext) = new_test_ext(3);
+
+	ext.execute_with(|| {
+		go_to_block(1, 6);
+
+		let offending_authority_pair = &pairs[0];
+		let equivocation_proof = generate_equivocation_proof(
+			0,
+			offending_authority_pair,
+			Babe::current_slot(),
+		);
+
+		// a key ownership proof for a different authority is rejected
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, pairs[1].public()),
+		).unwrap();
+
+		assert_err!(
+			Babe::report_equivocation(Origin::none(), equivocation_proof.clone(), key_owner_proof),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+
+		// and so is a key ownership proof for a session other than the one of
+		// the equivocation slot
+		let mut key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, offending_authority_pair.public()),
+		).unwrap();
+		key_owner_proof.session = 1;
+
+		assert_err!(
+			Babe::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+
+		assert!(reported_offences().is_empty());
+	});
+}
+
+#[test]
+fn session_start_epochs_are_recorded() {
+	new_test_ext(0).1.execute_with(|| {
+		go_to_block(1, 6);
+		assert_eq!(Babe::session_start_epoch(0), Some(0));
+		assert_eq!(Babe::session_start_epoch(1), None);
+
+		// the epoch change at block 4 (slot 9) rotates the session
+		progress_to_block(4);
+		assert_eq!(Session::current_index(), 1);
+		assert_eq!(Babe::epoch_index(), 1);
+		assert_eq!(Babe::session_start_epoch(1), Some(1));
+	});
+}
+
+#[test]
+fn report_equivocation_checks_session_of_slot() {
+	let (pairs, mut ext) = new_test_ext(3);
+
+	ext.execute_with(|| {
+		go_to_block(1, 6);
+
+		// session 1 starts with epoch 1, i.e. at slot 9
+		SessionStartEpoch::insert(1, 1);
+
+		let offending_authority_pair = &pairs[0];
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, offending_authority_pair.public()),
+		).unwrap();
+
+		// an equivocation in session 1 can't be proven with a session 0 key
+		// ownership proof
+		let equivocation_proof = generate_equivocation_proof(0, offending_authority_pair, 9);
+		assert_err!(
+			Babe::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof.clone()),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+
+		// while one at the last slot of session 0 can
+		let equivocation_proof = generate_equivocation_proof(0, offending_authority_pair, 8);
+		assert_ok!(Babe::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof));
+
+		assert_eq!(reported_offences(), vec![(0, 0, 8)]);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_equivocation_proof() {
+	let (pairs, mut ext) = new_test_ext(3);
+
+	ext.execute_with(|| {
+		go_to_block(1, 6);
+
+		let offending_authority_index = 0;
+		let offending_authority_pair = &pairs[offending_authority_index as usize];
+
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, offending_authority_pair.public()),
+		).unwrap();
+
+		let assert_invalid_equivocation = |equivocation_proof| {
+			assert_err!(
+				Babe::report_equivocation(
+					Origin::none(),
+					equivocation_proof,
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidEquivocationProof,
+			)
+		};
+
+		let valid_proof = generate_equivocation_proof(
+			offending_authority_index,
+			offending_authority_pair,
+			Babe::current_slot(),
+		);
+
+		// both headers are the same
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.second_header = equivocation_proof.first_header.clone();
+		assert_invalid_equivocation(equivocation_proof);
+
+		// the slot in the proof doesn't match the one in the headers
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.slot_number = Babe::current_slot() + 1;
+		assert_invalid_equivocation(equivocation_proof);
+
+		// the headers target different slots
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.second_header = generate_equivocation_proof(
+			offending_authority_index,
+			offending_authority_pair,
+			Babe::current_slot() + 1,
+		).second_header;
+		assert_invalid_equivocation(equivocation_proof);
+
+		// one of the headers is signed by a different authority
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.second_header = generate_equivocation_proof(
+			offending_authority_index,
+			&pairs[1],
+			Babe::current_slot(),
+		).second_header;
+		assert_invalid_equivocation(equivocation_proof);
+
+		// one of the headers is missing the seal
+		let mut equivocation_proof = valid_proof.clone();
+		equivocation_proof.second_header.digest_mut().pop();
+		assert_invalid_equivocation(equivocation_proof);
+
+		assert!(reported_offences().is_empty());
+	});
+}
+
+#[test]
+fn report_equivocation_rejects_duplicate_reports() {
+	let (pairs, mut ext) = new_test_ext(3);
+
+	ext.execute_with(|| {
+		go_to_block(1, 6);
+
+		let offending_authority_pair = &pairs[2];
+		let equivocation_proof = generate_equivocation_proof(
+			2,
+			offending_authority_pair,
+			Babe::current_slot(),
+		);
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, offending_authority_pair.public()),
+		).unwrap();
+
+		assert_ok!(Babe::report_equivocation(
+			Origin::none(),
+			equivocation_proof.clone(),
+			key_owner_proof.clone(),
+		));
+
+		assert_err!(
+			Babe::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::DuplicateOffenceReport,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_validate_unsigned_works() {
+	let (pairs, mut ext) = new_test_ext(3);
+
+	ext.execute_with(|| {
+		go_to_block(1, 6);
+
+		let offending_authority_index = 0;
+		let offending_authority_pair = &pairs[offending_authority_index as usize];
+
+		let equivocation_proof = generate_equivocation_proof(
+			offending_authority_index,
+			offending_authority_pair,
+			Babe::current_slot(),
+		);
+		let key_owner_proof = TestKeyOwnerProofSystem::prove(
+			(sp_consensus_babe::KEY_TYPE, offending_authority_pair.public()),
+		).unwrap();
+
+		let inner = super::Call::report_equivocation(equivocation_proof.clone(), key_owner_proof.clone());
+
+		// only local/inblock reports are allowed
+		assert_eq!(
+			<Babe as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &inner),
+			InvalidTransaction::Call.into(),
+		);
+
+		// the transaction is valid when passed as local
+		let tx_tag = (offending_authority_pair.public(), Babe::current_slot());
+		assert_eq!(
+			<Babe as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &inner),
+			TransactionValidity::Ok(ValidTransaction {
+				priority: TransactionPriority::max_value(),
+				requires: vec![],
+				provides: vec![("BabeEquivocation", tx_tag).encode()],
+				longevity: TransactionLongevity::max_value(),
+				propagate: false,
+			}),
+		);
+
+		// an invalid equivocation proof is rejected
+		let mut invalid_proof = equivocation_proof;
+		invalid_proof.second_header = invalid_proof.first_header.clone();
+		let invalid_call = super::Call::report_equivocation(invalid_proof, key_owner_proof);
+
+		assert_eq!(
+			<Babe as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &invalid_call),
+			InvalidTransaction::BadProof.into(),
+		);
+
+		// the pre-dispatch check applies the same validation
+		assert_eq!(
+			<Babe as ValidateUnsigned>::pre_dispatch(&invalid_call),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::BadProof)),
+		);
+	});
+}
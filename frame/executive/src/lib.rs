@@ -233,10 +233,7 @@ where
 		digest: &Digest<System::Hash>,
 	) {
 		if Self::runtime_upgraded() {
-			// System is not part of `AllModules`, so we need to call this manually.
-			let mut weight = <frame_system::Module::<System> as OnRuntimeUpgrade>::on_runtime_upgrade();
-			weight = weight.saturating_add(COnRuntimeUpgrade::on_runtime_upgrade());
-			weight = weight.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade());
+			let weight = Self::execute_on_runtime_upgrade();
 			<frame_system::Module<System>>::register_extra_weight_unchecked(weight, DispatchClass::Mandatory);
 		}
 		<frame_system::Module<System>>::initialize(
@@ -254,6 +251,17 @@ where
 		frame_system::Module::<System>::note_finished_initialize();
 	}
 
+	/// Execute all `OnRuntimeUpgrade` logic of this runtime and return the weight it consumed.
+	///
+	/// This is what happens when the first block after a runtime upgrade is initialized. Useful
+	/// to measure the weight of the migrations of a new runtime outside of a block.
+	pub fn execute_on_runtime_upgrade() -> frame_support::weights::Weight {
+		// System is not part of `AllModules`, so we need to call this manually.
+		let mut weight = <frame_system::Module::<System> as OnRuntimeUpgrade>::on_runtime_upgrade();
+		weight = weight.saturating_add(COnRuntimeUpgrade::on_runtime_upgrade());
+		weight.saturating_add(<AllModules as OnRuntimeUpgrade>::on_runtime_upgrade())
+	}
+
 	/// Returns if the runtime was upgraded since the last time this function was called.
 	fn runtime_upgraded() -> bool {
 		let last = frame_system::LastRuntimeUpgrade::get();
@@ -646,6 +654,14 @@ mod tests {
 		}
 	}
 
+	/// A migration weighing 100.
+	struct WeighedOnRuntimeUpgrade;
+	impl OnRuntimeUpgrade for WeighedOnRuntimeUpgrade {
+		fn on_runtime_upgrade() -> Weight {
+			100
+		}
+	}
+
 	type Executive = super::Executive<
 		Runtime,
 		Block<TestXt>,
@@ -936,6 +952,34 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn execute_on_runtime_upgrade_returns_migration_weight() {
+		type WeighedExecutive = super::Executive<
+			Runtime,
+			Block<TestXt>,
+			ChainContext<Runtime>,
+			Runtime,
+			AllModules,
+			(CustomOnRuntimeUpgrade, WeighedOnRuntimeUpgrade, WeighedOnRuntimeUpgrade),
+		>;
+
+		new_test_ext(1).execute_with(|| {
+			assert_eq!(WeighedExecutive::execute_on_runtime_upgrade(), 100 + 100);
+		});
+	}
+
+	#[test]
+	fn execute_on_runtime_upgrade_runs_all_migrations() {
+		new_test_ext(1).execute_with(|| {
+			assert_eq!(Executive::execute_on_runtime_upgrade(), 0);
+			// All the migrations ran, the module ones last.
+			assert_eq!(&sp_io::storage::get(TEST_KEY).unwrap()[..], *b"module");
+			assert_eq!(sp_io::storage::get(CUSTOM_ON_RUNTIME_KEY).unwrap(), true.encode());
+			// Executing the migrations doesn't count as an upgrade.
+			assert!(!Executive::runtime_upgraded());
+		});
+	}
+
 	#[test]
 	fn runtime_upgraded_should_work() {
 		new_test_ext(1).execute_with(|| {
@@ -43,7 +43,7 @@ use frame_support::{
 	decl_error, decl_event, decl_module, decl_storage, storage, traits::KeyOwnerProofSystem,
 	Parameter,
 };
-use frame_system::{self as system, ensure_signed, DigestOf};
+use frame_system::{self as system, ensure_none, DigestOf};
 use sp_runtime::{
 	generic::{DigestItem, OpaqueDigestItemId},
 	traits::Zero,
@@ -56,9 +56,10 @@ mod mock;
 mod tests;
 
 pub use equivocation::{
-	EquivocationHandler, GetSessionNumber, GetValidatorCount, GrandpaEquivocationOffence,
-	GrandpaOffence, GrandpaTimeSlot, HandleEquivocation, ValidateEquivocationReport,
+	EquivocationHandler, GrandpaEquivocationOffence, GrandpaOffence, GrandpaTimeSlot,
+	HandleEquivocation,
 };
+pub use pallet_session::{GetSessionNumber, GetValidatorCount};
 
 pub trait Trait: frame_system::Trait {
 	/// The event type of this module.
@@ -87,9 +88,8 @@ pub trait Trait: frame_system::Trait {
 	/// offence (after the equivocation has been validated) and for submitting a
 	/// transaction to report an equivocation (from an offchain context).
 	/// NOTE: when enabling equivocation handling (i.e. this type isn't set to
-	/// `()`) you must add the `equivocation::ValidateEquivocationReport` signed
-	/// extension to the runtime's `SignedExtra` definition, otherwise
-	/// equivocation reports won't be properly validated.
+	/// `()`) you must use this pallet's `ValidateUnsigned` in the runtime
+	/// definition.
 	type HandleEquivocation: HandleEquivocation<Self>;
 }
 
@@ -185,6 +185,8 @@ decl_error! {
 		ChangePending,
 		/// Cannot signal forced change so soon after last.
 		TooSoon,
+		/// An equivocation proof provided as part of an equivocation report is invalid.
+		InvalidEquivocationProof,
 		/// A key ownership proof provided as part of an equivocation report is invalid.
 		InvalidKeyOwnershipProof,
 		/// A given equivocation report is valid but already previously reported.
@@ -235,45 +237,19 @@ decl_module! {
 		/// against the extracted offender. If both are valid, the offence
 		/// will be reported.
 		///
-		/// Since the weight of the extrinsic is 0, in order to avoid DoS by
-		/// submission of invalid equivocation reports, a mandatory pre-validation of
-		/// the extrinsic is implemented in a `SignedExtension`.
+		/// This extrinsic must be called unsigned and it is expected that only
+		/// block authors will call it (validated in `ValidateUnsigned`), as such
+		/// if the block author is defined it will be defined as the equivocation
+		/// reporter.
 		#[weight = 0]
 		fn report_equivocation(
 			origin,
 			equivocation_proof: EquivocationProof<T::Hash, T::BlockNumber>,
 			key_owner_proof: T::KeyOwnerProof,
 		) {
-			let reporter_id = ensure_signed(origin)?;
+			ensure_none(origin)?;
 
-			let (session_index, validator_set_count) = (
-				key_owner_proof.session(),
-				key_owner_proof.validator_count(),
-			);
-
-			// we have already checked this proof in `SignedExtension`, we to
-			// check it again to get the full identification of the offender.
-			let offender =
-				T::KeyOwnerProofSystem::check_proof(
-					(fg_primitives::KEY_TYPE, equivocation_proof.offender().clone()),
-					key_owner_proof,
-				).ok_or(Error::<T>::InvalidKeyOwnershipProof)?;
-
-			// the set id and round when the offence happened
-			let set_id = equivocation_proof.set_id();
-			let round = equivocation_proof.round();
-
-			// report to the offences module rewarding the sender.
-			T::HandleEquivocation::report_offence(
-				vec![reporter_id],
-				<T::HandleEquivocation as HandleEquivocation<T>>::Offence::new(
-					session_index,
-					validator_set_count,
-					offender,
-					set_id,
-					round,
-				),
-			).map_err(|_| Error::<T>::DuplicateOffenceReport)?;
+			Self::do_report_equivocation(equivocation_proof, key_owner_proof)?;
 		}
 
 		fn on_finalize(block_number: T::BlockNumber) {
@@ -454,15 +430,93 @@ impl<T: Trait> Module<T> {
 		SetIdSession::insert(0, 0);
 	}
 
-	/// Submits an extrinsic to report an equivocation. This method will sign an
-	/// extrinsic with a call to `report_equivocation` with any reporting keys
-	/// available in the keystore and will push the transaction to the pool.
-	/// Only useful in an offchain context.
-	pub fn submit_report_equivocation_extrinsic(
+	/// Verifies the given equivocation report, returning the identification of
+	/// the offender if both the equivocation proof and the key ownership proof
+	/// are valid.
+	pub fn check_equivocation_report(
+		equivocation_proof: &EquivocationProof<T::Hash, T::BlockNumber>,
+		key_owner_proof: &T::KeyOwnerProof,
+	) -> Result<T::KeyOwnerIdentification, Error<T>> {
+		// validate equivocation proof (check votes are different and
+		// signatures are valid).
+		if sp_finality_grandpa::check_equivocation_proof(equivocation_proof.clone()).is_err() {
+			return Err(Error::InvalidEquivocationProof);
+		}
+
+		// we check the equivocation within the context of its set id (and
+		// associated session).
+		let set_id = equivocation_proof.set_id();
+		let session_index = key_owner_proof.session();
+
+		// fetch the current and previous sets last session index. on the
+		// genesis set there's no previous set.
+		let previous_set_id_session_index = if set_id == 0 {
+			None
+		} else {
+			Some(Self::session_for_set(set_id - 1).ok_or(Error::InvalidEquivocationProof)?)
+		};
+
+		let set_id_session_index =
+			Self::session_for_set(set_id).ok_or(Error::InvalidEquivocationProof)?;
+
+		// check that the session id for the membership proof is within the
+		// bounds of the set id reported in the equivocation.
+		if session_index > set_id_session_index ||
+			previous_set_id_session_index
+				.map(|previous_index| session_index <= previous_index)
+				.unwrap_or(false)
+		{
+			return Err(Error::InvalidKeyOwnershipProof);
+		}
+
+		// check the membership proof and extract the offender's id
+		T::KeyOwnerProofSystem::check_proof(
+			(fg_primitives::KEY_TYPE, equivocation_proof.offender().clone()),
+			key_owner_proof.clone(),
+		).ok_or(Error::InvalidKeyOwnershipProof)
+	}
+
+	fn do_report_equivocation(
+		equivocation_proof: EquivocationProof<T::Hash, T::BlockNumber>,
+		key_owner_proof: T::KeyOwnerProof,
+	) -> DispatchResult {
+		let offender = Self::check_equivocation_report(&equivocation_proof, &key_owner_proof)?;
+
+		// the set id and round when the offence happened
+		let set_id = equivocation_proof.set_id();
+		let round = equivocation_proof.round();
+
+		let offence = <T::HandleEquivocation as HandleEquivocation<T>>::Offence::new(
+			key_owner_proof.session(),
+			key_owner_proof.validator_count(),
+			offender,
+			set_id,
+			round,
+		);
+
+		let reporters = match T::HandleEquivocation::block_author() {
+			Some(author) => vec![author],
+			None => vec![],
+		};
+
+		T::HandleEquivocation::report_offence(reporters, offence)
+			.map_err(|_| Error::<T>::DuplicateOffenceReport)?;
+
+		Ok(())
+	}
+
+	/// Submits an extrinsic to report an equivocation. This method will create
+	/// an unsigned extrinsic with a call to `report_equivocation` and
+	/// will push the transaction to the pool. Only useful in an offchain
+	/// context.
+	pub fn submit_unsigned_equivocation_report(
 		equivocation_proof: EquivocationProof<T::Hash, T::BlockNumber>,
 		key_owner_proof: T::KeyOwnerProof,
 	) -> Option<()> {
-		T::HandleEquivocation::submit_equivocation_report(equivocation_proof, key_owner_proof).ok()
+		T::HandleEquivocation::submit_unsigned_equivocation_report(
+			equivocation_proof,
+			key_owner_proof,
+		).ok()
 	}
 }
 
@@ -25,12 +25,20 @@ use codec::{Decode, Encode};
 use fg_primitives::ScheduledChange;
 use frame_support::{
 	assert_err, assert_ok,
-	traits::{Currency, OnFinalize, UnfilteredDispatchable},
+	traits::{Currency, OnFinalize},
+	unsigned::{TransactionSource, TransactionValidityError, ValidateUnsigned},
 };
 use frame_system::{EventRecord, Phase};
 use sp_core::H256;
 use sp_keyring::Ed25519Keyring;
-use sp_runtime::{testing::Digest, traits::Header};
+use sp_runtime::{
+	testing::Digest,
+	traits::Header,
+	transaction_validity::{
+		InvalidTransaction, TransactionLongevity, TransactionPriority, TransactionValidity,
+		ValidTransaction,
+	},
+};
 
 #[test]
 fn authorities_change_logged() {
@@ -375,8 +383,11 @@ fn report_equivocation_current_set_works() {
 			Historical::prove((sp_finality_grandpa::KEY_TYPE, &equivocation_key)).unwrap();
 
 		// report the equivocation and the tx should be dispatched successfully
-		let inner = report_equivocation(equivocation_proof, key_owner_proof).unwrap();
-		assert_ok!(inner.dispatch_bypass_filter(Origin::signed(1)));
+		assert_ok!(Grandpa::report_equivocation(
+			Origin::none(),
+			equivocation_proof,
+			key_owner_proof,
+		));
 
 		start_era(2);
 
@@ -456,8 +467,11 @@ fn report_equivocation_old_set_works() {
 
 		// report the equivocation using the key ownership proof generated on
 		// the old set, the tx should be dispatched successfully
-		let inner = report_equivocation(equivocation_proof, key_owner_proof).unwrap();
-		assert_ok!(inner.dispatch_bypass_filter(Origin::signed(1)));
+		assert_ok!(Grandpa::report_equivocation(
+			Origin::none(),
+			equivocation_proof,
+			key_owner_proof,
+		));
 
 		start_era(3);
 
@@ -516,10 +530,10 @@ fn report_equivocation_invalid_set_id() {
 			(1, H256::random(), 10, &equivocation_keyring),
 		);
 
-		// it should be filtered by the signed extension validation
+		// the set id doesn't map to any known session
 		assert_err!(
-			report_equivocation(equivocation_proof, key_owner_proof),
-			equivocation::ReportEquivocationValidityError::InvalidSetId,
+			Grandpa::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::InvalidEquivocationProof,
 		);
 	});
 }
@@ -555,8 +569,8 @@ fn report_equivocation_invalid_session() {
 		// report an equivocation for the current set using an key ownership
 		// proof from the previous set, the session should be invalid.
 		assert_err!(
-			report_equivocation(equivocation_proof, key_owner_proof),
-			equivocation::ReportEquivocationValidityError::InvalidSession,
+			Grandpa::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::InvalidKeyOwnershipProof,
 		);
 	});
 }
@@ -596,8 +610,12 @@ fn report_equivocation_invalid_key_owner_proof() {
 		// report an equivocation for the current set using a key ownership
 		// proof for a different key than the one in the equivocation proof.
 		assert_err!(
-			report_equivocation(equivocation_proof, invalid_key_owner_proof),
-			equivocation::ReportEquivocationValidityError::InvalidKeyOwnershipProof,
+			Grandpa::report_equivocation(
+				Origin::none(),
+				equivocation_proof,
+				invalid_key_owner_proof,
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
 		);
 	});
 }
@@ -623,8 +641,12 @@ fn report_equivocation_invalid_equivocation_proof() {
 
 		let assert_invalid_equivocation_proof = |equivocation_proof| {
 			assert_err!(
-				report_equivocation(equivocation_proof, key_owner_proof.clone()),
-				equivocation::ReportEquivocationValidityError::InvalidEquivocationProof,
+				Grandpa::report_equivocation(
+					Origin::none(),
+					equivocation_proof,
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidEquivocationProof,
 			);
 		};
 
@@ -660,3 +682,83 @@ fn report_equivocation_invalid_equivocation_proof() {
 		));
 	});
 }
+
+#[test]
+fn report_equivocation_validate_unsigned_works() {
+	let authorities = test_authorities();
+
+	new_test_ext_raw_authorities(authorities).execute_with(|| {
+		start_era(1);
+
+		let authorities = Grandpa::grandpa_authorities();
+
+		let equivocation_authority_index = 0;
+		let equivocation_key = &authorities[equivocation_authority_index].0;
+		let equivocation_keyring = extract_keyring(equivocation_key);
+
+		let set_id = Grandpa::current_set_id();
+
+		let equivocation_proof = generate_equivocation_proof(
+			set_id,
+			(1, H256::random(), 10, &equivocation_keyring),
+			(1, H256::random(), 10, &equivocation_keyring),
+		);
+
+		let key_owner_proof =
+			Historical::prove((sp_finality_grandpa::KEY_TYPE, &equivocation_key)).unwrap();
+
+		let call = super::Call::report_equivocation(
+			equivocation_proof.clone(),
+			key_owner_proof.clone(),
+		);
+
+		// only local/inblock reports are allowed
+		assert_eq!(
+			<Grandpa as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into(),
+		);
+
+		// the transaction is valid when passed as local
+		let tx_tag = (equivocation_key, set_id, 1u64);
+		assert_eq!(
+			<Grandpa as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &call),
+			TransactionValidity::Ok(ValidTransaction {
+				priority: TransactionPriority::max_value(),
+				requires: vec![],
+				provides: vec![("GrandpaEquivocation", tx_tag).encode()],
+				longevity: TransactionLongevity::max_value(),
+				propagate: false,
+			}),
+		);
+
+		// an equivocation proof for a future set is rejected
+		let invalid_proof = generate_equivocation_proof(
+			set_id + 1,
+			(1, H256::random(), 10, &equivocation_keyring),
+			(1, H256::random(), 10, &equivocation_keyring),
+		);
+		let invalid_call = super::Call::report_equivocation(invalid_proof, key_owner_proof.clone());
+
+		assert_eq!(
+			<Grandpa as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &invalid_call),
+			InvalidTransaction::BadProof.into(),
+		);
+
+		// the pre-dispatch check applies the same validation
+		assert_eq!(
+			<Grandpa as ValidateUnsigned>::pre_dispatch(&invalid_call),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::BadProof)),
+		);
+
+		// once reported the offence can't be reported again
+		assert_ok!(Grandpa::report_equivocation(
+			Origin::none(),
+			equivocation_proof.clone(),
+			key_owner_proof.clone(),
+		));
+		assert_err!(
+			Grandpa::report_equivocation(Origin::none(), equivocation_proof, key_owner_proof),
+			Error::<Test>::DuplicateOffenceReport,
+		);
+	});
+}
@@ -32,7 +32,7 @@ use sp_runtime::{Perbill, traits::{Convert, StaticLookup, Saturating, UniqueSatu
 use sp_staking::offence::{ReportOffence, Offence, OffenceDetails};
 
 use pallet_balances::{Trait as BalancesTrait};
-use pallet_babe::BabeEquivocationOffence;
+use pallet_babe::{BabeEquivocationOffence, SlotOffence};
 use pallet_grandpa::{GrandpaEquivocationOffence, GrandpaTimeSlot};
 use pallet_im_online::{Trait as ImOnlineTrait, Module as ImOnline, UnresponsivenessOffence};
 use pallet_offences::{Trait as OffencesTrait, Module as Offences};
@@ -340,12 +340,12 @@ benchmarks! {
 		let (mut offenders, raw_offenders) = make_offenders::<T>(o, n)?;
 		let keys =  ImOnline::<T>::keys();
 
-		let offence = BabeEquivocationOffence {
-			slot: 0,
-			session_index: 0,
-			validator_set_count: keys.len() as u32,
-			offender: T::convert(offenders.pop().unwrap()),
-		};
+		let offence = BabeEquivocationOffence::new(
+			0,
+			keys.len() as u32,
+			T::convert(offenders.pop().unwrap()),
+			0,
+		);
 		assert_eq!(System::<T>::event_count(), 0);
 	}: {
 		let _ = Offences::<T>::report_offence(reporters, offence);
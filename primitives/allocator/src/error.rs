@@ -25,6 +25,9 @@ pub enum Error {
 	/// Allocator run out of space.
 	#[cfg_attr(feature = "std", display(fmt="Allocator ran out of space"))]
 	AllocatorOutOfSpace,
+	/// The allocation would exceed the heap limit of the allocator.
+	#[cfg_attr(feature = "std", display(fmt="Allocation exceeds the heap limit"))]
+	HeapLimitExceeded,
 	/// Some other error occurred.
 	Other(&'static str)
 }
@@ -286,6 +286,15 @@ impl IndexMut<Order> for FreeLists {
 	}
 }
 
+/// Statistics of the allocations served by a [`FreeingBumpHeapAllocator`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationStats {
+	/// The maximal number of bytes that were allocated at the same time, including headers.
+	pub peak_heap_size: u32,
+	/// The number of allocations served.
+	pub allocations: u32,
+}
+
 /// An implementation of freeing bump allocator.
 ///
 /// Refer to the module-level documentation for further details.
@@ -293,6 +302,8 @@ pub struct FreeingBumpHeapAllocator {
 	bumper: u32,
 	free_lists: FreeLists,
 	total_size: u32,
+	heap_limit: Option<u32>,
+	stats: AllocationStats,
 }
 
 impl FreeingBumpHeapAllocator {
@@ -309,9 +320,24 @@ impl FreeingBumpHeapAllocator {
 			bumper: aligned_heap_base,
 			free_lists: FreeLists::new(),
 			total_size: 0,
+			heap_limit: None,
+			stats: AllocationStats::default(),
 		}
 	}
 
+	/// Limit the number of bytes that can be allocated at the same time, including headers.
+	///
+	/// Allocations that would exceed the limit fail with `Error::HeapLimitExceeded`.
+	pub fn with_heap_limit(mut self, heap_limit: Option<u32>) -> Self {
+		self.heap_limit = heap_limit;
+		self
+	}
+
+	/// Returns the statistics of the allocations served so far.
+	pub fn stats(&self) -> AllocationStats {
+		self.stats
+	}
+
 	/// Gets requested number of bytes to allocate and returns a pointer.
 	/// The maximum size which can be allocated at once is 16 MiB.
 	/// There is no minimum size, but whatever size is passed into
@@ -329,6 +355,11 @@ impl FreeingBumpHeapAllocator {
 	) -> Result<Pointer<u8>, Error> {
 		let order = Order::from_size(size)?;
 
+		let total_size = self.total_size + order.size() + HEADER_SIZE;
+		if self.heap_limit.map_or(false, |limit| total_size > limit) {
+			return Err(Error::HeapLimitExceeded);
+		}
+
 		let header_ptr: u32 = match self.free_lists[order] {
 			Link::Ptr(header_ptr) => {
 				assert!(
@@ -354,7 +385,9 @@ impl FreeingBumpHeapAllocator {
 		// Write the order in the occupied header.
 		Header::Occupied(order).write_into(mem, header_ptr)?;
 
-		self.total_size += order.size() + HEADER_SIZE;
+		self.total_size = total_size;
+		self.stats.allocations += 1;
+		self.stats.peak_heap_size = self.stats.peak_heap_size.max(total_size);
 		trace!("Heap size is {} bytes after allocation", self.total_size);
 
 		Ok(Pointer::new(header_ptr + HEADER_SIZE))
@@ -723,6 +756,44 @@ mod tests {
 		assert_eq!(heap.total_size, 0);
 	}
 
+	#[test]
+	fn should_track_peak_heap_size_and_allocations() {
+		// given
+		let mut mem = [0u8; PAGE_SIZE as usize];
+		let mut heap = FreeingBumpHeapAllocator::new(0);
+		let ptr = heap.allocate(&mut mem[..], 24).unwrap();
+		heap.allocate(&mut mem[..], 8).unwrap();
+
+		// when
+		heap.deallocate(&mut mem[..], ptr).unwrap();
+		heap.allocate(&mut mem[..], 8).unwrap();
+
+		// then
+		// the peak contains the first two allocations of 32 and 8 bytes plus their headers
+		assert_eq!(heap.stats(), AllocationStats { peak_heap_size: 56, allocations: 3 });
+	}
+
+	#[test]
+	fn should_not_allocate_beyond_heap_limit() {
+		// given
+		let mut mem = [0u8; PAGE_SIZE as usize];
+		let mut heap = FreeingBumpHeapAllocator::new(0).with_heap_limit(Some(32));
+		let ptr = heap.allocate(&mut mem[..], 16).unwrap();
+
+		// when
+		let exceeding = heap.allocate(&mut mem[..], 1);
+		heap.deallocate(&mut mem[..], ptr).unwrap();
+		let fitting = heap.allocate(&mut mem[..], 16);
+
+		// then
+		match exceeding.unwrap_err() {
+			Error::HeapLimitExceeded => {},
+			e => panic!("Expected heap limit exceeded error, got: {:?}", e),
+		}
+		assert!(fitting.is_ok());
+		assert_eq!(heap.stats().allocations, 2);
+	}
+
 	#[test]
 	fn should_read_and_write_u64_correctly() {
 		// given
@@ -26,5 +26,5 @@
 mod error;
 mod freeing_bump;
 
-pub use freeing_bump::FreeingBumpHeapAllocator;
+pub use freeing_bump::{AllocationStats, FreeingBumpHeapAllocator};
 pub use error::Error;
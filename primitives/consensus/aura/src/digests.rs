@@ -0,0 +1,155 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Aura pre-runtime digests.
+
+use codec::{Encode, Decode, Input, Output, Error};
+use crate::AuthorityIndex;
+
+/// The slot number of a block.
+pub type SlotNumber = u64;
+
+/// The Aura pre-runtime digest, identifying the slot a block was authored in
+/// and, for blocks authored by the slot's backup author, the index of that
+/// author.
+///
+/// Blocks authored by the primary slot author encode as the bare slot number,
+/// which keeps them compatible with the digests of earlier versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreDigest {
+	/// The slot the block was authored in.
+	pub slot_number: SlotNumber,
+	/// The index of the secondary author, if the block was not authored by
+	/// the primary slot author.
+	pub secondary_author: Option<AuthorityIndex>,
+}
+
+impl PreDigest {
+	/// Create a pre-digest for a block authored by the primary slot author.
+	pub fn primary(slot_number: SlotNumber) -> Self {
+		PreDigest { slot_number, secondary_author: None }
+	}
+
+	/// Create a pre-digest for a block authored by the given secondary author.
+	pub fn secondary(slot_number: SlotNumber, author_index: AuthorityIndex) -> Self {
+		PreDigest { slot_number, secondary_author: Some(author_index) }
+	}
+
+	/// Whether the block was authored by the primary slot author.
+	pub fn is_primary(&self) -> bool {
+		self.secondary_author.is_none()
+	}
+
+	/// The index of the author of the block in an authority set of the given
+	/// size. Returns `None` if the set is empty or if the claimed secondary
+	/// author is not the backup author of the slot.
+	pub fn author_index(&self, authorities_len: usize) -> Option<AuthorityIndex> {
+		match self.secondary_author {
+			None => primary_slot_author_index(self.slot_number, authorities_len),
+			Some(index) => {
+				if secondary_slot_author_index(self.slot_number, authorities_len) == Some(index) {
+					Some(index)
+				} else {
+					None
+				}
+			}
+		}
+	}
+}
+
+impl Encode for PreDigest {
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		self.slot_number.encode_to(dest);
+		if let Some(index) = self.secondary_author {
+			index.encode_to(dest);
+		}
+	}
+}
+
+impl codec::EncodeLike for PreDigest {}
+
+impl Decode for PreDigest {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+		let slot_number = SlotNumber::decode(input)?;
+		let secondary_author = match input.remaining_len()? {
+			Some(0) => None,
+			_ => Some(AuthorityIndex::decode(input)?),
+		};
+
+		Ok(PreDigest { slot_number, secondary_author })
+	}
+}
+
+/// The index of the primary author of the given slot, i.e. the authority the
+/// slot is assigned to in the round-robin order.
+pub fn primary_slot_author_index(
+	slot_number: SlotNumber,
+	authorities_len: usize,
+) -> Option<AuthorityIndex> {
+	if authorities_len == 0 {
+		return None;
+	}
+
+	Some((slot_number % authorities_len as u64) as AuthorityIndex)
+}
+
+/// The index of the backup author of the given slot, which may author a block
+/// if the primary author fails to do so in time. The backup author is the
+/// primary author of the following slot, there is none if there's only one
+/// authority.
+pub fn secondary_slot_author_index(
+	slot_number: SlotNumber,
+	authorities_len: usize,
+) -> Option<AuthorityIndex> {
+	if authorities_len < 2 {
+		return None;
+	}
+
+	primary_slot_author_index(slot_number.wrapping_add(1), authorities_len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn primary_pre_digest_encodes_as_slot_number() {
+		let digest = PreDigest::primary(42);
+		assert_eq!(digest.encode(), 42u64.encode());
+		assert_eq!(PreDigest::decode(&mut &42u64.encode()[..]).unwrap(), digest);
+	}
+
+	#[test]
+	fn secondary_pre_digest_roundtrips() {
+		let digest = PreDigest::secondary(42, 3);
+		let encoded = digest.encode();
+
+		assert_eq!(PreDigest::decode(&mut &encoded[..]).unwrap(), digest);
+		// the slot number can still be read by decoders unaware of backup authors.
+		assert_eq!(u64::decode(&mut &encoded[..]).unwrap(), 42);
+	}
+
+	#[test]
+	fn author_index_checks_secondary_author() {
+		assert_eq!(PreDigest::primary(5).author_index(0), None);
+		assert_eq!(PreDigest::primary(5).author_index(3), Some(2));
+
+		assert_eq!(PreDigest::secondary(5, 0).author_index(3), Some(0));
+		assert_eq!(PreDigest::secondary(5, 1).author_index(3), None);
+		assert_eq!(PreDigest::secondary(5, 0).author_index(1), None);
+	}
+}
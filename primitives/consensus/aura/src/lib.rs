@@ -21,10 +21,14 @@
 
 use codec::{Encode, Decode, Codec};
 use sp_std::vec::Vec;
-use sp_runtime::ConsensusEngineId;
+use sp_application_crypto::RuntimeAppPublic;
+use sp_runtime::{ConsensusEngineId, traits::Header};
 
+pub mod digests;
 pub mod inherents;
 
+pub use digests::{PreDigest, SlotNumber, primary_slot_author_index, secondary_slot_author_index};
+
 pub mod sr25519 {
 	mod app_sr25519 {
 		use sp_application_crypto::{app_crypto, key_types::AURA, sr25519};
@@ -78,8 +82,93 @@ pub enum ConsensusLog<AuthorityId: Codec> {
 	OnDisabled(AuthorityIndex),
 }
 
+/// An equivocation proof for multiple block authorships on the same slot (i.e. double vote).
+pub type EquivocationProof<H, AuthorityId> = sp_consensus_slots::EquivocationProof<H, AuthorityId>;
+
+/// Verifies the equivocation proof by making sure that: both headers have
+/// different hashes, are targetting the same slot, and have valid signatures by
+/// the same authority.
+pub fn check_equivocation_proof<H, AuthorityId>(proof: EquivocationProof<H, AuthorityId>) -> bool
+where
+	H: Header,
+	AuthorityId: RuntimeAppPublic,
+{
+	let find_slot = |header: &H| {
+		header.digest().logs().iter().find_map(|log| match log.as_pre_runtime() {
+			Some((id, data)) if id == AURA_ENGINE_ID => u64::decode(&mut &data[..]).ok(),
+			_ => None,
+		})
+	};
+
+	let verify_seal_signature = |mut header: H, offender: &AuthorityId| {
+		let seal = header.digest_mut().pop()?;
+		let signature = match seal.as_seal() {
+			Some((id, data)) if id == AURA_ENGINE_ID =>
+				<AuthorityId::Signature as Decode>::decode(&mut &data[..]).ok()?,
+			_ => return None,
+		};
+
+		let pre_hash = header.hash();
+
+		if !offender.verify(&pre_hash.as_ref(), &signature) {
+			return None;
+		}
+
+		Some(())
+	};
+
+	let verify_proof = || {
+		// we must have different headers for the equivocation to be valid
+		if proof.first_header.hash() == proof.second_header.hash() {
+			return None;
+		}
+
+		let first_slot = find_slot(&proof.first_header)?;
+		let second_slot = find_slot(&proof.second_header)?;
+
+		// both headers must be targetting the same slot and it must
+		// be the same as the one in the proof.
+		if proof.slot_number != first_slot || first_slot != second_slot {
+			return None;
+		}
+
+		// we finally verify that the expected authority has signed both headers and
+		// that the signature is valid.
+		verify_seal_signature(proof.first_header, &proof.offender)?;
+		verify_seal_signature(proof.second_header, &proof.offender)?;
+
+		Some(())
+	};
+
+	verify_proof().is_some()
+}
+
+/// An opaque type used to represent the key ownership proof at the runtime API
+/// boundary. The inner value is an encoded representation of the actual key
+/// ownership proof which will be parameterized when defining the runtime. At
+/// the runtime API boundary this type is unknown and as such we keep this
+/// opaque representation, implementors of the runtime API will have to make
+/// sure that all usages of `OpaqueKeyOwnershipProof` refer to the same type.
+#[derive(Decode, Encode, PartialEq)]
+pub struct OpaqueKeyOwnershipProof(Vec<u8>);
+
+impl OpaqueKeyOwnershipProof {
+	/// Create a new `OpaqueKeyOwnershipProof` using the given encoded
+	/// representation.
+	pub fn new(inner: Vec<u8>) -> OpaqueKeyOwnershipProof {
+		OpaqueKeyOwnershipProof(inner)
+	}
+
+	/// Try to decode this `OpaqueKeyOwnershipProof` into the given concrete key
+	/// ownership proof type.
+	pub fn decode<T: Decode>(self) -> Option<T> {
+		Decode::decode(&mut &self.0[..]).ok()
+	}
+}
+
 sp_api::decl_runtime_apis! {
 	/// API necessary for block authorship with aura.
+	#[api_version(3)]
 	pub trait AuraApi<AuthorityId: Codec> {
 		/// Return the slot duration in seconds for Aura.
 		/// Currently, only the value provided by this type at genesis
@@ -90,5 +179,32 @@ sp_api::decl_runtime_apis! {
 
 		// Return the current set of authorities.
 		fn authorities() -> Vec<AuthorityId>;
+
+		/// Generates a proof of key ownership for the given authority in the
+		/// session of the given slot. Proofs of key ownership are necessary for
+		/// submitting equivocation reports. NOTE: the current implementations
+		/// rely on this method being called at a block where the session of the
+		/// given slot is still live on-chain.
+		fn generate_key_ownership_proof(
+			slot_number: u64,
+			authority_id: AuthorityId,
+		) -> Option<OpaqueKeyOwnershipProof>;
+
+		/// Submits an unsigned extrinsic to report an equivocation. The caller
+		/// must provide the equivocation proof and a key ownership proof
+		/// (should be obtained using `generate_key_ownership_proof`). The
+		/// extrinsic will be unsigned and should only be accepted for local
+		/// authorship (not to be broadcast to the network). This method returns
+		/// `None` when creation of the extrinsic fails, e.g. if equivocation
+		/// reporting is disabled for the given runtime (i.e. this method is
+		/// hardcoded to return `None`). Only useful in an offchain context.
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: EquivocationProof<Block::Header, AuthorityId>,
+			key_owner_proof: OpaqueKeyOwnershipProof,
+		) -> Option<()>;
+
+		/// Whether the backup author of a slot may author a block when the
+		/// primary author fails to do so in time.
+		fn secondary_authors_allowed() -> bool;
 	}
 }
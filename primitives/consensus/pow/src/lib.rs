@@ -26,6 +26,10 @@ use codec::Decode;
 /// The `ConsensusEngineId` of PoW.
 pub const POW_ENGINE_ID: ConsensusEngineId = [b'p', b'o', b'w', b'_'];
 
+/// The `ConsensusEngineId` of the pre-runtime digest carrying the SCALE encoded `u64` timestamp of
+/// a PoW block in milliseconds.
+pub const POW_TIMESTAMP_ENGINE_ID: ConsensusEngineId = [b'p', b'o', b'w', b't'];
+
 /// Type of seal.
 pub type Seal = Vec<u8>;
 
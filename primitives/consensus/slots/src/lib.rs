@@ -0,0 +1,130 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Primitives for slots-based consensus engines.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use sp_runtime::{Perbill, RuntimeDebug};
+use sp_staking::{
+	offence::{Kind, Offence},
+	SessionIndex,
+};
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Represents an equivocation proof. An equivocation happens when a validator
+/// produces more than one block on the same slot. The proof of equivocation
+/// are the given distinct headers that were signed by the validator and which
+/// include the slot number.
+#[derive(Clone, RuntimeDebug, Decode, Encode, PartialEq)]
+pub struct EquivocationProof<Header, Id> {
+	/// The authority id of the equivocator.
+	pub offender: Id,
+	/// The slot number at which the equivocation happened.
+	pub slot_number: u64,
+	/// The first header involved in the equivocation.
+	pub first_header: Header,
+	/// The second header involved in the equivocation.
+	pub second_header: Header,
+}
+
+/// The kind of an equivocation offence, telling which consensus engine it
+/// happened in.
+pub trait EquivocationKind {
+	/// Identifier of the offences of this kind.
+	const ID: Kind;
+}
+
+/// An equivocation offence report.
+///
+/// When a validator released two or more blocks at the same slot.
+pub struct EquivocationOffence<FullIdentification, K> {
+	/// The slot number in which this incident happened.
+	pub slot: u64,
+	/// The session index in which the incident happened.
+	pub session_index: SessionIndex,
+	/// The size of the validator set at the time of the offence.
+	pub validator_set_count: u32,
+	/// The authority that produced the equivocation.
+	pub offender: FullIdentification,
+	/// The kind of the offence.
+	pub kind: PhantomData<K>,
+}
+
+/// An interface for types that will be used as slot equivocation offences
+/// and must also implement the `Offence` trait. This trait provides a
+/// constructor that is provided all available data during processing of
+/// equivocations.
+pub trait SlotOffence<FullIdentification>: Offence<FullIdentification> {
+	/// Create a new offence using the given equivocation details.
+	fn new(
+		session_index: SessionIndex,
+		validator_set_count: u32,
+		offender: FullIdentification,
+		slot: u64,
+	) -> Self;
+}
+
+impl<FullIdentification: Clone, K: EquivocationKind> SlotOffence<FullIdentification>
+	for EquivocationOffence<FullIdentification, K>
+{
+	fn new(
+		session_index: SessionIndex,
+		validator_set_count: u32,
+		offender: FullIdentification,
+		slot: u64,
+	) -> Self {
+		EquivocationOffence {
+			slot,
+			session_index,
+			validator_set_count,
+			offender,
+			kind: PhantomData,
+		}
+	}
+}
+
+impl<FullIdentification: Clone, K: EquivocationKind> Offence<FullIdentification>
+	for EquivocationOffence<FullIdentification, K>
+{
+	const ID: Kind = K::ID;
+	type TimeSlot = u64;
+
+	fn offenders(&self) -> Vec<FullIdentification> {
+		vec![self.offender.clone()]
+	}
+
+	fn session_index(&self) -> SessionIndex {
+		self.session_index
+	}
+
+	fn validator_set_count(&self) -> u32 {
+		self.validator_set_count
+	}
+
+	fn time_slot(&self) -> Self::TimeSlot {
+		self.slot
+	}
+
+	fn slash_fraction(offenders_count: u32, validator_set_count: u32) -> Perbill {
+		// the formula is min((3k / n)^2, 1)
+		let x = Perbill::from_rational_approximation(3 * offenders_count, validator_set_count);
+		// _ ^ 2
+		x.square()
+	}
+}
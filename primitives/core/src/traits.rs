@@ -352,6 +352,14 @@ impl CallInWasmExt {
 	}
 }
 
+sp_externalities::decl_extension! {
+	/// Limits the number of bytes a Wasm runtime call can allocate at the same time.
+	///
+	/// Only registered for calls whose outcome doesn't have to agree between nodes, like RPC and
+	/// offchain calls. Calls that exceed the limit fail.
+	pub struct RuntimeHeapLimitExt(u32);
+}
+
 /// Something that can spawn tasks and also can be cloned.
 pub trait CloneableSpawn: futures::task::Spawn + Send + Sync {
 	/// Clone as heap-allocated handle.
@@ -64,6 +64,9 @@ pub enum Error {
 	/// The pool is not accepting future transactions.
 	#[display(fmt="The pool is not accepting future transactions")]
 	RejectedFutureTransaction,
+	/// The bundle can't be imported as a whole.
+	#[display(fmt="Invalid bundle: {}", _0)]
+	InvalidBundle(String),
 }
 
 impl std::error::Error for Error {}
@@ -54,6 +54,53 @@ impl PoolStatus {
 	}
 }
 
+/// The queue of the pool a transaction sits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionQueue {
+	/// All requirements are satisfied, the transaction can be included in a block.
+	Ready,
+	/// The transaction waits for tags no other transaction provides yet.
+	Future,
+}
+
+/// Details of a transaction in the pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionDetails<Hash> {
+	/// Transaction hash.
+	pub hash: Hash,
+	/// Source of the transaction.
+	pub source: TransactionSource,
+	/// Transaction priority.
+	pub priority: TransactionPriority,
+	/// Tags required by the transaction.
+	pub requires: Vec<TransactionTag>,
+	/// Tags provided by the transaction.
+	pub provides: Vec<TransactionTag>,
+	/// Block number until which the transaction is valid.
+	pub longevity: TransactionLongevity,
+	/// Number of the block the transaction was validated at when it entered the pool, if known.
+	pub inserted_at: Option<u64>,
+	/// The queue the transaction sits in.
+	pub queue: TransactionQueue,
+	/// Required tags that are neither provided by the pool nor were recently pruned.
+	///
+	/// Always empty for ready transactions.
+	pub missing_tags: Vec<TransactionTag>,
+}
+
+/// Reason of a transaction leaving the pool without being included in a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropReason<Hash> {
+	/// The transaction was replaced by the transaction with the given hash.
+	Usurped(Hash),
+	/// The transaction turned out to be invalid.
+	Invalid,
+	/// The transaction was dropped to keep the pool within its limits.
+	Limits,
+	/// The transaction was removed at the request of the node operator.
+	Removed,
+}
+
 /// Possible transaction status events.
 ///
 /// This events are being emitted by `TransactionPool` watchers,
@@ -205,6 +252,17 @@ pub trait TransactionPool: Send + Sync {
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<TxHash<Self>, Self::Error>;
 
+	/// Returns a future that imports a bundle of unverified transactions to the pool.
+	///
+	/// The members are validated against the same state and are only included in a block
+	/// together and in the given order.
+	fn submit_bundle(
+		&self,
+		at: &BlockId<Self::Block>,
+		source: TransactionSource,
+		xts: Vec<TransactionFor<Self>>,
+	) -> PoolFuture<Vec<TxHash<Self>>, Self::Error>;
+
 	/// Returns a future that import a single transaction and starts to watch their progress in the pool.
 	fn submit_and_watch(
 		&self,
@@ -228,6 +286,11 @@ pub trait TransactionPool: Send + Sync {
 	/// Remove transactions identified by given hashes (and dependent transactions) from the pool.
 	fn remove_invalid(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>>;
 
+	// *** RPC
+	/// Remove transactions identified by given hashes (and dependent transactions) from the pool
+	/// at the request of the node operator.
+	fn remove(&self, hashes: &[TxHash<Self>]) -> Vec<Arc<Self::InPoolTransaction>>;
+
 	// *** logging
 	/// Returns pool status.
 	fn status(&self) -> PoolStatus;
@@ -245,6 +308,16 @@ pub trait TransactionPool: Send + Sync {
 
 	/// Return specific ready transaction by hash, if there is one.
 	fn ready_transaction(&self, hash: &TxHash<Self>) -> Option<Arc<Self::InPoolTransaction>>;
+
+	/// Returns the members of the bundle the given transaction belongs to, in order.
+	fn bundle_of(&self, hash: &TxHash<Self>) -> Option<Vec<TxHash<Self>>>;
+
+	// *** RPC / debugging
+	/// Returns details of all transactions in the pool.
+	fn inspect(&self) -> Vec<TransactionDetails<TxHash<Self>>>;
+
+	/// Returns transactions recently dropped from the pool and why, oldest first.
+	fn recently_dropped(&self) -> Vec<(TxHash<Self>, DropReason<TxHash<Self>>)>;
 }
 
 /// Events that the transaction pool listens for.
@@ -20,7 +20,7 @@ use log::{debug, info};
 use sc_network::config::TransportConfig;
 use sc_service::{
 	AbstractService, RpcSession, Role, Configuration,
-	config::{DatabaseConfig, KeystoreConfig, NetworkConfiguration},
+	config::{DatabaseConfig, KeystoreConfig, NetworkConfiguration, DEFAULT_STACK_HEIGHT_LIMIT},
 	GenericChainSpec, RuntimeGenesis
 };
 use wasm_bindgen::prelude::*;
@@ -96,6 +96,12 @@ where
 		transaction_pool: Default::default(),
 		wasm_method: Default::default(),
 		max_runtime_instances: 8,
+		runtime_heap_limit: None,
+		runtime_stack_height_limit: Some(DEFAULT_STACK_HEIGHT_LIMIT),
+		runtime_fuel_limit: None,
+		runtime_instances_memory: None,
+		runtime_profiling: None,
+		wasm_runtime_cache: None,
 		announce_block: true,
 		base_path: None,
 		informant_output_format: sc_informant::OutputFormat {
@@ -0,0 +1,159 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Command ran by the CLI.
+
+use crate::{DifferentialCmd, DifferentialSubCmd, Divergence, Harness};
+use std::{fmt::Debug, fs, str::FromStr};
+use codec::Encode;
+use log::{info, warn};
+use sc_cli::{CliConfiguration, Error, ImportParams, Result, SharedParams};
+use sc_client_api::BlockBackend;
+use sc_service::{new_full_client, Configuration, NativeExecutionDispatch, TFullClient};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, NumberFor, One},
+};
+
+impl DifferentialCmd {
+	/// Run the command, comparing all executors on the chain data.
+	pub fn run<B, RA, EX>(&self, config: Configuration) -> Result<()>
+	where
+		B: BlockT,
+		B::Hash: FromStr,
+		<B::Hash as FromStr>::Err: Debug,
+		<NumberFor<B> as FromStr>::Err: Debug,
+		RA: Send + Sync + 'static,
+		EX: NativeExecutionDispatch + 'static,
+	{
+		let client = new_full_client::<B, RA, EX>(&config)?;
+		let harness = Harness::<EX>::new(config.default_heap_pages);
+
+		let mut calls = 0;
+		let mut divergences = 0;
+		match &self.command {
+			DifferentialSubCmd::Blocks { from, to } => {
+				let mut number: NumberFor<B> = from.parse()?;
+				let to = match to {
+					Some(to) => to.parse()?,
+					None => client.chain_info().best_number,
+				};
+
+				while number <= to {
+					let block = client.block(&BlockId::Number(number))?
+						.ok_or_else(|| format!("Block #{} not found", number))?
+						.block;
+					let (mut header, extrinsics) = block.deconstruct();
+					let hash = header.hash();
+
+					// Seals are not part of the executed block, the import queue strips them.
+					while header.digest().logs().last().map_or(false, |log| log.as_seal().is_some()) {
+						header.digest_mut().pop();
+					}
+					let parent = BlockId::Hash(*header.parent_hash());
+					let data = B::new(header, extrinsics).encode();
+
+					info!("Replaying block #{} ({:?})", number, hash);
+					calls += 1;
+					if let Some(divergence) = execute(&client, &harness, &parent, "Core_execute_block", &data)? {
+						divergences += 1;
+						let block = serde_json::json!({ "number": number.to_string(), "hash": format!("{:?}", hash) });
+						self.report(&divergence, &format!("block-{:?}", hash), "block", block)?;
+					}
+
+					number += One::one();
+				}
+			}
+			DifferentialSubCmd::Call { at, method, data } => {
+				let at = match at {
+					Some(at) => at.parse::<B>()?,
+					None => BlockId::Hash(client.chain_info().best_hash),
+				};
+				let hash = client.expect_block_hash_from_id(&at)?;
+				let data = hex::decode(data.trim_start_matches("0x"))
+					.map_err(|e| format!("Invalid call data: {}", e))?;
+
+				calls += 1;
+				if let Some(divergence) = execute(&client, &harness, &BlockId::Hash(hash), method, &data)? {
+					divergences += 1;
+					let at = serde_json::json!({ "hash": format!("{:?}", hash) });
+					self.report(&divergence, &format!("{}-{:?}", method, hash), "at", at)?;
+				}
+			}
+		}
+
+		if divergences > 0 {
+			return Err(Error::Other(format!(
+				"Executors diverged on {} of {} calls, reproducers written to {:?}",
+				divergences,
+				calls,
+				self.reproducer_dir,
+			)))
+		}
+		println!("All executors agree on {} calls", calls);
+		Ok(())
+	}
+
+	/// Write the reproducer of `divergence` together with the `context` of the call.
+	fn report(
+		&self,
+		divergence: &Divergence,
+		name: &str,
+		context_key: &str,
+		context: serde_json::Value,
+	) -> Result<()> {
+		let mut reproducer = divergence.reproducer();
+		reproducer[context_key] = context;
+
+		fs::create_dir_all(&self.reproducer_dir)?;
+		let path = self.reproducer_dir.join(format!("divergence-{}.json", name));
+		let reproducer = serde_json::to_string_pretty(&reproducer)
+			.map_err(|e| Error::Other(format!("Cannot encode reproducer: {}", e)))?;
+		fs::write(&path, reproducer)?;
+
+		warn!("Executors diverged on `{}`, reproducer written to {:?}", divergence.method, path);
+		Ok(())
+	}
+}
+
+fn execute<B, RA, EX>(
+	client: &TFullClient<B, RA, EX>,
+	harness: &Harness<EX>,
+	at: &BlockId<B>,
+	method: &str,
+	data: &[u8],
+) -> Result<Option<Divergence>>
+where
+	B: BlockT,
+	RA: Send + Sync + 'static,
+	EX: NativeExecutionDispatch + 'static,
+{
+	let state = client.state_at(at)?;
+	let version = client.runtime_version_at(at)?;
+	Ok(harness.run::<B, _>(&state, &version, method, data)?)
+}
+
+impl CliConfiguration for DifferentialCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
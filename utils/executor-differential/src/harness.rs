@@ -0,0 +1,354 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Runs a runtime call with every executor and compares the outcomes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use log::debug;
+use sc_executor::{NativeExecutor, NativeExecutionDispatch, RuntimeInfo, WasmExecutionMethod};
+use sp_core::{
+	tasks,
+	testing::KeyStore,
+	traits::KeystoreExt,
+	offchain::{OffchainExt, testing::TestOffchainExt},
+};
+use sp_externalities::Extensions;
+use sp_runtime::traits::{Block as BlockT, HashFor, NumberFor};
+use sp_state_machine::{
+	Backend, ExecutionStrategy, OffchainOverlayedChanges, OverlayedChanges, StateMachine,
+	backend::BackendRuntimeCode,
+};
+use sp_version::RuntimeVersion;
+
+/// An executor a call can be run with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Executor {
+	/// The native runtime the node was built with.
+	Native,
+	/// The on-chain runtime executed by wasmi.
+	Interpreted,
+	/// The on-chain runtime executed by wasmtime.
+	#[cfg(feature = "wasmtime")]
+	Compiled,
+}
+
+impl Executor {
+	/// All executors available in this build.
+	pub fn all() -> Vec<Executor> {
+		vec![
+			Executor::Native,
+			Executor::Interpreted,
+			#[cfg(feature = "wasmtime")]
+			Executor::Compiled,
+		]
+	}
+
+	/// Name of the executor as used in reproducers.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Executor::Native => "native",
+			Executor::Interpreted => "interpreted",
+			#[cfg(feature = "wasmtime")]
+			Executor::Compiled => "compiled",
+		}
+	}
+
+	fn wasm_method(&self) -> WasmExecutionMethod {
+		match self {
+			Executor::Native | Executor::Interpreted => WasmExecutionMethod::Interpreted,
+			#[cfg(feature = "wasmtime")]
+			Executor::Compiled => WasmExecutionMethod::Compiled,
+		}
+	}
+
+	fn strategy(&self) -> ExecutionStrategy {
+		match self {
+			Executor::Native => ExecutionStrategy::NativeWhenPossible,
+			_ => ExecutionStrategy::AlwaysWasm,
+		}
+	}
+}
+
+/// Storage changes made by a call. `None` values are deletions.
+pub type StorageChanges = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// The outcome of a call with a single executor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+	/// The encoded return value, or the reason of the failure.
+	pub result: Result<Vec<u8>, String>,
+	/// Changes of the main storage.
+	pub storage: StorageChanges,
+	/// Changes of child storages, by storage key of the child trie.
+	pub child_storage: BTreeMap<Vec<u8>, StorageChanges>,
+}
+
+impl Outcome {
+	/// Returns `true` if both outcomes agree.
+	///
+	/// Failures agree regardless of their reason, since a native panic and a Wasm trap are
+	/// reported differently.
+	pub fn agrees_with(&self, other: &Outcome) -> bool {
+		let results_agree = match (&self.result, &other.result) {
+			(Ok(a), Ok(b)) => a == b,
+			(Err(_), Err(_)) => true,
+			_ => false,
+		};
+		results_agree && self.storage == other.storage && self.child_storage == other.child_storage
+	}
+}
+
+/// A call whose outcome differs between executors.
+#[derive(Debug, Clone)]
+pub struct Divergence {
+	/// The called runtime function.
+	pub method: String,
+	/// The encoded arguments of the call.
+	pub data: Vec<u8>,
+	/// The outcome of the call with each executor.
+	pub outcomes: Vec<(Executor, Outcome)>,
+}
+
+impl Divergence {
+	/// A minimal reproducer of the divergence.
+	///
+	/// Contains the call, the result of each executor and the storage keys whose changes differ
+	/// between executors.
+	pub fn reproducer(&self) -> serde_json::Value {
+		let results = self.outcomes.iter()
+			.map(|(executor, outcome)| {
+				let result = match &outcome.result {
+					Ok(value) => serde_json::json!({ "ok": hex_encode(value) }),
+					Err(e) => serde_json::json!({ "err": e }),
+				};
+				(executor.name().to_string(), result)
+			})
+			.collect::<serde_json::Map<_, _>>();
+
+		let child_keys = self.outcomes.iter()
+			.flat_map(|(_, outcome)| outcome.child_storage.keys())
+			.collect::<BTreeSet<_>>();
+		let child_storage = child_keys.into_iter()
+			.map(|child| {
+				let diff = self.storage_diff(|outcome| outcome.child_storage.get(child));
+				(hex_encode(child), diff)
+			})
+			.filter(|(_, diff)| diff.as_object().map_or(false, |diff| !diff.is_empty()))
+			.collect::<serde_json::Map<_, _>>();
+
+		serde_json::json!({
+			"method": self.method,
+			"data": hex_encode(&self.data),
+			"results": results,
+			"storage": self.storage_diff(|outcome| Some(&outcome.storage)),
+			"child_storage": child_storage,
+		})
+	}
+
+	/// The changes of each executor to the keys whose changes differ between executors.
+	fn storage_diff<'a>(
+		&'a self,
+		changes: impl Fn(&'a Outcome) -> Option<&'a StorageChanges>,
+	) -> serde_json::Value {
+		let changes = self.outcomes.iter()
+			.map(|(executor, outcome)| (executor, changes(outcome)))
+			.collect::<Vec<_>>();
+		let keys = changes.iter()
+			.flat_map(|(_, changes)| changes.into_iter().flat_map(|changes| changes.keys()))
+			.collect::<BTreeSet<_>>();
+
+		keys.into_iter()
+			.filter_map(|key| {
+				let values = changes.iter()
+					.map(|(executor, changes)| {
+						(executor, changes.and_then(|changes| changes.get(key)))
+					})
+					.collect::<Vec<_>>();
+				if values.iter().all(|(_, value)| *value == values[0].1) {
+					return None
+				}
+
+				let values = values.into_iter()
+					.map(|(executor, value)| {
+						let value = match value {
+							Some(Some(value)) => serde_json::Value::from(hex_encode(value)),
+							Some(None) => "deleted".into(),
+							None => "unchanged".into(),
+						};
+						(executor.name().to_string(), value)
+					})
+					.collect::<serde_json::Map<_, _>>();
+				Some((hex_encode(key), serde_json::Value::Object(values)))
+			})
+			.collect::<serde_json::Map<_, _>>()
+			.into()
+	}
+}
+
+/// Runs runtime calls with the native runtime and every available Wasm executor.
+pub struct Harness<D> {
+	executors: Vec<(Executor, NativeExecutor<D>)>,
+}
+
+impl<D: NativeExecutionDispatch + 'static> Harness<D> {
+	/// Create a new harness using `heap_pages` for Wasm execution.
+	pub fn new(heap_pages: Option<u64>) -> Self {
+		let executors = Executor::all().into_iter()
+			.map(|executor| (executor, NativeExecutor::new(executor.wasm_method(), heap_pages, 1)))
+			.collect();
+		Harness { executors }
+	}
+
+	/// Run `method` with every executor on top of `state`.
+	///
+	/// The native runtime is skipped if it can't run calls of the `onchain_version`. Returns the
+	/// divergence if any executor disagrees with the first one.
+	pub fn run<Block, S>(
+		&self,
+		state: &S,
+		onchain_version: &RuntimeVersion,
+		method: &str,
+		data: &[u8],
+	) -> Result<Option<Divergence>, String>
+	where
+		Block: BlockT,
+		S: Backend<HashFor<Block>>,
+	{
+		let runtime_code = BackendRuntimeCode::new(state).runtime_code()?;
+
+		let mut outcomes = Vec::with_capacity(self.executors.len());
+		for (executor, native_executor) in &self.executors {
+			if *executor == Executor::Native &&
+				!native_executor.native_version().runtime_version.can_call_with(onchain_version)
+			{
+				debug!(
+					target: "differential",
+					"Skipping native execution, native runtime can't call {:?}",
+					onchain_version,
+				);
+				continue
+			}
+
+			let mut overlay = OverlayedChanges::default();
+			let mut offchain_overlay = OffchainOverlayedChanges::default();
+			let result = StateMachine::<_, _, NumberFor<Block>, _>::new(
+				state,
+				None,
+				&mut overlay,
+				&mut offchain_overlay,
+				native_executor,
+				method,
+				data,
+				extensions(),
+				&runtime_code,
+				tasks::executor(),
+			)
+			.execute(executor.strategy())
+			.map_err(|e| format!("{:?}", e));
+
+			let storage = overlay.changes()
+				.map(|(key, value)| (key.clone(), value.value().cloned()))
+				.collect();
+			let child_storage = overlay.children()
+				.map(|(changes, info)| (
+					info.storage_key().to_vec(),
+					changes.map(|(key, value)| (key.clone(), value.value().cloned())).collect(),
+				))
+				.collect();
+
+			outcomes.push((*executor, Outcome { result, storage, child_storage }));
+		}
+
+		let diverged = outcomes.iter().skip(1).any(|(_, outcome)| !outcome.agrees_with(&outcomes[0].1));
+		Ok(if diverged {
+			Some(Divergence { method: method.into(), data: data.to_vec(), outcomes })
+		} else {
+			None
+		})
+	}
+}
+
+fn extensions() -> Extensions {
+	let mut extensions = Extensions::default();
+	extensions.register(KeystoreExt(KeyStore::new()));
+	let (offchain, _) = TestOffchainExt::new();
+	extensions.register(OffchainExt::new(offchain));
+	extensions
+}
+
+fn hex_encode(data: &[u8]) -> String {
+	format!("0x{}", hex::encode(data))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::generic::BlockId;
+	use substrate_test_runtime_client::{prelude::*, runtime::Block, TestClientBuilder};
+
+	fn outcome(result: Result<Vec<u8>, String>, storage: &[(&[u8], Option<&[u8]>)]) -> Outcome {
+		Outcome {
+			result,
+			storage: storage.iter().map(|(k, v)| (k.to_vec(), v.map(|v| v.to_vec()))).collect(),
+			child_storage: Default::default(),
+		}
+	}
+
+	#[test]
+	fn executors_agree_on_runtime_calls() {
+		let client = TestClientBuilder::new().build();
+		let at = BlockId::Number(0);
+		let state = client.state_at(&at).unwrap();
+		let version = client.runtime_version_at(&at).unwrap();
+		let harness = Harness::<LocalExecutor>::new(None);
+
+		let divergence = harness.run::<Block, _>(&state, &version, "Core_version", &[]).unwrap();
+
+		assert!(divergence.is_none());
+	}
+
+	#[test]
+	fn failures_agree_regardless_of_reason() {
+		let native = outcome(Err("panicked".into()), &[]);
+		let wasm = outcome(Err("trapped".into()), &[]);
+		let succeeded = outcome(Ok(vec![]), &[]);
+
+		assert!(native.agrees_with(&wasm));
+		assert!(!native.agrees_with(&succeeded));
+	}
+
+	#[test]
+	fn reproducer_contains_diverging_keys_only() {
+		let divergence = Divergence {
+			method: "Core_execute_block".into(),
+			data: vec![1, 2],
+			outcomes: vec![
+				(Executor::Native, outcome(Ok(vec![]), &[(b"same", Some(b"1")), (b"diff", Some(b"2"))])),
+				(Executor::Interpreted, outcome(Ok(vec![]), &[(b"same", Some(b"1")), (b"diff", None)])),
+			],
+		};
+
+		assert_eq!(divergence.reproducer(), serde_json::json!({
+			"method": "Core_execute_block",
+			"data": "0x0102",
+			"results": { "native": { "ok": "0x" }, "interpreted": { "ok": "0x" } },
+			"storage": { "0x64696666": { "native": "0x32", "interpreted": "deleted" } },
+			"child_storage": {},
+		}));
+	}
+}
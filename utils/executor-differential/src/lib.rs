@@ -0,0 +1,81 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! Differential testing of runtime calls.
+//!
+//! Replays imported blocks or arbitrary runtime API calls with the native runtime and every
+//! available Wasm executor and compares their return values, storage changes and failures. For
+//! every call the executors disagree on, a minimal reproducer is written.
+
+#![warn(missing_docs)]
+
+mod command;
+mod harness;
+
+pub use harness::{Divergence, Executor, Harness, Outcome, StorageChanges};
+
+use std::path::PathBuf;
+use sc_cli::{BlockNumber, BlockNumberOrHash, ImportParams, SharedParams};
+use structopt::StructOpt;
+
+/// The `differential` command used to compare the executors on the chain data.
+#[derive(Debug, StructOpt)]
+pub struct DifferentialCmd {
+	#[allow(missing_docs)]
+	#[structopt(subcommand)]
+	pub command: DifferentialSubCmd,
+
+	/// Directory the reproducers of diverging calls are written to.
+	#[structopt(long, value_name = "PATH", parse(from_os_str), default_value = ".")]
+	pub reproducer_dir: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
+
+/// The possible sub-commands of the `differential` command.
+#[derive(Debug, StructOpt)]
+pub enum DifferentialSubCmd {
+	/// Replay a range of imported blocks.
+	Blocks {
+		/// Number of the first block to replay.
+		#[structopt(long, value_name = "NUMBER", default_value = "1")]
+		from: BlockNumber,
+
+		/// Number of the last block to replay. Defaults to the best block.
+		#[structopt(long, value_name = "NUMBER")]
+		to: Option<BlockNumber>,
+	},
+	/// Run a runtime API call.
+	Call {
+		/// The block whose state the call is run on top of. Defaults to the best block.
+		#[structopt(long, value_name = "HASH or NUMBER")]
+		at: Option<BlockNumberOrHash>,
+
+		/// The runtime function to call, e.g. `Core_version`.
+		method: String,
+
+		/// The 0x-prefixed SCALE encoded arguments of the call.
+		#[structopt(long, value_name = "BYTES", default_value = "0x")]
+		data: String,
+	},
+}
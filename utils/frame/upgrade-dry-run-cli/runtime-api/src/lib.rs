@@ -0,0 +1,33 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition required by the `upgrade-dry-run` command.
+//!
+//! The weight of the migrations can't be read back from the block they run in, so runtimes
+//! that want their migrations checked against the maximum block weight implement this API,
+//! usually by calling `frame_executive::Executive::execute_on_runtime_upgrade`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+sp_api::decl_runtime_apis! {
+	/// The API to run the migrations of a runtime outside of a block.
+	pub trait UpgradeDryRunApi {
+		/// Run all the `on_runtime_upgrade` hooks of the runtime and return the weight they
+		/// consumed.
+		fn on_runtime_upgrade() -> u64;
+	}
+}
@@ -0,0 +1,345 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command ran by the CLI.
+
+use crate::{keys_by_module, Metadata, MetadataDiff, UpgradeDryRunCmd};
+use std::{fmt::Debug, fs, str::FromStr};
+use codec::{Decode, Encode};
+use frame_upgrade_dry_run_runtime_api::UpgradeDryRunApi;
+use log::{info, warn};
+use sc_cli::{CliConfiguration, Error, ImportParams, Result, SharedParams};
+use sc_executor::NativeExecutor;
+use sc_service::{new_full_client, Configuration, NativeExecutionDispatch};
+use sp_api::RuntimeApiInfo;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	hashing::{blake2_256, twox_128},
+	storage::well_known_keys,
+	tasks,
+	traits::{RuntimeCode, WrappedRuntimeCode},
+};
+use sp_externalities::{Extensions, Externalities};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, HashFor, Header as HeaderT, NumberFor, One},
+};
+use sp_state_machine::{
+	Backend, ExecutionStrategy, Ext, OffchainOverlayedChanges, OverlayedChanges, StateMachine,
+	StorageTransactionCache, backend::BackendRuntimeCode,
+};
+use sp_version::RuntimeVersion;
+
+impl UpgradeDryRunCmd {
+	/// Run the command, dry-running the upgrade to the given runtime.
+	pub fn run<B, RA, EX>(&self, config: Configuration) -> Result<()>
+	where
+		B: BlockT,
+		B::Hash: FromStr,
+		<B::Hash as FromStr>::Err: Debug,
+		<NumberFor<B> as FromStr>::Err: Debug,
+		RA: Send + Sync + 'static,
+		EX: NativeExecutionDispatch + 'static,
+	{
+		let new_code = fs::read(&self.code)?;
+		let client = new_full_client::<B, RA, EX>(&config)?;
+		let executor = NativeExecutor::<EX>::new(config.wasm_method, config.default_heap_pages, 1);
+
+		let at = match &self.at {
+			Some(at) => at.parse::<B>()?,
+			None => BlockId::Hash(client.chain_info().best_hash),
+		};
+		let hash = client.expect_block_hash_from_id(&at)?;
+		let number = *client.header(&BlockId::Hash(hash))?
+			.ok_or_else(|| format!("Header of block {:?} not found", hash))?
+			.number();
+		let state = client.state_at(&BlockId::Hash(hash))?;
+		let runner = Runner::<B, _, _> { state: &state, executor, _block: Default::default() };
+
+		let old_runtime_code = BackendRuntimeCode::new(&state);
+		let old_runtime_code = old_runtime_code.runtime_code()?;
+		let new_code_fetcher = WrappedRuntimeCode(new_code.as_slice().into());
+		let new_runtime_code = RuntimeCode {
+			code_fetcher: &new_code_fetcher,
+			heap_pages: old_runtime_code.heap_pages,
+			hash: blake2_256(&new_code).to_vec(),
+		};
+
+		let old_version = runner.version(&old_runtime_code)?;
+		let new_version = runner.version(&new_runtime_code)?;
+		println!(
+			"Upgrading block #{} ({:?}) from {} to {}",
+			number,
+			hash,
+			describe(&old_version),
+			describe(&new_version),
+		);
+
+		let mut problems = Vec::new();
+		if !self.without_checks {
+			if new_version.spec_name != old_version.spec_name {
+				problems.push(format!(
+					"`spec_name` changes from {} to {}",
+					old_version.spec_name,
+					new_version.spec_name,
+				));
+			}
+			if new_version.spec_version <= old_version.spec_version {
+				problems.push(format!(
+					"`spec_version` doesn't increase from {} to {}",
+					old_version.spec_version,
+					new_version.spec_version,
+				));
+			}
+		}
+
+		// Consensus modules may rely on the pre-runtime digests, so the next block is initialized
+		// with the header it was imported with if it is known.
+		let next_number = number + One::one();
+		let next_header = match client.header(&BlockId::Number(next_number))? {
+			Some(header) if *header.parent_hash() == hash => header,
+			_ => B::Header::new(next_number, Default::default(), Default::default(), hash, Default::default()),
+		};
+		info!("Initializing block #{} with the new runtime", next_number);
+
+		let mut upgraded = OverlayedChanges::default();
+		runner.set_storage(&mut upgraded, well_known_keys::CODE.to_vec(), new_code.clone());
+		let initialized = runner.call(
+			&mut upgraded,
+			&new_runtime_code,
+			"Core_initialize_block",
+			&next_header.encode(),
+		);
+
+		// The weight of the migrations can't be read from the initialized block: they register it
+		// before `System::initialize` resets the block weight. So the migrations are run again on
+		// their own, through a runtime API returning their weight.
+		let has_upgrade_api = new_version.has_api_with(
+			&<dyn UpgradeDryRunApi<B, Error = ()> as RuntimeApiInfo>::ID,
+			|_| true,
+		);
+		let migration_weight = if has_upgrade_api {
+			let mut migrated = OverlayedChanges::default();
+			runner.set_storage(&mut migrated, well_known_keys::CODE.to_vec(), new_code);
+			runner.call(&mut migrated, &new_runtime_code, "UpgradeDryRunApi_on_runtime_upgrade", &[])
+				.and_then(|weight| u64::decode(&mut &weight[..])
+					.map_err(|e| format!("Invalid weight: {}", e.what()))
+				)
+				.map_err(|e| warn!("Running the migrations failed: {}", e))
+				.ok()
+		} else {
+			warn!(
+				"{} doesn't implement `UpgradeDryRunApi`, the weight of its migrations is unknown",
+				describe(&new_version),
+			);
+			None
+		};
+
+		let old_metadata = Metadata::decode(&runner.call(
+			&mut Default::default(),
+			&old_runtime_code,
+			"Metadata_metadata",
+			&[],
+		)?)?;
+		let new_metadata = Metadata::decode(&runner.call(
+			&mut Default::default(),
+			&new_runtime_code,
+			"Metadata_metadata",
+			&[],
+		)?)?;
+		let metadata_diff = MetadataDiff::new(&old_metadata, &new_metadata);
+		let maximum_weight = new_metadata.constant::<u64>("System", "MaximumBlockWeight");
+
+		let mut report = serde_json::json!({
+			"at": { "number": number.to_string(), "hash": format!("{:?}", hash) },
+			"old_version": describe(&old_version),
+			"new_version": describe(&new_version),
+			"problems": problems,
+			"metadata": metadata_diff.to_json(),
+		});
+
+		match &initialized {
+			Ok(_) => {
+				// The weight registered by initializing the block doesn't include the migrations.
+				let weight = block_weight(&upgraded).saturating_add(migration_weight.unwrap_or(0));
+
+				println!(
+					"Initialized block #{}, consuming {} weight ({} by migrations) of {}",
+					next_number,
+					weight,
+					migration_weight.map_or("unknown".into(), |weight| weight.to_string()),
+					maximum_weight.map_or("an unknown maximum".into(), |weight| format!("at most {}", weight)),
+				);
+				if let Some(maximum) = maximum_weight.filter(|maximum| weight > *maximum) {
+					problems.push(format!(
+						"Initializing the block consumes {} weight, more than the maximum of {}",
+						weight,
+						maximum,
+					));
+				}
+
+				let changed_keys = upgraded.changes()
+					.map(|(key, value)| (
+						format!("0x{}", hex::encode(key)),
+						if value.value().is_some() { "set" } else { "deleted" }.into(),
+					))
+					.collect::<serde_json::Map<_, _>>();
+				let by_module = keys_by_module(
+					upgraded.changes().map(|(key, _)| &key[..]),
+					&new_metadata.storage_prefixes,
+				);
+				let changed_child_tries = upgraded.children().count();
+				println!(
+					"Changed {} storage keys ({}) and {} child tries",
+					changed_keys.len(),
+					by_module.iter()
+						.map(|(module, count)| format!("{}: {}", module, count))
+						.collect::<Vec<_>>()
+						.join(", "),
+					changed_child_tries,
+				);
+
+				report["initialize_block"] = serde_json::json!({
+					"weight": weight,
+					"migration_weight": migration_weight,
+					"maximum_weight": maximum_weight,
+				});
+				report["storage"] = serde_json::json!({
+					"by_module": by_module,
+					"keys": changed_keys,
+					"changed_child_tries": changed_child_tries,
+				});
+			}
+			Err(e) => {
+				problems.push(format!("Initializing block #{} failed: {}", next_number, e));
+				report["initialize_block"] = serde_json::json!({ "error": e });
+			}
+		}
+
+		if metadata_diff.is_empty() {
+			println!("Metadata is unchanged");
+		} else {
+			println!(
+				"Metadata changes: {} added, {} removed, {} changed",
+				metadata_diff.added.len(),
+				metadata_diff.removed.len(),
+				metadata_diff.changed.len(),
+			);
+			for path in &metadata_diff.added {
+				println!("  + {}", path);
+			}
+			for path in &metadata_diff.removed {
+				println!("  - {}", path);
+			}
+			for (path, old, new) in &metadata_diff.changed {
+				println!("  ~ {}: {} -> {}", path, old, new);
+			}
+		}
+
+		report["problems"] = problems.clone().into();
+		if let Some(path) = &self.report {
+			let report = serde_json::to_string_pretty(&report)
+				.map_err(|e| Error::Other(format!("Cannot encode report: {}", e)))?;
+			fs::write(path, report)?;
+			info!("Report written to {:?}", path);
+		}
+
+		if !problems.is_empty() {
+			return Err(Error::Other(format!("Runtime upgrade would fail:\n{}", problems.join("\n"))))
+		}
+		println!("Runtime upgrade succeeds");
+		Ok(())
+	}
+}
+
+/// Runs calls on top of the state of a block.
+struct Runner<'a, B, S, EX> {
+	state: &'a S,
+	executor: NativeExecutor<EX>,
+	_block: std::marker::PhantomData<B>,
+}
+
+impl<'a, B, S, EX> Runner<'a, B, S, EX>
+where
+	B: BlockT,
+	S: Backend<HashFor<B>>,
+	EX: NativeExecutionDispatch + 'static,
+{
+	/// Call `method` of the Wasm runtime `runtime_code` on top of the `overlay`.
+	fn call(
+		&self,
+		overlay: &mut OverlayedChanges,
+		runtime_code: &RuntimeCode,
+		method: &str,
+		data: &[u8],
+	) -> std::result::Result<Vec<u8>, String> {
+		StateMachine::<_, _, NumberFor<B>, _>::new(
+			self.state,
+			None,
+			overlay,
+			&mut OffchainOverlayedChanges::default(),
+			&self.executor,
+			method,
+			data,
+			Extensions::default(),
+			runtime_code,
+			tasks::executor(),
+		)
+		.execute(ExecutionStrategy::AlwaysWasm)
+		.map_err(|e| format!("{:?}", e))
+	}
+
+	fn version(&self, runtime_code: &RuntimeCode) -> std::result::Result<RuntimeVersion, String> {
+		let version = self.call(&mut Default::default(), runtime_code, "Core_version", &[])?;
+		RuntimeVersion::decode(&mut &version[..])
+			.map_err(|e| format!("Invalid runtime version: {}", e.what()))
+	}
+
+	fn set_storage(&self, overlay: &mut OverlayedChanges, key: Vec<u8>, value: Vec<u8>) {
+		let mut offchain_overlay = OffchainOverlayedChanges::default();
+		let mut cache = StorageTransactionCache::default();
+		Ext::<HashFor<B>, NumberFor<B>, _>::new(overlay, &mut offchain_overlay, &mut cache, self.state, None, None)
+			.set_storage(key, value);
+	}
+}
+
+/// The total weight registered in the block by `overlay`.
+fn block_weight(overlay: &OverlayedChanges) -> u64 {
+	overlay.storage(&system_storage_key(b"BlockWeight"))
+		.and_then(|value| value)
+		.and_then(|value| <(u64, u64)>::decode(&mut &value[..]).ok())
+		.map_or(0, |(normal, operational)| normal.saturating_add(operational))
+}
+
+/// The key of a plain storage value of the system module.
+fn system_storage_key(name: &[u8]) -> Vec<u8> {
+	[&twox_128(b"System")[..], &twox_128(name)[..]].concat()
+}
+
+fn describe(version: &RuntimeVersion) -> String {
+	format!("{}-{}", version.spec_name, version.spec_version)
+}
+
+impl CliConfiguration for UpgradeDryRunCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn import_params(&self) -> Option<&ImportParams> {
+		Some(&self.import_params)
+	}
+}
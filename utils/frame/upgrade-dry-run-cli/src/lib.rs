@@ -0,0 +1,68 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dry-run of FRAME runtime upgrades.
+//!
+//! Sets the code of a new runtime on top of the state of an imported block and initializes the
+//! next block with it, just like the block after an enacted `set_code` would be. The migrations
+//! of the new runtime run on an overlay of the local state, so nothing is written to the database.
+//! Reports the weight consumed by the migrations, the changed storage keys, a failure of the
+//! block initialization and the differences between the metadata of both runtimes.
+//!
+//! The weight of the migrations is only known for runtimes implementing the
+//! `frame_upgrade_dry_run_runtime_api::UpgradeDryRunApi`.
+
+#![warn(missing_docs)]
+
+mod command;
+mod report;
+
+pub use report::{keys_by_module, Metadata, MetadataDiff};
+
+use std::path::PathBuf;
+use sc_cli::{BlockNumberOrHash, ImportParams, SharedParams};
+use structopt::StructOpt;
+
+/// The `upgrade-dry-run` command used to check a runtime upgrade against the chain state.
+#[derive(Debug, StructOpt)]
+pub struct UpgradeDryRunCmd {
+	/// Path to the Wasm blob of the new runtime.
+	#[structopt(value_name = "WASM", parse(from_os_str))]
+	pub code: PathBuf,
+
+	/// The block whose state the upgrade is applied to. Defaults to the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// Skip the checks of `set_code`, like `set_code_without_checks` does.
+	///
+	/// By default the new runtime needs the same `spec_name` and a higher `spec_version`.
+	#[structopt(long)]
+	pub without_checks: bool,
+
+	/// Write the full report, including all changed storage keys, as JSON to the given file.
+	#[structopt(long, value_name = "PATH", parse(from_os_str))]
+	pub report: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub import_params: ImportParams,
+}
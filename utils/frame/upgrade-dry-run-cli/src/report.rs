@@ -0,0 +1,262 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inspection of runtime metadata and storage changes.
+
+use std::collections::BTreeMap;
+use codec::Decode;
+use frame_metadata::{DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryType};
+use sp_core::hashing::twox_128;
+
+/// The parts of the metadata of a runtime an upgrade is checked against.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metadata {
+	/// The signature of every call, event, storage entry, constant and error by path, e.g.
+	/// `Balances::call::transfer`.
+	///
+	/// The signatures include the indices calls, events and errors are encoded with, since
+	/// changing them breaks transactions and clients just like changing the arguments does.
+	pub items: BTreeMap<String, String>,
+	/// The SCALE encoded value of every constant by path, e.g. `System::MaximumBlockWeight`.
+	pub constants: BTreeMap<String, Vec<u8>>,
+	/// The storage prefix of every module that has storage by module name.
+	pub storage_prefixes: BTreeMap<String, String>,
+}
+
+impl Metadata {
+	/// Decode the metadata as returned by `Metadata_metadata`.
+	pub fn decode(encoded: &[u8]) -> Result<Self, String> {
+		let opaque = Vec::<u8>::decode(&mut &encoded[..])
+			.map_err(|e| format!("Invalid metadata: {}", e.what()))?;
+		let metadata = RuntimeMetadataPrefixed::decode(&mut &opaque[..])
+			.map_err(|e| format!("Invalid metadata: {}", e.what()))?;
+		let modules = match metadata.1 {
+			RuntimeMetadata::V11(metadata) => decoded(metadata.modules),
+			_ => return Err("Unsupported metadata version, only V11 is supported".into()),
+		};
+
+		let mut result = Metadata::default();
+		let (mut call_module, mut event_module) = (0, 0);
+		for module in modules {
+			let name = decoded(module.name);
+
+			if let Some(storage) = module.storage.map(decoded) {
+				result.storage_prefixes.insert(name.clone(), decoded(storage.prefix));
+				for entry in decoded(storage.entries) {
+					let signature = format!("{:?} {}", entry.modifier, storage_type(entry.ty));
+					result.items.insert(format!("{}::storage::{}", name, decoded(entry.name)), signature);
+				}
+			}
+			if let Some(calls) = module.calls.map(decoded) {
+				for (index, call) in calls.into_iter().enumerate() {
+					let arguments = decoded(call.arguments).into_iter()
+						.map(|argument| format!("{}: {}", decoded(argument.name), decoded(argument.ty)))
+						.collect::<Vec<_>>();
+					result.items.insert(
+						format!("{}::call::{}", name, decoded(call.name)),
+						format!("#{}.{}({})", call_module, index, arguments.join(", ")),
+					);
+				}
+				call_module += 1;
+			}
+			if let Some(events) = module.event.map(decoded) {
+				for (index, event) in events.into_iter().enumerate() {
+					result.items.insert(
+						format!("{}::event::{}", name, decoded(event.name)),
+						format!("#{}.{}({})", event_module, index, decoded(event.arguments).join(", ")),
+					);
+				}
+				event_module += 1;
+			}
+			for constant in decoded(module.constants) {
+				let path = format!("{}::{}", name, decoded(constant.name));
+				let value = decoded(constant.value);
+				result.items.insert(
+					format!("{}::constant::{}", name, decoded(constant.name)),
+					format!("{} = 0x{}", decoded(constant.ty), hex::encode(&value)),
+				);
+				result.constants.insert(path, value);
+			}
+			for (index, error) in decoded(module.errors).into_iter().enumerate() {
+				result.items.insert(
+					format!("{}::error::{}", name, decoded(error.name)),
+					format!("#{}", index),
+				);
+			}
+		}
+
+		Ok(result)
+	}
+
+	/// Decode the value of the constant `name` of `module`.
+	pub fn constant<T: Decode>(&self, module: &str, name: &str) -> Option<T> {
+		self.constants.get(&format!("{}::{}", module, name))
+			.and_then(|value| T::decode(&mut &value[..]).ok())
+	}
+}
+
+/// The differences between the metadata of two runtimes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MetadataDiff {
+	/// Paths of the items only the new runtime has.
+	pub added: Vec<String>,
+	/// Paths of the items only the old runtime has.
+	pub removed: Vec<String>,
+	/// Paths of the items whose signature changed, with the old and the new signature.
+	pub changed: Vec<(String, String, String)>,
+}
+
+impl MetadataDiff {
+	/// Compare the metadata of the `old` and the `new` runtime.
+	pub fn new(old: &Metadata, new: &Metadata) -> Self {
+		let mut diff = MetadataDiff::default();
+		for (path, signature) in &old.items {
+			match new.items.get(path) {
+				None => diff.removed.push(path.clone()),
+				Some(new) if new != signature =>
+					diff.changed.push((path.clone(), signature.clone(), new.clone())),
+				Some(_) => {}
+			}
+		}
+		diff.added = new.items.keys()
+			.filter(|path| !old.items.contains_key(*path))
+			.cloned()
+			.collect();
+		diff
+	}
+
+	/// Returns `true` if the metadata of both runtimes is the same.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+	}
+
+	/// The differences as JSON.
+	pub fn to_json(&self) -> serde_json::Value {
+		let changed = self.changed.iter()
+			.map(|(path, old, new)| serde_json::json!({ "path": path, "old": old, "new": new }))
+			.collect::<Vec<_>>();
+		serde_json::json!({
+			"added": self.added,
+			"removed": self.removed,
+			"changed": changed,
+		})
+	}
+}
+
+/// Count the given storage `keys` by the module they belong to.
+///
+/// Keys are attributed to a module by the hash of its storage prefix in `storage_prefixes`.
+/// Well known keys like `:code` are counted as `well known`, any other key as `unknown`.
+pub fn keys_by_module<'a>(
+	keys: impl IntoIterator<Item = &'a [u8]>,
+	storage_prefixes: &BTreeMap<String, String>,
+) -> BTreeMap<String, usize> {
+	let modules = storage_prefixes.iter()
+		.map(|(module, prefix)| (twox_128(prefix.as_bytes()), module.as_str()))
+		.collect::<BTreeMap<_, _>>();
+
+	let mut counts = BTreeMap::new();
+	for key in keys {
+		let module = if key.starts_with(b":") {
+			"well known"
+		} else if key.len() >= 16 {
+			let mut prefix = [0u8; 16];
+			prefix.copy_from_slice(&key[..16]);
+			modules.get(&prefix).cloned().unwrap_or("unknown")
+		} else {
+			"unknown"
+		};
+		*counts.entry(module.to_string()).or_insert(0) += 1;
+	}
+	counts
+}
+
+fn storage_type(ty: StorageEntryType) -> String {
+	match ty {
+		StorageEntryType::Plain(value) => decoded(value),
+		StorageEntryType::Map { hasher, key, value, .. } =>
+			format!("map {:?}({}) => {}", hasher, decoded(key), decoded(value)),
+		StorageEntryType::DoubleMap { hasher, key1, key2, value, key2_hasher } => format!(
+			"double_map {:?}({}), {:?}({}) => {}",
+			hasher,
+			decoded(key1),
+			key2_hasher,
+			decoded(key2),
+			decoded(value),
+		),
+	}
+}
+
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> O {
+	match value {
+		DecodeDifferent::Decoded(value) => value,
+		DecodeDifferent::Encode(_) => unreachable!("decoding always yields `Decoded` values; qed"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn metadata(items: &[(&str, &str)]) -> Metadata {
+		Metadata {
+			items: items.iter().map(|(path, signature)| (path.to_string(), signature.to_string())).collect(),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn diff_reports_added_removed_and_changed_items() {
+		let old = metadata(&[
+			("Balances::call::transfer", "#4.0(dest: Address, value: Balance)"),
+			("Balances::storage::Locks", "Default map Blake2_128Concat(AccountId) => Vec<Lock>"),
+			("Sudo::call::sudo", "#5.0(call: Box<Call>)"),
+		]);
+		let new = metadata(&[
+			("Balances::call::transfer", "#4.0(dest: Address, value: Balance)"),
+			("Balances::storage::Locks", "Default map Twox64Concat(AccountId) => Vec<Lock>"),
+			("Staking::call::bond", "#5.0(value: Balance)"),
+		]);
+
+		let diff = MetadataDiff::new(&old, &new);
+
+		assert_eq!(diff, MetadataDiff {
+			added: vec!["Staking::call::bond".into()],
+			removed: vec!["Sudo::call::sudo".into()],
+			changed: vec![(
+				"Balances::storage::Locks".into(),
+				"Default map Blake2_128Concat(AccountId) => Vec<Lock>".into(),
+				"Default map Twox64Concat(AccountId) => Vec<Lock>".into(),
+			)],
+		});
+		assert!(MetadataDiff::new(&new, &new).is_empty());
+	}
+
+	#[test]
+	fn keys_are_counted_by_module() {
+		let prefixes = vec![("System".to_string(), "System".to_string())].into_iter().collect();
+		let system_key = [&twox_128(b"System")[..], &twox_128(b"Number")[..]].concat();
+		let other_key = [&twox_128(b"Other")[..], &twox_128(b"Value")[..]].concat();
+		let keys = vec![&system_key[..], &system_key[..], &other_key[..], &b":code"[..], &b"short"[..]];
+
+		let counts = keys_by_module(keys, &prefixes);
+
+		assert_eq!(counts.get("System"), Some(&2));
+		assert_eq!(counts.get("well known"), Some(&1));
+		assert_eq!(counts.get("unknown"), Some(&2));
+	}
+}